@@ -0,0 +1,594 @@
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Context;
+use ledger::scan_state::currency::{Amount, Balance, Slot, SlotSpan};
+use node::{
+    account::{AccountPublicKey, AccountSecretKey},
+    daemon_json::{self, DaemonJson},
+    p2p::{
+        connection::outgoing::{
+            P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+        },
+        identity::SecretKey,
+        webrtc::Host,
+    },
+    transition_frontier::genesis::GenesisConfig,
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+#[derive(Debug, clap::Args)]
+pub struct Genesis {
+    #[command(subcommand)]
+    command: GenesisCommand,
+}
+
+impl Genesis {
+    pub fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            GenesisCommand::Generate(command) => command.run(),
+        }
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum GenesisCommand {
+    /// Generates keys, genesis ledger and node configs for a private
+    /// network.
+    Generate(Generate),
+}
+
+/// Output directory layout:
+/// - `daemon.json` genesis config;
+/// - `keys/<name>` encrypted key files, with public keys in `keys/<name>.pub`;
+/// - `nodes/<name>/` with a `run.sh` script and a `peers.txt` peer list,
+///   for every producer and snarker.
+#[derive(Debug, clap::Args)]
+pub struct Generate {
+    /// Directory to write the network files to. Must be empty or missing.
+    #[arg(long, short)]
+    out: PathBuf,
+
+    /// Number of block producers.
+    #[arg(long, default_value_t = 2)]
+    producers: usize,
+
+    /// Number of snarkers.
+    #[arg(long, default_value_t = 1)]
+    snarkers: usize,
+
+    /// Number of funded user accounts, which don't run a node.
+    #[arg(long, default_value_t = 0)]
+    users: usize,
+
+    /// Number of accounts delegating to each producer.
+    #[arg(long, default_value_t = 0)]
+    delegators_per_producer: usize,
+
+    /// Balance of producer accounts, in mina.
+    #[arg(long, default_value_t = 1_000_000)]
+    producer_balance: u64,
+
+    /// Balance of snarker accounts, in mina.
+    #[arg(long, default_value_t = 1_000)]
+    snarker_balance: u64,
+
+    /// Balance of user accounts, in mina.
+    #[arg(long, default_value_t = 10_000)]
+    user_balance: u64,
+
+    /// Balance of delegator accounts, in mina.
+    #[arg(long, default_value_t = 100_000)]
+    delegator_balance: u64,
+
+    /// Vesting schedule of producer accounts, see `--user-timing`.
+    #[arg(long)]
+    producer_timing: Option<TimingArg>,
+
+    /// Vesting schedule of user accounts.
+    ///
+    /// Format is `<initial minimum balance>:<cliff time>:<cliff amount>:<vesting period>:<vesting increment>`,
+    /// with amounts in mina and times in slots.
+    #[arg(long)]
+    user_timing: Option<TimingArg>,
+
+    /// Vesting schedule of delegator accounts, see `--user-timing`.
+    #[arg(long)]
+    delegator_timing: Option<TimingArg>,
+
+    /// Genesis timestamp (RFC 3339), current time if not set.
+    #[arg(long, value_parser = parse_timestamp)]
+    genesis_timestamp: Option<OffsetDateTime>,
+
+    /// Password used to encrypt the key files.
+    #[arg(
+        long,
+        env = "MINA_PRIVKEY_PASS",
+        default_value = "",
+        hide_env_values = true
+    )]
+    password: String,
+
+    /// Host at which the nodes are reachable by each other.
+    #[arg(long, default_value = "127.0.0.1")]
+    host: Host,
+
+    /// Http port of the first node, next nodes use the following ports.
+    #[arg(long, default_value_t = 3000)]
+    base_port: u16,
+
+    /// LibP2P port of the first node, next nodes use the following ports.
+    #[arg(long, default_value_t = 8302)]
+    base_libp2p_port: u16,
+}
+
+/// Vesting schedule of an account, amounts are in nanomina.
+#[derive(Debug, Clone)]
+pub struct TimingArg {
+    initial_minimum_balance: u64,
+    cliff_time: u32,
+    cliff_amount: u64,
+    vesting_period: u32,
+    vesting_increment: u64,
+}
+
+impl FromStr for TimingArg {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split(':').collect::<Vec<_>>();
+        let [initial_minimum_balance, cliff_time, cliff_amount, vesting_period, vesting_increment] =
+            parts[..]
+        else {
+            anyhow::bail!("expected 5 `:` separated values, got {}", parts.len());
+        };
+        let timing = Self {
+            initial_minimum_balance: nanomina(
+                initial_minimum_balance
+                    .parse()
+                    .context("invalid initial minimum balance")?,
+            )?,
+            cliff_time: cliff_time.parse().context("invalid cliff time")?,
+            cliff_amount: nanomina(cliff_amount.parse().context("invalid cliff amount")?)?,
+            vesting_period: vesting_period.parse().context("invalid vesting period")?,
+            vesting_increment: nanomina(
+                vesting_increment
+                    .parse()
+                    .context("invalid vesting increment")?,
+            )?,
+        };
+        if timing.vesting_period == 0 {
+            anyhow::bail!("vesting period must be positive");
+        }
+        Ok(timing)
+    }
+}
+
+impl TimingArg {
+    fn to_account_timing(&self) -> daemon_json::AccountTiming {
+        daemon_json::AccountTiming::new(
+            Balance::from_u64(self.initial_minimum_balance),
+            Slot::from_u32(self.cliff_time),
+            Amount::from_u64(self.cliff_amount),
+            SlotSpan::from_u32(self.vesting_period),
+            Amount::from_u64(self.vesting_increment),
+        )
+    }
+}
+
+fn nanomina(mina: u64) -> anyhow::Result<u64> {
+    mina.checked_mul(1_000_000_000)
+        .with_context(|| format!("{mina} mina is too much"))
+}
+
+fn parse_timestamp(s: &str) -> Result<OffsetDateTime, time::error::Parse> {
+    OffsetDateTime::parse(s, &Rfc3339)
+}
+
+/// Generated account, along with its key.
+struct GeneratedKey {
+    name: String,
+    secret_key: AccountSecretKey,
+}
+
+impl GeneratedKey {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            secret_key: AccountSecretKey::rand(),
+        }
+    }
+
+    fn public_key(&self) -> AccountPublicKey {
+        self.secret_key.public_key()
+    }
+
+    /// Account with the `balance` in nanomina.
+    fn account(&self, balance: u64, delegate: Option<&AccountPublicKey>) -> daemon_json::Account {
+        daemon_json::Account::new(
+            self.public_key().to_string(),
+            daemon_json::to_mina_string(balance),
+            delegate.map(ToString::to_string),
+        )
+    }
+}
+
+/// Node of the generated network.
+struct GeneratedNode<'a> {
+    key: &'a GeneratedKey,
+    is_producer: bool,
+    p2p_secret_key: SecretKey,
+    port: u16,
+    libp2p_port: u16,
+}
+
+impl Generate {
+    pub fn run(self) -> anyhow::Result<()> {
+        if self.producers == 0 {
+            anyhow::bail!("at least one producer is required");
+        }
+        let out_is_empty = match fs::read_dir(&self.out) {
+            Ok(mut entries) => entries.next().is_none(),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => true,
+            Err(err) => return Err(err).context(format!("reading {:?}", self.out)),
+        };
+        if !out_is_empty {
+            anyhow::bail!("output directory {:?} is not empty", self.out);
+        }
+        let keys_dir = self.out.join("keys");
+        let nodes_dir = self.out.join("nodes");
+        fs::create_dir_all(&keys_dir).context(format!("creating {keys_dir:?}"))?;
+        fs::create_dir_all(&nodes_dir).context(format!("creating {nodes_dir:?}"))?;
+        // Scripts are run from the node directories.
+        let out = self
+            .out
+            .canonicalize()
+            .context(format!("resolving {:?}", self.out))?;
+
+        let producers = (0..self.producers)
+            .map(|i| GeneratedKey::new(format!("producer-{i}")))
+            .collect::<Vec<_>>();
+        let delegators = producers
+            .iter()
+            .enumerate()
+            .flat_map(|(i, producer)| {
+                (0..self.delegators_per_producer).map(move |j| {
+                    (
+                        GeneratedKey::new(format!("delegator-{i}-{j}")),
+                        producer.public_key(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        let snarkers = (0..self.snarkers)
+            .map(|i| GeneratedKey::new(format!("snarker-{i}")))
+            .collect::<Vec<_>>();
+        let users = (0..self.users)
+            .map(|i| GeneratedKey::new(format!("user-{i}")))
+            .collect::<Vec<_>>();
+
+        let producer_balance = nanomina(self.producer_balance)?;
+        let delegator_balance = nanomina(self.delegator_balance)?;
+        let snarker_balance = nanomina(self.snarker_balance)?;
+        let user_balance = nanomina(self.user_balance)?;
+        let with_timing = |account: daemon_json::Account, timing: &Option<TimingArg>| match timing {
+            Some(timing) => account.with_timing(timing.to_account_timing()),
+            None => account,
+        };
+        let accounts = producers
+            .iter()
+            .map(|key| with_timing(key.account(producer_balance, None), &self.producer_timing))
+            .chain(delegators.iter().map(|(key, producer)| {
+                with_timing(
+                    key.account(delegator_balance, Some(producer)),
+                    &self.delegator_timing,
+                )
+            }))
+            .chain(
+                snarkers
+                    .iter()
+                    .map(|key| key.account(snarker_balance, None)),
+            )
+            .chain(
+                users
+                    .iter()
+                    .map(|key| with_timing(key.account(user_balance, None), &self.user_timing)),
+            )
+            .collect::<Vec<_>>();
+
+        let mut ledger = daemon_json::Ledger {
+            accounts: Some(accounts),
+            num_accounts: None,
+            balances: None,
+            hash: None,
+            s3_data_hash: None,
+            name: None,
+            add_genesis_winner: None,
+        };
+        // Without the hash, ledgers are cached under a name which doesn't
+        // depend on the accounts.
+        let ledger_hash = GenesisConfig::daemon_json_ledger_hash(&ledger)
+            .context("failed to build the genesis ledger")?;
+        ledger.hash = Some(ledger_hash.to_string());
+
+        let genesis_timestamp = self
+            .genesis_timestamp
+            .unwrap_or_else(OffsetDateTime::now_utc)
+            .replace_nanosecond(0)?;
+        let daemon_json = DaemonJson {
+            daemon: None,
+            ledger: Some(ledger),
+            genesis: Some(
+                daemon_json::Genesis::default().with_genesis_state_timestamp(genesis_timestamp),
+            ),
+            epoch_data: None,
+        };
+
+        let daemon_json_path = out.join("daemon.json");
+        fs::write(&daemon_json_path, serde_json::to_vec_pretty(&daemon_json)?)
+            .context(format!("writing {daemon_json_path:?}"))?;
+
+        let all_keys = producers
+            .iter()
+            .chain(delegators.iter().map(|(key, _)| key))
+            .chain(&snarkers)
+            .chain(&users);
+        for key in all_keys {
+            let path = keys_dir.join(&key.name);
+            key.secret_key
+                .to_encrypted_file(&path, &self.password)
+                .map_err(|err| anyhow::anyhow!("failed to write {path:?}: {err}"))?;
+            fs::write(
+                path.with_extension("pub"),
+                format!("{}\n", key.public_key()),
+            )
+            .context(format!("writing public key of {}", key.name))?;
+        }
+
+        let nodes = producers
+            .iter()
+            .map(|key| (key, true))
+            .chain(snarkers.iter().map(|key| (key, false)))
+            .zip(0u16..)
+            .map(|((key, is_producer), i)| {
+                Ok(GeneratedNode {
+                    key,
+                    is_producer,
+                    p2p_secret_key: SecretKey::rand(),
+                    port: self
+                        .base_port
+                        .checked_add(i)
+                        .context("http port overflow")?,
+                    libp2p_port: self
+                        .base_libp2p_port
+                        .checked_add(i)
+                        .context("libp2p port overflow")?,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        for (i, node) in nodes.iter().enumerate() {
+            let node_dir = out.join("nodes").join(&node.key.name);
+            fs::create_dir_all(&node_dir).context(format!("creating {node_dir:?}"))?;
+
+            let peers = nodes.iter().enumerate().filter(|(j, _)| *j != i).fold(
+                String::new(),
+                |mut peers, (_, peer)| {
+                    let _ = writeln!(peers, "{}", self.peer_addr(peer));
+                    peers
+                },
+            );
+            fs::write(node_dir.join("peers.txt"), peers)
+                .context(format!("writing peers of {}", node.key.name))?;
+
+            // Default peers are used if the peer list is empty, which
+            // is the case for a network of a single node.
+            let script = node_run_script(node, i == 0, &node_dir, &out);
+            let script_path = node_dir.join("run.sh");
+            fs::write(&script_path, script).context(format!("writing {script_path:?}"))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))
+                    .context(format!("setting permissions of {script_path:?}"))?;
+            }
+        }
+
+        println!("genesis ledger hash: {ledger_hash}");
+        println!(
+            "genesis timestamp:   {}",
+            genesis_timestamp.format(&Rfc3339)?
+        );
+        for node in &nodes {
+            println!(
+                "{:<16} {}  http: {}  libp2p: {}",
+                node.key.name,
+                node.key.public_key(),
+                node.port,
+                node.libp2p_port
+            );
+        }
+        println!("network files written to {out:?}");
+
+        Ok(())
+    }
+
+    fn peer_addr(&self, node: &GeneratedNode) -> P2pConnectionOutgoingInitOpts {
+        P2pConnectionOutgoingInitOpts::LibP2P(P2pConnectionOutgoingInitLibp2pOpts {
+            peer_id: node.p2p_secret_key.public_key().peer_id(),
+            host: self.host.clone(),
+            port: node.libp2p_port,
+            transport: P2pLibp2pTransport::Tcp,
+        })
+    }
+}
+
+/// Script which starts the node with the generated config. Producer and
+/// snarker key password is taken from `MINA_PRIVKEY_PASS`.
+fn node_run_script(node: &GeneratedNode, is_seed: bool, node_dir: &Path, out: &Path) -> String {
+    let mut args = vec![
+        format!("--work-dir '{}'", node_dir.join("work").display()),
+        format!("--p2p-secret-key {}", node.p2p_secret_key),
+        format!("--port {}", node.port),
+        format!("--libp2p-port {}", node.libp2p_port),
+        format!("--config '{}'", out.join("daemon.json").display()),
+        format!(
+            "--peer-list-file '{}'",
+            node_dir.join("peers.txt").display()
+        ),
+    ];
+    if is_seed {
+        args.push("--seed".to_owned());
+    }
+    let key_path = out.join("keys").join(&node.key.name);
+    if node.is_producer {
+        args.push(format!("--producer-key '{}'", key_path.display()));
+    } else {
+        args.push(format!("--snarker-key '{}'", key_path.display()));
+    }
+    format!(
+        "#!/bin/sh\nexec \"${{OPENMINA:-openmina}}\" node \\\n    {} \\\n    \"$@\"\n",
+        args.join(" \\\n    ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_arg_from_str() {
+        let timing = TimingArg::from_str("1000:10:100:2:50").unwrap();
+        assert_eq!(timing.initial_minimum_balance, 1_000_000_000_000);
+        assert_eq!(timing.cliff_time, 10);
+        assert_eq!(timing.cliff_amount, 100_000_000_000);
+        assert_eq!(timing.vesting_period, 2);
+        assert_eq!(timing.vesting_increment, 50_000_000_000);
+
+        for invalid in [
+            "",
+            "1000:10:100:2",
+            "1000:10:100:2:50:1",
+            "1000:10:100:0:50",
+            "1000:-10:100:2:50",
+            "1000:10:100:2:0.5",
+            "18446744073709551615:10:100:2:50",
+        ] {
+            assert!(
+                TimingArg::from_str(invalid).is_err(),
+                "{invalid:?} must be rejected"
+            );
+        }
+    }
+
+    fn test_generate(out: PathBuf) -> Generate {
+        Generate {
+            out,
+            producers: 2,
+            snarkers: 1,
+            users: 1,
+            delegators_per_producer: 1,
+            producer_balance: 1_000_000,
+            snarker_balance: 1_000,
+            user_balance: 10_000,
+            delegator_balance: 100_000,
+            producer_timing: None,
+            user_timing: None,
+            delegator_timing: Some(TimingArg::from_str("1000:10:100:2:50").unwrap()),
+            genesis_timestamp: None,
+            password: "test".to_owned(),
+            host: Host::from_str("127.0.0.1").unwrap(),
+            base_port: 3000,
+            base_libp2p_port: 8302,
+        }
+    }
+
+    #[test]
+    fn test_generate_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("network");
+        test_generate(out.clone()).run().unwrap();
+
+        let mut entries = fs::read_dir(&out)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(entries, ["daemon.json", "keys", "nodes"]);
+
+        let daemon_json: DaemonJson =
+            serde_json::from_slice(&fs::read(out.join("daemon.json")).unwrap()).unwrap();
+        let ledger = daemon_json.ledger.as_ref().unwrap();
+        let accounts = ledger.accounts.as_ref().unwrap();
+        assert_eq!(accounts.len(), 6);
+        assert_eq!(
+            ledger.hash.as_ref().unwrap(),
+            &GenesisConfig::daemon_json_ledger_hash(ledger)
+                .unwrap()
+                .to_string()
+        );
+
+        let names = [
+            "producer-0",
+            "producer-1",
+            "delegator-0-0",
+            "delegator-1-0",
+            "snarker-0",
+            "user-0",
+        ];
+        let public_keys = names
+            .iter()
+            .zip(accounts)
+            .map(|(name, account)| {
+                let path = out.join("keys").join(name);
+                let secret_key = AccountSecretKey::from_encrypted_file(&path, "test").unwrap();
+                let public_key = fs::read_to_string(path.with_extension("pub")).unwrap();
+                assert_eq!(public_key.trim(), secret_key.public_key().to_string());
+                assert_eq!(account.public_key().unwrap(), secret_key.public_key());
+                secret_key.public_key()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            accounts[2].delegate().unwrap().as_ref(),
+            Some(&public_keys[0])
+        );
+        assert_eq!(
+            accounts[3].delegate().unwrap().as_ref(),
+            Some(&public_keys[1])
+        );
+        let accounts_json = serde_json::to_value(accounts).unwrap();
+        for (i, account) in accounts_json.as_array().unwrap().iter().enumerate() {
+            assert_eq!(
+                !account["timing"].is_null(),
+                (2..4).contains(&i),
+                "timing of {}",
+                names[i]
+            );
+        }
+
+        for (name, key_flag) in [
+            ("producer-0", "--producer-key"),
+            ("producer-1", "--producer-key"),
+            ("snarker-0", "--snarker-key"),
+        ] {
+            let node_dir = out.join("nodes").join(name);
+            let script = fs::read_to_string(node_dir.join("run.sh")).unwrap();
+            let key_path = out.canonicalize().unwrap().join("keys").join(name);
+            assert!(
+                script.contains(&format!("{key_flag} '{}'", key_path.display())),
+                "{script}"
+            );
+            assert!(script.contains("daemon.json"), "{script}");
+            assert_eq!(script.contains("--seed"), name == "producer-0");
+            let peers = fs::read_to_string(node_dir.join("peers.txt")).unwrap();
+            assert_eq!(peers.lines().count(), 2);
+        }
+        assert_eq!(fs::read_dir(out.join("nodes")).unwrap().count(), 3);
+
+        let err = test_generate(out).run().unwrap_err();
+        assert!(err.to_string().contains("is not empty"), "{err}");
+    }
+}
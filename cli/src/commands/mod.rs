@@ -1,8 +1,16 @@
 pub mod build_info;
+pub mod genesis;
 pub mod misc;
 pub mod node;
 pub mod replay;
 pub mod snark;
+pub mod tx;
+
+use std::{fs::File, path::Path, str::FromStr};
+
+use anyhow::Context;
+use node::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
+use openmina_core::network::CustomNetworkConfig;
 
 #[derive(Debug, clap::Parser)]
 #[command(name = "openmina", about = "Openmina Cli")]
@@ -17,10 +25,25 @@ pub struct OpenminaCli {
     /// Select the network (devnet or mainnet)
     pub network: Network,
 
+    #[arg(global = true, long, env = "OPENMINA_NETWORK_CONFIG")]
+    /// Custom network (e.g. private testnet) definition file, overrides
+    /// `--network` and the network referenced by the node config.
+    pub network_config: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+impl OpenminaCli {
+    /// Custom network definition, which overrides `--network`.
+    pub fn custom_network_config(&self) -> anyhow::Result<Option<CustomNetworkConfig>> {
+        match &self.network_config {
+            Some(path) => load_custom_network_config(path).map(Some),
+            None => self.command.custom_network_config(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, clap::ValueEnum)]
 pub enum Network {
     Devnet,
@@ -35,18 +58,47 @@ pub enum Command {
     Snark(snark::Snark),
     /// Miscilaneous utilities.
     Misc(misc::Misc),
+    /// Payments and stake delegations.
+    Tx(tx::Tx),
+    /// Private network setup.
+    Genesis(genesis::Genesis),
     Replay(replay::Replay),
     BuildInfo(build_info::Command),
 }
 
 impl Command {
+    /// Custom network definition referenced by the command's own config.
+    pub fn custom_network_config(&self) -> anyhow::Result<Option<CustomNetworkConfig>> {
+        match self {
+            Self::Node(v) => v.custom_network_config(),
+            _ => Ok(None),
+        }
+    }
+
     pub fn run(self) -> anyhow::Result<()> {
         match self {
             Self::Snark(v) => v.run(),
             Self::Node(v) => v.run(),
             Self::Misc(v) => v.run(),
+            Self::Tx(v) => v.run(),
+            Self::Genesis(v) => v.run(),
             Self::Replay(v) => v.run(),
             Self::BuildInfo(v) => v.run(),
         }
     }
 }
+
+/// Loads and validates custom network definition from `path`.
+pub fn load_custom_network_config(path: &Path) -> anyhow::Result<CustomNetworkConfig> {
+    let reader = File::open(path).with_context(|| format!("network config file {path:?}"))?;
+    let network: CustomNetworkConfig = serde_json::from_reader(std::io::BufReader::new(reader))
+        .with_context(|| format!("network config file {path:?}"))?;
+    network
+        .validate()
+        .map_err(|err| anyhow::anyhow!("network config file {path:?}: {err}"))?;
+    for peer in &network.default_peers {
+        P2pConnectionOutgoingInitOpts::from_str(peer)
+            .map_err(|err| anyhow::anyhow!("network config file {path:?}: peer {peer}: {err}"))?;
+    }
+    Ok(network)
+}
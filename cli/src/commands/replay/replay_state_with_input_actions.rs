@@ -1,5 +1,13 @@
-use node::BuildEnv;
-use openmina_node_native::replay_state_with_input_actions;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use node::{ActionKind, BuildEnv};
+use openmina_node_native::{
+    parse_action_kind, replay_state_with_input_actions_with_debugger, seek_recorded_state,
+    ReplayDebuggerConfig,
+};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 
 #[derive(Debug, clap::Args)]
 /// Replay node using initial state and input actions.
@@ -10,6 +18,36 @@ pub struct ReplayStateWithInputActions {
     #[arg(long, default_value = "./target/release/libreplay_dynamic_effects.so")]
     pub dynamic_effects_lib: String,
 
+    /// Instead of replaying the whole recording, replay it from the
+    /// latest checkpoint recorded at or before the action with this id
+    /// (its timestamp in nanoseconds) up to the action and dump the state
+    /// as json.
+    #[arg(long, conflicts_with = "until_time")]
+    pub until_action_id: Option<u64>,
+
+    /// Same as `--until-action-id`, but with an RFC 3339 timestamp,
+    /// e.g. `2024-06-01T12:00:00Z`.
+    #[arg(long)]
+    pub until_time: Option<String>,
+
+    /// File to which the state is dumped when seeking. Printed to
+    /// stdout if not set.
+    #[arg(long)]
+    pub dump_state: Option<PathBuf>,
+
+    /// Pause the replay after actions of this kind are applied, e.g.
+    /// `--break TransitionFrontierSynced`. Can be repeated.
+    #[arg(long = "break", value_parser = parse_action_kind_arg)]
+    pub breakpoints: Vec<ActionKind>,
+
+    /// Pause the replay after the first action.
+    #[arg(long)]
+    pub step: bool,
+
+    /// Print changes of the state made by every action.
+    #[arg(long)]
+    pub diff: bool,
+
     /// Verbosity level
     #[arg(long, short, default_value = "info")]
     pub verbosity: tracing::Level,
@@ -20,6 +58,7 @@ impl ReplayStateWithInputActions {
         openmina_node_native::tracing::initialize(self.verbosity);
 
         let dir = shellexpand::full(&self.dir)?.into_owned();
+
         let dynamic_effects_lib = shellexpand::full(&self.dynamic_effects_lib)?.into_owned();
 
         let dynamic_effects_lib = match std::path::Path::new(&dynamic_effects_lib).exists() {
@@ -30,10 +69,57 @@ impl ReplayStateWithInputActions {
             }
         };
 
-        replay_state_with_input_actions(&dir, dynamic_effects_lib, check_build_env)?;
+        if let Some(action_id) = self.seek_action_id()? {
+            let node = seek_recorded_state(&dir, action_id, dynamic_effects_lib, check_build_env)?;
+            let state = node.state();
+            eprintln!(
+                "replayed state after action {}",
+                state.last_action().time_as_nanos()
+            );
+            match &self.dump_state {
+                Some(path) => {
+                    let file = std::fs::File::create(path)
+                        .with_context(|| format!("failed to create {}", path.display()))?;
+                    serde_json::to_writer(std::io::BufWriter::new(file), state)?;
+                    eprintln!("state dumped to {}", path.display());
+                }
+                None => serde_json::to_writer(std::io::stdout().lock(), state)?,
+            }
+            return Ok(());
+        }
+
+        let debugger_config = ReplayDebuggerConfig {
+            breakpoints: self.breakpoints.into_iter().collect(),
+            step: self.step,
+            print_diffs: self.diff,
+        };
+        replay_state_with_input_actions_with_debugger(
+            &dir,
+            dynamic_effects_lib,
+            check_build_env,
+            debugger_config,
+        )?;
 
         Ok(())
     }
+
+    fn seek_action_id(&self) -> anyhow::Result<Option<u64>> {
+        if let Some(action_id) = self.until_action_id {
+            return Ok(Some(action_id));
+        }
+        let Some(time) = &self.until_time else {
+            return Ok(None);
+        };
+        let nanos = OffsetDateTime::parse(time, &Rfc3339)
+            .with_context(|| format!("invalid --until-time: {time}"))?
+            .unix_timestamp_nanos();
+        let nanos = u64::try_from(nanos).context("--until-time is before the unix epoch")?;
+        Ok(Some(nanos))
+    }
+}
+
+fn parse_action_kind_arg(s: &str) -> anyhow::Result<ActionKind> {
+    parse_action_kind(s).ok_or_else(|| anyhow::anyhow!("unknown action kind: {s}"))
 }
 
 pub fn check_build_env(record_env: &BuildEnv, replay_env: &BuildEnv) -> anyhow::Result<()> {
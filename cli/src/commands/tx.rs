@@ -0,0 +1,474 @@
+use std::{fs, io::Read, path::PathBuf, str::FromStr};
+
+use ledger::{
+    scan_state::{
+        currency::{Amount, Fee, Nonce, Slot},
+        transaction_logic::{
+            signed_command::{self, SignedCommand, SignedCommandPayload},
+            transaction_union_payload::TransactionUnionPayload,
+            Memo,
+        },
+    },
+    verifier::common::{legacy_sign, legacy_verify_signature},
+};
+use mina_p2p_messages::{
+    bigint::BigInt,
+    string::MEMO_MAX_LENGTH,
+    v2::{MinaBaseSignedCommandStableV2, MinaBaseUserCommandStableV2},
+};
+use mina_signer::CompressedPubKey;
+use node::{account::AccountPublicKey, account::AccountSecretKey, rpc::RpcInjectPayment};
+use openmina_node_native::graphql::user_command::{
+    SendDelegationInput, SendPaymentInput, SignatureInput,
+};
+use reqwest::Url;
+use serde_json::{json, Value};
+
+const SEND_PAYMENT_MUTATION: &str = "mutation($input: SendPaymentInput!, $signature: SignatureInput) { sendPayment(input: $input, signature: $signature) { payment { hash } } }";
+const SEND_DELEGATION_MUTATION: &str = "mutation($input: SendDelegationInput!, $signature: SignatureInput) { sendDelegation(input: $input, signature: $signature) { delegation { hash } } }";
+
+#[derive(Debug, clap::Args)]
+pub struct Tx {
+    #[command(subcommand)]
+    command: TxCommand,
+}
+
+impl Tx {
+    pub fn run(self) -> anyhow::Result<()> {
+        match self.command {
+            TxCommand::Payment(command) => command.run(),
+            TxCommand::Delegation(command) => command.run(),
+            TxCommand::Verify(command) => command.run(),
+        }
+    }
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum TxCommand {
+    /// Builds and signs a payment.
+    Payment(Payment),
+    /// Builds and signs a stake delegation.
+    Delegation(Delegation),
+    /// Verifies the signature of a signed payment or stake delegation.
+    Verify(Verify),
+}
+
+#[derive(Debug, Clone, clap::ValueEnum)]
+pub enum TxFormat {
+    /// Body of the `sendPayment`/`sendDelegation` GraphQL mutation.
+    Graphql,
+    /// Body of the node's `/send-payment` endpoint, payments only.
+    SendPayment,
+    /// Base64 of the binprot encoded command, as in GraphQL `id` fields.
+    Base64,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct TxArgs {
+    /// Encrypted key file of the sender.
+    #[arg(long)]
+    key: PathBuf,
+
+    /// Password used to decrypt the key file.
+    #[arg(
+        long,
+        env = "MINA_PRIVKEY_PASS",
+        default_value = "",
+        hide_env_values = true
+    )]
+    key_password: String,
+
+    /// Fee in nanomina.
+    #[arg(long)]
+    fee: u64,
+
+    /// Nonce of the sender's account.
+    #[arg(long)]
+    nonce: u32,
+
+    /// Last global slot since genesis at which the command is valid.
+    #[arg(long)]
+    valid_until: Option<u32>,
+
+    /// Memo, at most 32 bytes.
+    #[arg(long, default_value = "")]
+    memo: String,
+
+    #[arg(long, value_enum, default_value_t = TxFormat::Graphql)]
+    format: TxFormat,
+
+    /// Submit the command to the GraphQL endpoint of a running node,
+    /// e.g. `http://localhost:3000`.
+    #[arg(long)]
+    submit: Option<Url>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Payment {
+    #[command(flatten)]
+    args: TxArgs,
+
+    /// Receiver of the payment.
+    #[arg(long)]
+    to: AccountPublicKey,
+
+    /// Amount in nanomina.
+    #[arg(long)]
+    amount: u64,
+}
+
+impl Payment {
+    pub fn run(self) -> anyhow::Result<()> {
+        let body = signed_command::Body::Payment(signed_command::PaymentPayload {
+            receiver_pk: parse_public_key(self.to)?,
+            amount: Amount::from_u64(self.amount),
+        });
+        self.args.run(body)
+    }
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Delegation {
+    #[command(flatten)]
+    args: TxArgs,
+
+    /// Block producer to delegate the stake to.
+    #[arg(long)]
+    to: AccountPublicKey,
+}
+
+impl Delegation {
+    pub fn run(self) -> anyhow::Result<()> {
+        let body = signed_command::Body::StakeDelegation(
+            signed_command::StakeDelegationPayload::SetDelegate {
+                new_delegate: parse_public_key(self.to)?,
+            },
+        );
+        self.args.run(body)
+    }
+}
+
+impl TxArgs {
+    fn run(self, body: signed_command::Body) -> anyhow::Result<()> {
+        let cmd = self.sign(body)?;
+        let output = match self.format {
+            TxFormat::Graphql => graphql_body(&cmd),
+            TxFormat::SendPayment => send_payment_body(&cmd)?,
+            TxFormat::Base64 => {
+                Value::String(MinaBaseSignedCommandStableV2::from(&cmd).to_base64()?)
+            }
+        };
+        println!("{}", serde_json::to_string_pretty(&output)?);
+
+        if let Some(url) = self.submit {
+            submit(url, &cmd)?;
+        }
+        Ok(())
+    }
+
+    fn sign(&self, body: signed_command::Body) -> anyhow::Result<SignedCommand> {
+        let secret_key = AccountSecretKey::from_encrypted_file(&self.key, &self.key_password)
+            .map_err(|err| anyhow::anyhow!("failed to decrypt {:?}: {err}", self.key))?;
+        self.sign_with(secret_key, body)
+    }
+
+    /// Signs with the signature prefix of the configured network, the same
+    /// one [`Verify`] checks the signature with.
+    fn sign_with(
+        &self,
+        secret_key: AccountSecretKey,
+        body: signed_command::Body,
+    ) -> anyhow::Result<SignedCommand> {
+        if self.memo.len() > MEMO_MAX_LENGTH {
+            anyhow::bail!("memo is longer than {MEMO_MAX_LENGTH} bytes");
+        }
+        let signer_pk = secret_key.public_key_compressed();
+
+        let payload = SignedCommandPayload::create(
+            Fee::from_u64(self.fee),
+            signer_pk.clone(),
+            Nonce::from_u32(self.nonce),
+            self.valid_until.map(Slot::from_u32),
+            Memo::from_str(&self.memo).map_err(|_| anyhow::anyhow!("invalid memo"))?,
+            body,
+        );
+        let signature = legacy_sign(
+            &secret_key.into(),
+            &TransactionUnionPayload::of_user_command_payload(&payload),
+        );
+
+        Ok(SignedCommand {
+            payload,
+            signer: signer_pk,
+            signature,
+        })
+    }
+}
+
+fn parse_public_key(pk: AccountPublicKey) -> anyhow::Result<CompressedPubKey> {
+    pk.try_into()
+        .map_err(|_| anyhow::anyhow!("invalid public key"))
+}
+
+fn graphql_body(cmd: &SignedCommand) -> Value {
+    let common = &cmd.payload.common;
+    let from = AccountPublicKey::from(common.fee_payer_pk.clone()).to_string();
+    let fee = common.fee.as_u64().to_string();
+    let nonce = Some(common.nonce.as_u32().to_string());
+    let valid_until = Some(common.valid_until.as_u32().to_string());
+    let memo = Some(common.memo.to_string());
+    let signature = SignatureInput {
+        field: Some(BigInt::from(cmd.signature.rx).to_decimal()),
+        scalar: Some(BigInt::from(cmd.signature.s).to_decimal()),
+        raw_signature: None,
+    };
+
+    match &cmd.payload.body {
+        signed_command::Body::Payment(payment) => json!({
+            "query": SEND_PAYMENT_MUTATION,
+            "variables": {
+                "input": SendPaymentInput {
+                    from,
+                    to: AccountPublicKey::from(payment.receiver_pk.clone()).to_string(),
+                    amount: payment.amount.as_u64().to_string(),
+                    fee,
+                    valid_until,
+                    memo,
+                    nonce,
+                },
+                "signature": signature,
+            },
+        }),
+        signed_command::Body::StakeDelegation(
+            signed_command::StakeDelegationPayload::SetDelegate { new_delegate },
+        ) => json!({
+            "query": SEND_DELEGATION_MUTATION,
+            "variables": {
+                "input": SendDelegationInput {
+                    from,
+                    to: AccountPublicKey::from(new_delegate.clone()).to_string(),
+                    fee,
+                    valid_until,
+                    memo,
+                    nonce,
+                },
+                "signature": signature,
+            },
+        }),
+    }
+}
+
+fn send_payment_body(cmd: &SignedCommand) -> anyhow::Result<Value> {
+    let signed_command::Body::Payment(payment) = &cmd.payload.body else {
+        anyhow::bail!("`/send-payment` only accepts payments, use the graphql format");
+    };
+    let common = &cmd.payload.common;
+    let payment = RpcInjectPayment {
+        fee: common.fee.as_u64(),
+        amount: payment.amount.as_u64(),
+        to: payment.receiver_pk.clone().into(),
+        from: common.fee_payer_pk.clone().into(),
+        memo: common.memo.to_string(),
+        nonce: common.nonce.as_u32(),
+        valid_until: common.valid_until.as_u32(),
+        signature_field: cmd.signature.rx.into(),
+        signature_scalar: cmd.signature.s.into(),
+    };
+    Ok(serde_json::to_value(vec![payment])?)
+}
+
+fn submit(mut url: Url, cmd: &SignedCommand) -> anyhow::Result<()> {
+    url.set_path("graphql");
+    let response = reqwest::blocking::Client::new()
+        .post(url)
+        .json(&graphql_body(cmd))
+        .send()?
+        .json::<Value>()?;
+    if let Some(errors) = response.get("errors") {
+        anyhow::bail!("node rejected the command: {errors}");
+    }
+    eprintln!("submitted: {response}");
+    Ok(())
+}
+
+#[derive(Debug, clap::Args)]
+pub struct Verify {
+    /// Signed command in any of the `--format`s: a file, `-` for stdin
+    /// or the base64 string itself.
+    input: String,
+}
+
+impl Verify {
+    pub fn run(self) -> anyhow::Result<()> {
+        let input = if self.input == "-" {
+            let mut input = String::new();
+            std::io::stdin().read_to_string(&mut input)?;
+            input
+        } else if PathBuf::from(&self.input).is_file() {
+            fs::read_to_string(&self.input)?
+        } else {
+            self.input
+        };
+
+        let cmd = parse_signed_command(input.trim())?;
+        let hash = cmd.hash()?;
+        let cmd = SignedCommand::try_from(&cmd)
+            .map_err(|_| anyhow::anyhow!("invalid field element in the command"))?;
+        let signer = ledger::decompress_pk(&cmd.signer)
+            .ok_or_else(|| anyhow::anyhow!("invalid signer public key"))?;
+
+        println!("hash: {hash}");
+        println!("signer: {}", AccountPublicKey::from(cmd.signer.clone()));
+        let payload = TransactionUnionPayload::of_user_command_payload(&cmd.payload);
+        if !legacy_verify_signature(&cmd.signature, &signer, &payload) {
+            anyhow::bail!(
+                "invalid signature for {} network",
+                openmina_core::NetworkConfig::global().name
+            );
+        }
+        println!("signature: valid");
+        Ok(())
+    }
+}
+
+fn parse_signed_command(input: &str) -> anyhow::Result<MinaBaseSignedCommandStableV2> {
+    if !input.starts_with(['{', '[']) {
+        return Ok(MinaBaseSignedCommandStableV2::from_base64(input)?);
+    }
+
+    let cmd = match serde_json::from_str::<Value>(input)? {
+        Value::Array(mut payments) => {
+            if payments.len() != 1 {
+                anyhow::bail!("expected a single payment, got {}", payments.len());
+            }
+            let payment = serde_json::from_value::<RpcInjectPayment>(payments.remove(0))?;
+            MinaBaseUserCommandStableV2::try_from(payment)
+                .map_err(|_| anyhow::anyhow!("invalid field element in the payment"))?
+        }
+        mut body => {
+            let variables = body
+                .get_mut("variables")
+                .filter(|variables| variables.is_object())
+                .ok_or_else(|| anyhow::anyhow!("expected GraphQL `variables`"))?;
+            let signature =
+                serde_json::from_value::<SignatureInput>(variables["signature"].take())?;
+            let input = variables["input"].take();
+            if input.get("amount").is_some() {
+                serde_json::from_value::<SendPaymentInput>(input)?.into_command(signature)?
+            } else {
+                serde_json::from_value::<SendDelegationInput>(input)?.into_command(signature)?
+            }
+        }
+    };
+    match cmd {
+        MinaBaseUserCommandStableV2::SignedCommand(cmd) => Ok(cmd),
+        MinaBaseUserCommandStableV2::ZkappCommand(_) => anyhow::bail!("not a signed command"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Debug, clap::Parser)]
+    struct TestCli {
+        #[command(subcommand)]
+        command: TxCommand,
+    }
+
+    fn test_args(memo: &str) -> TxArgs {
+        TxArgs {
+            key: PathBuf::new(),
+            key_password: String::new(),
+            fee: 10_000_000,
+            nonce: 3,
+            valid_until: Some(100),
+            memo: memo.to_owned(),
+            format: TxFormat::Graphql,
+            submit: None,
+        }
+    }
+
+    fn payment_body(amount: u64) -> signed_command::Body {
+        signed_command::Body::Payment(signed_command::PaymentPayload {
+            receiver_pk: AccountSecretKey::rand().public_key_compressed(),
+            amount: Amount::from_u64(amount),
+        })
+    }
+
+    fn verify(cmd: &MinaBaseSignedCommandStableV2) -> bool {
+        let cmd = SignedCommand::try_from(cmd).unwrap();
+        let signer = ledger::decompress_pk(&cmd.signer).unwrap();
+        let payload = TransactionUnionPayload::of_user_command_payload(&cmd.payload);
+        legacy_verify_signature(&cmd.signature, &signer, &payload)
+    }
+
+    #[test]
+    fn test_sign_verify_round_trip() {
+        let secret_key = AccountSecretKey::rand();
+        let cmd = test_args("hello")
+            .sign_with(secret_key.clone(), payment_body(1_000))
+            .unwrap();
+        assert_eq!(cmd.signer, secret_key.public_key_compressed());
+
+        let graphql = graphql_body(&cmd).to_string();
+        let send_payment = send_payment_body(&cmd).unwrap().to_string();
+        let base64 = MinaBaseSignedCommandStableV2::from(&cmd)
+            .to_base64()
+            .unwrap();
+        for input in [graphql, send_payment, base64] {
+            let parsed = parse_signed_command(&input).unwrap();
+            assert_eq!(parsed, MinaBaseSignedCommandStableV2::from(&cmd));
+            assert!(verify(&parsed), "{input}");
+        }
+
+        let mut tampered = MinaBaseSignedCommandStableV2::from(&cmd);
+        tampered.payload.common.nonce = 4u32.into();
+        assert!(!verify(&tampered));
+    }
+
+    #[test]
+    fn test_memo_length() {
+        let secret_key = AccountSecretKey::rand();
+        let memo = "a".repeat(MEMO_MAX_LENGTH);
+        assert!(test_args(&memo)
+            .sign_with(secret_key.clone(), payment_body(1))
+            .is_ok());
+
+        let memo = "a".repeat(MEMO_MAX_LENGTH + 1);
+        let err = test_args(&memo)
+            .sign_with(secret_key, payment_body(1))
+            .unwrap_err();
+        assert!(err.to_string().contains("memo is longer"), "{err}");
+    }
+
+    #[test]
+    fn test_fee_and_nonce_parsing() {
+        let to = AccountSecretKey::rand().public_key().to_string();
+        let parse = |fee: &str, nonce: &str| {
+            TestCli::try_parse_from([
+                "tx", "payment", "--key", "key", "--fee", fee, "--nonce", nonce, "--to", &to,
+                "--amount", "1",
+            ])
+        };
+
+        let TxCommand::Payment(payment) = parse("10000000", "4294967295").unwrap().command else {
+            panic!("expected payment");
+        };
+        assert_eq!(payment.args.fee, 10_000_000);
+        assert_eq!(payment.args.nonce, u32::MAX);
+
+        for (fee, nonce) in [
+            ("-1", "0"),
+            ("0.1", "0"),
+            ("18446744073709551616", "0"),
+            ("1", "-1"),
+            ("1", "4294967296"),
+            ("1", "one"),
+        ] {
+            assert!(parse(fee, nonce).is_err(), "fee: {fee}, nonce: {nonce}");
+        }
+    }
+}
@@ -50,9 +50,12 @@ fn main() -> anyhow::Result<()> {
     unsafe_signal_handlers::setup();
     let app = commands::OpenminaCli::parse();
 
-    let network_init_result = match app.network {
-        commands::Network::Devnet => openmina_core::NetworkConfig::init("devnet"),
-        commands::Network::Mainnet => openmina_core::NetworkConfig::init("mainnet"),
+    let network_init_result = match app.custom_network_config()? {
+        Some(config) => openmina_core::NetworkConfig::init_custom(config),
+        None => match app.network {
+            commands::Network::Devnet => openmina_core::NetworkConfig::init("devnet"),
+            commands::Network::Mainnet => openmina_core::NetworkConfig::init("mainnet"),
+        },
     };
 
     network_init_result.expect("Failed to initialize network configuration");
@@ -1,6 +1,7 @@
 use binprot_derive::BinProtWrite;
 use mina_hasher::Fp;
 use mina_p2p_messages::{bigint, number, v2};
+use serde::{Deserialize, Serialize};
 
 pub const GENESIS_PRODUCER_SK: &str = "EKFKgDtU3rcuFTVSEpmpXSkukjmX4cKefYREi6Sdsk7E7wsT7KRw";
 
@@ -14,14 +15,32 @@ pub fn constraint_constants() -> &'static ConstraintConstants {
     NetworkConfig::global().constraint_constants
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ForkConstants {
+    #[serde(with = "fork_state_hash")]
     pub state_hash: Fp,
     pub blockchain_length: u32,
     pub global_slot_since_genesis: u32,
 }
 
-#[derive(Clone, Debug)]
+/// Fork state hash is (de)serialized as a base58 state hash.
+mod fork_state_hash {
+    use mina_hasher::Fp;
+    use mina_p2p_messages::v2::StateHash;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(state_hash: &Fp, serializer: S) -> Result<S::Ok, S::Error> {
+        StateHash::from_fp(*state_hash).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fp, D::Error> {
+        StateHash::deserialize(deserializer)?
+            .to_field()
+            .map_err(|err| serde::de::Error::custom(format!("invalid state hash: {err:?}")))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ConstraintConstants {
     pub sub_windows_per_window: u64,
     pub ledger_depth: u64,
@@ -32,6 +51,7 @@ pub struct ConstraintConstants {
     pub coinbase_amount: u64,
     pub supercharged_coinbase_factor: u64,
     pub account_creation_fee: u64,
+    #[serde(default)]
     pub fork: Option<ForkConstants>,
 }
 #[derive(Clone, Debug, BinProtWrite)]
@@ -100,11 +120,19 @@ const fn days_to_ms(days: u64) -> u64 {
 pub const CHECKPOINTS_PER_YEAR: u64 = 12;
 
 pub fn checkpoint_window_size_in_slots() -> u32 {
+    checkpoint_window_size_in_slots_for(constraint_constants().block_window_duration_ms)
+        .expect("slots per year must be divisible by checkpoints per year")
+}
+
+/// Returns `None` if slots per year, for the given block window
+/// duration, can't be evenly split into checkpoint windows.
+pub fn checkpoint_window_size_in_slots_for(block_window_duration_ms: u64) -> Option<u32> {
     let one_year_ms = days_to_ms(365);
-    let slots_per_year = one_year_ms / constraint_constants().block_window_duration_ms;
-    let size_in_slots = slots_per_year / CHECKPOINTS_PER_YEAR;
-    assert_eq!(slots_per_year % CHECKPOINTS_PER_YEAR, 0);
-    size_in_slots as u32
+    let slots_per_year = one_year_ms.checked_div(block_window_duration_ms)?;
+    if slots_per_year % CHECKPOINTS_PER_YEAR != 0 {
+        return None;
+    }
+    Some((slots_per_year / CHECKPOINTS_PER_YEAR) as u32)
 }
 
 pub const DEFAULT_GENESIS_TIMESTAMP_MILLISECONDS: u64 = 1707157200000;
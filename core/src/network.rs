@@ -3,11 +3,14 @@ use poseidon::hash::{
     legacy,
     params::{CODA_SIGNATURE, MAINNET_ZKAPP_BODY, MINA_SIGNATURE_MAINNET, TESTNET_ZKAPP_BODY},
 };
+use serde::{Deserialize, Serialize};
 
-use crate::constants::ConstraintConstants;
+use crate::constants::{checkpoint_window_size_in_slots_for, ConstraintConstants};
+use crate::ChainId;
 
 // From mina-signer, to avoid dependency
-#[derive(Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "lowercase")]
 pub enum NetworkId {
     /// Id for all testnets
     TESTNET = 0x00,
@@ -27,10 +30,15 @@ pub struct NetworkConfig {
     pub default_peers: Vec<&'static str>,
     pub circuits_config: &'static CircuitsConfig,
     pub constraint_constants: &'static ConstraintConstants,
+    /// Expected chain id. If set, node won't initialize p2p layer when
+    /// the chain id computed from the genesis doesn't match it.
+    pub chain_id: Option<ChainId>,
 }
 
 #[derive(Debug)]
 pub struct CircuitsConfig {
+    /// Network the circuits (and verifier indices) belong to.
+    pub network: &'static str,
     pub directory_name: &'static str,
 
     pub step_transaction_gates: &'static str,
@@ -73,6 +81,17 @@ impl NetworkConfig {
         Ok(())
     }
 
+    /// Initializes custom network (e.g. private testnet) configuration.
+    pub fn init_custom(config: CustomNetworkConfig) -> Result<(), String> {
+        let config = Self::custom_config(config)?;
+
+        CONFIG
+            .set(config)
+            .map_err(|_| "Double network configuration initialization".to_owned())?;
+
+        Ok(())
+    }
+
     fn default_config() -> Self {
         Self::devnet_config()
     }
@@ -88,6 +107,7 @@ impl NetworkConfig {
             default_peers: mainnet::default_peers(),
             circuits_config: &mainnet::CIRCUITS_CONFIG,
             constraint_constants: &mainnet::CONSTRAINT_CONSTANTS,
+            chain_id: None,
         }
     }
 
@@ -102,8 +122,128 @@ impl NetworkConfig {
             default_peers: devnet::default_peers(),
             circuits_config: &devnet::CIRCUITS_CONFIG,
             constraint_constants: &devnet::CONSTRAINT_CONSTANTS,
+            chain_id: None,
+        }
+    }
+
+    fn custom_config(config: CustomNetworkConfig) -> Result<Self, String> {
+        config.validate()?;
+
+        let base = match config.circuits.as_str() {
+            devnet::NAME => Self::devnet_config(),
+            mainnet::NAME => Self::mainnet_config(),
+            other => Err(format!("Unknown circuits {other}"))?,
+        };
+        let signature_prefix = config
+            .signature_prefix
+            .unwrap_or_else(|| config.network_id.signature_prefix().to_owned());
+        let account_update_hash_param = config
+            .account_update_hash_param
+            .unwrap_or_else(|| config.network_id.account_update_hash_param().to_owned());
+        // Config lives for the whole lifetime of the process.
+        let signature_prefix: &'static str = Box::leak(signature_prefix.into_boxed_str());
+        let account_update_hash_param: &'static str =
+            Box::leak(account_update_hash_param.into_boxed_str());
+
+        Ok(Self {
+            name: Box::leak(config.name.into_boxed_str()),
+            network_id: config.network_id,
+            signature_prefix: Box::leak(poseidon::hash::params::param(signature_prefix)),
+            legacy_signature_prefix: Box::leak(legacy::params::param(signature_prefix)),
+            account_update_hash_param: Box::leak(poseidon::hash::params::param(
+                account_update_hash_param,
+            )),
+            constraint_system_digests: base.constraint_system_digests,
+            default_peers: config
+                .default_peers
+                .into_iter()
+                .map(|peer| &*Box::leak(peer.into_boxed_str()))
+                .collect(),
+            circuits_config: base.circuits_config,
+            constraint_constants: Box::leak(Box::new(config.constraint_constants)),
+            chain_id: config.chain_id,
+        })
+    }
+}
+
+impl NetworkId {
+    pub fn signature_prefix(&self) -> &'static str {
+        match self {
+            Self::TESTNET => devnet::SIGNATURE_PREFIX,
+            Self::MAINNET => mainnet::SIGNATURE_PREFIX,
         }
     }
+
+    pub fn account_update_hash_param(&self) -> &'static str {
+        match self {
+            Self::TESTNET => devnet::ACCOUNT_UPDATE_HASH_PARAM,
+            Self::MAINNET => mainnet::ACCOUNT_UPDATE_HASH_PARAM,
+        }
+    }
+}
+
+/// Definition of a custom network, e.g. a private testnet.
+///
+/// We can't produce circuits ourselves, so custom network has to reuse
+/// circuits (and constraint system digests) of one of the known
+/// networks, selected with `circuits`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CustomNetworkConfig {
+    pub name: String,
+    pub network_id: NetworkId,
+    /// Defaults to the signature prefix of the `network_id`.
+    #[serde(default)]
+    pub signature_prefix: Option<String>,
+    /// Defaults to the account update hash param of the `network_id`.
+    #[serde(default)]
+    pub account_update_hash_param: Option<String>,
+    /// Known network (`devnet` or `mainnet`) whose circuits are used.
+    pub circuits: String,
+    /// Constraint constants, along with the fork data.
+    pub constraint_constants: ConstraintConstants,
+    #[serde(default)]
+    pub default_peers: Vec<String>,
+    /// Expected chain id, hex encoded.
+    #[serde(default)]
+    pub chain_id: Option<ChainId>,
+}
+
+impl CustomNetworkConfig {
+    /// Max length of the string, from which poseidon param can be built.
+    const MAX_PARAM_LEN: usize = 20;
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("network name must not be empty".to_owned());
+        }
+        let params = [
+            ("signature_prefix", &self.signature_prefix),
+            ("account_update_hash_param", &self.account_update_hash_param),
+        ];
+        for (field, param) in params {
+            if let Some(param) = param.as_ref().filter(|p| p.len() > Self::MAX_PARAM_LEN) {
+                return Err(format!(
+                    "{field} \"{param}\" is longer than {} bytes",
+                    Self::MAX_PARAM_LEN
+                ));
+            }
+        }
+
+        let constants = &self.constraint_constants;
+        if constants.sub_windows_per_window == 0 {
+            return Err("sub_windows_per_window must be positive".to_owned());
+        }
+        if constants.ledger_depth == 0 {
+            return Err("ledger_depth must be positive".to_owned());
+        }
+        if checkpoint_window_size_in_slots_for(constants.block_window_duration_ms).is_none() {
+            return Err(format!(
+                "block_window_duration_ms {} doesn't split a year into checkpoint windows",
+                constants.block_window_duration_ms
+            ));
+        }
+        Ok(())
+    }
 }
 
 // Network constants
@@ -161,6 +301,7 @@ pub mod devnet {
     };
 
     pub const CIRCUITS_CONFIG: CircuitsConfig = CircuitsConfig {
+        network: NAME,
         directory_name: "3.0.1devnet",
 
         step_transaction_gates: "step-step-proving-key-transaction-snark-transaction-0-c33ec5211c07928c87e850a63c6a2079",
@@ -244,6 +385,7 @@ pub mod mainnet {
     };
 
     pub const CIRCUITS_CONFIG: CircuitsConfig = CircuitsConfig {
+        network: NAME,
         directory_name: "3.0.0mainnet",
 
         step_transaction_gates: "step-step-proving-key-transaction-snark-transaction-0-b421ac835a0e73935f3d3569ff87f484",
@@ -301,3 +443,64 @@ pub mod mainnet {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devnet_like_config() -> CustomNetworkConfig {
+        serde_json::from_value(serde_json::json!({
+            "name": "private-testnet",
+            "network_id": "testnet",
+            "circuits": "devnet",
+            "constraint_constants": devnet::CONSTRAINT_CONSTANTS,
+            "default_peers": ["/ip4/10.0.0.1/tcp/8302/p2p/12D3KooWAdgYL6hv18M3iDBdaK1dRygPivSfAfBNDzie6YqydVbs"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_custom_network_config_same_as_devnet() {
+        let config = NetworkConfig::custom_config(devnet_like_config()).unwrap();
+        let devnet = NetworkConfig::devnet_config();
+
+        assert_eq!(config.name, "private-testnet");
+        assert_eq!(
+            config.signature_prefix.state(),
+            devnet.signature_prefix.state()
+        );
+        assert_eq!(
+            config.legacy_signature_prefix.state(),
+            devnet.legacy_signature_prefix.state()
+        );
+        assert_eq!(
+            config.account_update_hash_param.state(),
+            devnet.account_update_hash_param.state()
+        );
+        assert_eq!(
+            config.constraint_system_digests,
+            devnet.constraint_system_digests
+        );
+        assert_eq!(config.circuits_config.network, devnet::NAME);
+
+        let fork = config.constraint_constants.fork.as_ref().unwrap();
+        let devnet_fork = devnet.constraint_constants.fork.as_ref().unwrap();
+        assert_eq!(fork.state_hash, devnet_fork.state_hash);
+        assert_eq!(fork.blockchain_length, devnet_fork.blockchain_length);
+    }
+
+    #[test]
+    fn test_custom_network_config_validation() {
+        let mut config = devnet_like_config();
+        config.signature_prefix = Some("PrivateTestnetSignature".to_owned());
+        assert!(NetworkConfig::custom_config(config).is_err());
+
+        let mut config = devnet_like_config();
+        config.constraint_constants.block_window_duration_ms = 7;
+        assert!(NetworkConfig::custom_config(config).is_err());
+
+        let mut config = devnet_like_config();
+        config.circuits = "berkeley".to_owned();
+        assert!(NetworkConfig::custom_config(config).is_err());
+    }
+}
@@ -158,6 +158,7 @@ mod tests {
         {
             let addr = Address::try_from(*s).unwrap();
             assert_eq!(index as u64, addr.to_linear_index());
+            assert_eq!(Address::from_linear_index(index as u64), addr);
         }
     }
 
@@ -172,6 +172,14 @@ impl<const NBYTES: usize> Address<NBYTES> {
         2u64.checked_pow(self.length as u32).unwrap() + index.0 - 1
     }
 
+    /// Inverse of [`Self::to_linear_index`].
+    pub fn from_linear_index(linear: u64) -> Self {
+        let n = linear + 1;
+        let length = (u64::BITS - 1 - n.leading_zeros()) as usize;
+
+        Self::from_index(AccountIndex(n - (1 << length)), length)
+    }
+
     pub fn iter(&self) -> AddressIterator<NBYTES> {
         AddressIterator {
             addr: self.clone(),
@@ -9,7 +9,7 @@ use mina_signer::CompressedPubKey;
 
 use crate::{
     account::{Account, AccountId, TokenId},
-    address::Address,
+    address::{Address, AddressIterator},
     base::{AccountIndex, BaseLedger, GetOrCreated, MerklePath, Uuid},
     // tree::{Database, DatabaseError},
     tree_version::V2,
@@ -18,17 +18,37 @@ use crate::{
 
 use crate::HashesMatrix;
 
-use super::database_impl::DatabaseImpl;
+use super::{database_impl::DatabaseImpl, ondisk_database_impl::OndiskDatabaseImpl};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum DatabaseError {
     OutOfLeaves,
+    /// Reading or writing an on-disk ledger failed.
+    Io(String),
 }
 
 #[derive(Clone, Debug)]
 pub struct Database<T: TreeVersion> {
     // Using a mutex for now but this can be replaced with a RefCell
-    pub inner: Arc<Mutex<DatabaseImpl<T>>>,
+    pub inner: Arc<Mutex<DatabaseBackend<T>>>,
+}
+
+/// Where accounts and hashes of a [`Database`] are kept.
+#[derive(Debug)]
+pub enum DatabaseBackend<T: TreeVersion> {
+    Memory(DatabaseImpl<T>),
+    /// Persisted in a [`crate::ondisk::Database`], only for [`V2`].
+    Ondisk(OndiskDatabaseImpl),
+}
+
+macro_rules! with_backend {
+    ($this:expr, |$db:ident| $body:expr) => {{
+        let mut inner = $this.inner.try_lock().expect("lock failed");
+        match &mut *inner {
+            DatabaseBackend::Memory($db) => $body,
+            DatabaseBackend::Ondisk($db) => $body,
+        }
+    }};
 }
 
 // #[derive(Debug)]
@@ -38,22 +58,12 @@ pub struct Database<T: TreeVersion> {
 //     IPromiseIAmReparentingThisDatabase,
 // }
 
-impl Database<V2> {
-    pub fn with<F, R>(&self, fun: F) -> R
-    where
-        F: FnOnce(&mut DatabaseImpl<V2>) -> R,
-    {
-        let mut inner = self.inner.try_lock().expect("lock failed");
-        fun(&mut inner)
-    }
-}
-
 impl Database<V2> {
     pub fn create_with_dir(depth: u8, dir_name: Option<PathBuf>) -> Self {
         let db = DatabaseImpl::<V2>::create_with_dir(depth, dir_name);
 
         Self {
-            inner: Arc::new(Mutex::new(db)),
+            inner: Arc::new(Mutex::new(DatabaseBackend::Memory(db))),
         }
     }
 
@@ -61,53 +71,112 @@ impl Database<V2> {
         Self::create_with_dir(depth, None)
     }
 
+    /// Opens the ledger persisted in `directory`, creating an empty one
+    /// if there is none.
+    ///
+    /// Accounts are read from disk when needed instead of being kept in
+    /// memory.
+    pub fn open_ondisk(depth: u8, directory: PathBuf) -> std::io::Result<Self> {
+        let db = OndiskDatabaseImpl::open(depth, directory)?;
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(DatabaseBackend::Ondisk(db))),
+        })
+    }
+
+    pub fn is_ondisk(&self) -> bool {
+        let inner = self.inner.try_lock().expect("lock failed");
+        matches!(&*inner, DatabaseBackend::Ondisk(_))
+    }
+
+    /// Returns the first I/O error of an on-disk ledger since the previous
+    /// call, the [`crate::BaseLedger`] methods can't return them.
+    pub fn take_io_error(&self) -> Option<std::io::Error> {
+        let inner = self.inner.try_lock().expect("lock failed");
+        match &*inner {
+            DatabaseBackend::Memory(_) => None,
+            DatabaseBackend::Ondisk(db) => db.take_io_error(),
+        }
+    }
+
     pub fn root_hash(&mut self) -> Fp {
-        self.with(|this| this.root_hash())
+        with_backend!(self, |this| this.root_hash())
     }
 
     // Do not use
     pub fn naccounts(&self) -> usize {
-        self.with(|this| this.naccounts())
+        with_backend!(self, |this| this.naccounts())
     }
 
     pub fn create_checkpoint(&self, directory_name: String) {
-        self.with(|this| this.create_checkpoint(directory_name))
+        with_backend!(self, |this| this.create_checkpoint(directory_name))
     }
 
     pub fn make_checkpoint(&self, directory_name: String) {
-        self.with(|this| this.make_checkpoint(directory_name))
+        with_backend!(self, |this| this.make_checkpoint(directory_name))
     }
 
     pub fn clone_db(&self, directory_name: PathBuf) -> Self {
-        let db = self.with(|this| this.clone_db(directory_name));
+        let inner = self.inner.try_lock().expect("lock failed");
+        let db = match &*inner {
+            DatabaseBackend::Memory(db) => DatabaseBackend::Memory(db.clone_db(directory_name)),
+            DatabaseBackend::Ondisk(db) => DatabaseBackend::Ondisk(db.clone_db(directory_name)),
+        };
         Self {
             inner: Arc::new(Mutex::new(db)),
         }
     }
 
     pub fn get_cached_hash(&self, addr: &Address) -> Option<Fp> {
-        self.with(|this| this.get_cached_hash(addr))
+        with_backend!(self, |this| this.get_cached_hash(addr))
     }
 
     pub fn set_cached_hash(&mut self, addr: &Address, hash: Fp) {
-        self.with(|this| this.set_cached_hash(addr, hash))
+        with_backend!(self, |this| this.set_cached_hash(addr, hash))
     }
 
     pub fn empty_hash_at_height(&mut self, height: usize) -> Fp {
-        self.with(|this| this.empty_hash_at_height(height))
+        with_backend!(self, |this| this.empty_hash_at_height(height))
     }
 
     pub fn invalidate_hashes(&mut self, account_index: AccountIndex) {
-        self.with(|this| this.invalidate_hashes(account_index))
+        with_backend!(self, |this| this.invalidate_hashes(account_index))
     }
 
     pub fn transfert_hashes(&mut self, hashes: HashesMatrix) {
-        self.with(|this| this.transfert_hashes(hashes))
+        with_backend!(self, |this| this.transfert_hashes(hashes))
+    }
+
+    pub fn emulate_tree_recursive(&self, addr: Address, last_account: &Address) -> Fp {
+        with_backend!(self, |this| this.emulate_tree_recursive(addr, last_account))
+    }
+
+    pub fn emulate_tree_to_get_path(
+        &self,
+        addr: Address,
+        last_account: &Address,
+        path: &mut AddressIterator,
+        merkle_path: &mut Vec<MerklePath>,
+    ) -> Fp {
+        with_backend!(self, |this| this.emulate_tree_to_get_path(
+            addr,
+            last_account,
+            path,
+            merkle_path
+        ))
+    }
+
+    pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
+        with_backend!(self, |this| this.get_raw_inner_hashes())
+    }
+
+    pub fn set_raw_inner_hashes(&self, hashes: Vec<(u64, Fp)>) {
+        with_backend!(self, |this| this.set_raw_inner_hashes(hashes))
     }
 
     #[cfg(test)]
     pub fn test_matrix(&self) -> HashesMatrix {
-        self.with(|this| this.hashes_matrix.clone())
+        with_backend!(self, |this| this.test_matrix())
         // match self {
         //     Root { database, .. } => database,
         //     Unattached { hashes, .. } | Attached { hashes, .. } => hashes.clone(),
@@ -117,54 +186,55 @@ impl Database<V2> {
 
 impl BaseLedger for Database<V2> {
     fn to_list(&self) -> Vec<Account> {
-        self.with(|this| this.to_list())
+        with_backend!(self, |this| this.to_list())
     }
 
     fn iter<F>(&self, fun: F)
     where
         F: FnMut(&Account),
     {
-        self.with(|this| this.iter(fun))
+        with_backend!(self, |this| this.iter(fun))
     }
 
     fn fold<B, F>(&self, init: B, fun: F) -> B
     where
         F: FnMut(B, &Account) -> B,
     {
-        self.with(|this| this.fold(init, fun))
+        with_backend!(self, |this| this.fold(init, fun))
     }
 
     fn fold_with_ignored_accounts<B, F>(&self, ignoreds: HashSet<AccountId>, init: B, fun: F) -> B
     where
         F: FnMut(B, &Account) -> B,
     {
-        self.with(|this| this.fold_with_ignored_accounts(ignoreds, init, fun))
+        with_backend!(self, |this| this
+            .fold_with_ignored_accounts(ignoreds, init, fun))
     }
 
     fn fold_until<B, F>(&self, init: B, fun: F) -> B
     where
         F: FnMut(B, &Account) -> std::ops::ControlFlow<B, B>,
     {
-        self.with(|this| this.fold_until(init, fun))
+        with_backend!(self, |this| this.fold_until(init, fun))
     }
 
     fn accounts(&self) -> HashSet<AccountId> {
-        self.with(|this| this.accounts())
+        with_backend!(self, |this| this.accounts())
     }
 
     fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId> {
-        self.with(|this| this.tokens(public_key))
+        with_backend!(self, |this| this.tokens(public_key))
     }
 
     fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
-        self.with(|this| this.location_of_account(account_id))
+        with_backend!(self, |this| this.location_of_account(account_id))
     }
 
     fn location_of_account_batch(
         &self,
         account_ids: &[AccountId],
     ) -> Vec<(AccountId, Option<Address>)> {
-        self.with(|this| this.location_of_account_batch(account_ids))
+        with_backend!(self, |this| this.location_of_account_batch(account_ids))
     }
 
     fn get_or_create_account(
@@ -172,7 +242,7 @@ impl BaseLedger for Database<V2> {
         account_id: AccountId,
         account: Account,
     ) -> Result<GetOrCreated, DatabaseError> {
-        self.with(|this| this.get_or_create_account(account_id, account))
+        with_backend!(self, |this| this.get_or_create_account(account_id, account))
     }
 
     fn close(&self) {
@@ -180,87 +250,87 @@ impl BaseLedger for Database<V2> {
     }
 
     fn last_filled(&self) -> Option<Address> {
-        self.with(|this| this.last_filled())
+        with_backend!(self, |this| this.last_filled())
     }
 
     fn get_uuid(&self) -> Uuid {
-        self.with(|this| this.get_uuid())
+        with_backend!(self, |this| this.get_uuid())
     }
 
     fn get_directory(&self) -> Option<PathBuf> {
-        self.with(|this| this.get_directory())
+        with_backend!(self, |this| this.get_directory())
     }
 
     fn get_account_hash(&mut self, account_index: AccountIndex) -> Option<Fp> {
-        self.with(|this| this.get_account_hash(account_index))
+        with_backend!(self, |this| this.get_account_hash(account_index))
     }
 
     fn get(&self, addr: Address) -> Option<Box<Account>> {
-        self.with(|this| this.get(addr))
+        with_backend!(self, |this| this.get(addr))
     }
 
     fn get_batch(&self, addr: &[Address]) -> Vec<(Address, Option<Box<Account>>)> {
-        self.with(|this| this.get_batch(addr))
+        with_backend!(self, |this| this.get_batch(addr))
     }
 
     fn set(&mut self, addr: Address, account: Box<Account>) {
-        self.with(|this| this.set(addr, account))
+        with_backend!(self, |this| this.set(addr, account))
     }
 
     fn set_batch(&mut self, list: &[(Address, Box<Account>)]) {
-        self.with(|this| this.set_batch(list))
+        with_backend!(self, |this| this.set_batch(list))
     }
 
     fn get_at_index(&self, index: AccountIndex) -> Option<Box<Account>> {
-        self.with(|this| this.get_at_index(index))
+        with_backend!(self, |this| this.get_at_index(index))
     }
 
     fn set_at_index(&mut self, index: AccountIndex, account: Box<Account>) -> Result<(), ()> {
-        self.with(|this| this.set_at_index(index, account))
+        with_backend!(self, |this| this.set_at_index(index, account))
     }
 
     fn index_of_account(&self, account_id: AccountId) -> Option<AccountIndex> {
-        self.with(|this| this.index_of_account(account_id))
+        with_backend!(self, |this| this.index_of_account(account_id))
     }
 
     fn merkle_root(&mut self) -> Fp {
-        self.with(|this| this.merkle_root())
+        with_backend!(self, |this| this.merkle_root())
     }
 
     fn merkle_path(&mut self, addr: Address) -> Vec<MerklePath> {
-        self.with(|this| this.merkle_path(addr))
+        with_backend!(self, |this| this.merkle_path(addr))
     }
 
     fn merkle_path_at_index(&mut self, index: AccountIndex) -> Vec<MerklePath> {
-        self.with(|this| this.merkle_path_at_index(index))
+        with_backend!(self, |this| this.merkle_path_at_index(index))
     }
 
     fn remove_accounts(&mut self, ids: &[AccountId]) {
-        self.with(|this| this.remove_accounts(ids))
+        with_backend!(self, |this| this.remove_accounts(ids))
     }
 
     fn detached_signal(&mut self) {
-        self.with(|this| this.detached_signal())
+        with_backend!(self, |this| this.detached_signal())
     }
 
     fn depth(&self) -> u8 {
-        self.with(|this| this.depth())
+        with_backend!(self, |this| this.depth())
     }
 
     fn num_accounts(&self) -> usize {
-        self.with(|this| this.num_accounts())
+        with_backend!(self, |this| this.num_accounts())
     }
 
     fn merkle_path_at_addr(&mut self, addr: Address) -> Vec<MerklePath> {
-        self.with(|this| this.merkle_path_at_addr(addr))
+        with_backend!(self, |this| this.merkle_path_at_addr(addr))
     }
 
     fn get_inner_hash_at_addr(&mut self, addr: Address) -> Result<Fp, String> {
-        self.with(|this| this.get_inner_hash_at_addr(addr))
+        with_backend!(self, |this| this.get_inner_hash_at_addr(addr))
     }
 
     fn set_inner_hash_at_addr(&mut self, addr: Address, hash: Fp) -> Result<(), ()> {
-        self.with(|this| this.set_inner_hash_at_addr(addr, hash))
+        with_backend!(self, |this| this.set_inner_hash_at_addr(addr, hash))
     }
 
     fn set_all_accounts_rooted_at(
@@ -268,15 +338,15 @@ impl BaseLedger for Database<V2> {
         addr: Address,
         accounts: &[Box<Account>],
     ) -> Result<(), ()> {
-        self.with(|this| this.set_all_accounts_rooted_at(addr, accounts))
+        with_backend!(self, |this| this.set_all_accounts_rooted_at(addr, accounts))
     }
 
     fn get_all_accounts_rooted_at(&self, addr: Address) -> Option<Vec<(Address, Box<Account>)>> {
-        self.with(|this| this.get_all_accounts_rooted_at(addr))
+        with_backend!(self, |this| this.get_all_accounts_rooted_at(addr))
     }
 
     fn make_space_for(&mut self, space: usize) {
-        self.with(|this| this.make_space_for(space))
+        with_backend!(self, |this| this.make_space_for(space))
     }
 
     fn commit(&mut self) {
@@ -270,6 +270,19 @@ impl DatabaseImpl<V2> {
     pub fn transfert_hashes(&mut self, hashes: HashesMatrix) {
         self.hashes_matrix.transfert_hashes(hashes)
     }
+
+    pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
+        self.hashes_matrix.get_raw_inner_hashes()
+    }
+
+    pub fn set_raw_inner_hashes(&mut self, hashes: Vec<(u64, Fp)>) {
+        self.hashes_matrix.set_raw_inner_hashes(hashes)
+    }
+
+    #[cfg(test)]
+    pub fn test_matrix(&self) -> HashesMatrix {
+        self.hashes_matrix.clone()
+    }
 }
 
 impl DatabaseImpl<V1> {
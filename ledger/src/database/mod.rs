@@ -2,5 +2,6 @@
 
 mod database;
 mod database_impl;
+mod ondisk_database_impl;
 
 pub use database::*;
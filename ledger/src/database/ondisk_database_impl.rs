@@ -0,0 +1,1058 @@
+//! Ledger database persisted in an [`ondisk::Database`].
+//!
+//! Accounts, the account id index and merkle hashes are stored as separate
+//! entries, so only what is needed is read from disk. Every change is
+//! written in a single batch, together with invalidation of the hashes it
+//! affects, so the database can be reopened after a crash.
+//!
+//! [`BaseLedger`] has no way to return I/O errors. Failed reads of
+//! accounts panic, as callers would take them for missing accounts and
+//! diverge from the real ledger state. Other errors are kept until
+//! [`OndiskDatabaseImpl::take_io_error`] is called: failed reads of hashes
+//! behave as missing hashes, which get recomputed, and failed writes leave
+//! the ledger as it was before the change.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashSet},
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+};
+
+use mina_hasher::Fp;
+use mina_p2p_messages::{
+    bigint::BigInt,
+    binprot::{BinProtRead, BinProtWrite},
+};
+use mina_signer::CompressedPubKey;
+
+use crate::{
+    next_uuid,
+    ondisk::{self, Batch},
+    Account, AccountId, AccountIndex, Address, AddressIterator, BaseLedger, Direction,
+    GetOrCreated, HashesMatrix, MerklePath, TokenId, TreeVersion, Uuid, V2,
+};
+
+use super::DatabaseError;
+
+const META_KEY: &[u8] = b"meta";
+const ACCOUNT_KEY_PREFIX: &[u8] = b"account/";
+const ACCOUNT_ID_KEY_PREFIX: &[u8] = b"id/";
+const HASH_KEY_PREFIX: &[u8] = b"hash/";
+
+/// Depth, number of accounts and index of the last account.
+const META_NBYTES: usize = 17;
+
+pub struct OndiskDatabaseImpl {
+    // `ondisk::Database` needs `&mut self` to read
+    db: RefCell<ondisk::Database>,
+    depth: u8,
+    last_location: Option<Address>,
+    naccounts: usize,
+    /// Hashes computed or read since the database was opened.
+    hashes_matrix: HashesMatrix,
+    uuid: Uuid,
+    directory: PathBuf,
+    /// First I/O error that wasn't taken yet.
+    io_error: RefCell<Option<std::io::Error>>,
+}
+
+impl std::fmt::Debug for OndiskDatabaseImpl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OndiskDatabase")
+            .field("depth", &self.depth)
+            .field("naccounts", &self.naccounts)
+            .field("uuid", &self.uuid)
+            .field("directory", &self.directory)
+            .finish()
+    }
+}
+
+fn account_key(index: AccountIndex) -> Box<[u8]> {
+    [ACCOUNT_KEY_PREFIX, &index.as_u64().to_be_bytes()]
+        .concat()
+        .into()
+}
+
+fn account_id_key(account_id: &AccountId) -> Box<[u8]> {
+    [ACCOUNT_ID_KEY_PREFIX, &encode(account_id)].concat().into()
+}
+
+fn hash_key(addr: &Address) -> Box<[u8]> {
+    [HASH_KEY_PREFIX, &addr.to_linear_index().to_be_bytes()]
+        .concat()
+        .into()
+}
+
+fn encode<T: BinProtWrite>(value: &T) -> Box<[u8]> {
+    let mut buf = Vec::with_capacity(64);
+    value
+        .binprot_write(&mut buf)
+        .expect("writing to a vec can't fail");
+    buf.into()
+}
+
+fn decode<T: BinProtRead>(mut bytes: &[u8]) -> std::io::Result<T> {
+    T::binprot_read(&mut bytes)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))
+}
+
+fn decode_index(bytes: &[u8]) -> std::io::Result<AccountIndex> {
+    let bytes = bytes
+        .try_into()
+        .map_err(|_| std::io::Error::from(std::io::ErrorKind::InvalidData))?;
+    Ok(AccountIndex(u64::from_be_bytes(bytes)))
+}
+
+impl OndiskDatabaseImpl {
+    /// Opens the ledger stored in `directory`, creating an empty one if
+    /// there is none.
+    pub fn open(depth: u8, directory: PathBuf) -> std::io::Result<Self> {
+        assert!((1..0xfe).contains(&depth));
+
+        let mut db = ondisk::Database::create(&directory)?;
+        // Hashes are overwritten on every change of the ledger
+        db.set_compaction_config(Some(ondisk::CompactionConfig::default()));
+
+        let mut this = Self {
+            db: RefCell::new(db),
+            depth,
+            last_location: None,
+            naccounts: 0,
+            hashes_matrix: HashesMatrix::new(depth as usize),
+            uuid: next_uuid(),
+            directory,
+            io_error: RefCell::new(None),
+        };
+        match this.db.get_mut().get(META_KEY)? {
+            Some(meta) => this.read_meta(&meta)?,
+            None => {
+                let mut batch = Batch::new();
+                batch.set(META_KEY.into(), this.meta());
+                this.db.get_mut().run_batch(&mut batch)?;
+            }
+        }
+
+        Ok(this)
+    }
+
+    fn meta(&self) -> Box<[u8]> {
+        let last_index = self
+            .last_location
+            .as_ref()
+            .map_or(u64::MAX, |addr| addr.to_index().as_u64());
+        [
+            &[self.depth][..],
+            &(self.naccounts as u64).to_le_bytes(),
+            &last_index.to_le_bytes(),
+        ]
+        .concat()
+        .into()
+    }
+
+    fn read_meta(&mut self, meta: &[u8]) -> std::io::Result<()> {
+        use std::io::{Error, ErrorKind::InvalidData};
+
+        if meta.len() != META_NBYTES {
+            return Err(InvalidData.into());
+        }
+        let (depth, rest) = meta.split_at(1);
+        let (naccounts, last_index) = rest.split_at(8);
+
+        if depth[0] != self.depth {
+            return Err(Error::new(
+                InvalidData,
+                format!(
+                    "ledger depth mismatch, expected: {}, found: {}",
+                    self.depth, depth[0]
+                ),
+            ));
+        }
+        let naccounts = u64::from_le_bytes(naccounts.try_into().map_err(|_| InvalidData)?);
+        let last_index = u64::from_le_bytes(last_index.try_into().map_err(|_| InvalidData)?);
+
+        self.naccounts = naccounts as usize;
+        self.last_location = (last_index != u64::MAX)
+            .then(|| Address::from_index(AccountIndex(last_index), self.depth as usize));
+        Ok(())
+    }
+
+    /// Returns the first I/O error since the previous call.
+    pub fn take_io_error(&self) -> Option<std::io::Error> {
+        self.io_error.borrow_mut().take()
+    }
+
+    fn record_io_error(&self, error: std::io::Error) {
+        let mut io_error = self.io_error.borrow_mut();
+        if io_error.is_none() {
+            *io_error = Some(std::io::Error::new(
+                error.kind(),
+                format!("ondisk ledger {:?}: {error}", self.directory),
+            ));
+        }
+    }
+
+    fn read<T>(
+        &self,
+        key: &[u8],
+        decode: impl FnOnce(&[u8]) -> std::io::Result<T>,
+    ) -> std::io::Result<Option<T>> {
+        self.db
+            .borrow_mut()
+            .get(key)
+            .and_then(|value| value.map(|value| decode(&value)).transpose())
+    }
+
+    /// Reads an account entry, see the module documentation on why
+    /// failures panic.
+    fn read_account_entry<T>(
+        &self,
+        key: &[u8],
+        decode: impl FnOnce(&[u8]) -> std::io::Result<T>,
+    ) -> Option<T> {
+        self.read(key, decode).unwrap_or_else(|e| {
+            panic!(
+                "ondisk ledger {:?}: failed to read account entry: {e}",
+                self.directory
+            )
+        })
+    }
+
+    /// Writes `batch` along with the ledger metadata.
+    fn write(&mut self, mut batch: Batch) -> std::io::Result<()> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        batch.set(META_KEY.into(), self.meta());
+        self.db.get_mut().run_batch(&mut batch).map_err(|e| {
+            let kind = e.kind();
+            self.record_io_error(e);
+            std::io::Error::new(kind, "write failed")
+        })
+    }
+
+    /// Writes `batch` of account changes, restoring the metadata to
+    /// `prev` when it fails.
+    fn write_accounts(
+        &mut self,
+        batch: Batch,
+        prev: (usize, Option<Address>),
+    ) -> std::io::Result<()> {
+        let result = self.write(batch);
+        if result.is_err() {
+            (self.naccounts, self.last_location) = prev;
+        }
+        result
+    }
+
+    fn meta_snapshot(&self) -> (usize, Option<Address>) {
+        (self.naccounts, self.last_location.clone())
+    }
+
+    fn get_hash(&mut self, addr: &Address) -> Option<Fp> {
+        if let Some(hash) = self.hashes_matrix.get(addr) {
+            return Some(*hash);
+        }
+        let hash: BigInt = match self.read(&hash_key(addr), decode) {
+            Ok(hash) => hash?,
+            Err(e) => {
+                self.record_io_error(e);
+                return None;
+            }
+        };
+        match hash.to_field() {
+            Ok(hash) => {
+                self.hashes_matrix.set(addr, hash);
+                Some(hash)
+            }
+            Err(_) => {
+                // It gets recomputed and overwritten
+                self.record_io_error(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid hash at {addr:?}"),
+                ));
+                None
+            }
+        }
+    }
+
+    fn set_hash(&mut self, addr: &Address, hash: Fp, batch: &mut Batch) {
+        self.hashes_matrix.remove(addr);
+        self.hashes_matrix.set(addr, hash);
+        batch.set(hash_key(addr), encode(&BigInt::from(hash)));
+    }
+
+    /// Removes hashes on the path from the account to the root.
+    fn invalidate_hashes_impl(&mut self, account_index: AccountIndex, batch: &mut Batch) {
+        self.hashes_matrix.invalidate_hashes(account_index);
+
+        let mut addr = Address::from_index(account_index, self.depth as usize);
+        loop {
+            let key = hash_key(&addr);
+            if self.db.get_mut().contains_key(&key) {
+                batch.remove(key);
+            }
+            addr = match addr.parent() {
+                Some(addr) => addr,
+                None => break,
+            }
+        }
+    }
+
+    fn account_hash(&mut self, account_index: AccountIndex, batch: &mut Batch) -> Option<Fp> {
+        let addr = Address::from_index(account_index, self.depth as usize);
+
+        if let Some(hash) = self.get_hash(&addr) {
+            return Some(hash);
+        }
+
+        let hash = self.get(addr.clone())?.hash();
+        self.set_hash(&addr, hash, batch);
+
+        Some(hash)
+    }
+
+    fn set_impl(&mut self, addr: Address, account: Box<Account>, batch: &mut Batch) {
+        let index = addr.to_index();
+
+        self.invalidate_hashes_impl(index, batch);
+
+        match self.get(addr.clone()) {
+            Some(prev) => batch.remove(account_id_key(&prev.id())),
+            None => self.naccounts += 1,
+        }
+
+        batch.set(
+            account_id_key(&account.id()),
+            index.as_u64().to_be_bytes().into(),
+        );
+        batch.set(account_key(index), encode(&*account));
+
+        if self
+            .last_location
+            .as_ref()
+            .map(|l| l.to_index() < addr.to_index())
+            .unwrap_or(true)
+        {
+            self.last_location = Some(addr);
+        }
+    }
+
+    fn emulate_tree_to_get_hash_at(&mut self, addr: Address) -> Fp {
+        if let Some(hash) = self.get_hash(&addr) {
+            return hash;
+        };
+
+        let last_account = self
+            .last_filled()
+            .unwrap_or_else(|| Address::first(self.depth as usize));
+
+        self.emulate_tree_recursive(addr, &last_account)
+    }
+
+    pub fn emulate_tree_recursive(&mut self, addr: Address, last_account: &Address) -> Fp {
+        let mut batch = Batch::new();
+        let hash = self.emulate_tree_recursive_impl(addr, last_account, &mut batch);
+        // Hashes are kept in memory even if they couldn't be written
+        let _ = self.write(batch);
+        hash
+    }
+
+    fn emulate_tree_recursive_impl(
+        &mut self,
+        addr: Address,
+        last_account: &Address,
+        batch: &mut Batch,
+    ) -> Fp {
+        let tree_depth = self.depth as usize;
+        let current_depth = tree_depth - addr.length();
+
+        if current_depth == 0 {
+            return self
+                .account_hash(addr.to_index(), batch)
+                .unwrap_or_else(|| self.hashes_matrix.empty_hash_at_height(0));
+        }
+
+        let mut get_child_hash = |addr: Address, batch: &mut Batch| {
+            if let Some(hash) = self.get_hash(&addr) {
+                hash
+            } else if addr.is_before(last_account) {
+                self.emulate_tree_recursive_impl(addr, last_account, batch)
+            } else {
+                self.hashes_matrix.empty_hash_at_height(current_depth - 1)
+            }
+        };
+
+        let left_hash = get_child_hash(addr.child_left(), batch);
+        let right_hash = get_child_hash(addr.child_right(), batch);
+
+        match self.get_hash(&addr) {
+            Some(hash) => hash,
+            None => {
+                let hash = V2::hash_node(current_depth - 1, left_hash, right_hash);
+                self.set_hash(&addr, hash, batch);
+                hash
+            }
+        }
+    }
+
+    pub fn emulate_tree_to_get_path(
+        &mut self,
+        addr: Address,
+        last_account: &Address,
+        path: &mut AddressIterator,
+        merkle_path: &mut Vec<MerklePath>,
+    ) -> Fp {
+        let mut batch = Batch::new();
+        let hash =
+            self.emulate_tree_to_get_path_impl(addr, last_account, path, merkle_path, &mut batch);
+        let _ = self.write(batch);
+        hash
+    }
+
+    fn emulate_tree_to_get_path_impl(
+        &mut self,
+        addr: Address,
+        last_account: &Address,
+        path: &mut AddressIterator,
+        merkle_path: &mut Vec<MerklePath>,
+        batch: &mut Batch,
+    ) -> Fp {
+        let tree_depth = self.depth as usize;
+
+        if addr.length() == tree_depth {
+            return self
+                .account_hash(addr.to_index(), batch)
+                .unwrap_or_else(|| self.hashes_matrix.empty_hash_at_height(0));
+        }
+
+        let next_direction = path.next();
+
+        // We go until the end of the path
+        if let Some(direction) = next_direction.as_ref() {
+            let child = match direction {
+                Direction::Left => addr.child_left(),
+                Direction::Right => addr.child_right(),
+            };
+            self.emulate_tree_to_get_path_impl(child, last_account, path, merkle_path, batch);
+        };
+
+        let depth_in_tree = tree_depth - addr.length();
+
+        let mut get_child_hash = |addr: Address, batch: &mut Batch| match self.get_hash(&addr) {
+            Some(hash) => hash,
+            None => {
+                if addr.is_before(last_account) {
+                    self.emulate_tree_to_get_path_impl(addr, last_account, path, merkle_path, batch)
+                } else {
+                    self.hashes_matrix.empty_hash_at_height(depth_in_tree - 1)
+                }
+            }
+        };
+
+        let left = get_child_hash(addr.child_left(), batch);
+        let right = get_child_hash(addr.child_right(), batch);
+
+        if let Some(direction) = next_direction {
+            let hash = match direction {
+                Direction::Left => MerklePath::Left(right),
+                Direction::Right => MerklePath::Right(left),
+            };
+            merkle_path.push(hash);
+        };
+
+        match self.get_hash(&addr) {
+            Some(hash) => hash,
+            None => {
+                let hash = V2::hash_node(depth_in_tree - 1, left, right);
+                self.set_hash(&addr, hash, batch);
+                hash
+            }
+        }
+    }
+
+    /// Copies the database to `directory`.
+    pub fn make_checkpoint(&self, directory_name: String) {
+        let result = self.db.borrow_mut().make_checkpoint(&directory_name);
+        if let Err(e) = result {
+            self.record_io_error(std::io::Error::new(
+                e.kind(),
+                format!("checkpoint to {directory_name:?} failed: {e}"),
+            ));
+        }
+    }
+
+    pub fn create_checkpoint(&self, directory_name: String) {
+        self.make_checkpoint(directory_name)
+    }
+
+    /// Copies the database to `new_directory`. When it is the directory of
+    /// this database, the copy is made in a sibling directory instead.
+    ///
+    /// # Panics
+    ///
+    /// When the copy can't be made, use [`Self::try_clone_db`] to handle it.
+    pub fn clone_db(&self, new_directory: PathBuf) -> Self {
+        self.try_clone_db(new_directory)
+            .unwrap_or_else(|e| panic!("ondisk ledger {:?}: {e}", self.directory))
+    }
+
+    pub fn try_clone_db(&self, new_directory: PathBuf) -> std::io::Result<Self> {
+        let uuid = next_uuid();
+        let directory = if new_directory == self.directory {
+            sibling_directory(&self.directory, &uuid)
+        } else {
+            new_directory
+        };
+
+        let db = self
+            .db
+            .borrow_mut()
+            .create_checkpoint(&directory)
+            .map_err(|e| {
+                std::io::Error::new(e.kind(), format!("copy to {directory:?} failed: {e}"))
+            })?;
+
+        Ok(Self {
+            db: RefCell::new(db),
+            depth: self.depth,
+            last_location: self.last_location.clone(),
+            naccounts: self.naccounts,
+            hashes_matrix: self.hashes_matrix.clone(),
+            uuid,
+            directory,
+            io_error: RefCell::new(None),
+        })
+    }
+
+    pub fn get_cached_hash(&self, addr: &Address) -> Option<Fp> {
+        self.hashes_matrix.get(addr).copied()
+    }
+
+    pub fn set_cached_hash(&mut self, addr: &Address, hash: Fp) {
+        let mut batch = Batch::new();
+        self.set_hash(addr, hash, &mut batch);
+        let _ = self.write(batch);
+    }
+
+    pub fn empty_hash_at_height(&mut self, height: usize) -> Fp {
+        self.hashes_matrix.empty_hash_at_height(height)
+    }
+
+    pub fn invalidate_hashes(&mut self, account_index: AccountIndex) {
+        let mut batch = Batch::new();
+        self.invalidate_hashes_impl(account_index, &mut batch);
+        let _ = self.write(batch);
+    }
+
+    pub fn transfert_hashes(&mut self, hashes: HashesMatrix) {
+        self.set_raw_inner_hashes(hashes.get_raw_inner_hashes())
+    }
+
+    /// Returns hashes cached in memory, hashes only stored on disk are
+    /// not included.
+    pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
+        self.hashes_matrix.get_raw_inner_hashes()
+    }
+
+    pub fn set_raw_inner_hashes(&mut self, hashes: Vec<(u64, Fp)>) {
+        let mut batch = Batch::new();
+        for (index, hash) in hashes {
+            let addr = Address::from_linear_index(index);
+            self.set_hash(&addr, hash, &mut batch);
+        }
+        let _ = self.write(batch);
+    }
+
+    pub fn root_hash(&mut self) -> Fp {
+        self.emulate_tree_to_get_hash_at(Address::root())
+    }
+
+    pub fn naccounts(&self) -> usize {
+        self.naccounts
+    }
+
+    #[cfg(test)]
+    pub fn test_matrix(&self) -> HashesMatrix {
+        self.hashes_matrix.clone()
+    }
+}
+
+fn sibling_directory(directory: &Path, uuid: &str) -> PathBuf {
+    let mut name = directory.file_name().unwrap_or_default().to_os_string();
+    name.push(format!("-{uuid}"));
+    directory.with_file_name(name)
+}
+
+impl BaseLedger for OndiskDatabaseImpl {
+    fn to_list(&self) -> Vec<Account> {
+        self.fold(
+            Vec::with_capacity(self.naccounts),
+            |mut accounts, account| {
+                accounts.push(account.clone());
+                accounts
+            },
+        )
+    }
+
+    fn iter<F>(&self, mut fun: F)
+    where
+        F: FnMut(&Account),
+    {
+        self.fold((), |(), account| fun(account))
+    }
+
+    fn fold<B, F>(&self, init: B, mut fun: F) -> B
+    where
+        F: FnMut(B, &Account) -> B,
+    {
+        self.fold_until(init, |accum, account| {
+            ControlFlow::Continue(fun(accum, account))
+        })
+    }
+
+    fn fold_with_ignored_accounts<B, F>(
+        &self,
+        ignoreds: HashSet<AccountId>,
+        init: B,
+        mut fun: F,
+    ) -> B
+    where
+        F: FnMut(B, &Account) -> B,
+    {
+        self.fold(init, |accum, account| {
+            if ignoreds.contains(&account.id()) {
+                accum
+            } else {
+                fun(accum, account)
+            }
+        })
+    }
+
+    fn fold_until<B, F>(&self, init: B, mut fun: F) -> B
+    where
+        F: FnMut(B, &Account) -> ControlFlow<B, B>,
+    {
+        let Some(last) = self.last_location.as_ref() else {
+            return init;
+        };
+
+        let mut accum = init;
+        for index in 0..=last.to_index().as_u64() {
+            let Some(account) = self.get_at_index(AccountIndex(index)) else {
+                continue;
+            };
+            match fun(accum, &account) {
+                ControlFlow::Continue(v) => accum = v,
+                ControlFlow::Break(v) => return v,
+            }
+        }
+        accum
+    }
+
+    fn accounts(&self) -> HashSet<AccountId> {
+        self.fold(
+            HashSet::with_capacity(self.naccounts),
+            |mut set, account| {
+                set.insert(account.id());
+                set
+            },
+        )
+    }
+
+    fn tokens(&self, public_key: CompressedPubKey) -> HashSet<TokenId> {
+        self.fold(HashSet::with_capacity(100), |mut set, account| {
+            if account.public_key == public_key {
+                set.insert(account.token_id.clone());
+            }
+            set
+        })
+    }
+
+    fn location_of_account(&self, account_id: &AccountId) -> Option<Address> {
+        self.index_of_account(account_id.clone())
+            .map(|index| Address::from_index(index, self.depth as usize))
+    }
+
+    fn location_of_account_batch(
+        &self,
+        account_ids: &[AccountId],
+    ) -> Vec<(AccountId, Option<Address>)> {
+        account_ids
+            .iter()
+            .map(|account_id| (account_id.clone(), self.location_of_account(account_id)))
+            .collect()
+    }
+
+    fn get_or_create_account(
+        &mut self,
+        account_id: AccountId,
+        account: Account,
+    ) -> Result<GetOrCreated, DatabaseError> {
+        if let Some(addr) = self.location_of_account(&account_id) {
+            return Ok(GetOrCreated::Existed(addr));
+        }
+
+        let location = match self.last_location.as_ref() {
+            Some(last) => last.next().ok_or(DatabaseError::OutOfLeaves)?,
+            None => Address::first(self.depth as usize),
+        };
+
+        let prev = self.meta_snapshot();
+        let mut batch = Batch::new();
+        self.set_impl(location.clone(), Box::new(account), &mut batch);
+        self.write_accounts(batch, prev)
+            .map_err(|e| DatabaseError::Io(e.to_string()))?;
+
+        Ok(GetOrCreated::Added(location))
+    }
+
+    fn close(&self) {
+        // Drop
+    }
+
+    fn last_filled(&self) -> Option<Address> {
+        self.last_location.clone()
+    }
+
+    fn get_uuid(&self) -> Uuid {
+        self.uuid.clone()
+    }
+
+    fn get_directory(&self) -> Option<PathBuf> {
+        Some(self.directory.clone())
+    }
+
+    fn get_account_hash(&mut self, account_index: AccountIndex) -> Option<Fp> {
+        let mut batch = Batch::new();
+        let hash = self.account_hash(account_index, &mut batch);
+        let _ = self.write(batch);
+        hash
+    }
+
+    fn get(&self, addr: Address) -> Option<Box<Account>> {
+        self.read_account_entry(&account_key(addr.to_index()), decode)
+            .map(Box::new)
+    }
+
+    fn get_batch(&self, addr: &[Address]) -> Vec<(Address, Option<Box<Account>>)> {
+        addr.iter()
+            .map(|addr| (addr.clone(), self.get(addr.clone())))
+            .collect()
+    }
+
+    fn set(&mut self, addr: Address, account: Box<Account>) {
+        let prev = self.meta_snapshot();
+        let mut batch = Batch::new();
+        self.set_impl(addr, account, &mut batch);
+        let _ = self.write_accounts(batch, prev);
+    }
+
+    fn set_batch(&mut self, list: &[(Address, Box<Account>)]) {
+        // Accounts of the batch aren't readable until it is written, so
+        // only the last account set at an address is kept.
+        let list = list
+            .iter()
+            .map(|(addr, account)| (addr.to_index(), (addr, account)))
+            .collect::<BTreeMap<_, _>>();
+
+        let prev = self.meta_snapshot();
+        let mut batch = Batch::new();
+        for (addr, account) in list.into_values() {
+            assert_eq!(addr.length(), self.depth as usize, "addr={:?}", addr);
+            self.set_impl(addr.clone(), account.clone(), &mut batch);
+        }
+        let _ = self.write_accounts(batch, prev);
+    }
+
+    fn get_at_index(&self, index: AccountIndex) -> Option<Box<Account>> {
+        let addr = Address::from_index(index, self.depth as usize);
+        self.get(addr)
+    }
+
+    fn set_at_index(&mut self, index: AccountIndex, account: Box<Account>) -> Result<(), ()> {
+        let addr = Address::from_index(index, self.depth as usize);
+        let prev = self.meta_snapshot();
+        let mut batch = Batch::new();
+        self.set_impl(addr, account, &mut batch);
+        self.write_accounts(batch, prev).map_err(|_| ())
+    }
+
+    fn index_of_account(&self, account_id: AccountId) -> Option<AccountIndex> {
+        self.read_account_entry(&account_id_key(&account_id), decode_index)
+    }
+
+    fn merkle_root(&mut self) -> Fp {
+        self.root_hash()
+    }
+
+    fn merkle_path(&mut self, addr: Address) -> Vec<MerklePath> {
+        let mut merkle_path = Vec::with_capacity(addr.length());
+        let mut path = addr.into_iter();
+        let addr = Address::root();
+
+        let last_account = self
+            .last_filled()
+            .unwrap_or_else(|| Address::first(self.depth as usize));
+
+        self.emulate_tree_to_get_path(addr, &last_account, &mut path, &mut merkle_path);
+
+        merkle_path
+    }
+
+    fn merkle_path_at_index(&mut self, index: AccountIndex) -> Vec<MerklePath> {
+        let addr = Address::from_index(index, self.depth as usize);
+        self.merkle_path(addr)
+    }
+
+    fn remove_accounts(&mut self, ids: &[AccountId]) {
+        let mut addrs = ids
+            .iter()
+            .map(|account_id| self.location_of_account(account_id).unwrap())
+            .collect::<Vec<_>>();
+        addrs.sort_by_key(Address::to_index);
+
+        let prev = self.meta_snapshot();
+        let mut batch = Batch::new();
+        for addr in addrs.iter().rev() {
+            let account_index = addr.to_index();
+            self.invalidate_hashes_impl(account_index, &mut batch);
+
+            let account = match self.get(addr.clone()) {
+                Some(account) => account,
+                None => continue,
+            };
+
+            batch.remove(account_id_key(&account.id()));
+            batch.remove(account_key(account_index));
+
+            self.naccounts = self
+                .naccounts
+                .checked_sub(1)
+                .expect("invalid naccounts counter");
+
+            if self
+                .last_location
+                .as_ref()
+                .map(|last| last == addr)
+                .unwrap_or(false)
+            {
+                self.last_location = addr.prev();
+            }
+        }
+        let _ = self.write_accounts(batch, prev);
+    }
+
+    fn detached_signal(&mut self) {
+        // no-op, nothing is kept for the masks on top of this database
+    }
+
+    fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    fn num_accounts(&self) -> usize {
+        self.naccounts
+    }
+
+    fn merkle_path_at_addr(&mut self, addr: Address) -> Vec<MerklePath> {
+        self.merkle_path(addr)
+    }
+
+    fn get_inner_hash_at_addr(&mut self, addr: Address) -> Result<Fp, String> {
+        Ok(self.emulate_tree_to_get_hash_at(addr))
+    }
+
+    fn set_inner_hash_at_addr(&mut self, _addr: Address, _hash: Fp) -> Result<(), ()> {
+        // No-op, hashes are computed from accounts
+        Ok(())
+    }
+
+    fn set_all_accounts_rooted_at(
+        &mut self,
+        addr: Address,
+        accounts: &[Box<Account>],
+    ) -> Result<(), ()> {
+        if addr.length() > self.depth as usize {
+            return Err(());
+        }
+
+        let list = addr
+            .iter_children(self.depth as usize)
+            .zip(accounts.iter().cloned())
+            .collect::<Vec<_>>();
+        self.set_batch(&list);
+
+        Ok(())
+    }
+
+    fn get_all_accounts_rooted_at(&self, addr: Address) -> Option<Vec<(Address, Box<Account>)>> {
+        if addr.length() > self.depth as usize {
+            return None;
+        }
+
+        let accounts = addr
+            .iter_children(self.depth as usize)
+            .filter_map(|child_addr| Some((child_addr.clone(), self.get(child_addr)?)))
+            .collect::<Vec<_>>();
+
+        if accounts.is_empty() {
+            None
+        } else {
+            Some(accounts)
+        }
+    }
+
+    fn make_space_for(&mut self, _space: usize) {
+        // No op, the file grows as needed
+    }
+
+    fn commit(&mut self) {
+        // no-op, every change is written right away
+    }
+}
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use crate::Database;
+
+    use super::*;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new() -> Self {
+            Self(std::env::temp_dir().join(format!("ondisk-ledger-{}", next_uuid())))
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_same_root_hash_as_in_memory() {
+        const DEPTH: u8 = 10;
+
+        let dir = TempDir::new();
+        let mut ondisk = OndiskDatabaseImpl::open(DEPTH, dir.0.clone()).unwrap();
+        let mut memory = Database::create(DEPTH);
+        assert_eq!(ondisk.merkle_root(), memory.merkle_root());
+
+        let accounts = (0..100).map(|_| Account::rand()).collect::<Vec<_>>();
+        for account in &accounts {
+            ondisk
+                .get_or_create_account(account.id(), account.clone())
+                .unwrap();
+            memory
+                .get_or_create_account(account.id(), account.clone())
+                .unwrap();
+        }
+        assert_eq!(ondisk.merkle_root(), memory.merkle_root());
+        assert_eq!(
+            ondisk.merkle_path_at_index(AccountIndex(42)),
+            memory.merkle_path_at_index(AccountIndex(42))
+        );
+
+        let removed = accounts[90..].iter().map(Account::id).collect::<Vec<_>>();
+        ondisk.remove_accounts(&removed);
+        memory.remove_accounts(&removed);
+        assert_eq!(ondisk.num_accounts(), 90);
+        assert_eq!(ondisk.merkle_root(), memory.merkle_root());
+    }
+
+    #[test]
+    fn test_reopen() {
+        const DEPTH: u8 = 10;
+
+        let dir = TempDir::new();
+        let accounts = (0..50).map(|_| Account::rand()).collect::<Vec<_>>();
+
+        let root_hash = {
+            let mut db = OndiskDatabaseImpl::open(DEPTH, dir.0.clone()).unwrap();
+            for account in &accounts {
+                db.get_or_create_account(account.id(), account.clone())
+                    .unwrap();
+            }
+            let root_hash = db.merkle_root();
+
+            // Hashes invalidated by this change must not be picked up on reopen
+            let mut account = accounts[7].clone();
+            account.nonce = account.nonce.incr();
+            db.set_at_index(AccountIndex(7), Box::new(account)).unwrap();
+            db.merkle_root();
+
+            db.set_at_index(AccountIndex(7), Box::new(accounts[7].clone()))
+                .unwrap();
+            root_hash
+        };
+
+        assert!(OndiskDatabaseImpl::open(DEPTH + 1, dir.0.clone()).is_err());
+
+        let mut db = OndiskDatabaseImpl::open(DEPTH, dir.0.clone()).unwrap();
+        assert_eq!(db.num_accounts(), accounts.len());
+        assert_eq!(db.merkle_root(), root_hash);
+        assert_eq!(
+            db.location_of_account(&accounts[3].id()),
+            Some(Address::from_index(AccountIndex(3), DEPTH as usize))
+        );
+        assert_eq!(db.to_list(), accounts);
+    }
+
+    #[test]
+    fn test_invalid_entries_are_reported() {
+        const DEPTH: u8 = 10;
+
+        let dir = TempDir::new();
+        let accounts = (0..10).map(|_| Account::rand()).collect::<Vec<_>>();
+
+        let root_hash = {
+            let mut db = OndiskDatabaseImpl::open(DEPTH, dir.0.clone()).unwrap();
+            for account in &accounts {
+                db.get_or_create_account(account.id(), account.clone())
+                    .unwrap();
+            }
+            let root_hash = db.merkle_root();
+
+            let mut batch = Batch::new();
+            batch.set(hash_key(&Address::root()), [0xff; 8].into());
+            db.db.get_mut().run_batch(&mut batch).unwrap();
+            root_hash
+        };
+
+        let mut db = OndiskDatabaseImpl::open(DEPTH, dir.0.clone()).unwrap();
+        assert!(db.take_io_error().is_none());
+
+        // The invalid root hash is recomputed
+        assert_eq!(db.merkle_root(), root_hash);
+        let error = db.take_io_error().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(db.take_io_error().is_none());
+        assert_eq!(
+            db.get_at_index(AccountIndex(4)),
+            Some(Box::new(accounts[4].clone()))
+        );
+        assert!(db.take_io_error().is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to read account entry")]
+    fn test_invalid_account_is_fatal() {
+        const DEPTH: u8 = 10;
+
+        let dir = TempDir::new();
+        let mut db = OndiskDatabaseImpl::open(DEPTH, dir.0.clone()).unwrap();
+        for _ in 0..5 {
+            let account = Account::rand();
+            db.get_or_create_account(account.id(), account).unwrap();
+        }
+
+        let mut batch = Batch::new();
+        batch.set(account_key(AccountIndex(3)), [0xff; 8].into());
+        db.db.get_mut().run_batch(&mut batch).unwrap();
+
+        db.get_at_index(AccountIndex(3));
+    }
+}
@@ -745,6 +745,8 @@ ocaml_export! {
             },
             Err(e) => match e {
                 OutOfLeaves => Err(DatabaseErrorFFI::OutOfLeaves),
+                // Ledgers created through ffi are kept in memory
+                Io(e) => unreachable!("{e}"),
             },
         };
 
@@ -183,6 +183,12 @@ impl Mask {
         self.with(|this| this.get_parent())
     }
 
+    /// Returns the first I/O error of the on-disk database at the root of
+    /// this mask, see [`Database::take_io_error`].
+    pub fn take_io_error(&self) -> Option<std::io::Error> {
+        self.with(|this| this.take_io_error())
+    }
+
     pub fn unset_parent(&self, trigger_detach_signal: bool) {
         self.with(|this| this.unset_parent(trigger_detach_signal))
     }
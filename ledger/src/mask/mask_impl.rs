@@ -189,6 +189,14 @@ impl MaskImpl {
         }
     }
 
+    pub fn take_io_error(&self) -> Option<std::io::Error> {
+        match self {
+            Root { database, .. } => database.take_io_error(),
+            Attached { parent, .. } => parent.take_io_error(),
+            Unattached { .. } => None,
+        }
+    }
+
     pub(super) fn any_child_alive(&self) -> bool {
         let childs = match self {
             Root { childs, .. } => childs,
@@ -564,7 +572,7 @@ impl MaskImpl {
     pub fn compute_hash_or_parent(&mut self, addr: Address, last_account: &Address) -> Fp {
         let (matrix, own, parent) = match self {
             Root { database, .. } => {
-                return database.with(|db| db.emulate_tree_recursive(addr, last_account));
+                return database.emulate_tree_recursive(addr, last_account);
             }
             Attached {
                 hashes,
@@ -605,8 +613,7 @@ impl MaskImpl {
     ) -> Fp {
         let (matrix, own, parent) = match self {
             Root { database, .. } => {
-                return database
-                    .with(|db| db.emulate_tree_to_get_path(addr, last_account, path, merkle_path));
+                return database.emulate_tree_to_get_path(addr, last_account, path, merkle_path);
             }
             Attached {
                 hashes,
@@ -950,9 +957,7 @@ impl MaskImpl {
 
     pub fn get_raw_inner_hashes(&self) -> Vec<(u64, Fp)> {
         match self {
-            Root { database, .. } => {
-                database.with(|this| this.hashes_matrix.get_raw_inner_hashes())
-            }
+            Root { database, .. } => database.get_raw_inner_hashes(),
             Attached { hashes, .. } => hashes.clone().get_raw_inner_hashes(),
             Unattached { hashes, .. } => hashes.clone().get_raw_inner_hashes(),
         }
@@ -960,9 +965,7 @@ impl MaskImpl {
 
     pub fn set_raw_inner_hashes(&self, raw_hashes: Vec<(u64, Fp)>) {
         match self {
-            Root { database, .. } => {
-                database.with(|this| this.hashes_matrix.set_raw_inner_hashes(raw_hashes))
-            }
+            Root { database, .. } => database.set_raw_inner_hashes(raw_hashes),
             Attached { hashes, .. } => hashes.clone().set_raw_inner_hashes(raw_hashes),
             Unattached { hashes, .. } => hashes.clone().set_raw_inner_hashes(raw_hashes),
         }
@@ -30,6 +30,10 @@ impl Batch {
         self.actions.push(Action::Remove(key));
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
     pub(super) fn take(&mut self) -> Vec<Action> {
         std::mem::take(&mut self.actions)
     }
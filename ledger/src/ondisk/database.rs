@@ -25,15 +25,26 @@ const IS_REMOVED_BIT: u8 = 1 << 2;
 
 const BUFFER_DEFAULT_CAPACITY: usize = 4096;
 
-const DATABASE_VERSION: u64 = 1;
+/// Version 2 added the checksum of the entry header
+const DATABASE_VERSION: u64 = 2;
 const DATABASE_VERSION_NBYTES: usize = 8;
 
+const FILENAME: &str = "db";
+const TMP_FILENAME: &str = "db_tmp";
+
 pub struct Database {
     uuid: Uuid,
-    /// Index of keys to their values offset
-    index: HashMap<Key, Offset>,
+    /// Index of keys to their entries
+    index: HashMap<Key, IndexEntry>,
     /// Points to end of file
     current_file_offset: Offset,
+    /// Sum of the lengths of entries in `index`, everything else in the
+    /// file is garbage
+    live_bytes: u64,
+    /// Number of bytes of a torn entry removed at the end of the file when
+    /// it was reloaded
+    truncated_bytes: u64,
+    compaction: Option<CompactionConfig>,
     file: BufWriter<LockedFile>,
     /// Read buffer
     buffer: Vec<u8>,
@@ -41,6 +52,33 @@ pub struct Database {
     filename: PathBuf,
 }
 
+/// Location of a live entry in the file
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    header_offset: Offset,
+    /// Length of the entry, including its header
+    length: u64,
+}
+
+/// When [`Database`] compacts its file automatically, after a write
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompactionConfig {
+    /// The file is not compacted while it is smaller than this
+    pub min_file_size: u64,
+    /// The file is compacted when the ratio of its bytes that are not live
+    /// entries (overwritten or removed entries) reaches this value
+    pub min_garbage_ratio: f64,
+}
+
+impl Default for CompactionConfig {
+    fn default() -> Self {
+        Self {
+            min_file_size: 64 * 1024 * 1024, // 64 MB
+            min_garbage_ratio: 0.5,
+        }
+    }
+}
+
 /// Compute crc32 of an entry
 ///
 /// This is used to verify data corruption
@@ -77,7 +115,10 @@ struct EntryHeader {
 
 impl EntryHeader {
     /// Number of bytes the `EntryHeader` occupies on disk
-    pub const NBYTES: usize = 17;
+    pub const NBYTES: usize = 21;
+
+    /// Number of bytes covered by the header checksum
+    const CHECKSUMMED_NBYTES: usize = 17;
 
     /// Returns key + value length
     fn entry_length(&self) -> std::io::Result<u64> {
@@ -110,6 +151,9 @@ impl EntryHeader {
         bytes.write_all(&[bitflags])?;
         bytes.write_all(&self.crc32.to_le_bytes())?;
 
+        let header_crc32 = crc32fast::hash(&bytes.get_ref()[..Self::CHECKSUMMED_NBYTES]);
+        bytes.write_all(&header_crc32.to_le_bytes())?;
+
         Ok(bytes.into_inner())
     }
 
@@ -154,12 +198,18 @@ impl EntryHeader {
 
     /// Reads a header from a slice of bytes
     ///
-    /// Returns an error when the slice is too small
+    /// Returns an error when the slice is too small or the header checksum
+    /// doesn't match
     fn read(bytes: &[u8]) -> std::io::Result<Self> {
         if bytes.len() < Self::NBYTES {
             return Err(UnexpectedEof.into());
         }
 
+        let header_crc32 = read_u32(&bytes[Self::CHECKSUMMED_NBYTES..])?;
+        if crc32fast::hash(&bytes[..Self::CHECKSUMMED_NBYTES]) != header_crc32 {
+            return Err(InvalidData.into());
+        }
+
         let key_length = read_u32(bytes)?;
         let value_length = read_u64(&bytes[4..])?;
         let bitflags = read_u8(&bytes[12..])?;
@@ -275,8 +325,8 @@ impl Database {
         let directory = directory.as_ref();
 
         let filename = directory.join(match mode {
-            CreateMode::Regular => "db",
-            CreateMode::Temporary => "db_tmp",
+            CreateMode::Regular => FILENAME,
+            CreateMode::Temporary => TMP_FILENAME,
         });
 
         if filename.try_exists()? {
@@ -306,6 +356,9 @@ impl Database {
             uuid: next_uuid(),
             index: HashMap::with_capacity(128),
             current_file_offset: DATABASE_VERSION_NBYTES as u64,
+            live_bytes: 0,
+            truncated_bytes: 0,
+            compaction: None,
             file: BufWriter::with_capacity(4 * 1024 * 1024, file), // 4 MB
             buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
             filename,
@@ -313,6 +366,13 @@ impl Database {
     }
 
     /// Reload the database at the specified path
+    ///
+    /// An entry at the end of the file which was not completely written (the
+    /// process was killed in the middle of a write) is truncated. A corrupted
+    /// entry anywhere else is an error.
+    ///
+    /// The header is checked first, so its length is trusted: an entry
+    /// reaching past the end of file can only be the last one.
     fn reload(filename: PathBuf) -> std::io::Result<Self> {
         use std::io::Read;
 
@@ -343,46 +403,87 @@ impl Database {
             current_offset += DATABASE_VERSION_NBYTES as u64;
         }
 
-        let mut index = HashMap::with_capacity(256);
+        let mut index: HashMap<Key, IndexEntry> = HashMap::with_capacity(256);
+        let mut live_bytes: u64 = 0;
 
         while current_offset < eof {
             let header_offset = current_offset;
+            let remaining = eof - current_offset;
+
+            if remaining < EntryHeader::NBYTES as u64 {
+                break; // Torn header
+            }
 
             ensure_buffer_length(&mut bytes, EntryHeader::NBYTES);
             reader.read_exact(&mut bytes[..EntryHeader::NBYTES])?;
 
             let header = EntryHeader::read(&bytes)?;
-            let entry_length = header.entry_length()? as usize;
+            let length = header
+                .entry_length()?
+                .checked_add(EntryHeader::NBYTES as u64)
+                .ok_or_else(|| std::io::Error::from(InvalidData))?;
+            if length > remaining {
+                break; // Torn entry
+            }
+            let entry_length = length as usize - EntryHeader::NBYTES;
             let key_length = header.key_length as usize;
 
             ensure_buffer_length(&mut bytes, entry_length);
             reader.read_exact(&mut bytes[..entry_length])?;
 
-            ensure_buffer_length(&mut bytes, entry_length);
             let (key_bytes, value_bytes) = bytes[..entry_length].split_at(key_length);
 
-            header.verify_checksum(key_bytes, value_bytes)?;
+            if let Err(e) = header.verify_checksum(key_bytes, value_bytes) {
+                if length == remaining {
+                    break; // Last entry was partially written
+                }
+                return Err(e);
+            }
 
             let key = decompress(key_bytes, header.key_is_compressed)?;
 
-            if header.is_removed {
-                index.remove(&key);
+            let previous = if header.is_removed {
+                index.remove(&key)
             } else {
-                index.insert(key, header_offset);
+                live_bytes += length;
+                index.insert(
+                    key,
+                    IndexEntry {
+                        header_offset,
+                        length,
+                    },
+                )
+            };
+            if let Some(previous) = previous {
+                live_bytes -= previous.length;
             }
 
-            current_offset += (EntryHeader::NBYTES + entry_length) as u64;
+            current_offset += length;
         }
 
-        if eof != current_offset {
-            return Err(UnexpectedEof.into());
+        let file = reader.into_inner();
+
+        let truncated_bytes = eof - current_offset;
+        if truncated_bytes > 0 {
+            file.set_len(current_offset)?;
+            file.sync_all()?;
+        }
+
+        // Left by a compaction which didn't complete, the database file is
+        // still the previous one
+        let tmp_filename = filename.with_file_name(TMP_FILENAME);
+        if tmp_filename.try_exists()? {
+            std::fs::remove_file(&tmp_filename)?;
         }
 
         Ok(Self {
             uuid: next_uuid(),
             index,
-            current_file_offset: eof,
-            file: BufWriter::with_capacity(4 * 1024 * 1024, reader.into_inner()), // 4 MB
+            current_file_offset: current_offset,
+            live_bytes,
+            truncated_bytes,
+            compaction: None,
+            file: BufWriter::with_capacity(4 * 1024 * 1024, file), // 4 MB
             buffer: Vec::with_capacity(BUFFER_DEFAULT_CAPACITY),
             filename,
         })
@@ -404,6 +505,68 @@ impl Database {
         // NOTE: `close` is actually implemented at the ffi level, where `Self` is dropped
     }
 
+    /// Number of bytes of a partially written entry which were removed from
+    /// the end of the file when the database was reloaded.
+    pub fn truncated_bytes(&self) -> u64 {
+        self.truncated_bytes
+    }
+
+    /// Enables compaction of the file after writes, when it reaches the
+    /// thresholds of `config`. `None` disables it.
+    pub fn set_compaction_config(&mut self, config: Option<CompactionConfig>) {
+        self.compaction = config;
+    }
+
+    /// Size of the database file, in bytes.
+    pub fn file_size(&self) -> u64 {
+        self.current_file_offset
+    }
+
+    /// Ratio of the file occupied by overwritten or removed entries, which
+    /// are dropped on compaction.
+    pub fn garbage_ratio(&self) -> f64 {
+        let entries_bytes = self.current_file_offset - DATABASE_VERSION_NBYTES as u64;
+        if entries_bytes == 0 {
+            return 0.0;
+        }
+        (entries_bytes - self.live_bytes) as f64 / entries_bytes as f64
+    }
+
+    /// Returns whether the file reached the thresholds of the compaction
+    /// config. Always false when compaction is disabled.
+    pub fn needs_compaction(&self) -> bool {
+        self.compaction.as_ref().is_some_and(|config| {
+            self.file_size() >= config.min_file_size
+                && self.garbage_ratio() >= config.min_garbage_ratio
+        })
+    }
+
+    /// Returns the number of keys in the database.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Iterates over all keys of the database, in no particular order.
+    pub fn keys(&self) -> impl Iterator<Item = &[u8]> {
+        self.index.keys().map(AsRef::as_ref)
+    }
+
+    /// Iterates over all entries (key-value pairs) of the database, in no
+    /// particular order.
+    ///
+    /// Values are read from the file while iterating.
+    pub fn iter(&mut self) -> Iter<'_> {
+        let keys: Vec<Key> = self.index.keys().cloned().collect();
+        Iter {
+            db: self,
+            keys: keys.into_iter(),
+        }
+    }
+
     fn read_header(&mut self, header_offset: Offset) -> std::io::Result<EntryHeader> {
         ensure_buffer_length(&mut self.buffer, EntryHeader::NBYTES);
         read_exact_at(
@@ -435,8 +598,8 @@ impl Database {
     pub fn get(&mut self, key: &[u8]) -> std::io::Result<Option<Value>> {
         // Note: `&mut self` is required for `File::seek`
 
-        let header_offset = match self.index.get(key).copied() {
-            Some(header_offset) => header_offset,
+        let header_offset = match self.index.get(key) {
+            Some(entry) => entry.header_offset,
             None => return Ok(None),
         };
 
@@ -474,15 +637,51 @@ impl Database {
         self.current_file_offset += buffer_len;
 
         // Update index
-        if is_removed {
-            self.index.remove(&key);
+        let previous = if is_removed {
+            self.index.remove(&key)
         } else {
-            self.index.insert(key, header_offset);
+            self.live_bytes += buffer_len;
+            self.index.insert(
+                key,
+                IndexEntry {
+                    header_offset,
+                    length: buffer_len,
+                },
+            )
+        };
+        if let Some(previous) = previous {
+            self.live_bytes -= previous.length;
         }
 
         Ok(())
     }
 
+    /// Appends an entry copied as is from another database
+    fn append_raw_entry(&mut self, key: Key, entry: &[u8]) -> std::io::Result<()> {
+        let header_offset = self.current_file_offset;
+        let length = entry.len() as u64;
+
+        self.file.write_all(entry)?;
+        self.current_file_offset += length;
+
+        self.live_bytes += length;
+        self.index.insert(
+            key,
+            IndexEntry {
+                header_offset,
+                length,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Returns whether the database contains an entry for `key`, without
+    /// reading it.
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.index.contains_key(key)
+    }
+
     /// Adds or updates an entry (key-value pair) in the database.
     ///
     /// # Arguments
@@ -496,6 +695,7 @@ impl Database {
     pub fn set(&mut self, key: Key, value: Value) -> std::io::Result<()> {
         self.set_impl(key, Some(value))?;
         self.flush()?;
+        self.compact_if_needed()?;
         Ok(())
     }
 
@@ -524,6 +724,7 @@ impl Database {
         }
 
         self.flush()?;
+        self.compact_if_needed()?;
 
         Ok(())
     }
@@ -608,7 +809,9 @@ impl Database {
     ///   otherwise returns an error.
     pub fn remove(&mut self, key: Key) -> std::io::Result<()> {
         self.remove_impl(key)?;
-        self.flush()
+        self.flush()?;
+        self.compact_if_needed()?;
+        Ok(())
     }
 
     /// Retrieves all entries (key-value pairs) from the database.
@@ -618,17 +821,7 @@ impl Database {
     /// * `Result<Vec<(Box<[u8]>, Box<[u8]>)>>` - Returns a vector containing
     ///   all key-value pairs as boxed byte arrays. Returns an error if retrieval fails.
     pub fn to_alist(&mut self) -> std::io::Result<Vec<(Key, Value)>> {
-        let keys: Vec<Key> = self.index.keys().cloned().collect();
-
-        keys.into_iter()
-            .map(|key| {
-                Ok((
-                    key.clone(),
-                    self.get(&key)?
-                        .ok_or_else(|| std::io::Error::from(InvalidData))?,
-                ))
-            })
-            .collect()
+        self.iter().collect()
     }
 
     /// Processes a pre-built batch of operations, effectively running the batch on the database.
@@ -651,38 +844,97 @@ impl Database {
             }
         }
 
-        self.flush()
+        self.flush()?;
+        self.compact_if_needed()?;
+        Ok(())
     }
 
     /// Triggers garbage collection for the database, cleaning up obsolete
     /// data and potentially freeing up storage space.
     ///
+    /// Live entries are copied to a new file, which then atomically replaces
+    /// the current one. If the process stops before that, the current file is
+    /// left untouched.
+    ///
     /// # Returns
     ///
     /// * `Result<()>` - Returns () if garbage collection is successful,
     ///   otherwise returns an error.
     pub fn gc(&mut self) -> std::io::Result<()> {
-        let directory = self.filename.parent().unwrap();
-        let mut new_db = Self::create_impl(directory, CreateMode::Temporary)?;
-
-        let keys: Vec<Key> = self.index.keys().cloned().collect();
-
-        for key in keys {
-            let value = self.get(&key)?;
-            new_db.set_impl(key, value)?;
+        let directory = self.filename.parent().unwrap().to_path_buf();
+        let mut new_db = Self::create_impl(&directory, CreateMode::Temporary)?;
+
+        let mut entries: Vec<(Key, IndexEntry)> = self
+            .index
+            .iter()
+            .map(|(key, entry)| (key.clone(), *entry))
+            .collect();
+        // Read the file sequentially
+        entries.sort_unstable_by_key(|(_, entry)| entry.header_offset);
+
+        for (key, entry) in entries {
+            let bytes = self.read_value(entry.header_offset, entry.length as usize)?;
+            new_db.append_raw_entry(key, bytes)?;
         }
 
         new_db.flush()?;
 
         exchange_file_atomically(&self.filename, &new_db.filename)?;
+        sync_directory(&directory)?;
 
         new_db.filename.clone_from(&self.filename);
         new_db.uuid.clone_from(&self.uuid);
+        new_db.compaction = self.compaction;
 
         *self = new_db;
 
         Ok(())
     }
+
+    /// Runs [`Self::gc`] when [`Self::needs_compaction`]
+    ///
+    /// Returns whether the file was compacted.
+    pub fn compact_if_needed(&mut self) -> std::io::Result<bool> {
+        if !self.needs_compaction() {
+            return Ok(false);
+        }
+        self.gc()?;
+        Ok(true)
+    }
+}
+
+/// Iterator over entries of a [`Database`], see [`Database::iter`]
+pub struct Iter<'a> {
+    db: &'a mut Database,
+    keys: std::vec::IntoIter<Key>,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = std::io::Result<(Key, Value)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?;
+        let value = self
+            .db
+            .get(&key)
+            .and_then(|value| value.ok_or_else(|| std::io::Error::from(InvalidData)));
+        Some(value.map(|value| (key, value)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.keys.size_hint()
+    }
+}
+
+/// Makes renames in `directory` durable
+#[cfg(unix)]
+fn sync_directory(directory: &Path) -> std::io::Result<()> {
+    File::open(directory)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_directory(_directory: &Path) -> std::io::Result<()> {
+    Ok(())
 }
 
 #[cfg(not(target_os = "linux"))]
@@ -939,6 +1191,152 @@ mod tests {
         assert_eq!(db.get(&key("a")).unwrap().unwrap(), value("b"));
     }
 
+    #[test]
+    fn test_compaction_config() {
+        let db_dir = TempDir::new();
+
+        let sorted = make_random_key_values(1000);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set_batch(sorted.clone(), []).unwrap();
+        assert_eq!(db.garbage_ratio(), 0.0);
+
+        db.set_compaction_config(Some(CompactionConfig {
+            min_file_size: 0,
+            min_garbage_ratio: 0.6,
+        }));
+
+        db.set_batch([], sorted[..300].iter().map(|(k, _)| k.clone()))
+            .unwrap();
+        assert!(db.garbage_ratio() > 0.0);
+        assert!(!db.needs_compaction());
+
+        let offset = db.file_size();
+        db.set_batch([], sorted[300..700].iter().map(|(k, _)| k.clone()))
+            .unwrap();
+
+        // Compacted after the write
+        assert!(db.file_size() < offset);
+        assert_eq!(db.garbage_ratio(), 0.0);
+        assert_eq!(db.len(), 300);
+
+        let alist = sorted_vec(db.to_alist().unwrap());
+        assert_eq!(alist, sorted[700..]);
+
+        drop(db);
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(db.garbage_ratio(), 0.0);
+        assert_eq!(sorted_vec(db.to_alist().unwrap()), alist);
+    }
+
+    #[test]
+    fn test_truncate_torn_tail() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(FILENAME);
+
+        let sorted = make_random_key_values(100);
+
+        let file_size = {
+            let mut db = Database::create(db_dir.as_path()).unwrap();
+            db.set_batch(sorted.clone(), []).unwrap();
+            db.file_size()
+        };
+
+        // Last entry partially written
+        {
+            let mut db = Database::create(db_dir.as_path()).unwrap();
+            db.set(key("abc"), value("def")).unwrap();
+        }
+        let file = OpenOptions::new().write(true).open(&filename).unwrap();
+        file.set_len(file_size + 20).unwrap();
+        drop(file);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(db.truncated_bytes(), 20);
+        assert_eq!(db.file_size(), file_size);
+        assert_eq!(sorted_vec(db.to_alist().unwrap()), sorted);
+
+        db.set(key("abc"), value("def")).unwrap();
+        drop(db);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(db.truncated_bytes(), 0);
+        assert_eq!(db.get(&key("abc")).unwrap().unwrap(), value("def"));
+
+        // Stale file of an interrupted compaction
+        drop(db);
+        std::fs::write(db_dir.as_path().join(TMP_FILENAME), b"garbage").unwrap();
+        let db = Database::create(db_dir.as_path()).unwrap();
+        assert_eq!(db.len(), sorted.len() + 1);
+        assert!(!db_dir.as_path().join(TMP_FILENAME).exists());
+    }
+
+    #[test]
+    fn test_corrupted_entry() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(FILENAME);
+
+        {
+            let mut db = Database::create(db_dir.as_path()).unwrap();
+            db.set(key("a"), value("abc")).unwrap();
+            db.set(key("b"), value("def")).unwrap();
+        }
+
+        // Flip the last byte of the first entry
+        let mut bytes = std::fs::read(&filename).unwrap();
+        let first_entry_end = DATABASE_VERSION_NBYTES + EntryHeader::NBYTES + 1 + 3;
+        bytes[first_entry_end - 1] ^= 0xff;
+        std::fs::write(&filename, &bytes).unwrap();
+
+        let error = Database::create(db_dir.as_path()).err().unwrap();
+        assert_eq!(error.kind(), InvalidData);
+    }
+
+    #[test]
+    fn test_corrupted_entry_length() {
+        let db_dir = TempDir::new();
+        let filename = db_dir.as_path().join(FILENAME);
+
+        {
+            let mut db = Database::create(db_dir.as_path()).unwrap();
+            db.set(key("a"), value("abc")).unwrap();
+            db.set(key("b"), value("def")).unwrap();
+            db.set(key("c"), value("ghi")).unwrap();
+        }
+
+        // Value length of the first entry reaching past the end of file
+        let mut bytes = std::fs::read(&filename).unwrap();
+        let value_length_end = DATABASE_VERSION_NBYTES + 4 + 8;
+        bytes[value_length_end - 1] = 0x7f;
+        std::fs::write(&filename, &bytes).unwrap();
+
+        let error = Database::create(db_dir.as_path()).err().unwrap();
+        assert_eq!(error.kind(), InvalidData);
+
+        // Later entries are not truncated
+        assert_eq!(std::fs::read(&filename).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_iter() {
+        let db_dir = TempDir::new();
+
+        let sorted = make_random_key_values(100);
+
+        let mut db = Database::create(db_dir.as_path()).unwrap();
+        db.set_batch(sorted.clone(), []).unwrap();
+
+        let mut keys: Vec<Key> = db.keys().map(Box::from).collect();
+        keys.sort();
+        assert_eq!(
+            keys,
+            sorted.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>()
+        );
+
+        let entries = db.iter().collect::<std::io::Result<Vec<_>>>().unwrap();
+        assert_eq!(sorted_vec(entries), sorted);
+    }
+
     #[test]
     fn test_to_alist() {
         let db_dir = TempDir::new();
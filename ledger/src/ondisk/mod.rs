@@ -10,19 +10,19 @@
 //! ```ignored
 //! +------------+-----------+-----------+
 //! |    HDR     |    KEY    |   VALUE   |
-//! | (17 bytes) | (X bytes) | (X bytes) |
+//! | (21 bytes) | (X bytes) | (X bytes) |
 //! +------------+-----------+-----------+
 //!       ^
 //!       |
 //!       |
-//! +------------+--------------+-----------+------------+
-//! | KEY_LENGTH | VALUE_LENGTH | BITFLAGS  |   CRC32    |
-//! | (4 bytes)  | (8 bytes)    | (1 byte)  | (4 bytes)  |
-//! +------------+--------------+-----------+------------+
+//! +------------+--------------+-----------+------------+------------+
+//! | KEY_LENGTH | VALUE_LENGTH | BITFLAGS  |   CRC32    | HDR_CRC32  |
+//! | (4 bytes)  | (8 bytes)    | (1 byte)  | (4 bytes)  | (4 bytes)  |
+//! +------------+--------------+-----------+------------+------------+
 //! ```
 //!
 //! Where:
-//! - `HDR`: A 17 bytes header
+//! - `HDR`: A 21 bytes header
 //!   - `KEY_LENGTH`: Length of the key, stored in 4 bytes.
 //!   - `VALUE_LENGTH`: Length of the value, stored in 8 bytes.
 //!   - `BITFLAGS`: A 1 byte bitflags including:
@@ -30,9 +30,25 @@
 //!      - `value_is_compressed`: A flag indicating if the value is compressed.
 //!      - `is_removed`: A flag indicating if the entry has been removed.
 //!   - `CRC32`: The CRC32 checksum of the entry (including its header), stored in 4 bytes.
+//!   - `HDR_CRC32`: The CRC32 checksum of the previous header fields, stored in 4 bytes.
 //! - `KEY`: The key data
 //! - `VALUE`: The value data
 //!
+//! ## Compaction
+//!
+//! Overwritten and removed entries stay in the file until it is compacted with
+//! `Database::gc`, which copies the live entries to a new file and atomically
+//! swaps it with the current one. With a `CompactionConfig`, this is done
+//! automatically after a write once the file is large enough and its ratio of
+//! garbage is reached.
+//!
+//! ## Recovery
+//!
+//! When the database is reloaded, an entry at the end of the file which was
+//! only partially written (the process was killed while writing) is truncated.
+//! A corrupted entry anywhere else fails the reload. The header has its own
+//! checksum, so a corrupted length can't be mistaken for a partial write.
+//!
 //! ## Example Usage
 //!
 //! Create an instance of MyDatabase:
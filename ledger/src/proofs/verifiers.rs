@@ -187,7 +187,9 @@ impl BlockVerifier {
     }
 
     fn src_json() -> &'static str {
-        let network_name = openmina_core::NetworkConfig::global().name;
+        let network_name = openmina_core::NetworkConfig::global()
+            .circuits_config
+            .network;
         match network_name {
             "mainnet" => include_str!("data/mainnet_blockchain_verifier_index.json"),
             "devnet" => include_str!("data/devnet_blockchain_verifier_index.json"),
@@ -202,7 +204,9 @@ impl TransactionVerifier {
     }
 
     fn src_json() -> &'static str {
-        let network_name = openmina_core::NetworkConfig::global().name;
+        let network_name = openmina_core::NetworkConfig::global()
+            .circuits_config
+            .network;
         match network_name {
             "mainnet" => include_str!("data/mainnet_transaction_verifier_index.json"),
             "devnet" => include_str!("data/devnet_transaction_verifier_index.json"),
@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use mina_hasher::Fp;
 use mina_p2p_messages::v2::MinaStateProtocolStateValueStableV2;
@@ -24,11 +24,13 @@ use crate::{
         },
         snark_work::spec,
         transaction_logic::{
-            apply_transaction_first_pass, apply_transaction_second_pass, local_state::LocalState,
+            apply_transaction_first_pass, apply_transaction_second_pass,
+            local_state::LocalState,
             protocol_state::ProtocolStateView,
-            transaction_partially_applied::TransactionPartiallyApplied, valid,
-            zkapp_command::MaybeWithStatus, CoinbaseFeeTransfer, Transaction, TransactionStatus,
-            UserCommand, WithStatus,
+            transaction_partially_applied::TransactionPartiallyApplied,
+            valid,
+            zkapp_command::{AccessedOrNot, MaybeWithStatus},
+            CoinbaseFeeTransfer, Transaction, TransactionStatus, UserCommand, WithStatus,
         },
     },
     sparse_ledger::SparseLedger,
@@ -1960,8 +1962,41 @@ impl StagedLedger {
         })
     }
 
+    /// Accounts accessed by the transactions of the latest block, in the
+    /// order of the transactions. Used for archiving.
+    pub fn latest_block_accounts_accessed(&self, previous_block_state_hash: Fp) -> Vec<AccountId> {
+        let f = |t: Arc<TransactionWithWitness>| {
+            let TransactionWithWitness {
+                transaction_with_info,
+                state_hash: (leaf_block_hash, _),
+                ..
+            } = t.as_ref();
+            if leaf_block_hash != &previous_block_state_hash {
+                return None;
+            }
+            let WithStatus { data, status } = transaction_with_info.transaction();
+            Some(
+                data.account_access_statuses(&status)
+                    .into_iter()
+                    .filter(|(_, access)| matches!(access, AccessedOrNot::Accessed))
+                    .map(|(id, _)| id)
+                    .collect::<Vec<_>>(),
+            )
+        };
+
+        let latest = self.scan_state.base_jobs_on_latest_tree().filter_map(f);
+        let earlier = self.scan_state.base_jobs_on_earlier_tree(0).filter_map(f);
+
+        let mut seen = HashSet::new();
+        latest
+            .chain(earlier)
+            .flatten()
+            .filter(|id| seen.insert(id.clone()))
+            .collect()
+    }
+
     /// https://github.com/MinaProtocol/mina/blob/05c2f73d0f6e4f1341286843814ce02dcb3919e0/src/lib/staged_ledger/staged_ledger.ml#L2024
-    fn latest_block_accounts_created(&self, previous_block_state_hash: Fp) -> Vec<AccountId> {
+    pub fn latest_block_accounts_created(&self, previous_block_state_hash: Fp) -> Vec<AccountId> {
         use scan_state::transaction_logic::transaction_applied::signed_command_applied::Body;
         use scan_state::transaction_logic::transaction_applied::CommandApplied;
         use scan_state::transaction_logic::transaction_applied::Varying;
@@ -194,8 +194,11 @@ mod consensus {
     }
 }
 
-/// Fee increase required to replace a transaction.
-const REPLACE_FEE: Fee = Fee::of_nanomina_int_exn(1);
+/// Default fee increase required to replace a transaction.
+pub const DEFAULT_REPLACE_FEE: Fee = Fee::of_nanomina_int_exn(1);
+
+/// Default maximum number of queued transactions from a single sender.
+pub const DEFAULT_MAX_PER_SENDER: usize = 100;
 
 pub type ValidCommandWithHash = WithHash<valid::UserCommand, v2::TransactionHash>;
 
@@ -213,6 +216,7 @@ pub mod diff {
         UnwantedFeeToken,
         Expired,
         Overloaded,
+        SenderQueueFull,
         FeePayerAccountNotFound,
         FeePayerNotPermittedToSend,
         AfterSlotTxEnd,
@@ -230,6 +234,7 @@ pub mod diff {
                 | Error::InsufficientFunds
                 | Error::Expired
                 | Error::Overloaded
+                | Error::SenderQueueFull
                 | Error::FeePayerAccountNotFound
                 | Error::FeePayerNotPermittedToSend
                 | Error::AfterSlotTxEnd
@@ -280,6 +285,12 @@ fn preload_accounts(
 pub struct Config {
     pub trust_system: (),
     pub pool_max_size: usize,
+    /// Maximum number of queued transactions from a single sender, at
+    /// least 1.
+    pub max_per_sender: usize,
+    /// Minimum fee increase for replacing a queued transaction with one
+    /// with the same sender and nonce.
+    pub replace_fee: Fee,
     pub slot_tx_end: Option<Slot>,
 }
 
@@ -540,6 +551,9 @@ pub enum CommandError {
         replace_fee: Fee,
         fee: Fee,
     },
+    SenderQueueFull {
+        max_per_sender: usize,
+    },
     Overflow,
     BadToken,
     Expired {
@@ -570,6 +584,7 @@ impl From<CommandError> for diff::Error {
             CommandError::InvalidNonce { .. } => diff::Error::InvalidNonce,
             CommandError::InsufficientFunds { .. } => diff::Error::InsufficientFunds,
             CommandError::InsufficientReplaceFee { .. } => diff::Error::InsufficientReplaceFee,
+            CommandError::SenderQueueFull { .. } => diff::Error::SenderQueueFull,
             CommandError::Overflow => diff::Error::Overflow,
             CommandError::BadToken => diff::Error::BadToken,
             CommandError::Expired { .. } => diff::Error::Expired,
@@ -586,6 +601,8 @@ impl From<CommandError> for diff::Error {
 pub struct IndexedPoolConfig {
     pub consensus_constants: consensus::Constants,
     slot_tx_end: Option<Slot>,
+    max_per_sender: usize,
+    replace_fee: Fee,
 }
 
 // module Config = struct
@@ -644,7 +661,7 @@ pub enum RevalidateKind<'a> {
 }
 
 impl IndexedPool {
-    fn new(constants: &ConsensusConstants) -> Self {
+    fn new(constants: &ConsensusConstants, config: &Config) -> Self {
         Self {
             applicable_by_fee: HashMap::new(),
             all_by_sender: HashMap::new(),
@@ -655,6 +672,9 @@ impl IndexedPool {
             config: IndexedPoolConfig {
                 consensus_constants: consensus::Constants::create(constants),
                 slot_tx_end: None,
+                // The first transaction of a sender is always accepted
+                max_per_sender: config.max_per_sender.max(1),
+                replace_fee: config.replace_fee,
             },
         }
     }
@@ -667,6 +687,22 @@ impl IndexedPool {
         self.all_by_fee.keys().min().cloned()
     }
 
+    /// Whether `cmd` pays enough to get into the pool. Once the pool is full,
+    /// a transaction has to pay more per weight unit than the cheapest one,
+    /// which then gets evicted.
+    fn has_sufficient_fee(&self, pool_max_size: usize, cmd: &valid::UserCommand) -> bool {
+        match self.min_fee() {
+            None => true,
+            Some(min_fee) => {
+                if self.size() >= pool_max_size {
+                    cmd.forget_check().fee_per_wu() > min_fee
+                } else {
+                    true
+                }
+            }
+        }
+    }
+
     fn member(&self, cmd: &ValidCommandWithHash) -> bool {
         self.all_by_hash.contains_key(&cmd.hash)
     }
@@ -1018,7 +1054,12 @@ impl IndexedPool {
         by_sender: &mut SenderState,
         updates: &mut Vec<Update>,
     ) -> Result<(ValidCommandWithHash, VecDeque<ValidCommandWithHash>), CommandError> {
-        let IndexedPoolConfig { slot_tx_end, .. } = &self.config;
+        let IndexedPoolConfig {
+            slot_tx_end,
+            max_per_sender,
+            replace_fee: min_replace_fee,
+            ..
+        } = &self.config;
 
         if !slot_tx_end
             .as_ref()
@@ -1079,6 +1120,12 @@ impl IndexedPool {
                     last.data.forget_check().expected_target_nonce()
                 };
                 if queue_target_nonce == cmd_applicable_at_nonce {
+                    if queued_cmds.len() >= *max_per_sender {
+                        return Err(CommandError::SenderQueueFull {
+                            max_per_sender: *max_per_sender,
+                        });
+                    }
+
                     let reserved_currency = consumed
                         .checked_add(&reserved_currency)
                         .ok_or(CommandError::Overflow)?;
@@ -1203,9 +1250,9 @@ impl IndexedPool {
                         let _ = drop_tail.next();
                     }
 
-                    if increment < REPLACE_FEE {
+                    if increment < *min_replace_fee {
                         return Err(CommandError::InsufficientReplaceFee {
-                            replace_fee: REPLACE_FEE,
+                            replace_fee: *min_replace_fee,
                             fee: increment,
                         });
                     }
@@ -1586,7 +1633,7 @@ pub struct TransactionPool {
 impl TransactionPool {
     pub fn new(config: Config, consensus_constants: &ConsensusConstants) -> Self {
         Self {
-            pool: IndexedPool::new(consensus_constants),
+            pool: IndexedPool::new(consensus_constants, &config),
             locally_generated_uncommitted: Default::default(),
             locally_generated_committed: Default::default(),
             current_batch: 0,
@@ -1663,19 +1710,6 @@ impl TransactionPool {
         Ok(dropped)
     }
 
-    fn has_sufficient_fee(&self, pool_max_size: usize, cmd: &valid::UserCommand) -> bool {
-        match self.pool.min_fee() {
-            None => true,
-            Some(min_fee) => {
-                if self.pool.size() >= pool_max_size {
-                    cmd.forget_check().fee_per_wu() > min_fee
-                } else {
-                    true
-                }
-            }
-        }
-    }
-
     fn drop_until_below_max_size(
         &mut self,
         pool_max_size: usize,
@@ -1849,7 +1883,7 @@ impl TransactionPool {
             };
 
             if !self.locally_generated_committed.contains_key(cmd) {
-                if !self.has_sufficient_fee(pool_max_size, &cmd.data) {
+                if !self.pool.has_sufficient_fee(pool_max_size, &cmd.data) {
                     remove_cmd(self)
                 } else {
                     let unchecked = &cmd.data;
@@ -1911,10 +1945,13 @@ impl TransactionPool {
     > {
         let fee_payer = |cmd: &ValidCommandWithHash| cmd.data.fee_payer();
         let fee_payer_accounts = accounts;
+        let pool_max_size = self.config.pool_max_size;
 
         let check_command = |pool: &IndexedPool, cmd: &ValidCommandWithHash| {
             if pool.member(cmd) {
                 Err(diff::Error::Duplicate)
+            } else if !pool.has_sufficient_fee(pool_max_size, &cmd.data) {
+                Err(diff::Error::Overloaded)
             } else {
                 match fee_payer_accounts.get(&fee_payer(cmd)) {
                     None => Err(diff::Error::FeePayerAccountNotFound),
@@ -1978,7 +2015,7 @@ impl TransactionPool {
             .flatten()
             .collect::<Vec<_>>();
 
-        let dropped_for_size = self.drop_until_below_max_size(self.config.pool_max_size)?;
+        let dropped_for_size = self.drop_until_below_max_size(pool_max_size)?;
 
         let all_dropped_cmds = dropped_for_add
             .iter()
@@ -2023,7 +2060,10 @@ impl TransactionPool {
         for result in &add_results {
             match result {
                 Ok((cmd, _dropped)) => {
-                    if all_dropped_cmd_hashes.contains(&cmd.hash) {
+                    if dropped_for_size_hashes.contains(&cmd.hash) {
+                        // Evicted right away, along with lower fee commands.
+                        rejected.push((cmd.clone(), diff::Error::Overloaded));
+                    } else if all_dropped_cmd_hashes.contains(&cmd.hash) {
                         // ignored (dropped)
                     } else {
                         accepted.push(cmd.clone());
@@ -2271,8 +2311,84 @@ impl TransactionPool {
 
 #[cfg(test)]
 mod tests {
+    use mina_signer::{Keypair, Signature};
+
+    use crate::{
+        dummy::for_tests::dummy_protocol_state,
+        scan_state::transaction_logic::{
+            signed_command::{Body, PaymentPayload, SignedCommand, SignedCommandPayload},
+            Memo,
+        },
+    };
+
     use super::*;
 
+    fn test_pool(pool_max_size: usize, max_per_sender: usize, replace_fee: Fee) -> TransactionPool {
+        let constants = ConsensusConstants::create(
+            openmina_core::constants::constraint_constants(),
+            &dummy_protocol_state().body.constants,
+        );
+        let config = Config {
+            trust_system: (),
+            pool_max_size,
+            max_per_sender,
+            replace_fee,
+            slot_tx_end: None,
+        };
+        TransactionPool::new(config, &constants)
+    }
+
+    fn test_account(keypair: &Keypair) -> (AccountId, Account) {
+        let account_id = AccountId::new(keypair.public.into_compressed(), TokenId::default());
+        let account =
+            Account::create_with(account_id.clone(), Balance::from_u64(1_000_000_000_000));
+        (account_id, account)
+    }
+
+    fn payment(sender: &Keypair, nonce: u32, fee: u64) -> ValidCommandWithHash {
+        let sender_pk = sender.public.into_compressed();
+        let payload = SignedCommandPayload::create(
+            Fee::from_u64(fee),
+            sender_pk.clone(),
+            Nonce::from_u32(nonce),
+            None,
+            Memo::empty(),
+            Body::Payment(PaymentPayload {
+                receiver_pk: sender_pk.clone(),
+                amount: Amount::from_u64(1),
+            }),
+        );
+        // The pool trusts commands to be verified already
+        let cmd = SignedCommand {
+            payload,
+            signer: sender_pk,
+            signature: Signature::dummy(),
+        };
+        transaction_hash::hash_command(valid::UserCommand::SignedCommand(Box::new(cmd)))
+    }
+
+    fn apply(
+        pool: &mut TransactionPool,
+        accounts: &BTreeMap<AccountId, Account>,
+        list: Vec<ValidCommandWithHash>,
+    ) -> (
+        Vec<ValidCommandWithHash>,
+        Vec<(ValidCommandWithHash, diff::Error)>,
+        HashSet<v2::TransactionHash>,
+    ) {
+        let (_, accepted, rejected, dropped) = pool
+            .unsafe_apply(
+                redux::Timestamp::ZERO,
+                Slot::zero(),
+                Slot::zero(),
+                &diff::DiffVerified { list },
+                accounts,
+                false,
+            )
+            .unwrap();
+        (accepted, rejected, dropped)
+    }
+
     /// Make sure that the merge in `TransactionPool::verify` is correct
     #[test]
     fn test_map_merge() {
@@ -2309,4 +2425,123 @@ mod tests {
 
         dbg!(merged);
     }
+
+    #[test]
+    fn test_replace_fee() {
+        let sender = crate::gen_keypair();
+        let accounts = BTreeMap::from([test_account(&sender)]);
+        let mut pool = test_pool(100, 10, Fee::from_u64(1_000));
+
+        let (accepted, _, _) = apply(&mut pool, &accounts, vec![payment(&sender, 0, 10_000)]);
+        assert_eq!(accepted.len(), 1);
+
+        // Fee increase below the configured one
+        let cmd = payment(&sender, 0, 10_999);
+        let (accepted, rejected, dropped) = apply(&mut pool, &accounts, vec![cmd.clone()]);
+        assert!(accepted.is_empty());
+        assert!(dropped.is_empty());
+        assert!(matches!(
+            rejected.as_slice(),
+            [(rejected, diff::Error::InsufficientReplaceFee)] if rejected == &cmd
+        ));
+
+        let cmd = payment(&sender, 0, 11_000);
+        let (accepted, rejected, dropped) = apply(&mut pool, &accounts, vec![cmd.clone()]);
+        assert_eq!(accepted, vec![cmd]);
+        assert!(rejected.is_empty());
+        assert_eq!(dropped, HashSet::from([payment(&sender, 0, 10_000).hash]));
+        assert_eq!(pool.size(), 1);
+    }
+
+    #[test]
+    fn test_max_per_sender() {
+        let sender = crate::gen_keypair();
+        let accounts = BTreeMap::from([test_account(&sender)]);
+        let mut pool = test_pool(100, 2, Fee::from_u64(1));
+
+        let cmds = (0..3)
+            .map(|nonce| payment(&sender, nonce, 10_000))
+            .collect::<Vec<_>>();
+        let (accepted, rejected, _) = apply(&mut pool, &accounts, cmds.clone());
+        assert_eq!(accepted, cmds[..2]);
+        assert!(matches!(
+            rejected.as_slice(),
+            [(rejected, diff::Error::SenderQueueFull)] if rejected == &cmds[2]
+        ));
+        assert_eq!(pool.size(), 2);
+    }
+
+    #[test]
+    fn test_max_per_sender_zero_is_clamped() {
+        let sender = crate::gen_keypair();
+        let accounts = BTreeMap::from([test_account(&sender)]);
+        let mut pool = test_pool(100, 0, Fee::from_u64(1));
+
+        let cmds = (0..2)
+            .map(|nonce| payment(&sender, nonce, 10_000))
+            .collect::<Vec<_>>();
+        let (accepted, rejected, _) = apply(&mut pool, &accounts, cmds.clone());
+        assert_eq!(accepted, cmds[..1]);
+        assert!(matches!(
+            rejected.as_slice(),
+            [(_, diff::Error::SenderQueueFull)]
+        ));
+    }
+
+    #[test]
+    fn test_overloaded_pool_requires_higher_fee() {
+        let senders = [(); 3].map(|_| crate::gen_keypair());
+        let accounts = senders.iter().map(test_account).collect::<BTreeMap<_, _>>();
+        let mut pool = test_pool(1, 10, Fee::from_u64(1));
+
+        let (accepted, _, _) = apply(&mut pool, &accounts, vec![payment(&senders[0], 0, 10_000)]);
+        assert_eq!(accepted.len(), 1);
+
+        let cheap = payment(&senders[1], 0, 10_000);
+        assert!(!pool.pool.has_sufficient_fee(1, &cheap.data));
+        assert!(pool.pool.has_sufficient_fee(2, &cheap.data));
+        let (accepted, rejected, dropped) = apply(&mut pool, &accounts, vec![cheap.clone()]);
+        assert!(accepted.is_empty());
+        assert!(dropped.is_empty());
+        assert!(matches!(
+            rejected.as_slice(),
+            [(rejected, diff::Error::Overloaded)] if rejected == &cheap
+        ));
+
+        // Evicts the cheapest transaction
+        let expensive = payment(&senders[2], 0, 10_001);
+        assert!(pool.pool.has_sufficient_fee(1, &expensive.data));
+        let (accepted, rejected, dropped) = apply(&mut pool, &accounts, vec![expensive.clone()]);
+        assert_eq!(accepted, vec![expensive]);
+        assert!(rejected.is_empty());
+        assert_eq!(
+            dropped,
+            HashSet::from([payment(&senders[0], 0, 10_000).hash])
+        );
+        assert_eq!(pool.size(), 1);
+    }
+
+    #[test]
+    fn test_command_evicted_when_added_is_rejected() {
+        let senders = [(); 2].map(|_| crate::gen_keypair());
+        let accounts = senders.iter().map(test_account).collect::<BTreeMap<_, _>>();
+        let mut pool = test_pool(2, 10, Fee::from_u64(1));
+
+        let low = payment(&senders[0], 0, 10_000);
+        let other = payment(&senders[1], 0, 20_000);
+        let (accepted, _, _) = apply(&mut pool, &accounts, vec![low.clone(), other.clone()]);
+        assert_eq!(accepted.len(), 2);
+
+        // Pays enough to get in, but depends on the cheapest transaction of
+        // the pool, so both are evicted to make room
+        let dependent = payment(&senders[0], 1, 30_000);
+        let (accepted, rejected, dropped) = apply(&mut pool, &accounts, vec![dependent.clone()]);
+        assert!(accepted.is_empty());
+        assert!(matches!(
+            rejected.as_slice(),
+            [(rejected, diff::Error::Overloaded)] if rejected == &dependent
+        ));
+        assert_eq!(dropped, HashSet::from([low.hash, dependent.hash]));
+        assert_eq!(pool.get_all_transactions(), vec![other]);
+    }
 }
@@ -438,4 +438,57 @@ pub mod common {
         let rv = rv.into_affine();
         rv.y.into_repr().is_even() && rv.x == *rx
     }
+
+    /// Sign with legacy style, using the signature prefix of the network
+    /// config like [`legacy_verify_signature`] does, so custom networks
+    /// are supported.
+    pub fn legacy_sign(keypair: &mina_signer::Keypair, msg: &TransactionUnionPayload) -> Signature {
+        use ::poseidon::hash::legacy;
+        use ark_ec::{AffineCurve, ProjectiveCurve};
+        use ark_ff::{BigInteger, PrimeField};
+        use blake2::{
+            digest::{Update, VariableOutput},
+            Blake2bVar,
+        };
+        use mina_curves::pasta::Fq;
+        use mina_curves::pasta::Pallas;
+        use mina_signer::CurvePoint;
+
+        let network = openmina_core::NetworkConfig::global();
+        let Pallas { x, y, .. } = keypair.public.point();
+        let mut secret = hex::decode(keypair.to_hex()).expect("valid secret key hex");
+        secret.reverse();
+        let secret = Fq::from_le_bytes_mod_order(&secret);
+
+        // Deterministic nonce, derived from the key, the message and the
+        // network, like `mina_signer` does
+        let k = {
+            let mut hasher = Blake2bVar::new(32).expect("valid output size");
+            for field in msg.to_input_legacy().to_fields().iter().chain([x, y]) {
+                hasher.update(&field.into_repr().to_bytes_le());
+            }
+            hasher.update(&secret.into_repr().to_bytes_le());
+            hasher.update(network.name.as_bytes());
+            let mut bytes = [0; 32];
+            hasher
+                .finalize_variable(&mut bytes)
+                .expect("valid output size");
+            Fq::from_le_bytes_mod_order(&bytes)
+        };
+        let r: CurvePoint = CurvePoint::prime_subgroup_generator().mul(k).into_affine();
+        let k = if r.y.into_repr().is_even() { k } else { -k };
+
+        let mut inputs = msg.to_input_legacy();
+        inputs.append_field(*x);
+        inputs.append_field(*y);
+        inputs.append_field(r.x);
+
+        let hash = legacy::hash_with_kimchi(network.legacy_signature_prefix, &inputs.to_fields());
+        let hash: Fq = Fq::try_from(hash.into_repr()).unwrap(); // Never fail, `Fq` is larger than `Fp`
+
+        Signature {
+            rx: r.x,
+            s: k + hash * secret,
+        }
+    }
 }
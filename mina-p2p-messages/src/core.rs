@@ -68,6 +68,17 @@ pub type Error = Info;
 #[derive(Clone, Debug, Serialize, Deserialize, BinProtRead, BinProtWrite, PartialEq)]
 pub struct Time(f64);
 
+impl Time {
+    /// Time as seconds since the Unix epoch.
+    pub fn from_secs_f64(secs: f64) -> Self {
+        Self(secs)
+    }
+
+    pub fn as_secs_f64(&self) -> f64 {
+        self.0
+    }
+}
+
 pub type InetAddrV1Versioned = Versioned<InetAddrV1, 1>;
 
 #[derive(
@@ -44,6 +44,8 @@ pub mod b58version {
     pub const PENDING_COINBASE_HASH_BUILDER: u8 = 0x19;
     pub const VERIFICATION_KEY: u8 = 0x1B;
     pub const TOKEN_ID_KEY: u8 = 0x1c;
+    pub const TRANSACTION_HASH: u8 = 0x1d;
+    pub const PRIVATE_KEY: u8 = 0x5a;
     pub const NON_ZERO_CURVE_POINT_COMPRESSED: u8 = 0xcb;
     pub const SIGNATURE: u8 = 0x9a;
 }
@@ -14,6 +14,8 @@ pub type CharString = BoundedCharString<MINA_STRING_MAX_LENGTH>;
 pub const ZKAPP_URI_MAX_LENGTH: usize = 255;
 // https://github.com/MinaProtocol/mina/blob/c0c9d702b8cba34a603a28001c293ca462b1dfec/src/lib/mina_base/account.ml#L92
 pub const TOKEN_SYMBOL_MAX_LENGTH: usize = 6;
+// https://github.com/MinaProtocol/mina/blob/c0c9d702b8cba34a603a28001c293ca462b1dfec/src/lib/mina_base/signed_command_memo.ml
+pub const MEMO_MAX_LENGTH: usize = 32;
 
 pub type ZkAppUri = BoundedCharString<ZKAPP_URI_MAX_LENGTH>;
 pub type TokenSymbol = BoundedCharString<TOKEN_SYMBOL_MAX_LENGTH>;
@@ -74,9 +74,12 @@ impl std::str::FromStr for TransactionHash {
     type Err = bs58::decode::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = bs58::decode(s).with_check(Some(0x1D)).into_vec()?;
-        dbg!(bytes.len());
-        let bytes = (&bytes[2..])
+        let bytes = bs58::decode(s)
+            .with_check(Some(crate::b58version::TRANSACTION_HASH))
+            .into_vec()?;
+        let bytes = bytes
+            .get(2..)
+            .ok_or(bs58::decode::Error::BufferTooSmall)?
             .try_into()
             .map_err(|_| bs58::decode::Error::BufferTooSmall)?;
         Ok(Self(Arc::new(bytes)))
@@ -88,7 +91,7 @@ impl fmt::Display for TransactionHash {
         let mut bytes = [32; 33];
         bytes[1..].copy_from_slice(&*self.0);
         bs58::encode(bytes)
-            .with_check_version(0x1D)
+            .with_check_version(crate::b58version::TRANSACTION_HASH)
             .into_string()
             .fmt(f)
     }
@@ -208,6 +211,38 @@ impl generated::MinaBaseSignedCommandStableV2 {
     }
 }
 
+impl generated::MinaBaseFeeTransferSingleStableV2 {
+    /// Hash of the fee transfer as an internal command, as in the archive.
+    pub fn hash(&self) -> io::Result<TransactionHash> {
+        binprot_blake2_hash(self)
+    }
+}
+
+impl generated::MinaBaseCoinbaseStableV1 {
+    /// Hash of the coinbase as an internal command, as in the archive.
+    pub fn hash(&self) -> io::Result<TransactionHash> {
+        binprot_blake2_hash(self)
+    }
+}
+
+fn binprot_blake2_hash(value: &impl BinProtWrite) -> io::Result<TransactionHash> {
+    use blake2::{
+        digest::{Update, VariableOutput},
+        Blake2bVar,
+    };
+    let mut encoded = vec![];
+    value.binprot_write(&mut encoded)?;
+
+    let mut hasher = Blake2bVar::new(32).expect("Invalid Blake2bVar output size");
+    hasher.update(&encoded);
+    let mut hash = [0; 32];
+    hasher
+        .finalize_variable(&mut hash)
+        .expect("Invalid buffer size"); // Never occur
+
+    Ok(TransactionHash(hash.into()))
+}
+
 // TODO(adonagy): reduce duplication
 impl generated::MinaBaseZkappCommandTStableV1WireStableV1 {
     fn binprot_write_with_default(&self) -> io::Result<Vec<u8>> {
@@ -387,6 +422,19 @@ mod tests {
             expected_hash
         )
     }
+
+    #[test]
+    fn test_tx_hash_from_short_str() {
+        for len in [0, 1, 31] {
+            let s = bs58::encode(vec![32; len])
+                .with_check_version(crate::b58version::TRANSACTION_HASH)
+                .into_string();
+            assert!(s.parse::<TransactionHash>().is_err(), "length: {len}");
+        }
+
+        let hash = TransactionHash(Arc::new([7; 32]));
+        assert_eq!(hash.to_string().parse::<TransactionHash>().unwrap(), hash);
+    }
 }
 
 fn fp_state_hash_from_fp_hashes(previous_state_hash: Fp, body_hash: Fp) -> Fp {
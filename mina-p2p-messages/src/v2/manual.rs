@@ -528,6 +528,11 @@ base58check_of_binprot!(
     versioned MinaBaseSignatureStableV1,
     SIGNATURE
 );
+base58check_of_binprot!(
+    PrivateKey,
+    versioned SignatureLibPrivateKeyStableV1,
+    PRIVATE_KEY
+);
 
 impl StateHash {
     pub fn zero() -> Self {
@@ -0,0 +1,363 @@
+//! Archive sink. Blocks applied to the transition frontier are sent here
+//! (see [`node::transition_frontier::archive`]) and written by a separate
+//! thread either as precomputed block files or into the archive database.
+//!
+//! Blocks that can't be written even after retrying are spooled on disk as
+//! precomputed blocks. While the spool isn't empty the sink is in catch-up
+//! mode: new blocks are appended to the spool too and the spool is
+//! periodically flushed in height order, so the archive receives blocks in
+//! the same order as the node applied them. The spool is kept across
+//! restarts, so catch-up resumes after downtime.
+//!
+//! Catch-up only covers the time the archive was unavailable. Only the
+//! blocks the node applies reach the sink, so when the node was down for
+//! longer than `k` blocks, the blocks below the root it bootstraps from are
+//! never archived. They have to be imported separately, e.g. from
+//! precomputed blocks of another node.
+
+mod precomputed;
+pub use precomputed::*;
+
+#[cfg(not(target_family = "wasm"))]
+mod rows;
+#[cfg(not(target_family = "wasm"))]
+mod sql;
+#[cfg(not(target_family = "wasm"))]
+pub use sql::ArchiveDatabase;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use mina_p2p_messages::v2;
+use node::{
+    core::{thread, NetworkConfig},
+    transition_frontier::archive::ArchiveAppliedBlock,
+};
+
+use crate::NodeService;
+
+const MAX_WRITE_ATTEMPTS: u32 = 5;
+const RETRY_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const CATCH_UP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(thiserror::Error, Debug)]
+pub enum ArchiveError {
+    #[error("io error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("json error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("database error: {0}")]
+    Database(String),
+    #[error("invalid block: {0}")]
+    InvalidBlock(String),
+}
+
+/// Destination of the archived blocks.
+pub trait ArchiveWriter: Send {
+    fn write(
+        &mut self,
+        state_hash: &v2::StateHash,
+        block: &PrecomputedBlock,
+    ) -> Result<(), ArchiveError>;
+}
+
+#[derive(Debug, Clone)]
+pub enum ArchiveStorage {
+    /// Directory where precomputed blocks are written as json files.
+    PrecomputedDir(PathBuf),
+    /// Url of the archive (postgres) database.
+    #[cfg(not(target_family = "wasm"))]
+    Database(String),
+}
+
+pub struct ArchiveService {
+    sender: mpsc::Sender<ArchiveAppliedBlock>,
+}
+
+impl ArchiveService {
+    pub fn start(
+        storage: ArchiveStorage,
+        spool_dir: impl AsRef<Path>,
+    ) -> Result<Self, ArchiveError> {
+        let network = NetworkConfig::global().name;
+        let writer: Box<dyn ArchiveWriter> = match storage {
+            ArchiveStorage::PrecomputedDir(dir) => {
+                Box::new(PrecomputedBlockDir::open(dir, network)?)
+            }
+            #[cfg(not(target_family = "wasm"))]
+            ArchiveStorage::Database(url) => Box::new(ArchiveDatabase::connect(&url)?),
+        };
+        let spool_dir = spool_dir.as_ref().to_owned();
+        fs::create_dir_all(&spool_dir)?;
+
+        let mut worker = ArchiveWorker {
+            writer,
+            catching_up: false,
+            spool_dir,
+        };
+        worker.catching_up = !worker.spooled()?.is_empty();
+
+        let (sender, receiver) = mpsc::channel();
+        thread::Builder::new()
+            .name("openmina_archive".to_owned())
+            .spawn(move || worker.run(receiver))?;
+
+        Ok(Self { sender })
+    }
+}
+
+impl node::service::TransitionFrontierArchiveService for NodeService {
+    fn send_to_archive(&mut self, block: ArchiveAppliedBlock) {
+        if self.replayer.is_some() {
+            return;
+        }
+        if let Some(archive) = self.archive.as_ref() {
+            let _ = archive.sender.send(block);
+        }
+    }
+}
+
+struct ArchiveWorker {
+    writer: Box<dyn ArchiveWriter>,
+    /// Whether there are spooled blocks which must be written before any
+    /// new block.
+    catching_up: bool,
+    spool_dir: PathBuf,
+}
+
+impl ArchiveWorker {
+    fn run(mut self, receiver: mpsc::Receiver<ArchiveAppliedBlock>) {
+        if self.catching_up {
+            self.catch_up();
+        }
+        let mut last_catch_up = Instant::now();
+        loop {
+            match receiver.recv_timeout(CATCH_UP_INTERVAL) {
+                Ok(block) => self.handle(block),
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+            if self.catching_up && last_catch_up.elapsed() >= CATCH_UP_INTERVAL {
+                self.catch_up();
+                last_catch_up = Instant::now();
+            }
+        }
+    }
+
+    fn handle(&mut self, block: ArchiveAppliedBlock) {
+        let state_hash = block.block.hash().clone();
+        let block = PrecomputedBlock::from(&block);
+
+        if !self.catching_up {
+            match self.write_with_retries(&state_hash, &block) {
+                Ok(()) => return,
+                Err(error) => {
+                    openmina_core::warn!(
+                        openmina_core::log::system_time();
+                        kind = "ArchiveCatchUpStart",
+                        summary = format!("failed to archive block {state_hash}, spooling it"),
+                        error = error.to_string()
+                    );
+                    self.catching_up = true;
+                }
+            }
+        }
+
+        if let Err(error) = self.spool(&state_hash, &block) {
+            openmina_core::error!(
+                openmina_core::log::system_time();
+                kind = "ArchiveSpoolError",
+                summary = format!("failed to spool block {state_hash}, it won't be archived"),
+                error = error.to_string()
+            );
+        }
+    }
+
+    fn write_with_retries(
+        &mut self,
+        state_hash: &v2::StateHash,
+        block: &PrecomputedBlock,
+    ) -> Result<(), ArchiveError> {
+        let mut backoff = RETRY_INITIAL_BACKOFF;
+        let mut attempt = 1;
+        loop {
+            match self.writer.write(state_hash, block) {
+                Ok(()) => return Ok(()),
+                Err(error) if attempt >= MAX_WRITE_ATTEMPTS => return Err(error),
+                Err(error) => {
+                    openmina_core::debug!(
+                        openmina_core::log::system_time();
+                        kind = "ArchiveWriteRetry",
+                        summary = format!("failed to archive block {state_hash}, attempt {attempt}"),
+                        error = error.to_string()
+                    );
+                }
+            }
+            thread::sleep(backoff);
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+
+    /// Writes spooled blocks to the archive, lowest height first. Stops at
+    /// the first failure, the rest is retried on the next call.
+    fn catch_up(&mut self) {
+        let spooled = match self.spooled() {
+            Ok(spooled) => spooled,
+            Err(error) => {
+                openmina_core::error!(
+                    openmina_core::log::system_time();
+                    kind = "ArchiveSpoolError",
+                    summary = "failed to list spooled blocks",
+                    error = error.to_string()
+                );
+                return;
+            }
+        };
+
+        for path in spooled {
+            if let Err(error) = self.write_spooled(&path) {
+                openmina_core::warn!(
+                    openmina_core::log::system_time();
+                    kind = "ArchiveCatchUpError",
+                    summary = format!("failed to archive spooled block {}", path.display()),
+                    error = error.to_string()
+                );
+                return;
+            }
+        }
+
+        self.catching_up = false;
+        openmina_core::info!(
+            openmina_core::log::system_time();
+            kind = "ArchiveCatchUpDone",
+            summary = "all spooled blocks were archived"
+        );
+    }
+
+    fn write_spooled(&mut self, path: &Path) -> Result<(), ArchiveError> {
+        let block: PrecomputedBlock = serde_json::from_slice(&fs::read(path)?)?;
+        let state_hash = block
+            .data
+            .protocol_state
+            .try_hash()
+            .map_err(|err| ArchiveError::InvalidBlock(err.to_string()))?;
+        self.writer.write(&state_hash, &block)?;
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    fn spool(
+        &self,
+        state_hash: &v2::StateHash,
+        block: &PrecomputedBlock,
+    ) -> Result<(), ArchiveError> {
+        // Zero padded height, so that sorting by name sorts by height.
+        let name = format!("{:010}-{state_hash}.json", block.height());
+        write_json_atomic(&self.spool_dir.join(name), block)
+    }
+
+    /// Spooled blocks, sorted by height.
+    fn spooled(&self) -> Result<Vec<PathBuf>, ArchiveError> {
+        let mut paths = fs::read_dir(&self.spool_dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .filter(|path| {
+                path.as_ref().map_or(true, |path| {
+                    path.extension().is_some_and(|ext| ext == "json")
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::precomputed::test_precomputed_block;
+    use super::*;
+
+    /// Records heights of the written blocks, fails while `failing` is set.
+    #[derive(Clone, Default)]
+    struct TestWriter {
+        written: Arc<Mutex<Vec<u32>>>,
+        failing: Arc<Mutex<bool>>,
+    }
+
+    impl ArchiveWriter for TestWriter {
+        fn write(
+            &mut self,
+            _state_hash: &v2::StateHash,
+            block: &PrecomputedBlock,
+        ) -> Result<(), ArchiveError> {
+            if *self.failing.lock().unwrap() {
+                return Err(ArchiveError::Database("unavailable".to_owned()));
+            }
+            self.written.lock().unwrap().push(block.height());
+            Ok(())
+        }
+    }
+
+    fn worker(name: &str, writer: TestWriter) -> ArchiveWorker {
+        let spool_dir = std::env::temp_dir().join(format!(
+            "openmina-archive-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&spool_dir);
+        fs::create_dir_all(&spool_dir).unwrap();
+        ArchiveWorker {
+            writer: Box::new(writer),
+            catching_up: true,
+            spool_dir,
+        }
+    }
+
+    fn spool(worker: &ArchiveWorker, height: u32) {
+        let block = test_precomputed_block(height);
+        let state_hash = block.data.protocol_state.try_hash().unwrap();
+        worker.spool(&state_hash, &block).unwrap();
+    }
+
+    #[test]
+    fn test_catch_up_writes_spooled_blocks_in_height_order() {
+        let writer = TestWriter::default();
+        let mut worker = worker("order", writer.clone());
+        for height in [12, 3, 10] {
+            spool(&worker, height);
+        }
+
+        worker.catch_up();
+
+        assert_eq!(*writer.written.lock().unwrap(), vec![3, 10, 12]);
+        assert!(worker.spooled().unwrap().is_empty());
+        assert!(!worker.catching_up);
+    }
+
+    #[test]
+    fn test_catch_up_keeps_spool_on_failure() {
+        let writer = TestWriter::default();
+        let mut worker = worker("failure", writer.clone());
+        for height in [5, 4] {
+            spool(&worker, height);
+        }
+        *writer.failing.lock().unwrap() = true;
+
+        worker.catch_up();
+
+        assert!(writer.written.lock().unwrap().is_empty());
+        assert_eq!(worker.spooled().unwrap().len(), 2);
+        assert!(worker.catching_up);
+
+        *writer.failing.lock().unwrap() = false;
+        worker.catch_up();
+
+        assert_eq!(*writer.written.lock().unwrap(), vec![4, 5]);
+        assert!(!worker.catching_up);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use mina_p2p_messages::{list::List, v2};
+use node::transition_frontier::archive::ArchiveAppliedBlock;
+use serde::{Deserialize, Serialize};
+
+use super::{ArchiveError, ArchiveWriter};
+
+/// Version of the precomputed block format, as written by the OCaml node.
+const PRECOMPUTED_BLOCK_VERSION: u32 = 3;
+
+/// Block in the Mina precomputed block form, which the archive can import
+/// (e.g. using `mina-archive-blocks --precomputed`).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrecomputedBlock {
+    pub version: u32,
+    pub data: PrecomputedBlockData,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PrecomputedBlockData {
+    pub scheduled_time: String,
+    pub protocol_state: v2::MinaStateProtocolStateValueStableV2,
+    #[serde(with = "proof_base64")]
+    pub protocol_state_proof: v2::MinaBaseProofStableV2,
+    pub staged_ledger_diff: v2::StagedLedgerDiffDiffStableV2,
+    pub delta_transition_chain_proof: (v2::StateHash, List<v2::StateBodyHash>),
+    pub protocol_version: v2::ProtocolVersionStableV2,
+    pub proposed_protocol_version: Option<v2::ProtocolVersionStableV2>,
+    pub accounts_accessed: Vec<(u64, v2::MinaBaseAccountBinableArgStableV2)>,
+    pub accounts_created: Vec<(v2::MinaBaseAccountIdStableV2, v2::CurrencyFeeStableV1)>,
+    pub tokens_used: Vec<(v2::TokenIdKeyHash, Option<v2::MinaBaseAccountIdStableV2>)>,
+}
+
+/// OCaml writes the proof as a base64 encoded s-expression. That's how
+/// [`v2::TransactionSnarkProofStableV2`], wrapping the same pickles proof,
+/// is serialized into json, so the conversion is delegated to it.
+mod proof_base64 {
+    use mina_p2p_messages::v2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        proof: &v2::MinaBaseProofStableV2,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        v2::TransactionSnarkProofStableV2(proof.0.clone()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<v2::MinaBaseProofStableV2, D::Error> {
+        v2::TransactionSnarkProofStableV2::deserialize(deserializer)
+            .map(|proof| v2::MinaBaseProofStableV2(proof.0))
+    }
+}
+
+impl PrecomputedBlock {
+    pub fn height(&self) -> u32 {
+        self.data
+            .protocol_state
+            .body
+            .consensus_state
+            .blockchain_length
+            .as_u32()
+    }
+}
+
+impl From<&ArchiveAppliedBlock> for PrecomputedBlock {
+    fn from(value: &ArchiveAppliedBlock) -> Self {
+        let header = value.block.header();
+        let data = value.data.as_ref();
+        Self {
+            version: PRECOMPUTED_BLOCK_VERSION,
+            data: PrecomputedBlockData {
+                scheduled_time: header
+                    .protocol_state
+                    .body
+                    .blockchain_state
+                    .timestamp
+                    .as_u64()
+                    .to_string(),
+                protocol_state: header.protocol_state.clone(),
+                protocol_state_proof: (*header.protocol_state_proof).clone(),
+                staged_ledger_diff: value.block.body().staged_ledger_diff.clone(),
+                delta_transition_chain_proof: header.delta_block_chain_proof.clone(),
+                protocol_version: header.current_protocol_version.clone(),
+                proposed_protocol_version: header.proposed_protocol_version_opt.clone(),
+                accounts_accessed: data.accounts_accessed.clone(),
+                accounts_created: data.accounts_created.clone(),
+                tokens_used: data.tokens_used.clone(),
+            },
+        }
+    }
+}
+
+/// File name used by the OCaml node: `<network>-<height>-<state hash>.json`.
+pub fn precomputed_block_file_name(
+    network: &str,
+    height: u32,
+    state_hash: &v2::StateHash,
+) -> String {
+    format!("{network}-{height}-{state_hash}.json")
+}
+
+/// Writes precomputed blocks as json files into a directory.
+pub struct PrecomputedBlockDir {
+    dir: PathBuf,
+    network: &'static str,
+}
+
+impl PrecomputedBlockDir {
+    pub fn open(dir: impl AsRef<Path>, network: &'static str) -> Result<Self, ArchiveError> {
+        let dir = dir.as_ref().to_owned();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, network })
+    }
+}
+
+impl ArchiveWriter for PrecomputedBlockDir {
+    fn write(
+        &mut self,
+        state_hash: &v2::StateHash,
+        block: &PrecomputedBlock,
+    ) -> Result<(), ArchiveError> {
+        let name = precomputed_block_file_name(self.network, block.height(), state_hash);
+        write_json_atomic(&self.dir.join(name), block)
+    }
+}
+
+/// Writes `value` to a temporary file first and then renames it, so that
+/// a crash can't leave a truncated file behind.
+pub(super) fn write_json_atomic(path: &Path, value: &impl Serialize) -> Result<(), ArchiveError> {
+    let tmp_path = path.with_extension("json.tmp");
+    let mut file = fs::File::create(&tmp_path)?;
+    serde_json::to_writer(&mut file, value)?;
+    file.flush()?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Block of the `new_state.bin` test fixture, relabeled to `height`.
+#[cfg(test)]
+pub(super) fn test_precomputed_block(height: u32) -> PrecomputedBlock {
+    use mina_p2p_messages::{binprot::BinProtRead, gossip::GossipNetMessageV2};
+
+    let mut bytes: &[u8] =
+        include_bytes!("../../../../../mina-p2p-messages/tests/files/v2/gossip/new_state.bin");
+    let GossipNetMessageV2::NewState(block) = GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+    else {
+        panic!("expected a block");
+    };
+    let header = &block.header;
+    let mut protocol_state = header.protocol_state.clone();
+    protocol_state.body.consensus_state.blockchain_length = height.into();
+
+    PrecomputedBlock {
+        version: PRECOMPUTED_BLOCK_VERSION,
+        data: PrecomputedBlockData {
+            scheduled_time: protocol_state
+                .body
+                .blockchain_state
+                .timestamp
+                .as_u64()
+                .to_string(),
+            protocol_state,
+            protocol_state_proof: (*header.protocol_state_proof).clone(),
+            staged_ledger_diff: block.body.staged_ledger_diff.clone(),
+            delta_transition_chain_proof: header.delta_block_chain_proof.clone(),
+            protocol_version: header.current_protocol_version.clone(),
+            proposed_protocol_version: header.proposed_protocol_version_opt.clone(),
+            accounts_accessed: vec![],
+            accounts_created: vec![],
+            tokens_used: vec![],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_state_proof_json() {
+        let block = test_precomputed_block(10);
+
+        let json = serde_json::to_value(&block).unwrap();
+        let proof = json["data"]["protocol_state_proof"]
+            .as_str()
+            .expect("proof should be a string");
+        // base64 of `((statement`, how every OCaml precomputed block proof starts
+        assert!(proof.starts_with("KChzdGF0ZW1lbnQ"), "{proof}");
+
+        let decoded: PrecomputedBlock = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            decoded.data.protocol_state_proof,
+            block.data.protocol_state_proof
+        );
+        assert_eq!(decoded.data.protocol_state, block.data.protocol_state);
+    }
+}
@@ -0,0 +1,1077 @@
+//! Rows of the archive database for a precomputed block, see
+//! [`BlockRows::new`]. Values are formatted as the OCaml archive stores
+//! them: amounts and field elements as decimal strings, hashes and public
+//! keys in base58check.
+
+use ledger::{
+    scan_state::transaction_logic::{Transaction, TransactionStatus, WithStatus},
+    staged_ledger::diff::Diff,
+    FpExt, VerificationKey,
+};
+use mina_p2p_messages::{bigint::BigInt, v2};
+use mina_signer::CompressedPubKey;
+use openmina_core::constants::constraint_constants;
+
+use super::{ArchiveError, PrecomputedBlock};
+
+#[derive(Debug)]
+pub struct BlockRows {
+    pub block: BlockRow,
+    pub user_commands: Vec<UserCommandRow>,
+    pub internal_commands: Vec<InternalCommandRow>,
+    pub zkapp_commands: Vec<ZkappCommandRow>,
+    pub accounts_accessed: Vec<AccountAccessedRow>,
+    pub accounts_created: Vec<AccountCreatedRow>,
+    /// Tokens used in the block, with their owners.
+    pub tokens_used: Vec<(String, Option<AccountIdRow>)>,
+}
+
+#[derive(Debug)]
+pub struct BlockRow {
+    pub state_hash: String,
+    pub parent_hash: String,
+    pub creator: String,
+    pub block_winner: String,
+    pub last_vrf_output: String,
+    pub snarked_ledger_hash: String,
+    pub staking_epoch_data: EpochDataRow,
+    pub next_epoch_data: EpochDataRow,
+    pub min_window_density: i64,
+    pub sub_window_densities: Vec<i64>,
+    pub total_currency: String,
+    pub ledger_hash: String,
+    pub height: i64,
+    /// Number of blocks after which the block is final.
+    pub k: i64,
+    pub global_slot_since_hard_fork: i64,
+    pub global_slot_since_genesis: i64,
+    pub protocol_version: ProtocolVersionRow,
+    pub proposed_protocol_version: Option<ProtocolVersionRow>,
+    pub timestamp: String,
+}
+
+#[derive(Debug)]
+pub struct EpochDataRow {
+    pub seed: String,
+    pub ledger_hash: String,
+    pub total_currency: String,
+    pub start_checkpoint: String,
+    pub lock_checkpoint: String,
+    pub epoch_length: i64,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ProtocolVersionRow {
+    pub transaction: i32,
+    pub network: i32,
+    pub patch: i32,
+}
+
+#[derive(Debug)]
+pub struct UserCommandRow {
+    pub sequence_no: i32,
+    pub command_type: &'static str,
+    pub fee_payer: String,
+    pub receiver: String,
+    pub nonce: i64,
+    pub amount: Option<String>,
+    pub fee: String,
+    pub valid_until: i64,
+    pub memo: String,
+    pub hash: String,
+    pub status: &'static str,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct InternalCommandRow {
+    pub sequence_no: i32,
+    pub secondary_sequence_no: i32,
+    pub command_type: &'static str,
+    pub receiver: String,
+    pub fee: String,
+    pub hash: String,
+    pub status: &'static str,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ZkappCommandRow {
+    pub sequence_no: i32,
+    pub fee_payer: ZkappFeePayerBodyRow,
+    /// Account updates of the call forest in pre-order.
+    pub account_updates: Vec<ZkappAccountUpdateBodyRow>,
+    pub memo: String,
+    pub hash: String,
+    pub status: &'static str,
+    /// Failures of the account updates, empty if the command was applied.
+    pub failure_reasons: Vec<ZkappAccountUpdateFailuresRow>,
+}
+
+#[derive(Debug)]
+pub struct ZkappFeePayerBodyRow {
+    pub public_key: String,
+    pub fee: String,
+    pub valid_until: Option<i64>,
+    pub nonce: i64,
+}
+
+/// Optional values are `None` if the update is `Keep` or the precondition
+/// is `Ignore`, as in the archive.
+#[derive(Debug)]
+pub struct ZkappAccountUpdateBodyRow {
+    pub account: AccountIdRow,
+    pub update: ZkappUpdatesRow,
+    pub balance_change: String,
+    pub increment_nonce: bool,
+    pub events: Vec<Vec<String>>,
+    pub actions: Vec<Vec<String>>,
+    pub call_data: String,
+    pub call_depth: i32,
+    pub network_precondition: ZkappNetworkPreconditionRow,
+    pub account_precondition: ZkappAccountPreconditionRow,
+    pub valid_while_precondition: Option<(i64, i64)>,
+    pub use_full_commitment: bool,
+    pub implicit_account_creation_fee: bool,
+    pub may_use_token: &'static str,
+    pub authorization_kind: &'static str,
+    pub verification_key_hash: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ZkappUpdatesRow {
+    pub app_state: [Option<String>; 8],
+    pub delegate: Option<String>,
+    pub verification_key: Option<VerificationKeyRow>,
+    pub permissions: Option<PermissionsRow>,
+    pub zkapp_uri: Option<String>,
+    pub token_symbol: Option<String>,
+    pub timing: Option<TimingRow>,
+    pub voting_for: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ZkappNetworkPreconditionRow {
+    pub snarked_ledger_hash: Option<String>,
+    pub blockchain_length: Option<(i64, i64)>,
+    pub min_window_density: Option<(i64, i64)>,
+    pub total_currency: Option<(String, String)>,
+    pub global_slot_since_genesis: Option<(i64, i64)>,
+    pub staking_epoch_data: ZkappEpochDataRow,
+    pub next_epoch_data: ZkappEpochDataRow,
+}
+
+#[derive(Debug)]
+pub struct ZkappEpochDataRow {
+    pub ledger_hash: Option<String>,
+    pub total_currency: Option<(String, String)>,
+    pub seed: Option<String>,
+    pub start_checkpoint: Option<String>,
+    pub lock_checkpoint: Option<String>,
+    pub epoch_length: Option<(i64, i64)>,
+}
+
+#[derive(Debug)]
+pub struct ZkappAccountPreconditionRow {
+    pub balance: Option<(String, String)>,
+    pub nonce: Option<(i64, i64)>,
+    pub receipt_chain_hash: Option<String>,
+    pub delegate: Option<String>,
+    pub state: [Option<String>; 8],
+    pub action_state: Option<String>,
+    pub proved_state: Option<bool>,
+    pub is_new: Option<bool>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ZkappAccountUpdateFailuresRow {
+    pub index: i32,
+    pub failures: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct AccountAccessedRow {
+    pub ledger_index: i32,
+    pub account: AccountIdRow,
+    pub token_symbol: String,
+    pub balance: String,
+    pub nonce: i64,
+    pub receipt_chain_hash: String,
+    pub delegate: Option<String>,
+    pub voting_for: String,
+    /// All zeros for untimed accounts.
+    pub timing: TimingRow,
+    pub permissions: PermissionsRow,
+    pub zkapp: Option<ZkappAccountRow>,
+}
+
+#[derive(Debug)]
+pub struct ZkappAccountRow {
+    pub app_state: [String; 8],
+    pub verification_key: Option<VerificationKeyRow>,
+    pub zkapp_version: i64,
+    pub action_state: [String; 5],
+    pub last_action_slot: i64,
+    pub proved_state: bool,
+    pub zkapp_uri: String,
+}
+
+#[derive(Debug)]
+pub struct AccountCreatedRow {
+    pub account: AccountIdRow,
+    pub creation_fee: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountIdRow {
+    pub public_key: String,
+    pub token_id: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct TimingRow {
+    pub initial_minimum_balance: String,
+    pub cliff_time: i64,
+    pub cliff_amount: String,
+    pub vesting_period: i64,
+    pub vesting_increment: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct PermissionsRow {
+    pub edit_state: &'static str,
+    pub send: &'static str,
+    pub receive: &'static str,
+    pub access: &'static str,
+    pub set_delegate: &'static str,
+    pub set_permissions: &'static str,
+    pub set_verification_key_auth: &'static str,
+    pub set_verification_key_txn_version: i32,
+    pub set_zkapp_uri: &'static str,
+    pub edit_action_state: &'static str,
+    pub set_token_symbol: &'static str,
+    pub increment_nonce: &'static str,
+    pub set_voting_for: &'static str,
+    pub set_timing: &'static str,
+}
+
+#[derive(Debug)]
+pub struct VerificationKeyRow {
+    /// Base64 of the binprot encoded key.
+    pub verification_key: String,
+    pub hash: String,
+}
+
+impl BlockRows {
+    /// Commands are numbered in the order in which they were applied
+    /// (`sequence_no`), the same way as the OCaml archive does it when
+    /// importing a precomputed block.
+    pub fn new(state_hash: &v2::StateHash, block: &PrecomputedBlock) -> Result<Self, ArchiveError> {
+        let data = &block.data;
+        let mut rows = Self {
+            block: BlockRow::new(state_hash, block),
+            user_commands: vec![],
+            internal_commands: vec![],
+            zkapp_commands: vec![],
+            accounts_accessed: data
+                .accounts_accessed
+                .iter()
+                .map(|(ledger_index, account)| AccountAccessedRow::new(*ledger_index, account))
+                .collect::<Result<_, _>>()?,
+            accounts_created: data
+                .accounts_created
+                .iter()
+                .map(|(account_id, creation_fee)| AccountCreatedRow {
+                    account: AccountIdRow::from(account_id),
+                    creation_fee: creation_fee.as_u64().to_string(),
+                })
+                .collect(),
+            tokens_used: data
+                .tokens_used
+                .iter()
+                .map(|(token, owner)| (token.to_string(), owner.as_ref().map(AccountIdRow::from)))
+                .collect(),
+        };
+
+        for (sequence_no, transaction) in transactions(block)?.into_iter().enumerate() {
+            let sequence_no = sequence_no as i32;
+            let WithStatus { data, status } = transaction;
+            match data {
+                Transaction::Command(command) => {
+                    match v2::MinaBaseUserCommandStableV2::from(&command) {
+                        v2::MinaBaseUserCommandStableV2::SignedCommand(command) => rows
+                            .user_commands
+                            .push(UserCommandRow::new(sequence_no, &command, &status)?),
+                        v2::MinaBaseUserCommandStableV2::ZkappCommand(command) => rows
+                            .zkapp_commands
+                            .push(ZkappCommandRow::new(sequence_no, &command, &status)?),
+                    }
+                }
+                Transaction::FeeTransfer(fee_transfers) => {
+                    for (secondary_sequence_no, fee_transfer) in fee_transfers.iter().enumerate() {
+                        rows.internal_commands
+                            .push(InternalCommandRow::fee_transfer(
+                                "fee_transfer",
+                                (sequence_no, secondary_sequence_no as i32),
+                                &v2::MinaBaseFeeTransferSingleStableV2::from(fee_transfer),
+                                &status,
+                            )?);
+                    }
+                }
+                Transaction::Coinbase(coinbase) => {
+                    let coinbase = v2::MinaBaseCoinbaseStableV1::from(&coinbase);
+                    if let Some(fee_transfer) = &coinbase.fee_transfer {
+                        let fee_transfer = v2::MinaBaseFeeTransferSingleStableV2 {
+                            receiver_pk: fee_transfer.receiver_pk.clone(),
+                            fee: fee_transfer.fee.clone(),
+                            fee_token: v2::TokenIdKeyHash::default(),
+                        };
+                        rows.internal_commands
+                            .push(InternalCommandRow::fee_transfer(
+                                "fee_transfer_via_coinbase",
+                                (sequence_no, 0),
+                                &fee_transfer,
+                                &status,
+                            )?);
+                    }
+                    rows.internal_commands.push(InternalCommandRow::coinbase(
+                        sequence_no,
+                        &coinbase,
+                        &status,
+                    )?);
+                }
+            }
+        }
+        Ok(rows)
+    }
+}
+
+/// Transactions of the block in the order in which they were applied.
+fn transactions(block: &PrecomputedBlock) -> Result<Vec<WithStatus<Transaction>>, ArchiveError> {
+    let consensus_state = &block.data.protocol_state.body.consensus_state;
+    let coinbase_receiver: CompressedPubKey = (&consensus_state.coinbase_receiver)
+        .try_into()
+        .map_err(invalid_block)?;
+    let diff = Diff::try_from(&block.data.staged_ledger_diff).map_err(invalid_block)?;
+    diff.get_transactions(
+        constraint_constants(),
+        coinbase_receiver,
+        consensus_state.supercharge_coinbase,
+    )
+    .map_err(invalid_block)
+}
+
+fn invalid_block(err: impl std::fmt::Debug) -> ArchiveError {
+    ArchiveError::InvalidBlock(format!("{err:?}"))
+}
+
+impl BlockRow {
+    fn new(state_hash: &v2::StateHash, block: &PrecomputedBlock) -> Self {
+        let protocol_state = &block.data.protocol_state;
+        let blockchain_state = &protocol_state.body.blockchain_state;
+        let consensus_state = &protocol_state.body.consensus_state;
+        let staking_epoch_data = &consensus_state.staking_epoch_data;
+        let next_epoch_data = &consensus_state.next_epoch_data;
+
+        Self {
+            state_hash: state_hash.to_string(),
+            parent_hash: protocol_state.previous_state_hash.to_string(),
+            creator: consensus_state.block_creator.to_string(),
+            block_winner: consensus_state.block_stake_winner.to_string(),
+            // Same representation as in the precomputed block.
+            last_vrf_output: match serde_json::to_value(&consensus_state.last_vrf_output) {
+                Ok(serde_json::Value::String(s)) => s,
+                _ => String::new(),
+            },
+            snarked_ledger_hash: blockchain_state
+                .ledger_proof_statement
+                .target
+                .first_pass_ledger
+                .to_string(),
+            staking_epoch_data: EpochDataRow {
+                seed: staking_epoch_data.seed.to_string(),
+                ledger_hash: staking_epoch_data.ledger.hash.to_string(),
+                total_currency: staking_epoch_data
+                    .ledger
+                    .total_currency
+                    .as_u64()
+                    .to_string(),
+                start_checkpoint: staking_epoch_data.start_checkpoint.to_string(),
+                lock_checkpoint: staking_epoch_data.lock_checkpoint.to_string(),
+                epoch_length: staking_epoch_data.epoch_length.as_u32().into(),
+            },
+            next_epoch_data: EpochDataRow {
+                seed: next_epoch_data.seed.to_string(),
+                ledger_hash: next_epoch_data.ledger.hash.to_string(),
+                total_currency: next_epoch_data.ledger.total_currency.as_u64().to_string(),
+                start_checkpoint: next_epoch_data.start_checkpoint.to_string(),
+                lock_checkpoint: next_epoch_data.lock_checkpoint.to_string(),
+                epoch_length: next_epoch_data.epoch_length.as_u32().into(),
+            },
+            min_window_density: consensus_state.min_window_density.as_u32().into(),
+            sub_window_densities: consensus_state
+                .sub_window_densities
+                .iter()
+                .map(|density| i64::from(density.as_u32()))
+                .collect(),
+            total_currency: consensus_state.total_currency.as_u64().to_string(),
+            ledger_hash: blockchain_state
+                .staged_ledger_hash
+                .non_snark
+                .ledger_hash
+                .to_string(),
+            height: consensus_state.blockchain_length.as_u32().into(),
+            k: protocol_state.body.constants.k.as_u32().into(),
+            global_slot_since_hard_fork: consensus_state
+                .curr_global_slot_since_hard_fork
+                .slot_number
+                .as_u32()
+                .into(),
+            global_slot_since_genesis: consensus_state.global_slot_since_genesis.as_u32().into(),
+            protocol_version: ProtocolVersionRow::from(&block.data.protocol_version),
+            proposed_protocol_version: block
+                .data
+                .proposed_protocol_version
+                .as_ref()
+                .map(ProtocolVersionRow::from),
+            timestamp: block.data.scheduled_time.clone(),
+        }
+    }
+}
+
+impl From<&v2::ProtocolVersionStableV2> for ProtocolVersionRow {
+    fn from(version: &v2::ProtocolVersionStableV2) -> Self {
+        Self {
+            transaction: version.transaction.as_u64() as i32,
+            network: version.network.as_u64() as i32,
+            patch: version.patch.as_u64() as i32,
+        }
+    }
+}
+
+impl UserCommandRow {
+    fn new(
+        sequence_no: i32,
+        command: &v2::MinaBaseSignedCommandStableV2,
+        status: &TransactionStatus,
+    ) -> Result<Self, ArchiveError> {
+        let common = &command.payload.common;
+        let (command_type, receiver, amount) = match &command.payload.body {
+            v2::MinaBaseSignedCommandPayloadBodyStableV2::Payment(payment) => (
+                "payment",
+                &payment.receiver_pk,
+                Some(payment.amount.as_u64().to_string()),
+            ),
+            v2::MinaBaseSignedCommandPayloadBodyStableV2::StakeDelegation(
+                v2::MinaBaseStakeDelegationStableV2::SetDelegate { new_delegate },
+            ) => ("delegation", new_delegate, None),
+        };
+        Ok(Self {
+            sequence_no,
+            command_type,
+            fee_payer: common.fee_payer_pk.to_string(),
+            receiver: receiver.to_string(),
+            nonce: common.nonce.as_u32().into(),
+            amount,
+            fee: common.fee.as_u64().to_string(),
+            valid_until: common.valid_until.as_u32().into(),
+            memo: common.memo.to_base58check(),
+            hash: command.hash().map_err(invalid_block)?.to_string(),
+            status: status_str(status),
+            failure_reason: failure_reason(status),
+        })
+    }
+}
+
+impl InternalCommandRow {
+    fn fee_transfer(
+        command_type: &'static str,
+        (sequence_no, secondary_sequence_no): (i32, i32),
+        fee_transfer: &v2::MinaBaseFeeTransferSingleStableV2,
+        status: &TransactionStatus,
+    ) -> Result<Self, ArchiveError> {
+        Ok(Self {
+            sequence_no,
+            secondary_sequence_no,
+            command_type,
+            receiver: fee_transfer.receiver_pk.to_string(),
+            fee: fee_transfer.fee.as_u64().to_string(),
+            hash: fee_transfer.hash().map_err(invalid_block)?.to_string(),
+            status: status_str(status),
+            failure_reason: failure_reason(status),
+        })
+    }
+
+    fn coinbase(
+        sequence_no: i32,
+        coinbase: &v2::MinaBaseCoinbaseStableV1,
+        status: &TransactionStatus,
+    ) -> Result<Self, ArchiveError> {
+        Ok(Self {
+            sequence_no,
+            secondary_sequence_no: 0,
+            command_type: "coinbase",
+            receiver: coinbase.receiver.to_string(),
+            fee: coinbase.amount.as_u64().to_string(),
+            hash: coinbase.hash().map_err(invalid_block)?.to_string(),
+            status: status_str(status),
+            failure_reason: failure_reason(status),
+        })
+    }
+}
+
+fn status_str(status: &TransactionStatus) -> &'static str {
+    match status {
+        TransactionStatus::Applied => "applied",
+        TransactionStatus::Failed(_) => "failed",
+    }
+}
+
+fn failure_reason(status: &TransactionStatus) -> Option<String> {
+    match status {
+        TransactionStatus::Applied => None,
+        TransactionStatus::Failed(failures) => {
+            failures.iter().flatten().next().map(|f| f.to_string())
+        }
+    }
+}
+
+impl ZkappCommandRow {
+    fn new(
+        sequence_no: i32,
+        command: &v2::MinaBaseZkappCommandTStableV1WireStableV1,
+        status: &TransactionStatus,
+    ) -> Result<Self, ArchiveError> {
+        let fee_payer = &command.fee_payer.body;
+        let mut account_updates = vec![];
+        for update in command.account_updates.iter() {
+            push_account_updates(&update.elt, 0, &mut account_updates)?;
+        }
+        Ok(Self {
+            sequence_no,
+            fee_payer: ZkappFeePayerBodyRow {
+                public_key: fee_payer.public_key.to_string(),
+                fee: fee_payer.fee.as_u64().to_string(),
+                valid_until: fee_payer
+                    .valid_until
+                    .as_ref()
+                    .map(|slot| slot.as_u32().into()),
+                nonce: fee_payer.nonce.as_u32().into(),
+            },
+            account_updates,
+            memo: command.memo.to_base58check(),
+            hash: command.hash().map_err(invalid_block)?.to_string(),
+            status: status_str(status),
+            // Failures are indexed by the account update, the fee payer being
+            // the first one, and only updates which failed are listed.
+            failure_reasons: match status {
+                TransactionStatus::Applied => vec![],
+                TransactionStatus::Failed(failures) => failures
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, failures)| !failures.is_empty())
+                    .map(|(index, failures)| ZkappAccountUpdateFailuresRow {
+                        index: index as i32,
+                        failures: failures.iter().map(|f| f.to_string()).collect(),
+                    })
+                    .collect(),
+            },
+        })
+    }
+}
+
+fn push_account_updates(
+    tree: &v2::MinaBaseZkappCommandTStableV1WireStableV1AccountUpdatesAA,
+    call_depth: i32,
+    rows: &mut Vec<ZkappAccountUpdateBodyRow>,
+) -> Result<(), ArchiveError> {
+    rows.push(ZkappAccountUpdateBodyRow::new(
+        &tree.account_update.body,
+        call_depth,
+    )?);
+    for call in tree.calls.iter() {
+        push_account_updates(&call.elt, call_depth + 1, rows)?;
+    }
+    Ok(())
+}
+
+impl ZkappAccountUpdateBodyRow {
+    fn new(
+        body: &v2::MinaBaseAccountUpdateBodyStableV1,
+        call_depth: i32,
+    ) -> Result<Self, ArchiveError> {
+        use v2::MinaBaseAccountUpdateAuthorizationKindStableV1 as AuthorizationKind;
+        use v2::MinaBaseAccountUpdateMayUseTokenStableV1 as MayUseToken;
+
+        let (authorization_kind, verification_key_hash) = match &body.authorization_kind {
+            AuthorizationKind::NoneGiven => ("None_given", None),
+            AuthorizationKind::Signature => ("Signature", None),
+            AuthorizationKind::Proof(hash) => ("Proof", Some(hash.to_decimal())),
+        };
+        Ok(Self {
+            account: AccountIdRow {
+                public_key: body.public_key.to_string(),
+                token_id: body.token_id.to_string(),
+            },
+            update: ZkappUpdatesRow::new(&body.update)?,
+            balance_change: signed_amount(&body.balance_change),
+            increment_nonce: body.increment_nonce,
+            events: fields_lists(&body.events),
+            actions: fields_lists(&body.actions),
+            call_data: body.call_data.to_decimal(),
+            call_depth,
+            network_precondition: ZkappNetworkPreconditionRow::new(&body.preconditions.network),
+            account_precondition: ZkappAccountPreconditionRow::new(&body.preconditions.account.0),
+            valid_while_precondition: global_slot_bounds(&body.preconditions.valid_while),
+            use_full_commitment: body.use_full_commitment,
+            implicit_account_creation_fee: body.implicit_account_creation_fee,
+            may_use_token: match body.may_use_token {
+                MayUseToken::No => "No",
+                MayUseToken::ParentsOwnToken => "ParentsOwnToken",
+                MayUseToken::InheritFromParent => "InheritFromParent",
+            },
+            authorization_kind,
+            verification_key_hash,
+        })
+    }
+}
+
+fn signed_amount(amount: &v2::MinaStateBlockchainStateValueStableV2SignedAmount) -> String {
+    match amount.sgn {
+        v2::SgnStableV1::Pos => amount.magnitude.as_u64().to_string(),
+        v2::SgnStableV1::Neg => format!("-{}", amount.magnitude.as_u64()),
+    }
+}
+
+fn fields_lists(events: &v2::MinaBaseAccountUpdateBodyEventsStableV1) -> Vec<Vec<String>> {
+    events
+        .0
+        .iter()
+        .map(|fields| fields.iter().map(BigInt::to_decimal).collect())
+        .collect()
+}
+
+impl ZkappUpdatesRow {
+    fn new(update: &v2::MinaBaseAccountUpdateUpdateStableV1) -> Result<Self, ArchiveError> {
+        use v2::{
+            MinaBaseAccountUpdateUpdateStableV1AppStateA as AppState,
+            MinaBaseAccountUpdateUpdateStableV1Delegate as Delegate,
+            MinaBaseAccountUpdateUpdateStableV1Permissions as Permissions,
+            MinaBaseAccountUpdateUpdateStableV1Timing as Timing,
+            MinaBaseAccountUpdateUpdateStableV1TokenSymbol as TokenSymbol,
+            MinaBaseAccountUpdateUpdateStableV1VerificationKey as VerificationKey,
+            MinaBaseAccountUpdateUpdateStableV1VotingFor as VotingFor,
+            MinaBaseAccountUpdateUpdateStableV1ZkappUri as ZkappUri,
+        };
+
+        Ok(Self {
+            app_state: update.app_state.0.each_ref().map(|field| match field {
+                AppState::Set(field) => Some(field.to_decimal()),
+                AppState::Keep => None,
+            }),
+            delegate: match &update.delegate {
+                Delegate::Set(delegate) => Some(delegate.to_string()),
+                Delegate::Keep => None,
+            },
+            verification_key: match &update.verification_key {
+                VerificationKey::Set(vk) => Some(VerificationKeyRow::new(vk)?),
+                VerificationKey::Keep => None,
+            },
+            permissions: match &update.permissions {
+                Permissions::Set(permissions) => Some(PermissionsRow::from(&**permissions)),
+                Permissions::Keep => None,
+            },
+            zkapp_uri: match &update.zkapp_uri {
+                ZkappUri::Set(uri) => Some(uri.to_string()),
+                ZkappUri::Keep => None,
+            },
+            token_symbol: match &update.token_symbol {
+                TokenSymbol::Set(symbol) => Some(symbol.to_string()),
+                TokenSymbol::Keep => None,
+            },
+            timing: match &update.timing {
+                Timing::Set(timing) => Some(TimingRow {
+                    initial_minimum_balance: timing.initial_minimum_balance.0.as_u64().to_string(),
+                    cliff_time: timing.cliff_time.as_u32().into(),
+                    cliff_amount: timing.cliff_amount.as_u64().to_string(),
+                    vesting_period: timing.vesting_period.as_u32().into(),
+                    vesting_increment: timing.vesting_increment.as_u64().to_string(),
+                }),
+                Timing::Keep => None,
+            },
+            voting_for: match &update.voting_for {
+                VotingFor::Set(state_hash) => Some(state_hash.to_string()),
+                VotingFor::Keep => None,
+            },
+        })
+    }
+}
+
+impl ZkappNetworkPreconditionRow {
+    fn new(network: &v2::MinaBaseZkappPreconditionProtocolStateStableV1) -> Self {
+        use v2::MinaBaseZkappPreconditionProtocolStateStableV1SnarkedLedgerHash as LedgerHash;
+
+        Self {
+            snarked_ledger_hash: match &network.snarked_ledger_hash {
+                LedgerHash::Check(hash) => Some(hash.to_string()),
+                LedgerHash::Ignore => None,
+            },
+            blockchain_length: length_bounds(&network.blockchain_length),
+            min_window_density: length_bounds(&network.min_window_density),
+            total_currency: amount_bounds(&network.total_currency),
+            global_slot_since_genesis: global_slot_bounds(&network.global_slot_since_genesis),
+            staking_epoch_data: ZkappEpochDataRow::new(&network.staking_epoch_data),
+            next_epoch_data: ZkappEpochDataRow::new(&network.next_epoch_data),
+        }
+    }
+}
+
+impl ZkappEpochDataRow {
+    fn new(epoch_data: &v2::MinaBaseZkappPreconditionProtocolStateEpochDataStableV1) -> Self {
+        use v2::{
+            MinaBaseZkappPreconditionProtocolStateEpochDataStableV1EpochSeed as EpochSeed,
+            MinaBaseZkappPreconditionProtocolStateEpochDataStableV1StartCheckpoint as Checkpoint,
+            MinaBaseZkappPreconditionProtocolStateStableV1SnarkedLedgerHash as LedgerHash,
+        };
+        let checkpoint = |checkpoint: &Checkpoint| match checkpoint {
+            Checkpoint::Check(state_hash) => Some(state_hash.to_string()),
+            Checkpoint::Ignore => None,
+        };
+
+        Self {
+            ledger_hash: match &epoch_data.ledger.hash {
+                LedgerHash::Check(hash) => Some(hash.to_string()),
+                LedgerHash::Ignore => None,
+            },
+            total_currency: amount_bounds(&epoch_data.ledger.total_currency),
+            seed: match &epoch_data.seed {
+                EpochSeed::Check(seed) => Some(seed.to_string()),
+                EpochSeed::Ignore => None,
+            },
+            start_checkpoint: checkpoint(&epoch_data.start_checkpoint),
+            lock_checkpoint: checkpoint(&epoch_data.lock_checkpoint),
+            epoch_length: length_bounds(&epoch_data.epoch_length),
+        }
+    }
+}
+
+impl ZkappAccountPreconditionRow {
+    fn new(account: &v2::MinaBaseZkappPreconditionAccountStableV2) -> Self {
+        use v2::{
+            MinaBaseZkappPreconditionAccountStableV2Balance as Balance,
+            MinaBaseZkappPreconditionAccountStableV2Delegate as Delegate,
+            MinaBaseZkappPreconditionAccountStableV2ProvedState as Flag,
+            MinaBaseZkappPreconditionAccountStableV2ReceiptChainHash as ReceiptChainHash,
+            MinaBaseZkappPreconditionAccountStableV2StateA as Field,
+        };
+        let field = |field: &Field| match field {
+            Field::Check(field) => Some(field.to_decimal()),
+            Field::Ignore => None,
+        };
+        let flag = |flag: &Flag| match flag {
+            Flag::Check(flag) => Some(*flag),
+            Flag::Ignore => None,
+        };
+
+        Self {
+            balance: match &account.balance {
+                Balance::Check(bounds) => Some((
+                    bounds.lower.0.as_u64().to_string(),
+                    bounds.upper.0.as_u64().to_string(),
+                )),
+                Balance::Ignore => None,
+            },
+            nonce: length_bounds(&account.nonce),
+            receipt_chain_hash: match &account.receipt_chain_hash {
+                ReceiptChainHash::Check(hash) => {
+                    Some(v2::ReceiptChainHash::from(hash.clone()).to_string())
+                }
+                ReceiptChainHash::Ignore => None,
+            },
+            delegate: match &account.delegate {
+                Delegate::Check(delegate) => Some(delegate.to_string()),
+                Delegate::Ignore => None,
+            },
+            state: account.state.0.each_ref().map(field),
+            action_state: field(&account.action_state),
+            proved_state: flag(&account.proved_state),
+            is_new: flag(&account.is_new),
+        }
+    }
+}
+
+fn length_bounds(
+    bounds: &v2::MinaBaseZkappPreconditionProtocolStateStableV1Length,
+) -> Option<(i64, i64)> {
+    match bounds {
+        v2::MinaBaseZkappPreconditionProtocolStateStableV1Length::Check(bounds) => {
+            Some((bounds.lower.as_u32().into(), bounds.upper.as_u32().into()))
+        }
+        v2::MinaBaseZkappPreconditionProtocolStateStableV1Length::Ignore => None,
+    }
+}
+
+fn amount_bounds(
+    bounds: &v2::MinaBaseZkappPreconditionProtocolStateStableV1Amount,
+) -> Option<(String, String)> {
+    match bounds {
+        v2::MinaBaseZkappPreconditionProtocolStateStableV1Amount::Check(bounds) => Some((
+            bounds.lower.as_u64().to_string(),
+            bounds.upper.as_u64().to_string(),
+        )),
+        v2::MinaBaseZkappPreconditionProtocolStateStableV1Amount::Ignore => None,
+    }
+}
+
+fn global_slot_bounds(
+    bounds: &v2::MinaBaseZkappPreconditionProtocolStateStableV1GlobalSlot,
+) -> Option<(i64, i64)> {
+    match bounds {
+        v2::MinaBaseZkappPreconditionProtocolStateStableV1GlobalSlot::Check(bounds) => {
+            Some((bounds.lower.as_u32().into(), bounds.upper.as_u32().into()))
+        }
+        v2::MinaBaseZkappPreconditionProtocolStateStableV1GlobalSlot::Ignore => None,
+    }
+}
+
+impl AccountAccessedRow {
+    fn new(
+        ledger_index: u64,
+        account: &v2::MinaBaseAccountBinableArgStableV2,
+    ) -> Result<Self, ArchiveError> {
+        Ok(Self {
+            ledger_index: ledger_index as i32,
+            account: AccountIdRow {
+                public_key: account.public_key.to_string(),
+                token_id: account.token_id.to_string(),
+            },
+            token_symbol: String::from_utf8_lossy(&account.token_symbol).into_owned(),
+            balance: account.balance.0.as_u64().to_string(),
+            nonce: account.nonce.as_u32().into(),
+            receipt_chain_hash: v2::ReceiptChainHash::from(account.receipt_chain_hash.clone())
+                .to_string(),
+            delegate: account.delegate.as_ref().map(ToString::to_string),
+            voting_for: account.voting_for.to_string(),
+            timing: match &account.timing {
+                v2::MinaBaseAccountTimingStableV2::Untimed => TimingRow {
+                    initial_minimum_balance: "0".to_owned(),
+                    cliff_time: 0,
+                    cliff_amount: "0".to_owned(),
+                    vesting_period: 0,
+                    vesting_increment: "0".to_owned(),
+                },
+                v2::MinaBaseAccountTimingStableV2::Timed {
+                    initial_minimum_balance,
+                    cliff_time,
+                    cliff_amount,
+                    vesting_period,
+                    vesting_increment,
+                } => TimingRow {
+                    initial_minimum_balance: initial_minimum_balance.0.as_u64().to_string(),
+                    cliff_time: cliff_time.as_u32().into(),
+                    cliff_amount: cliff_amount.as_u64().to_string(),
+                    vesting_period: vesting_period.as_u32().into(),
+                    vesting_increment: vesting_increment.as_u64().to_string(),
+                },
+            },
+            permissions: PermissionsRow::from(&account.permissions),
+            zkapp: account
+                .zkapp
+                .as_ref()
+                .map(|zkapp| -> Result<_, ArchiveError> {
+                    Ok(ZkappAccountRow {
+                        app_state: zkapp.app_state.0.each_ref().map(BigInt::to_decimal),
+                        verification_key: zkapp
+                            .verification_key
+                            .as_ref()
+                            .map(VerificationKeyRow::new)
+                            .transpose()?,
+                        zkapp_version: zkapp.zkapp_version.0.as_u32().into(),
+                        action_state: zkapp.action_state.0.each_ref().map(BigInt::to_decimal),
+                        last_action_slot: zkapp.last_action_slot.as_u32().into(),
+                        proved_state: zkapp.proved_state,
+                        zkapp_uri: zkapp.zkapp_uri.to_string(),
+                    })
+                })
+                .transpose()?,
+        })
+    }
+}
+
+impl From<&v2::MinaBaseAccountIdStableV2> for AccountIdRow {
+    fn from(account_id: &v2::MinaBaseAccountIdStableV2) -> Self {
+        let v2::MinaBaseAccountIdStableV2(public_key, token) = account_id;
+        Self {
+            public_key: public_key.to_string(),
+            token_id: v2::TokenIdKeyHash::from(token.clone()).to_string(),
+        }
+    }
+}
+
+impl From<&v2::MinaBasePermissionsStableV2> for PermissionsRow {
+    fn from(permissions: &v2::MinaBasePermissionsStableV2) -> Self {
+        let (set_verification_key_auth, txn_version) = &permissions.set_verification_key;
+        Self {
+            edit_state: auth_required(&permissions.edit_state),
+            send: auth_required(&permissions.send),
+            receive: auth_required(&permissions.receive),
+            access: auth_required(&permissions.access),
+            set_delegate: auth_required(&permissions.set_delegate),
+            set_permissions: auth_required(&permissions.set_permissions),
+            set_verification_key_auth: auth_required(set_verification_key_auth),
+            set_verification_key_txn_version: txn_version.as_u32() as i32,
+            set_zkapp_uri: auth_required(&permissions.set_zkapp_uri),
+            edit_action_state: auth_required(&permissions.edit_action_state),
+            set_token_symbol: auth_required(&permissions.set_token_symbol),
+            increment_nonce: auth_required(&permissions.increment_nonce),
+            set_voting_for: auth_required(&permissions.set_voting_for),
+            set_timing: auth_required(&permissions.set_timing),
+        }
+    }
+}
+
+fn auth_required(auth: &v2::MinaBasePermissionsAuthRequiredStableV2) -> &'static str {
+    match auth {
+        v2::MinaBasePermissionsAuthRequiredStableV2::None => "none",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Either => "either",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Proof => "proof",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Signature => "signature",
+        v2::MinaBasePermissionsAuthRequiredStableV2::Impossible => "impossible",
+    }
+}
+
+impl VerificationKeyRow {
+    fn new(vk: &v2::MinaBaseVerificationKeyWireStableV1) -> Result<Self, ArchiveError> {
+        Ok(Self {
+            verification_key: vk.to_base64().map_err(invalid_block)?,
+            hash: VerificationKey::try_from(vk)
+                .map_err(invalid_block)?
+                .hash()
+                .to_decimal(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use ledger::{scan_state::currency::Balance, Account, AccountId};
+
+    use super::super::precomputed::test_precomputed_block;
+    use super::*;
+
+    #[test]
+    fn test_block_rows() {
+        let mut block = test_precomputed_block(10);
+        let account = Account::create_with(AccountId::empty(), Balance::from_u64(1_000));
+        block.data.accounts_accessed = vec![(7, (&account).into())];
+        let state_hash = block.data.protocol_state.try_hash().unwrap();
+        let consensus_state = &block.data.protocol_state.body.consensus_state;
+
+        let rows = BlockRows::new(&state_hash, &block).unwrap();
+
+        assert_eq!(rows.block.state_hash, state_hash.to_string());
+        assert_eq!(
+            rows.block.parent_hash,
+            block.data.protocol_state.previous_state_hash.to_string()
+        );
+        assert_eq!(rows.block.height, 10);
+        assert_eq!(
+            rows.block.k,
+            i64::from(block.data.protocol_state.body.constants.k.as_u32())
+        );
+        assert_eq!(
+            rows.block.creator,
+            consensus_state.block_creator.to_string()
+        );
+        assert_eq!(
+            rows.block.protocol_version,
+            ProtocolVersionRow::from(&block.data.protocol_version)
+        );
+
+        // Every transaction gets its own sequence number, in order.
+        let user_sequence_nos = rows.user_commands.iter().map(|row| row.sequence_no);
+        let zkapp_sequence_nos = rows.zkapp_commands.iter().map(|row| row.sequence_no);
+        let commands_count = user_sequence_nos.len() + zkapp_sequence_nos.len();
+        let internal_sequence_nos = rows
+            .internal_commands
+            .iter()
+            .map(|row| row.sequence_no)
+            .collect::<BTreeSet<_>>();
+        let sequence_nos = user_sequence_nos
+            .chain(zkapp_sequence_nos)
+            .chain(internal_sequence_nos.iter().copied())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            sequence_nos.len(),
+            commands_count + internal_sequence_nos.len()
+        );
+        assert!(sequence_nos
+            .iter()
+            .copied()
+            .eq(0..sequence_nos.len() as i32));
+        assert_eq!(
+            rows.user_commands.len(),
+            block.data.staged_ledger_diff.diff.0.commands.len()
+                + block
+                    .data
+                    .staged_ledger_diff
+                    .diff
+                    .1
+                    .as_ref()
+                    .map_or(0, |diff| diff.commands.len())
+                - rows.zkapp_commands.len()
+        );
+
+        // Coinbase goes to the coinbase receiver, after its fee transfer.
+        let coinbases = rows
+            .internal_commands
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.command_type == "coinbase")
+            .collect::<Vec<_>>();
+        assert!(!coinbases.is_empty());
+        for (i, coinbase) in coinbases {
+            assert_eq!(
+                coinbase.receiver,
+                consensus_state.coinbase_receiver.to_string()
+            );
+            assert_eq!(coinbase.secondary_sequence_no, 0);
+            if i > 0 {
+                let previous = &rows.internal_commands[i - 1];
+                if previous.sequence_no == coinbase.sequence_no {
+                    assert_eq!(previous.command_type, "fee_transfer_via_coinbase");
+                }
+            }
+        }
+        // Secondary sequence numbers of fee transfers tell apart the two
+        // transfers of one transaction.
+        for row in &rows.internal_commands {
+            let same_transaction = rows
+                .internal_commands
+                .iter()
+                .filter(|other| {
+                    other.command_type == row.command_type && other.sequence_no == row.sequence_no
+                })
+                .map(|other| other.secondary_sequence_no)
+                .collect::<Vec<_>>();
+            assert!(same_transaction
+                .iter()
+                .copied()
+                .eq(0..same_transaction.len() as i32));
+        }
+
+        assert_eq!(rows.accounts_accessed.len(), 1);
+        let accessed = &rows.accounts_accessed[0];
+        assert_eq!(accessed.ledger_index, 7);
+        assert_eq!(accessed.balance, "1000");
+        assert_eq!(
+            accessed.account.public_key,
+            account.public_key.into_address()
+        );
+        assert_eq!(accessed.timing.cliff_amount, "0");
+        assert_eq!(accessed.permissions.send, "signature");
+        assert!(accessed.zkapp.is_none());
+    }
+}
@@ -0,0 +1,1239 @@
+//! Writer for the Mina archive (postgres) database, using the schema from
+//! `src/app/archive/create_schema.sql` of the Mina repository.
+//!
+//! Writes everything the OCaml archive writes when importing a precomputed
+//! block: the block, user, zkapp and internal commands with their statuses,
+//! and accessed and created accounts. The block is converted to rows first
+//! (see [`BlockRows`]), so that only the inserts happen in the transaction.
+
+use mina_p2p_messages::v2;
+use sqlx::{
+    postgres::{PgArguments, PgPoolOptions},
+    Arguments, PgConnection, PgPool,
+};
+
+use super::{
+    rows::{
+        AccountAccessedRow, AccountIdRow, BlockRow, BlockRows, EpochDataRow, InternalCommandRow,
+        PermissionsRow, ProtocolVersionRow, UserCommandRow, VerificationKeyRow,
+        ZkappAccountPreconditionRow, ZkappAccountRow, ZkappAccountUpdateBodyRow, ZkappCommandRow,
+        ZkappEpochDataRow, ZkappNetworkPreconditionRow, ZkappUpdatesRow,
+    },
+    ArchiveError, ArchiveWriter, PrecomputedBlock,
+};
+
+/// Tokens used in the block, with their owners.
+type TokensUsed = [(String, Option<AccountIdRow>)];
+
+pub struct ArchiveDatabase {
+    runtime: tokio::runtime::Runtime,
+    pool: PgPool,
+}
+
+impl ArchiveDatabase {
+    /// Doesn't connect yet, so that the node can start while the database
+    /// is unavailable.
+    pub fn connect(url: &str) -> Result<Self, ArchiveError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let pool = {
+            let _guard = runtime.enter();
+            PgPoolOptions::new()
+                .max_connections(1)
+                .connect_lazy(url)
+                .map_err(db_error)?
+        };
+        Ok(Self { runtime, pool })
+    }
+}
+
+impl ArchiveWriter for ArchiveDatabase {
+    fn write(
+        &mut self,
+        state_hash: &v2::StateHash,
+        block: &PrecomputedBlock,
+    ) -> Result<(), ArchiveError> {
+        let rows = BlockRows::new(state_hash, block)?;
+        self.runtime
+            .block_on(write_block(&self.pool, &rows))
+            .map_err(db_error)
+    }
+}
+
+fn db_error(err: sqlx::Error) -> ArchiveError {
+    ArchiveError::Database(err.to_string())
+}
+
+/// Writes the block in a single transaction. Blocks which are already in
+/// the database are skipped, so retrying a write is safe.
+async fn write_block(pool: &PgPool, rows: &BlockRows) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let existing: Option<i32> = sqlx::query_scalar("SELECT id FROM blocks WHERE state_hash = $1")
+        .bind(&rows.block.state_hash)
+        .fetch_optional(&mut *tx)
+        .await?;
+    if existing.is_some() {
+        return Ok(());
+    }
+
+    let block_id = insert_block(&mut tx, &rows.block).await?;
+    link_children(&mut tx, block_id, &rows.block).await?;
+    insert_user_commands(&mut tx, block_id, &rows.user_commands).await?;
+    insert_internal_commands(&mut tx, block_id, &rows.internal_commands).await?;
+    insert_zkapp_commands(&mut tx, block_id, &rows.zkapp_commands, &rows.tokens_used).await?;
+    insert_accounts_accessed(
+        &mut tx,
+        block_id,
+        &rows.accounts_accessed,
+        &rows.tokens_used,
+    )
+    .await?;
+    insert_accounts_created(&mut tx, block_id, rows).await?;
+    update_chain_status(&mut tx, block_id, &rows.block).await?;
+
+    tx.commit().await
+}
+
+/// Children written before their parent (e.g. when the parent was spooled)
+/// only have the `parent_hash`, point them to the parent row now.
+async fn link_children(
+    conn: &mut PgConnection,
+    block_id: i32,
+    block: &BlockRow,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE blocks SET parent_id = $1 WHERE parent_hash = $2 AND parent_id IS NULL")
+        .bind(block_id)
+        .bind(&block.state_hash)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Same as the OCaml archive: once the block makes its ancestor at
+/// `height - k` final, that ancestor and the pending blocks between it and
+/// the highest canonical block on the block's chain become canonical, and
+/// the other blocks at those heights are orphaned.
+async fn update_chain_status(
+    conn: &mut PgConnection,
+    block_id: i32,
+    block: &BlockRow,
+) -> Result<(), sqlx::Error> {
+    let final_height = block.height - block.k;
+    if final_height < 1 {
+        return Ok(());
+    }
+    let canonical_height: i64 = sqlx::query_scalar(
+        "SELECT COALESCE(MAX(height), 0) FROM blocks WHERE chain_status = 'canonical'",
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    if final_height <= canonical_height {
+        return Ok(());
+    }
+
+    let chain: Vec<(i32, i64)> = sqlx::query_as(
+        "WITH RECURSIVE chain AS ( \
+         SELECT id, parent_id, height FROM blocks WHERE id = $1 \
+         UNION ALL \
+         SELECT b.id, b.parent_id, b.height FROM blocks b \
+         JOIN chain ON b.id = chain.parent_id WHERE b.height > $3 \
+         ) \
+         SELECT id, height FROM chain WHERE height <= $2",
+    )
+    .bind(block_id)
+    .bind(final_height)
+    .bind(canonical_height)
+    .fetch_all(&mut *conn)
+    .await?;
+    let (ids, heights): (Vec<i32>, Vec<i64>) = chain.into_iter().unzip();
+
+    sqlx::query("UPDATE blocks SET chain_status = 'canonical' WHERE id = ANY($1)")
+        .bind(&ids)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        "UPDATE blocks SET chain_status = 'orphaned' \
+         WHERE height = ANY($1) AND NOT (id = ANY($2)) AND chain_status = 'pending'",
+    )
+    .bind(&heights)
+    .bind(&ids)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+async fn insert_block(conn: &mut PgConnection, block: &BlockRow) -> Result<i32, sqlx::Error> {
+    let creator_id = public_key_id(conn, &block.creator).await?;
+    let block_winner_id = public_key_id(conn, &block.block_winner).await?;
+    let snarked_ledger_hash_id = snarked_ledger_hash_id(conn, &block.snarked_ledger_hash).await?;
+    let staking_epoch_data_id = epoch_data_id(conn, &block.staking_epoch_data).await?;
+    let next_epoch_data_id = epoch_data_id(conn, &block.next_epoch_data).await?;
+    let protocol_version_id = protocol_version_id(conn, &block.protocol_version).await?;
+    let proposed_protocol_version_id = match &block.proposed_protocol_version {
+        Some(version) => Some(protocol_version_id(conn, version).await?),
+        None => None,
+    };
+
+    sqlx::query_scalar(
+        "INSERT INTO blocks (state_hash, parent_id, parent_hash, creator_id, block_winner_id, \
+         last_vrf_output, snarked_ledger_hash_id, staking_epoch_data_id, next_epoch_data_id, \
+         min_window_density, sub_window_densities, total_currency, ledger_hash, height, \
+         global_slot_since_hard_fork, global_slot_since_genesis, protocol_version_id, \
+         proposed_protocol_version_id, timestamp, chain_status) \
+         VALUES ($1, (SELECT id FROM blocks WHERE state_hash = $2), $2, $3, $4, $5, $6, $7, $8, \
+         $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, 'pending') \
+         RETURNING id",
+    )
+    .bind(&block.state_hash)
+    .bind(&block.parent_hash)
+    .bind(creator_id)
+    .bind(block_winner_id)
+    .bind(&block.last_vrf_output)
+    .bind(snarked_ledger_hash_id)
+    .bind(staking_epoch_data_id)
+    .bind(next_epoch_data_id)
+    .bind(block.min_window_density)
+    .bind(&block.sub_window_densities)
+    .bind(&block.total_currency)
+    .bind(&block.ledger_hash)
+    .bind(block.height)
+    .bind(block.global_slot_since_hard_fork)
+    .bind(block.global_slot_since_genesis)
+    .bind(protocol_version_id)
+    .bind(proposed_protocol_version_id)
+    .bind(&block.timestamp)
+    .fetch_one(conn)
+    .await
+}
+
+async fn insert_user_commands(
+    conn: &mut PgConnection,
+    block_id: i32,
+    commands: &[UserCommandRow],
+) -> Result<(), sqlx::Error> {
+    for command in commands {
+        let user_command_id = user_command_id(conn, command).await?;
+        sqlx::query(
+            "INSERT INTO blocks_user_commands \
+             (block_id, user_command_id, sequence_no, status, failure_reason) \
+             VALUES ($1, $2, $3, $4::transaction_status, $5)",
+        )
+        .bind(block_id)
+        .bind(user_command_id)
+        .bind(command.sequence_no)
+        .bind(command.status)
+        .bind(&command.failure_reason)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn user_command_id(
+    conn: &mut PgConnection,
+    command: &UserCommandRow,
+) -> Result<i32, sqlx::Error> {
+    let existing: Option<i32> = sqlx::query_scalar("SELECT id FROM user_commands WHERE hash = $1")
+        .bind(&command.hash)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let fee_payer_id = public_key_id(conn, &command.fee_payer).await?;
+    let receiver_id = public_key_id(conn, &command.receiver).await?;
+
+    sqlx::query_scalar(
+        "INSERT INTO user_commands (command_type, fee_payer_id, source_id, receiver_id, nonce, \
+         amount, fee, valid_until, memo, hash) \
+         VALUES ($1::user_command_type, $2, $2, $3, $4, $5, $6, $7, $8, $9) \
+         RETURNING id",
+    )
+    .bind(command.command_type)
+    .bind(fee_payer_id)
+    .bind(receiver_id)
+    .bind(command.nonce)
+    .bind(&command.amount)
+    .bind(&command.fee)
+    .bind(command.valid_until)
+    .bind(&command.memo)
+    .bind(&command.hash)
+    .fetch_one(conn)
+    .await
+}
+
+async fn insert_internal_commands(
+    conn: &mut PgConnection,
+    block_id: i32,
+    commands: &[InternalCommandRow],
+) -> Result<(), sqlx::Error> {
+    for command in commands {
+        let internal_command_id = internal_command_id(conn, command).await?;
+        sqlx::query(
+            "INSERT INTO blocks_internal_commands (block_id, internal_command_id, sequence_no, \
+             secondary_sequence_no, status, failure_reason) \
+             VALUES ($1, $2, $3, $4, $5::transaction_status, $6)",
+        )
+        .bind(block_id)
+        .bind(internal_command_id)
+        .bind(command.sequence_no)
+        .bind(command.secondary_sequence_no)
+        .bind(command.status)
+        .bind(&command.failure_reason)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn internal_command_id(
+    conn: &mut PgConnection,
+    command: &InternalCommandRow,
+) -> Result<i32, sqlx::Error> {
+    let receiver_id = public_key_id(conn, &command.receiver).await?;
+    sqlx::query(
+        "INSERT INTO internal_commands (command_type, receiver_id, fee, hash) \
+         VALUES ($1::internal_command_type, $2, $3, $4) \
+         ON CONFLICT (hash, command_type) DO NOTHING",
+    )
+    .bind(command.command_type)
+    .bind(receiver_id)
+    .bind(&command.fee)
+    .bind(&command.hash)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar(
+        "SELECT id FROM internal_commands \
+         WHERE hash = $1 AND command_type = $2::internal_command_type",
+    )
+    .bind(&command.hash)
+    .bind(command.command_type)
+    .fetch_one(conn)
+    .await
+}
+
+async fn insert_zkapp_commands(
+    conn: &mut PgConnection,
+    block_id: i32,
+    commands: &[ZkappCommandRow],
+    tokens: &TokensUsed,
+) -> Result<(), sqlx::Error> {
+    for command in commands {
+        let zkapp_command_id = zkapp_command_id(conn, command, tokens).await?;
+        let failure_reasons_ids = if command.failure_reasons.is_empty() {
+            None
+        } else {
+            let mut ids = vec![];
+            for failures in &command.failure_reasons {
+                let id = select_or_insert(
+                    conn,
+                    "zkapp_account_update_failures",
+                    &[
+                        ("index", Value::Int(Some(failures.index))),
+                        ("failures", Value::TextArray(&failures.failures)),
+                    ],
+                )
+                .await?;
+                ids.push(id);
+            }
+            Some(ids)
+        };
+
+        sqlx::query(
+            "INSERT INTO blocks_zkapp_commands \
+             (block_id, zkapp_command_id, sequence_no, status, failure_reasons_ids) \
+             VALUES ($1, $2, $3, $4::transaction_status, $5)",
+        )
+        .bind(block_id)
+        .bind(zkapp_command_id)
+        .bind(command.sequence_no)
+        .bind(command.status)
+        .bind(failure_reasons_ids)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn zkapp_command_id(
+    conn: &mut PgConnection,
+    command: &ZkappCommandRow,
+    tokens: &TokensUsed,
+) -> Result<i32, sqlx::Error> {
+    let existing: Option<i32> = sqlx::query_scalar("SELECT id FROM zkapp_commands WHERE hash = $1")
+        .bind(&command.hash)
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let fee_payer = &command.fee_payer;
+    let fee_payer_public_key_id = public_key_id(conn, &fee_payer.public_key).await?;
+    let fee_payer_body_id = select_or_insert(
+        conn,
+        "zkapp_fee_payer_body",
+        &[
+            ("public_key_id", Value::Int(Some(fee_payer_public_key_id))),
+            ("fee", Value::Text(Some(&fee_payer.fee))),
+            ("valid_until", Value::BigInt(fee_payer.valid_until)),
+            ("nonce", Value::BigInt(Some(fee_payer.nonce))),
+        ],
+    )
+    .await?;
+    let mut account_update_ids = vec![];
+    for body in &command.account_updates {
+        let body_id = zkapp_account_update_body_id(conn, body, tokens).await?;
+        let id = select_or_insert(
+            conn,
+            "zkapp_account_update",
+            &[("body_id", Value::Int(Some(body_id)))],
+        )
+        .await?;
+        account_update_ids.push(id);
+    }
+
+    sqlx::query_scalar(
+        "INSERT INTO zkapp_commands (zkapp_fee_payer_body_id, zkapp_account_updates_ids, memo, \
+         hash) VALUES ($1, $2, $3, $4) RETURNING id",
+    )
+    .bind(fee_payer_body_id)
+    .bind(account_update_ids)
+    .bind(&command.memo)
+    .bind(&command.hash)
+    .fetch_one(conn)
+    .await
+}
+
+async fn zkapp_account_update_body_id(
+    conn: &mut PgConnection,
+    body: &ZkappAccountUpdateBodyRow,
+    tokens: &TokensUsed,
+) -> Result<i32, sqlx::Error> {
+    let account_identifier_id = account_identifier_id(conn, &body.account, tokens).await?;
+    let update_id = zkapp_updates_id(conn, &body.update).await?;
+    let events_id = zkapp_events_id(conn, &body.events).await?;
+    let actions_id = zkapp_events_id(conn, &body.actions).await?;
+    let call_data_id = zkapp_field_id(conn, &body.call_data).await?;
+    let network_precondition_id =
+        zkapp_network_precondition_id(conn, &body.network_precondition).await?;
+    let account_precondition_id =
+        zkapp_account_precondition_id(conn, &body.account_precondition).await?;
+    let valid_while_precondition_id =
+        global_slot_bounds_id(conn, &body.valid_while_precondition).await?;
+    let verification_key_hash_id = match &body.verification_key_hash {
+        Some(hash) => Some(verification_key_hash_id(conn, hash).await?),
+        None => None,
+    };
+
+    select_or_insert(
+        conn,
+        "zkapp_account_update_body",
+        &[
+            (
+                "account_identifier_id",
+                Value::Int(Some(account_identifier_id)),
+            ),
+            ("update_id", Value::Int(Some(update_id))),
+            ("balance_change", Value::Text(Some(&body.balance_change))),
+            ("increment_nonce", Value::Bool(Some(body.increment_nonce))),
+            ("events_id", Value::Int(Some(events_id))),
+            ("actions_id", Value::Int(Some(actions_id))),
+            ("call_data_id", Value::Int(Some(call_data_id))),
+            ("call_depth", Value::Int(Some(body.call_depth))),
+            (
+                "zkapp_network_precondition_id",
+                Value::Int(Some(network_precondition_id)),
+            ),
+            (
+                "zkapp_account_precondition_id",
+                Value::Int(Some(account_precondition_id)),
+            ),
+            (
+                "zkapp_valid_while_precondition_id",
+                Value::Int(valid_while_precondition_id),
+            ),
+            (
+                "use_full_commitment",
+                Value::Bool(Some(body.use_full_commitment)),
+            ),
+            (
+                "implicit_account_creation_fee",
+                Value::Bool(Some(body.implicit_account_creation_fee)),
+            ),
+            (
+                "may_use_token",
+                Value::Enum("may_use_token", body.may_use_token),
+            ),
+            (
+                "authorization_kind",
+                Value::Enum("authorization_kind_type", body.authorization_kind),
+            ),
+            (
+                "verification_key_hash_id",
+                Value::Int(verification_key_hash_id),
+            ),
+        ],
+    )
+    .await
+}
+
+async fn zkapp_updates_id(
+    conn: &mut PgConnection,
+    update: &ZkappUpdatesRow,
+) -> Result<i32, sqlx::Error> {
+    let app_state_id = zkapp_states_id(conn, "zkapp_states_nullable", &update.app_state).await?;
+    let delegate_id = match &update.delegate {
+        Some(delegate) => Some(public_key_id(conn, delegate).await?),
+        None => None,
+    };
+    let verification_key_id = match &update.verification_key {
+        Some(vk) => Some(verification_key_id(conn, vk).await?),
+        None => None,
+    };
+    let permissions_id = match &update.permissions {
+        Some(permissions) => Some(permissions_id(conn, permissions).await?),
+        None => None,
+    };
+    let zkapp_uri_id = match &update.zkapp_uri {
+        Some(uri) => Some(zkapp_uri_id(conn, uri).await?),
+        None => None,
+    };
+    let token_symbol_id = match &update.token_symbol {
+        Some(symbol) => Some(token_symbol_id(conn, symbol).await?),
+        None => None,
+    };
+    let timing_id = match &update.timing {
+        Some(timing) => Some(
+            select_or_insert(
+                conn,
+                "zkapp_timing_info",
+                &[
+                    (
+                        "initial_minimum_balance",
+                        Value::Text(Some(&timing.initial_minimum_balance)),
+                    ),
+                    ("cliff_time", Value::BigInt(Some(timing.cliff_time))),
+                    ("cliff_amount", Value::Text(Some(&timing.cliff_amount))),
+                    ("vesting_period", Value::BigInt(Some(timing.vesting_period))),
+                    (
+                        "vesting_increment",
+                        Value::Text(Some(&timing.vesting_increment)),
+                    ),
+                ],
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let voting_for_id = match &update.voting_for {
+        Some(voting_for) => Some(voting_for_id(conn, voting_for).await?),
+        None => None,
+    };
+
+    select_or_insert(
+        conn,
+        "zkapp_updates",
+        &[
+            ("app_state_id", Value::Int(Some(app_state_id))),
+            ("delegate_id", Value::Int(delegate_id)),
+            ("verification_key_id", Value::Int(verification_key_id)),
+            ("permissions_id", Value::Int(permissions_id)),
+            ("zkapp_uri_id", Value::Int(zkapp_uri_id)),
+            ("token_symbol_id", Value::Int(token_symbol_id)),
+            ("timing_id", Value::Int(timing_id)),
+            ("voting_for_id", Value::Int(voting_for_id)),
+        ],
+    )
+    .await
+}
+
+async fn zkapp_network_precondition_id(
+    conn: &mut PgConnection,
+    network: &ZkappNetworkPreconditionRow,
+) -> Result<i32, sqlx::Error> {
+    let snarked_ledger_hash_id = match &network.snarked_ledger_hash {
+        Some(hash) => Some(snarked_ledger_hash_id(conn, hash).await?),
+        None => None,
+    };
+    let blockchain_length_id = length_bounds_id(conn, &network.blockchain_length).await?;
+    let min_window_density_id = length_bounds_id(conn, &network.min_window_density).await?;
+    let total_currency_id = amount_bounds_id(conn, &network.total_currency).await?;
+    let global_slot_since_genesis =
+        global_slot_bounds_id(conn, &network.global_slot_since_genesis).await?;
+    let staking_epoch_data_id = zkapp_epoch_data_id(conn, &network.staking_epoch_data).await?;
+    let next_epoch_data_id = zkapp_epoch_data_id(conn, &network.next_epoch_data).await?;
+
+    select_or_insert(
+        conn,
+        "zkapp_network_precondition",
+        &[
+            ("snarked_ledger_hash_id", Value::Int(snarked_ledger_hash_id)),
+            ("blockchain_length_id", Value::Int(blockchain_length_id)),
+            ("min_window_density_id", Value::Int(min_window_density_id)),
+            ("total_currency_id", Value::Int(total_currency_id)),
+            (
+                "global_slot_since_genesis",
+                Value::Int(global_slot_since_genesis),
+            ),
+            (
+                "staking_epoch_data_id",
+                Value::Int(Some(staking_epoch_data_id)),
+            ),
+            ("next_epoch_data_id", Value::Int(Some(next_epoch_data_id))),
+        ],
+    )
+    .await
+}
+
+async fn zkapp_epoch_data_id(
+    conn: &mut PgConnection,
+    epoch_data: &ZkappEpochDataRow,
+) -> Result<i32, sqlx::Error> {
+    let hash_id = match &epoch_data.ledger_hash {
+        Some(hash) => Some(snarked_ledger_hash_id(conn, hash).await?),
+        None => None,
+    };
+    let total_currency_id = amount_bounds_id(conn, &epoch_data.total_currency).await?;
+    let epoch_ledger_id = select_or_insert(
+        conn,
+        "zkapp_epoch_ledger",
+        &[
+            ("hash_id", Value::Int(hash_id)),
+            ("total_currency_id", Value::Int(total_currency_id)),
+        ],
+    )
+    .await?;
+    let epoch_length_id = length_bounds_id(conn, &epoch_data.epoch_length).await?;
+
+    select_or_insert(
+        conn,
+        "zkapp_epoch_data",
+        &[
+            ("epoch_ledger_id", Value::Int(Some(epoch_ledger_id))),
+            ("epoch_seed", Value::Text(epoch_data.seed.as_deref())),
+            (
+                "start_checkpoint",
+                Value::Text(epoch_data.start_checkpoint.as_deref()),
+            ),
+            (
+                "lock_checkpoint",
+                Value::Text(epoch_data.lock_checkpoint.as_deref()),
+            ),
+            ("epoch_length_id", Value::Int(epoch_length_id)),
+        ],
+    )
+    .await
+}
+
+async fn zkapp_account_precondition_id(
+    conn: &mut PgConnection,
+    account: &ZkappAccountPreconditionRow,
+) -> Result<i32, sqlx::Error> {
+    let balance_id = match &account.balance {
+        Some((lower, upper)) => Some(
+            select_or_insert(
+                conn,
+                "zkapp_balance_bounds",
+                &[
+                    ("balance_lower_bound", Value::Text(Some(lower))),
+                    ("balance_upper_bound", Value::Text(Some(upper))),
+                ],
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let nonce_id = match account.nonce {
+        Some((lower, upper)) => Some(
+            select_or_insert(
+                conn,
+                "zkapp_nonce_bounds",
+                &[
+                    ("nonce_lower_bound", Value::BigInt(Some(lower))),
+                    ("nonce_upper_bound", Value::BigInt(Some(upper))),
+                ],
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let delegate_id = match &account.delegate {
+        Some(delegate) => Some(public_key_id(conn, delegate).await?),
+        None => None,
+    };
+    let state_id = zkapp_states_id(conn, "zkapp_states_nullable", &account.state).await?;
+    let action_state_id = match &account.action_state {
+        Some(field) => Some(zkapp_field_id(conn, field).await?),
+        None => None,
+    };
+
+    select_or_insert(
+        conn,
+        "zkapp_account_precondition",
+        &[
+            ("balance_id", Value::Int(balance_id)),
+            ("nonce_id", Value::Int(nonce_id)),
+            (
+                "receipt_chain_hash",
+                Value::Text(account.receipt_chain_hash.as_deref()),
+            ),
+            ("delegate_id", Value::Int(delegate_id)),
+            ("state_id", Value::Int(Some(state_id))),
+            ("action_state_id", Value::Int(action_state_id)),
+            ("proved_state", Value::Bool(account.proved_state)),
+            ("is_new", Value::Bool(account.is_new)),
+        ],
+    )
+    .await
+}
+
+async fn length_bounds_id(
+    conn: &mut PgConnection,
+    bounds: &Option<(i64, i64)>,
+) -> Result<Option<i32>, sqlx::Error> {
+    let Some((lower, upper)) = *bounds else {
+        return Ok(None);
+    };
+    select_or_insert(
+        conn,
+        "zkapp_length_bounds",
+        &[
+            ("length_lower_bound", Value::BigInt(Some(lower))),
+            ("length_upper_bound", Value::BigInt(Some(upper))),
+        ],
+    )
+    .await
+    .map(Some)
+}
+
+async fn amount_bounds_id(
+    conn: &mut PgConnection,
+    bounds: &Option<(String, String)>,
+) -> Result<Option<i32>, sqlx::Error> {
+    let Some((lower, upper)) = bounds else {
+        return Ok(None);
+    };
+    select_or_insert(
+        conn,
+        "zkapp_amount_bounds",
+        &[
+            ("amount_lower_bound", Value::Text(Some(lower))),
+            ("amount_upper_bound", Value::Text(Some(upper))),
+        ],
+    )
+    .await
+    .map(Some)
+}
+
+async fn global_slot_bounds_id(
+    conn: &mut PgConnection,
+    bounds: &Option<(i64, i64)>,
+) -> Result<Option<i32>, sqlx::Error> {
+    let Some((lower, upper)) = *bounds else {
+        return Ok(None);
+    };
+    select_or_insert(
+        conn,
+        "zkapp_global_slot_bounds",
+        &[
+            ("global_slot_lower_bound", Value::BigInt(Some(lower))),
+            ("global_slot_upper_bound", Value::BigInt(Some(upper))),
+        ],
+    )
+    .await
+    .map(Some)
+}
+
+async fn zkapp_field_id(conn: &mut PgConnection, field: &str) -> Result<i32, sqlx::Error> {
+    select_or_insert(conn, "zkapp_field", &[("field", Value::Text(Some(field)))]).await
+}
+
+/// Events and actions are stored as arrays of field arrays.
+async fn zkapp_events_id(
+    conn: &mut PgConnection,
+    events: &[Vec<String>],
+) -> Result<i32, sqlx::Error> {
+    let mut field_array_ids = vec![];
+    for fields in events {
+        let mut field_ids = vec![];
+        for field in fields {
+            field_ids.push(zkapp_field_id(conn, field).await?);
+        }
+        let id = select_or_insert(
+            conn,
+            "zkapp_field_array",
+            &[("element_ids", Value::IntArray(&field_ids))],
+        )
+        .await?;
+        field_array_ids.push(id);
+    }
+    select_or_insert(
+        conn,
+        "zkapp_events",
+        &[("element_ids", Value::IntArray(&field_array_ids))],
+    )
+    .await
+}
+
+/// Row of the fixed width field tables (`zkapp_states_nullable`,
+/// `zkapp_states`, `zkapp_action_states`), with the `element<i>` columns.
+async fn zkapp_states_id(
+    conn: &mut PgConnection,
+    table: &str,
+    fields: &[Option<String>],
+) -> Result<i32, sqlx::Error> {
+    const COLUMNS: [&str; 8] = [
+        "element0", "element1", "element2", "element3", "element4", "element5", "element6",
+        "element7",
+    ];
+
+    let mut field_ids = vec![];
+    for field in fields {
+        field_ids.push(match field {
+            Some(field) => Some(zkapp_field_id(conn, field).await?),
+            None => None,
+        });
+    }
+    let columns = COLUMNS
+        .iter()
+        .zip(field_ids)
+        .map(|(column, id)| (*column, Value::Int(id)))
+        .collect::<Vec<_>>();
+    select_or_insert(conn, table, &columns).await
+}
+
+async fn verification_key_hash_id(conn: &mut PgConnection, hash: &str) -> Result<i32, sqlx::Error> {
+    select_or_insert(
+        conn,
+        "zkapp_verification_key_hashes",
+        &[("value", Value::Text(Some(hash)))],
+    )
+    .await
+}
+
+async fn verification_key_id(
+    conn: &mut PgConnection,
+    vk: &VerificationKeyRow,
+) -> Result<i32, sqlx::Error> {
+    let hash_id = verification_key_hash_id(conn, &vk.hash).await?;
+    select_or_insert(
+        conn,
+        "zkapp_verification_keys",
+        &[
+            ("verification_key", Value::Text(Some(&vk.verification_key))),
+            ("hash_id", Value::Int(Some(hash_id))),
+        ],
+    )
+    .await
+}
+
+async fn permissions_id(
+    conn: &mut PgConnection,
+    permissions: &PermissionsRow,
+) -> Result<i32, sqlx::Error> {
+    let auth = |auth| Value::Enum("zkapp_auth_required_type", auth);
+    select_or_insert(
+        conn,
+        "zkapp_permissions",
+        &[
+            ("edit_state", auth(permissions.edit_state)),
+            ("send", auth(permissions.send)),
+            ("receive", auth(permissions.receive)),
+            ("access", auth(permissions.access)),
+            ("set_delegate", auth(permissions.set_delegate)),
+            ("set_permissions", auth(permissions.set_permissions)),
+            (
+                "set_verification_key_auth",
+                auth(permissions.set_verification_key_auth),
+            ),
+            (
+                "set_verification_key_txn_version",
+                Value::Int(Some(permissions.set_verification_key_txn_version)),
+            ),
+            ("set_zkapp_uri", auth(permissions.set_zkapp_uri)),
+            ("edit_action_state", auth(permissions.edit_action_state)),
+            ("set_token_symbol", auth(permissions.set_token_symbol)),
+            ("increment_nonce", auth(permissions.increment_nonce)),
+            ("set_voting_for", auth(permissions.set_voting_for)),
+            ("set_timing", auth(permissions.set_timing)),
+        ],
+    )
+    .await
+}
+
+async fn zkapp_uri_id(conn: &mut PgConnection, uri: &str) -> Result<i32, sqlx::Error> {
+    select_or_insert(conn, "zkapp_uris", &[("value", Value::Text(Some(uri)))]).await
+}
+
+async fn token_symbol_id(conn: &mut PgConnection, symbol: &str) -> Result<i32, sqlx::Error> {
+    select_or_insert(
+        conn,
+        "token_symbols",
+        &[("value", Value::Text(Some(symbol)))],
+    )
+    .await
+}
+
+async fn voting_for_id(conn: &mut PgConnection, state_hash: &str) -> Result<i32, sqlx::Error> {
+    select_or_insert(
+        conn,
+        "voting_for",
+        &[("value", Value::Text(Some(state_hash)))],
+    )
+    .await
+}
+
+async fn insert_accounts_accessed(
+    conn: &mut PgConnection,
+    block_id: i32,
+    accounts: &[AccountAccessedRow],
+    tokens: &TokensUsed,
+) -> Result<(), sqlx::Error> {
+    for account in accounts {
+        let account_identifier_id = account_identifier_id(conn, &account.account, tokens).await?;
+        let token_symbol_id = token_symbol_id(conn, &account.token_symbol).await?;
+        let delegate_id = match &account.delegate {
+            Some(delegate) => Some(public_key_id(conn, delegate).await?),
+            None => None,
+        };
+        let voting_for_id = voting_for_id(conn, &account.voting_for).await?;
+        let timing_id = timing_info_id(conn, account_identifier_id, account).await?;
+        let permissions_id = permissions_id(conn, &account.permissions).await?;
+        let zkapp_id = match &account.zkapp {
+            Some(zkapp) => Some(zkapp_account_id(conn, zkapp).await?),
+            None => None,
+        };
+
+        sqlx::query(
+            "INSERT INTO accounts_accessed (ledger_index, block_id, account_identifier_id, \
+             token_symbol_id, balance, nonce, receipt_chain_hash, delegate_id, voting_for_id, \
+             timing_id, permissions_id, zkapp_id) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(account.ledger_index)
+        .bind(block_id)
+        .bind(account_identifier_id)
+        .bind(token_symbol_id)
+        .bind(&account.balance)
+        .bind(account.nonce)
+        .bind(&account.receipt_chain_hash)
+        .bind(delegate_id)
+        .bind(voting_for_id)
+        .bind(timing_id)
+        .bind(permissions_id)
+        .bind(zkapp_id)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// There is one timing per account, the first one written is kept.
+async fn timing_info_id(
+    conn: &mut PgConnection,
+    account_identifier_id: i32,
+    account: &AccountAccessedRow,
+) -> Result<i32, sqlx::Error> {
+    let timing = &account.timing;
+    sqlx::query(
+        "INSERT INTO timing_info (account_identifier_id, initial_minimum_balance, cliff_time, \
+         cliff_amount, vesting_period, vesting_increment) VALUES ($1, $2, $3, $4, $5, $6) \
+         ON CONFLICT (account_identifier_id) DO NOTHING",
+    )
+    .bind(account_identifier_id)
+    .bind(&timing.initial_minimum_balance)
+    .bind(timing.cliff_time)
+    .bind(&timing.cliff_amount)
+    .bind(timing.vesting_period)
+    .bind(&timing.vesting_increment)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar("SELECT id FROM timing_info WHERE account_identifier_id = $1")
+        .bind(account_identifier_id)
+        .fetch_one(conn)
+        .await
+}
+
+async fn zkapp_account_id(
+    conn: &mut PgConnection,
+    zkapp: &ZkappAccountRow,
+) -> Result<i32, sqlx::Error> {
+    let app_state = zkapp.app_state.clone().map(Some);
+    let app_state_id = zkapp_states_id(conn, "zkapp_states", &app_state).await?;
+    let verification_key_id = match &zkapp.verification_key {
+        Some(vk) => Some(verification_key_id(conn, vk).await?),
+        None => None,
+    };
+    let action_state = zkapp.action_state.clone().map(Some);
+    let action_state_id = zkapp_states_id(conn, "zkapp_action_states", &action_state).await?;
+    let zkapp_uri_id = zkapp_uri_id(conn, &zkapp.zkapp_uri).await?;
+
+    select_or_insert(
+        conn,
+        "zkapp_accounts",
+        &[
+            ("app_state_id", Value::Int(Some(app_state_id))),
+            ("verification_key_id", Value::Int(verification_key_id)),
+            ("zkapp_version", Value::BigInt(Some(zkapp.zkapp_version))),
+            ("action_state_id", Value::Int(Some(action_state_id))),
+            (
+                "last_action_slot",
+                Value::BigInt(Some(zkapp.last_action_slot)),
+            ),
+            ("proved_state", Value::Bool(Some(zkapp.proved_state))),
+            ("zkapp_uri_id", Value::Int(Some(zkapp_uri_id))),
+        ],
+    )
+    .await
+}
+
+async fn insert_accounts_created(
+    conn: &mut PgConnection,
+    block_id: i32,
+    rows: &BlockRows,
+) -> Result<(), sqlx::Error> {
+    for account in &rows.accounts_created {
+        let account_identifier_id =
+            account_identifier_id(conn, &account.account, &rows.tokens_used).await?;
+        sqlx::query(
+            "INSERT INTO accounts_created (block_id, account_identifier_id, creation_fee) \
+             VALUES ($1, $2, $3)",
+        )
+        .bind(block_id)
+        .bind(account_identifier_id)
+        .bind(&account.creation_fee)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn account_identifier_id(
+    conn: &mut PgConnection,
+    account: &AccountIdRow,
+    tokens: &TokensUsed,
+) -> Result<i32, sqlx::Error> {
+    let public_key_id = public_key_id(conn, &account.public_key).await?;
+    let token_id = token_id(conn, &account.token_id, tokens).await?;
+
+    sqlx::query(
+        "INSERT INTO account_identifiers (public_key_id, token_id) VALUES ($1, $2) \
+         ON CONFLICT (public_key_id, token_id) DO NOTHING",
+    )
+    .bind(public_key_id)
+    .bind(token_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar(
+        "SELECT id FROM account_identifiers WHERE public_key_id = $1 AND token_id = $2",
+    )
+    .bind(public_key_id)
+    .bind(token_id)
+    .fetch_one(conn)
+    .await
+}
+
+async fn public_key_id(conn: &mut PgConnection, public_key: &str) -> Result<i32, sqlx::Error> {
+    sqlx::query("INSERT INTO public_keys (value) VALUES ($1) ON CONFLICT (value) DO NOTHING")
+        .bind(public_key)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query_scalar("SELECT id FROM public_keys WHERE value = $1")
+        .bind(public_key)
+        .fetch_one(conn)
+        .await
+}
+
+async fn snarked_ledger_hash_id(
+    conn: &mut PgConnection,
+    ledger_hash: &str,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO snarked_ledger_hashes (value) VALUES ($1) ON CONFLICT (value) DO NOTHING",
+    )
+    .bind(ledger_hash)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar("SELECT id FROM snarked_ledger_hashes WHERE value = $1")
+        .bind(ledger_hash)
+        .fetch_one(conn)
+        .await
+}
+
+/// Token's owner is taken from the block's `tokens_used`. Owner's own
+/// token is inserted without an owner if it isn't in the database yet.
+async fn token_id(
+    conn: &mut PgConnection,
+    token: &str,
+    tokens: &TokensUsed,
+) -> Result<i32, sqlx::Error> {
+    let owner = tokens
+        .iter()
+        .find(|(used, _)| used == token)
+        .and_then(|(_, owner)| owner.as_ref());
+    let (owner_public_key_id, owner_token_id) = match owner {
+        Some(owner) => {
+            sqlx::query("INSERT INTO tokens (value) VALUES ($1) ON CONFLICT (value) DO NOTHING")
+                .bind(&owner.token_id)
+                .execute(&mut *conn)
+                .await?;
+            let owner_token_id: i32 = sqlx::query_scalar("SELECT id FROM tokens WHERE value = $1")
+                .bind(&owner.token_id)
+                .fetch_one(&mut *conn)
+                .await?;
+            let owner_public_key_id = public_key_id(conn, &owner.public_key).await?;
+            (Some(owner_public_key_id), Some(owner_token_id))
+        }
+        None => (None, None),
+    };
+
+    sqlx::query(
+        "INSERT INTO tokens (value, owner_public_key_id, owner_token_id) VALUES ($1, $2, $3) \
+         ON CONFLICT (value) DO NOTHING",
+    )
+    .bind(token)
+    .bind(owner_public_key_id)
+    .bind(owner_token_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar("SELECT id FROM tokens WHERE value = $1")
+        .bind(token)
+        .fetch_one(conn)
+        .await
+}
+
+async fn epoch_data_id(conn: &mut PgConnection, data: &EpochDataRow) -> Result<i32, sqlx::Error> {
+    let ledger_hash_id = snarked_ledger_hash_id(conn, &data.ledger_hash).await?;
+    sqlx::query(
+        "INSERT INTO epoch_data (seed, ledger_hash_id, total_currency, start_checkpoint, \
+         lock_checkpoint, epoch_length) VALUES ($1, $2, $3, $4, $5, $6) \
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(&data.seed)
+    .bind(ledger_hash_id)
+    .bind(&data.total_currency)
+    .bind(&data.start_checkpoint)
+    .bind(&data.lock_checkpoint)
+    .bind(data.epoch_length)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar(
+        "SELECT id FROM epoch_data WHERE seed = $1 AND ledger_hash_id = $2 \
+         AND total_currency = $3 AND start_checkpoint = $4 AND lock_checkpoint = $5 \
+         AND epoch_length = $6",
+    )
+    .bind(&data.seed)
+    .bind(ledger_hash_id)
+    .bind(&data.total_currency)
+    .bind(&data.start_checkpoint)
+    .bind(&data.lock_checkpoint)
+    .bind(data.epoch_length)
+    .fetch_one(conn)
+    .await
+}
+
+async fn protocol_version_id(
+    conn: &mut PgConnection,
+    version: &ProtocolVersionRow,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO protocol_versions (transaction, network, patch) VALUES ($1, $2, $3) \
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(version.transaction)
+    .bind(version.network)
+    .bind(version.patch)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query_scalar(
+        "SELECT id FROM protocol_versions WHERE transaction = $1 AND network = $2 AND patch = $3",
+    )
+    .bind(version.transaction)
+    .bind(version.network)
+    .bind(version.patch)
+    .fetch_one(conn)
+    .await
+}
+
+/// Column value for [`select_or_insert`].
+#[derive(Clone, Copy)]
+enum Value<'a> {
+    Int(Option<i32>),
+    BigInt(Option<i64>),
+    Text(Option<&'a str>),
+    Bool(Option<bool>),
+    IntArray(&'a [i32]),
+    TextArray(&'a [String]),
+    /// Value of the postgres enum type with the given name.
+    Enum(&'static str, &'a str),
+}
+
+impl Value<'_> {
+    fn placeholder(&self, index: usize) -> String {
+        match self {
+            Value::Enum(type_name, _) => format!("${index}::{type_name}"),
+            _ => format!("${index}"),
+        }
+    }
+}
+
+fn arguments(columns: &[(&str, Value<'_>)]) -> PgArguments {
+    let mut args = PgArguments::default();
+    for (_, value) in columns {
+        match *value {
+            Value::Int(value) => args.add(value),
+            Value::BigInt(value) => args.add(value),
+            Value::Text(value) => args.add(value),
+            Value::Bool(value) => args.add(value),
+            Value::IntArray(value) => args.add(value),
+            Value::TextArray(value) => args.add(value),
+            Value::Enum(_, value) => args.add(value),
+        }
+    }
+    args
+}
+
+/// Id of the row with the given values, inserting the row if there is
+/// none. Most of the zkapp tables have no unique constraints, and NULL
+/// stands for `Keep`/`Ignore` there, so NULLs compare as equal.
+async fn select_or_insert(
+    conn: &mut PgConnection,
+    table: &str,
+    columns: &[(&str, Value<'_>)],
+) -> Result<i32, sqlx::Error> {
+    let condition = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (column, value))| {
+            format!("{column} IS NOT DISTINCT FROM {}", value.placeholder(i + 1))
+        })
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let select = format!("SELECT id FROM {table} WHERE {condition} LIMIT 1");
+    let existing: Option<i32> = sqlx::query_scalar_with(&select, arguments(columns))
+        .fetch_optional(&mut *conn)
+        .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let names = columns
+        .iter()
+        .map(|(column, _)| *column)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let placeholders = columns
+        .iter()
+        .enumerate()
+        .map(|(i, (_, value))| value.placeholder(i + 1))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let insert = format!("INSERT INTO {table} ({names}) VALUES ({placeholders}) RETURNING id");
+    sqlx::query_scalar_with(&insert, arguments(columns))
+        .fetch_one(conn)
+        .await
+}
@@ -1,6 +1,6 @@
 mod vrf_evaluator;
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use ledger::proofs::{
     block::BlockParams, generate_block_proof, provers::BlockProver, transaction::ProofError,
@@ -10,7 +10,7 @@ use mina_p2p_messages::{
     v2::{MinaBaseProofStableV2, ProverExtendBlockchainInputStableV2, StateHash},
 };
 use node::{
-    account::AccountSecretKey,
+    account::{AccountPublicKey, AccountSecretKey},
     block_producer::{vrf_evaluator::VrfEvaluatorInput, BlockProducerEvent},
     core::{channels::mpsc, constants::constraint_constants, thread},
 };
@@ -19,7 +19,7 @@ use crate::EventSender;
 
 pub struct BlockProducerService {
     provers: Option<BlockProver>,
-    keypair: AccountSecretKey,
+    keypairs: BTreeMap<AccountPublicKey, AccountSecretKey>,
     vrf_evaluation_sender: mpsc::UnboundedSender<VrfEvaluatorInput>,
     prove_sender: mpsc::UnboundedSender<(
         BlockProver,
@@ -30,7 +30,7 @@ pub struct BlockProducerService {
 
 impl BlockProducerService {
     pub fn new(
-        keypair: AccountSecretKey,
+        keypairs: BTreeMap<AccountPublicKey, AccountSecretKey>,
         vrf_evaluation_sender: mpsc::UnboundedSender<VrfEvaluatorInput>,
         prove_sender: mpsc::UnboundedSender<(
             BlockProver,
@@ -41,7 +41,7 @@ impl BlockProducerService {
     ) -> Self {
         Self {
             provers,
-            keypair,
+            keypairs,
             vrf_evaluation_sender,
             prove_sender,
         }
@@ -49,41 +49,48 @@ impl BlockProducerService {
 
     pub fn start(
         event_sender: EventSender,
-        keypair: AccountSecretKey,
+        keypairs: Vec<AccountSecretKey>,
         provers: Option<BlockProver>,
     ) -> Self {
+        let keypairs = keypairs
+            .into_iter()
+            .map(|keypair| (keypair.public_key(), keypair))
+            .collect::<BTreeMap<_, _>>();
         let (vrf_evaluation_sender, vrf_evaluation_receiver) = mpsc::unbounded_channel();
         let (prove_sender, prove_receiver) = mpsc::unbounded_channel();
 
         let event_sender_clone = event_sender.clone();
-        let producer_keypair = keypair.clone();
+        let producer_keypairs = keypairs
+            .iter()
+            .map(|(pub_key, keypair)| (pub_key.clone(), keypair.clone().into()))
+            .collect();
         thread::Builder::new()
             .name("openmina_vrf_evaluator".to_owned())
             .spawn(move || {
                 vrf_evaluator::vrf_evaluator(
                     event_sender_clone,
                     vrf_evaluation_receiver,
-                    producer_keypair.into(),
+                    producer_keypairs,
                 );
             })
             .unwrap();
 
-        let producer_keypair = keypair.clone();
+        let producer_keypairs = keypairs.clone();
         thread::Builder::new()
             .name("openmina_block_prover".to_owned())
-            .spawn(move || prover_loop(producer_keypair, event_sender, prove_receiver))
+            .spawn(move || prover_loop(producer_keypairs, event_sender, prove_receiver))
             .unwrap();
 
-        BlockProducerService::new(keypair, vrf_evaluation_sender, prove_sender, provers)
+        BlockProducerService::new(keypairs, vrf_evaluation_sender, prove_sender, provers)
     }
 
-    pub fn keypair(&self) -> AccountSecretKey {
-        self.keypair.clone()
+    pub fn keypair(&self, pub_key: &AccountPublicKey) -> Option<AccountSecretKey> {
+        self.keypairs.get(pub_key).cloned()
     }
 }
 
 fn prover_loop(
-    keypair: AccountSecretKey,
+    keypairs: BTreeMap<AccountPublicKey, AccountSecretKey>,
     event_sender: EventSender,
     mut rx: mpsc::UnboundedReceiver<(
         BlockProver,
@@ -92,6 +99,14 @@ fn prover_loop(
     )>,
 ) {
     while let Some((provers, block_hash, input)) = rx.blocking_recv() {
+        let block_creator =
+            AccountPublicKey::from(input.next_state.body.consensus_state.block_creator.clone());
+        let Some(keypair) = keypairs.get(&block_creator) else {
+            let error = format!("no keypair for block creator {block_creator}");
+            let _ =
+                event_sender.send(BlockProducerEvent::BlockProve(block_hash, Err(error)).into());
+            continue;
+        };
         let res =
             prove(provers, input.clone(), keypair.clone(), false).map_err(|err| format!("{err:?}"));
         if res.is_err() {
@@ -1,5 +1,8 @@
+use std::collections::BTreeMap;
+
 use mina_signer::Keypair;
 use node::{
+    account::AccountPublicKey,
     block_producer::BlockProducerVrfEvaluatorEvent,
     block_producer::{
         vrf_evaluator::{VrfEvaluationOutputWithHash, VrfEvaluatorInput},
@@ -15,43 +18,13 @@ use crate::NodeService;
 pub fn vrf_evaluator(
     event_sender: UnboundedSender<Event>,
     mut vrf_evaluation_receiver: UnboundedReceiver<VrfEvaluatorInput>,
-    keypair: Keypair,
+    keypairs: BTreeMap<AccountPublicKey, Keypair>,
 ) {
     while let Some(vrf_evaluator_input) = vrf_evaluation_receiver.blocking_recv() {
         // let bytes = serde_json::to_string(&vrf_evaluator_input).unwrap();
         // openmina_core::http::download("vrf.json".to_string(), bytes.as_bytes().to_vec()).unwrap();
 
-        let keypair = &keypair;
-        let VrfEvaluatorInput {
-            epoch_seed,
-            delegator_table,
-            global_slot,
-            total_currency,
-            staking_ledger_hash: _,
-        } = &vrf_evaluator_input;
-
-        let vrf_result = delegator_table
-            .iter()
-            .find_map(|(index, (pub_key, stake))| {
-                let vrf_input = VrfEvaluationInput {
-                    producer_key: keypair.clone(),
-                    global_slot: *global_slot,
-                    epoch_seed: epoch_seed.clone(),
-                    account_pub_key: pub_key.clone(),
-                    delegator_index: *index,
-                    delegated_stake: (*stake).into(),
-                    total_currency: (*total_currency).into(),
-                };
-
-                let vrf_result = vrf::evaluate_vrf(vrf_input).unwrap();
-
-                // the first delegate that won the slot
-                if let VrfEvaluationOutput::SlotWon(_) = vrf_result {
-                    return Some(vrf_result);
-                }
-                None
-            })
-            .unwrap_or(VrfEvaluationOutput::SlotLost(*global_slot));
+        let vrf_result = evaluate_slot(&vrf_evaluator_input, &keypairs);
 
         let vrf_result_with_hash = VrfEvaluationOutputWithHash::new(
             vrf_result,
@@ -68,6 +41,50 @@ pub fn vrf_evaluator(
     }
 }
 
+/// Evaluates the slot for the delegators of all of our producer keys,
+/// the slot is won by the first winning delegator.
+fn evaluate_slot(
+    vrf_evaluator_input: &VrfEvaluatorInput,
+    keypairs: &BTreeMap<AccountPublicKey, Keypair>,
+) -> VrfEvaluationOutput {
+    let VrfEvaluatorInput {
+        epoch_seed,
+        delegator_table,
+        global_slot,
+        total_currency,
+        staking_ledger_hash: _,
+    } = vrf_evaluator_input;
+
+    delegator_table
+        .iter()
+        .filter_map(|(producer, delegators)| Some((keypairs.get(producer)?, delegators)))
+        .flat_map(|(keypair, delegators)| {
+            delegators
+                .iter()
+                .map(move |(index, (pub_key, stake))| (keypair, index, pub_key, stake))
+        })
+        .find_map(|(keypair, index, pub_key, stake)| {
+            let vrf_input = VrfEvaluationInput {
+                producer_key: keypair.clone(),
+                global_slot: *global_slot,
+                epoch_seed: epoch_seed.clone(),
+                account_pub_key: pub_key.clone(),
+                delegator_index: *index,
+                delegated_stake: (*stake).into(),
+                total_currency: (*total_currency).into(),
+            };
+
+            let vrf_result = vrf::evaluate_vrf(vrf_input).unwrap();
+
+            // the first delegate that won the slot
+            if let VrfEvaluationOutput::SlotWon(_) = vrf_result {
+                return Some(vrf_result);
+            }
+            None
+        })
+        .unwrap_or(VrfEvaluationOutput::SlotLost(*global_slot))
+}
+
 impl node::block_producer_effectful::vrf_evaluator_effectful::BlockProducerVrfEvaluatorService
     for NodeService
 {
@@ -80,13 +97,88 @@ impl node::block_producer_effectful::vrf_evaluator_effectful::BlockProducerVrfEv
 
 #[cfg(test)]
 mod tests {
-    use std::str::FromStr;
+    use std::{str::FromStr, sync::Arc};
 
     // use mina_signer::keypair;
-    use node::account::AccountSecretKey;
+    use ledger::AccountIndex;
+    use mina_p2p_messages::v2::{EpochSeed, LedgerHash, NonZeroCurvePoint};
+    use node::{
+        account::AccountSecretKey,
+        block_producer::{BlockProducerConfig, BlockProducerKeyConfig},
+    };
 
     use super::*;
 
+    #[test]
+    fn test_vrf_multiple_producers() {
+        // Same inputs as `vrf::tests::test_evaluate_vrf_won_slot`, with the
+        // winning delegator among the delegators of other producer keys.
+        let winner =
+            AccountSecretKey::from_str("EKEEpMELfQkMbJDt2fB4cFXKwSf1x4t7YD4twREy5yuJ84HBZtF9")
+                .unwrap();
+        let loser = AccountSecretKey::rand();
+        let not_ours = AccountSecretKey::rand();
+        let delegator = AccountSecretKey::genesis_producer().public_key();
+        let total_currency = 6_000_000_000_001_000;
+
+        let delegator_table = [
+            (
+                loser.public_key(),
+                [(AccountIndex(1), (delegator.clone(), 1))].into(),
+            ),
+            (
+                winner.public_key(),
+                [(AccountIndex(2), (delegator.clone(), 1_000_000_000_000_000))].into(),
+            ),
+            (
+                not_ours.public_key(),
+                [(AccountIndex(3), (delegator.clone(), total_currency))].into(),
+            ),
+        ]
+        .into();
+        let keypairs = [&winner, &loser]
+            .into_iter()
+            .map(|key| (key.public_key(), key.clone().into()))
+            .collect();
+        let mut input = VrfEvaluatorInput {
+            epoch_seed: EpochSeed::from_str("2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA")
+                .unwrap(),
+            delegator_table: Arc::new(delegator_table),
+            global_slot: 6,
+            total_currency,
+            staking_ledger_hash: LedgerHash::zero(),
+        };
+
+        let VrfEvaluationOutput::SlotWon(won_slot) = evaluate_slot(&input, &keypairs) else {
+            panic!("slot should have been won");
+        };
+        assert_eq!(won_slot.producer, winner.public_key());
+        assert_eq!(won_slot.winner_account, delegator);
+        assert_eq!(won_slot.account_index, AccountIndex(2));
+
+        let coinbase_receiver = AccountSecretKey::rand().public_key();
+        let mut config = BlockProducerConfig::new(loser.public_key().into());
+        config.add_producer(BlockProducerKeyConfig {
+            pub_key: winner.public_key().into(),
+            custom_coinbase_receiver: Some(coinbase_receiver.clone().into()),
+        });
+        let producer = config.producer(&won_slot.producer.into()).unwrap();
+        assert_eq!(
+            producer.pub_key,
+            NonZeroCurvePoint::from(winner.public_key())
+        );
+        assert_eq!(
+            producer.coinbase_receiver(),
+            &NonZeroCurvePoint::from(coinbase_receiver)
+        );
+
+        input.global_slot = 518;
+        assert_eq!(
+            evaluate_slot(&input, &keypairs),
+            VrfEvaluationOutput::SlotLost(518)
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_vrf() {
@@ -95,6 +187,7 @@ mod tests {
 
         let private = "SOME_KEY";
         let private = AccountSecretKey::from_str(private).unwrap();
+        let producer = private.public_key();
         let keypair: Keypair = private.into();
 
         let VrfEvaluatorInput {
@@ -108,7 +201,9 @@ mod tests {
         let now = std::time::Instant::now();
 
         let vrf_result = delegator_table
-            .iter()
+            .get(&producer)
+            .into_iter()
+            .flatten()
             .map(|(index, (pub_key, stake))| {
                 let vrf_input = VrfEvaluationInput {
                     producer_key: keypair.clone(),
@@ -138,7 +233,11 @@ mod tests {
 
         let elapsed = now.elapsed();
         let slot = vrf_evaluator_input.global_slot;
-        let ndelegator = vrf_evaluator_input.delegator_table.len();
+        let ndelegator = vrf_evaluator_input
+            .delegator_table
+            .values()
+            .map(|delegators| delegators.len())
+            .sum::<usize>();
         // let nevaluated = nevaluated.load(std::sync::atomic::Ordering::Relaxed);
         eprintln!("TOTAL vrf::evaluate_vrf: {elapsed:?} slot:{slot:?} ndelegators:{ndelegator:?}");
         dbg!(vrf_result);
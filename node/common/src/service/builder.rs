@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use ledger::proofs::provers::BlockProver;
 use node::{
     account::AccountSecretKey,
@@ -23,7 +25,10 @@ use crate::{
     EventReceiver, EventSender, NodeService,
 };
 
-use super::block_producer::BlockProducerService;
+use super::{
+    archive::{ArchiveError, ArchiveService, ArchiveStorage},
+    block_producer::BlockProducerService,
+};
 
 pub struct NodeServiceCommonBuilder {
     rng_seed: [u8; 32],
@@ -34,6 +39,7 @@ pub struct NodeServiceCommonBuilder {
     event_receiver: EventReceiver,
     ledger_manager: Option<LedgerManager>,
     block_producer: Option<BlockProducerService>,
+    archive: Option<ArchiveService>,
     p2p: Option<P2pServiceCtx>,
     gather_stats: bool,
     rpc: RpcService,
@@ -57,6 +63,7 @@ impl NodeServiceCommonBuilder {
             event_receiver: event_receiver.into(),
             ledger_manager: None,
             block_producer: None,
+            archive: None,
             p2p: None,
             rpc: RpcService::new(),
             gather_stats: false,
@@ -78,19 +85,51 @@ impl NodeServiceCommonBuilder {
         self
     }
 
+    /// Same as [`Self::ledger_init`], but also opens the transition
+    /// frontier store at `frontier_store_path`, so that the frontier can be
+    /// persisted, and keeps snarked, epoch and staged ledgers on disk in
+    /// `ondisk_ledgers_path`.
+    pub fn ledger_init_persistent(
+        &mut self,
+        frontier_store_path: Option<&Path>,
+        ondisk_ledgers_path: Option<&Path>,
+    ) -> std::io::Result<&mut Self> {
+        let mut ctx = LedgerCtx::default();
+        if let Some(path) = frontier_store_path {
+            ctx.frontier_store_open(path)?;
+        }
+        if let Some(path) = ondisk_ledgers_path {
+            ctx.ondisk_ledgers_open(path)?;
+        }
+        ctx.set_event_sender(self.event_sender.clone());
+        self.ledger_manager = Some(LedgerManager::spawn(ctx));
+        Ok(self)
+    }
+
     pub fn block_producer_init(
         &mut self,
-        keypair: AccountSecretKey,
+        keypairs: Vec<AccountSecretKey>,
         provers: Option<BlockProver>,
     ) -> &mut Self {
         self.block_producer = Some(BlockProducerService::start(
             self.event_sender.clone(),
-            keypair,
+            keypairs,
             provers,
         ));
         self
     }
 
+    /// Starts the archive sink. Blocks which couldn't be archived are
+    /// kept in `spool_dir` until they can be.
+    pub fn archive_init(
+        &mut self,
+        storage: ArchiveStorage,
+        spool_dir: impl AsRef<Path>,
+    ) -> Result<&mut Self, ArchiveError> {
+        self.archive = Some(ArchiveService::start(storage, spool_dir)?);
+        Ok(self)
+    }
+
     pub fn p2p_init<S: TaskSpawner>(
         &mut self,
         secret_key: P2pSecretKey,
@@ -132,6 +171,8 @@ impl NodeServiceCommonBuilder {
             ),
             ledger_manager,
             block_producer: self.block_producer,
+            snark_worker: None,
+            archive: self.archive,
             p2p,
             stats: self.gather_stats.then(Stats::new),
             rpc: self.rpc,
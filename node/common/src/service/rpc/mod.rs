@@ -8,16 +8,18 @@ pub mod transaction_pool;
 pub mod transition_frontier;
 
 use node::rpc::{
-    RpcBestChainResponse, RpcBlockProducerStatsGetResponse, RpcConsensusConstantsGetResponse,
-    RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse,
-    RpcLedgerAccountsResponse, RpcLedgerSlimAccountsResponse, RpcMessageProgressResponse,
+    RpcBestChainResponse, RpcBlockGetResponse, RpcBlockProducerStatsGetResponse,
+    RpcConsensusConstantsGetResponse, RpcDiscoveryBoostrapStatsResponse,
+    RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse, RpcLedgerAccountsResponse,
+    RpcLedgerSlimAccountsResponse, RpcMessageProgressResponse, RpcMetricsGetResponse,
     RpcPeersGetResponse, RpcReadinessCheckResponse, RpcRequest, RpcStateGetError,
-    RpcStatusGetResponse, RpcTransactionInjectResponse, RpcTransactionPoolResponse,
-    RpcTransactionStatusGetResponse, RpcTransitionFrontierUserCommandsResponse,
+    RpcStatusGetResponse, RpcSubscriptionEvent, RpcTransactionInjectResponse,
+    RpcTransactionPoolResponse, RpcTransactionStatusGetResponse,
+    RpcTransitionFrontierUserCommandsResponse,
 };
 use serde::{Deserialize, Serialize};
 
-use node::core::channels::{mpsc, oneshot};
+use node::core::channels::{broadcast, mpsc, oneshot};
 use node::core::requests::PendingRequests;
 use node::p2p::connection::P2pConnectionResponse;
 use node::State;
@@ -47,11 +49,17 @@ pub struct NodeRpcRequest {
 
 pub type RpcReceiver = mpsc::Receiver<NodeRpcRequest>;
 
+/// How many subscription events may be queued for a single subscriber
+/// before it is considered lagging and gets dropped.
+pub const SUBSCRIPTION_BACKLOG: usize = 64;
+
 pub struct RpcService {
     pending: PendingRequests<RpcIdType, Box<dyn Send + std::any::Any>>,
 
     req_sender: mpsc::Sender<NodeRpcRequest>,
     req_receiver: mpsc::Receiver<NodeRpcRequest>,
+
+    subscriptions: broadcast::Sender<RpcSubscriptionEvent>,
 }
 
 impl Default for RpcService {
@@ -63,16 +71,18 @@ impl Default for RpcService {
 impl RpcService {
     pub fn new() -> Self {
         let (tx, rx) = mpsc::channel(8);
+        let (subscriptions, _) = broadcast::channel(SUBSCRIPTION_BACKLOG);
         Self {
             pending: Default::default(),
             req_sender: tx,
             req_receiver: rx,
+            subscriptions,
         }
     }
 
     /// Channel for sending the rpc request to state machine.
     pub fn req_sender(&self) -> RpcSender {
-        RpcSender::new(self.req_sender.clone())
+        RpcSender::new(self.req_sender.clone(), self.subscriptions.clone())
     }
 
     /// Channel for receiving rpc requests in state machine.
@@ -222,6 +232,7 @@ impl node::rpc_effectful::RpcService for NodeService {
         respond_block_producer_stats_get,
         RpcBlockProducerStatsGetResponse
     );
+    rpc_service_impl!(respond_metrics_get, RpcMetricsGetResponse);
     rpc_service_impl!(
         respond_message_progress_stats_get,
         RpcMessageProgressResponse
@@ -306,6 +317,12 @@ impl node::rpc_effectful::RpcService for NodeService {
         RpcConsensusConstantsGetResponse
     );
     rpc_service_impl!(respond_transaction_status, RpcTransactionStatusGetResponse);
+    rpc_service_impl!(respond_block_get, RpcBlockGetResponse);
+
+    fn publish_subscription_event(&mut self, event: RpcSubscriptionEvent) {
+        // Fails only if there are no subscribers.
+        let _ = self.rpc.subscriptions.send(event);
+    }
 }
 
 #[cfg(test)]
@@ -4,7 +4,7 @@ use serde::Serialize;
 #[cfg(target_family = "wasm")]
 use wasm_bindgen::prelude::*;
 
-use node::core::channels::{mpsc, oneshot};
+use node::core::channels::{broadcast, mpsc, oneshot};
 use node::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
 use node::rpc::*;
 
@@ -19,11 +19,22 @@ use super::NodeRpcRequest;
 #[cfg_attr(target_family = "wasm", wasm_bindgen)]
 pub struct RpcSender {
     tx: mpsc::Sender<NodeRpcRequest>,
+    subscriptions: broadcast::Sender<RpcSubscriptionEvent>,
 }
 
 impl RpcSender {
-    pub fn new(tx: mpsc::Sender<NodeRpcRequest>) -> Self {
-        Self { tx }
+    pub fn new(
+        tx: mpsc::Sender<NodeRpcRequest>,
+        subscriptions: broadcast::Sender<RpcSubscriptionEvent>,
+    ) -> Self {
+        Self { tx, subscriptions }
+    }
+
+    /// Subscribe to the best chain updates. Receiver that falls behind
+    /// by more than [`super::SUBSCRIPTION_BACKLOG`] events gets
+    /// [`broadcast::error::RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<RpcSubscriptionEvent> {
+        self.subscriptions.subscribe()
     }
 
     pub async fn oneshot_request<T>(&self, req: RpcRequest) -> Option<T>
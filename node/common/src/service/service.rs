@@ -18,10 +18,12 @@ use sha3::{
 use crate::rpc::RpcReceiver;
 
 use super::{
+    archive::ArchiveService,
     block_producer::BlockProducerService,
     p2p::webrtc_with_libp2p::P2pServiceCtx,
     replay::ReplayerState,
     rpc::{RpcSender, RpcService},
+    snark_worker::SnarkWorker,
     snarks::SnarkBlockVerifyArgs,
     EventReceiver, EventSender,
 };
@@ -41,6 +43,8 @@ pub struct NodeService {
 
     pub ledger_manager: LedgerManager,
     pub block_producer: Option<BlockProducerService>,
+    pub snark_worker: Option<SnarkWorker>,
+    pub archive: Option<ArchiveService>,
     pub p2p: P2pServiceCtx,
 
     pub stats: Option<Stats>,
@@ -111,6 +115,8 @@ impl NodeService {
             snark_block_proof_verify: mpsc::unbounded_channel().0,
             ledger_manager: LedgerManager::spawn(Default::default()),
             block_producer: None,
+            snark_worker: None,
+            archive: None,
             p2p: P2pServiceCtx::mocked(p2p_sec_key),
             stats: Some(Stats::new()),
             rpc: RpcService::new(),
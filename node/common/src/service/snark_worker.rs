@@ -1,40 +1,401 @@
-use mina_p2p_messages::v2;
-use node::external_snark_worker::{ExternalSnarkWorkerError, SnarkWorkSpec};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
-use crate::NodeService;
+use ledger::{
+    proofs::{
+        generate_merge_proof, generate_tx_proof,
+        merge::MergeParams,
+        provers::{TransactionProver, ZkappProver},
+        transaction::{ProofError, TransactionParams},
+        zkapp::{generate_zkapp_proof, LedgerProof, ZkappParams},
+    },
+    scan_state::scan_state::transaction_snark::{SokMessage, Statement},
+};
+use mina_p2p_messages::v2::{
+    self, SnarkWorkerWorkerRpcsVersionedGetWorkV2TResponseA0Single as SnarkWorkSingleSpec,
+};
+use node::{
+    account::AccountPublicKey,
+    core::{channels::mpsc, thread},
+    external_snark_worker::{
+        ExternalSnarkWorkerError, ExternalSnarkWorkerEvent, ExternalSnarkWorkerWorkError,
+        SnarkWorkSpec,
+    },
+};
 
-pub struct SnarkWorker {}
+use crate::{EventSender, NodeService};
+
+/// Single spec contains at most two instances, which are proven in parallel.
+const PROVER_THREADS: usize = 2;
+
+/// In-process snark worker, proving snark work with the Rust provers
+/// instead of spawning external OCaml process.
+pub struct SnarkWorker {
+    event_sender: EventSender,
+    job_sender: mpsc::UnboundedSender<(u64, SnarkWorkSpec)>,
+    /// Incremented on each cancellation, jobs submitted with an older
+    /// value are discarded once (or before) they are done.
+    generation: Arc<AtomicU64>,
+}
+
+struct SnarkWorkerProvers {
+    tx: TransactionProver,
+    zkapp: ZkappProver,
+}
+
+impl SnarkWorker {
+    pub fn start(
+        event_sender: EventSender,
+        public_key: v2::NonZeroCurvePoint,
+        fee: v2::CurrencyFeeStableV1,
+    ) -> Result<Self, ExternalSnarkWorkerError> {
+        let prover = AccountPublicKey::from(public_key)
+            .try_into()
+            .map_err(|err| ExternalSnarkWorkerError::Error(format!("{err:?}")))?;
+        let message = SokMessage::create((&fee).into(), prover);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(PROVER_THREADS)
+            .thread_name(|i| format!("openmina_snark_worker_prover_{i}"))
+            .build()
+            .map_err(|err| ExternalSnarkWorkerError::Error(err.to_string()))?;
+
+        let (job_sender, job_receiver) = mpsc::unbounded_channel();
+        let generation = Arc::new(AtomicU64::new(0));
+
+        let worker_event_sender = event_sender.clone();
+        let worker_generation = generation.clone();
+        thread::Builder::new()
+            .name("openmina_snark_worker".to_owned())
+            .spawn(move || {
+                worker_loop(
+                    worker_event_sender,
+                    job_receiver,
+                    worker_generation,
+                    pool,
+                    message,
+                )
+            })
+            .map_err(|err| ExternalSnarkWorkerError::IOError(err.to_string()))?;
+
+        Ok(Self {
+            event_sender,
+            job_sender,
+            generation,
+        })
+    }
+
+    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        self.job_sender
+            .send((generation, spec))
+            .map_err(|_| ExternalSnarkWorkerError::NotRunning)
+    }
+
+    fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError> {
+        // Provers can't be interrupted, so the job in progress (if any)
+        // keeps running and its result is thrown away.
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let _ = self
+            .event_sender
+            .send(ExternalSnarkWorkerEvent::WorkCancelled.into());
+        Ok(())
+    }
+
+    fn kill(self) -> Result<(), ExternalSnarkWorkerError> {
+        // Dropping job sender makes the worker thread exit.
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        let _ = self
+            .event_sender
+            .send(ExternalSnarkWorkerEvent::Killed.into());
+        Ok(())
+    }
+}
+
+fn worker_loop(
+    event_sender: EventSender,
+    rx: mpsc::UnboundedReceiver<(u64, SnarkWorkSpec)>,
+    generation: Arc<AtomicU64>,
+    pool: rayon::ThreadPool,
+    message: SokMessage,
+) {
+    let provers = SnarkWorkerProvers {
+        tx: TransactionProver::make(None),
+        zkapp: ZkappProver::make(None),
+    };
+    let _ = event_sender.send(ExternalSnarkWorkerEvent::Started.into());
+
+    run_jobs(&event_sender, rx, &generation, |spec| {
+        pool.install(|| prove(&provers, &message, spec))
+    });
+}
+
+/// Proves received jobs one by one until the channel is closed. Jobs
+/// of an older generation than the current one are dropped, whether
+/// they are still waiting or were cancelled while being proven.
+fn run_jobs<J>(
+    event_sender: &EventSender,
+    mut rx: mpsc::UnboundedReceiver<(u64, J)>,
+    generation: &AtomicU64,
+    mut prove: impl FnMut(&J) -> Result<v2::TransactionSnarkWorkTStableV2Proofs, ProofError>,
+) {
+    let is_current = |job_generation| generation.load(Ordering::SeqCst) == job_generation;
+
+    while let Some((job_generation, job)) = rx.blocking_recv() {
+        if !is_current(job_generation) {
+            continue;
+        }
+        let res = prove(&job);
+        if !is_current(job_generation) {
+            continue;
+        }
+        let event = match res {
+            Ok(proofs) => ExternalSnarkWorkerEvent::WorkResult(Arc::new(proofs)),
+            Err(err) => ExternalSnarkWorkerWorkError::Error(format!("{err:?}")).into(),
+        };
+        let _ = event_sender.send(event.into());
+    }
+}
+
+fn prove(
+    provers: &SnarkWorkerProvers,
+    message: &SokMessage,
+    spec: &SnarkWorkSpec,
+) -> Result<v2::TransactionSnarkWorkTStableV2Proofs, ProofError> {
+    Ok(match spec {
+        SnarkWorkSpec::One(single) => {
+            v2::TransactionSnarkWorkTStableV2Proofs::One(prove_single(provers, message, single)?)
+        }
+        SnarkWorkSpec::Two((first, second)) => {
+            let (first, second) = rayon::join(
+                || prove_single(provers, message, first),
+                || prove_single(provers, message, second),
+            );
+            v2::TransactionSnarkWorkTStableV2Proofs::Two((first?, second?))
+        }
+    })
+}
+
+fn prove_single(
+    provers: &SnarkWorkerProvers,
+    message: &SokMessage,
+    spec: &SnarkWorkSingleSpec,
+) -> Result<v2::LedgerProofProdStableV2, ProofError> {
+    let proof = match spec {
+        SnarkWorkSingleSpec::Transition(statement, tx_witness)
+            if is_zkapp(&tx_witness.transaction) =>
+        {
+            generate_zkapp_proof(ZkappParams {
+                statement,
+                tx_witness,
+                message,
+                step_opt_signed_opt_signed_prover: &provers.zkapp.step_opt_signed_opt_signed_prover,
+                step_opt_signed_prover: &provers.zkapp.step_opt_signed_prover,
+                step_proof_prover: &provers.zkapp.step_proof_prover,
+                merge_step_prover: &provers.zkapp.merge_step_prover,
+                tx_wrap_prover: &provers.zkapp.tx_wrap_prover,
+                opt_signed_path: None,
+                proved_path: None,
+            })?
+        }
+        SnarkWorkSingleSpec::Transition(statement, tx_witness) => {
+            let proof = generate_tx_proof(TransactionParams {
+                statement,
+                tx_witness,
+                message,
+                tx_step_prover: &provers.tx.tx_step_prover,
+                tx_wrap_prover: &provers.tx.tx_wrap_prover,
+                only_verify_constraints: false,
+                expected_step_proof: None,
+                ocaml_wrap_witness: None,
+            })?;
+            LedgerProof {
+                statement: Statement::<()>::try_from(&statement.0)?.with_digest(message.digest()),
+                proof,
+            }
+        }
+        SnarkWorkSingleSpec::Merge(merge) => {
+            let (statement, proof1, proof2) = &**merge;
+            let statement = Statement::<()>::try_from(&statement.0)?;
+            let proof = generate_merge_proof(MergeParams {
+                statement: statement.clone(),
+                proofs: &[proof1.clone(), proof2.clone()],
+                message,
+                step_prover: &provers.tx.merge_step_prover,
+                wrap_prover: &provers.tx.tx_wrap_prover,
+                only_verify_constraints: false,
+                expected_step_proof: None,
+                ocaml_wrap_witness: None,
+            })?;
+            LedgerProof {
+                statement: statement.with_digest(message.digest()),
+                proof,
+            }
+        }
+    };
+    Ok((&proof).into())
+}
+
+/// ZkApp commands need the zkApp prover, everything else is proven
+/// with the transaction prover.
+fn is_zkapp(transaction: &v2::MinaTransactionTransactionStableV2) -> bool {
+    matches!(
+        transaction,
+        v2::MinaTransactionTransactionStableV2::Command(cmd)
+            if matches!(&**cmd, v2::MinaBaseUserCommandStableV2::ZkappCommand(_))
+    )
+}
 
 impl node::service::ExternalSnarkWorkerService for NodeService {
     fn start(
         &mut self,
-        _public_key: v2::NonZeroCurvePoint,
-        _fee: v2::CurrencyFeeStableV1,
+        public_key: v2::NonZeroCurvePoint,
+        fee: v2::CurrencyFeeStableV1,
     ) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        let worker = SnarkWorker::start(self.event_sender.clone(), public_key, fee)?;
+        self.snark_worker = Some(worker);
+        Ok(())
     }
 
     fn kill(&mut self) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .take()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .kill()
     }
 
-    fn submit(&mut self, _spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError> {
+    fn submit(&mut self, spec: SnarkWorkSpec) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .as_mut()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .submit(spec)
     }
 
     fn cancel(&mut self) -> Result<(), ExternalSnarkWorkerError> {
         if self.replayer.is_some() {
             return Ok(());
         }
-        todo!()
+        self.snark_worker
+            .as_mut()
+            .ok_or(ExternalSnarkWorkerError::NotRunning)?
+            .cancel()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mina_p2p_messages::{bigint::BigInt, list::List, v2::*};
+    use node::event_source::Event;
+
+    use super::*;
+
+    #[test]
+    fn test_cancelled_jobs_are_dropped() {
+        let (event_sender, mut event_receiver) = mpsc::unbounded_channel();
+        let (job_sender, job_receiver) = mpsc::unbounded_channel();
+        let generation = AtomicU64::new(0);
+
+        // cancelled while being proven
+        job_sender.send((0, 1)).unwrap();
+        // superseded before being proven
+        job_sender.send((0, 2)).unwrap();
+        job_sender.send((1, 3)).unwrap();
+        drop(job_sender);
+
+        let mut proven = vec![];
+        run_jobs(&event_sender, job_receiver, &generation, |job: &u32| {
+            proven.push(*job);
+            if *job == 1 {
+                generation.fetch_add(1, Ordering::SeqCst);
+            }
+            Err(ProofError::ConstraintsNotSatisfied(job.to_string()))
+        });
+        assert_eq!(proven, [1, 3]);
+
+        let Ok(Event::ExternalSnarkWorker(ExternalSnarkWorkerEvent::WorkError(
+            ExternalSnarkWorkerWorkError::Error(error),
+        ))) = event_receiver.try_recv()
+        else {
+            panic!("expected work error");
+        };
+        assert!(error.contains("\"3\""), "{error}");
+        assert!(event_receiver.try_recv().is_err());
+    }
+
+    fn fee_payer() -> MinaBaseAccountUpdateFeePayerStableV1 {
+        MinaBaseAccountUpdateFeePayerStableV1 {
+            body: MinaBaseAccountUpdateBodyFeePayerStableV1 {
+                public_key: NonZeroCurvePoint::default(),
+                fee: CurrencyFeeStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+                    1_000_000_u64.into(),
+                )),
+                valid_until: None,
+                nonce: UnsignedExtendedUInt32StableV1::default(),
+            },
+            authorization: MinaBaseSignatureStableV1(BigInt::zero(), BigInt::zero()).into(),
+        }
+    }
+
+    #[test]
+    fn test_zkapp_routing() {
+        let memo = MinaBaseSignedCommandMemoStableV1(vec![0; 34].into());
+        let fee_payer = fee_payer();
+
+        let zkapp = MinaTransactionTransactionStableV2::Command(Box::new(
+            MinaBaseUserCommandStableV2::ZkappCommand(MinaBaseZkappCommandTStableV1WireStableV1 {
+                fee_payer: fee_payer.clone(),
+                account_updates: List::new(),
+                memo: memo.clone(),
+            }),
+        ));
+        assert!(is_zkapp(&zkapp));
+
+        let body = fee_payer.body;
+        let payment = MinaTransactionTransactionStableV2::Command(Box::new(
+            MinaBaseUserCommandStableV2::SignedCommand(MinaBaseSignedCommandStableV2 {
+                payload: MinaBaseSignedCommandPayloadStableV2 {
+                    common: MinaBaseSignedCommandPayloadCommonStableV2 {
+                        fee: body.fee,
+                        fee_payer_pk: body.public_key.clone(),
+                        nonce: body.nonce,
+                        valid_until: MinaNumbersGlobalSlotSinceGenesisMStableV1::SinceGenesis(
+                            UnsignedExtendedUInt32StableV1::default(),
+                        ),
+                        memo,
+                    },
+                    body: MinaBaseSignedCommandPayloadBodyStableV2::Payment(
+                        MinaBasePaymentPayloadStableV2 {
+                            receiver_pk: body.public_key.clone(),
+                            amount: CurrencyAmountStableV1(
+                                UnsignedExtendedUInt64Int64ForVersionTagsStableV1(1_u64.into()),
+                            ),
+                        },
+                    ),
+                },
+                signer: body.public_key.clone(),
+                signature: fee_payer.authorization,
+            }),
+        ));
+        assert!(!is_zkapp(&payment));
+
+        let coinbase = MinaTransactionTransactionStableV2::Coinbase(MinaBaseCoinbaseStableV1 {
+            receiver: body.public_key,
+            amount: CurrencyAmountStableV1(UnsignedExtendedUInt64Int64ForVersionTagsStableV1(
+                1_u64.into(),
+            )),
+            fee_transfer: None,
+        });
+        assert!(!is_zkapp(&coinbase));
     }
 }
@@ -0,0 +1,119 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use node::{ActionKind, ActionWithMeta, Service, Store};
+use serde::Serialize;
+
+use crate::{InvariantIgnoreReason, InvariantResult, InvariantService, Invariants};
+
+/// Violation of an invariant, along with the action which caused it.
+#[derive(Serialize, Debug, Clone)]
+pub struct InvariantViolation {
+    pub invariant: &'static str,
+    pub time: redux::Timestamp,
+    pub action_kind: ActionKind,
+    pub action: serde_json::Value,
+    pub violation: String,
+    /// Part of the state relevant for the invariant.
+    pub state: serde_json::Value,
+}
+
+/// Checks all registered invariants after every action and records
+/// violations in a report file (one json per line).
+pub struct InvariantsChecker {
+    report_path: PathBuf,
+    report: BufWriter<File>,
+    halt_on_violation: bool,
+    violations_count: usize,
+}
+
+impl InvariantsChecker {
+    /// Violations are appended to the `report_path` file.
+    pub fn new(report_path: impl AsRef<Path>, halt_on_violation: bool) -> io::Result<Self> {
+        let report_path = report_path.as_ref().to_owned();
+        if let Some(dir) = report_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(dir)?;
+        }
+        let report = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&report_path)?;
+        Ok(Self {
+            report_path,
+            report: BufWriter::new(report),
+            halt_on_violation,
+            violations_count: 0,
+        })
+    }
+
+    pub fn report_path(&self) -> &Path {
+        &self.report_path
+    }
+
+    /// Whether the node should be stopped once an invariant is violated.
+    pub fn halt_on_violation(&self) -> bool {
+        self.halt_on_violation
+    }
+
+    /// Total number of violations recorded so far.
+    pub fn violations_count(&self) -> usize {
+        self.violations_count
+    }
+
+    /// Checks invariants triggered by the `action` and records violations.
+    ///
+    /// Global invariants are ignored, since they can only be checked
+    /// in the testing cluster.
+    pub fn check<S: Service + InvariantService>(
+        &mut self,
+        store: &mut Store<S>,
+        action: &ActionWithMeta,
+    ) -> Vec<InvariantViolation> {
+        let violations = Invariants::check_all(store, action)
+            .filter_map(|(invariant, res)| match res {
+                InvariantResult::Violation(violation) => Some((invariant, violation)),
+                InvariantResult::Ignored(
+                    InvariantIgnoreReason::GlobalInvariantNotInTestingCluster,
+                )
+                | InvariantResult::Updated
+                | InvariantResult::Ok => None,
+            })
+            .collect::<Vec<_>>();
+
+        let violations = violations
+            .into_iter()
+            .map(|(invariant, violation)| InvariantViolation {
+                invariant: invariant.to_str(),
+                time: action.time(),
+                action_kind: action.action().kind(),
+                action: serde_json::to_value(action.action())
+                    .unwrap_or_else(|err| format!("failed to serialize action: {err}").into()),
+                violation,
+                state: invariant.state_snippet(store),
+            })
+            .collect::<Vec<_>>();
+
+        for violation in &violations {
+            if let Err(err) = self.record(violation) {
+                node::core::error!(
+                    action.time();
+                    "failed to record invariant violation in {:?}: {err}",
+                    self.report_path
+                );
+            }
+        }
+        self.violations_count = self.violations_count.saturating_add(violations.len());
+        violations
+    }
+
+    fn record(&mut self, violation: &InvariantViolation) -> io::Result<()> {
+        serde_json::to_writer(&mut self.report, violation)?;
+        self.report.write_all(b"\n")?;
+        // Node might be halted right after the violation.
+        self.report.flush()
+    }
+}
@@ -0,0 +1,2 @@
+mod read_requests_are_accounted;
+pub use read_requests_are_accounted::*;
@@ -0,0 +1,73 @@
+use node::{
+    ledger::{
+        read::{LedgerReadAction, LedgerReadRequestState},
+        LedgerAction,
+    },
+    Action, ActionKind, ActionWithMeta, Service, Store,
+};
+
+use crate::{Invariant, InvariantResult};
+
+/// Makes sure that:
+/// 1. Total cost of pending ledger read requests matches the sum of
+///    costs of the requests.
+/// 2. Ledger read request is marked as successful once the response
+///    arrives, and is removed from the state once pruned.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct LedgerReadRequestsAreAccounted;
+
+impl Invariant for LedgerReadRequestsAreAccounted {
+    type InternalState = ();
+    fn triggers(&self) -> &[ActionKind] {
+        &[
+            ActionKind::LedgerReadPending,
+            ActionKind::LedgerReadSuccess,
+            ActionKind::LedgerReadPrune,
+        ]
+    }
+
+    fn check<S: Service>(
+        self,
+        _: &mut Self::InternalState,
+        store: &Store<S>,
+        action: &ActionWithMeta,
+    ) -> InvariantResult {
+        let read = &store.state().ledger.read;
+
+        let expected_cost = read
+            .pending_requests()
+            .map(|(_, req)| req.request().cost())
+            .fold(0usize, |acc, cost| acc.saturating_add(cost));
+        if read.total_cost() != expected_cost {
+            return InvariantResult::Violation(format!(
+                "ledger read requests total cost mismatch!\ntotal_cost: {}, expected: {expected_cost}, pending_count: {}",
+                read.total_cost(),
+                read.pending_count(),
+            ));
+        }
+
+        match action.action() {
+            Action::Ledger(LedgerAction::Read(LedgerReadAction::Success { id, .. })) => {
+                if !matches!(read.get(*id), Some(LedgerReadRequestState::Success { .. })) {
+                    return InvariantResult::Violation(format!(
+                        "ledger read request({id}) not marked as successful after response!"
+                    ));
+                }
+            }
+            Action::Ledger(LedgerAction::Read(LedgerReadAction::Prune { id })) => {
+                if read.contains(*id) {
+                    return InvariantResult::Violation(format!(
+                        "ledger read request({id}) still pending after prune!"
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        InvariantResult::Ok
+    }
+
+    fn state_snippet<S: Service>(&self, store: &Store<S>) -> serde_json::Value {
+        serde_json::to_value(&store.state().ledger.read).unwrap_or_default()
+    }
+}
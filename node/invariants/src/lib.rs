@@ -10,6 +10,18 @@ use p2p::*;
 pub mod transition_frontier;
 use transition_frontier::*;
 
+pub mod transaction_pool;
+use transaction_pool::*;
+
+pub mod snark_pool;
+use snark_pool::*;
+
+pub mod ledger;
+use ledger::*;
+
+mod checker;
+pub use checker::{InvariantViolation, InvariantsChecker};
+
 pub use node::core::invariants::{InvariantService, InvariantsState};
 
 use strum_macros::{EnumDiscriminants, EnumIter, EnumString, IntoStaticStr};
@@ -41,6 +53,12 @@ pub trait Invariant {
         store: &Store<S>,
         action: &ActionWithMeta,
     ) -> InvariantResult;
+
+    /// Part of the state relevant for the invariant, which gets recorded
+    /// along with the violation.
+    fn state_snippet<S: Service>(&self, _store: &Store<S>) -> serde_json::Value {
+        serde_json::Value::Null
+    }
 }
 
 macro_rules! define_invariants_enum {
@@ -68,6 +86,12 @@ macro_rules! define_invariants_enum {
                 }
             }
 
+            pub fn state_snippet<S: Service>(&self, store: &Store<S>) -> serde_json::Value {
+                match self {
+                    $(Self::$invariant(invariant) => invariant.state_snippet(store),)*
+                }
+            }
+
             pub fn check<S: Service + InvariantService>(
                 self,
                 store: &mut Store<S>,
@@ -109,6 +133,9 @@ define_invariants_enum! {
     NoRecursion,
     P2pStatesAreConsistent,
     TransitionFrontierOnlySyncsToBetterBlocks,
+    TransactionPoolOnlyPropagatesPooledTransactions,
+    SnarkPoolJobCommitmentsAreConsistent,
+    LedgerReadRequestsAreAccounted,
 }
 
 lazy_static::lazy_static! {
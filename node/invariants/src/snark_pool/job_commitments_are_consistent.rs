@@ -0,0 +1,82 @@
+use node::{snark_pool::SnarkPoolAction, Action, ActionKind, ActionWithMeta, Service, Store};
+
+use crate::{Invariant, InvariantResult};
+
+/// Makes sure that:
+/// 1. Commitments and snarks in the snark pool are stored under the job
+///    they were made for.
+/// 2. Timed out commitment is removed from the job.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct SnarkPoolJobCommitmentsAreConsistent;
+
+impl Invariant for SnarkPoolJobCommitmentsAreConsistent {
+    type InternalState = ();
+    fn triggers(&self) -> &[ActionKind] {
+        &[
+            ActionKind::SnarkPoolJobsUpdate,
+            ActionKind::SnarkPoolCommitmentAdd,
+            ActionKind::SnarkPoolWorkAdd,
+            ActionKind::SnarkPoolJobCommitmentTimeout,
+        ]
+    }
+
+    fn check<S: Service>(
+        self,
+        _: &mut Self::InternalState,
+        store: &Store<S>,
+        action: &ActionWithMeta,
+    ) -> InvariantResult {
+        let snark_pool = &store.state().snark_pool;
+
+        if let Action::SnarkPool(SnarkPoolAction::JobCommitmentTimeout { job_id }) = action.action()
+        {
+            if let Some(commitment) = snark_pool
+                .get(job_id)
+                .and_then(|job| job.commitment.as_ref())
+            {
+                return InvariantResult::Violation(format!(
+                    "commitment wasn't removed after timeout!\njob_id: {job_id}\ncommitment: {}",
+                    serde_json::to_string(commitment).unwrap(),
+                ));
+            }
+        }
+
+        for job in snark_pool.jobs_iter() {
+            if let Some(commitment) = &job.commitment {
+                if commitment.commitment.job_id != job.id {
+                    return InvariantResult::Violation(format!(
+                        "commitment stored under a wrong job!\njob_id: {}\ncommitment_job_id: {}",
+                        job.id, commitment.commitment.job_id,
+                    ));
+                }
+            }
+            if let Some(snark) = &job.snark {
+                let snark_job_id = snark.work.job_id();
+                if snark_job_id != job.id {
+                    return InvariantResult::Violation(format!(
+                        "snark stored under a wrong job!\njob_id: {}\nsnark_job_id: {snark_job_id}",
+                        job.id,
+                    ));
+                }
+            }
+        }
+
+        InvariantResult::Ok
+    }
+
+    fn state_snippet<S: Service>(&self, store: &Store<S>) -> serde_json::Value {
+        let jobs = store
+            .state()
+            .snark_pool
+            .jobs_iter()
+            .map(|job| {
+                serde_json::json!({
+                    "id": job.id,
+                    "commitment": job.commitment,
+                    "snark_job_id": job.snark.as_ref().map(|snark| snark.work.job_id()),
+                })
+            })
+            .collect::<Vec<_>>();
+        serde_json::json!({ "jobs": jobs })
+    }
+}
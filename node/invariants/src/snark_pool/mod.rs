@@ -0,0 +1,2 @@
+mod job_commitments_are_consistent;
+pub use job_commitments_are_consistent::*;
@@ -0,0 +1,2 @@
+mod only_propagates_pooled_transactions;
+pub use only_propagates_pooled_transactions::*;
@@ -0,0 +1,51 @@
+use node::{ActionKind, ActionWithMeta, Service, Store};
+
+use crate::{Invariant, InvariantResult};
+
+/// Makes sure that transactions which are being propagated to peers
+/// (`dpool`) are all present in the transaction pool.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct TransactionPoolOnlyPropagatesPooledTransactions;
+
+impl Invariant for TransactionPoolOnlyPropagatesPooledTransactions {
+    type InternalState = ();
+    fn triggers(&self) -> &[ActionKind] {
+        &[
+            ActionKind::TransactionPoolBestTipChangedWithAccounts,
+            ActionKind::TransactionPoolApplyVerifiedDiffWithAccounts,
+            ActionKind::TransactionPoolApplyTransitionFrontierDiffWithAccounts,
+        ]
+    }
+
+    fn check<S: Service>(
+        self,
+        _: &mut Self::InternalState,
+        store: &Store<S>,
+        _action: &ActionWithMeta,
+    ) -> InvariantResult {
+        let transaction_pool = &store.state().transaction_pool;
+        let missing = transaction_pool
+            .for_propagation_iter()
+            .filter(|tx| !transaction_pool.contains(&tx.hash))
+            .map(|tx| tx.hash.to_string())
+            .collect::<Vec<_>>();
+
+        if !missing.is_empty() {
+            return InvariantResult::Violation(format!(
+                "transactions are being propagated, but are missing from the pool!\npool_size: {}, for_propagation_size: {}\nmissing:\n{missing:?}",
+                transaction_pool.size(),
+                transaction_pool.for_propagation_size(),
+            ));
+        }
+
+        InvariantResult::Ok
+    }
+
+    fn state_snippet<S: Service>(&self, store: &Store<S>) -> serde_json::Value {
+        let transaction_pool = &store.state().transaction_pool;
+        serde_json::json!({
+            "size": transaction_pool.size(),
+            "for_propagation": transaction_pool.for_propagation_iter().collect::<Vec<_>>(),
+        })
+    }
+}
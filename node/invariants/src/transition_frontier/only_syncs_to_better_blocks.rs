@@ -109,4 +109,18 @@ impl Invariant for TransitionFrontierOnlySyncsToBetterBlocks {
             InvariantResult::Updated
         }
     }
+
+    fn state_snippet<S: redux::Service>(&self, store: &Store<S>) -> serde_json::Value {
+        let transition_frontier = &store.state().transition_frontier;
+        let block_summary = |block: &ArcBlockWithHash| {
+            serde_json::json!({
+                "hash": block.hash(),
+                "consensus_state": block.consensus_state(),
+            })
+        };
+        serde_json::json!({
+            "best_tip": transition_frontier.best_tip().map(block_summary),
+            "sync_best_tip": transition_frontier.sync.best_tip().map(block_summary),
+        })
+    }
 }
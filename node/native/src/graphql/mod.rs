@@ -1,19 +1,27 @@
 use std::str::FromStr;
+use std::sync::Arc;
 
 use juniper::{graphql_value, FieldError};
-use juniper::{EmptySubscription, GraphQLEnum, RootNode};
+use juniper::{GraphQLEnum, RootNode};
+use juniper_graphql_ws::ConnectionConfig;
 use ledger::Account;
 use mina_p2p_messages::v2::MinaBaseSignedCommandStableV2;
 use mina_p2p_messages::v2::MinaBaseUserCommandStableV2;
 use mina_p2p_messages::v2::MinaBaseZkappCommandTStableV1WireStableV1;
+use mina_p2p_messages::v2::StateHash;
 use mina_p2p_messages::v2::TokenIdKeyHash;
 use node::rpc::RpcTransactionInjectResponse;
+use node::rpc::RpcTransactionInjectSuccess;
 use node::rpc::RpcTransactionInjectedCommand;
 use node::rpc::RpcTransactionStatusGetResponse;
+use node::rpc::{
+    RpcBlockGetQuery, RpcBlockGetResponse, RpcSnarkPoolGetResponse, RpcTransactionPoolResponse,
+};
 use node::{
     account::AccountPublicKey,
     rpc::{AccountQuery, RpcRequest, RpcSyncStatsGetResponse, SyncStatsQuery},
     stats::sync::SyncKind,
+    BuildEnv,
 };
 use openmina_core::block::AppliedBlock;
 use openmina_core::consensus::ConsensusConstants;
@@ -24,6 +32,9 @@ use warp::{Filter, Rejection, Reply};
 pub mod account;
 pub mod block;
 pub mod constants;
+pub mod snark;
+pub mod subscription;
+pub mod user_command;
 pub mod zkapp;
 
 #[derive(Debug, thiserror::Error)]
@@ -66,10 +77,94 @@ pub enum ConversionError {
     Custom(String),
 }
 
+#[derive(Clone)]
 struct Context(RpcSender);
 
 impl juniper::Context for Context {}
 
+impl Context {
+    async fn transaction_pool(&self) -> Result<RpcTransactionPoolResponse, Error> {
+        self.0
+            .oneshot_request(RpcRequest::TransactionPoolGet)
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)
+    }
+
+    async fn inject_user_command(
+        &self,
+        command: MinaBaseUserCommandStableV2,
+    ) -> juniper::FieldResult<RpcTransactionInjectSuccess> {
+        let res: RpcTransactionInjectResponse = self
+            .0
+            .oneshot_request(RpcRequest::TransactionInject(vec![command]))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        match res {
+            RpcTransactionInjectResponse::Success(res) => Ok(res),
+            RpcTransactionInjectResponse::Rejected(rejected) => {
+                let error_list = rejected
+                    .into_iter()
+                    .map(|(_, err)| graphql_value!({ "message": err.to_string() }))
+                    .collect::<Vec<_>>();
+
+                Err(FieldError::new(
+                    "Transaction rejected",
+                    graphql_value!(juniper::Value::List(error_list)),
+                ))
+            }
+            RpcTransactionInjectResponse::Failure(failure) => {
+                let error_list = failure
+                    .into_iter()
+                    .map(|err| graphql_value!({ "message": err.to_string() }))
+                    .collect::<Vec<_>>();
+
+                Err(FieldError::new(
+                    "Transaction failed",
+                    graphql_value!(juniper::Value::List(error_list)),
+                ))
+            }
+        }
+    }
+}
+
+/// Filter for the pooled commands queries. Commands are matched by
+/// the fee payer and, if any are given, by hashes or ids.
+struct PooledCommandsFilter {
+    public_key: Option<String>,
+    hashes: Option<Vec<String>>,
+    ids: Option<Vec<String>>,
+}
+
+impl PooledCommandsFilter {
+    fn matches(&self, fee_payer: &str, hash: &str, id: &str) -> bool {
+        if self.public_key.as_deref().is_some_and(|pk| pk != fee_payer) {
+            return false;
+        }
+        match (&self.hashes, &self.ids) {
+            (None, None) => true,
+            (hashes, ids) => {
+                hashes.iter().flatten().any(|h| h == hash) || ids.iter().flatten().any(|i| i == id)
+            }
+        }
+    }
+}
+
+/// Pooled commands which can't be converted are logged and left out, so
+/// that a single one doesn't fail the whole query.
+fn skip_unconvertible<T>(result: Result<T, ConversionError>) -> Option<T> {
+    result
+        .map_err(|err| {
+            openmina_core::warn!(
+                openmina_core::log::system_time();
+                kind = "GraphQLPooledCommands",
+                summary = "skipping pooled command which can't be converted",
+                error = err.to_string()
+            );
+        })
+        .ok()
+}
+
 #[derive(Clone, Copy, Debug, GraphQLEnum)]
 #[allow(clippy::upper_case_acronyms)]
 enum SyncStatus {
@@ -264,6 +359,102 @@ impl Query {
             .ok_or(Error::StateMachineEmptyResponse)?;
         Ok(res.to_string())
     }
+
+    async fn block(
+        state_hash: Option<String>,
+        height: Option<i32>,
+        context: &Context,
+    ) -> juniper::FieldResult<block::GraphQLBestChainBlock> {
+        let query = match (state_hash, height) {
+            (Some(state_hash), None) => {
+                RpcBlockGetQuery::ForBlockWithHash(StateHash::from_str(&state_hash)?)
+            }
+            (None, Some(height)) => RpcBlockGetQuery::ForBlockWithHeight(height.try_into()?),
+            _ => {
+                return Err(Error::Custom(
+                    "Must provide exactly one of state hash or height".to_string(),
+                )
+                .into())
+            }
+        };
+        let block: RpcBlockGetResponse = context
+            .0
+            .oneshot_request(RpcRequest::BlockGet(query))
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(block
+            .ok_or_else(|| Error::Custom("Block not found in transition frontier".to_string()))?
+            .try_into()?)
+    }
+
+    async fn pooled_user_commands(
+        public_key: Option<String>,
+        hashes: Option<Vec<String>>,
+        ids: Option<Vec<String>>,
+        context: &Context,
+    ) -> juniper::FieldResult<Vec<user_command::GraphQLUserCommand>> {
+        let filter = PooledCommandsFilter {
+            public_key,
+            hashes,
+            ids,
+        };
+        let pool = context.transaction_pool().await?;
+
+        Ok(pool
+            .into_iter()
+            .map(|tx| MinaBaseUserCommandStableV2::from(tx.data))
+            .filter(|cmd| matches!(cmd, MinaBaseUserCommandStableV2::SignedCommand(_)))
+            .filter_map(|cmd| skip_unconvertible(user_command::GraphQLUserCommand::try_from(cmd)))
+            .filter(|cmd| filter.matches(&cmd.fee_payer.public_key, &cmd.hash, &cmd.id))
+            .collect())
+    }
+
+    async fn pooled_zkapp_commands(
+        public_key: Option<String>,
+        hashes: Option<Vec<String>>,
+        ids: Option<Vec<String>>,
+        context: &Context,
+    ) -> juniper::FieldResult<Vec<zkapp::GraphQLZkapp>> {
+        let filter = PooledCommandsFilter {
+            public_key,
+            hashes,
+            ids,
+        };
+        let pool = context.transaction_pool().await?;
+
+        Ok(pool
+            .into_iter()
+            .map(|tx| MinaBaseUserCommandStableV2::from(tx.data))
+            .filter(|cmd| matches!(cmd, MinaBaseUserCommandStableV2::ZkappCommand(_)))
+            .filter_map(|cmd| {
+                skip_unconvertible(
+                    zkapp::GraphQLSendZkappResponse::try_from(cmd).map(|res| res.zkapp),
+                )
+            })
+            .filter(|zkapp| {
+                let fee_payer = &zkapp.zkapp_command.fee_payer.body.public_key;
+                filter.matches(fee_payer, &zkapp.hash, &zkapp.id)
+            })
+            .collect())
+    }
+
+    async fn snark_pool(context: &Context) -> juniper::FieldResult<Vec<snark::GraphQLSnarkWork>> {
+        let jobs: RpcSnarkPoolGetResponse = context
+            .0
+            .oneshot_request(RpcRequest::SnarkPoolGet)
+            .await
+            .ok_or(Error::StateMachineEmptyResponse)?;
+
+        Ok(jobs
+            .into_iter()
+            .filter_map(snark::GraphQLSnarkWork::from_job)
+            .collect())
+    }
+
+    fn version() -> String {
+        BuildEnv::get().git.commit_hash
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -275,56 +466,65 @@ impl Mutation {
         input: zkapp::SendZkappInput,
         context: &Context,
     ) -> juniper::FieldResult<zkapp::GraphQLSendZkappResponse> {
-        let res: RpcTransactionInjectResponse = context
-            .0
-            .oneshot_request(RpcRequest::TransactionInject(vec![input.try_into()?]))
-            .await
-            .ok_or(Error::StateMachineEmptyResponse)?;
+        let res = context.inject_user_command(input.try_into()?).await?;
 
-        match res {
-            RpcTransactionInjectResponse::Success(res) => {
-                let zkapp_cmd: MinaBaseUserCommandStableV2 = match res.first().cloned() {
-                    Some(RpcTransactionInjectedCommand::Zkapp(zkapp_cmd)) => zkapp_cmd.into(),
-                    _ => unreachable!(),
-                };
-                Ok(zkapp_cmd.try_into()?)
-            }
-            RpcTransactionInjectResponse::Rejected(rejected) => {
-                let error_list = rejected
-                    .into_iter()
-                    .map(|(_, err)| graphql_value!({ "message": err.to_string() }))
-                    .collect::<Vec<_>>();
+        let zkapp_cmd: MinaBaseUserCommandStableV2 = match res.first().cloned() {
+            Some(RpcTransactionInjectedCommand::Zkapp(zkapp_cmd)) => zkapp_cmd.into(),
+            _ => unreachable!(),
+        };
+        Ok(zkapp_cmd.try_into()?)
+    }
 
-                Err(FieldError::new(
-                    "Transaction rejected",
-                    graphql_value!(juniper::Value::List(error_list)),
-                ))
-            }
-            RpcTransactionInjectResponse::Failure(failure) => {
-                let error_list = failure
-                    .into_iter()
-                    .map(|err| graphql_value!({ "message": err.to_string() }))
-                    .collect::<Vec<_>>();
+    async fn send_payment(
+        input: user_command::SendPaymentInput,
+        signature: Option<user_command::SignatureInput>,
+        context: &Context,
+    ) -> juniper::FieldResult<user_command::GraphQLSendPaymentResponse> {
+        let signature = signature.ok_or(Error::Custom(
+            "Signature is required, node doesn't hold any wallet keys".to_string(),
+        ))?;
+        let cmd = input.into_command(signature)?;
+        context.inject_user_command(cmd.clone()).await?;
+
+        Ok(user_command::GraphQLSendPaymentResponse {
+            payment: cmd.try_into()?,
+        })
+    }
 
-                Err(FieldError::new(
-                    "Transaction failed",
-                    graphql_value!(juniper::Value::List(error_list)),
-                ))
-            }
-        }
+    async fn send_delegation(
+        input: user_command::SendDelegationInput,
+        signature: Option<user_command::SignatureInput>,
+        context: &Context,
+    ) -> juniper::FieldResult<user_command::GraphQLSendDelegationResponse> {
+        let signature = signature.ok_or(Error::Custom(
+            "Signature is required, node doesn't hold any wallet keys".to_string(),
+        ))?;
+        let cmd = input.into_command(signature)?;
+        context.inject_user_command(cmd.clone()).await?;
+
+        Ok(user_command::GraphQLSendDelegationResponse {
+            delegation: cmd.try_into()?,
+        })
     }
 }
 
 pub fn routes(
     rpc_sernder: RpcSender,
 ) -> impl Filter<Error = Rejection, Extract = impl Reply> + Clone {
-    let state = warp::any().map(move || Context(rpc_sernder.clone()));
-    let schema = RootNode::new(Query, Mutation, EmptySubscription::<Context>::new());
-    let graphql_filter = juniper_warp::make_graphql_filter(schema, state.boxed());
-    let graphiql_filter = juniper_warp::graphiql_filter("/graphql", None);
-    let playground_filter = juniper_warp::playground_filter("/graphql", None);
+    let context = Context(rpc_sernder);
+    let state = {
+        let context = context.clone();
+        warp::any().map(move || context.clone())
+    };
+    let schema = Arc::new(RootNode::new(Query, Mutation, subscription::Subscription));
+    let graphql_filter = juniper_warp::make_graphql_filter(schema.clone(), state.boxed());
+    let subscriptions_filter =
+        juniper_warp::subscriptions::make_ws_filter(schema, ConnectionConfig::new(context));
+    let graphiql_filter = juniper_warp::graphiql_filter("/graphql", Some("/subscriptions"));
+    let playground_filter = juniper_warp::playground_filter("/graphql", Some("/subscriptions"));
 
     (warp::post().and(warp::path("graphql")).and(graphql_filter))
+        .or(warp::path("subscriptions").and(subscriptions_filter))
         .or(warp::get()
             .and(warp::path("playground"))
             .and(playground_filter))
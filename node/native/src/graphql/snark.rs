@@ -0,0 +1,34 @@
+use juniper::GraphQLObject;
+use node::{core::snark::SnarkJobId, rpc::RpcSnarkPoolJobSummary};
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Completed snark work in the snark pool")]
+pub struct GraphQLSnarkWork {
+    pub prover: String,
+    pub fee: String,
+    pub work_ids: Vec<i32>,
+}
+
+impl GraphQLSnarkWork {
+    /// Returns `None` if the job doesn't have a snark yet.
+    pub fn from_job(job: RpcSnarkPoolJobSummary) -> Option<Self> {
+        let snark = job.snark?;
+        Some(Self {
+            prover: snark.snarker.to_string(),
+            fee: snark.fee.as_u64().to_string(),
+            work_ids: vec![work_id(&job.id)],
+        })
+    }
+}
+
+/// Non-negative `Int` id of the work.
+///
+/// The OCaml node hashes the work statements, which aren't available
+/// here, so this is a stable (FNV-1a) hash of the job id instead. It
+/// identifies the work within this node, but doesn't match OCaml ids.
+fn work_id(id: &SnarkJobId) -> i32 {
+    let hash = id.to_string().bytes().fold(0x811c_9dc5_u32, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193)
+    });
+    (hash & 0x7fff_ffff) as i32
+}
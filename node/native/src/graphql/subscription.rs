@@ -0,0 +1,146 @@
+use std::{future::ready, pin::Pin, str::FromStr};
+
+use futures::{Stream, StreamExt};
+use juniper::{FieldError, FieldResult, GraphQLObject};
+use ledger::Account;
+use mina_p2p_messages::v2::TokenIdKeyHash;
+use node::{
+    account::AccountPublicKey,
+    core::channels::broadcast,
+    rpc::{AccountQuery, RpcChainReorganization, RpcRequest, RpcSubscriptionEvent},
+};
+use openmina_node_common::rpc::RpcSender;
+
+use super::{account, block, Context, Error};
+
+type SubscriptionStream<T> = Pin<Box<dyn Stream<Item = FieldResult<T>> + Send>>;
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "Best tip switched to a block that isn't a descendant of the previous one")]
+pub struct GraphQLChainReorganization {
+    pub old_best_tip: String,
+    pub new_best_tip: String,
+    pub common_ancestor: Option<String>,
+    /// Short range fork decision, missing if the fork was long range.
+    pub decision: Option<String>,
+}
+
+impl From<RpcChainReorganization> for GraphQLChainReorganization {
+    fn from(value: RpcChainReorganization) -> Self {
+        Self {
+            old_best_tip: value.old_best_tip.to_string(),
+            new_best_tip: value.new_best_tip.to_string(),
+            common_ancestor: value.common_ancestor.map(|hash| hash.to_string()),
+            decision: value.decision.map(|decision| format!("{decision:?}")),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Subscription;
+
+#[juniper::graphql_subscription(context = Context)]
+impl Subscription {
+    /// Blocks as they become part of the best chain.
+    async fn new_block(context: &Context) -> SubscriptionStream<block::GraphQLBestChainBlock> {
+        Box::pin(events(context.0.subscribe()).filter_map(|event| {
+            ready(match event {
+                Ok(RpcSubscriptionEvent::NewBlock(block)) => {
+                    Some(block::GraphQLBestChainBlock::try_from(block).map_err(FieldError::from))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+        }))
+    }
+
+    async fn chain_reorganization(
+        context: &Context,
+    ) -> SubscriptionStream<GraphQLChainReorganization> {
+        Box::pin(events(context.0.subscribe()).filter_map(|event| {
+            ready(match event {
+                Ok(RpcSubscriptionEvent::ChainReorganization(reorg)) => {
+                    Some(Ok(GraphQLChainReorganization::from(reorg)))
+                }
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+        }))
+    }
+
+    /// Account state whenever it changes in the best tip ledger.
+    async fn new_account_state(
+        public_key: String,
+        token: Option<String>,
+        context: &Context,
+    ) -> FieldResult<SubscriptionStream<account::GraphQLAccount>> {
+        let public_key = AccountPublicKey::from_str(&public_key)?;
+        let query = match token {
+            Some(token) => {
+                AccountQuery::PubKeyWithTokenId(public_key, TokenIdKeyHash::from_str(&token)?)
+            }
+            None => AccountQuery::SinglePublicKey(public_key),
+        };
+        let sender = context.0.clone();
+
+        let stream = events(context.0.subscribe())
+            .filter_map(|event| {
+                ready(match event {
+                    Ok(RpcSubscriptionEvent::NewBlock(_)) => Some(Ok(())),
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                })
+            })
+            .then(move |res| {
+                let sender = sender.clone();
+                let query = query.clone();
+                async move {
+                    res?;
+                    fetch_account(&sender, query).await
+                }
+            })
+            .scan(None, |last: &mut Option<Account>, state| {
+                let item = match state {
+                    Ok(Some(state)) if last.as_ref() != Some(&state) => {
+                        *last = Some(state.clone());
+                        Some(account::GraphQLAccount::try_from(state).map_err(FieldError::from))
+                    }
+                    Ok(_) => None,
+                    Err(err) => Some(Err(err)),
+                };
+                ready(Some(item))
+            })
+            .filter_map(ready);
+
+        Ok(Box::pin(stream))
+    }
+}
+
+async fn fetch_account(sender: &RpcSender, query: AccountQuery) -> FieldResult<Option<Account>> {
+    let accounts: Vec<Account> = sender
+        .oneshot_request(RpcRequest::LedgerAccountsGet(query))
+        .await
+        .ok_or(Error::StateMachineEmptyResponse)?;
+    Ok(accounts.into_iter().next())
+}
+
+/// Events from the state machine. Subscriber that lags behind by more
+/// than the backlog gets an error and the stream ends, so that a slow
+/// client can't make the node buffer events for it.
+fn events(
+    rx: broadcast::Receiver<RpcSubscriptionEvent>,
+) -> impl Stream<Item = FieldResult<RpcSubscriptionEvent>> + Send {
+    futures::stream::unfold(Some(rx), |rx| async move {
+        let mut rx = rx?;
+        match rx.recv().await {
+            Ok(event) => Some((Ok(event), Some(rx))),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                let err = Error::Custom(format!(
+                    "subscriber lagged behind by {skipped} events, dropping"
+                ));
+                Some((Err(err.into()), None))
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    })
+}
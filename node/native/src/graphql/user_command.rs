@@ -0,0 +1,351 @@
+use std::str::FromStr;
+
+use juniper::{GraphQLInputObject, GraphQLObject};
+use ledger::scan_state::{
+    currency::{Amount, Fee, Nonce, Slot},
+    transaction_logic::{signed_command, Memo},
+};
+use mina_p2p_messages::{
+    bigint::BigInt,
+    string::MEMO_MAX_LENGTH,
+    v2::{
+        MinaBaseSignedCommandPayloadBodyStableV2, MinaBaseSignedCommandStableV2,
+        MinaBaseStakeDelegationStableV2, MinaBaseUserCommandStableV2,
+    },
+};
+use mina_signer::CompressedPubKey;
+use node::account::AccountPublicKey;
+use serde::{Deserialize, Serialize};
+
+use super::ConversionError;
+
+#[derive(GraphQLInputObject, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SendPaymentInput {
+    pub from: String,
+    pub to: String,
+    pub amount: String,
+    pub fee: String,
+    pub valid_until: Option<String>,
+    pub memo: Option<String>,
+    pub nonce: Option<String>,
+}
+
+#[derive(GraphQLInputObject, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SendDelegationInput {
+    pub from: String,
+    pub to: String,
+    pub fee: String,
+    pub valid_until: Option<String>,
+    pub memo: Option<String>,
+    pub nonce: Option<String>,
+}
+
+/// Signature either as a pair of decimal field and scalar, or
+/// as a hex encoded `rawSignature`.
+#[derive(GraphQLInputObject, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SignatureInput {
+    pub field: Option<String>,
+    pub scalar: Option<String>,
+    pub raw_signature: Option<String>,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLSendPaymentResponse {
+    pub payment: GraphQLUserCommand,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLSendDelegationResponse {
+    pub delegation: GraphQLUserCommand,
+}
+
+#[derive(GraphQLObject, Debug)]
+#[graphql(description = "A signed payment or stake delegation")]
+pub struct GraphQLUserCommand {
+    /// Signed command represented as base64 string
+    pub id: String,
+    pub hash: String,
+    pub kind: String,
+    pub nonce: i32,
+    pub from: String,
+    pub to: String,
+    pub source: GraphQLUserCommandAccount,
+    pub receiver: GraphQLUserCommandAccount,
+    pub fee_payer: GraphQLUserCommandAccount,
+    pub amount: String,
+    pub fee: String,
+    pub memo: String,
+    pub valid_until: String,
+    pub is_delegation: bool,
+}
+
+#[derive(GraphQLObject, Debug)]
+pub struct GraphQLUserCommandAccount {
+    pub public_key: String,
+}
+
+impl SendPaymentInput {
+    pub fn into_command(
+        self,
+        signature: SignatureInput,
+    ) -> Result<MinaBaseUserCommandStableV2, ConversionError> {
+        let body = signed_command::Body::Payment(signed_command::PaymentPayload {
+            receiver_pk: parse_public_key(&self.to)?,
+            amount: Amount::from_u64(self.amount.parse()?),
+        });
+        build_signed_command(
+            &self.from,
+            &self.fee,
+            self.nonce.as_deref(),
+            self.valid_until.as_deref(),
+            self.memo.as_deref(),
+            body,
+            signature,
+        )
+    }
+}
+
+impl SendDelegationInput {
+    pub fn into_command(
+        self,
+        signature: SignatureInput,
+    ) -> Result<MinaBaseUserCommandStableV2, ConversionError> {
+        let body = signed_command::Body::StakeDelegation(
+            signed_command::StakeDelegationPayload::SetDelegate {
+                new_delegate: parse_public_key(&self.to)?,
+            },
+        );
+        build_signed_command(
+            &self.from,
+            &self.fee,
+            self.nonce.as_deref(),
+            self.valid_until.as_deref(),
+            self.memo.as_deref(),
+            body,
+            signature,
+        )
+    }
+}
+
+fn build_signed_command(
+    from: &str,
+    fee: &str,
+    nonce: Option<&str>,
+    valid_until: Option<&str>,
+    memo: Option<&str>,
+    body: signed_command::Body,
+    signature: SignatureInput,
+) -> Result<MinaBaseUserCommandStableV2, ConversionError> {
+    // The signature commits to the nonce, so we can't infer it here.
+    let nonce = nonce.ok_or_else(|| ConversionError::MissingField("nonce".to_owned()))?;
+    let memo = memo.unwrap_or_default();
+    if memo.len() > MEMO_MAX_LENGTH {
+        return Err(ConversionError::Custom(format!(
+            "memo is longer than {MEMO_MAX_LENGTH} bytes"
+        )));
+    }
+    let from = parse_public_key(from)?;
+
+    let cmd = signed_command::SignedCommand {
+        payload: signed_command::SignedCommandPayload::create(
+            Fee::from_u64(fee.parse()?),
+            from.clone(),
+            Nonce::from_u32(nonce.parse()?),
+            valid_until
+                .map(|slot| slot.parse().map(Slot::from_u32))
+                .transpose()?,
+            Memo::from_str(memo).map_err(|_| ConversionError::Custom("invalid memo".into()))?,
+            body,
+        ),
+        signer: from,
+        signature: signature.try_into()?,
+    };
+
+    Ok(MinaBaseUserCommandStableV2::SignedCommand((&cmd).into()))
+}
+
+fn parse_public_key(s: &str) -> Result<CompressedPubKey, ConversionError> {
+    AccountPublicKey::from_str(s)?
+        .try_into()
+        .map_err(|_| ConversionError::InvalidBigInt)
+}
+
+impl TryFrom<SignatureInput> for mina_signer::Signature {
+    type Error = ConversionError;
+
+    fn try_from(value: SignatureInput) -> Result<Self, Self::Error> {
+        let (field, scalar) = match value {
+            SignatureInput {
+                field: Some(field),
+                scalar: Some(scalar),
+                ..
+            } => (
+                BigInt::from_decimal(&field)?,
+                BigInt::from_decimal(&scalar)?,
+            ),
+            SignatureInput {
+                raw_signature: Some(raw),
+                ..
+            } => {
+                // 32 bytes of field followed by 32 bytes of scalar, both little endian.
+                let bytes = decode_hex(&raw)?;
+                if bytes.len() != 64 {
+                    return Err(ConversionError::InvalidLength);
+                }
+                let (field, scalar) = bytes.split_at(32);
+                (
+                    BigInt::from_bytes(field.try_into().or(Err(ConversionError::InvalidLength))?),
+                    BigInt::from_bytes(scalar.try_into().or(Err(ConversionError::InvalidLength))?),
+                )
+            }
+            _ => {
+                return Err(ConversionError::MissingField(
+                    "field and scalar, or rawSignature".to_owned(),
+                ))
+            }
+        };
+
+        Ok(mina_signer::Signature {
+            rx: field
+                .try_into()
+                .map_err(|_| ConversionError::InvalidBigInt)?,
+            s: scalar
+                .try_into()
+                .map_err(|_| ConversionError::InvalidBigInt)?,
+        })
+    }
+}
+
+fn decode_hex(s: &str) -> Result<Vec<u8>, ConversionError> {
+    if s.len() % 2 != 0 {
+        return Err(ConversionError::InvalidLength);
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| {
+            let byte = s.get(i..i + 2).ok_or(ConversionError::InvalidLength)?;
+            Ok(u8::from_str_radix(byte, 16)?)
+        })
+        .collect()
+}
+
+impl TryFrom<MinaBaseUserCommandStableV2> for GraphQLUserCommand {
+    type Error = ConversionError;
+
+    fn try_from(value: MinaBaseUserCommandStableV2) -> Result<Self, Self::Error> {
+        let MinaBaseUserCommandStableV2::SignedCommand(cmd) = value else {
+            return Err(ConversionError::WrongVariant);
+        };
+        cmd.try_into()
+    }
+}
+
+impl TryFrom<MinaBaseSignedCommandStableV2> for GraphQLUserCommand {
+    type Error = ConversionError;
+
+    fn try_from(value: MinaBaseSignedCommandStableV2) -> Result<Self, Self::Error> {
+        let common = &value.payload.common;
+        let fee_payer = common.fee_payer_pk.to_string();
+        let (kind, receiver, amount) = match &value.payload.body {
+            MinaBaseSignedCommandPayloadBodyStableV2::Payment(payment) => (
+                "PAYMENT",
+                payment.receiver_pk.to_string(),
+                payment.amount.as_u64(),
+            ),
+            MinaBaseSignedCommandPayloadBodyStableV2::StakeDelegation(
+                MinaBaseStakeDelegationStableV2::SetDelegate { new_delegate },
+            ) => ("STAKE_DELEGATION", new_delegate.to_string(), 0),
+        };
+
+        Ok(Self {
+            id: value.to_base64()?,
+            hash: value.hash()?.to_string(),
+            kind: kind.to_owned(),
+            nonce: common.nonce.as_u32().try_into()?,
+            from: fee_payer.clone(),
+            to: receiver.clone(),
+            source: GraphQLUserCommandAccount {
+                public_key: fee_payer.clone(),
+            },
+            receiver: GraphQLUserCommandAccount {
+                public_key: receiver,
+            },
+            fee_payer: GraphQLUserCommandAccount {
+                public_key: fee_payer,
+            },
+            amount: amount.to_string(),
+            fee: common.fee.as_u64().to_string(),
+            memo: common.memo.to_base58check(),
+            valid_until: common.valid_until.as_u32().to_string(),
+            is_delegation: kind == "STAKE_DELEGATION",
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PK: &str = "B62qpD75xH5R19wxZG2uz8whNsHPTioVoYcPV3zfjjSbzTmaHQHKKEV";
+
+    #[test]
+    fn test_send_payment_into_command() {
+        let input = SendPaymentInput {
+            from: PK.to_owned(),
+            to: PK.to_owned(),
+            amount: "1000000000".to_owned(),
+            fee: "10000000".to_owned(),
+            valid_until: None,
+            memo: Some("hello".to_owned()),
+            nonce: Some("3".to_owned()),
+        };
+        let signature = SignatureInput {
+            field: Some("1".to_owned()),
+            scalar: Some("1".to_owned()),
+            raw_signature: None,
+        };
+        let cmd = input.into_command(signature).unwrap();
+        let cmd = GraphQLUserCommand::try_from(cmd).unwrap();
+
+        assert_eq!(cmd.kind, "PAYMENT");
+        assert_eq!(cmd.nonce, 3);
+        assert_eq!(cmd.amount, "1000000000");
+        assert_eq!(cmd.fee_payer.public_key, PK);
+    }
+
+    #[test]
+    fn test_send_delegation_input_json() {
+        let json = serde_json::json!({
+            "from": PK,
+            "to": PK,
+            "fee": "10000000",
+            "validUntil": "100",
+            "memo": null,
+            "nonce": "1",
+        });
+        let input = serde_json::from_value::<SendDelegationInput>(json.clone()).unwrap();
+        assert_eq!(input.valid_until.as_deref(), Some("100"));
+        assert_eq!(serde_json::to_value(&input).unwrap(), json);
+
+        let signature = serde_json::from_value::<SignatureInput>(serde_json::json!({
+            "rawSignature": format!("01{:0<62}01{:0<62}", "", ""),
+        }))
+        .unwrap();
+        let cmd = GraphQLUserCommand::try_from(input.into_command(signature).unwrap()).unwrap();
+        assert!(cmd.is_delegation);
+        assert_eq!(cmd.valid_until, "100");
+    }
+
+    #[test]
+    fn test_signature_requires_field_and_scalar() {
+        let signature = SignatureInput {
+            field: Some("1".to_owned()),
+            scalar: None,
+            raw_signature: None,
+        };
+        assert!(mina_signer::Signature::try_from(signature).is_err());
+    }
+}
@@ -1,3 +1,5 @@
+mod metrics;
+
 use std::{convert::Infallible, mem::size_of, str::FromStr};
 
 use mina_p2p_messages::binprot::BinProtWrite;
@@ -587,6 +589,7 @@ pub async fn run(port: u16, rpc_sender: RpcSender) {
         transition_frontier_user_commands,
         healthcheck(rpc_sender.clone()),
         readiness(rpc_sender.clone()),
+        prometheus_metrics(rpc_sender.clone()),
         discovery::routing_table(rpc_sender.clone()),
         discovery::bootstrap_stats(rpc_sender.clone()),
         super::graphql::routes(rpc_sender),
@@ -647,6 +650,36 @@ fn readiness(
     })
 }
 
+fn prometheus_metrics(
+    rpc_sender: RpcSender,
+) -> impl Filter<Error = Rejection, Extract = impl Reply> + Clone {
+    warp::path!("metrics").and(warp::get()).then(move || {
+        let rpc_sender = rpc_sender.clone();
+        async move {
+            rpc_sender
+                .oneshot_request(RpcRequest::MetricsGet)
+                .await
+                .map_or_else(
+                    || {
+                        with_status(
+                            String::from(DROPPED_CHANNEL),
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                        )
+                        .into_response()
+                    },
+                    |reply: RpcMetricsGetResponse| {
+                        warp::reply::with_header(
+                            metrics::encode(&reply),
+                            CONTENT_TYPE,
+                            metrics::CONTENT_TYPE,
+                        )
+                        .into_response()
+                    },
+                )
+        }
+    })
+}
+
 mod discovery {
     use node::rpc::{
         RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse, RpcRequest,
@@ -0,0 +1,313 @@
+//! Renders [`RpcMetrics`] in the Prometheus text exposition format.
+
+use std::fmt::{Display, Write};
+
+use node::rpc::RpcMetrics;
+
+const PREFIX: &str = "openmina";
+
+const SYNC_PHASES: [&str; 3] = ["Bootstrap", "Catchup", "Synced"];
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+#[derive(Default)]
+struct Encoder {
+    out: String,
+}
+
+impl Encoder {
+    fn header(&mut self, name: &str, kind: &str, help: &str) {
+        let _ = writeln!(self.out, "# HELP {PREFIX}_{name} {help}");
+        let _ = writeln!(self.out, "# TYPE {PREFIX}_{name} {kind}");
+    }
+
+    fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: impl Display) {
+        let _ = write!(self.out, "{PREFIX}_{name}");
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (label, value)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{label}=\"{}\"", escape_label_value(value));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {value}");
+    }
+
+    fn gauge(&mut self, name: &str, help: &str, value: impl Display) {
+        self.header(name, "gauge", help);
+        self.sample(name, &[], value);
+    }
+
+    /// Gauge which is omitted when its value is unknown.
+    fn gauge_opt(&mut self, name: &str, help: &str, value: Option<impl Display>) {
+        if let Some(value) = value {
+            self.gauge(name, help, value);
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn nanos_to_secs(nanos: u64) -> f64 {
+    nanos as f64 / 1_000_000_000.0
+}
+
+pub fn encode(metrics: &RpcMetrics) -> String {
+    let mut e = Encoder::default();
+
+    e.gauge_opt(
+        "best_tip_height",
+        "Height of the best tip.",
+        metrics.best_tip_height,
+    );
+    e.gauge_opt(
+        "best_tip_global_slot",
+        "Global slot of the best tip.",
+        metrics.best_tip_global_slot,
+    );
+    e.gauge_opt(
+        "current_global_slot",
+        "Current global slot.",
+        metrics.current_global_slot,
+    );
+    if let (Some(current), Some(best_tip)) =
+        (metrics.current_global_slot, metrics.best_tip_global_slot)
+    {
+        e.gauge(
+            "best_tip_lag_slots",
+            "Number of slots between the best tip and the current slot.",
+            current.saturating_sub(best_tip),
+        );
+    }
+
+    e.header(
+        "sync_phase",
+        "gauge",
+        "Current phase of the transition frontier synchronization.",
+    );
+    let sync_phase = metrics.sync_phase.to_string();
+    for phase in SYNC_PHASES {
+        e.sample(
+            "sync_phase",
+            &[("phase", phase)],
+            u8::from(phase == sync_phase),
+        );
+    }
+
+    let peers = &metrics.peers;
+    e.header("peers", "gauge", "Number of peers, by connection state.");
+    for (state, count) in [
+        ("connecting", peers.connecting),
+        ("ready", peers.ready),
+        ("disconnecting", peers.disconnecting),
+        ("disconnected", peers.disconnected),
+    ] {
+        e.sample("peers", &[("state", state)], count);
+    }
+    e.gauge("peers_banned", "Number of banned peers.", peers.banned);
+
+    e.header(
+        "gossip_messages_received_total",
+        "counter",
+        "Gossip messages received from peers, including duplicates.",
+    );
+    for (topic, stats) in &metrics.gossip {
+        e.sample(
+            "gossip_messages_received_total",
+            &[("topic", topic)],
+            stats.received,
+        );
+    }
+    e.header(
+        "gossip_messages_duplicate_total",
+        "counter",
+        "Gossip messages received from peers which were already seen.",
+    );
+    for (topic, stats) in &metrics.gossip {
+        e.sample(
+            "gossip_messages_duplicate_total",
+            &[("topic", topic)],
+            stats.duplicates,
+        );
+    }
+    e.header(
+        "gossip_messages_published_total",
+        "counter",
+        "Gossip messages published by this node.",
+    );
+    for (topic, stats) in &metrics.gossip {
+        e.sample(
+            "gossip_messages_published_total",
+            &[("topic", topic)],
+            stats.published,
+        );
+    }
+
+    let transaction_pool = &metrics.transaction_pool;
+    e.gauge(
+        "transaction_pool_size",
+        "Number of transactions in the transaction pool.",
+        transaction_pool.transactions,
+    );
+    e.gauge(
+        "transaction_pool_for_propagation_size",
+        "Number of transactions of the pool waiting to be propagated.",
+        transaction_pool.transactions_for_propagation,
+    );
+    e.gauge(
+        "transaction_pool_candidates",
+        "Number of received transactions waiting to be verified.",
+        transaction_pool.transaction_candidates,
+    );
+    e.gauge(
+        "snark_pool_jobs",
+        "Number of jobs in the snark pool.",
+        metrics.snark_pool.total_jobs,
+    );
+    e.gauge(
+        "snark_pool_snarks",
+        "Number of jobs in the snark pool with a snark.",
+        metrics.snark_pool.snarks,
+    );
+
+    e.gauge(
+        "ledger_read_pending_requests",
+        "Number of read requests queued in the ledger service.",
+        metrics.ledger_read_pending,
+    );
+    e.gauge(
+        "ledger_read_pending_cost",
+        "Total cost of read requests queued in the ledger service.",
+        metrics.ledger_read_total_cost,
+    );
+    e.gauge(
+        "ledger_write_pending",
+        "Whether a write request is being handled by the ledger service.",
+        u8::from(metrics.ledger_write_pending),
+    );
+
+    if let Some(outcomes) = &metrics.block_production {
+        e.header(
+            "block_production_attempts_total",
+            "counter",
+            "Block production attempts, by outcome.",
+        );
+        for (outcome, count) in [
+            ("scheduled", outcomes.scheduled),
+            ("committed", outcomes.committed),
+            ("orphaned", outcomes.orphaned),
+            ("discarded", outcomes.discarded),
+        ] {
+            e.sample(
+                "block_production_attempts_total",
+                &[("outcome", outcome)],
+                count,
+            );
+        }
+    }
+
+    if let Some(action_stats) = &metrics.action_stats {
+        let name = "action_duration_seconds";
+        e.header(
+            name,
+            "histogram",
+            "Time from an action until the next one, by action kind.",
+        );
+        for (kind, stats) in action_stats.iter() {
+            let ranges = stats.ranges();
+            let count = ranges.iter().fold(0u64, |acc, (_, range)| {
+                acc.saturating_add(range.total_calls)
+            });
+            if count == 0 {
+                continue;
+            }
+            let kind = format!("{kind:?}");
+
+            let mut cumulative = 0u64;
+            let mut sum = 0u64;
+            for (upper_bound, range) in ranges {
+                cumulative = cumulative.saturating_add(range.total_calls);
+                sum = sum.saturating_add(range.total_duration);
+                let le = upper_bound.map_or_else(
+                    || "+Inf".to_owned(),
+                    |nanos| nanos_to_secs(nanos).to_string(),
+                );
+                e.sample(
+                    &format!("{name}_bucket"),
+                    &[("kind", &kind), ("le", &le)],
+                    cumulative,
+                );
+            }
+            e.sample(
+                &format!("{name}_sum"),
+                &[("kind", &kind)],
+                nanos_to_secs(sum),
+            );
+            e.sample(&format!("{name}_count"), &[("kind", &kind)], count);
+        }
+    }
+
+    e.out
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use node::p2p::P2pNetworkPubsubTopicStats;
+    use node::rpc::{RpcMetrics, RpcMetricsPeers};
+    use node::transition_frontier::sync::SyncPhase;
+
+    use super::encode;
+
+    fn metrics() -> RpcMetrics {
+        RpcMetrics {
+            best_tip_height: Some(10),
+            best_tip_global_slot: Some(20),
+            current_global_slot: Some(23),
+            sync_phase: SyncPhase::Synced,
+            peers: RpcMetricsPeers {
+                ready: 5,
+                ..Default::default()
+            },
+            gossip: BTreeMap::from([(
+                "topic\"a".to_owned(),
+                P2pNetworkPubsubTopicStats {
+                    received: 3,
+                    duplicates: 1,
+                    published: 2,
+                },
+            )]),
+            transaction_pool: Default::default(),
+            snark_pool: Default::default(),
+            ledger_read_pending: 1,
+            ledger_read_total_cost: 4,
+            ledger_write_pending: false,
+            action_stats: None,
+            block_production: None,
+        }
+    }
+
+    #[test]
+    fn test_encode() {
+        let out = encode(&metrics());
+        let lines = out.lines().collect::<Vec<_>>();
+
+        assert!(lines.contains(&"openmina_best_tip_lag_slots 3"));
+        assert!(lines.contains(&"openmina_sync_phase{phase=\"Synced\"} 1"));
+        assert!(lines.contains(&"openmina_sync_phase{phase=\"Catchup\"} 0"));
+        assert!(lines.contains(&"openmina_peers{state=\"ready\"} 5"));
+        assert!(lines.contains(&"openmina_gossip_messages_received_total{topic=\"topic\\\"a\"} 3"));
+        assert!(lines.contains(&"# TYPE openmina_ledger_read_pending_requests gauge"));
+        assert!(!out.contains("action_duration_seconds"));
+        assert!(!out.contains("block_production_attempts_total"));
+    }
+}
@@ -0,0 +1,41 @@
+use std::sync::Mutex;
+
+use node::{ActionWithMeta, Store};
+use openmina_node_invariants::InvariantsChecker;
+
+use crate::NodeService;
+
+/// Effects are plain functions, so the checker has to live outside of them.
+static CHECKER: Mutex<Option<InvariantsChecker>> = Mutex::new(None);
+
+/// Installs the `checker` and returns effects which run it after every
+/// action.
+pub(crate) fn effects_with_checker(checker: InvariantsChecker) -> node::Effects<NodeService> {
+    *CHECKER.lock().unwrap() = Some(checker);
+    effects
+}
+
+fn effects(store: &mut Store<NodeService>, action: ActionWithMeta) {
+    // Taken out, since effects are called recursively on dispatch.
+    let checker = CHECKER.lock().unwrap().take();
+    if let Some(mut checker) = checker {
+        let violations = checker.check(store, &action);
+        for violation in &violations {
+            node::core::error!(action.time();
+                kind = "InvariantViolation",
+                summary = format!("invariant {} violated after {:?}", violation.invariant, violation.action_kind),
+                violation = %violation.violation,
+                report = ?checker.report_path(),
+            );
+        }
+        if !violations.is_empty() && checker.halt_on_violation() {
+            panic!(
+                "invariant violated! halting the node, see report: {:?}",
+                checker.report_path()
+            );
+        }
+        *CHECKER.lock().unwrap() = Some(checker);
+    }
+
+    node::effects(store, action)
+}
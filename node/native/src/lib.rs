@@ -9,6 +9,15 @@ pub use service::{NodeService, *};
 mod node;
 pub use node::{Node, NodeBuilder};
 
+#[cfg(feature = "invariants")]
+mod invariants;
+
 #[path = "replay.rs"]
 mod replayer;
 pub use replayer::*;
+
+mod replay_debugger;
+pub use replay_debugger::{
+    parse_action_kind, ReplayCheckpointMismatch, ReplayDebugger, ReplayDebuggerConfig,
+    ReplayDivergence, ReplayValueChange, StateDiff, StateDiffEntry,
+};
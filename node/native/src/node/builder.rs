@@ -1,30 +1,31 @@
 use std::{
     fs::File,
     io::{BufRead, BufReader, Read},
-    net::IpAddr,
-    path::Path,
+    net::{IpAddr, SocketAddr},
+    path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
 
 use anyhow::Context;
-use ledger::proofs::provers::BlockProver;
+use ledger::{proofs::provers::BlockProver, scan_state::currency::Fee, transaction_pool};
 use mina_p2p_messages::v2::{self, NonZeroCurvePoint};
 use node::{
     account::AccountSecretKey,
     daemon_json::Daemon,
     p2p::{
         channels::ChannelId, connection::outgoing::P2pConnectionOutgoingInitOpts,
-        identity::SecretKey as P2pSecretKey, P2pLimits, P2pMeshsubConfig, P2pTimeouts,
+        identity::SecretKey as P2pSecretKey, P2pLimits, P2pMeshsubConfig, P2pNatConfig,
+        P2pReputationConfig, P2pTimeouts,
     },
     service::Recorder,
     snark::{get_srs, BlockVerifier, TransactionVerifier, VerifierSRS},
     transition_frontier::genesis::GenesisConfig,
-    BlockProducerConfig, GlobalConfig, LedgerConfig, P2pConfig, SnarkConfig, SnarkerConfig,
-    SnarkerStrategy, TransitionFrontierConfig,
+    BlockProducerConfig, BlockProducerKeyConfig, GlobalConfig, LedgerConfig, P2pConfig,
+    SnarkConfig, SnarkerConfig, SnarkerStrategy, TransitionFrontierConfig,
 };
 use openmina_core::{consensus::ConsensusConstants, constants::constraint_constants};
-use openmina_node_common::p2p::TaskSpawner;
+use openmina_node_common::{archive::ArchiveStorage, p2p::TaskSpawner};
 use rand::Rng;
 
 use crate::NodeServiceBuilder;
@@ -40,13 +41,22 @@ pub struct NodeBuilder {
     p2p_is_seed: bool,
     p2p_is_started: bool,
     block_producer: Option<BlockProducerConfig>,
+    block_producer_keys: Vec<AccountSecretKey>,
+    block_producer_provers: Option<BlockProver>,
     snarker: Option<SnarkerConfig>,
     service: NodeServiceBuilder,
     verifier_srs: Option<Arc<VerifierSRS>>,
     block_verifier_index: Option<BlockVerifier>,
     work_verifier_index: Option<TransactionVerifier>,
     http_port: Option<u16>,
+    frontier_store_path: Option<PathBuf>,
+    ondisk_ledgers_path: Option<PathBuf>,
+    archive: bool,
     daemon_conf: Daemon,
+    tx_pool_max_per_sender: usize,
+    tx_pool_replace_fee: Fee,
+    #[cfg(feature = "invariants")]
+    invariants_checker: Option<openmina_node_invariants::InvariantsChecker>,
 }
 
 impl NodeBuilder {
@@ -68,6 +78,7 @@ impl NodeBuilder {
             genesis_config,
             p2p: P2pConfig {
                 libp2p_port: None,
+                quic_port: None,
                 listen_port: None,
                 // Must be replaced with builder api.
                 identity_pub_key: P2pSecretKey::deterministic(0).public_key(),
@@ -81,18 +92,29 @@ impl NodeBuilder {
                 },
                 timeouts: P2pTimeouts::default(),
                 limits: P2pLimits::default().with_max_peers(Some(100)),
+                reputation: P2pReputationConfig::default(),
+                nat: P2pNatConfig::default(),
             },
             p2p_sec_key: None,
             p2p_is_seed: false,
             p2p_is_started: false,
             block_producer: None,
+            block_producer_keys: Vec::new(),
+            block_producer_provers: None,
             snarker: None,
             service: NodeServiceBuilder::new(rng_seed),
             verifier_srs: None,
             block_verifier_index: None,
             work_verifier_index: None,
             http_port: None,
+            frontier_store_path: None,
+            ondisk_ledgers_path: None,
+            archive: false,
             daemon_conf,
+            tx_pool_max_per_sender: transaction_pool::DEFAULT_MAX_PER_SENDER,
+            tx_pool_replace_fee: transaction_pool::DEFAULT_REPLACE_FEE,
+            #[cfg(feature = "invariants")]
+            invariants_checker: None,
         }
     }
 
@@ -114,6 +136,12 @@ impl NodeBuilder {
         self
     }
 
+    /// Listen for libp2p QUIC connections on the UDP port.
+    pub fn p2p_quic_port(&mut self, port: u16) -> &mut Self {
+        self.p2p.quic_port = Some(port);
+        self
+    }
+
     /// Set up node as a seed node.
     pub fn p2p_seed_node(&mut self) -> &mut Self {
         self.p2p_is_seed = true;
@@ -139,6 +167,15 @@ impl NodeBuilder {
         self
     }
 
+    /// Request the NAT gateway to forward the libp2p port, using NAT-PMP
+    /// and falling back to UPnP. If `gateway` is not set, the default
+    /// gateway is used.
+    pub fn p2p_port_mapping(&mut self, gateway: Option<SocketAddr>) -> &mut Self {
+        self.p2p.nat.port_mapping = true;
+        self.p2p.nat.gateway = gateway;
+        self
+    }
+
     /// Extend p2p initial peers from file.
     pub fn initial_peers_from_file(&mut self, path: impl AsRef<Path>) -> anyhow::Result<&mut Self> {
         peers_from_reader(
@@ -176,6 +213,18 @@ impl NodeBuilder {
         self
     }
 
+    /// Limit the number of queued transactions from a single sender.
+    pub fn tx_pool_max_per_sender(&mut self, limit: usize) -> &mut Self {
+        self.tx_pool_max_per_sender = limit;
+        self
+    }
+
+    /// Minimum fee increase for replacing a queued transaction.
+    pub fn tx_pool_replace_fee(&mut self, fee: Fee) -> &mut Self {
+        self.tx_pool_replace_fee = fee;
+        self
+    }
+
     /// Override default p2p task spawner.
     pub fn p2p_custom_task_spawner(
         &mut self,
@@ -194,16 +243,32 @@ impl NodeBuilder {
         key: AccountSecretKey,
         provers: Option<BlockProver>,
     ) -> &mut Self {
-        let config = BlockProducerConfig {
-            pub_key: key.public_key().into(),
-            custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
-        };
+        let config = BlockProducerConfig::new(key.public_key().into());
         self.block_producer = Some(config);
-        self.service.block_producer_init(key, provers);
+        self.block_producer_keys = vec![key];
+        self.block_producer_provers = provers;
         self
     }
 
+    /// Produce blocks with one more key, in addition to the one set up
+    /// with [`Self::block_producer`].
+    pub fn additional_block_producer(
+        &mut self,
+        key: AccountSecretKey,
+        coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let bp = self.block_producer.as_mut().ok_or_else(|| {
+            anyhow::anyhow!("block producer not initialized! Call `block_producer` function first.")
+        })?;
+        let mut producer = BlockProducerKeyConfig::new(key.public_key().into());
+        producer.custom_coinbase_receiver = coinbase_receiver;
+        bp.add_producer(producer);
+        self.block_producer_keys
+            .retain(|v| v.public_key() != key.public_key());
+        self.block_producer_keys.push(key);
+        Ok(self)
+    }
+
     /// Set up block producer using keys from file.
     pub fn block_producer_from_file(
         &mut self,
@@ -216,6 +281,18 @@ impl NodeBuilder {
         Ok(self.block_producer(key, provers))
     }
 
+    /// Produce blocks with one more key from file.
+    pub fn additional_block_producer_from_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        password: &str,
+        coinbase_receiver: Option<NonZeroCurvePoint>,
+    ) -> anyhow::Result<&mut Self> {
+        let key = AccountSecretKey::from_encrypted_file(path, password)
+            .context("Failed to decrypt secret key file")?;
+        self.additional_block_producer(key, coinbase_receiver)
+    }
+
     /// Receive block producer's coinbase reward to another account.
     pub fn custom_coinbase_receiver(
         &mut self,
@@ -226,7 +303,9 @@ impl NodeBuilder {
                 "can't set custom_coinbase_receiver when block producer is not initialized."
             )
         })?;
-        bp.custom_coinbase_receiver = Some(addr);
+        if let Some(producer) = bp.producers.first_mut() {
+            producer.custom_coinbase_receiver = Some(addr);
+        }
         Ok(self)
     }
 
@@ -284,6 +363,51 @@ impl NodeBuilder {
         self
     }
 
+    /// Persist transition frontier in `path` and resume from it on restart.
+    pub fn transition_frontier_persistence(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.frontier_store_path = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Keep snarked and epoch ledgers in `path` instead of memory and
+    /// reopen them on restart.
+    pub fn ondisk_ledgers(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.ondisk_ledgers_path = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Send blocks applied to the transition frontier to the archive
+    /// `storage`. Blocks that can't be archived at the moment are kept in
+    /// `spool_dir` and archived once the storage is available again.
+    pub fn archive(
+        &mut self,
+        storage: ArchiveStorage,
+        spool_dir: impl AsRef<Path>,
+    ) -> anyhow::Result<&mut Self> {
+        self.service
+            .archive_init(storage, spool_dir)
+            .context("starting archive sink")?;
+        self.archive = true;
+        Ok(self)
+    }
+
+    /// Check all registered invariants after every action and append
+    /// violations to the `report_path` file. If `halt_on_violation` is
+    /// set, node panics on the first violation.
+    #[cfg(feature = "invariants")]
+    pub fn check_invariants(
+        &mut self,
+        report_path: impl AsRef<Path>,
+        halt_on_violation: bool,
+    ) -> anyhow::Result<&mut Self> {
+        let report_path = report_path.as_ref();
+        let checker =
+            openmina_node_invariants::InvariantsChecker::new(report_path, halt_on_violation)
+                .with_context(|| format!("opening invariants report {report_path:?}"))?;
+        self.invariants_checker = Some(checker);
+        Ok(self)
+    }
+
     pub fn http_server(&mut self, port: u16) -> &mut Self {
         self.http_port = Some(port);
         self.service.http_server_init(port);
@@ -345,27 +469,55 @@ impl NodeBuilder {
                 work_verifier_index,
                 work_verifier_srs: srs,
             },
-            transition_frontier: TransitionFrontierConfig::new(self.genesis_config),
+            transition_frontier: TransitionFrontierConfig::new(self.genesis_config)
+                .with_persistence(self.frontier_store_path.is_some())
+                .with_archive(self.archive),
             block_producer: self.block_producer,
-            tx_pool: ledger::transaction_pool::Config {
+            tx_pool: transaction_pool::Config {
                 trust_system: (),
                 pool_max_size: self.daemon_conf.tx_pool_max_size(),
+                max_per_sender: self.tx_pool_max_per_sender,
+                replace_fee: self.tx_pool_replace_fee,
                 slot_tx_end: self.daemon_conf.slot_tx_end(),
             },
         };
 
         // build service
         let mut service = self.service;
-        service.ledger_init();
+        if self.frontier_store_path.is_some() || self.ondisk_ledgers_path.is_some() {
+            service
+                .ledger_init_persistent(
+                    self.frontier_store_path.as_deref(),
+                    self.ondisk_ledgers_path.as_deref(),
+                )
+                .context(anyhow::anyhow!(
+                    "opening transition frontier store {:?} or ledgers {:?}",
+                    self.frontier_store_path,
+                    self.ondisk_ledgers_path
+                ))?;
+        } else {
+            service.ledger_init();
+        }
 
         if !self.p2p_is_started {
             service.p2p_init(p2p_sec_key);
         }
 
+        if !self.block_producer_keys.is_empty() {
+            service.block_producer_init(self.block_producer_keys, self.block_producer_provers);
+        }
+
         let service = service.build()?;
         let state = node::State::new(node_config, &consensus_consts, initial_time);
 
-        Ok(Node::new(self.rng_seed, state, service, None))
+        #[cfg(feature = "invariants")]
+        let effects = self
+            .invariants_checker
+            .map(crate::invariants::effects_with_checker);
+        #[cfg(not(feature = "invariants"))]
+        let effects = None;
+
+        Ok(Node::new(self.rng_seed, state, service, effects))
     }
 }
 
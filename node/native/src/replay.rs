@@ -1,43 +1,257 @@
 use std::cell::RefCell;
 
 use node::{
-    core::thread, recorder::StateWithInputActionsReader, snark::BlockVerifier, ActionWithMeta,
-    BuildEnv, Store,
+    core::thread, p2p::identity::SecretKey as P2pSecretKey, recorder::StateWithInputActionsReader,
+    snark::BlockVerifier, ActionWithMeta, BuildEnv, State, Store,
 };
 
-use crate::NodeService;
+use crate::{
+    NodeService, ReplayCheckpointMismatch, ReplayDebugger, ReplayDebuggerConfig, ReplayDivergence,
+    ReplayValueChange,
+};
 
 pub fn replay_state_with_input_actions(
     dir: &str,
     dynamic_effects_lib: Option<String>,
-    mut check_build_env: impl FnMut(&BuildEnv, &BuildEnv) -> anyhow::Result<()>,
+    check_build_env: impl FnMut(&BuildEnv, &BuildEnv) -> anyhow::Result<()>,
+) -> anyhow::Result<crate::Node> {
+    replay_state_with_input_actions_with_debugger(
+        dir,
+        dynamic_effects_lib,
+        check_build_env,
+        ReplayDebuggerConfig::default(),
+    )
+}
+
+/// Same as [`replay_state_with_input_actions`], but lets the user pause
+/// the replay and inspect the state. See [`ReplayDebugger`].
+///
+/// If the initial state was already removed by the rolling recorder,
+/// the replay starts from the earliest checkpoint.
+///
+/// Fails with the divergence report if the replay diverges from the
+/// recording.
+pub fn replay_state_with_input_actions_with_debugger(
+    dir: &str,
+    dynamic_effects_lib: Option<String>,
+    check_build_env: impl FnMut(&BuildEnv, &BuildEnv) -> anyhow::Result<()>,
+    debugger_config: ReplayDebuggerConfig,
 ) -> anyhow::Result<crate::Node> {
     eprintln!("replaying node based on initial state and actions from the dir: {dir}");
     let reader = StateWithInputActionsReader::new(dir);
+    let start = ReplayStart::read(&reader, None)?;
+    let (node, mut debugger) = replay(
+        dir,
+        &reader,
+        start,
+        dynamic_effects_lib.clone(),
+        check_build_env,
+        |state| ReplayDebugger::new(debugger_config, dir, state),
+        None,
+    )?;
+    match debugger.take_divergence() {
+        None => Ok(node),
+        Some(divergence) => Err(divergence_error(
+            dir,
+            &reader,
+            divergence,
+            dynamic_effects_lib,
+        )),
+    }
+}
 
-    eprintln!(
-        "reading initial state from file: {}",
-        reader.initial_state_path().as_path().to_str().unwrap()
-    );
-    let initial_state = match reader.read_initial_state() {
-        Err(err) => anyhow::bail!("failed to read initial state. err: {err}"),
-        Ok(v) => v,
-    };
+/// Replays the recording up to the action with `action_id`, starting
+/// from the latest state recorded at or before it, which is either a
+/// checkpoint written by the rolling recorder, or the initial state.
+///
+/// Input actions recorded after `action_id` aren't applied, so the
+/// returned node has the state right after the action.
+pub fn seek_recorded_state(
+    dir: &str,
+    action_id: u64,
+    dynamic_effects_lib: Option<String>,
+    check_build_env: impl FnMut(&BuildEnv, &BuildEnv) -> anyhow::Result<()>,
+) -> anyhow::Result<crate::Node> {
+    let reader = StateWithInputActionsReader::new(dir);
+    let start = ReplayStart::read(&reader, Some(action_id))?;
+    let start_action_id = start.state.last_action().time_as_nanos();
+    if start_action_id > action_id {
+        anyhow::bail!(
+            "action {action_id} precedes the recording, which starts at {start_action_id}"
+        );
+    }
+    eprintln!("replaying actions from {start_action_id} up to {action_id}");
+    let (node, mut debugger) = replay(
+        dir,
+        &reader,
+        start,
+        dynamic_effects_lib.clone(),
+        check_build_env,
+        |state| ReplayDebugger::new(ReplayDebuggerConfig::default(), dir, state),
+        Some(action_id),
+    )?;
+    match debugger.take_divergence() {
+        None => Ok(node),
+        Some(divergence) => Err(divergence_error(
+            dir,
+            &reader,
+            divergence,
+            dynamic_effects_lib,
+        )),
+    }
+}
+
+/// Error with the report of the divergence. If the state differed from
+/// a checkpoint, the part of the recording since the last matching
+/// checkpoint is replayed again to find the first diverging action.
+fn divergence_error(
+    dir: &str,
+    reader: &StateWithInputActionsReader,
+    divergence: ReplayDivergence,
+    dynamic_effects_lib: Option<String>,
+) -> anyhow::Error {
+    let mut report = divergence.report;
+    if let Some(mismatch) = divergence.checkpoint_mismatch {
+        eprintln!(
+            "state differs from the checkpoint {}, replaying again to find the first diverging action",
+            mismatch.checkpoint_id
+        );
+        match first_diverging_action(dir, reader, mismatch, dynamic_effects_lib) {
+            Ok(Some(change)) => report.push_str(&format!(
+                "first action whose resulting state diverged: {:?} (id: {}), it set `{}`\n",
+                change.kind, change.action_id, change.path
+            )),
+            Ok(None) => report.push_str(
+                "diverging values weren't changed by the replay since the last matching checkpoint, only by the recording\n",
+            ),
+            Err(err) => report.push_str(&format!(
+                "failed to find the first diverging action: {err}\n"
+            )),
+        }
+    }
+    anyhow::anyhow!("replay diverged from the recording\n{report}")
+}
 
-    let rng_seed = initial_state.rng_seed;
-    let state = {
-        let mut state = initial_state.state.into_owned();
+/// Replays the recording from the last checkpoint which matched up to the
+/// mismatching one, and finds the earliest of the actions which last set
+/// the diverging values.
+fn first_diverging_action(
+    dir: &str,
+    reader: &StateWithInputActionsReader,
+    mismatch: ReplayCheckpointMismatch,
+    dynamic_effects_lib: Option<String>,
+) -> anyhow::Result<Option<ReplayValueChange>> {
+    let start = ReplayStart::read(reader, mismatch.last_matching_checkpoint)?;
+    let (_, debugger) = replay(
+        dir,
+        reader,
+        start,
+        dynamic_effects_lib,
+        |_, _| Ok(()),
+        |state| ReplayDebugger::watching(dir, state, mismatch.paths),
+        Some(mismatch.checkpoint_id),
+    )?;
+    Ok(debugger.first_last_change().cloned())
+}
+
+/// State the replay starts from, either the initial state or a checkpoint.
+struct ReplayStart {
+    rng_seed: [u8; 32],
+    p2p_sec_key: P2pSecretKey,
+    state: State,
+    /// Index of the first actions file to replay.
+    actions_f_index: usize,
+}
+
+impl ReplayStart {
+    /// Reads the latest checkpoint at or before `until_action_id`, or the
+    /// initial state if there is none. Without `until_action_id`, reads the
+    /// initial state, or the earliest checkpoint if the initial state was
+    /// already removed.
+    fn read(
+        reader: &StateWithInputActionsReader,
+        until_action_id: Option<u64>,
+    ) -> anyhow::Result<Self> {
+        let checkpoint = match until_action_id {
+            Some(action_id) => reader.nearest_checkpoint(action_id),
+            None if reader.initial_state_path().exists() => Ok(None),
+            None => reader
+                .checkpoints()
+                .map(|checkpoints| checkpoints.into_iter().next()),
+        };
+        let checkpoint = match checkpoint {
+            Err(err) => anyhow::bail!("failed to list checkpoints. err: {err}"),
+            Ok(v) => v,
+        };
+
+        let mut start = match checkpoint {
+            Some(checkpoint) => {
+                eprintln!(
+                    "reading checkpoint from file: {}",
+                    checkpoint.path.as_path().to_str().unwrap()
+                );
+                let checkpoint = match reader.read_checkpoint(&checkpoint) {
+                    Err(err) => anyhow::bail!("failed to read checkpoint. err: {err}"),
+                    Ok(v) => v,
+                };
+                Self {
+                    rng_seed: checkpoint.rng_seed,
+                    p2p_sec_key: checkpoint.p2p_sec_key,
+                    state: checkpoint.state.into_owned(),
+                    actions_f_index: checkpoint.actions_f_index,
+                }
+            }
+            None => {
+                eprintln!(
+                    "reading initial state from file: {}",
+                    reader.initial_state_path().as_path().to_str().unwrap()
+                );
+                let initial_state = match reader.read_initial_state() {
+                    Err(err) => anyhow::bail!("failed to read initial state. err: {err}"),
+                    Ok(v) => v,
+                };
+                Self {
+                    rng_seed: initial_state.rng_seed,
+                    p2p_sec_key: initial_state.p2p_sec_key,
+                    state: initial_state.state.into_owned(),
+                    actions_f_index: 1,
+                }
+            }
+        };
         // TODO(binier): we shouldn't have to do this, but serialized
         // index/srs doesn't match deserialized one.
-        state.snark.block_verify.verifier_index = BlockVerifier::make();
-        state.snark.block_verify.verifier_srs = node::snark::get_srs();
-        state
-    };
+        start.state.snark.block_verify.verifier_index = BlockVerifier::make();
+        start.state.snark.block_verify.verifier_srs = node::snark::get_srs();
+        Ok(start)
+    }
+}
+
+/// Replays the recorded input actions following the `start` state. If
+/// `until_action_id` is set, stops before the first input action after it.
+///
+/// The service rng is seeded with the recorded seed, so when starting
+/// from a checkpoint, effects using randomness may not match the recording.
+///
+/// Stops at the first divergence, the returned debugger holds its report.
+fn replay(
+    dir: &str,
+    reader: &StateWithInputActionsReader,
+    start: ReplayStart,
+    dynamic_effects_lib: Option<String>,
+    mut check_build_env: impl FnMut(&BuildEnv, &BuildEnv) -> anyhow::Result<()>,
+    make_debugger: impl FnOnce(&State) -> ReplayDebugger,
+    until_action_id: Option<u64>,
+) -> anyhow::Result<(crate::Node, ReplayDebugger)> {
+    let ReplayStart {
+        rng_seed,
+        p2p_sec_key,
+        state,
+        actions_f_index,
+    } = start;
 
     let effects: node::Effects<NodeService> = dynamic_effects_lib
         .as_ref()
         .map_or(replayer_effects, |_| replayer_effects_with_dyn_effects);
-    let p2p_sec_key = initial_state.p2p_sec_key;
 
     let service = NodeService::for_replay(rng_seed, state.time(), p2p_sec_key, dynamic_effects_lib);
 
@@ -48,11 +262,14 @@ pub fn replay_state_with_input_actions(
     let replay_env = BuildEnv::get();
     check_build_env(&store.state().config.build, &replay_env)?;
 
+    let debugger = make_debugger(store.state());
+    REPLAY_DEBUGGER.with(|cell| *cell.borrow_mut() = Some(debugger));
+
     eprintln!("reading actions from dir: {dir}");
 
     let mut input_action = None;
     let mut actions = reader
-        .read_actions()
+        .read_actions_from(actions_f_index)
         .flat_map(|(path, actions)| {
             let file_path = path.as_path().to_str().unwrap();
             eprintln!("processing actions from file: {file_path}");
@@ -65,11 +282,22 @@ pub fn replay_state_with_input_actions(
         let expected_actions = &mut replayer.expected_actions;
 
         let action = if input_action.is_none() {
-            assert_eq!(
-                expected_actions.len(),
-                0,
-                "not all expected effects of the input action were dispatched! Ones left: {expected_actions:?}"
-            );
+            if !expected_actions.is_empty() {
+                let reason = format!("not all expected effects of the input action were dispatched! Ones left: {expected_actions:?}");
+                with_debugger(|debugger| debugger.diverged(&reason, None));
+            }
+            let action_id = action.meta.time_as_nanos();
+            with_debugger(|debugger| debugger.before_input_action(action_id, store.state.get()));
+            if with_debugger(|debugger| debugger.has_diverged()) {
+                break;
+            }
+            if until_action_id.is_some_and(|until_action_id| action_id > until_action_id) {
+                break;
+            }
+            if with_debugger(|debugger| debugger.should_quit()) {
+                eprintln!("replay stopped by the user");
+                break;
+            }
             let (action, meta) = actions
                 .next()
                 .unwrap()
@@ -101,10 +329,20 @@ pub fn replay_state_with_input_actions(
                 eprintln!("Warning! Executing last action for which we might not have all effect actions recorded.");
             }
             let action = input_action.take().unwrap();
-            assert!(store.dispatch(action));
+            let kind = action.kind();
+            if !store.dispatch(action) {
+                let reason = format!("input action {kind:?} wasn't enabled");
+                with_debugger(|debugger| debugger.diverged(&reason, None));
+            }
         }
     }
-    Ok(node)
+    if input_action.is_none() && actions.peek().is_none() {
+        with_debugger(|debugger| debugger.before_input_action(u64::MAX, store.state.get()));
+    }
+    let debugger = REPLAY_DEBUGGER
+        .with(|cell| cell.borrow_mut().take())
+        .expect("replay debugger not initialized");
+    Ok((node, debugger))
 }
 
 fn replayer_effects_with_dyn_effects(store: &mut Store<NodeService>, action: ActionWithMeta) {
@@ -114,17 +352,35 @@ fn replayer_effects_with_dyn_effects(store: &mut Store<NodeService>, action: Act
 
 fn replayer_effects(store: &mut Store<NodeService>, action: ActionWithMeta) {
     let replayer = store.service.replayer().unwrap();
-    let (kind, meta) = match replayer.expected_actions.pop_front() {
-        Some(v) => v,
-        None => panic!("unexpected action: {:?}", action),
+    let state = store.state.get();
+    let reason = match replayer.expected_actions.pop_front() {
+        None => Some(format!("unexpected action: {:?}", action)),
+        Some((kind, _)) if kind != action.action().kind() => Some(format!(
+            "expected action {kind:?}, got {:?}",
+            action.action().kind()
+        )),
+        Some((kind, meta)) if meta.time() != action.meta().time() => Some(format!(
+            "expected action {kind:?} at {}, got it at {}",
+            meta.time_as_nanos(),
+            action.meta().time_as_nanos()
+        )),
+        Some(_) => None,
     };
-
-    assert_eq!(kind, action.action().kind());
-    assert_eq!(meta.time(), action.meta().time());
+    with_debugger(|debugger| match reason {
+        Some(reason) => debugger.diverged_at(&reason, state),
+        None => debugger.on_action(&action, state),
+    });
 
     node::effects(store, action)
 }
 
+fn with_debugger<T>(f: impl FnOnce(&mut ReplayDebugger) -> T) -> T {
+    REPLAY_DEBUGGER.with(|cell| {
+        let mut debugger = cell.borrow_mut();
+        f(debugger.as_mut().expect("replay debugger not initialized"))
+    })
+}
+
 fn dyn_effects(store: &mut Store<NodeService>, action: &ActionWithMeta) {
     DYN_EFFECTS_LIB.with(move |cell| loop {
         let mut opt = cell.borrow_mut();
@@ -163,6 +419,7 @@ fn dyn_effects(store: &mut Store<NodeService>, action: &ActionWithMeta) {
 }
 
 thread_local! {
+    static REPLAY_DEBUGGER: RefCell<Option<ReplayDebugger>> = const { RefCell::new(None) };
     static DYN_EFFECTS_LIB: RefCell<Option<DynEffectsLib>> = const { RefCell::new(None)};
 }
 
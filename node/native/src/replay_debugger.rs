@@ -0,0 +1,640 @@
+//! Debugger for replays of recorded actions.
+//!
+//! It can pause the replay after actions of the given kinds or after
+//! each action, print structural diffs of the [`State`] grouped by top
+//! level subtree (`p2p`, `transition_frontier`, `snark_pool`...), and
+//! compares the replayed state with checkpoints of the recording.
+//!
+//! When the replay diverges from the recording, it is stopped with an
+//! error carrying a report, which pinpoints the first action which doesn't
+//! match the recording.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::io::{BufRead, Write};
+
+use node::recorder::{RecordedCheckpointInfo, StateWithInputActionsReader};
+use node::snark::BlockVerifier;
+use node::{ActionKind, ActionWithMeta, State};
+use serde_json::Value;
+use strum::VariantArray;
+
+/// How many of the last replayed actions are included in the
+/// divergence report.
+const HISTORY_LEN: usize = 32;
+/// Max number of changes printed per subtree.
+const MAX_SUBTREE_CHANGES: usize = 32;
+/// Max length of a printed json value.
+const MAX_VALUE_LEN: usize = 160;
+
+/// Fields of the state which change with every action.
+const IGNORED_FIELDS: [&str; 2] = ["last_action", "applied_actions_count"];
+
+#[derive(Debug, Default, Clone)]
+pub struct ReplayDebuggerConfig {
+    /// Pause after actions of these kinds are applied.
+    pub breakpoints: BTreeSet<ActionKind>,
+    /// Pause after the first replayed action.
+    pub step: bool,
+    /// Print the state diff of every action.
+    pub print_diffs: bool,
+}
+
+pub fn parse_action_kind(s: &str) -> Option<ActionKind> {
+    ActionKind::VARIANTS
+        .iter()
+        .find(|kind| format!("{kind:?}") == s)
+        .copied()
+}
+
+/// Change of a single value in the state.
+#[derive(Debug, PartialEq)]
+pub struct StateDiffEntry {
+    /// Path of the value, e.g. `transition_frontier.sync`.
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// Structural diff of two states, grouped by top level subtree.
+#[derive(Debug, Default)]
+pub struct StateDiff {
+    pub subtrees: BTreeMap<String, Vec<StateDiffEntry>>,
+}
+
+impl StateDiff {
+    pub fn new(before: &State, after: &State) -> Self {
+        Self::from_values(&state_to_value(before), &state_to_value(after))
+    }
+
+    pub fn from_values(before: &Value, after: &Value) -> Self {
+        let empty = serde_json::Map::new();
+        let before = before.as_object().unwrap_or(&empty);
+        let after = after.as_object().unwrap_or(&empty);
+
+        let subtrees = before
+            .keys()
+            .chain(after.keys())
+            .filter(|key| !IGNORED_FIELDS.contains(&key.as_str()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|key| {
+                let mut changes = vec![];
+                diff_values(key.clone(), before.get(key), after.get(key), &mut changes);
+                Some((key.clone(), changes)).filter(|(_, changes)| !changes.is_empty())
+            })
+            .collect();
+        Self { subtrees }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subtrees.is_empty()
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "  no changes");
+        }
+        for (subtree, changes) in &self.subtrees {
+            writeln!(f, "  [{subtree}] {} change(s)", changes.len())?;
+            for change in changes.iter().take(MAX_SUBTREE_CHANGES) {
+                writeln!(
+                    f,
+                    "    {}: {} -> {}",
+                    change.path,
+                    display_value(change.before.as_ref()),
+                    display_value(change.after.as_ref())
+                )?;
+            }
+            if changes.len() > MAX_SUBTREE_CHANGES {
+                let more = changes.len() - MAX_SUBTREE_CHANGES;
+                writeln!(f, "    ... and {more} more")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn diff_values(
+    path: String,
+    before: Option<&Value>,
+    after: Option<&Value>,
+    changes: &mut Vec<StateDiffEntry>,
+) {
+    match (before, after) {
+        (Some(Value::Object(before)), Some(Value::Object(after))) => {
+            let keys = before.keys().chain(after.keys()).collect::<BTreeSet<_>>();
+            for key in keys {
+                diff_values(
+                    format!("{path}.{key}"),
+                    before.get(key),
+                    after.get(key),
+                    changes,
+                );
+            }
+        }
+        (Some(Value::Array(before)), Some(Value::Array(after))) => {
+            for i in 0..before.len().max(after.len()) {
+                diff_values(format!("{path}[{i}]"), before.get(i), after.get(i), changes);
+            }
+        }
+        (before, after) if before != after => changes.push(StateDiffEntry {
+            path,
+            before: before.cloned(),
+            after: after.cloned(),
+        }),
+        _ => {}
+    }
+}
+
+fn display_value(value: Option<&Value>) -> String {
+    let Some(value) = value else {
+        return "<none>".to_owned();
+    };
+    let mut s = value.to_string();
+    if s.len() > MAX_VALUE_LEN {
+        let mut end = MAX_VALUE_LEN;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+        s.push_str("...");
+    }
+    s
+}
+
+/// Converts the path of a [`StateDiffEntry`], e.g. `snark_pool.jobs[2]`,
+/// to a json pointer, `/snark_pool/jobs/2`.
+fn json_pointer(path: &str) -> String {
+    path.split(['.', '['])
+        .map(|key| key.trim_end_matches(']'))
+        .fold(String::new(), |acc, key| {
+            acc + "/" + &key.replace('~', "~0").replace('/', "~1")
+        })
+}
+
+fn state_to_value(state: &State) -> Value {
+    serde_json::to_value(state)
+        .unwrap_or_else(|err| Value::String(format!("failed to serialize state: {err}")))
+}
+
+/// The replay diverged from the recording, it is stopped.
+#[derive(Debug)]
+pub struct ReplayDivergence {
+    pub report: String,
+    /// Set if the state differed from a recorded checkpoint.
+    pub checkpoint_mismatch: Option<ReplayCheckpointMismatch>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayCheckpointMismatch {
+    /// Last checkpoint which matched the replayed state, `None` if the
+    /// mismatch is at the first compared checkpoint.
+    pub last_matching_checkpoint: Option<u64>,
+    pub checkpoint_id: u64,
+    /// Paths of the values which differ from the checkpoint.
+    pub paths: Vec<String>,
+}
+
+/// Action after which a watched value was last changed.
+#[derive(Debug, Clone)]
+pub struct ReplayValueChange {
+    pub path: String,
+    pub kind: ActionKind,
+    pub action_id: u64,
+}
+
+/// Values of the state tracked after every action, see
+/// [`ReplayDebugger::watching`].
+struct WatchedValues {
+    paths: Vec<String>,
+    values: Vec<Option<Value>>,
+    last_changes: Vec<Option<ReplayValueChange>>,
+}
+
+pub struct ReplayDebugger {
+    config: ReplayDebuggerConfig,
+    /// Pause after the next action.
+    stepping: bool,
+    quit: bool,
+    /// State before the action which is being handled. Only kept when
+    /// state changes need to be shown, as cloning it isn't cheap.
+    prev_state: Option<State>,
+    history: VecDeque<(ActionKind, redux::ActionMeta)>,
+    reader: StateWithInputActionsReader,
+    checkpoints: VecDeque<RecordedCheckpointInfo>,
+    last_matching_checkpoint: Option<u64>,
+    divergence: Option<ReplayDivergence>,
+    watched: Option<WatchedValues>,
+}
+
+impl ReplayDebugger {
+    pub fn new(config: ReplayDebuggerConfig, dir: &str, initial_state: &State) -> Self {
+        let reader = StateWithInputActionsReader::new(dir);
+        let initial_action_id = initial_state.last_action().time_as_nanos();
+        let checkpoints = reader
+            .checkpoints()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|checkpoint| checkpoint.action_id > initial_action_id)
+            .collect::<VecDeque<_>>();
+        if !checkpoints.is_empty() {
+            eprintln!(
+                "replayed state will be compared with {} recorded checkpoint(s)",
+                checkpoints.len()
+            );
+        }
+
+        let mut debugger = Self {
+            stepping: config.step,
+            config,
+            quit: false,
+            prev_state: None,
+            history: VecDeque::with_capacity(HISTORY_LEN),
+            reader,
+            checkpoints,
+            last_matching_checkpoint: None,
+            divergence: None,
+            watched: None,
+        };
+        if debugger.tracks_state() {
+            debugger.prev_state = Some(initial_state.clone());
+        }
+        debugger
+    }
+
+    /// Debugger which doesn't compare the state with checkpoints, but
+    /// tracks the values at `paths` to find out which actions changed
+    /// them last. Used to replay the part of the recording in which the
+    /// state diverged from a checkpoint again.
+    pub fn watching(dir: &str, initial_state: &State, paths: Vec<String>) -> Self {
+        let mut debugger = Self::new(ReplayDebuggerConfig::default(), dir, initial_state);
+        debugger.checkpoints.clear();
+        let value = state_to_value(initial_state);
+        debugger.watched = Some(WatchedValues {
+            values: paths
+                .iter()
+                .map(|path| value.pointer(&json_pointer(path)).cloned())
+                .collect(),
+            last_changes: vec![None; paths.len()],
+            paths,
+        });
+        debugger
+    }
+
+    /// Whether the user asked to stop the replay.
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    pub fn has_diverged(&self) -> bool {
+        self.divergence.is_some()
+    }
+
+    pub fn take_divergence(&mut self) -> Option<ReplayDivergence> {
+        self.divergence.take()
+    }
+
+    /// Of the actions which last changed each of the watched values, the
+    /// earliest one. Values set by it and kept until the end of the
+    /// replay differ from the recording, so it is the first action whose
+    /// resulting state diverged.
+    pub fn first_last_change(&self) -> Option<&ReplayValueChange> {
+        self.watched
+            .as_ref()?
+            .last_changes
+            .iter()
+            .flatten()
+            .min_by_key(|change| change.action_id)
+    }
+
+    fn tracks_state(&self) -> bool {
+        self.stepping || self.config.print_diffs || !self.config.breakpoints.is_empty()
+    }
+
+    /// Must be called with the state after `action` was applied, but
+    /// before its effects are run.
+    pub fn on_action(&mut self, action: &ActionWithMeta, state: &State) {
+        let kind = action.action().kind();
+        if self.history.len() >= HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back((kind, action.meta().clone()));
+
+        if let Some(watched) = &mut self.watched {
+            watched.on_action(kind, action.meta().time_as_nanos(), state);
+        }
+
+        if !self.tracks_state() {
+            return;
+        }
+        let pause = self.stepping || self.config.breakpoints.contains(&kind);
+        if pause || self.config.print_diffs {
+            eprintln!("action {kind:?} (id: {})", action.meta().time_as_nanos());
+            if let Some(before) = &self.prev_state {
+                eprint!("{}", StateDiff::new(before, state));
+            }
+        }
+        if pause {
+            self.pause(action, state);
+        }
+        self.prev_state = self.tracks_state().then(|| state.clone());
+    }
+
+    /// Must be called with the current state before the input action with
+    /// `action_id` is applied. Checkpoints are taken in between input
+    /// actions, so the state is compared with the latest checkpoint taken
+    /// before the action.
+    pub fn before_input_action(&mut self, action_id: u64, state: &State) {
+        let mut checkpoint = None;
+        while let Some(next) = self
+            .checkpoints
+            .front()
+            .filter(|checkpoint| checkpoint.action_id <= action_id)
+        {
+            checkpoint = Some(next.clone());
+            self.checkpoints.pop_front();
+        }
+        let Some(checkpoint) = checkpoint else {
+            return;
+        };
+        let checkpoint_id = checkpoint.action_id;
+
+        let mut recorded = match self.reader.read_checkpoint(&checkpoint) {
+            Err(err) => {
+                eprintln!(
+                    "failed to read checkpoint {}: {err}",
+                    checkpoint.path.display()
+                );
+                return;
+            }
+            Ok(v) => v.state.into_owned(),
+        };
+        // same as for the initial state, serialized index/srs doesn't
+        // match deserialized one.
+        recorded.snark.block_verify.verifier_index = BlockVerifier::make();
+        recorded.snark.block_verify.verifier_srs = node::snark::get_srs();
+
+        let diff = StateDiff::new(&recorded, state);
+        if !diff.is_empty() {
+            let mismatch = ReplayCheckpointMismatch {
+                last_matching_checkpoint: self.last_matching_checkpoint,
+                checkpoint_id,
+                paths: diff
+                    .subtrees
+                    .values()
+                    .flatten()
+                    .map(|change| change.path.clone())
+                    .collect(),
+            };
+            if self.divergence.is_none() {
+                self.diverged(
+                    &format!("state differs from the recorded checkpoint {checkpoint_id}"),
+                    Some(("difference from the recording (recorded -> replayed)", diff)),
+                );
+                if let Some(divergence) = &mut self.divergence {
+                    divergence.checkpoint_mismatch = Some(mismatch);
+                }
+            }
+            return;
+        }
+        eprintln!("state matches the recorded checkpoint {checkpoint_id}");
+        self.last_matching_checkpoint = Some(checkpoint_id);
+    }
+
+    /// Stops the replay, which then fails with the divergence report.
+    /// Only the first divergence is reported.
+    pub fn diverged(&mut self, reason: &str, diff: Option<(&str, StateDiff)>) {
+        if self.divergence.is_some() {
+            return;
+        }
+        let mut report = String::new();
+        let _ = self.write_report(&mut report, reason, diff);
+        self.divergence = Some(ReplayDivergence {
+            report,
+            checkpoint_mismatch: None,
+        });
+        self.detach();
+    }
+
+    /// Same as [`ReplayDebugger::diverged`], but includes changes made by
+    /// the diverging action if the previous state is tracked.
+    pub fn diverged_at(&mut self, reason: &str, state: &State) {
+        let diff = self.prev_state.as_ref().map(|before| {
+            (
+                "changes made by the diverging action",
+                StateDiff::new(before, state),
+            )
+        });
+        self.diverged(reason, diff);
+    }
+
+    fn write_report(
+        &self,
+        w: &mut impl fmt::Write,
+        reason: &str,
+        diff: Option<(&str, StateDiff)>,
+    ) -> fmt::Result {
+        writeln!(w, "======== replay diverged ========")?;
+        writeln!(w, "reason: {reason}")?;
+        match self.history.back() {
+            Some((kind, meta)) => writeln!(
+                w,
+                "last replayed action: {kind:?} (id: {})",
+                meta.time_as_nanos()
+            )?,
+            None => writeln!(w, "no action was replayed")?,
+        }
+        match self.last_matching_checkpoint {
+            Some(id) => writeln!(w, "last checkpoint matching the recording: {id}")?,
+            None => writeln!(w, "no checkpoint was matched")?,
+        }
+        writeln!(w, "recently replayed actions (oldest first):")?;
+        for (kind, meta) in &self.history {
+            writeln!(w, "  {} {kind:?}", meta.time_as_nanos())?;
+        }
+        match diff {
+            Some((title, diff)) => {
+                writeln!(w, "{title}:")?;
+                write!(w, "{diff}")?;
+            }
+            None => writeln!(
+                w,
+                "state changes weren't tracked, replay with `--diff` to include them"
+            )?,
+        }
+        Ok(())
+    }
+
+    fn detach(&mut self) {
+        self.stepping = false;
+        self.config.breakpoints.clear();
+    }
+
+    fn pause(&mut self, action: &ActionWithMeta, state: &State) {
+        let stdin = std::io::stdin();
+        loop {
+            eprint!("(replay) ");
+            let _ = std::io::stderr().flush();
+
+            let mut line = String::new();
+            if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+                eprintln!("stdin closed, continuing without pausing");
+                self.detach();
+                return;
+            }
+            let mut args = line.split_whitespace();
+            match (args.next(), args.next()) {
+                (None | Some("s" | "step"), _) => {
+                    self.stepping = true;
+                    return;
+                }
+                (Some("c" | "continue"), _) => {
+                    self.stepping = false;
+                    return;
+                }
+                (Some("b" | "break"), Some(kind)) => match parse_action_kind(kind) {
+                    Some(kind) => {
+                        self.config.breakpoints.insert(kind);
+                    }
+                    None => eprintln!("unknown action kind: {kind}"),
+                },
+                (Some("d" | "delete"), Some(kind)) => match parse_action_kind(kind) {
+                    Some(kind) => {
+                        self.config.breakpoints.remove(&kind);
+                    }
+                    None => eprintln!("unknown action kind: {kind}"),
+                },
+                (Some("l" | "list"), _) => {
+                    for kind in &self.config.breakpoints {
+                        eprintln!("{kind:?}");
+                    }
+                }
+                (Some("a" | "action"), _) => eprintln!("{action:#?}"),
+                (Some("p" | "print"), path) => {
+                    let value = state_to_value(state);
+                    let pointer = path.map_or_else(String::new, json_pointer);
+                    match value.pointer(&pointer) {
+                        Some(value) => eprintln!(
+                            "{}",
+                            serde_json::to_string_pretty(value).unwrap_or_default()
+                        ),
+                        None => eprintln!("no such path in the state"),
+                    }
+                }
+                (Some("diff"), _) => match &self.prev_state {
+                    Some(before) => eprint!("{}", StateDiff::new(before, state)),
+                    None => eprintln!("previous state wasn't tracked"),
+                },
+                (Some("q" | "quit"), _) => {
+                    self.quit = true;
+                    self.detach();
+                    return;
+                }
+                (Some("h" | "help"), _) => eprintln!("{HELP}"),
+                (Some(cmd), _) => eprintln!("unknown command: {cmd}, try `help`"),
+            }
+        }
+    }
+}
+
+impl WatchedValues {
+    fn on_action(&mut self, kind: ActionKind, action_id: u64, state: &State) {
+        let value = state_to_value(state);
+        for (i, path) in self.paths.iter().enumerate() {
+            let new = value.pointer(&json_pointer(path));
+            if new != self.values[i].as_ref() {
+                self.values[i] = new.cloned();
+                self.last_changes[i] = Some(ReplayValueChange {
+                    path: path.clone(),
+                    kind,
+                    action_id,
+                });
+            }
+        }
+    }
+}
+
+const HELP: &str = "\
+commands:
+  s, step             apply the next action and pause (also an empty line)
+  c, continue         continue until the next breakpoint
+  b, break <kind>     pause after actions of the kind, e.g. `b P2pPeerReady`
+  d, delete <kind>    remove the breakpoint
+  l, list             list breakpoints
+  a, action           print the last applied action
+  p, print [path]     print the state or its part, e.g. `p transition_frontier.sync`
+  diff                print changes made by the last applied action
+  q, quit             stop the replay after the current input action
+  h, help             print this message";
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_state_diff() {
+        let before = json!({
+            "p2p": { "peers": { "a": 1, "b": 2 } },
+            "snark_pool": { "jobs": [1, 2] },
+            "rpc": {},
+            "last_action": 1,
+        });
+        let after = json!({
+            "p2p": { "peers": { "a": 1, "c": 3 } },
+            "snark_pool": { "jobs": [1, 2, 3] },
+            "rpc": {},
+            "last_action": 2,
+        });
+        let diff = StateDiff::from_values(&before, &after);
+
+        assert_eq!(
+            diff.subtrees.keys().collect::<Vec<_>>(),
+            vec!["p2p", "snark_pool"]
+        );
+        assert_eq!(
+            diff.subtrees["p2p"],
+            vec![
+                StateDiffEntry {
+                    path: "p2p.peers.b".to_owned(),
+                    before: Some(json!(2)),
+                    after: None,
+                },
+                StateDiffEntry {
+                    path: "p2p.peers.c".to_owned(),
+                    before: None,
+                    after: Some(json!(3)),
+                },
+            ]
+        );
+        assert_eq!(diff.subtrees["snark_pool"][0].path, "snark_pool.jobs[2]");
+        assert!(StateDiff::from_values(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_json_pointer() {
+        let value = json!({ "snark_pool": { "jobs": [1, 2, { "a/b": 3 }] } });
+        assert_eq!(json_pointer("snark_pool.jobs[2]"), "/snark_pool/jobs/2");
+        assert_eq!(
+            value.pointer(&json_pointer("snark_pool.jobs[1]")),
+            Some(&json!(2))
+        );
+        assert_eq!(
+            value.pointer(&json_pointer("snark_pool.jobs[2].a/b")),
+            Some(&json!(3))
+        );
+    }
+
+    #[test]
+    fn test_parse_action_kind() {
+        assert_eq!(
+            parse_action_kind("CheckTimeouts"),
+            Some(ActionKind::CheckTimeouts)
+        );
+        assert_eq!(parse_action_kind("NoSuchAction"), None);
+    }
+}
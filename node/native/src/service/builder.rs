@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use ledger::proofs::provers::BlockProver;
 use node::{
     account::AccountSecretKey, core::thread, p2p::identity::SecretKey as P2pSecretKey,
@@ -5,7 +7,10 @@ use node::{
 };
 pub use openmina_node_common::NodeServiceCommonBuildError;
 use openmina_node_common::{
-    p2p::TaskSpawner, rpc::RpcSender, EventSender, NodeServiceCommonBuilder,
+    archive::{ArchiveError, ArchiveStorage},
+    p2p::TaskSpawner,
+    rpc::RpcSender,
+    EventSender, NodeServiceCommonBuilder,
 };
 
 use crate::{http_server, NodeService, P2pTaskSpawner};
@@ -44,15 +49,34 @@ impl NodeServiceBuilder {
         self
     }
 
+    pub fn ledger_init_persistent(
+        &mut self,
+        frontier_store_path: Option<&Path>,
+        ondisk_ledgers_path: Option<&Path>,
+    ) -> std::io::Result<&mut Self> {
+        self.common
+            .ledger_init_persistent(frontier_store_path, ondisk_ledgers_path)?;
+        Ok(self)
+    }
+
     pub fn block_producer_init(
         &mut self,
-        keypair: AccountSecretKey,
+        keypairs: Vec<AccountSecretKey>,
         provers: Option<BlockProver>,
     ) -> &mut Self {
-        self.common.block_producer_init(keypair, provers);
+        self.common.block_producer_init(keypairs, provers);
         self
     }
 
+    pub fn archive_init(
+        &mut self,
+        storage: ArchiveStorage,
+        spool_dir: impl AsRef<Path>,
+    ) -> Result<&mut Self, ArchiveError> {
+        self.common.archive_init(storage, spool_dir)?;
+        Ok(self)
+    }
+
     pub fn p2p_init(&mut self, secret_key: P2pSecretKey) -> &mut Self {
         self.common.p2p_init(secret_key, P2pTaskSpawner {});
         self
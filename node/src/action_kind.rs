@@ -55,11 +55,15 @@ use crate::p2p::network::kad::kad_effectful::P2pNetworkKadEffectfulAction;
 use crate::p2p::network::kad::request::P2pNetworkKadRequestAction;
 use crate::p2p::network::kad::stream::P2pNetworkKademliaStreamAction;
 use crate::p2p::network::kad::{P2pNetworkKadAction, P2pNetworkKademliaAction};
+use crate::p2p::network::nat::P2pNetworkNatAction;
+use crate::p2p::network::nat_effectful::P2pNetworkNatEffectfulAction;
 use crate::p2p::network::noise::P2pNetworkNoiseAction;
 use crate::p2p::network::pnet::P2pNetworkPnetAction;
 use crate::p2p::network::pnet_effectful::P2pNetworkPnetEffectfulAction;
 use crate::p2p::network::pubsub::pubsub_effectful::P2pNetworkPubsubEffectfulAction;
 use crate::p2p::network::pubsub::P2pNetworkPubsubAction;
+use crate::p2p::network::quic::P2pNetworkQuicAction;
+use crate::p2p::network::quic_effectful::P2pNetworkQuicEffectfulAction;
 use crate::p2p::network::rpc::P2pNetworkRpcAction;
 use crate::p2p::network::scheduler::P2pNetworkSchedulerAction;
 use crate::p2p::network::scheduler_effectful::P2pNetworkSchedulerEffectfulAction;
@@ -67,6 +71,7 @@ use crate::p2p::network::select::P2pNetworkSelectAction;
 use crate::p2p::network::yamux::P2pNetworkYamuxAction;
 use crate::p2p::network::{P2pNetworkAction, P2pNetworkEffectfulAction};
 use crate::p2p::peer::P2pPeerAction;
+use crate::p2p::reputation::P2pReputationAction;
 use crate::p2p::{P2pAction, P2pEffectfulAction, P2pInitializeAction};
 use crate::rpc::RpcAction;
 use crate::rpc_effectful::RpcEffectfulAction;
@@ -83,6 +88,7 @@ use crate::transaction_pool::candidate::TransactionPoolCandidateAction;
 use crate::transaction_pool::{TransactionPoolAction, TransactionPoolEffectfulAction};
 use crate::transition_frontier::genesis::TransitionFrontierGenesisAction;
 use crate::transition_frontier::genesis_effectful::TransitionFrontierGenesisEffectfulAction;
+use crate::transition_frontier::persistence::TransitionFrontierPersistenceAction;
 use crate::transition_frontier::sync::ledger::snarked::TransitionFrontierSyncLedgerSnarkedAction;
 use crate::transition_frontier::sync::ledger::staged::TransitionFrontierSyncLedgerStagedAction;
 use crate::transition_frontier::sync::ledger::TransitionFrontierSyncLedgerAction;
@@ -387,6 +393,11 @@ pub enum ActionKind {
     P2pNetworkKademliaStreamSendResponse,
     P2pNetworkKademliaStreamWaitIncoming,
     P2pNetworkKademliaStreamWaitOutgoing,
+    P2pNetworkNatMappingError,
+    P2pNetworkNatMappingRequest,
+    P2pNetworkNatMappingSuccess,
+    P2pNetworkNatObservedAddr,
+    P2pNetworkNatEffectfulMapPort,
     P2pNetworkNoiseDecryptedData,
     P2pNetworkNoiseHandshakeDone,
     P2pNetworkNoiseIncomingChunk,
@@ -405,6 +416,7 @@ pub enum ActionKind {
     P2pNetworkPubsubBroadcast,
     P2pNetworkPubsubBroadcastSigned,
     P2pNetworkPubsubGraft,
+    P2pNetworkPubsubHeartbeat,
     P2pNetworkPubsubIncomingData,
     P2pNetworkPubsubIncomingMessage,
     P2pNetworkPubsubIncomingMessageCleanup,
@@ -419,6 +431,20 @@ pub enum ActionKind {
     P2pNetworkPubsubValidateIncomingMessages,
     P2pNetworkPubsubEffectfulSign,
     P2pNetworkPubsubEffectfulValidateIncomingMessages,
+    P2pNetworkQuicDidConnect,
+    P2pNetworkQuicIncomingData,
+    P2pNetworkQuicInit,
+    P2pNetworkQuicListenerError,
+    P2pNetworkQuicListenerReady,
+    P2pNetworkQuicOpenStream,
+    P2pNetworkQuicOutgoingConnect,
+    P2pNetworkQuicOutgoingData,
+    P2pNetworkQuicResetStream,
+    P2pNetworkQuicStreamDidReset,
+    P2pNetworkQuicEffectfulConnect,
+    P2pNetworkQuicEffectfulListen,
+    P2pNetworkQuicEffectfulOutgoingData,
+    P2pNetworkQuicEffectfulResetStream,
     P2pNetworkRpcHeartbeatSend,
     P2pNetworkRpcIncomingData,
     P2pNetworkRpcIncomingMessage,
@@ -473,8 +499,12 @@ pub enum ActionKind {
     P2pPeerDiscovered,
     P2pPeerReady,
     P2pPeerRemove,
+    P2pReputationBan,
+    P2pReputationPenalize,
+    P2pReputationPrune,
     RpcActionStatsGet,
     RpcBestChain,
+    RpcBlockGet,
     RpcBlockProducerStatsGet,
     RpcConsensusConstantsGet,
     RpcDiscoveryBoostrapStats,
@@ -486,6 +516,7 @@ pub enum ActionKind {
     RpcLedgerAccountsGetPending,
     RpcLedgerAccountsGetSuccess,
     RpcMessageProgressGet,
+    RpcMetricsGet,
     RpcP2pConnectionIncomingAnswerReady,
     RpcP2pConnectionIncomingError,
     RpcP2pConnectionIncomingInit,
@@ -520,6 +551,7 @@ pub enum ActionKind {
     RpcTransitionFrontierUserCommandsGet,
     RpcEffectfulActionStatsGet,
     RpcEffectfulBestChain,
+    RpcEffectfulBlockGet,
     RpcEffectfulBlockProducerStatsGet,
     RpcEffectfulConsensusConstantsGet,
     RpcEffectfulDiscoveryBoostrapStats,
@@ -528,6 +560,7 @@ pub enum ActionKind {
     RpcEffectfulHealthCheck,
     RpcEffectfulLedgerAccountsGetSuccess,
     RpcEffectfulMessageProgressGet,
+    RpcEffectfulMetricsGet,
     RpcEffectfulP2pConnectionIncomingError,
     RpcEffectfulP2pConnectionIncomingRespond,
     RpcEffectfulP2pConnectionIncomingSuccess,
@@ -618,6 +651,7 @@ pub enum ActionKind {
     TransactionPoolEffectfulFetchAccounts,
     TransitionFrontierGenesisInject,
     TransitionFrontierGenesisProvenInject,
+    TransitionFrontierSnapshotInject,
     TransitionFrontierSyncFailed,
     TransitionFrontierSynced,
     TransitionFrontierGenesisLedgerLoadInit,
@@ -629,6 +663,14 @@ pub enum ActionKind {
     TransitionFrontierGenesisProveSuccess,
     TransitionFrontierGenesisEffectfulLedgerLoadInit,
     TransitionFrontierGenesisEffectfulProveInit,
+    TransitionFrontierPersistencePersistError,
+    TransitionFrontierPersistencePersistInit,
+    TransitionFrontierPersistencePersistPending,
+    TransitionFrontierPersistencePersistSuccess,
+    TransitionFrontierPersistenceRestoreError,
+    TransitionFrontierPersistenceRestoreInit,
+    TransitionFrontierPersistenceRestorePending,
+    TransitionFrontierPersistenceRestoreSuccess,
     TransitionFrontierSyncBestTipUpdate,
     TransitionFrontierSyncBlocksFetchSuccess,
     TransitionFrontierSyncBlocksNextApplyError,
@@ -707,7 +749,7 @@ pub enum ActionKind {
 }
 
 impl ActionKind {
-    pub const COUNT: u16 = 597;
+    pub const COUNT: u16 = 633;
 }
 
 impl std::fmt::Display for ActionKind {
@@ -767,6 +809,7 @@ impl ActionKindGet for P2pAction {
             Self::Initialization(a) => a.kind(),
             Self::Connection(a) => a.kind(),
             Self::Disconnection(a) => a.kind(),
+            Self::Reputation(a) => a.kind(),
             Self::Identify(a) => a.kind(),
             Self::Channels(a) => a.kind(),
             Self::Peer(a) => a.kind(),
@@ -872,9 +915,11 @@ impl ActionKindGet for TransitionFrontierAction {
         match self {
             Self::Genesis(a) => a.kind(),
             Self::GenesisEffect(a) => a.kind(),
+            Self::Persistence(a) => a.kind(),
             Self::Sync(a) => a.kind(),
             Self::GenesisInject => ActionKind::TransitionFrontierGenesisInject,
             Self::GenesisProvenInject => ActionKind::TransitionFrontierGenesisProvenInject,
+            Self::SnapshotInject => ActionKind::TransitionFrontierSnapshotInject,
             Self::Synced { .. } => ActionKind::TransitionFrontierSynced,
             Self::SyncFailed { .. } => ActionKind::TransitionFrontierSyncFailed,
         }
@@ -1039,6 +1084,7 @@ impl ActionKindGet for RpcAction {
             Self::ActionStatsGet { .. } => ActionKind::RpcActionStatsGet,
             Self::SyncStatsGet { .. } => ActionKind::RpcSyncStatsGet,
             Self::BlockProducerStatsGet { .. } => ActionKind::RpcBlockProducerStatsGet,
+            Self::MetricsGet { .. } => ActionKind::RpcMetricsGet,
             Self::MessageProgressGet { .. } => ActionKind::RpcMessageProgressGet,
             Self::PeersGet { .. } => ActionKind::RpcPeersGet,
             Self::P2pConnectionOutgoingInit { .. } => ActionKind::RpcP2pConnectionOutgoingInit,
@@ -1094,6 +1140,7 @@ impl ActionKindGet for RpcAction {
             Self::BestChain { .. } => ActionKind::RpcBestChain,
             Self::ConsensusConstantsGet { .. } => ActionKind::RpcConsensusConstantsGet,
             Self::TransactionStatusGet { .. } => ActionKind::RpcTransactionStatusGet,
+            Self::BlockGet { .. } => ActionKind::RpcBlockGet,
             Self::Finish { .. } => ActionKind::RpcFinish,
         }
     }
@@ -1107,6 +1154,7 @@ impl ActionKindGet for RpcEffectfulAction {
             Self::ActionStatsGet { .. } => ActionKind::RpcEffectfulActionStatsGet,
             Self::SyncStatsGet { .. } => ActionKind::RpcEffectfulSyncStatsGet,
             Self::BlockProducerStatsGet { .. } => ActionKind::RpcEffectfulBlockProducerStatsGet,
+            Self::MetricsGet { .. } => ActionKind::RpcEffectfulMetricsGet,
             Self::MessageProgressGet { .. } => ActionKind::RpcEffectfulMessageProgressGet,
             Self::PeersGet { .. } => ActionKind::RpcEffectfulPeersGet,
             Self::P2pConnectionOutgoingError { .. } => {
@@ -1158,6 +1206,7 @@ impl ActionKindGet for RpcEffectfulAction {
             Self::BestChain { .. } => ActionKind::RpcEffectfulBestChain,
             Self::ConsensusConstantsGet { .. } => ActionKind::RpcEffectfulConsensusConstantsGet,
             Self::TransactionStatusGet { .. } => ActionKind::RpcEffectfulTransactionStatusGet,
+            Self::BlockGet { .. } => ActionKind::RpcEffectfulBlockGet,
         }
     }
 }
@@ -1224,6 +1273,16 @@ impl ActionKindGet for P2pDisconnectionAction {
     }
 }
 
+impl ActionKindGet for P2pReputationAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Penalize { .. } => ActionKind::P2pReputationPenalize,
+            Self::Ban { .. } => ActionKind::P2pReputationBan,
+            Self::Prune => ActionKind::P2pReputationPrune,
+        }
+    }
+}
+
 impl ActionKindGet for P2pIdentifyAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1268,6 +1327,8 @@ impl ActionKindGet for P2pNetworkAction {
             Self::Select(a) => a.kind(),
             Self::Noise(a) => a.kind(),
             Self::Yamux(a) => a.kind(),
+            Self::Quic(a) => a.kind(),
+            Self::Nat(a) => a.kind(),
             Self::Identify(a) => a.kind(),
             Self::Kad(a) => a.kind(),
             Self::Pubsub(a) => a.kind(),
@@ -1319,6 +1380,8 @@ impl ActionKindGet for P2pNetworkEffectfulAction {
         match self {
             Self::Scheduler(a) => a.kind(),
             Self::Pnet(a) => a.kind(),
+            Self::Quic(a) => a.kind(),
+            Self::Nat(a) => a.kind(),
             Self::Pubsub(a) => a.kind(),
             Self::Identify(a) => a.kind(),
             Self::Kad(a) => a.kind(),
@@ -1435,6 +1498,21 @@ impl ActionKindGet for TransitionFrontierGenesisEffectfulAction {
     }
 }
 
+impl ActionKindGet for TransitionFrontierPersistenceAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::RestoreInit => ActionKind::TransitionFrontierPersistenceRestoreInit,
+            Self::RestorePending => ActionKind::TransitionFrontierPersistenceRestorePending,
+            Self::RestoreSuccess { .. } => ActionKind::TransitionFrontierPersistenceRestoreSuccess,
+            Self::RestoreError { .. } => ActionKind::TransitionFrontierPersistenceRestoreError,
+            Self::PersistInit => ActionKind::TransitionFrontierPersistencePersistInit,
+            Self::PersistPending => ActionKind::TransitionFrontierPersistencePersistPending,
+            Self::PersistSuccess { .. } => ActionKind::TransitionFrontierPersistencePersistSuccess,
+            Self::PersistError { .. } => ActionKind::TransitionFrontierPersistencePersistError,
+        }
+    }
+}
+
 impl ActionKindGet for TransitionFrontierSyncAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1912,6 +1990,34 @@ impl ActionKindGet for P2pNetworkYamuxAction {
     }
 }
 
+impl ActionKindGet for P2pNetworkQuicAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::ListenerReady { .. } => ActionKind::P2pNetworkQuicListenerReady,
+            Self::ListenerError { .. } => ActionKind::P2pNetworkQuicListenerError,
+            Self::OutgoingConnect { .. } => ActionKind::P2pNetworkQuicOutgoingConnect,
+            Self::DidConnect { .. } => ActionKind::P2pNetworkQuicDidConnect,
+            Self::Init { .. } => ActionKind::P2pNetworkQuicInit,
+            Self::IncomingData { .. } => ActionKind::P2pNetworkQuicIncomingData,
+            Self::OutgoingData { .. } => ActionKind::P2pNetworkQuicOutgoingData,
+            Self::OpenStream { .. } => ActionKind::P2pNetworkQuicOpenStream,
+            Self::ResetStream { .. } => ActionKind::P2pNetworkQuicResetStream,
+            Self::StreamDidReset { .. } => ActionKind::P2pNetworkQuicStreamDidReset,
+        }
+    }
+}
+
+impl ActionKindGet for P2pNetworkNatAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::ObservedAddr { .. } => ActionKind::P2pNetworkNatObservedAddr,
+            Self::MappingRequest => ActionKind::P2pNetworkNatMappingRequest,
+            Self::MappingSuccess { .. } => ActionKind::P2pNetworkNatMappingSuccess,
+            Self::MappingError { .. } => ActionKind::P2pNetworkNatMappingError,
+        }
+    }
+}
+
 impl ActionKindGet for P2pNetworkIdentifyAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -1945,6 +2051,7 @@ impl ActionKindGet for P2pNetworkPubsubAction {
             }
             Self::Graft { .. } => ActionKind::P2pNetworkPubsubGraft,
             Self::Prune { .. } => ActionKind::P2pNetworkPubsubPrune,
+            Self::Heartbeat => ActionKind::P2pNetworkPubsubHeartbeat,
             Self::Broadcast { .. } => ActionKind::P2pNetworkPubsubBroadcast,
             Self::Sign { .. } => ActionKind::P2pNetworkPubsubSign,
             Self::SignError { .. } => ActionKind::P2pNetworkPubsubSignError,
@@ -2037,6 +2144,25 @@ impl ActionKindGet for P2pNetworkPnetEffectfulAction {
     }
 }
 
+impl ActionKindGet for P2pNetworkQuicEffectfulAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::Listen { .. } => ActionKind::P2pNetworkQuicEffectfulListen,
+            Self::Connect { .. } => ActionKind::P2pNetworkQuicEffectfulConnect,
+            Self::OutgoingData { .. } => ActionKind::P2pNetworkQuicEffectfulOutgoingData,
+            Self::ResetStream { .. } => ActionKind::P2pNetworkQuicEffectfulResetStream,
+        }
+    }
+}
+
+impl ActionKindGet for P2pNetworkNatEffectfulAction {
+    fn kind(&self) -> ActionKind {
+        match self {
+            Self::MapPort { .. } => ActionKind::P2pNetworkNatEffectfulMapPort,
+        }
+    }
+}
+
 impl ActionKindGet for P2pNetworkPubsubEffectfulAction {
     fn kind(&self) -> ActionKind {
         match self {
@@ -96,6 +96,7 @@ impl redux::EnablingCondition<crate::State> for BlockProducerAction {
                 }
 
                 this.current.won_slot_should_search()
+                    && this.config.is_producer(&won_slot.producer)
                     && Some(won_slot.global_slot()) >= state.cur_global_slot()
                     && won_slot > best_tip
             }),
@@ -3,17 +3,57 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockProducerConfig {
+    /// Keys we produce blocks with. Slots are evaluated for all of them
+    /// and their delegators, block is produced with whichever key wins.
+    pub producers: Vec<BlockProducerKeyConfig>,
+    pub proposed_protocol_version: Option<ProtocolVersionStableV2>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockProducerKeyConfig {
     pub pub_key: NonZeroCurvePoint,
     pub custom_coinbase_receiver: Option<NonZeroCurvePoint>,
-    pub proposed_protocol_version: Option<ProtocolVersionStableV2>,
 }
 
 impl BlockProducerConfig {
+    pub fn new(pub_key: NonZeroCurvePoint) -> Self {
+        Self {
+            producers: vec![BlockProducerKeyConfig::new(pub_key)],
+            proposed_protocol_version: None,
+        }
+    }
+
+    /// Adds another producer key, replacing the existing config for it.
+    pub fn add_producer(&mut self, producer: BlockProducerKeyConfig) {
+        self.producers.retain(|v| v.pub_key != producer.pub_key);
+        self.producers.push(producer);
+    }
+
+    pub fn producer(&self, pub_key: &NonZeroCurvePoint) -> Option<&BlockProducerKeyConfig> {
+        self.producers.iter().find(|v| &v.pub_key == pub_key)
+    }
+
+    pub fn producer_mut(
+        &mut self,
+        pub_key: &NonZeroCurvePoint,
+    ) -> Option<&mut BlockProducerKeyConfig> {
+        self.producers.iter_mut().find(|v| &v.pub_key == pub_key)
+    }
+
+    pub fn is_producer(&self, pub_key: &NonZeroCurvePoint) -> bool {
+        self.producer(pub_key).is_some()
+    }
+
+    pub fn pub_keys(&self) -> impl Iterator<Item = &NonZeroCurvePoint> {
+        self.producers.iter().map(|v| &v.pub_key)
+    }
+}
+
+impl BlockProducerKeyConfig {
     pub fn new(pub_key: NonZeroCurvePoint) -> Self {
         Self {
             pub_key,
             custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
         }
     }
 
@@ -417,8 +417,12 @@ impl BlockProducerEnabled {
         let vrf_truncated_output: v2::ConsensusVrfOutputTruncatedStableV1 =
             (*won_slot.vrf_output).clone().into();
         let vrf_hash = won_slot.vrf_output.hash();
-        let block_creator = self.config.pub_key.clone();
-        let coinbase_receiver = self.config.coinbase_receiver().clone();
+        let Some(producer) = self.config.producer(&won_slot.producer) else {
+            bug_condition!("Invalid state for `BlockProducerAction::BlockUnprovenBuild`: won slot belongs to unknown producer key");
+            return;
+        };
+        let block_creator = producer.pub_key.clone();
+        let coinbase_receiver = producer.coinbase_receiver().clone();
         let proposed_protocol_version_opt = self.config.proposed_protocol_version.clone();
 
         let ledger_proof_statement = ledger_proof_statement_from_emitted_proof(
@@ -176,13 +176,13 @@ impl BlockProducerState {
     }
 
     pub fn is_me(&self, producer: &v2::NonZeroCurvePoint) -> bool {
-        self.with(false, |this| producer == &this.config.pub_key)
+        self.with(false, |this| this.config.is_producer(producer))
     }
 
     /// Checks if the block was produced by us recently.
     pub fn is_produced_by_me(&self, block: &ArcBlockWithHash) -> bool {
         self.with(false, |this| {
-            block.producer() == &this.config.pub_key && this.injected_blocks.contains(block.hash())
+            this.config.is_producer(block.producer()) && this.injected_blocks.contains(block.hash())
         })
     }
 
@@ -222,7 +222,9 @@ impl BlockProducerState {
     }
 
     /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         self.vrf_evaluator()?.vrf_delegator_table_inputs()
     }
 
@@ -1,3 +1,4 @@
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 use crate::account::AccountPublicKey;
@@ -64,7 +65,7 @@ pub enum BlockProducerVrfEvaluatorAction {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Constructing delegator table.
     #[action_event(level = info)]
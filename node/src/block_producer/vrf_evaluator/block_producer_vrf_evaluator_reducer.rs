@@ -211,7 +211,7 @@ impl BlockProducerVrfEvaluatorState {
                         dispatcher.push(
                             BlockProducerVrfEvaluatorAction::InitializeEpochEvaluation {
                                 staking_epoch_data: epoch_data,
-                                producer: config.pub_key.clone().into(),
+                                producers: config.pub_keys().cloned().map(Into::into).collect(),
                                 best_tip_global_slot: *best_tip_global_slot,
                                 best_tip_epoch,
                                 best_tip_slot: *best_tip_slot,
@@ -233,7 +233,7 @@ impl BlockProducerVrfEvaluatorState {
                 best_tip_global_slot,
                 next_epoch_first_slot,
                 staking_epoch_data,
-                producer,
+                producers,
             } => {
                 state.status = BlockProducerVrfEvaluatorStatus::ReadyToEvaluate {
                     time: meta.time(),
@@ -245,7 +245,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 };
 
                 let dispatcher = state_context.into_dispatcher();
@@ -258,7 +258,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     time: _,
                     is_current_epoch_evaluated: _,
                     is_next_epoch_evaluated: _,
@@ -274,18 +274,18 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 };
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
-                let (staking_ledger_hash, producer) =
+                let (staking_ledger_hash, producers) =
                     match state.block_producer.vrf_delegator_table_inputs() {
                         Some((v1, v2)) => (v1.clone(), v2.clone()),
                         None => return,
                     };
 
                 dispatcher.push(LedgerReadAction::Init {
-                    request: LedgerReadRequest::DelegatorTable(staking_ledger_hash, producer),
+                    request: LedgerReadRequest::DelegatorTable(staking_ledger_hash, producers),
                     callback: LedgerReadInitCallback::None,
                 })
             }
@@ -298,7 +298,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot,
                     next_epoch_first_slot,
                     staking_epoch_data,
-                    producer,
+                    producers,
                     time: _,
                     staking_epoch_ledger_hash: _,
                 } = &state.status
@@ -307,11 +307,17 @@ impl BlockProducerVrfEvaluatorState {
                     return;
                 };
 
-                openmina_core::log::warn!(
-                    meta.time();
-                    kind = "BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction",
-                    message = "Empty delegator table, account may not exist yet in the staking ledger"
-                );
+                for producer in producers
+                    .iter()
+                    .filter(|producer| !delegator_table.contains_key(producer))
+                {
+                    openmina_core::log::warn!(
+                        meta.time();
+                        kind = "BlockProducerVrfEvaluatorAction::FinalizeDelegatorTableConstruction",
+                        message = "Empty delegator table, account may not exist yet in the staking ledger",
+                        producer = producer.to_string()
+                    );
+                }
 
                 let mut staking_epoch_data = staking_epoch_data.clone();
                 staking_epoch_data.delegator_table = delegator_table.clone();
@@ -324,7 +330,7 @@ impl BlockProducerVrfEvaluatorState {
                     best_tip_global_slot: *best_tip_global_slot,
                     next_epoch_first_slot: *next_epoch_first_slot,
                     staking_epoch_data: staking_epoch_data.clone(),
-                    producer: producer.clone(),
+                    producers: producers.clone(),
                 };
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
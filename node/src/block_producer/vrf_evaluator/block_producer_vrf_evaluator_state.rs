@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
@@ -359,13 +359,15 @@ impl BlockProducerVrfEvaluatorState {
     }
 
     /// If we need to construct delegator table, get it's inputs.
-    pub fn vrf_delegator_table_inputs(&self) -> Option<(&v2::LedgerHash, &AccountPublicKey)> {
+    pub fn vrf_delegator_table_inputs(
+        &self,
+    ) -> Option<(&v2::LedgerHash, &BTreeSet<AccountPublicKey>)> {
         match &self.status {
             BlockProducerVrfEvaluatorStatus::EpochDelegatorTablePending {
                 staking_epoch_ledger_hash,
-                producer,
+                producers,
                 ..
-            } => Some((staking_epoch_ledger_hash, producer)),
+            } => Some((staking_epoch_ledger_hash, producers)),
             _ => None,
         }
     }
@@ -440,7 +442,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Waiting for delegator table building
     EpochDelegatorTablePending {
@@ -452,7 +454,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     /// Delegator table built successfully
     EpochDelegatorTableSuccess {
@@ -464,7 +466,7 @@ pub enum BlockProducerVrfEvaluatorStatus {
         best_tip_global_slot: u32,
         next_epoch_first_slot: u32,
         staking_epoch_data: EpochData,
-        producer: AccountPublicKey,
+        producers: BTreeSet<AccountPublicKey>,
     },
     InitialSlotSelection {
         time: redux::Timestamp,
@@ -19,7 +19,11 @@ use vrf::{VrfEvaluationOutput, VrfWonSlot};
 
 use crate::account::AccountPublicKey;
 
-pub type DelegatorTable = BTreeMap<AccountIndex, (AccountPublicKey, u64)>;
+/// Accounts delegating to a single producer key, with their stake.
+pub type ProducerDelegators = BTreeMap<AccountIndex, (AccountPublicKey, u64)>;
+
+/// Delegators of each of our producer keys.
+pub type DelegatorTable = BTreeMap<AccountPublicKey, ProducerDelegators>;
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct VrfEvaluatorInput {
@@ -6,7 +6,7 @@ use openmina_core::consensus::ConsensusConstants;
 use serde::{Deserialize, Serialize};
 
 use crate::account::AccountPublicKey;
-pub use crate::block_producer::BlockProducerConfig;
+pub use crate::block_producer::{BlockProducerConfig, BlockProducerKeyConfig};
 pub use crate::ledger::LedgerConfig;
 pub use crate::p2p::P2pConfig;
 pub use crate::snark::SnarkConfig;
@@ -48,6 +48,9 @@ pub struct SnarkerConfig {
 pub enum SnarkerStrategy {
     Sequential,
     Random,
+    /// Pick jobs by [`crate::snark_pool::JobRank`], taking the fee offered
+    /// by competing snarkers and position in the scan state into account.
+    FeeAware,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -112,7 +115,7 @@ impl BuildEnv {
 }
 
 #[derive(thiserror::Error, Debug)]
-#[error("invalid strategy: {0}! expected one of: seq/sequential/rand/random")]
+#[error("invalid strategy: {0}! expected one of: seq/sequential/rand/random/fee/fee-aware")]
 pub struct SnarkerStrategyParseError(String);
 
 impl FromStr for SnarkerStrategy {
@@ -122,6 +125,7 @@ impl FromStr for SnarkerStrategy {
         Ok(match s {
             "seq" | "sequential" => SnarkerStrategy::Sequential,
             "rand" | "random" => SnarkerStrategy::Random,
+            "fee" | "fee-aware" => SnarkerStrategy::FeeAware,
             other => return Err(SnarkerStrategyParseError(other.to_owned())),
         })
     }
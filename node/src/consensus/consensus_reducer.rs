@@ -6,6 +6,7 @@ use openmina_core::{
 use snark::block_verify::{SnarkBlockVerifyAction, SnarkBlockVerifyError, SnarkBlockVerifyId};
 
 use crate::{
+    p2p::reputation::{P2pPenaltyReason, P2pReputationAction},
     transition_frontier::sync::{
         ledger::{
             snarked::TransitionFrontierSyncLedgerSnarkedAction,
@@ -126,8 +127,20 @@ impl ConsensusState {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(ConsensusAction::DetectForkRange { hash });
             }
-            ConsensusAction::BlockSnarkVerifyError { .. } => {
-                // TODO: handle block verification error.
+            ConsensusAction::BlockSnarkVerifyError { hash, .. } => {
+                // Peers whose best tip is the invalid block are the ones who sent it.
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                let Some(p2p) = global_state.p2p.ready() else {
+                    return;
+                };
+                p2p.ready_peers_iter()
+                    .filter(|(_, peer)| peer.best_tip.as_ref().map_or(false, |b| &b.hash == hash))
+                    .for_each(|(peer_id, _)| {
+                        dispatcher.push(P2pReputationAction::Penalize {
+                            peer_id: *peer_id,
+                            reason: P2pPenaltyReason::InvalidBlock,
+                        })
+                    });
             }
             ConsensusAction::DetectForkRange { hash } => {
                 let candidate_hash = hash;
@@ -9,7 +9,7 @@ use mina_p2p_messages::v2::{
 use openmina_core::constants::PROTOCOL_CONSTANTS;
 
 #[serde_with::serde_as]
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Genesis {
     k: Option<u32>,
     slots_per_epoch: Option<u32>,
@@ -36,6 +36,11 @@ impl Genesis {
     value_or_protocol_default!(delta, UnsignedExtendedUInt32StableV1);
     value_or_protocol_default!(genesis_state_timestamp, BlockTimeTimeStableV1);
 
+    pub fn with_genesis_state_timestamp(mut self, timestamp: OffsetDateTime) -> Self {
+        self.genesis_state_timestamp = Some(timestamp);
+        self
+    }
+
     pub fn protocol_constants(&self) -> MinaBaseProtocolConstantsCheckedValueStableV1 {
         MinaBaseProtocolConstantsCheckedValueStableV1 {
             k: self.k(),
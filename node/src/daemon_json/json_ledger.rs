@@ -8,8 +8,8 @@ use std::fmt::{self, Display, Formatter};
 
 use ledger::{
     scan_state::currency::{Amount, Balance, Magnitude, Nonce, Slot, SlotSpan, TxnVersion},
-    AuthRequired, Permissions, ReceiptChainHash, SetVerificationKey, Timing, TokenId, TokenSymbol,
-    VotingFor, ZkAppAccount, ZkAppUri,
+    AuthRequired, FpExt, Permissions, ReceiptChainHash, SetVerificationKey, Timing, TokenId,
+    TokenSymbol, VotingFor, ZkAppAccount, ZkAppUri,
 };
 use openmina_node_account::{AccountPublicKey, AccountSecretKey};
 
@@ -142,6 +142,11 @@ impl Account {
             zkapp: None,
         }
     }
+
+    pub fn with_timing(mut self, timing: AccountTiming) -> Self {
+        self.timing = Some(timing);
+        self
+    }
 }
 
 impl Account {
@@ -267,6 +272,60 @@ impl Account {
     }
 }
 
+/// Inverse of [`Account::to_account`].
+///
+/// Verification keys of zkApp accounts aren't supported by the format,
+/// so they are left out.
+impl From<&ledger::Account> for Account {
+    fn from(account: &ledger::Account) -> Self {
+        let is_default_token = account.token_id.is_default();
+        Account {
+            pk: AccountPublicKey::from(account.public_key.clone()).to_string(),
+            sk: None,
+            balance: to_mina_string(account.balance.as_u64()),
+            delegate: account
+                .delegate
+                .clone()
+                .map(|delegate| AccountPublicKey::from(delegate).to_string()),
+            token_id: (!is_default_token).then(|| account.token_id.0.to_decimal()),
+            token_symbol: Some(account.token_symbol.as_bytes().to_vec()),
+            nonce: Some(account.nonce.as_u32()),
+            receipt_chain_hash: Some(
+                mina_p2p_messages::v2::ReceiptChainHash::from(account.receipt_chain_hash.clone())
+                    .to_string(),
+            ),
+            voting_for: Some(account.voting_for.to_base58check()),
+            timing: match &account.timing {
+                Timing::Untimed => None,
+                Timing::Timed {
+                    initial_minimum_balance,
+                    cliff_time,
+                    cliff_amount,
+                    vesting_period,
+                    vesting_increment,
+                } => Some(AccountTiming::new(
+                    *initial_minimum_balance,
+                    *cliff_time,
+                    *cliff_amount,
+                    *vesting_period,
+                    *vesting_increment,
+                )),
+            },
+            permissions: Some(AccountPermissions::from(&account.permissions)),
+            zkapp: account.zkapp.as_deref().map(Zkapp::from),
+        }
+    }
+}
+
+/// Formats an amount of nanomina like `1.000000000`.
+pub fn to_mina_string(nanomina: u64) -> RawCurrency {
+    format!(
+        "{}.{:09}",
+        nanomina / 1_000_000_000,
+        nanomina % 1_000_000_000
+    )
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountTiming {
     initial_minimum_balance: RawCurrency,
@@ -277,6 +336,22 @@ pub struct AccountTiming {
 }
 
 impl AccountTiming {
+    pub fn new(
+        initial_minimum_balance: Balance,
+        cliff_time: Slot,
+        cliff_amount: Amount,
+        vesting_period: SlotSpan,
+        vesting_increment: Amount,
+    ) -> Self {
+        AccountTiming {
+            initial_minimum_balance: to_mina_string(initial_minimum_balance.as_u64()),
+            cliff_time: GlobalSlotSinceGenesis(cliff_time.as_u32()),
+            cliff_amount: to_mina_string(cliff_amount.as_u64()),
+            vesting_period: GlobalSlotSpan(vesting_period.as_u32()),
+            vesting_increment: to_mina_string(vesting_increment.as_u64()),
+        }
+    }
+
     fn to_timing(&self) -> Result<Timing, AccountConfigError> {
         let initial_minimum_balance = Balance::of_mina_string_exn(&self.initial_minimum_balance);
         let GlobalSlotSinceGenesis(cliff_time) = self.cliff_time;
@@ -349,6 +424,29 @@ pub struct AccountPermissions {
     set_timing: Option<AuthRequired>,
 }
 
+impl From<&Permissions<AuthRequired>> for AccountPermissions {
+    fn from(permissions: &Permissions<AuthRequired>) -> Self {
+        AccountPermissions {
+            access: Some(permissions.access),
+            edit_state: Some(permissions.edit_state),
+            send: Some(permissions.send),
+            receive: Some(permissions.receive),
+            set_delegate: Some(permissions.set_delegate),
+            set_permissions: Some(permissions.set_permissions),
+            set_verification_key: SetVrfKeyPerm {
+                auth: permissions.set_verification_key.auth,
+                txn_version: permissions.set_verification_key.txn_version.as_u32(),
+            },
+            set_zkapp_uri: Some(permissions.set_zkapp_uri),
+            edit_action_state: Some(permissions.edit_action_state),
+            set_token_symbol: Some(permissions.set_token_symbol),
+            increment_nonce: Some(permissions.increment_nonce),
+            set_voting_for: Some(permissions.set_voting_for),
+            set_timing: Some(permissions.set_timing),
+        }
+    }
+}
+
 impl AccountPermissions {
     fn to_permissions(&self) -> Permissions<AuthRequired> {
         // Defaults from https://github.com/MinaProtocol/mina/blob/3.0.0devnet/src/lib/mina_base/permissions.ml#L580-L594
@@ -392,6 +490,20 @@ fn parse_fp(str: &str) -> Result<Fp, AccountConfigError> {
     Fp::from_str(str).map_err(|_| AccountConfigError::MalformedFp(str.to_owned()))
 }
 
+impl From<&ZkAppAccount> for Zkapp {
+    fn from(zkapp: &ZkAppAccount) -> Self {
+        Zkapp {
+            app_state: zkapp.app_state.iter().map(FpExt::to_decimal).collect(),
+            verification_key: None,
+            zkapp_version: zkapp.zkapp_version,
+            action_state: zkapp.action_state.iter().map(FpExt::to_decimal).collect(),
+            last_action_slot: zkapp.last_action_slot.as_u32().to_string(),
+            proved_state: zkapp.proved_state,
+            zkapp_uri: zkapp.zkapp_uri.to_vec(),
+        }
+    }
+}
+
 impl Zkapp {
     fn to_zkapp_account(&self) -> Result<Box<ZkAppAccount>, AccountConfigError> {
         let app_state_fps: Vec<Fp> = self
@@ -6,8 +6,8 @@ mod json_ledger;
 pub use json_daemon::Daemon;
 pub use json_genesis::Genesis;
 pub use json_ledger::{
-    build_ledger_name, Account, AccountConfigError, AccountPermissions, AccountTiming, Ledger,
-    Zkapp,
+    build_ledger_name, to_mina_string, Account, AccountConfigError, AccountPermissions,
+    AccountTiming, Ledger, Zkapp,
 };
 
 /// This type represents a JSON object loaded from daemon.json
@@ -24,7 +24,7 @@ use crate::{
 use crate::p2p::channels::rpc::{P2pChannelsRpcAction, P2pRpcRequest};
 
 pub fn effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta) {
-    store.service.recorder().action(&action);
+    store.service.recorder().action(&action, store.state.get());
 
     let (action, meta) = action.split();
 
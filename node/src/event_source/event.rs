@@ -35,6 +35,7 @@ impl std::fmt::Display for Event {
                     RpcRequest::ActionStatsGet(query) => write!(f, "ActionStatsGet, {query:?}"),
                     RpcRequest::SyncStatsGet(query) => write!(f, "SyncStatsGet, {query:?}"),
                     RpcRequest::BlockProducerStatsGet => write!(f, "BlockProducerStatsGet"),
+                    RpcRequest::MetricsGet => write!(f, "MetricsGet"),
                     RpcRequest::PeersGet => write!(f, "PeersGet"),
                     RpcRequest::MessageProgressGet => write!(f, "MessageProgressGet"),
                     RpcRequest::P2pConnectionOutgoing(opts) => {
@@ -71,6 +72,7 @@ impl std::fmt::Display for Event {
                     RpcRequest::BestChain(..) => write!(f, "BestChain"),
                     RpcRequest::ConsensusConstantsGet => write!(f, "ConsensusConstantsGet"),
                     RpcRequest::TransactionStatusGet(..) => write!(f, "TransactionStatusGet"),
+                    RpcRequest::BlockGet(query) => write!(f, "BlockGet, {query:?}"),
                 }
             }
             Self::ExternalSnarkWorker(event) => {
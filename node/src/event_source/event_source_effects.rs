@@ -21,7 +21,7 @@ use crate::p2p::connection::{P2pConnectionErrorResponse, P2pConnectionResponse};
 use crate::p2p::disconnection::{P2pDisconnectionAction, P2pDisconnectionReason};
 use crate::p2p::P2pChannelEvent;
 #[cfg(feature = "p2p-libp2p")]
-use crate::p2p::{MioEvent, P2pNetworkSchedulerAction};
+use crate::p2p::{MioEvent, P2pNetworkNatAction, P2pNetworkQuicAction, P2pNetworkSchedulerAction};
 use crate::rpc::{RpcAction, RpcRequest};
 use crate::snark::block_verify::SnarkBlockVerifyAction;
 use crate::snark::work_verify::SnarkWorkVerifyAction;
@@ -117,6 +117,34 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                     MioEvent::ConnectionDidCloseOnDemand(addr) => {
                         store.dispatch(P2pNetworkSchedulerAction::Prune { addr });
                     }
+                    MioEvent::QuicListenerReady { listener } => {
+                        store.dispatch(P2pNetworkQuicAction::ListenerReady { listener });
+                    }
+                    MioEvent::QuicListenerError { listener, error } => {
+                        store.dispatch(P2pNetworkQuicAction::ListenerError { listener, error });
+                    }
+                    MioEvent::QuicConnectionDidConnect(addr, result) => {
+                        store.dispatch(P2pNetworkQuicAction::DidConnect { addr, result });
+                    }
+                    MioEvent::QuicStreamDidReceive(addr, stream_id, data, fin) => {
+                        store.dispatch(P2pNetworkQuicAction::IncomingData {
+                            addr,
+                            stream_id,
+                            data,
+                            fin,
+                        });
+                    }
+                    MioEvent::QuicStreamDidReset(addr, stream_id) => {
+                        store.dispatch(P2pNetworkQuicAction::StreamDidReset { addr, stream_id });
+                    }
+                    MioEvent::NatPortMapped(result) => match result {
+                        Ok(mapping) => {
+                            store.dispatch(P2pNetworkNatAction::MappingSuccess { mapping });
+                        }
+                        Err(error) => {
+                            store.dispatch(P2pNetworkNatAction::MappingError { error });
+                        }
+                    },
                 },
                 P2pEvent::Connection(e) => match e {
                     P2pConnectionEvent::OfferSdpReady(peer_id, res) => match res {
@@ -316,6 +344,9 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::BlockProducerStatsGet => {
                     store.dispatch(RpcAction::BlockProducerStatsGet { rpc_id });
                 }
+                RpcRequest::MetricsGet => {
+                    store.dispatch(RpcAction::MetricsGet { rpc_id });
+                }
                 RpcRequest::PeersGet => {
                     store.dispatch(RpcAction::PeersGet { rpc_id });
                 }
@@ -385,6 +416,9 @@ pub fn event_source_effects<S: Service>(store: &mut Store<S>, action: EventSourc
                 RpcRequest::TransactionStatusGet(tx) => {
                     store.dispatch(RpcAction::TransactionStatusGet { rpc_id, tx });
                 }
+                RpcRequest::BlockGet(query) => {
+                    store.dispatch(RpcAction::BlockGet { rpc_id, query });
+                }
             },
             Event::ExternalSnarkWorker(e) => match e {
                 ExternalSnarkWorkerEvent::Started => {
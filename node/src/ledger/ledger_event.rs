@@ -43,6 +43,15 @@ impl std::fmt::Display for LedgerEvent {
                     LedgerWriteResponse::Commit { best_tip_hash, .. } => {
                         write!(f, ", {best_tip_hash}")
                     }
+                    LedgerWriteResponse::FrontierPersist {
+                        best_tip_hash,
+                        result,
+                    } => {
+                        write!(f, ", {best_tip_hash}, {}", res_kind_str(result))
+                    }
+                    LedgerWriteResponse::FrontierRestore { result } => {
+                        write!(f, ", {}", res_kind_str(result))
+                    }
                 }
             }
             Self::Read(id, resp) => {
@@ -0,0 +1,548 @@
+//! On-disk snapshot of the transition frontier.
+//!
+//! Holds everything needed to resume the node from its last best chain
+//! without bootstrapping again: blocks of the best chain, the root
+//! snarked ledger, both epoch ledgers of the root and the staged ledger
+//! aux data of the root.
+//!
+//! Ledgers are stored account by account, so that only accounts which
+//! changed since the previous snapshot have to be written.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+use ledger::{
+    ondisk::{self, batch::Batch, CompactionConfig},
+    Account, AccountIndex, BaseLedger, Mask,
+};
+use mina_hasher::Fp;
+use mina_p2p_messages::{
+    binprot::{
+        self,
+        macros::{BinProtRead, BinProtWrite},
+        BinProtRead, BinProtWrite,
+    },
+    number::UInt64,
+    v2::{LedgerHash, MinaStateProtocolStateValueStableV2, StateHash},
+};
+use openmina_core::block::{AppliedBlock, ArcBlock, BlockWithHash};
+
+use crate::p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases;
+
+use super::LEDGER_DEPTH;
+
+const META_KEY: &[u8] = b"meta";
+const STAGED_LEDGER_AUX_KEY: &[u8] = b"root_staged_ledger_aux";
+const BLOCK_KEY_PREFIX: &[u8] = b"block/";
+const ACCOUNT_KEY_PREFIX: &[u8] = b"account/";
+
+/// Number of accounts read from the database at once.
+const ACCOUNTS_READ_CHUNK_SIZE: u64 = 4096;
+
+/// Ledgers stored alongside the best chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) enum FrontierLedger {
+    StakingEpoch,
+    NextEpoch,
+    RootSnarked,
+}
+
+impl FrontierLedger {
+    pub(super) const ALL: [Self; 3] = [Self::StakingEpoch, Self::NextEpoch, Self::RootSnarked];
+
+    fn account_key(self, index: u64) -> Box<[u8]> {
+        [ACCOUNT_KEY_PREFIX, &[self as u8], &index.to_be_bytes()]
+            .concat()
+            .into()
+    }
+}
+
+#[derive(BinProtRead, BinProtWrite, Debug, Clone)]
+struct PersistedLedgerMeta {
+    hash: LedgerHash,
+    num_accounts: UInt64,
+}
+
+#[derive(BinProtRead, BinProtWrite, Debug)]
+struct FrontierSnapshotMeta {
+    genesis_state_hash: StateHash,
+    /// Hashes of the best chain blocks, from the root to the best tip.
+    best_chain: Vec<StateHash>,
+    staking_epoch_ledger: PersistedLedgerMeta,
+    next_epoch_ledger: PersistedLedgerMeta,
+    root_snarked_ledger: PersistedLedgerMeta,
+    needed_protocol_states: Vec<MinaStateProtocolStateValueStableV2>,
+}
+
+impl FrontierSnapshotMeta {
+    fn ledger(&self, kind: FrontierLedger) -> &PersistedLedgerMeta {
+        match kind {
+            FrontierLedger::StakingEpoch => &self.staking_epoch_ledger,
+            FrontierLedger::NextEpoch => &self.next_epoch_ledger,
+            FrontierLedger::RootSnarked => &self.root_snarked_ledger,
+        }
+    }
+}
+
+#[derive(BinProtRead, BinProtWrite, Debug)]
+struct PersistedBlock {
+    block: ArcBlock,
+    just_emitted_a_proof: bool,
+}
+
+/// Ledger that is currently stored on disk.
+struct PersistedLedger {
+    hash: LedgerHash,
+    /// Hashes of the stored accounts, by account index.
+    account_hashes: Vec<Fp>,
+}
+
+/// Snapshot loaded from disk, with ledgers verified against their hashes.
+pub(super) struct FrontierSnapshot {
+    pub chain: Vec<AppliedBlock>,
+    pub ledgers: Vec<(LedgerHash, Mask)>,
+    pub staged_ledger_aux: StagedLedgerAuxAndPendingCoinbases,
+    pub needed_protocol_states: BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>,
+}
+
+pub(super) struct FrontierStore {
+    db: ondisk::Database,
+    ledgers: BTreeMap<FrontierLedger, PersistedLedger>,
+    blocks: BTreeSet<StateHash>,
+}
+
+impl FrontierStore {
+    pub fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut db = ondisk::Database::create(path)?;
+        db.set_compaction_config(Some(CompactionConfig::default()));
+        Ok(Self {
+            db,
+            ledgers: Default::default(),
+            blocks: Default::default(),
+        })
+    }
+
+    /// Writes the snapshot in a single batch, the meta entry being the
+    /// last one, so a snapshot which wasn't completely written is never
+    /// picked up by [`Self::load`].
+    pub fn persist(
+        &mut self,
+        genesis_state_hash: &StateHash,
+        best_chain: &[AppliedBlock],
+        ledgers: [(LedgerHash, Mask); 3],
+        staged_ledger_aux: &StagedLedgerAuxAndPendingCoinbases,
+        needed_protocol_states: &BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>,
+    ) -> Result<(), String> {
+        let mut batch = Batch::new();
+
+        let chain_hashes = best_chain
+            .iter()
+            .map(|b| b.hash().clone())
+            .collect::<BTreeSet<_>>();
+        for block in best_chain {
+            if self.blocks.contains(block.hash()) {
+                continue;
+            }
+            let value = PersistedBlock {
+                block: block.block().clone(),
+                just_emitted_a_proof: block.just_emitted_a_proof,
+            };
+            batch.set(block_key(block.hash()), encode(&value)?);
+        }
+        for hash in self.blocks.difference(&chain_hashes) {
+            batch.remove(block_key(hash));
+        }
+
+        let mut updated_ledgers = Vec::with_capacity(ledgers.len());
+        let mut ledgers_meta = Vec::with_capacity(ledgers.len());
+        for (kind, (hash, mut mask)) in FrontierLedger::ALL.into_iter().zip(ledgers) {
+            let (meta, updated) = self.persist_ledger(&mut batch, kind, hash, &mut mask)?;
+            ledgers_meta.push(meta);
+            updated_ledgers.extend(updated.map(|ledger| (kind, ledger)));
+        }
+        let [staking_epoch_ledger, next_epoch_ledger, root_snarked_ledger]: [_; 3] = ledgers_meta
+            .try_into()
+            .map_err(|_| "unexpected number of ledgers".to_owned())?;
+
+        batch.set(STAGED_LEDGER_AUX_KEY.into(), encode(staged_ledger_aux)?);
+
+        let meta = FrontierSnapshotMeta {
+            genesis_state_hash: genesis_state_hash.clone(),
+            best_chain: best_chain.iter().map(|b| b.hash().clone()).collect(),
+            staking_epoch_ledger,
+            next_epoch_ledger,
+            root_snarked_ledger,
+            needed_protocol_states: needed_protocol_states.values().cloned().collect(),
+        };
+        batch.set(META_KEY.into(), encode(&meta)?);
+
+        self.db.run_batch(&mut batch).map_err(|e| e.to_string())?;
+
+        self.blocks = chain_hashes;
+        self.ledgers.extend(updated_ledgers);
+        Ok(())
+    }
+
+    /// Adds accounts of the ledger that changed since it was last
+    /// persisted to the `batch`.
+    ///
+    /// Returns new state of the persisted ledger if anything changed.
+    fn persist_ledger(
+        &self,
+        batch: &mut Batch,
+        kind: FrontierLedger,
+        hash: LedgerHash,
+        mask: &mut Mask,
+    ) -> Result<(PersistedLedgerMeta, Option<PersistedLedger>), String> {
+        let prev = self.ledgers.get(&kind);
+        let num_accounts = mask.num_accounts();
+        let meta = PersistedLedgerMeta {
+            hash: hash.clone(),
+            num_accounts: UInt64::from(&(num_accounts as u64)),
+        };
+        if prev.map_or(false, |prev| prev.hash == hash) {
+            return Ok((meta, None));
+        }
+        let prev_account_hashes = prev.map_or(&[][..], |prev| &prev.account_hashes[..]);
+
+        // Computing the root caches hashes of all accounts.
+        mask.merkle_root();
+
+        let mut account_hashes = Vec::with_capacity(num_accounts);
+        for index in 0..num_accounts as u64 {
+            let account_index = AccountIndex(index);
+            let account_hash = mask
+                .get_account_hash(account_index)
+                .ok_or_else(|| format!("{kind:?} ledger {hash}: missing account {index}"))?;
+            if prev_account_hashes.get(index as usize) != Some(&account_hash) {
+                let account = mask
+                    .get_at_index(account_index)
+                    .ok_or_else(|| format!("{kind:?} ledger {hash}: missing account {index}"))?;
+                batch.set(kind.account_key(index), encode(&*account)?);
+            }
+            account_hashes.push(account_hash);
+        }
+        for index in num_accounts..prev_account_hashes.len() {
+            batch.remove(kind.account_key(index as u64));
+        }
+
+        Ok((
+            meta,
+            Some(PersistedLedger {
+                hash,
+                account_hashes,
+            }),
+        ))
+    }
+
+    /// Loads the snapshot, verifying that it belongs to the chain with
+    /// `genesis_state_hash` and that it is consistent.
+    pub fn load(&mut self, genesis_state_hash: &StateHash) -> Result<FrontierSnapshot, String> {
+        let meta: FrontierSnapshotMeta = self
+            .get(META_KEY)?
+            .ok_or_else(|| "no snapshot found".to_owned())?;
+        // Blocks that are on disk, even if the snapshot turns out to be
+        // unusable, so that they are removed on the next `persist`.
+        self.blocks = meta.best_chain.iter().cloned().collect();
+
+        if &meta.genesis_state_hash != genesis_state_hash {
+            return Err(format!(
+                "snapshot belongs to a different chain, genesis: {}",
+                meta.genesis_state_hash
+            ));
+        }
+
+        let mut chain: Vec<AppliedBlock> = Vec::with_capacity(meta.best_chain.len());
+        for hash in &meta.best_chain {
+            let persisted: PersistedBlock = self
+                .get(&block_key(hash))?
+                .ok_or_else(|| format!("missing block {hash}"))?;
+            let block = BlockWithHash::try_new(persisted.block)
+                .map_err(|e| format!("invalid block {hash}: {e:?}"))?;
+            if block.hash() != hash {
+                return Err(format!("block hash mismatch: {hash} != {}", block.hash()));
+            }
+            if let Some(pred) = chain.last() {
+                if block.pred_hash() != pred.hash() {
+                    return Err(format!("block {hash} isn't a child of {}", pred.hash()));
+                }
+            }
+            chain.push(AppliedBlock {
+                block,
+                just_emitted_a_proof: persisted.just_emitted_a_proof,
+            });
+        }
+        if chain.is_empty() {
+            return Err("snapshot has empty best chain".to_owned());
+        }
+
+        let mut ledgers = Vec::with_capacity(FrontierLedger::ALL.len());
+        for kind in FrontierLedger::ALL {
+            let (mask, persisted) = self.load_ledger(kind, meta.ledger(kind))?;
+            ledgers.push((persisted.hash.clone(), mask));
+            self.ledgers.insert(kind, persisted);
+        }
+
+        let staged_ledger_aux = self
+            .get(STAGED_LEDGER_AUX_KEY)?
+            .ok_or_else(|| "missing root staged ledger aux".to_owned())?;
+
+        let needed_protocol_states = meta
+            .needed_protocol_states
+            .into_iter()
+            .map(|state| Ok((state.try_hash().map_err(|e| format!("{e:?}"))?, state)))
+            .collect::<Result<_, String>>()?;
+
+        Ok(FrontierSnapshot {
+            chain,
+            ledgers,
+            staged_ledger_aux,
+            needed_protocol_states,
+        })
+    }
+
+    fn load_ledger(
+        &mut self,
+        kind: FrontierLedger,
+        meta: &PersistedLedgerMeta,
+    ) -> Result<(Mask, PersistedLedger), String> {
+        let num_accounts = meta.num_accounts.as_u64();
+        let mut mask = Mask::new_root(ledger::Database::create(LEDGER_DEPTH as u8));
+
+        let mut start = 0;
+        while start < num_accounts {
+            let end = start
+                .saturating_add(ACCOUNTS_READ_CHUNK_SIZE)
+                .min(num_accounts);
+            let values = self
+                .db
+                .get_batch((start..end).map(|index| kind.account_key(index)))
+                .map_err(|e| e.to_string())?;
+            for (index, value) in (start..end).zip(values) {
+                let value =
+                    value.ok_or_else(|| format!("{kind:?} ledger: missing account {index}"))?;
+                let account: Account = decode(&value)?;
+                mask.get_or_create_account(account.id(), account)
+                    .map_err(|_| format!("{kind:?} ledger: failed to add account {index}"))?;
+            }
+            start = end;
+        }
+
+        let hash = LedgerHash::from_fp(mask.merkle_root());
+        if hash != meta.hash {
+            return Err(format!(
+                "{kind:?} ledger hash mismatch, expected: {}, found: {hash}",
+                meta.hash
+            ));
+        }
+
+        let account_hashes = (0..num_accounts)
+            .map(|index| {
+                mask.get_account_hash(AccountIndex(index))
+                    .ok_or_else(|| format!("{kind:?} ledger: missing account {index}"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok((
+            mask,
+            PersistedLedger {
+                hash,
+                account_hashes,
+            },
+        ))
+    }
+
+    fn get<T: BinProtRead>(&mut self, key: &[u8]) -> Result<Option<T>, String> {
+        self.db
+            .get(key)
+            .map_err(|e| e.to_string())?
+            .map(|value| decode(&value))
+            .transpose()
+    }
+}
+
+fn block_key(hash: &StateHash) -> Box<[u8]> {
+    [BLOCK_KEY_PREFIX, hash.to_string().as_bytes()]
+        .concat()
+        .into()
+}
+
+fn encode<T: BinProtWrite>(value: &T) -> Result<Box<[u8]>, String> {
+    let mut buf = Vec::new();
+    value.binprot_write(&mut buf).map_err(|e| e.to_string())?;
+    Ok(buf.into())
+}
+
+fn decode<T: BinProtRead>(mut bytes: &[u8]) -> Result<T, String> {
+    T::binprot_read(&mut bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use ledger::staged_ledger::staged_ledger::StagedLedger;
+    use mina_p2p_messages::gossip::GossipNetMessageV2;
+    use openmina_core::constants::constraint_constants;
+
+    use super::*;
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "openmina-frontier-store-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn test_block() -> AppliedBlock {
+        let mut bytes: &[u8] =
+            include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/new_state.bin");
+        let GossipNetMessageV2::NewState(block) =
+            GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+        else {
+            panic!("expected a block");
+        };
+        AppliedBlock {
+            block: BlockWithHash::try_new(Arc::new(block)).unwrap(),
+            just_emitted_a_proof: true,
+        }
+    }
+
+    fn test_ledger(n: usize) -> (LedgerHash, Mask) {
+        let mut mask = Mask::new_root(ledger::Database::create(LEDGER_DEPTH as u8));
+        for _ in 0..n {
+            let account = Account::rand();
+            mask.get_or_create_account(account.id(), account).unwrap();
+        }
+        (LedgerHash::from_fp(mask.merkle_root()), mask)
+    }
+
+    fn test_staged_ledger_aux(mask: &Mask) -> StagedLedgerAuxAndPendingCoinbases {
+        let mut staged_ledger =
+            StagedLedger::create_exn(constraint_constants().clone(), mask.copy()).unwrap();
+        staged_ledger.pending_coinbase_collection_merkle_root();
+        StagedLedgerAuxAndPendingCoinbases {
+            scan_state: staged_ledger.scan_state().into(),
+            staged_ledger_hash: LedgerHash::from_fp(mask.clone().merkle_root()),
+            pending_coinbase: staged_ledger.pending_coinbase_collection().into(),
+            needed_blocks: Default::default(),
+        }
+    }
+
+    struct TestSnapshot {
+        genesis_state_hash: StateHash,
+        chain: Vec<AppliedBlock>,
+        ledgers: [(LedgerHash, Mask); 3],
+        staged_ledger_aux: StagedLedgerAuxAndPendingCoinbases,
+    }
+
+    impl TestSnapshot {
+        fn new() -> Self {
+            let block = test_block();
+            let ledgers = [test_ledger(10), test_ledger(20), test_ledger(30)];
+            Self {
+                genesis_state_hash: block.pred_hash().clone(),
+                staged_ledger_aux: test_staged_ledger_aux(&ledgers[2].1),
+                chain: vec![block],
+                ledgers,
+            }
+        }
+
+        fn persist(&self, store: &mut FrontierStore) {
+            store
+                .persist(
+                    &self.genesis_state_hash,
+                    &self.chain,
+                    self.ledgers.clone(),
+                    &self.staged_ledger_aux,
+                    &Default::default(),
+                )
+                .unwrap();
+        }
+
+        fn assert_loaded(&self, snapshot: &FrontierSnapshot) {
+            let chain = |chain: &[AppliedBlock]| {
+                chain
+                    .iter()
+                    .map(|b| (b.hash().clone(), b.just_emitted_a_proof))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(chain(&snapshot.chain), chain(&self.chain));
+            assert!(snapshot.needed_protocol_states.is_empty());
+            assert_eq!(
+                snapshot.staged_ledger_aux.staged_ledger_hash,
+                self.staged_ledger_aux.staged_ledger_hash
+            );
+            assert_eq!(snapshot.ledgers.len(), self.ledgers.len());
+            for ((hash, mask), (expected_hash, expected_mask)) in
+                snapshot.ledgers.iter().zip(&self.ledgers)
+            {
+                assert_eq!(hash, expected_hash);
+                assert_eq!(mask.to_list(), expected_mask.to_list());
+            }
+        }
+    }
+
+    #[test]
+    fn test_persist_load_round_trip() {
+        let dir = test_dir("round-trip");
+        let mut snapshot = TestSnapshot::new();
+
+        let mut store = FrontierStore::open(&dir).unwrap();
+        assert!(store.load(&snapshot.genesis_state_hash).is_err());
+        snapshot.persist(&mut store);
+
+        // Only the changed ledger gets written again
+        let mut root_snarked_ledger = snapshot.ledgers[2].1.make_child();
+        let account = Account::rand();
+        root_snarked_ledger
+            .get_or_create_account(account.id(), account)
+            .unwrap();
+        snapshot.ledgers[2] = (
+            LedgerHash::from_fp(root_snarked_ledger.merkle_root()),
+            root_snarked_ledger,
+        );
+        snapshot.persist(&mut store);
+        drop(store);
+
+        let mut store = FrontierStore::open(&dir).unwrap();
+        let loaded = store.load(&snapshot.genesis_state_hash).unwrap();
+        snapshot.assert_loaded(&loaded);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_or_corrupt_snapshot_is_rejected() {
+        let dir = test_dir("rejected");
+        let snapshot = TestSnapshot::new();
+
+        let mut store = FrontierStore::open(&dir).unwrap();
+        snapshot.persist(&mut store);
+
+        // Snapshot of another chain
+        let other_genesis_state_hash = snapshot.chain[0].hash().clone();
+        let error = store.load(&other_genesis_state_hash).unwrap_err();
+        assert!(error.contains("different chain"), "{error}");
+
+        // Account that doesn't match the ledger hash
+        let mut batch = Batch::new();
+        let account = Account::rand();
+        batch.set(
+            FrontierLedger::NextEpoch.account_key(3),
+            encode(&account).unwrap(),
+        );
+        store.db.run_batch(&mut batch).unwrap();
+        drop(store);
+
+        let mut store = FrontierStore::open(&dir).unwrap();
+        let error = store.load(&snapshot.genesis_state_hash).unwrap_err();
+        assert!(error.contains("hash mismatch"), "{error}");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
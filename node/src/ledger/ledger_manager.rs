@@ -158,6 +158,7 @@ impl LedgerRequest {
                     block,
                     pred_block,
                     skip_verification,
+                    archive,
                 } => {
                     let block_hash = block.hash().clone();
                     let skip_verification = if skip_verification {
@@ -165,7 +166,8 @@ impl LedgerRequest {
                     } else {
                         None
                     };
-                    let result = ledger_ctx.block_apply(block, pred_block, skip_verification);
+                    let result =
+                        ledger_ctx.block_apply(block, pred_block, skip_verification, archive);
                     LedgerWriteResponse::BlockApply { block_hash, result }
                 }
                 LedgerWriteRequest::Commit {
@@ -188,20 +190,49 @@ impl LedgerRequest {
                         result,
                     }
                 }
+                LedgerWriteRequest::FrontierPersist {
+                    genesis_state_hash,
+                    best_chain,
+                    needed_protocol_states,
+                } => {
+                    let best_tip_hash = best_chain
+                        .last()
+                        .map(|b| b.hash().clone())
+                        .unwrap_or_else(|| genesis_state_hash.clone());
+                    let result = ledger_ctx.frontier_persist(
+                        &genesis_state_hash,
+                        &best_chain,
+                        &needed_protocol_states,
+                    );
+                    LedgerWriteResponse::FrontierPersist {
+                        best_tip_hash,
+                        result,
+                    }
+                }
+                LedgerWriteRequest::FrontierRestore { genesis_state_hash } => {
+                    let result = ledger_ctx.frontier_restore(&genesis_state_hash);
+                    LedgerWriteResponse::FrontierRestore { result }
+                }
             }),
             Self::Read(id, request) => LedgerResponse::Read(
                 id,
                 match request {
-                    LedgerReadRequest::DelegatorTable(ledger_hash, producer) => {
+                    LedgerReadRequest::DelegatorTable(ledger_hash, producers) => {
                         let res = ledger_ctx
                             .producers_with_delegates(&ledger_hash, |pub_key| {
-                                AccountPublicKey::from(pub_key.clone()) == producer
+                                producers.contains(&AccountPublicKey::from(pub_key.clone()))
                             })
-                            .and_then(|list| list.into_iter().next())
-                            .map(|(_, table)| {
-                                table
-                                    .into_iter()
-                                    .map(|(index, pub_key, balance)| (index, (pub_key, balance)))
+                            .map(|list| {
+                                list.into_iter()
+                                    .map(|(producer, table)| {
+                                        let table = table
+                                            .into_iter()
+                                            .map(|(index, pub_key, balance)| {
+                                                (index, (pub_key, balance))
+                                            })
+                                            .collect();
+                                        (producer, table)
+                                    })
                                     .collect()
                             });
 
@@ -226,6 +257,10 @@ impl LedgerRequest {
                         );
                         LedgerReadResponse::GetStagedLedgerAuxAndPendingCoinbases(res)
                     }
+                    LedgerReadRequest::GetEpochLedger(ledger_hash) => {
+                        let res = ledger_ctx.get_epoch_ledger(&ledger_hash);
+                        LedgerReadResponse::GetEpochLedger(res)
+                    }
                     LedgerReadRequest::ScanStateSummary(ledger_hash) => {
                         let res = ledger_ctx.scan_state_summary(&ledger_hash);
                         LedgerReadResponse::ScanStateSummary(res)
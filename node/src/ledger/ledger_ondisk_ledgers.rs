@@ -0,0 +1,264 @@
+//! Snarked and epoch ledgers persisted in the work dir.
+//!
+//! Every ledger is an on-disk [`Database`] in its own directory. Masks of
+//! newer snarked ledgers are committed into the database they were made
+//! from, so the content of a directory changes over time and its ledger
+//! hash is computed when it is opened instead of being taken from its
+//! name.
+//!
+//! Only the genesis ledger is copied from memory. Copies of on-disk roots,
+//! like the ones made for ledger sync and staged ledgers, are checkpoints
+//! next to the original directory, so these ledgers stay on disk too.
+
+use std::{
+    collections::BTreeSet,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use ledger::{AccountIndex, BaseLedger, Database, Mask};
+use mina_p2p_messages::v2::{LedgerHash, MinaBaseLedgerHash0StableV1};
+
+use super::LEDGER_DEPTH;
+
+/// Number of accounts copied to disk at once.
+const ACCOUNTS_WRITE_CHUNK_SIZE: u64 = 4096;
+
+pub(super) struct OndiskLedgers {
+    dir: PathBuf,
+}
+
+impl OndiskLedgers {
+    /// Opens every ledger in `dir`, creating the directory if needed.
+    ///
+    /// Returned masks are the roots of the on-disk databases.
+    pub fn open(dir: impl AsRef<Path>) -> std::io::Result<(Self, Vec<(LedgerHash, Mask)>)> {
+        let dir = dir.as_ref().to_owned();
+        fs::create_dir_all(&dir)?;
+
+        let mut ledgers = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            match open_ledger(&path) {
+                Ok(ledger) => ledgers.push(ledger),
+                Err(e) => {
+                    openmina_core::warn!(openmina_core::log::system_time();
+                        kind = "OndiskLedgers::open",
+                        summary = format!("removing unreadable ledger {path:?}: {e}"));
+                    fs::remove_dir_all(&path)?;
+                }
+            }
+        }
+
+        Ok((Self { dir }, ledgers))
+    }
+
+    /// Copies the ledger to disk unless its mask is already backed by a
+    /// ledger in this directory.
+    ///
+    /// Returns the root of the on-disk ledger.
+    pub fn persist(&self, hash: &LedgerHash, mask: &Mask) -> Result<Mask, String> {
+        if self.contains(mask) {
+            return Ok(mask.clone());
+        }
+
+        let mut path = self.dir.join(hash.to_string());
+        if path.exists() {
+            path = self.dir.join(format!("{hash}-{}", ledger::next_uuid()));
+        }
+        let result = copy_ledger(&path, hash, mask);
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&path);
+        }
+        result
+    }
+
+    /// Whether the mask is backed by a ledger in this directory.
+    pub fn contains(&self, mask: &Mask) -> bool {
+        let mut root = mask.clone();
+        while let Some(parent) = root.get_parent() {
+            root = parent;
+        }
+        root.get_directory()
+            .map_or(false, |dir| dir.parent() == Some(self.dir.as_path()))
+    }
+
+    /// Removes ledgers that none of `masks` is backed by.
+    pub fn remove_unused(&self, masks: impl IntoIterator<Item = Mask>) -> std::io::Result<()> {
+        let used = masks
+            .into_iter()
+            .filter_map(|mask| mask.get_directory())
+            .collect::<BTreeSet<_>>();
+
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.is_dir() && !used.contains(&path) {
+                openmina_core::debug!(openmina_core::log::system_time();
+                    kind = "OndiskLedgers::remove_unused",
+                    summary = format!("removing ledger {path:?}"));
+                fs::remove_dir_all(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn merkle_root(mask: &mut Mask) -> LedgerHash {
+    MinaBaseLedgerHash0StableV1(mask.merkle_root().into()).into()
+}
+
+fn open_ledger(path: &Path) -> std::io::Result<(LedgerHash, Mask)> {
+    let db = Database::open_ondisk(LEDGER_DEPTH as u8, path.to_owned())?;
+    let mut root = Mask::new_root(db);
+    let hash = merkle_root(&mut root);
+    if let Some(e) = root.take_io_error() {
+        return Err(e);
+    }
+    Ok((hash, root))
+}
+
+fn copy_ledger(path: &Path, hash: &LedgerHash, mask: &Mask) -> Result<Mask, String> {
+    let db = Database::open_ondisk(LEDGER_DEPTH as u8, path.to_owned())
+        .map_err(|e| format!("failed to create ledger {path:?}: {e}"))?;
+    let mut root = Mask::new_root(db);
+
+    let num_accounts = mask
+        .last_filled()
+        .map_or(0, |addr| addr.to_index().as_u64() + 1);
+    let mut start = 0;
+    while start < num_accounts {
+        let end = start
+            .saturating_add(ACCOUNTS_WRITE_CHUNK_SIZE)
+            .min(num_accounts);
+        let accounts = (start..end)
+            .filter_map(|index| {
+                let addr = ledger::Address::from_index(AccountIndex(index), LEDGER_DEPTH);
+                Some((addr.clone(), mask.get(addr)?))
+            })
+            .collect::<Vec<_>>();
+        root.set_batch(&accounts);
+        if let Some(e) = root.take_io_error() {
+            return Err(e.to_string());
+        }
+        start = end;
+    }
+
+    let persisted_hash = merkle_root(&mut root);
+    if let Some(e) = root.take_io_error() {
+        return Err(e.to_string());
+    }
+    if &persisted_hash != hash {
+        return Err(format!(
+            "persisted ledger hash mismatch, expected: {hash}, found: {persisted_hash}"
+        ));
+    }
+
+    openmina_core::debug!(openmina_core::log::system_time();
+        kind = "OndiskLedgers::persist",
+        summary = format!("persisted ledger {hash} to {path:?}"));
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use ledger::Account;
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "openmina-ondisk-ledgers-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn ledger_with_accounts(n: usize) -> (LedgerHash, Mask) {
+        let mut mask = Mask::new_root(Database::create(LEDGER_DEPTH as u8));
+        for _ in 0..n {
+            let account = Account::rand();
+            mask.get_or_create_account(account.id(), account).unwrap();
+        }
+        (merkle_root(&mut mask), mask)
+    }
+
+    #[test]
+    fn test_ledgers_are_reopened() {
+        let dir = test_dir("reopen");
+        let (store, ledgers) = OndiskLedgers::open(&dir).unwrap();
+        assert!(ledgers.is_empty());
+
+        let (hash, mask) = ledger_with_accounts(20);
+        let persisted = store.persist(&hash, &mask).unwrap();
+        assert!(!store.contains(&mask));
+        assert!(store.contains(&persisted));
+        assert_eq!(
+            store.persist(&hash, &persisted).unwrap().get_directory(),
+            persisted.get_directory()
+        );
+
+        // Newer ledger committed down to the on-disk database, like
+        // `LedgerCtx::commit` does once the older ledger isn't needed
+        let mut child = persisted.make_child();
+        let account = Account::rand();
+        child.get_or_create_account(account.id(), account).unwrap();
+        let new_hash = merkle_root(&mut child);
+        child.commit();
+        assert!(store.contains(&child));
+        drop((store, persisted, child));
+
+        let (_, ledgers) = OndiskLedgers::open(&dir).unwrap();
+        assert_eq!(ledgers.len(), 1);
+        let (reopened_hash, reopened) = &ledgers[0];
+        assert_eq!(reopened_hash, &new_hash);
+        assert_eq!(reopened.num_accounts(), 21);
+        assert_eq!(
+            reopened.get_at_index(AccountIndex(7)),
+            mask.get_at_index(AccountIndex(7))
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_copies_stay_on_disk() {
+        let dir = test_dir("copy");
+        let (store, _) = OndiskLedgers::open(&dir).unwrap();
+
+        let (hash, mask) = ledger_with_accounts(5);
+        let persisted = store.persist(&hash, &mask).unwrap();
+        let mut copy = persisted.copy();
+        assert!(store.contains(&copy));
+        assert_ne!(copy.get_directory(), persisted.get_directory());
+        assert_eq!(merkle_root(&mut copy), hash);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unused_ledgers_are_removed() {
+        let dir = test_dir("remove-unused");
+        let (store, _) = OndiskLedgers::open(&dir).unwrap();
+
+        let (hash1, mask1) = ledger_with_accounts(5);
+        let (hash2, mask2) = ledger_with_accounts(6);
+        let persisted1 = store.persist(&hash1, &mask1).unwrap();
+        let persisted2 = store.persist(&hash2, &mask2).unwrap();
+
+        store.remove_unused([persisted2, mask1]).unwrap();
+        drop(persisted1);
+
+        let (_, ledgers) = OndiskLedgers::open(&dir).unwrap();
+        let hashes = ledgers
+            .into_iter()
+            .map(|(hash, _)| hash)
+            .collect::<Vec<_>>();
+        assert_eq!(hashes, vec![hash2]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,10 +1,12 @@
 use super::{
     ledger_empty_hash_at_depth,
+    ledger_frontier_store::{FrontierSnapshot, FrontierStore},
+    ledger_ondisk_ledgers::OndiskLedgers,
     read::LedgerReadResponse,
     read::{LedgerReadId, LedgerReadRequest},
-    write::CommitResult,
     write::LedgerWriteRequest,
     write::LedgerWriteResponse,
+    write::{CommitResult, FrontierRestoreResult},
     LedgerAddress, LedgerEvent, LEDGER_DEPTH,
 };
 use crate::{
@@ -12,7 +14,7 @@ use crate::{
     block_producer_effectful::StagedLedgerDiffCreateOutput,
     ledger::{
         ledger_manager::{LedgerManager, LedgerRequest},
-        write::BlockApplyResult,
+        write::{BlockApplyResult, BlockApplyResultArchive},
     },
     p2p::channels::rpc::StagedLedgerAuxAndPendingCoinbases,
     rpc::{
@@ -28,7 +30,7 @@ use crate::{
 use ark_ff::fields::arithmetic::InvalidBigInt;
 use ledger::{
     scan_state::{
-        currency::Slot,
+        currency::{Fee, Slot},
         scan_state::{AvailableJobMessage, JobValueBase, JobValueMerge, JobValueWithIndex, Pass},
         transaction_logic::{
             local_state::LocalState,
@@ -44,7 +46,7 @@ use ledger::{
         validate_block::block_body_hash,
     },
     verifier::Verifier,
-    Account, AccountId, BaseLedger, Database, Mask, UnregisterBehavior,
+    Account, AccountId, BaseLedger, Database, Mask, TokenId, UnregisterBehavior,
 };
 use mina_hasher::Fp;
 use mina_p2p_messages::{
@@ -73,6 +75,56 @@ use std::{
     sync::Arc,
 };
 
+/// Collects the ledger changes made by the latest block applied to
+/// `staged_ledger`, whose predecessor has state hash `pred_state_hash`.
+fn block_apply_archive_data(
+    staged_ledger: &StagedLedger,
+    pred_state_hash: Fp,
+) -> BlockApplyResultArchive {
+    let ledger = staged_ledger.ledger();
+    let accessed = staged_ledger.latest_block_accounts_accessed(pred_state_hash);
+
+    let accounts_accessed = accessed
+        .iter()
+        .filter_map(|id| {
+            let index = ledger.index_of_account(id.clone())?;
+            let account = ledger.get_at_index(index)?;
+            Some((index.0, (&*account).into()))
+        })
+        .collect();
+
+    let creation_fee = Fee::from_u64(constraint_constants().account_creation_fee);
+    let accounts_created = staged_ledger
+        .latest_block_accounts_created(pred_state_hash)
+        .into_iter()
+        .map(|id| (id.into(), (&creation_fee).into()))
+        .collect();
+
+    let mut tokens = Vec::<TokenId>::new();
+    for id in &accessed {
+        if !tokens.contains(&id.token_id) {
+            tokens.push(id.token_id.clone());
+        }
+    }
+    let tokens_used = tokens
+        .into_iter()
+        .map(|token_id| {
+            let owner = (!token_id.is_default())
+                .then(|| accessed.iter().find(|id| id.derive_token_id() == token_id))
+                .flatten()
+                .cloned()
+                .map(Into::into);
+            ((&token_id).into(), owner)
+        })
+        .collect();
+
+    BlockApplyResultArchive {
+        accounts_accessed,
+        accounts_created,
+        tokens_used,
+    }
+}
+
 fn merkle_root(mask: &mut Mask) -> LedgerHash {
     MinaBaseLedgerHash0StableV1(mask.merkle_root().into()).into()
 }
@@ -81,6 +133,9 @@ fn error_to_string(e: InvalidBigInt) -> String {
     format!("{:?}", e)
 }
 
+/// Number of sparse ledgers kept for `get_epoch_ledger` rpc.
+const EPOCH_LEDGERS_CACHE_SIZE: usize = 2;
+
 /// Indexing `StagedLedger` both by their "merkle root hash" and their "staged ledger hash"
 #[derive(Default)]
 struct StagedLedgersStorage {
@@ -174,6 +229,14 @@ pub struct LedgerCtx {
     additional_snarked_ledgers: BTreeMap<LedgerHash, Mask>,
     staged_ledgers: StagedLedgersStorage,
     sync: LedgerSyncState,
+    /// Snapshot of the transition frontier on disk, if enabled.
+    frontier_store: Option<FrontierStore>,
+    /// Snarked and epoch ledgers kept on disk instead of in memory, if
+    /// enabled.
+    ondisk_ledgers: Option<OndiskLedgers>,
+    /// Epoch ledgers recently served by `get_epoch_ledger` rpc, most
+    /// recent last.
+    epoch_ledgers_cache: Vec<(LedgerHash, Arc<v2::MinaBaseSparseLedgerBaseStableV2>)>,
     event_sender:
         Option<openmina_core::channels::mpsc::UnboundedSender<crate::event_source::Event>>,
 }
@@ -242,8 +305,35 @@ impl LedgerCtx {
         self.send_event(LedgerEvent::Read(id, resp))
     }
 
+    /// Opens (or creates) the transition frontier snapshot store at `path`.
+    pub fn frontier_store_open<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        self.frontier_store = Some(FrontierStore::open(path)?);
+        Ok(())
+    }
+
+    /// Keeps snarked, epoch and staged ledgers on disk in `path`, reopening
+    /// the ledgers persisted there before.
+    pub fn ondisk_ledgers_open<P>(&mut self, path: P) -> std::io::Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let (ondisk_ledgers, ledgers) = OndiskLedgers::open(path)?;
+        for (hash, mask) in ledgers {
+            openmina_core::info!(openmina_core::log::system_time();
+                kind = "LedgerService::ondisk_ledgers_open",
+                summary = format!("reopened snarked ledger {hash}"));
+            self.snarked_ledgers.entry(hash).or_insert(mask);
+        }
+        self.ondisk_ledgers = Some(ondisk_ledgers);
+        Ok(())
+    }
+
     pub fn insert_genesis_ledger(&mut self, mut mask: Mask) {
         let merkle_root_hash = merkle_root(&mut mask);
+        let mask = self.ondisk_ledgers_insert(&merkle_root_hash, mask);
         let staged_ledger =
             StagedLedger::create_exn(constraint_constants().clone(), mask.copy()).unwrap();
         self.snarked_ledgers.insert(merkle_root_hash.clone(), mask);
@@ -254,6 +344,30 @@ impl LedgerCtx {
             .insert(Arc::new(staged_ledger_hash), staged_ledger);
     }
 
+    /// Returns the on-disk copy of an in-memory ledger, reusing a reopened
+    /// ledger with the same hash.
+    ///
+    /// Masks copied from the returned one are on-disk checkpoints, so
+    /// ledgers synced from it and staged ledgers built on it aren't kept
+    /// in memory either.
+    fn ondisk_ledgers_insert(&self, hash: &LedgerHash, mask: Mask) -> Mask {
+        let Some(ondisk_ledgers) = &self.ondisk_ledgers else {
+            return mask;
+        };
+        if let Some(reopened) = self.snarked_ledgers.get(hash) {
+            return reopened.clone();
+        }
+        match ondisk_ledgers.persist(hash, &mask) {
+            Ok(persisted) => persisted,
+            Err(e) => {
+                openmina_core::error!(openmina_core::log::system_time();
+                    kind = "LedgerService::ondisk_ledgers_insert",
+                    summary = format!("failed to persist ledger {hash}, keeping it in memory: {e}"));
+                mask
+            }
+        }
+    }
+
     pub fn staged_ledger_reconstruct_result_store(&mut self, ledger: StagedLedger) {
         self.staged_ledgers.insert_by_recomputing_hash(ledger);
     }
@@ -640,6 +754,7 @@ impl LedgerCtx {
         block: ArcBlockWithHash,
         pred_block: AppliedBlock,
         skip_verification: Option<SkipVerification>,
+        archive: bool,
     ) -> Result<BlockApplyResult, String> {
         openmina_core::info!(openmina_core::log::system_time();
             kind = "LedgerService::block_apply",
@@ -673,6 +788,7 @@ impl LedgerCtx {
 
         let prev_protocol_state: ledger::proofs::block::ProtocolState =
             prev_protocol_state.try_into()?;
+        let prev_state_and_body_hash = prev_protocol_state.hashes();
 
         let result = staged_ledger
             .apply(
@@ -683,7 +799,7 @@ impl LedgerCtx {
                 (),
                 &Verifier,
                 &prev_state_view,
-                prev_protocol_state.hashes(),
+                prev_state_and_body_hash,
                 coinbase_receiver,
                 supercharge_coinbase,
             )
@@ -714,12 +830,17 @@ impl LedgerCtx {
             panic!("staged ledger hash mismatch. found: {ledger_hashes:#?}, expected: {expected_ledger_hashes:#?}");
         }
 
+        let archive_data = archive
+            .then(|| block_apply_archive_data(&staged_ledger, prev_state_and_body_hash.0))
+            .map(Arc::new);
+
         self.sync
             .staged_ledgers
             .insert(Arc::new(ledger_hashes), staged_ledger);
 
         Ok(BlockApplyResult {
             just_emitted_a_proof,
+            archive_data,
         })
     }
 
@@ -810,6 +931,16 @@ impl LedgerCtx {
             }
         }
 
+        if let Err(e) = self.ondisk_ledgers_cleanup([
+            new_root.staking_epoch_ledger_hash(),
+            new_root.next_epoch_ledger_hash(),
+            new_root.snarked_ledger_hash(),
+        ]) {
+            openmina_core::error!(openmina_core::log::system_time();
+                kind = "LedgerService::commit - ondisk_ledgers_cleanup",
+                summary = format!("on-disk ledgers failure: {e}"));
+        }
+
         // TODO(tizoc): should this fail silently?
         let Some(new_root_ledger) = self.staged_ledgers.get_mut(new_root.staged_ledger_hashes())
         else {
@@ -847,6 +978,173 @@ impl LedgerCtx {
         }
     }
 
+    /// Removes on-disk ledgers that aren't used anymore and reports I/O
+    /// errors of the ledgers since the previous commit.
+    ///
+    /// Nothing is copied here, the ledgers are expected to be on disk
+    /// already (see [`Self::ondisk_ledgers_insert`]).
+    fn ondisk_ledgers_cleanup(&self, hashes: [&LedgerHash; 3]) -> Result<(), String> {
+        let Some(ondisk_ledgers) = &self.ondisk_ledgers else {
+            return Ok(());
+        };
+
+        for hash in hashes {
+            let (mask, _) = self
+                .mask(hash)
+                .ok_or_else(|| format!("ledger is missing: {hash}"))?;
+            if !ondisk_ledgers.contains(&mask) {
+                openmina_core::warn!(openmina_core::log::system_time();
+                    kind = "LedgerService::ondisk_ledgers_cleanup",
+                    summary = format!("ledger {hash} is kept in memory"));
+            }
+        }
+
+        let masks = self
+            .snarked_ledgers
+            .values()
+            .chain(self.sync.snarked_ledgers.values())
+            .cloned()
+            .chain(
+                self.staged_ledgers
+                    .staged_ledgers
+                    .values()
+                    .map(|staged_ledger| staged_ledger.ledger()),
+            )
+            .collect::<Vec<_>>();
+        ondisk_ledgers
+            .remove_unused(masks.iter().cloned())
+            .map_err(|e| e.to_string())?;
+
+        // Errors of writes and hash reads made since the previous commit
+        masks
+            .iter()
+            .find_map(Mask::take_io_error)
+            .map_or(Ok(()), |e| Err(e.to_string()))
+    }
+
+    /// Writes snapshot of the transition frontier to the frontier store.
+    pub fn frontier_persist(
+        &mut self,
+        genesis_state_hash: &StateHash,
+        best_chain: &[AppliedBlock],
+        needed_protocol_states: &BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>,
+    ) -> Result<(), String> {
+        let root = best_chain
+            .first()
+            .ok_or_else(|| "best chain is empty".to_owned())?;
+        let ledger = |hash: &LedgerHash| {
+            self.mask(hash)
+                .map(|(mask, _)| (hash.clone(), mask))
+                .ok_or_else(|| format!("ledger is missing: {hash}"))
+        };
+        let ledgers = [
+            ledger(root.staking_epoch_ledger_hash())?,
+            ledger(root.next_epoch_ledger_hash())?,
+            ledger(root.snarked_ledger_hash())?,
+        ];
+
+        let protocol_states = best_chain
+            .iter()
+            .map(|b| (b.hash().clone(), b.header().protocol_state.clone()))
+            .chain(needed_protocol_states.clone())
+            .collect();
+        let staged_ledger_aux = self
+            .staged_ledger_aux_and_pending_coinbase(root.staged_ledger_hashes(), protocol_states)
+            .ok_or_else(|| format!("root staged ledger aux is unavailable: {}", root.hash()))?;
+
+        let store = self
+            .frontier_store
+            .as_mut()
+            .ok_or_else(|| "frontier store is not open".to_owned())?;
+        store.persist(
+            genesis_state_hash,
+            best_chain,
+            ledgers,
+            &staged_ledger_aux,
+            needed_protocol_states,
+        )?;
+
+        openmina_core::debug!(openmina_core::log::system_time();
+            kind = "LedgerService::frontier_persist",
+            summary = format!("persisted {} blocks, root: {}", best_chain.len(), root.hash()));
+        Ok(())
+    }
+
+    /// Loads snapshot of the transition frontier from the frontier store
+    /// and reconstructs ledgers of every block in it.
+    pub fn frontier_restore(
+        &mut self,
+        genesis_state_hash: &StateHash,
+    ) -> Result<FrontierRestoreResult, String> {
+        let store = self
+            .frontier_store
+            .as_mut()
+            .ok_or_else(|| "frontier store is not open".to_owned())?;
+        let FrontierSnapshot {
+            chain,
+            ledgers,
+            staged_ledger_aux,
+            needed_protocol_states,
+        } = store.load(genesis_state_hash)?;
+
+        let root = chain
+            .first()
+            .ok_or_else(|| "best chain is empty".to_owned())?;
+        for (hash, mask) in ledgers {
+            self.snarked_ledgers.entry(hash).or_insert(mask);
+        }
+        for hash in [
+            root.staking_epoch_ledger_hash(),
+            root.next_epoch_ledger_hash(),
+            root.snarked_ledger_hash(),
+        ] {
+            if !self.snarked_ledgers.contains_key(hash) {
+                return Err(format!("root ledger is missing from snapshot: {hash}"));
+            }
+        }
+
+        let snarked_ledger = self
+            .snarked_ledgers
+            .get(root.snarked_ledger_hash())
+            .cloned()
+            .ok_or_else(|| format!("root snarked ledger is missing: {}", root.hash()))?;
+        let (_, result) = staged_ledger_reconstruct(
+            snarked_ledger,
+            root.snarked_ledger_hash().clone(),
+            Some(Arc::new(staged_ledger_aux)),
+        )
+        .map_err(error_to_string)?;
+        let mut staged_ledger = result?;
+        let staged_ledger_hash = MinaBaseStagedLedgerHashStableV1::from(&staged_ledger.hash());
+        if &staged_ledger_hash != root.staged_ledger_hashes() {
+            return Err(format!(
+                "root staged ledger hash mismatch, expected: {:?}, found: {staged_ledger_hash:?}",
+                root.staged_ledger_hashes()
+            ));
+        }
+        self.staged_ledgers
+            .insert(Arc::new(staged_ledger_hash), staged_ledger);
+
+        // Blocks were already verified before they got into the frontier.
+        for (pred_block, block) in chain.iter().zip(chain.iter().skip(1)) {
+            self.block_apply(
+                block.block.clone(),
+                pred_block.clone(),
+                Some(SkipVerification::All),
+                false,
+            )?;
+        }
+        self.staged_ledgers.extend(self.sync.staged_ledgers.take());
+
+        openmina_core::info!(openmina_core::log::system_time();
+            kind = "LedgerService::frontier_restore",
+            summary = format!("restored {} blocks, root: {}", chain.len(), root.hash()));
+        Ok(FrontierRestoreResult {
+            chain,
+            needed_protocol_states,
+        })
+    }
+
     pub fn get_num_accounts(
         &mut self,
         ledger_hash: v2::LedgerHash,
@@ -903,6 +1201,38 @@ impl LedgerCtx {
         Some(accounts)
     }
 
+    /// Whole epoch ledger as a sparse ledger, served by `get_epoch_ledger` rpc.
+    ///
+    /// Building it walks the whole ledger, so converted ledgers are cached
+    /// by their hash, which covers both epoch ledgers that peers ask for.
+    pub fn get_epoch_ledger(
+        &mut self,
+        ledger_hash: &v2::LedgerHash,
+    ) -> Option<Arc<v2::MinaBaseSparseLedgerBaseStableV2>> {
+        if let Some(i) = self
+            .epoch_ledgers_cache
+            .iter()
+            .position(|(hash, _)| hash == ledger_hash)
+        {
+            let cached = self.epoch_ledgers_cache.remove(i);
+            let ledger = cached.1.clone();
+            self.epoch_ledgers_cache.push(cached);
+            return Some(ledger);
+        }
+
+        let (mask, _) = self.mask(ledger_hash).filter(|(_, is_synced)| *is_synced)?;
+        let account_ids = mask.accounts().into_iter().collect::<Vec<_>>();
+        let sparse_ledger = SparseLedger::of_ledger_subset_exn(mask, &account_ids);
+        let ledger = Arc::new((&sparse_ledger).into());
+
+        if self.epoch_ledgers_cache.len() >= EPOCH_LEDGERS_CACHE_SIZE {
+            self.epoch_ledgers_cache.remove(0);
+        }
+        self.epoch_ledgers_cache
+            .push((ledger_hash.clone(), Arc::clone(&ledger)));
+        Some(ledger)
+    }
+
     pub fn get_accounts(
         &mut self,
         ledger_hash: v2::LedgerHash,
@@ -1478,4 +1808,54 @@ mod tests {
             assert_eq!(hash.to_string(), expected_hash);
         });
     }
+
+    fn ledger_with_accounts(n: usize) -> (LedgerHash, Mask) {
+        let mut mask = Mask::new_root(ledger::Database::create(LEDGER_DEPTH as u8));
+        for _ in 0..n {
+            let account = ledger::Account::rand();
+            mask.get_or_create_account(account.id(), account).unwrap();
+        }
+        (merkle_root(&mut mask), mask)
+    }
+
+    #[test]
+    fn test_get_epoch_ledger() {
+        let mut ctx = LedgerCtx::default();
+        let ledgers = [
+            ledger_with_accounts(5),
+            ledger_with_accounts(6),
+            ledger_with_accounts(7),
+        ];
+        for (hash, mask) in &ledgers {
+            ctx.snarked_ledgers.insert(hash.clone(), mask.clone());
+        }
+
+        let (hash, mask) = &ledgers[0];
+        let served = ctx.get_epoch_ledger(hash).unwrap();
+        let mut sparse_ledger = SparseLedger::try_from(&*served).unwrap();
+        assert_eq!(&merkle_root_sparse(&mut sparse_ledger), hash);
+        assert_eq!(served.indexes.len(), mask.num_accounts());
+
+        // Served again from the cache
+        assert!(Arc::ptr_eq(&ctx.get_epoch_ledger(hash).unwrap(), &served));
+
+        // Least recently served ledger is evicted
+        ctx.get_epoch_ledger(&ledgers[1].0).unwrap();
+        ctx.get_epoch_ledger(hash).unwrap();
+        ctx.get_epoch_ledger(&ledgers[2].0).unwrap();
+        let cached = ctx
+            .epoch_ledgers_cache
+            .iter()
+            .map(|(hash, _)| hash)
+            .collect::<Vec<_>>();
+        assert_eq!(cached, vec![hash, &ledgers[2].0]);
+
+        let (unknown_hash, _) = ledger_with_accounts(1);
+        assert!(ctx.get_epoch_ledger(&unknown_hash).is_none());
+        assert_eq!(ctx.epoch_ledgers_cache.len(), EPOCH_LEDGERS_CACHE_SIZE);
+    }
+
+    fn merkle_root_sparse(ledger: &mut SparseLedger) -> LedgerHash {
+        MinaBaseLedgerHash0StableV1(ledger.merkle_root().into()).into()
+    }
 }
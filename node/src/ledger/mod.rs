@@ -19,6 +19,9 @@ mod ledger_reducer;
 mod ledger_service;
 pub use ledger_service::*;
 
+mod ledger_frontier_store;
+mod ledger_ondisk_ledgers;
+
 pub mod ledger_manager;
 
 pub use ledger::AccountIndex as LedgerAccountIndex;
@@ -74,12 +74,12 @@ impl LedgerReadState {
 
         match (request.request(), response) {
             (
-                LedgerReadRequest::DelegatorTable(ledger_hash, pub_key),
+                LedgerReadRequest::DelegatorTable(ledger_hash, producers),
                 LedgerReadResponse::DelegatorTable(table),
             ) => {
                 let expected = state.block_producer.vrf_delegator_table_inputs();
-                if !expected.map_or(false, |(expected_hash, producer)| {
-                    ledger_hash == expected_hash && pub_key == producer
+                if !expected.map_or(false, |(expected_hash, expected_producers)| {
+                    ledger_hash == expected_hash && producers == expected_producers
                 }) {
                     bug_condition!("delegator table unexpected");
                     return;
@@ -190,6 +190,17 @@ impl LedgerReadState {
                     });
                 }
             }
+            (req, LedgerReadResponse::GetEpochLedger(resp)) => {
+                for (peer_id, id, _) in find_peers_with_ledger_rpc(state, req) {
+                    dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                        peer_id,
+                        id,
+                        response: resp
+                            .clone()
+                            .map(|ledger| Box::new(P2pRpcResponse::EpochLedger(ledger))),
+                    });
+                }
+            }
             (_, LedgerReadResponse::ScanStateSummary(..)) => unreachable!(),
             (_req, LedgerReadResponse::GetAccounts(..)) => todo!(),
             (_, LedgerReadResponse::AccountsForRpc(rpc_id, accounts, account_query)) => {
@@ -242,6 +253,9 @@ impl LedgerReadState {
                         P2pRpcRequest::StagedLedgerAuxAndPendingCoinbasesAtBlock(block_hash) => {
                             build_staged_ledger_parts_request(state, block_hash)?
                         }
+                        P2pRpcRequest::EpochLedger(hash) => {
+                            LedgerReadRequest::GetEpochLedger(hash.clone())
+                        }
                         _ => return None,
                     };
 
@@ -364,6 +378,9 @@ fn find_peers_with_ledger_rpc(
                         .map_or(false, |b| {
                             b.blockchain_state.staged_ledger_hash == data.ledger_hash
                         }),
+                    (LedgerReadRequest::GetEpochLedger(h1), P2pRpcRequest::EpochLedger(h2)) => {
+                        h1 == h2
+                    }
                     _ => false,
                 })
                 .map(|(peer_id, rpc_id, _)| (*peer_id, rpc_id, false));
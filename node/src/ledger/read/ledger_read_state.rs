@@ -47,6 +47,22 @@ impl LedgerReadState {
         self.pending.get_mut(id)
     }
 
+    /// Number of requests sent to the ledger service which weren't
+    /// handled yet.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn pending_requests(
+        &self,
+    ) -> impl Iterator<Item = (LedgerReadId, &LedgerReadRequestState)> {
+        self.pending.iter()
+    }
+
+    pub fn total_cost(&self) -> usize {
+        self.total_cost
+    }
+
     pub fn is_total_cost_under_limit(&self) -> bool {
         self.total_cost < MAX_TOTAL_COST
     }
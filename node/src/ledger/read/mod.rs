@@ -12,7 +12,7 @@ use redux::Callback;
 
 mod ledger_read_reducer;
 
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
 use mina_p2p_messages::v2;
@@ -32,20 +32,22 @@ pub enum LedgerReadKind {
     GetChildHashesAtAddr,
     GetChildAccountsAtAddr,
     GetStagedLedgerAuxAndPendingCoinbases,
+    GetEpochLedger,
     ScanStateSummary,
     AccountsForRpc,
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
 pub enum LedgerReadRequest {
-    /// Delegator table requested by vrf state machine.
-    DelegatorTable(v2::LedgerHash, AccountPublicKey),
+    /// Delegator table of our producer keys, requested by vrf state machine.
+    DelegatorTable(v2::LedgerHash, BTreeSet<AccountPublicKey>),
     // p2p rpcs
     GetNumAccounts(v2::LedgerHash),
     GetAccounts(v2::LedgerHash, Vec<AccountId>, Option<RpcId>),
     GetChildHashesAtAddr(v2::LedgerHash, LedgerAddress),
     GetChildAccountsAtAddr(v2::LedgerHash, LedgerAddress),
     GetStagedLedgerAuxAndPendingCoinbases(LedgerReadStagedLedgerAuxAndPendingCoinbases),
+    GetEpochLedger(v2::LedgerHash),
     // rpcs
     ScanStateSummary(v2::MinaBaseStagedLedgerHashStableV1),
     AccountsForRpc(RpcId, v2::LedgerHash, AccountQuery),
@@ -61,6 +63,7 @@ pub enum LedgerReadResponse {
     GetChildHashesAtAddr(Option<(v2::LedgerHash, v2::LedgerHash)>),
     GetChildAccountsAtAddr(Option<Vec<v2::MinaBaseAccountBinableArgStableV2>>),
     GetStagedLedgerAuxAndPendingCoinbases(Option<Arc<StagedLedgerAuxAndPendingCoinbases>>),
+    GetEpochLedger(Option<Arc<v2::MinaBaseSparseLedgerBaseStableV2>>),
     // rpcs
     ScanStateSummary(Result<Vec<Vec<RpcScanStateSummaryScanStateJob>>, String>),
     AccountsForRpc(RpcId, Vec<Account>, AccountQuery),
@@ -83,6 +86,7 @@ impl LedgerReadRequest {
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => {
                 LedgerReadKind::GetStagedLedgerAuxAndPendingCoinbases
             }
+            Self::GetEpochLedger(..) => LedgerReadKind::GetEpochLedger,
             Self::ScanStateSummary(..) => LedgerReadKind::ScanStateSummary,
             Self::AccountsForRpc(..) => LedgerReadKind::AccountsForRpc,
         }
@@ -100,6 +104,7 @@ impl LedgerReadRequest {
             }
             Self::GetChildHashesAtAddr(..) => 1,
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => 100,
+            Self::GetEpochLedger(..) => 100,
             Self::ScanStateSummary(..) => 100,
             // TODO(adonagy): not sure
             Self::AccountsForRpc(..) => 10,
@@ -119,6 +124,7 @@ impl LedgerReadResponse {
             Self::GetStagedLedgerAuxAndPendingCoinbases(..) => {
                 LedgerReadKind::GetStagedLedgerAuxAndPendingCoinbases
             }
+            Self::GetEpochLedger(..) => LedgerReadKind::GetEpochLedger,
             Self::ScanStateSummary(..) => LedgerReadKind::ScanStateSummary,
             Self::AccountsForRpc(..) => LedgerReadKind::AccountsForRpc,
         }
@@ -2,8 +2,11 @@ use redux::Dispatcher;
 
 use crate::{
     ledger_effectful::LedgerEffectfulAction,
-    transition_frontier::sync::{
-        ledger::staged::TransitionFrontierSyncLedgerStagedAction, TransitionFrontierSyncAction,
+    transition_frontier::{
+        persistence::{restored_frontier_check, TransitionFrontierPersistenceAction},
+        sync::{
+            ledger::staged::TransitionFrontierSyncLedgerStagedAction, TransitionFrontierSyncAction,
+        },
     },
     Action, BlockProducerAction, State, Substate,
 };
@@ -55,6 +58,7 @@ impl LedgerWriteState {
                 dispatcher.push(TransitionFrontierSyncAction::BlocksNextApplyInit);
                 dispatcher.push(TransitionFrontierSyncAction::CommitInit);
                 dispatcher.push(TransitionFrontierSyncLedgerStagedAction::ReconstructInit);
+                dispatcher.push(TransitionFrontierPersistenceAction::PersistInit);
             }
         }
     }
@@ -144,6 +148,7 @@ impl LedgerWriteState {
                     dispatcher.push(TransitionFrontierSyncAction::BlocksNextApplySuccess {
                         hash,
                         just_emitted_a_proof: result.just_emitted_a_proof,
+                        archive_data: result.archive_data,
                     });
                 }
             },
@@ -159,6 +164,40 @@ impl LedgerWriteState {
                     dispatcher.push(TransitionFrontierSyncAction::CommitSuccess { result });
                 }
             }
+            (
+                _,
+                LedgerWriteResponse::FrontierPersist {
+                    best_tip_hash,
+                    result,
+                },
+            ) => match result {
+                Err(error) => {
+                    dispatcher.push(TransitionFrontierPersistenceAction::PersistError {
+                        best_tip_hash,
+                        error,
+                    });
+                }
+                Ok(()) => {
+                    dispatcher.push(TransitionFrontierPersistenceAction::PersistSuccess {
+                        best_tip_hash,
+                    });
+                }
+            },
+            (_, LedgerWriteResponse::FrontierRestore { result }) => {
+                match result.and_then(|result| {
+                    restored_frontier_check(state, &result)?;
+                    Ok(result)
+                }) {
+                    Err(error) => {
+                        dispatcher
+                            .push(TransitionFrontierPersistenceAction::RestoreError { error });
+                    }
+                    Ok(result) => {
+                        dispatcher
+                            .push(TransitionFrontierPersistenceAction::RestoreSuccess { result });
+                    }
+                }
+            }
         }
     }
 }
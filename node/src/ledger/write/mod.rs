@@ -28,6 +28,8 @@ pub enum LedgerWriteKind {
     StagedLedgerDiffCreate,
     BlockApply,
     Commit,
+    FrontierPersist,
+    FrontierRestore,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -51,6 +53,8 @@ pub enum LedgerWriteRequest {
         block: ArcBlockWithHash,
         pred_block: AppliedBlock,
         skip_verification: bool,
+        /// Collect data needed by the archive, see [`BlockApplyResultArchive`].
+        archive: bool,
     },
     Commit {
         ledgers_to_keep: BTreeSet<v2::LedgerHash>,
@@ -59,6 +63,15 @@ pub enum LedgerWriteRequest {
         new_root: AppliedBlock,
         new_best_tip: AppliedBlock,
     },
+    /// Write snapshot of the transition frontier to disk.
+    FrontierPersist {
+        genesis_state_hash: v2::StateHash,
+        best_chain: Vec<AppliedBlock>,
+        needed_protocol_states: BTreeMap<v2::StateHash, v2::MinaStateProtocolStateValueStableV2>,
+    },
+    /// Load transition frontier snapshot from disk and reconstruct
+    /// ledgers required by it.
+    FrontierRestore { genesis_state_hash: v2::StateHash },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -80,11 +93,33 @@ pub enum LedgerWriteResponse {
         best_tip_hash: v2::StateHash,
         result: CommitResult,
     },
+    FrontierPersist {
+        best_tip_hash: v2::StateHash,
+        result: Result<(), String>,
+    },
+    FrontierRestore {
+        result: Result<FrontierRestoreResult, String>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BlockApplyResult {
     pub just_emitted_a_proof: bool,
+    pub archive_data: Option<Arc<BlockApplyResultArchive>>,
+}
+
+/// Ledger changes made by the block, which are needed by the archive
+/// but can't be derived from the block itself. Same as the corresponding
+/// fields of the Mina precomputed block.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockApplyResultArchive {
+    /// Accounts accessed by the block, with their ledger index, in the
+    /// state after applying the block.
+    pub accounts_accessed: Vec<(u64, v2::MinaBaseAccountBinableArgStableV2)>,
+    /// Accounts created by the block, with the creation fee paid.
+    pub accounts_created: Vec<(v2::MinaBaseAccountIdStableV2, v2::CurrencyFeeStableV1)>,
+    /// Tokens used by the block, with the token owner if it was accessed.
+    pub tokens_used: Vec<(v2::TokenIdKeyHash, Option<v2::MinaBaseAccountIdStableV2>)>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -93,6 +128,14 @@ pub struct CommitResult {
     pub needed_protocol_states: BTreeSet<v2::StateHash>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FrontierRestoreResult {
+    /// Best chain, from the root to the best tip.
+    pub chain: Vec<AppliedBlock>,
+    /// Protocol states needed by the root scan state, which aren't in `chain`.
+    pub needed_protocol_states: BTreeMap<v2::StateHash, v2::MinaStateProtocolStateValueStableV2>,
+}
+
 impl LedgerWriteRequest {
     pub fn kind(&self) -> LedgerWriteKind {
         match self {
@@ -100,6 +143,8 @@ impl LedgerWriteRequest {
             Self::StagedLedgerDiffCreate { .. } => LedgerWriteKind::StagedLedgerDiffCreate,
             Self::BlockApply { .. } => LedgerWriteKind::BlockApply,
             Self::Commit { .. } => LedgerWriteKind::Commit,
+            Self::FrontierPersist { .. } => LedgerWriteKind::FrontierPersist,
+            Self::FrontierRestore { .. } => LedgerWriteKind::FrontierRestore,
         }
     }
 }
@@ -111,6 +156,8 @@ impl LedgerWriteResponse {
             Self::StagedLedgerDiffCreate { .. } => LedgerWriteKind::StagedLedgerDiffCreate,
             Self::BlockApply { .. } => LedgerWriteKind::BlockApply,
             Self::Commit { .. } => LedgerWriteKind::Commit,
+            Self::FrontierPersist { .. } => LedgerWriteKind::FrontierPersist,
+            Self::FrontierRestore { .. } => LedgerWriteKind::FrontierRestore,
         }
     }
 }
@@ -65,6 +65,7 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
                 P2pConnectionAction::Incoming(action) => action.action_event(&context),
             },
             P2pAction::Disconnection(action) => action.action_event(&context),
+            P2pAction::Reputation(action) => action.action_event(&context),
             P2pAction::Identify(action) => action.action_event(&context),
             P2pAction::Channels(action) => match action {
                 P2pChannelsAction::MessageReceived(action) => action.action_event(&context),
@@ -98,6 +99,8 @@ pub fn logger_effects<S: Service>(store: &Store<S>, action: ActionWithMetaRef<'_
                 P2pNetworkAction::Select(action) => action.action_event(&context),
                 P2pNetworkAction::Noise(action) => action.action_event(&context),
                 P2pNetworkAction::Yamux(action) => action.action_event(&context),
+                P2pNetworkAction::Quic(action) => action.action_event(&context),
+                P2pNetworkAction::Nat(action) => action.action_event(&context),
                 P2pNetworkAction::Rpc(action) => action.action_event(&context),
                 P2pNetworkAction::Kad(action) => action.action_event(&context),
                 P2pNetworkAction::Pubsub(action) => action.action_event(&context),
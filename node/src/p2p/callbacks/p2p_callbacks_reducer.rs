@@ -1,13 +1,22 @@
 use ark_ff::fields::arithmetic::InvalidBigInt;
 use mina_p2p_messages::v2::{MinaLedgerSyncLedgerAnswerStableV2, StateHash};
-use openmina_core::{block::BlockWithHash, bug_condition, transaction::TransactionWithHash};
+use openmina_core::{
+    block::{AppliedBlock, BlockWithHash},
+    bug_condition,
+    consensus::consensus_take,
+    transaction::TransactionWithHash,
+};
 use p2p::{
     channels::{
         best_tip::P2pChannelsBestTipAction,
-        rpc::{BestTipWithProof, P2pChannelsRpcAction, P2pRpcRequest, P2pRpcResponse},
+        rpc::{
+            BestTipWithProof, P2pChannelsRpcAction, P2pRpcRequest, P2pRpcResponse,
+            TransitionChainProof,
+        },
         streaming_rpc::P2pStreamingRpcResponseFull,
     },
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    reputation::{P2pPenaltyReason, P2pReputationAction},
     PeerId,
 };
 use redux::{ActionMeta, ActionWithMeta, Dispatcher};
@@ -34,6 +43,27 @@ use crate::{
 
 use super::P2pCallbacksAction;
 
+/// Best tip along with the proof, which is the root block and body hashes
+/// of the blocks after it.
+fn best_tip_with_proof(
+    best_chain: &[AppliedBlock],
+) -> Result<Option<BestTipWithProof>, InvalidBigInt> {
+    let (Some(best_tip), Some((root_block, rest))) = (best_chain.last(), best_chain.split_first())
+    else {
+        return Ok(None);
+    };
+    // TODO(binier): cache body hashes
+    let body_hashes = rest
+        .iter()
+        .map(|b| b.header().protocol_state.body.try_hash())
+        .collect::<Result<_, _>>()?;
+
+    Ok(Some(BestTipWithProof {
+        best_tip: best_tip.block().clone(),
+        proof: (body_hashes, root_block.block().clone()),
+    }))
+}
+
 fn get_rpc_request<'a>(state: &'a State, peer_id: &PeerId) -> Option<&'a P2pRpcRequest> {
     state
         .p2p
@@ -108,6 +138,10 @@ impl crate::State {
                     rpc_id,
                     error: PeerBlockFetchError::Timeout,
                 });
+                dispatcher.push(P2pReputationAction::Penalize {
+                    peer_id,
+                    reason: P2pPenaltyReason::Timeout,
+                });
                 dispatcher.push(P2pDisconnectionAction::Init {
                     peer_id,
                     reason: P2pDisconnectionReason::TransitionFrontierRpcTimeout(rpc_kind),
@@ -162,6 +196,10 @@ impl crate::State {
                         error: PeerStagedLedgerPartsFetchError::Timeout,
                     },
                 );
+                dispatcher.push(P2pReputationAction::Penalize {
+                    peer_id,
+                    reason: P2pPenaltyReason::Timeout,
+                });
                 dispatcher.push(P2pDisconnectionAction::Init {
                     peer_id,
                     reason: P2pDisconnectionReason::TransitionFrontierStreamingRpcTimeout(rpc_kind),
@@ -303,32 +341,90 @@ impl crate::State {
     ) {
         match request {
             P2pRpcRequest::BestTipWithProof => {
+                let best_chain = &state.transition_frontier.best_chain;
+                let response = best_tip_with_proof(best_chain).unwrap_or_else(|_| {
+                    openmina_core::error!(meta.time(); "P2pRpcRequest::BestTipWithProof: invalid protocol state");
+                    None
+                });
+                let response = response.map(P2pRpcResponse::BestTipWithProof).map(Box::new);
+                dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                    peer_id,
+                    id,
+                    response,
+                });
+            }
+            P2pRpcRequest::Ancestry(hash, consensus_state) => {
+                let best_chain = &state.transition_frontier.best_chain;
+                // Like the OCaml node, only answer if our best tip would be
+                // taken over the block the peer is asking about.
+                let is_better = best_chain.last().is_some_and(|best_tip| {
+                    consensus_take(
+                        &consensus_state,
+                        best_tip.consensus_state(),
+                        &hash,
+                        best_tip.hash(),
+                    )
+                });
+                let response = if is_better {
+                    best_tip_with_proof(best_chain).unwrap_or_else(|_| {
+                        openmina_core::error!(meta.time(); "P2pRpcRequest::Ancestry: invalid protocol state");
+                        None
+                    })
+                } else {
+                    None
+                };
+                let response = response.map(P2pRpcResponse::Ancestry).map(Box::new);
+                dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                    peer_id,
+                    id,
+                    response,
+                });
+            }
+            P2pRpcRequest::TransitionChainProof(hash) => {
+                // Only blocks of the best chain are searched, so there's no
+                // proof for blocks of forks, even if we have them.
                 let best_chain = &state.transition_frontier.best_chain;
                 let response = None.or_else(|| {
-                    let best_tip = best_chain.last()?;
-                    let mut chain_iter = best_chain.iter();
-                    let root_block = chain_iter.next()?;
-                    // TODO(binier): cache body hashes
-                    let Ok(body_hashes) = chain_iter
+                    let root_block = best_chain.first()?;
+                    let index = best_chain.iter().rposition(|b| b.hash() == &hash)?;
+                    let Ok(body_hashes) = best_chain
+                        .get(1..=index)?
+                        .iter()
                         .map(|b| b.header().protocol_state.body.try_hash())
                         .collect::<Result<_, _>>()
                     else {
-                        openmina_core::error!(meta.time(); "P2pRpcRequest::BestTipWithProof: invalid protocol state");
+                        openmina_core::error!(meta.time(); "P2pRpcRequest::TransitionChainProof: invalid protocol state");
                         return None;
                     };
 
-                    Some(BestTipWithProof {
-                        best_tip: best_tip.block().clone(),
-                        proof: (body_hashes, root_block.block().clone()),
+                    Some(TransitionChainProof {
+                        first_hash: root_block.hash().clone(),
+                        body_hashes,
                     })
                 });
-                let response = response.map(P2pRpcResponse::BestTipWithProof).map(Box::new);
+                let response = response
+                    .map(P2pRpcResponse::TransitionChainProof)
+                    .map(Box::new);
                 dispatcher.push(P2pChannelsRpcAction::ResponseSend {
                     peer_id,
                     id,
                     response,
                 });
             }
+            P2pRpcRequest::EpochLedger(hash) => {
+                let is_known = state.transition_frontier.best_tip().is_some_and(|b| {
+                    b.staking_epoch_ledger_hash() == &hash || b.next_epoch_ledger_hash() == &hash
+                });
+                if !is_known {
+                    dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                        peer_id,
+                        id,
+                        response: None,
+                    });
+                }
+                // otherwise async ledger request will be triggered
+                // by `LedgerReadAction::FindTodos`.
+            }
             P2pRpcRequest::Block(hash) => {
                 let best_chain = &state.transition_frontier.best_chain;
                 let response = best_chain
@@ -395,6 +491,14 @@ impl crate::State {
                     response,
                 });
             }
+            P2pRpcRequest::BanNotify(_) => {
+                // Acknowledge, so the peer can disconnect us.
+                dispatcher.push(P2pChannelsRpcAction::ResponseSend {
+                    peer_id,
+                    id,
+                    response: None,
+                });
+            }
         }
     }
 
@@ -451,6 +555,13 @@ impl crate::State {
                     error: PeerBlockFetchError::DataUnavailable,
                 });
             }
+            Some(
+                P2pRpcResponse::Ancestry(_)
+                | P2pRpcResponse::TransitionChainProof(_)
+                | P2pRpcResponse::EpochLedger(_),
+            ) => {
+                // we don't request these yet, only serve them.
+            }
             Some(P2pRpcResponse::BestTipWithProof(resp)) => {
                 let (body_hashes, root_block) = &resp.proof;
 
@@ -95,6 +95,8 @@ impl_into_global_action!(connection::incoming::P2pConnectionIncomingAction);
 
 impl_into_global_action!(disconnection::P2pDisconnectionAction);
 
+impl_into_global_action!(reputation::P2pReputationAction);
+
 impl_into_global_action!(network::P2pNetworkSchedulerAction);
 impl_into_global_action!(network::kad::P2pNetworkKademliaAction);
 impl_into_global_action!(network::pubsub::P2pNetworkPubsubAction);
@@ -113,6 +115,8 @@ impl_into_global_action!(p2p::P2pNetworkKademliaStreamAction);
 impl_into_global_action!(p2p::P2pNetworkKadRequestAction);
 impl_into_global_action!(p2p::P2pNetworkKadBootstrapAction);
 impl_into_global_action!(p2p::P2pNetworkYamuxAction);
+impl_into_global_action!(p2p::P2pNetworkQuicAction);
+impl_into_global_action!(p2p::P2pNetworkNatAction);
 impl_into_global_action!(p2p::peer::P2pPeerAction);
 impl_into_global_action!(p2p::network::identify::stream::P2pNetworkIdentifyStreamAction);
 impl_into_global_action!(p2p::identify::P2pIdentifyAction);
@@ -124,6 +128,8 @@ impl_into_global_action!(p2p::P2pNetworkRpcAction);
 impl_into_global_action!(effectful network::kad_effectful::P2pNetworkKadEffectfulAction);
 impl_into_global_action!(effectful p2p::P2pNetworkSchedulerEffectfulAction);
 impl_into_global_action!(effectful p2p::P2pNetworkPnetEffectfulAction);
+impl_into_global_action!(effectful p2p::P2pNetworkQuicEffectfulAction);
+impl_into_global_action!(effectful p2p::P2pNetworkNatEffectfulAction);
 impl_into_global_action!(effectful connection::incoming_effectful::P2pConnectionIncomingEffectfulAction);
 impl_into_global_action!(effectful connection::outgoing_effectful::P2pConnectionOutgoingEffectfulAction);
 impl_into_global_action!(effectful p2p::disconnection_effectful::P2pDisconnectionEffectfulAction);
@@ -1,9 +1,9 @@
 #[allow(clippy::module_inception)]
 mod recorder;
-pub use recorder::Recorder;
+pub use recorder::{Recorder, RecorderSegment, RollingRecorderConfig};
 
 mod replayer;
-pub use replayer::StateWithInputActionsReader;
+pub use replayer::{RecordedCheckpointInfo, StateWithInputActionsReader};
 
 use std::{
     borrow::Cow,
@@ -25,6 +25,17 @@ fn actions_path<P: AsRef<Path>>(path: P, file_index: usize) -> PathBuf {
         .join(format!("actions_{}.postcard", file_index))
 }
 
+/// Path of the checkpoint taken right after the action with `action_id`.
+/// The id is encoded in the name, so that checkpoints can be listed
+/// without decoding them.
+fn checkpoint_path<P: AsRef<Path>>(path: P, action_id: u64) -> PathBuf {
+    path.as_ref()
+        .join(format!("{CHECKPOINT_PREFIX}{action_id}{CHECKPOINT_SUFFIX}"))
+}
+
+const CHECKPOINT_PREFIX: &str = "checkpoint_";
+const CHECKPOINT_SUFFIX: &str = ".postcard";
+
 #[derive(Serialize, Deserialize)]
 pub struct RecordedInitialState<'a> {
     pub rng_seed: [u8; 32],
@@ -42,6 +53,28 @@ impl RecordedInitialState<'_> {
     }
 }
 
+/// Full state periodically written by the rolling recorder. Along with
+/// the actions recorded after it, it is enough to resume the replay.
+#[derive(Serialize, Deserialize)]
+pub struct RecordedCheckpoint<'a> {
+    pub rng_seed: [u8; 32],
+    pub p2p_sec_key: P2pSecretKey,
+    /// Index of the actions file which holds actions dispatched after
+    /// the checkpoint.
+    pub actions_f_index: usize,
+    pub state: Cow<'a, State>,
+}
+
+impl RecordedCheckpoint<'_> {
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> postcard::Result<()> {
+        postcard::to_io(self, writer).and(Ok(()))
+    }
+
+    pub fn decode(encoded: &[u8]) -> postcard::Result<Self> {
+        postcard::from_bytes(encoded)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct RecordedActionWithMeta<'a> {
     pub kind: ActionKind,
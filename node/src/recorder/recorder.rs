@@ -1,16 +1,46 @@
 use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fs;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, TryLockError};
+use std::time::{Duration, SystemTime};
 
 use crate::p2p::identity::SecretKey as P2pSecretKey;
 use crate::{Action, ActionWithMeta, EventSourceAction, State};
 
-use super::{RecordedActionWithMeta, RecordedInitialState};
+use super::{RecordedActionWithMeta, RecordedCheckpoint, RecordedInitialState};
 
 static ACTIONS_F: Mutex<Vec<Option<fs::File>>> = Mutex::new(Vec::new());
 
+const ACTIONS_F_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy)]
+pub struct RollingRecorderConfig {
+    /// For how long recorded actions and checkpoints are kept.
+    pub retention: Duration,
+    /// How often the whole state is written to disk.
+    pub checkpoint_interval: Duration,
+}
+
+impl Default for RollingRecorderConfig {
+    fn default() -> Self {
+        Self {
+            retention: Duration::from_secs(24 * 60 * 60),
+            checkpoint_interval: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Part of the recording starting with a checkpoint (or the initial
+/// state), followed by actions files up to the next segment.
+#[derive(Debug)]
+pub struct RecorderSegment {
+    start_time_nanos: u64,
+    first_actions_f_index: usize,
+    checkpoint_path: PathBuf,
+}
+
 /// Panics: if all the `Recorder` instances aren't in the same thread.
 pub enum Recorder {
     None,
@@ -20,6 +50,22 @@ pub enum Recorder {
         actions_f_bytes_written: u64,
         actions_f_index: usize,
     },
+    /// Records input actions like [`Recorder::OnlyInputActions`], but
+    /// periodically writes checkpoints of the whole state and deletes
+    /// recordings older than [`RollingRecorderConfig::retention`].
+    Rolling {
+        recorder_i: usize,
+        recorder_path: PathBuf,
+        actions_f_bytes_written: u64,
+        actions_f_index: usize,
+        config: RollingRecorderConfig,
+        segments: VecDeque<RecorderSegment>,
+        next_checkpoint_nanos: u64,
+        /// Written to each checkpoint, as the initial state is removed
+        /// once it expires.
+        rng_seed: [u8; 32],
+        p2p_sec_key: Option<P2pSecretKey>,
+    },
 }
 
 impl Recorder {
@@ -30,40 +76,91 @@ impl Recorder {
         fs::create_dir_all(&path).expect("creating dir for openmina recorder failed!");
 
         let actions_f_index = 1;
-        let actions_path = super::actions_path(&path, actions_f_index);
+        Self::OnlyInputActions {
+            recorder_i: open_first_actions_file(&path, actions_f_index),
+            recorder_path: path,
+            actions_f_bytes_written: 0,
+            actions_f_index,
+        }
+    }
 
-        let file = fs::File::create(actions_path)
-            .expect("creating file for openmina recorder initial state failed!");
-        let mut actions_files = ACTIONS_F.try_lock().unwrap();
-        actions_files.push(Some(file));
+    /// Unlike [`Recorder::only_input_actions`], previous recordings
+    /// aren't wiped. Each run is recorded in its own directory
+    /// `recorder/<unix timestamp in ms>` and runs older than the retention
+    /// period are removed.
+    pub fn rolling<P: AsRef<Path>>(work_dir: P, config: RollingRecorderConfig) -> Self {
+        let runs_path = work_dir.as_ref().join("recorder");
+        let now = SystemTime::now();
+        remove_old_runs(&runs_path, now, config.retention);
 
-        Self::OnlyInputActions {
-            recorder_i: actions_files.len().saturating_sub(1),
+        let run_name = now
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis()
+            .to_string();
+        let path = runs_path.join(run_name);
+        fs::create_dir_all(&path).expect("creating dir for openmina recorder failed!");
+
+        let actions_f_index = 1;
+        Self::Rolling {
+            recorder_i: open_first_actions_file(&path, actions_f_index),
             recorder_path: path,
             actions_f_bytes_written: 0,
             actions_f_index,
+            config,
+            segments: VecDeque::new(),
+            next_checkpoint_nanos: 0,
+            rng_seed: [0; 32],
+            p2p_sec_key: None,
         }
     }
 
     pub fn initial_state(&mut self, rng_seed: [u8; 32], p2p_sec_key: P2pSecretKey, state: &State) {
-        match self {
-            Self::None => {}
-            Self::OnlyInputActions { recorder_path, .. } => {
-                let initial_state = RecordedInitialState {
-                    rng_seed,
-                    p2p_sec_key,
-                    state: Cow::Borrowed(state),
-                };
-                let initial_state_path = super::initial_state_path(recorder_path);
-                let mut initial_state_f = fs::File::create(initial_state_path)
-                    .expect("creating file for openmina recorder initial state failed!");
-                initial_state.write_to(&mut initial_state_f).unwrap();
-                initial_state_f.sync_all().unwrap();
+        let recorder_path = match self {
+            Self::None => return,
+            Self::OnlyInputActions { recorder_path, .. } => recorder_path,
+            Self::Rolling {
+                recorder_path,
+                config,
+                segments,
+                next_checkpoint_nanos,
+                rng_seed: checkpoint_rng_seed,
+                p2p_sec_key: checkpoint_p2p_sec_key,
+                ..
+            } => {
+                *checkpoint_rng_seed = rng_seed;
+                *checkpoint_p2p_sec_key = Some(p2p_sec_key.clone());
+                let start_time_nanos = state.last_action().time_as_nanos();
+                segments.push_back(RecorderSegment {
+                    start_time_nanos,
+                    first_actions_f_index: 1,
+                    checkpoint_path: super::initial_state_path(recorder_path.as_path()),
+                });
+                *next_checkpoint_nanos =
+                    start_time_nanos.saturating_add(duration_nanos(config.checkpoint_interval));
+                recorder_path
             }
-        }
+        };
+        let initial_state = RecordedInitialState {
+            rng_seed,
+            p2p_sec_key,
+            state: Cow::Borrowed(state),
+        };
+        let initial_state_path = super::initial_state_path(recorder_path);
+        let mut initial_state_f = fs::File::create(initial_state_path)
+            .expect("creating file for openmina recorder initial state failed!");
+        initial_state.write_to(&mut initial_state_f).unwrap();
+        initial_state_f.sync_all().unwrap();
     }
 
-    pub fn action(&mut self, action: &ActionWithMeta) {
+    /// `state` must be the state after `action` was applied. It is
+    /// written to disk as a checkpoint by the [`Recorder::Rolling`].
+    ///
+    /// Checkpoints are only taken at [`EventSourceAction::WaitForEvents`],
+    /// which is dispatched by the main loop once the effects of previous
+    /// input actions are done, so that the replay can resume from the
+    /// checkpoint with the next recorded input action.
+    pub fn action(&mut self, action: &ActionWithMeta, state: &State) {
         match self {
             Self::None => {}
             Self::OnlyInputActions {
@@ -73,54 +170,84 @@ impl Recorder {
                 actions_f_index,
                 ..
             } => {
-                let is_input = match action.action() {
-                    Action::CheckTimeouts(_) => true,
-                    Action::EventSource(e) => match e {
-                        EventSourceAction::NewEvent { .. } => true,
-                        _ => return,
-                    },
-                    _ => false,
+                let Some(data) = recorded_action(action) else {
+                    return;
                 };
+                write_action(
+                    *recorder_i,
+                    recorder_path,
+                    actions_f_bytes_written,
+                    actions_f_index,
+                    &data,
+                );
+            }
+            Self::Rolling {
+                recorder_i,
+                recorder_path,
+                actions_f_bytes_written,
+                actions_f_index,
+                config,
+                segments,
+                next_checkpoint_nanos,
+                rng_seed,
+                p2p_sec_key,
+            } => {
+                if let Some(data) = recorded_action(action) {
+                    write_action(
+                        *recorder_i,
+                        recorder_path,
+                        actions_f_bytes_written,
+                        actions_f_index,
+                        &data,
+                    );
+                }
 
-                let data = if !is_input {
-                    let kind = action.action().kind();
-                    RecordedActionWithMeta::from((kind, action.meta().clone()))
-                } else {
-                    RecordedActionWithMeta::from(action)
+                let now = action.meta().time_as_nanos();
+                let is_checkpoint_boundary = matches!(
+                    action.action(),
+                    Action::EventSource(EventSourceAction::WaitForEvents)
+                );
+                if !is_checkpoint_boundary || now < *next_checkpoint_nanos {
+                    return;
+                }
+                let Some(p2p_sec_key) = p2p_sec_key.clone() else {
+                    return;
                 };
+                // Actions after the checkpoint go to a new file, so
+                // that the whole segment can be removed once expired.
+                {
+                    let mut files = ACTIONS_F.try_lock().unwrap();
+                    let cur_f = files.get_mut(*recorder_i).unwrap();
+                    next_actions_file(
+                        cur_f,
+                        recorder_path,
+                        actions_f_bytes_written,
+                        actions_f_index,
+                    );
+                }
 
-                let mut files = ACTIONS_F.try_lock().unwrap();
-                let cur_f = files.get_mut(*recorder_i).unwrap(); // TODO: error propagation
-
-                let file = if *actions_f_bytes_written > 64 * 1024 * 1024 {
-                    cur_f.take().unwrap().sync_all().unwrap();
-                    *actions_f_bytes_written = 0;
-                    *actions_f_index = actions_f_index
-                        .checked_add(1)
-                        .expect("overflow in actions_f_index");
-                    cur_f.insert(
-                        fs::File::create(super::actions_path(recorder_path, *actions_f_index))
-                            .unwrap(),
-                    )
-                } else {
-                    cur_f.as_mut().unwrap()
+                let checkpoint = RecordedCheckpoint {
+                    rng_seed: *rng_seed,
+                    p2p_sec_key,
+                    actions_f_index: *actions_f_index,
+                    state: Cow::Borrowed(state),
                 };
+                let checkpoint_path = super::checkpoint_path(recorder_path.as_path(), now);
+                let mut checkpoint_f = fs::File::create(&checkpoint_path)
+                    .expect("creating file for openmina recorder checkpoint failed!");
+                checkpoint.write_to(&mut checkpoint_f).unwrap();
+                checkpoint_f.sync_all().unwrap();
 
-                let mut writer = BufWriter::new(file);
-
-                let encoded = data.encode().unwrap();
-                writer
-                    .write_all(&(encoded.len() as u64).to_be_bytes())
-                    .unwrap();
-                writer.write_all(&encoded).unwrap();
-                writer.flush().unwrap();
-
-                *actions_f_bytes_written = actions_f_bytes_written
-                    .checked_add(
-                        8u64.checked_add(encoded.len() as u64)
-                            .expect("overflow in encoded len"),
-                    )
-                    .expect("overflow in actions_f_bytes_written");
+                segments.push_back(RecorderSegment {
+                    start_time_nanos: now,
+                    first_actions_f_index: *actions_f_index,
+                    checkpoint_path,
+                });
+                *next_checkpoint_nanos =
+                    now.saturating_add(duration_nanos(config.checkpoint_interval));
+
+                let cutoff = now.saturating_sub(duration_nanos(config.retention));
+                remove_expired_segments(recorder_path, segments, cutoff);
             }
         }
     }
@@ -140,11 +267,147 @@ impl Drop for Recorder {
     fn drop(&mut self) {
         match self {
             Self::None => {}
-            Self::OnlyInputActions { recorder_i, .. } => graceful_shutdown(Some(*recorder_i)),
+            Self::OnlyInputActions { recorder_i, .. } | Self::Rolling { recorder_i, .. } => {
+                graceful_shutdown(Some(*recorder_i))
+            }
         }
     }
 }
 
+fn recorded_action(action: &ActionWithMeta) -> Option<RecordedActionWithMeta<'_>> {
+    let is_input = match action.action() {
+        Action::CheckTimeouts(_) => true,
+        Action::EventSource(e) => match e {
+            EventSourceAction::NewEvent { .. } => true,
+            _ => return None,
+        },
+        _ => false,
+    };
+
+    Some(if !is_input {
+        let kind = action.action().kind();
+        RecordedActionWithMeta::from((kind, action.meta().clone()))
+    } else {
+        RecordedActionWithMeta::from(action)
+    })
+}
+
+fn open_first_actions_file(recorder_path: &Path, actions_f_index: usize) -> usize {
+    let actions_path = super::actions_path(recorder_path, actions_f_index);
+
+    let file = fs::File::create(actions_path)
+        .expect("creating file for openmina recorder initial state failed!");
+    let mut actions_files = ACTIONS_F.try_lock().unwrap();
+    actions_files.push(Some(file));
+    actions_files.len().saturating_sub(1)
+}
+
+fn next_actions_file<'a>(
+    cur_f: &'a mut Option<fs::File>,
+    recorder_path: &Path,
+    actions_f_bytes_written: &mut u64,
+    actions_f_index: &mut usize,
+) -> &'a mut fs::File {
+    cur_f.take().unwrap().sync_all().unwrap();
+    *actions_f_bytes_written = 0;
+    *actions_f_index = actions_f_index
+        .checked_add(1)
+        .expect("overflow in actions_f_index");
+    cur_f.insert(fs::File::create(super::actions_path(recorder_path, *actions_f_index)).unwrap())
+}
+
+fn write_action(
+    recorder_i: usize,
+    recorder_path: &Path,
+    actions_f_bytes_written: &mut u64,
+    actions_f_index: &mut usize,
+    data: &RecordedActionWithMeta,
+) {
+    let mut files = ACTIONS_F.try_lock().unwrap();
+    let cur_f = files.get_mut(recorder_i).unwrap(); // TODO: error propagation
+
+    let file = if *actions_f_bytes_written > ACTIONS_F_MAX_SIZE {
+        next_actions_file(
+            cur_f,
+            recorder_path,
+            actions_f_bytes_written,
+            actions_f_index,
+        )
+    } else {
+        cur_f.as_mut().unwrap()
+    };
+
+    let mut writer = BufWriter::new(file);
+
+    let encoded = data.encode().unwrap();
+    writer
+        .write_all(&(encoded.len() as u64).to_be_bytes())
+        .unwrap();
+    writer.write_all(&encoded).unwrap();
+    writer.flush().unwrap();
+
+    *actions_f_bytes_written = actions_f_bytes_written
+        .checked_add(
+            8u64.checked_add(encoded.len() as u64)
+                .expect("overflow in encoded len"),
+        )
+        .expect("overflow in actions_f_bytes_written");
+}
+
+/// Removes segments which only contain actions older than `cutoff`. The
+/// latest segment is always kept.
+fn remove_expired_segments(
+    recorder_path: &Path,
+    segments: &mut VecDeque<RecorderSegment>,
+    cutoff: u64,
+) {
+    while segments
+        .get(1)
+        .is_some_and(|next| next.start_time_nanos <= cutoff)
+    {
+        let Some(segment) = segments.pop_front() else {
+            break;
+        };
+        let next_first_actions_f_index = segments[0].first_actions_f_index;
+        for i in segment.first_actions_f_index..next_first_actions_f_index {
+            let _ = fs::remove_file(super::actions_path(recorder_path, i));
+        }
+        let _ = fs::remove_file(&segment.checkpoint_path);
+    }
+}
+
+/// Removes recordings of previous runs, which weren't modified during
+/// the `retention` period.
+fn remove_old_runs(runs_path: &Path, now: SystemTime, retention: Duration) {
+    let Ok(entries) = fs::read_dir(runs_path) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let is_run_dir = path.is_dir()
+            && path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.parse::<u64>().is_ok());
+        if !is_run_dir {
+            continue;
+        }
+        let is_expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > retention);
+        if is_expired {
+            let _ = fs::remove_dir_all(&path);
+        }
+    }
+}
+
+fn duration_nanos(duration: Duration) -> u64 {
+    duration.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
 fn graceful_shutdown(only_i: Option<usize>) {
     let Some(mut files) = ACTIONS_F.try_lock().map_or_else(
         |err| match err {
@@ -166,3 +429,70 @@ fn graceful_shutdown(only_i: Option<usize>) {
         let _ = file.sync_all();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{actions_path, checkpoint_path};
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "openmina-recorder-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn segment(
+        path: &Path,
+        start_time_nanos: u64,
+        first_actions_f_index: usize,
+    ) -> RecorderSegment {
+        let checkpoint_path = checkpoint_path(path, start_time_nanos);
+        fs::write(&checkpoint_path, b"checkpoint").unwrap();
+        RecorderSegment {
+            start_time_nanos,
+            first_actions_f_index,
+            checkpoint_path,
+        }
+    }
+
+    #[test]
+    fn test_remove_expired_segments() {
+        let path = temp_dir("expired-segments");
+        for i in 1..=5 {
+            fs::write(actions_path(&path, i), b"actions").unwrap();
+        }
+        let mut segments = VecDeque::from([
+            segment(&path, 100, 1),
+            segment(&path, 200, 3),
+            segment(&path, 300, 4),
+        ]);
+
+        // the first segment still has actions after the cutoff
+        remove_expired_segments(&path, &mut segments, 150);
+        assert_eq!(segments.len(), 3);
+        assert!(actions_path(&path, 1).exists());
+
+        remove_expired_segments(&path, &mut segments, 250);
+        assert_eq!(segments.len(), 2);
+        assert!(!checkpoint_path(&path, 100).exists());
+        assert!(!actions_path(&path, 1).exists());
+        assert!(!actions_path(&path, 2).exists());
+        assert!(checkpoint_path(&path, 200).exists());
+        assert!(actions_path(&path, 3).exists());
+
+        // the latest segment is kept, even if expired
+        remove_expired_segments(&path, &mut segments, 1000);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].start_time_nanos, 300);
+        assert!(!actions_path(&path, 3).exists());
+        assert!(checkpoint_path(&path, 300).exists());
+        assert!(actions_path(&path, 4).exists());
+        assert!(actions_path(&path, 5).exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -3,7 +3,14 @@ use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 
-use super::{RecordedActionWithMeta, RecordedInitialState};
+use super::{RecordedActionWithMeta, RecordedCheckpoint, RecordedInitialState};
+
+#[derive(Debug, Clone)]
+pub struct RecordedCheckpointInfo {
+    /// Id of the last action applied to the checkpointed state.
+    pub action_id: u64,
+    pub path: PathBuf,
+}
 
 pub struct StateWithInputActionsReader {
     dir: PathBuf,
@@ -26,10 +33,62 @@ impl StateWithInputActionsReader {
         Ok(RecordedInitialState::decode(&encoded)?)
     }
 
+    /// Checkpoints written by the rolling recorder, sorted by action id.
+    pub fn checkpoints(&self) -> std::io::Result<Vec<RecordedCheckpointInfo>> {
+        let mut checkpoints = fs::read_dir(&self.dir)?
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                let action_id = name
+                    .to_str()?
+                    .strip_prefix(super::CHECKPOINT_PREFIX)?
+                    .strip_suffix(super::CHECKPOINT_SUFFIX)?
+                    .parse()
+                    .ok()?;
+                Some(RecordedCheckpointInfo {
+                    action_id,
+                    path: entry.path(),
+                })
+            })
+            .collect::<Vec<_>>();
+        checkpoints.sort_by_key(|checkpoint| checkpoint.action_id);
+        Ok(checkpoints)
+    }
+
+    /// Latest checkpoint taken at or before the action with `action_id`.
+    pub fn nearest_checkpoint(
+        &self,
+        action_id: u64,
+    ) -> std::io::Result<Option<RecordedCheckpointInfo>> {
+        Ok(self
+            .checkpoints()?
+            .into_iter()
+            .take_while(|checkpoint| checkpoint.action_id <= action_id)
+            .last())
+    }
+
+    pub fn read_checkpoint(
+        &self,
+        checkpoint: &RecordedCheckpointInfo,
+    ) -> Result<RecordedCheckpoint<'static>, Box<dyn Error>> {
+        let encoded = fs::read(&checkpoint.path)?;
+        Ok(RecordedCheckpoint::decode(&encoded)?)
+    }
+
     pub fn read_actions(
         &self,
     ) -> impl Iterator<Item = (PathBuf, impl Iterator<Item = RecordedActionWithMeta<'_>>)> {
-        (1..).map_while(move |file_index| {
+        self.read_actions_from(1)
+    }
+
+    /// Same as [`StateWithInputActionsReader::read_actions`], but starts
+    /// with the actions file with `first_file_index`, e.g. the one
+    /// following a checkpoint.
+    pub fn read_actions_from(
+        &self,
+        first_file_index: usize,
+    ) -> impl Iterator<Item = (PathBuf, impl Iterator<Item = RecordedActionWithMeta<'_>>)> {
+        (first_file_index..).map_while(move |file_index| {
             let path = super::actions_path(&self.dir, file_index);
             let mut file = fs::File::open(&path).ok()?;
 
@@ -46,3 +105,37 @@ impl StateWithInputActionsReader {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::{actions_path, checkpoint_path};
+    use super::*;
+
+    #[test]
+    fn test_nearest_checkpoint() {
+        let dir = std::env::temp_dir().join(format!(
+            "openmina-replayer-test-checkpoints-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for action_id in [200, 100, 300] {
+            fs::write(checkpoint_path(&dir, action_id), b"").unwrap();
+        }
+        fs::write(actions_path(&dir, 1), b"").unwrap();
+
+        let reader = StateWithInputActionsReader::new(&dir);
+        let nearest = |action_id| {
+            reader
+                .nearest_checkpoint(action_id)
+                .unwrap()
+                .map(|checkpoint| checkpoint.action_id)
+        };
+        assert_eq!(nearest(50), None);
+        assert_eq!(nearest(100), Some(100));
+        assert_eq!(nearest(299), Some(200));
+        assert_eq!(nearest(1000), Some(300));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
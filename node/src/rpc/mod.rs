@@ -18,6 +18,7 @@ use openmina_core::block::AppliedBlock;
 use openmina_core::consensus::ConsensusConstants;
 use openmina_node_account::AccountPublicKey;
 use p2p::bootstrap::P2pNetworkKadBootstrapStats;
+use p2p::P2pNetworkPubsubTopicStats;
 pub use rpc_state::*;
 
 mod rpc_actions;
@@ -37,18 +38,21 @@ use openmina_core::snark::SnarkJobId;
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
+use crate::consensus::ConsensusShortRangeForkDecision;
 use crate::external_snark_worker::{
     ExternalSnarkWorkerError, ExternalSnarkWorkerWorkError, SnarkWorkSpecError,
 };
 use crate::p2p::connection::incoming::P2pConnectionIncomingInitOpts;
 use crate::p2p::connection::outgoing::P2pConnectionOutgoingInitOpts;
 use crate::p2p::PeerId;
-use crate::snark_pool::{JobCommitment, JobSummary};
+use crate::snark_pool::{JobCommitment, JobRank, JobSummary};
 use crate::stats::actions::{ActionStatsForBlock, ActionStatsSnapshot};
 use crate::stats::block_producer::{
-    BlockProductionAttempt, BlockProductionAttemptWonSlot, VrfEvaluatorStats,
+    BlockProductionAttempt, BlockProductionAttemptWonSlot, BlockProductionOutcomes,
+    VrfEvaluatorStats,
 };
 use crate::stats::sync::SyncStatsSnapshot;
+use crate::transition_frontier::sync::SyncPhase;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum RpcRequest {
@@ -57,6 +61,7 @@ pub enum RpcRequest {
     ActionStatsGet(ActionStatsQuery),
     SyncStatsGet(SyncStatsQuery),
     BlockProducerStatsGet,
+    MetricsGet,
     MessageProgressGet,
     PeersGet,
     P2pConnectionOutgoing(P2pConnectionOutgoingInitOpts),
@@ -79,21 +84,22 @@ pub enum RpcRequest {
     BestChain(MaxLength),
     ConsensusConstantsGet,
     TransactionStatusGet(MinaBaseUserCommandStableV2),
+    BlockGet(RpcBlockGetQuery),
 }
 
 pub type MaxLength = u32;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcInjectPayment {
-    fee: u64,
-    amount: u64,
-    to: AccountPublicKey,
-    from: AccountPublicKey,
-    memo: String,
-    nonce: u32,
-    valid_until: u32,
-    signature_field: BigInt,
-    signature_scalar: BigInt,
+    pub fee: u64,
+    pub amount: u64,
+    pub to: AccountPublicKey,
+    pub from: AccountPublicKey,
+    pub memo: String,
+    pub nonce: u32,
+    pub valid_until: u32,
+    pub signature_field: BigInt,
+    pub signature_scalar: BigInt,
 }
 // MinaBaseUserCommandStableV2
 impl TryFrom<RpcInjectPayment> for MinaBaseUserCommandStableV2 {
@@ -144,6 +150,12 @@ pub enum RpcScanStateSummaryGetQuery {
     ForBlockWithHeight(u32),
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RpcBlockGetQuery {
+    ForBlockWithHash(StateHash),
+    ForBlockWithHeight(u32),
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "kind")]
 pub enum ActionStatsResponse {
@@ -169,6 +181,10 @@ pub struct RpcPeerInfo {
     pub address: Option<String>,
     pub incoming: bool,
     pub time: u64,
+    /// Reputation score, zero is neutral and penalties make it negative.
+    pub score: f64,
+    /// Set if the peer is banned.
+    pub banned_until: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -249,6 +265,9 @@ pub struct RpcSnarkPoolJobSummary {
     pub id: SnarkJobId,
     pub commitment: Option<JobCommitment>,
     pub snark: Option<RpcSnarkPoolJobSnarkWork>,
+    /// Rank for our snarker, `None` if the node isn't a snarker
+    /// or it can't win the job.
+    pub rank: Option<JobRank>,
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -342,6 +361,7 @@ pub type RpcStatusGetResponse = Option<RpcNodeStatus>;
 pub type RpcActionStatsGetResponse = Option<ActionStatsResponse>;
 pub type RpcSyncStatsGetResponse = Option<Vec<SyncStatsSnapshot>>;
 pub type RpcBlockProducerStatsGetResponse = Option<RpcBlockProducerStats>;
+pub type RpcMetricsGetResponse = RpcMetrics;
 pub type RpcPeersGetResponse = Vec<RpcPeerInfo>;
 pub type RpcP2pConnectionOutgoingResponse = Result<(), String>;
 pub type RpcScanStateSummaryGetResponse = Result<RpcScanStateSummary, String>;
@@ -355,6 +375,29 @@ pub type RpcTransitionFrontierUserCommandsResponse = Vec<MinaBaseUserCommandStab
 pub type RpcBestChainResponse = Vec<AppliedBlock>;
 pub type RpcConsensusConstantsGetResponse = ConsensusConstants;
 pub type RpcTransactionStatusGetResponse = TransactionStatus;
+pub type RpcBlockGetResponse = Option<AppliedBlock>;
+
+/// Event pushed to the RPC subscribers whenever the best chain changes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum RpcSubscriptionEvent {
+    /// Block that became part of the best chain.
+    NewBlock(AppliedBlock),
+    /// Best tip was switched to a block which isn't a descendant of
+    /// the previous best tip.
+    ChainReorganization(RpcChainReorganization),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcChainReorganization {
+    pub old_best_tip: StateHash,
+    pub new_best_tip: StateHash,
+    /// Last block shared by both chains, `None` if the new chain
+    /// doesn't contain any block of the old one.
+    pub common_ancestor: Option<StateHash>,
+    /// Short range fork decision that lead to the new best tip, `None`
+    /// if it was decided by the long range fork rule.
+    pub decision: Option<ConsensusShortRangeForkDecision>,
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone, strum_macros::Display)]
 #[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
@@ -420,7 +463,7 @@ impl From<ValidCommandWithHash> for RpcTransactionInjectedCommand {
                         })
                     }
                     transaction_logic::signed_command::Body::StakeDelegation(_) => {
-                        todo!("inject stake delegation")
+                        Self::Delegation
                     }
                 }
             }
@@ -499,13 +542,57 @@ pub struct RpcBlockProducerStats {
     pub current_epoch: Option<u32>,
     pub epoch_start: Option<u32>,
     pub epoch_end: Option<u32>,
+    /// First of our producer keys, see `producers` for all of them.
     pub public_key: AccountPublicKey,
+    pub producers: Vec<RpcBlockProducerKeyStats>,
     pub attempts: Vec<BlockProductionAttempt>,
     pub future_won_slots: Vec<BlockProductionAttemptWonSlot>,
     pub current_epoch_vrf_stats: Option<VrfEvaluatorStats>,
     pub vrf_stats: BTreeMap<u32, VrfEvaluatorStats>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RpcBlockProducerKeyStats {
+    pub public_key: AccountPublicKey,
+    pub coinbase_receiver: AccountPublicKey,
+    pub future_won_slots: usize,
+    pub attempts: usize,
+    pub canonical: usize,
+    pub orphaned: usize,
+    pub discarded: usize,
+}
+
+/// Snapshot of node internals, exported as Prometheus metrics.
+#[derive(Serialize, Debug, Clone)]
+pub struct RpcMetrics {
+    pub best_tip_height: Option<u32>,
+    pub best_tip_global_slot: Option<u32>,
+    pub current_global_slot: Option<u32>,
+    pub sync_phase: SyncPhase,
+    pub peers: RpcMetricsPeers,
+    /// Gossip messages, by topic.
+    pub gossip: BTreeMap<String, P2pNetworkPubsubTopicStats>,
+    pub transaction_pool: RpcNodeStatusTransactionPool,
+    pub snark_pool: RpcNodeStatusSnarkPool,
+    pub ledger_read_pending: usize,
+    pub ledger_read_total_cost: usize,
+    pub ledger_write_pending: bool,
+    /// `None` when stats are disabled.
+    pub action_stats: Option<ActionStatsSnapshot>,
+    /// `None` when stats are disabled.
+    pub block_production: Option<BlockProductionOutcomes>,
+}
+
+/// Number of peers, by connection state.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct RpcMetricsPeers {
+    pub connecting: usize,
+    pub ready: usize,
+    pub disconnecting: usize,
+    pub disconnected: usize,
+    pub banned: usize,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RpcSnarkerConfig {
     pub public_key: NonZeroCurvePoint,
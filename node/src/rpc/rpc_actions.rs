@@ -15,8 +15,8 @@ use crate::p2p::connection::outgoing::{P2pConnectionOutgoingError, P2pConnection
 use crate::p2p::connection::P2pConnectionResponse;
 
 use super::{
-    ActionStatsQuery, RpcId, RpcScanStateSummaryGetQuery, RpcScanStateSummaryScanStateJob,
-    SyncStatsQuery,
+    ActionStatsQuery, RpcBlockGetQuery, RpcId, RpcScanStateSummaryGetQuery,
+    RpcScanStateSummaryScanStateJob, SyncStatsQuery,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
@@ -41,6 +41,9 @@ pub enum RpcAction {
     BlockProducerStatsGet {
         rpc_id: RpcId,
     },
+    MetricsGet {
+        rpc_id: RpcId,
+    },
 
     MessageProgressGet {
         rpc_id: RpcId,
@@ -202,6 +205,10 @@ pub enum RpcAction {
         rpc_id: RpcId,
         tx: MinaBaseUserCommandStableV2,
     },
+    BlockGet {
+        rpc_id: RpcId,
+        query: RpcBlockGetQuery,
+    },
 
     Finish {
         rpc_id: RpcId,
@@ -223,6 +230,7 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::ActionStatsGet { .. } => true,
             RpcAction::SyncStatsGet { .. } => true,
             RpcAction::BlockProducerStatsGet { .. } => true,
+            RpcAction::MetricsGet { .. } => true,
             RpcAction::MessageProgressGet { .. } => true,
             RpcAction::PeersGet { .. } => true,
             RpcAction::P2pConnectionOutgoingInit { rpc_id, .. } => {
@@ -297,6 +305,7 @@ impl redux::EnablingCondition<crate::State> for RpcAction {
             RpcAction::ConsensusConstantsGet { .. } => true,
             RpcAction::BestChain { .. } => state.transition_frontier.best_tip().is_some(),
             RpcAction::TransactionStatusGet { .. } => true,
+            RpcAction::BlockGet { .. } => true,
             RpcAction::LedgerAccountsGetInit { .. } => {
                 state.transition_frontier.best_tip().is_some()
             }
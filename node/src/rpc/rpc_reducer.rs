@@ -19,8 +19,9 @@ use crate::{
 };
 
 use super::{
-    PeerConnectionStatus, RpcAction, RpcPeerInfo, RpcRequest, RpcRequestExtraData, RpcRequestState,
-    RpcRequestStatus, RpcScanStateSummaryGetQuery, RpcSnarkerConfig, RpcState,
+    PeerConnectionStatus, RpcAction, RpcBlockGetQuery, RpcPeerInfo, RpcRequest,
+    RpcRequestExtraData, RpcRequestState, RpcRequestStatus, RpcScanStateSummaryGetQuery,
+    RpcSnarkerConfig, RpcState,
 };
 
 impl RpcState {
@@ -60,6 +61,10 @@ impl RpcState {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(RpcEffectfulAction::BlockProducerStatsGet { rpc_id: *rpc_id });
             }
+            RpcAction::MetricsGet { rpc_id } => {
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(RpcEffectfulAction::MetricsGet { rpc_id: *rpc_id });
+            }
             RpcAction::MessageProgressGet { rpc_id } => {
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(RpcEffectfulAction::MessageProgressGet { rpc_id: *rpc_id });
@@ -614,6 +619,24 @@ impl RpcState {
                     tx: tx.clone(),
                 });
             }
+            RpcAction::BlockGet { rpc_id, query } => {
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let best_chain = &state.transition_frontier.best_chain;
+
+                let block = match query {
+                    RpcBlockGetQuery::ForBlockWithHash(hash) => {
+                        best_chain.iter().rev().find(|b| b.hash() == hash)
+                    }
+                    RpcBlockGetQuery::ForBlockWithHeight(height) => {
+                        best_chain.iter().rev().find(|b| b.height() == *height)
+                    }
+                };
+
+                dispatcher.push(RpcEffectfulAction::BlockGet {
+                    rpc_id: *rpc_id,
+                    block: block.cloned(),
+                });
+            }
             RpcAction::P2pConnectionIncomingAnswerReady {
                 rpc_id,
                 answer,
@@ -632,6 +655,7 @@ impl RpcState {
 }
 
 pub fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
+    let now = state.time();
     state.p2p.ready().map_or_else(Vec::new, |p2p| {
         p2p.peers
             .iter()
@@ -668,8 +692,38 @@ pub fn collect_rpc_peers_info(state: &crate::State) -> Vec<RpcPeerInfo> {
                     best_tip_global_slot: best_tip.map(|bt| bt.global_slot_since_genesis()),
                     best_tip_timestamp: best_tip.map(|bt| bt.timestamp().into()),
                     time,
+                    score: p2p
+                        .reputation
+                        .peer_score(peer_id, now, &p2p.config.reputation),
+                    banned_until: p2p
+                        .reputation
+                        .banned_peers
+                        .get(peer_id)
+                        .map(|ban| ban.until.into()),
                 }
             })
+            .chain(
+                // Banned peers are removed from `peers` once disconnected.
+                p2p.reputation
+                    .banned_peers
+                    .iter()
+                    .filter(|(peer_id, _)| !p2p.peers.contains_key(peer_id))
+                    .map(|(peer_id, ban)| RpcPeerInfo {
+                        peer_id: *peer_id,
+                        connection_status: PeerConnectionStatus::Disconnected,
+                        address: None,
+                        incoming: false,
+                        best_tip: None,
+                        best_tip_height: None,
+                        best_tip_global_slot: None,
+                        best_tip_timestamp: None,
+                        time: ban.since.into(),
+                        score: p2p
+                            .reputation
+                            .peer_score(peer_id, now, &p2p.config.reputation),
+                        banned_until: Some(ban.until.into()),
+                    }),
+            )
             .collect()
     })
 }
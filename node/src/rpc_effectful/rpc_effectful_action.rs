@@ -3,7 +3,7 @@ use crate::{
     p2p::connection::P2pConnectionResponse,
     rpc::{
         discovery::RpcDiscoveryRoutingTable, AccountQuery, ActionStatsQuery, RpcBestChainResponse,
-        RpcPeerInfo, RpcScanStateSummaryScanStateJob, RpcSnarkerConfig,
+        RpcBlockGetResponse, RpcPeerInfo, RpcScanStateSummaryScanStateJob, RpcSnarkerConfig,
         RpcTransactionInjectFailure, RpcTransactionInjectRejected, RpcTransactionInjectSuccess,
         SyncStatsQuery,
     },
@@ -39,6 +39,9 @@ pub enum RpcEffectfulAction {
     BlockProducerStatsGet {
         rpc_id: RpcId,
     },
+    MetricsGet {
+        rpc_id: RpcId,
+    },
 
     MessageProgressGet {
         rpc_id: RpcId,
@@ -144,6 +147,10 @@ pub enum RpcEffectfulAction {
         rpc_id: RpcId,
         tx: MinaBaseUserCommandStableV2,
     },
+    BlockGet {
+        rpc_id: RpcId,
+        block: RpcBlockGetResponse,
+    },
 }
 
 impl redux::EnablingCondition<crate::State> for RpcEffectfulAction {
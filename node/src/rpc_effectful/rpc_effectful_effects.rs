@@ -7,16 +7,18 @@ use crate::{
     rpc::{
         AccountQuery, AccountSlim, ActionStatsQuery, ActionStatsResponse, CurrentMessageProgress,
         MessagesStats, RootLedgerSyncProgress, RootStagedLedgerSyncProgress, RpcAction,
-        RpcBlockProducerStats, RpcMessageProgressResponse, RpcNodeStatus,
-        RpcNodeStatusTransactionPool, RpcNodeStatusTransitionFrontier,
-        RpcNodeStatusTransitionFrontierBlockSummary, RpcNodeStatusTransitionFrontierSync,
-        RpcRequestExtraData, RpcScanStateSummary, RpcScanStateSummaryBlock,
-        RpcScanStateSummaryBlockTransaction, RpcScanStateSummaryBlockTransactionKind,
-        RpcScanStateSummaryScanStateJob, RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork,
-        RpcSnarkPoolJobSummary, RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse,
-        RpcTransactionInjectResponse, TransactionStatus,
+        RpcBlockProducerKeyStats, RpcBlockProducerStats, RpcMessageProgressResponse, RpcMetrics,
+        RpcMetricsPeers, RpcNodeStatus, RpcNodeStatusTransactionPool,
+        RpcNodeStatusTransitionFrontier, RpcNodeStatusTransitionFrontierBlockSummary,
+        RpcNodeStatusTransitionFrontierSync, RpcRequestExtraData, RpcScanStateSummary,
+        RpcScanStateSummaryBlock, RpcScanStateSummaryBlockTransaction,
+        RpcScanStateSummaryBlockTransactionKind, RpcScanStateSummaryScanStateJob,
+        RpcSnarkPoolJobFull, RpcSnarkPoolJobSnarkWork, RpcSnarkPoolJobSummary,
+        RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse, RpcTransactionInjectResponse,
+        TransactionStatus,
     },
     snark_pool::SnarkPoolAction,
+    stats::block_producer::{BlockProductionAttemptWonSlot, BlockProductionStatus},
     transition_frontier::sync::{
         ledger::TransitionFrontierSyncLedgerState, TransitionFrontierSyncState,
     },
@@ -133,7 +135,8 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
             let mut create_response = || {
                 let state = store.state.get();
                 let best_tip = state.transition_frontier.best_tip()?;
-                let public_key = state.block_producer.config()?.pub_key.clone();
+                let config = state.block_producer.config()?;
+                let public_key = config.producers.first()?.pub_key.clone();
                 let won_slots = &state.block_producer.vrf_evaluator()?.won_slots;
 
                 let stats = store.service.stats()?;
@@ -155,6 +158,51 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                     .and_then(|epoch| stats.block_producer().vrf_evaluator.get(&epoch).cloned());
                 let vrf_stats = stats.block_producer().vrf_evaluator.clone();
 
+                let future_won_slots = won_slots
+                    .range(future_slot..)
+                    .map(|(_, won_slot)| {
+                        let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
+                            won_slot,
+                            best_tip.genesis_timestamp(),
+                        );
+                        BlockProductionAttemptWonSlot::from(&won_slot)
+                    })
+                    .collect::<Vec<_>>();
+
+                let producers = config
+                    .producers
+                    .iter()
+                    .map(|producer| {
+                        let attempts = attempts
+                            .iter()
+                            .filter(|attempt| attempt.won_slot.producer == producer.pub_key);
+                        let count_status = |f: fn(&BlockProductionStatus) -> bool| {
+                            attempts
+                                .clone()
+                                .filter(|attempt| f(&attempt.status))
+                                .count()
+                        };
+                        RpcBlockProducerKeyStats {
+                            public_key: producer.pub_key.clone().into(),
+                            coinbase_receiver: producer.coinbase_receiver().clone().into(),
+                            future_won_slots: future_won_slots
+                                .iter()
+                                .filter(|won_slot| won_slot.producer == producer.pub_key)
+                                .count(),
+                            attempts: attempts.clone().count(),
+                            canonical: count_status(|status| {
+                                matches!(status, BlockProductionStatus::Canonical { .. })
+                            }),
+                            orphaned: count_status(|status| {
+                                matches!(status, BlockProductionStatus::Orphaned { .. })
+                            }),
+                            discarded: count_status(|status| {
+                                matches!(status, BlockProductionStatus::Discarded { .. })
+                            }),
+                        }
+                    })
+                    .collect();
+
                 Some(RpcBlockProducerStats {
                     current_time: meta.time(),
                     current_global_slot: cur_global_slot,
@@ -165,17 +213,9 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                     epoch_end: epoch_start
                         .map(|slot| slot.checked_add(slots_per_epoch).expect("overflow")),
                     public_key: public_key.into(),
+                    producers,
                     attempts,
-                    future_won_slots: won_slots
-                        .range(future_slot..)
-                        .map(|(_, won_slot)| {
-                            let won_slot = BlockProducerWonSlot::from_vrf_won_slot(
-                                won_slot,
-                                best_tip.genesis_timestamp(),
-                            );
-                            (&won_slot).into()
-                        })
-                        .collect(),
+                    future_won_slots,
                 })
             };
             let response = create_response();
@@ -183,6 +223,63 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 .service
                 .respond_block_producer_stats_get(rpc_id, response);
         }
+        RpcEffectfulAction::MetricsGet { rpc_id } => {
+            let state = store.state.get();
+            let best_tip = state.transition_frontier.best_tip();
+
+            let mut peers = RpcMetricsPeers::default();
+            let mut gossip = BTreeMap::new();
+            if let Some(p2p) = state.p2p.ready() {
+                for peer in p2p.peers.values() {
+                    let count = match &peer.status {
+                        p2p::P2pPeerStatus::Connecting(_) => &mut peers.connecting,
+                        p2p::P2pPeerStatus::Ready(_) => &mut peers.ready,
+                        p2p::P2pPeerStatus::Disconnecting { .. } => &mut peers.disconnecting,
+                        p2p::P2pPeerStatus::Disconnected { .. } => &mut peers.disconnected,
+                    };
+                    *count = count.saturating_add(1);
+                }
+                peers.banned = p2p.reputation.banned_peers.len();
+                gossip.clone_from(&p2p.network.scheduler.broadcast_state.topic_stats);
+            }
+
+            let mut metrics = RpcMetrics {
+                best_tip_height: best_tip.map(|b| b.height()),
+                best_tip_global_slot: best_tip.map(|b| b.global_slot()),
+                current_global_slot: state.cur_global_slot(),
+                sync_phase: state.transition_frontier.sync.sync_phase(),
+                peers,
+                gossip,
+                transaction_pool: RpcNodeStatusTransactionPool {
+                    transactions: state.transaction_pool.size(),
+                    transactions_for_propagation: state.transaction_pool.for_propagation_size(),
+                    transaction_candidates: state.transaction_pool.candidates.transactions_count(),
+                },
+                snark_pool: state.snark_pool.jobs_iter().fold(
+                    Default::default(),
+                    |mut acc, job| {
+                        if job.snark.is_some() {
+                            acc.snarks = acc.snarks.saturating_add(1);
+                        }
+                        acc.total_jobs = acc.total_jobs.saturating_add(1);
+                        acc
+                    },
+                ),
+                ledger_read_pending: state.ledger.read.pending_count(),
+                ledger_read_total_cost: state.ledger.read.total_cost(),
+                ledger_write_pending: state.ledger.write.request().is_some(),
+                action_stats: None,
+                block_production: None,
+            };
+            if let Some(stats) = store.service.stats() {
+                metrics.action_stats = Some(stats.collect_action_stats_since_start());
+                metrics.block_production = Some(stats.block_producer().outcomes.clone());
+            }
+            respond_or_log!(
+                store.service().respond_metrics_get(rpc_id, metrics),
+                meta.time()
+            );
+        }
         RpcEffectfulAction::MessageProgressGet { rpc_id } => {
             // TODO: move to stats
             let p2p = p2p_ready!(store.state().p2p, meta.time());
@@ -439,8 +536,13 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
             let _ = store.service.respond_scan_state_summary_get(rpc_id, res);
         }
         RpcEffectfulAction::SnarkPoolAvailableJobsGet { rpc_id } => {
-            let resp = store
-                .state()
+            let state = store.state();
+            let snarker = state
+                .config
+                .snarker
+                .as_ref()
+                .map(|config| (&config.fee, config.public_key.clone().into()));
+            let resp = state
                 .snark_pool
                 .range(..)
                 .map(|(_, job)| RpcSnarkPoolJobSummary {
@@ -453,6 +555,9 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                         received_t: snark.received_t,
                         sender: snark.sender,
                     }),
+                    rank: snarker
+                        .as_ref()
+                        .and_then(|(fee, public_key)| job.rank(fee, public_key)),
                 })
                 .collect::<Vec<_>>();
             let _ = store.service().respond_snark_pool_get(rpc_id, resp);
@@ -718,6 +823,12 @@ pub fn rpc_effects<S: Service>(store: &mut Store<S>, action: ActionWithMeta<RpcE
                 meta.time()
             )
         }
+        RpcEffectfulAction::BlockGet { rpc_id, block } => {
+            respond_or_log!(
+                store.service().respond_block_get(rpc_id, block),
+                meta.time()
+            )
+        }
         RpcEffectfulAction::ConsensusConstantsGet { rpc_id, response } => {
             respond_or_log!(
                 store
@@ -1,13 +1,14 @@
 use crate::{
     p2p::connection::P2pConnectionResponse,
     rpc::{
-        RpcActionStatsGetResponse, RpcBestChainResponse, RpcBlockProducerStatsGetResponse,
-        RpcDiscoveryBoostrapStatsResponse, RpcDiscoveryRoutingTableResponse,
-        RpcHealthCheckResponse, RpcId, RpcLedgerAccountsResponse, RpcLedgerSlimAccountsResponse,
-        RpcMessageProgressResponse, RpcP2pConnectionOutgoingResponse, RpcPeersGetResponse,
-        RpcReadinessCheckResponse, RpcScanStateSummaryGetResponse, RpcSnarkPoolGetResponse,
-        RpcSnarkPoolJobGetResponse, RpcSnarkerConfigGetResponse, RpcSnarkerJobCommitResponse,
-        RpcSnarkerJobSpecResponse, RpcSnarkerWorkersResponse, RpcStatusGetResponse,
+        RpcActionStatsGetResponse, RpcBestChainResponse, RpcBlockGetResponse,
+        RpcBlockProducerStatsGetResponse, RpcDiscoveryBoostrapStatsResponse,
+        RpcDiscoveryRoutingTableResponse, RpcHealthCheckResponse, RpcId, RpcLedgerAccountsResponse,
+        RpcLedgerSlimAccountsResponse, RpcMessageProgressResponse, RpcMetricsGetResponse,
+        RpcP2pConnectionOutgoingResponse, RpcPeersGetResponse, RpcReadinessCheckResponse,
+        RpcScanStateSummaryGetResponse, RpcSnarkPoolGetResponse, RpcSnarkPoolJobGetResponse,
+        RpcSnarkerConfigGetResponse, RpcSnarkerJobCommitResponse, RpcSnarkerJobSpecResponse,
+        RpcSnarkerWorkersResponse, RpcStatusGetResponse, RpcSubscriptionEvent,
         RpcSyncStatsGetResponse, RpcTransactionInjectResponse, RpcTransactionPoolResponse,
         RpcTransactionStatusGetResponse, RpcTransitionFrontierUserCommandsResponse,
     },
@@ -67,6 +68,11 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcBlockProducerStatsGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_metrics_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcMetricsGetResponse,
+    ) -> Result<(), RespondError>;
     fn respond_message_progress_stats_get(
         &mut self,
         rpc_id: RpcId,
@@ -187,4 +193,13 @@ pub trait RpcService {
         rpc_id: RpcId,
         response: RpcTransactionStatusGetResponse,
     ) -> Result<(), RespondError>;
+    fn respond_block_get(
+        &mut self,
+        rpc_id: RpcId,
+        response: RpcBlockGetResponse,
+    ) -> Result<(), RespondError>;
+
+    /// Publish the event to the subscribers. Must not block, subscribers
+    /// that can't keep up are expected to be dropped.
+    fn publish_subscription_event(&mut self, event: RpcSubscriptionEvent);
 }
@@ -9,6 +9,7 @@ pub use crate::rpc_effectful::RpcService;
 pub use crate::snark::block_verify_effectful::SnarkBlockVerifyService;
 pub use crate::snark::work_verify_effectful::SnarkWorkVerifyService;
 pub use crate::snark_pool::SnarkPoolService;
+pub use crate::transition_frontier::archive::TransitionFrontierArchiveService;
 pub use crate::transition_frontier::genesis_effectful::TransitionFrontierGenesisService;
 pub use crate::transition_frontier::sync::ledger::snarked::TransitionFrontierSyncLedgerSnarkedService;
 pub use redux::TimeService;
@@ -25,6 +26,7 @@ pub trait Service:
     + LedgerService
     + TransitionFrontierGenesisService
     + TransitionFrontierSyncLedgerSnarkedService
+    + TransitionFrontierArchiveService
     + SnarkPoolService
     + SnarkUserCommandVerifyService
     + BlockProducerVrfEvaluatorService
@@ -4,7 +4,7 @@ use crate::{p2p_ready, SnarkPoolAction};
 use openmina_core::snark::Snark;
 use p2p::{
     channels::rpc::{P2pChannelsRpcAction, P2pRpcId, P2pRpcRequest},
-    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    reputation::{P2pPenaltyReason, P2pReputationAction},
     PeerId,
 };
 use snark::{work_verify::SnarkWorkVerifyAction, work_verify_effectful::SnarkWorkVerifyId};
@@ -147,12 +147,10 @@ impl SnarkPoolCandidatesState {
             SnarkPoolCandidateAction::WorkVerifyError { peer_id, verify_id } => {
                 state.verify_result(meta.time(), peer_id, *verify_id, Err(()));
 
-                // TODO(binier): blacklist peer
                 let dispatcher = state_context.into_dispatcher();
-                let peer_id = *peer_id;
-                dispatcher.push(P2pDisconnectionAction::Init {
-                    peer_id,
-                    reason: P2pDisconnectionReason::SnarkPoolVerifyError,
+                dispatcher.push(P2pReputationAction::Penalize {
+                    peer_id: *peer_id,
+                    reason: P2pPenaltyReason::InvalidSnark,
                 });
             }
             SnarkPoolCandidateAction::WorkVerifySuccess {
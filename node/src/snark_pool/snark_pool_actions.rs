@@ -7,6 +7,7 @@ use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
 use crate::p2p::PeerId;
+use crate::SnarkerStrategy;
 
 use super::candidate::SnarkPoolCandidateAction;
 use super::SnarkWork;
@@ -62,7 +63,21 @@ impl redux::EnablingCondition<crate::State> for SnarkPoolAction {
                 .map_or(false, |v| v.auto_commit),
             SnarkPoolAction::CommitmentCreateMany { .. } => state.config.snarker.is_some(),
             SnarkPoolAction::CommitmentCreate { job_id } => {
-                state.config.snarker.is_some() && state.snark_pool.should_create_commitment(job_id)
+                state
+                    .config
+                    .snarker
+                    .as_ref()
+                    .map_or(false, |config| match config.strategy {
+                        // Allowed to undercut competing commitments and snarks.
+                        SnarkerStrategy::FeeAware => state.snark_pool.can_win_job(
+                            job_id,
+                            &config.fee,
+                            &config.public_key.clone().into(),
+                        ),
+                        SnarkerStrategy::Sequential | SnarkerStrategy::Random => {
+                            state.snark_pool.should_create_commitment(job_id)
+                        }
+                    })
             }
             SnarkPoolAction::CommitmentAdd { commitment, .. } => state
                 .snark_pool
@@ -87,11 +87,11 @@ impl SnarkPoolState {
                 let available_workers = global_state.external_snark_worker.available();
 
                 if available_workers > 0 {
-                    let jobs = global_state
-                        .snark_pool
-                        .available_jobs_with_highest_priority(available_workers);
                     match snarker_config.strategy {
                         SnarkerStrategy::Sequential => {
+                            let jobs = global_state
+                                .snark_pool
+                                .available_jobs_with_highest_priority(available_workers);
                             let job_ids = jobs
                                 .into_iter()
                                 .map(|job| job.id.clone())
@@ -113,6 +113,19 @@ impl SnarkPoolState {
                                 ),
                             });
                         }
+                        SnarkerStrategy::FeeAware => {
+                            let job_ids = global_state
+                                .snark_pool
+                                .ranked_jobs(
+                                    &snarker_config.fee,
+                                    &snarker_config.public_key.clone().into(),
+                                )
+                                .into_iter()
+                                .map(|(job, _)| job.id.clone())
+                                .take(available_workers)
+                                .collect();
+                            dispatcher.push(SnarkPoolAction::CommitmentCreateMany { job_ids });
+                        }
                     }
                 };
             }
@@ -1,8 +1,10 @@
+use std::borrow::Cow;
 use std::time::Duration;
-use std::{fmt, ops::RangeBounds};
+use std::{cmp::Ordering, fmt, ops::RangeBounds};
 
 use ledger::scan_state::scan_state::{transaction_snark::OneOrTwo, AvailableJobMessage};
-use openmina_core::snark::{Snark, SnarkInfo, SnarkJobCommitment, SnarkJobId};
+use mina_p2p_messages::v2::{CurrencyFeeStableV1, NonZeroCurvePoint};
+use openmina_core::snark::{Snark, SnarkCmp, SnarkInfo, SnarkJobCommitment, SnarkJobId};
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
@@ -45,6 +47,25 @@ pub struct SnarkWork {
     pub sender: PeerId,
 }
 
+/// How worthwhile a job is for a snarker offering a particular fee.
+/// Lower rank is better.
+///
+/// Uncommitted jobs come first, ordered by their position in the scan
+/// state. Jobs which were already committed to or completed by other
+/// snarkers, but with a higher fee, follow, ordered by how much the
+/// snarker would undercut them.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JobRank {
+    /// Whether another snarker has already committed to or completed the job.
+    pub contested: bool,
+    /// Difference (in nanomina) between the lowest fee currently offered
+    /// for the job and our fee. Zero if the job isn't contested.
+    pub fee_margin: u64,
+    /// Position of the job in the scan state. Lower means the proof is
+    /// needed sooner.
+    pub order: usize,
+}
+
 /// Whether the job is a merge proof job, or a transaction proof job, with particular number of account updates.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum JobSummary {
@@ -168,6 +189,30 @@ impl SnarkPoolState {
             })
     }
 
+    /// Jobs which the snarker offering `fee` can still win, best first.
+    pub fn ranked_jobs(
+        &self,
+        fee: &CurrencyFeeStableV1,
+        snarker: &NonZeroCurvePoint,
+    ) -> Vec<(&JobState, JobRank)> {
+        let mut jobs = self
+            .jobs_iter()
+            .filter_map(|job| Some((job, job.rank(fee, snarker)?)))
+            .collect::<Vec<_>>();
+        jobs.sort_by_key(|(_, rank)| *rank);
+        jobs
+    }
+
+    pub fn can_win_job(
+        &self,
+        job_id: &SnarkJobId,
+        fee: &CurrencyFeeStableV1,
+        snarker: &NonZeroCurvePoint,
+    ) -> bool {
+        self.get(job_id)
+            .map_or(false, |job| job.rank(fee, snarker).is_some())
+    }
+
     pub fn completed_snarks_iter(&self) -> impl '_ + Iterator<Item = &'_ Snark> {
         self.jobs_iter()
             .filter_map(|job| job.snark.as_ref())
@@ -234,6 +279,41 @@ impl JobState {
         self.commitment.is_none() && self.snark.is_none()
     }
 
+    /// Rank of the job for a snarker offering `fee`. `None` if the snarker
+    /// can't win the job, because it has already committed to it, or someone
+    /// else offered a lower fee.
+    pub fn rank(&self, fee: &CurrencyFeeStableV1, snarker: &NonZeroCurvePoint) -> Option<JobRank> {
+        let ours = SnarkCmp {
+            job_id: Cow::Borrowed(&self.id),
+            fee: fee.0.as_u64(),
+            prover: snarker,
+        };
+        let commitment = self.commitment_msg();
+        let snark = self.snark.as_ref().map(|v| &v.work);
+
+        if commitment.map_or(false, |v| &v.snarker == snarker)
+            || snark.map_or(false, |v| &v.snarker == snarker)
+        {
+            return None;
+        }
+        if commitment.map_or(false, |v| SnarkCmp::from(v) >= ours)
+            || snark.map_or(false, |v| SnarkCmp::from(v) >= ours)
+        {
+            return None;
+        }
+
+        let competing_fee = commitment
+            .map(|v| v.fee.0.as_u64())
+            .into_iter()
+            .chain(snark.map(|v| v.fee.0.as_u64()))
+            .min();
+        Some(JobRank {
+            contested: competing_fee.is_some(),
+            fee_margin: competing_fee.map_or(0, |competing| competing.saturating_sub(ours.fee)),
+            order: self.order,
+        })
+    }
+
     pub fn commitment_msg(&self) -> Option<&SnarkJobCommitment> {
         self.commitment.as_ref().map(|v| &v.commitment)
     }
@@ -285,6 +365,21 @@ impl AsRef<SnarkJobId> for JobState {
     }
 }
 
+impl Ord for JobRank {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.contested
+            .cmp(&other.contested)
+            .then_with(|| self.fee_margin.cmp(&other.fee_margin).reverse())
+            .then_with(|| self.order.cmp(&other.order))
+    }
+}
+
+impl PartialOrd for JobRank {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl JobSummary {
     pub fn estimated_duration(&self) -> Duration {
         const BASE: Duration = Duration::from_secs(10);
@@ -294,3 +389,195 @@ impl JobSummary {
         BASE.saturating_mul(*n as u32).saturating_add(MAX_LATENCY)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mina_p2p_messages::{
+        binprot::BinProtRead,
+        gossip::GossipNetMessageV2,
+        v2::{
+            MinaBaseSokMessageStableV1, NetworkPoolSnarkPoolDiffVersionedStableV2,
+            TransactionSnarkScanStateLedgerProofWithSokMessageStableV2,
+            TransactionSnarkWorkTStableV2Proofs,
+        },
+    };
+
+    use super::*;
+    use crate::account::AccountSecretKey;
+    use crate::p2p::identity::SecretKey;
+
+    fn test_snark() -> Snark {
+        let mut bytes: &[u8] =
+            include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/snark_pool_diff.bin");
+        let GossipNetMessageV2::SnarkPoolDiff {
+            message: NetworkPoolSnarkPoolDiffVersionedStableV2::AddSolvedWork(work),
+            ..
+        } = GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+        else {
+            panic!("expected solved work");
+        };
+        work.1.into()
+    }
+
+    /// Job for the work of `snark`, with nothing committed or completed.
+    fn test_job(snark: &Snark, order: usize) -> JobState {
+        let proof = match &*snark.proofs {
+            TransactionSnarkWorkTStableV2Proofs::One(proof)
+            | TransactionSnarkWorkTStableV2Proofs::Two((proof, _)) => proof.clone(),
+        };
+        let sok_message = MinaBaseSokMessageStableV1 {
+            fee: snark.fee.clone(),
+            prover: snark.snarker.clone(),
+        };
+        let merge = TransactionSnarkScanStateLedgerProofWithSokMessageStableV2(proof, sok_message);
+        JobState {
+            time: Timestamp::ZERO,
+            id: snark.job_id(),
+            job: OneOrTwo::One(AvailableJobMessage::Merge {
+                left: merge.clone(),
+                right: merge,
+            }),
+            commitment: None,
+            snark: None,
+            order,
+        }
+    }
+
+    fn fee(fee: u64) -> CurrencyFeeStableV1 {
+        CurrencyFeeStableV1(fee.into())
+    }
+
+    fn snarker() -> NonZeroCurvePoint {
+        AccountSecretKey::rand().public_key().into()
+    }
+
+    fn commit(job: &mut JobState, fee: u64, snarker: &NonZeroCurvePoint) {
+        job.commitment = Some(JobCommitment {
+            commitment: SnarkJobCommitment::new(0, job.id.clone(), self::fee(fee), snarker.clone()),
+            received_t: Timestamp::ZERO,
+            sender: SecretKey::rand().public_key().peer_id(),
+        });
+    }
+
+    fn complete(job: &mut JobState, snark: &Snark, fee: u64, snarker: &NonZeroCurvePoint) {
+        job.snark = Some(SnarkWork {
+            work: Snark {
+                snarker: snarker.clone(),
+                fee: self::fee(fee),
+                proofs: Arc::clone(&snark.proofs),
+            },
+            received_t: Timestamp::ZERO,
+            sender: SecretKey::rand().public_key().peer_id(),
+        });
+    }
+
+    #[test]
+    fn job_rank_excludes_own_work() {
+        let snark = test_snark();
+        let me = snarker();
+        let job = test_job(&snark, 3);
+        assert_eq!(
+            job.rank(&fee(10), &me),
+            Some(JobRank {
+                contested: false,
+                fee_margin: 0,
+                order: 3,
+            })
+        );
+
+        // even if we would undercut our own commitment
+        let mut committed = job.clone();
+        commit(&mut committed, 10, &me);
+        assert_eq!(committed.rank(&fee(5), &me), None);
+        assert!(!committed.is_available());
+
+        let mut completed = job.clone();
+        complete(&mut completed, &snark, 10, &me);
+        assert_eq!(completed.rank(&fee(5), &me), None);
+
+        // someone else can still undercut us
+        assert!(committed.rank(&fee(5), &snarker()).is_some());
+        assert!(completed.rank(&fee(5), &snarker()).is_some());
+    }
+
+    #[test]
+    fn job_rank_undercuts_competing_fee() {
+        let snark = test_snark();
+        let (me, other) = (snarker(), snarker());
+        let mut job = test_job(&snark, 1);
+        commit(&mut job, 10, &other);
+
+        assert_eq!(job.rank(&fee(11), &me), None);
+        assert_eq!(
+            job.rank(&fee(4), &me),
+            Some(JobRank {
+                contested: true,
+                fee_margin: 6,
+                order: 1,
+            })
+        );
+
+        // equal fees are decided by the tie breaker, only one side wins
+        let mut theirs = test_job(&snark, 1);
+        commit(&mut theirs, 10, &me);
+        assert_ne!(
+            job.rank(&fee(10), &me).is_some(),
+            theirs.rank(&fee(10), &other).is_some()
+        );
+        if let Some(rank) = job.rank(&fee(10), &me) {
+            assert_eq!(rank.fee_margin, 0);
+        }
+
+        // margin is computed against the lowest competing fee
+        complete(&mut job, &snark, 8, &snarker());
+        assert_eq!(job.rank(&fee(9), &me), None);
+        assert_eq!(job.rank(&fee(4), &me).map(|rank| rank.fee_margin), Some(4));
+
+        let bidder = snarker();
+        let mut ranks = [(5, 2), (8, 0)]
+            .into_iter()
+            .map(|(competing_fee, order)| {
+                let mut job = test_job(&snark, order);
+                commit(&mut job, competing_fee, &other);
+                job.rank(&fee(1), &bidder).unwrap()
+            })
+            .chain(test_job(&snark, 7).rank(&fee(1), &bidder))
+            .collect::<Vec<_>>();
+        ranks.sort();
+        assert_eq!(
+            ranks
+                .iter()
+                .map(|rank| (rank.contested, rank.fee_margin, rank.order))
+                .collect::<Vec<_>>(),
+            [(false, 0, 7), (true, 7, 0), (true, 4, 2)]
+        );
+    }
+
+    #[test]
+    fn job_rank_ordering() {
+        let rank = |contested, fee_margin, order| JobRank {
+            contested,
+            fee_margin,
+            order,
+        };
+        let mut ranks = vec![
+            rank(true, 10, 0),
+            rank(false, 0, 5),
+            rank(true, 50, 7),
+            rank(false, 0, 1),
+        ];
+        ranks.sort();
+
+        assert_eq!(
+            ranks,
+            vec![
+                rank(false, 0, 1),
+                rank(false, 0, 5),
+                rank(true, 50, 7),
+                rank(true, 10, 0),
+            ]
+        );
+    }
+}
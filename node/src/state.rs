@@ -46,6 +46,7 @@ use crate::transaction_pool::candidate::{
 };
 use crate::transaction_pool::TransactionPoolState;
 use crate::transition_frontier::genesis::TransitionFrontierGenesisState;
+use crate::transition_frontier::persistence::TransitionFrontierPersistenceState;
 use crate::transition_frontier::sync::ledger::snarked::TransitionFrontierSyncLedgerSnarkedState;
 use crate::transition_frontier::sync::ledger::staged::TransitionFrontierSyncLedgerStagedState;
 use crate::transition_frontier::sync::ledger::TransitionFrontierSyncLedgerState;
@@ -122,6 +123,11 @@ impl_substate_access!(
     transition_frontier.genesis
 );
 impl_substate_access!(State, TransitionFrontierSyncState, transition_frontier.sync);
+impl_substate_access!(
+    State,
+    TransitionFrontierPersistenceState,
+    transition_frontier.persistence
+);
 impl_substate_access!(State, SnarkPoolState, snark_pool);
 impl_substate_access!(State, SnarkPoolCandidatesState, snark_pool.candidates);
 impl_substate_access!(State, ExternalSnarkWorkers, external_snark_worker);
@@ -89,6 +89,15 @@ impl ActionStatsSnapshot {
             .expect("kind_i out of bounds")
             .add(duration);
     }
+
+    /// Stats of each action kind, except `None`.
+    pub fn iter(&self) -> impl Iterator<Item = (ActionKind, &ActionStatsForRanges)> {
+        self.0
+            .iter()
+            .enumerate()
+            .skip(1) // skip `None` action
+            .filter_map(|(i, v)| Some((ActionKind::try_from(i as u16).ok()?, v)))
+    }
 }
 
 impl Serialize for ActionStatsSnapshot {
@@ -176,6 +185,22 @@ pub struct ActionStatsForRanges {
 }
 
 impl ActionStatsForRanges {
+    /// Ranges along with their upper bound in nanoseconds, `None` for the
+    /// last one which has no upper bound.
+    pub fn ranges(&self) -> [(Option<u64>, &ActionStatsForRange); 9] {
+        [
+            (Some(1_000), &self.under_1_us),
+            (Some(10_000), &self.under_10_us),
+            (Some(50_000), &self.under_50_us),
+            (Some(100_000), &self.under_100_us),
+            (Some(500_000), &self.under_500_us),
+            (Some(1_000_000), &self.under_1_ms),
+            (Some(5_000_000), &self.under_5_ms),
+            (Some(50_000_000), &self.under_50_ms),
+            (None, &self.above_50_ms),
+        ]
+    }
+
     pub fn add(&mut self, duration: u64) {
         let stats = if duration <= 1_000 {
             &mut self.under_1_us
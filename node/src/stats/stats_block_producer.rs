@@ -16,6 +16,17 @@ const MAX_HISTORY: usize = 2048;
 pub struct BlockProducerStats {
     pub(super) attempts: VecDeque<BlockProductionAttempt>,
     pub vrf_evaluator: BTreeMap<u32, VrfEvaluatorStats>,
+    /// Outcomes of all attempts since the start of the node, unlike
+    /// `attempts` which only keeps the latest ones.
+    pub outcomes: BlockProductionOutcomes,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct BlockProductionOutcomes {
+    pub scheduled: u64,
+    pub committed: u64,
+    pub orphaned: u64,
+    pub discarded: u64,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -32,6 +43,7 @@ pub struct BlockProductionAttemptWonSlot {
     pub slot_time: redux::Timestamp,
     pub global_slot: u32,
     pub epoch: u32,
+    pub producer: v2::NonZeroCurvePoint,
     pub delegator: (v2::NonZeroCurvePoint, AccountIndex),
     pub value_with_threshold: Option<(f64, f64)>,
 }
@@ -153,6 +165,9 @@ impl BlockProducerStats {
                         };
                     }
                     Some(b) => {
+                        if !matches!(attempt.status, BlockProductionStatus::Orphaned { .. }) {
+                            self.outcomes.orphaned = self.outcomes.orphaned.saturating_add(1);
+                        }
                         attempt.status = BlockProductionStatus::Orphaned {
                             orphaned_by: b.hash().clone(),
                         };
@@ -190,6 +205,7 @@ impl BlockProducerStats {
         if self.attempts.len() >= MAX_HISTORY {
             self.attempts.pop_front();
         }
+        self.outcomes.scheduled = self.outcomes.scheduled.saturating_add(1);
         self.attempts.push_back(BlockProductionAttempt {
             won_slot: won_slot.into(),
             block: None,
@@ -311,17 +327,23 @@ impl BlockProducerStats {
             return;
         }
 
-        self.update("committed", move |attempt| match attempt.status {
+        let mut is_committed = false;
+        self.update("committed", |attempt| match attempt.status {
             BlockProductionStatus::BlockApplySuccess => {
                 attempt.status = BlockProductionStatus::Committed;
                 attempt.times.committed = Some(time);
+                is_committed = true;
                 true
             }
             _ => false,
         });
+        if is_committed {
+            self.outcomes.committed = self.outcomes.committed.saturating_add(1);
+        }
     }
 
     pub fn discarded(&mut self, time: redux::Timestamp, reason: BlockProducerWonSlotDiscardReason) {
+        self.outcomes.discarded = self.outcomes.discarded.saturating_add(1);
         self.update("discarded", move |attempt| {
             attempt.status = BlockProductionStatus::Discarded {
                 discard_reason: reason,
@@ -375,6 +397,7 @@ impl From<&BlockProducerWonSlot> for BlockProductionAttemptWonSlot {
             slot_time: won_slot.slot_time,
             global_slot: won_slot.global_slot(),
             epoch: won_slot.epoch(),
+            producer: won_slot.producer.clone(),
             delegator: won_slot.delegator.clone(),
             value_with_threshold: won_slot.value_with_threshold,
         }
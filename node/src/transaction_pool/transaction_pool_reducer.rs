@@ -21,7 +21,7 @@ use crate::{BlockProducerAction, RpcAction};
 
 use super::{
     PendingId, TransactionPoolAction, TransactionPoolActionWithMetaRef,
-    TransactionPoolEffectfulAction, TransactionPoolState, TransactionState,
+    TransactionPoolEffectfulAction, TransactionPoolState,
 };
 
 impl TransactionPoolState {
@@ -266,20 +266,21 @@ impl TransactionPoolState {
                         is_sender_local,
                     ) {
                     Ok((ApplyDecision::Accept, accepted, rejected, dropped)) => {
-                        for hash in dropped {
-                            substate.dpool.remove(&hash);
-                        }
-                        for tx in &accepted {
-                            substate.dpool.insert(TransactionState {
-                                time: meta.time(),
-                                hash: tx.hash.clone(),
-                            });
-                        }
-                        let rpc_action =
-                            from_rpc.map(|rpc_id| RpcAction::TransactionInjectSuccess {
-                                rpc_id,
-                                response: accepted.clone(),
-                            });
+                        substate.update_propagation(meta.time(), &accepted, dropped);
+                        // Let the caller know why nothing from the diff made it into the pool.
+                        let rpc_action = from_rpc.map(|rpc_id| {
+                            if accepted.is_empty() && !rejected.is_empty() {
+                                RpcAction::TransactionInjectRejected {
+                                    rpc_id,
+                                    response: rejected.clone(),
+                                }
+                            } else {
+                                RpcAction::TransactionInjectSuccess {
+                                    rpc_id,
+                                    response: accepted.clone(),
+                                }
+                            }
+                        });
                         (rpc_action, true, accepted, rejected)
                     }
                     Ok((ApplyDecision::Reject, accepted, rejected, _)) => {
@@ -371,6 +372,9 @@ impl TransactionPoolState {
                         e
                     );
                 }
+                // Commands included into the new best tip are no longer in
+                // the pool, so stop propagating them.
+                substate.retain_pooled_for_propagation();
             }
             TransactionPoolAction::Rebroadcast { accepted, rejected } => {
                 let rejected = rejected.iter().map(|(cmd, _)| cmd.data.forget_check());
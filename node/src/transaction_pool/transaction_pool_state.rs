@@ -76,6 +76,38 @@ impl TransactionPoolState {
         self.dpool.len()
     }
 
+    /// Transactions which are being propagated to peers.
+    pub fn for_propagation_iter(&self) -> impl Iterator<Item = &TransactionState> {
+        self.dpool.states()
+    }
+
+    /// Starts propagating `accepted` commands, then stops propagating the
+    /// `dropped` ones. Accepted commands might get dropped too if the pool
+    /// is full, so the order matters.
+    pub(super) fn update_propagation(
+        &mut self,
+        time: redux::Timestamp,
+        accepted: &[ValidCommandWithHash],
+        dropped: impl IntoIterator<Item = TransactionHash>,
+    ) {
+        for tx in accepted {
+            self.dpool.insert(TransactionState {
+                time,
+                hash: tx.hash.clone(),
+            });
+        }
+        for hash in dropped {
+            self.dpool.remove(&hash);
+        }
+    }
+
+    /// Stops propagating commands which are no longer in the pool, e.g.
+    /// after they got included into the new best tip.
+    pub(super) fn retain_pooled_for_propagation(&mut self) {
+        let pool = &self.pool;
+        self.dpool.retain(|hash, _| pool.pool.get(hash).is_some());
+    }
+
     pub fn contains(&self, hash: &TransactionHash) -> bool {
         self.get(hash).is_some()
     }
@@ -136,7 +168,177 @@ mod tests {
     use super::super::TransactionPoolActionWithMeta;
     use super::*;
     use crate::State;
-    use redux::Dispatcher;
+    use ledger::{
+        scan_state::{
+            currency::{Balance, Fee},
+            transaction_logic::{
+                signed_command::{Body, PaymentPayload, SignedCommand, SignedCommandPayload},
+                valid, Memo, TransactionStatus, WithStatus,
+            },
+        },
+        transaction_pool::{diff, transaction_hash},
+        Account, TokenId,
+    };
+    use mina_signer::{Keypair, Signature};
+    use openmina_core::constants::constraint_constants;
+    use redux::{Dispatcher, Timestamp};
+    use std::collections::BTreeSet;
+
+    fn test_pool_state(pool_max_size: usize) -> TransactionPoolState {
+        let constants = ConsensusConstants::create(constraint_constants(), &v2::PROTOCOL_CONSTANTS);
+        let config = Config {
+            trust_system: (),
+            pool_max_size,
+            max_per_sender: 10,
+            replace_fee: Fee::from_u64(5),
+            slot_tx_end: None,
+        };
+        TransactionPoolState::new(config, &constants)
+    }
+
+    fn test_account(keypair: &Keypair, nonce: u32) -> (AccountId, Account) {
+        let account_id = AccountId::new(keypair.public.into_compressed(), TokenId::default());
+        let mut account =
+            Account::create_with(account_id.clone(), Balance::from_u64(1_000_000_000_000));
+        account.nonce = Nonce::from_u32(nonce);
+        (account_id, account)
+    }
+
+    fn payment(sender: &Keypair, nonce: u32, fee: u64) -> ValidCommandWithHash {
+        let sender_pk = sender.public.into_compressed();
+        let payload = SignedCommandPayload::create(
+            Fee::from_u64(fee),
+            sender_pk.clone(),
+            Nonce::from_u32(nonce),
+            None,
+            Memo::empty(),
+            Body::Payment(PaymentPayload {
+                receiver_pk: sender_pk.clone(),
+                amount: Amount::from_u64(1),
+            }),
+        );
+        let cmd = SignedCommand {
+            payload,
+            signer: sender_pk,
+            signature: Signature::dummy(),
+        };
+        transaction_hash::hash_command(valid::UserCommand::SignedCommand(Box::new(cmd)))
+    }
+
+    /// Applies `list` to the pool the same way the reducer does.
+    fn apply(
+        state: &mut TransactionPoolState,
+        accounts: &BTreeMap<AccountId, Account>,
+        list: Vec<ValidCommandWithHash>,
+    ) -> Vec<ValidCommandWithHash> {
+        let (_, accepted, _, dropped) = state
+            .pool
+            .unsafe_apply(
+                Timestamp::ZERO,
+                Slot::zero(),
+                Slot::zero(),
+                &diff::DiffVerified { list },
+                accounts,
+                false,
+            )
+            .unwrap();
+        state.update_propagation(Timestamp::ZERO, &accepted, dropped);
+        accepted
+    }
+
+    fn propagated(state: &TransactionPoolState) -> BTreeSet<TransactionHash> {
+        state
+            .for_propagation_iter()
+            .map(|tx| tx.hash.clone())
+            .collect()
+    }
+
+    fn pooled(state: &TransactionPoolState) -> BTreeSet<TransactionHash> {
+        state
+            .get_all_transactions()
+            .into_iter()
+            .map(|tx| tx.hash)
+            .collect()
+    }
+
+    #[test]
+    fn test_dpool_skips_dropped_commands() {
+        let mut state = test_pool_state(1);
+        let (a, b, c) = (
+            ledger::gen_keypair(),
+            ledger::gen_keypair(),
+            ledger::gen_keypair(),
+        );
+        let accounts = [&a, &b, &c]
+            .into_iter()
+            .map(|kp| test_account(kp, 0))
+            .collect::<BTreeMap<_, _>>();
+
+        let tx_a = payment(&a, 0, 10_000_000);
+        apply(&mut state, &accounts, vec![tx_a.clone()]);
+        assert_eq!(propagated(&state), BTreeSet::from([tx_a.hash.clone()]));
+
+        // A higher fee command evicts `tx_a` from the full pool.
+        let tx_c = payment(&c, 0, 20_000_000);
+        let accepted = apply(&mut state, &accounts, vec![tx_c.clone()]);
+        assert_eq!(accepted.len(), 1);
+        assert_eq!(propagated(&state), BTreeSet::from([tx_c.hash.clone()]));
+        assert_eq!(propagated(&state), pooled(&state));
+
+        // Two commands in one diff, the cheaper one is accepted and then
+        // dropped right away because the pool is full.
+        let tx_b = payment(&b, 0, 30_000_000);
+        let tx_a = payment(&a, 0, 40_000_000);
+        apply(&mut state, &accounts, vec![tx_b, tx_a.clone()]);
+        assert_eq!(propagated(&state), BTreeSet::from([tx_a.hash]));
+        assert_eq!(propagated(&state), pooled(&state));
+    }
+
+    #[test]
+    fn test_dpool_trimmed_on_best_tip_diff() {
+        let mut state = test_pool_state(10);
+        let a = ledger::gen_keypair();
+        let accounts = BTreeMap::from([test_account(&a, 0)]);
+
+        let tx0 = payment(&a, 0, 10_000_000);
+        let tx1 = payment(&a, 1, 10_000_000);
+        apply(&mut state, &accounts, vec![tx0.clone(), tx1.clone()]);
+        assert_eq!(
+            propagated(&state),
+            BTreeSet::from([tx0.hash.clone(), tx1.hash.clone()])
+        );
+
+        // The new best tip includes `tx0`, so the account nonce moves on.
+        let diff = diff::BestTipDiff {
+            new_commands: vec![WithStatus {
+                data: tx0.data.clone(),
+                status: TransactionStatus::Applied,
+            }],
+            removed_commands: vec![],
+            reorg_best_tip: false,
+        };
+        let (account_ids, uncommitted) = state.pool.get_accounts_to_handle_transition_diff(&diff);
+        let accounts = BTreeMap::from([test_account(&a, 1)]);
+        let uncommitted = uncommitted
+            .iter()
+            .filter_map(|id| Some((id.clone(), accounts.get(id)?.clone())))
+            .collect();
+        state
+            .pool
+            .handle_transition_frontier_diff(
+                Slot::zero(),
+                Slot::zero(),
+                &diff,
+                &account_ids,
+                &accounts,
+                &uncommitted,
+            )
+            .unwrap();
+        state.retain_pooled_for_propagation();
+
+        assert_eq!(propagated(&state), BTreeSet::from([tx1.hash]));
+        assert_eq!(propagated(&state), pooled(&state));
+    }
 
     #[allow(unused)]
     #[test]
@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use openmina_core::block::AppliedBlock;
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::write::BlockApplyResultArchive;
+
+/// Block applied to the transition frontier, together with the ledger
+/// changes made by it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ArchiveAppliedBlock {
+    pub block: AppliedBlock,
+    pub data: Arc<BlockApplyResultArchive>,
+}
+
+pub trait TransitionFrontierArchiveService: redux::Service {
+    /// Hand the applied block over to the archive sink. Must not block,
+    /// writing (and retrying) is up to the service.
+    fn send_to_archive(&mut self, block: ArchiveAppliedBlock);
+}
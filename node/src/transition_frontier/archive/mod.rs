@@ -0,0 +1,2 @@
+mod archive_service;
+pub use archive_service::*;
@@ -360,6 +360,21 @@ impl GenesisConfig {
         })
     }
 
+    /// Builds the genesis ledger described by the daemon.json `ledger`
+    /// section and computes its hash, without touching the ledger cache.
+    pub fn daemon_json_ledger_hash(
+        ledger: &daemon_json::Ledger,
+    ) -> Result<v2::LedgerHash, GenesisConfigError> {
+        let accounts = ledger
+            .accounts_with_genesis_winner()
+            .iter()
+            .map(daemon_json::Account::to_account)
+            .collect::<Result<Vec<_>, _>>()?;
+        let (mut mask, _total_currency) =
+            Self::build_ledger_from_accounts(accounts.into_iter().map(Ok))?;
+        Ok(ledger_hash(&mut mask))
+    }
+
     fn build_or_load_ledger(
         ledger_name: String,
         accounts: impl Iterator<Item = ledger::Account>,
@@ -600,6 +615,20 @@ impl PrebuiltGenesisConfig {
         self.binprot_write(&mut writer)
     }
 
+    pub fn genesis_ledger(&self) -> (&LedgerHash, &[MinaBaseAccountBinableArgStableV2]) {
+        (&self.ledger_hash, &self.accounts)
+    }
+
+    pub fn staking_epoch_ledger(&self) -> (&LedgerHash, &[MinaBaseAccountBinableArgStableV2]) {
+        let data = &self.staking_epoch_data;
+        (&data.ledger_hash, &data.accounts)
+    }
+
+    pub fn next_epoch_ledger(&self) -> (&LedgerHash, &[MinaBaseAccountBinableArgStableV2]) {
+        let data = &self.next_epoch_data;
+        (&data.ledger_hash, &data.accounts)
+    }
+
     pub fn load(self) -> Result<(Vec<ledger::Mask>, GenesisConfigLoaded), GenesisConfigError> {
         let mut masks = Vec::new();
         let (mask, genesis_total_currency) = GenesisConfig::build_ledger_from_accounts_and_hashes(
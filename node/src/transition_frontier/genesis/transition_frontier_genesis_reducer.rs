@@ -104,17 +104,25 @@ impl TransitionFrontierGenesisState {
                         error!(meta.time(); "incorrect state: {:?}", global_state.transition_frontier.genesis);
                         return;
                     };
-                    use openmina_core::{constants, ChainId};
-                    let constraint_system_digests =
-                        openmina_core::NetworkConfig::global().constraint_system_digests;
+                    use openmina_core::{constants, ChainId, NetworkConfig};
+                    let network = NetworkConfig::global();
                     let chain_id = ChainId::compute(
-                        constraint_system_digests,
+                        network.constraint_system_digests,
                         genesis_hash,
                         &genesis.body.constants,
                         constants::PROTOCOL_TRANSACTION_VERSION,
                         constants::PROTOCOL_NETWORK_VERSION,
                         &v2::UnsignedExtendedUInt32StableV1::from(constants::TX_POOL_MAX_SIZE),
                     );
+                    // Genesis config doesn't match the network definition,
+                    // the node would be on a chain nobody else is on.
+                    if let Some(expected) = network.chain_id.as_ref().filter(|id| *id != &chain_id)
+                    {
+                        panic!(
+                            "chain id mismatch for network {}! expected: {expected}, computed from genesis: {chain_id}",
+                            network.name
+                        );
+                    }
                     dispatcher.push(P2pInitializeAction::Initialize { chain_id });
                 }
                 dispatcher.push(TransitionFrontierGenesisAction::ProveInit);
@@ -1,5 +1,7 @@
+pub mod archive;
 pub mod genesis;
 pub mod genesis_effectful;
+pub mod persistence;
 pub mod sync;
 
 mod transition_frontier_config;
@@ -0,0 +1,85 @@
+//! Persistence of the transition frontier.
+//!
+//! After the node is synced, snapshot of the transition frontier is
+//! written to disk by the ledger service. On startup, after genesis is
+//! loaded, the node tries to resume from that snapshot and falls back to
+//! the regular bootstrap if the snapshot is missing, stale or corrupted.
+
+mod transition_frontier_persistence_state;
+pub use transition_frontier_persistence_state::*;
+
+mod transition_frontier_persistence_actions;
+pub use transition_frontier_persistence_actions::*;
+
+mod transition_frontier_persistence_reducer;
+
+use openmina_core::block::AppliedBlock;
+
+use crate::ledger::write::FrontierRestoreResult;
+
+/// Checks that the restored frontier can still be used.
+///
+/// Frontier whose best tip is more than `k` slots behind the current
+/// slot can't have a common root with the rest of the network, so we
+/// bootstrap from scratch instead.
+pub fn restored_frontier_check(
+    state: &crate::State,
+    restored: &FrontierRestoreResult,
+) -> Result<(), String> {
+    let best_tip = restored
+        .chain
+        .last()
+        .ok_or_else(|| "restored chain is empty".to_owned())?;
+    let cur_global_slot = state
+        .cur_global_slot()
+        .ok_or_else(|| "current global slot is unknown".to_owned())?;
+    best_tip_staleness_check(best_tip, cur_global_slot)
+}
+
+fn best_tip_staleness_check(best_tip: &AppliedBlock, cur_global_slot: u32) -> Result<(), String> {
+    let k = best_tip.constants().k.as_u32();
+    let slots_behind = cur_global_slot.saturating_sub(best_tip.global_slot());
+    if slots_behind > k {
+        return Err(format!(
+            "snapshot is stale, best tip {} is {slots_behind} slots behind",
+            best_tip.hash()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mina_p2p_messages::{binprot::BinProtRead, gossip::GossipNetMessageV2};
+    use openmina_core::block::BlockWithHash;
+
+    use super::*;
+
+    fn test_best_tip() -> AppliedBlock {
+        let mut bytes: &[u8] =
+            include_bytes!("../../../../mina-p2p-messages/tests/files/v2/gossip/new_state.bin");
+        let GossipNetMessageV2::NewState(block) =
+            GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+        else {
+            panic!("expected a block");
+        };
+        AppliedBlock {
+            block: BlockWithHash::try_new(Arc::new(block)).unwrap(),
+            just_emitted_a_proof: false,
+        }
+    }
+
+    #[test]
+    fn test_stale_best_tip_is_rejected() {
+        let best_tip = test_best_tip();
+        let slot = best_tip.global_slot();
+        let k = best_tip.constants().k.as_u32();
+
+        assert!(best_tip_staleness_check(&best_tip, slot).is_ok());
+        assert!(best_tip_staleness_check(&best_tip, slot + k).is_ok());
+        let error = best_tip_staleness_check(&best_tip, slot + k + 1).unwrap_err();
+        assert!(error.contains("stale"), "{error}");
+    }
+}
@@ -0,0 +1,105 @@
+use mina_p2p_messages::v2::StateHash;
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::ledger::write::{FrontierRestoreResult, LedgerWriteState};
+
+use super::TransitionFrontierPersistenceState;
+
+pub type TransitionFrontierPersistenceActionWithMeta =
+    redux::ActionWithMeta<TransitionFrontierPersistenceAction>;
+pub type TransitionFrontierPersistenceActionWithMetaRef<'a> =
+    redux::ActionWithMeta<&'a TransitionFrontierPersistenceAction>;
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = debug)]
+pub enum TransitionFrontierPersistenceAction {
+    /// Restore transition frontier from the snapshot on disk.
+    #[action_event(level = info)]
+    RestoreInit,
+    RestorePending,
+    #[action_event(level = info)]
+    RestoreSuccess {
+        result: FrontierRestoreResult,
+    },
+    /// Snapshot is missing or unusable, continue from genesis.
+    #[action_event(level = warn, fields(error))]
+    RestoreError {
+        error: String,
+    },
+    /// Write snapshot of the current transition frontier to disk.
+    PersistInit,
+    PersistPending,
+    PersistSuccess {
+        best_tip_hash: StateHash,
+    },
+    #[action_event(level = warn, fields(display(best_tip_hash), error))]
+    PersistError {
+        best_tip_hash: StateHash,
+        error: String,
+    },
+}
+
+impl redux::EnablingCondition<crate::State> for TransitionFrontierPersistenceAction {
+    fn is_enabled(&self, state: &crate::State, _time: redux::Timestamp) -> bool {
+        let transition_frontier = &state.transition_frontier;
+        let persistence = &transition_frontier.persistence;
+        let ledger_write_ready = matches!(
+            state.ledger.write,
+            LedgerWriteState::Idle | LedgerWriteState::Success { .. }
+        );
+        match self {
+            TransitionFrontierPersistenceAction::RestoreInit => {
+                transition_frontier.config.persistence
+                    && matches!(persistence, TransitionFrontierPersistenceState::Idle)
+                    && transition_frontier.root().is_none()
+                    && state.genesis_block().is_some()
+                    && ledger_write_ready
+            }
+            TransitionFrontierPersistenceAction::RestorePending => matches!(
+                persistence,
+                TransitionFrontierPersistenceState::RestoreInit { .. }
+            ),
+            TransitionFrontierPersistenceAction::RestoreSuccess { result } => {
+                !result.chain.is_empty()
+                    && matches!(
+                        persistence,
+                        TransitionFrontierPersistenceState::RestorePending { .. }
+                    )
+            }
+            TransitionFrontierPersistenceAction::RestoreError { .. } => matches!(
+                persistence,
+                TransitionFrontierPersistenceState::RestorePending { .. }
+            ),
+            TransitionFrontierPersistenceAction::PersistInit => {
+                transition_frontier.config.persistence
+                    && !persistence.is_restore_pending()
+                    && !persistence.is_persist_pending()
+                    && transition_frontier.sync.is_synced()
+                    && ledger_write_ready
+                    && transition_frontier.best_tip().map_or(false, |tip| {
+                        // Nothing worth resuming from until we leave genesis.
+                        !tip.is_genesis() && persistence.last_best_tip_hash() != Some(tip.hash())
+                    })
+            }
+            TransitionFrontierPersistenceAction::PersistPending => matches!(
+                persistence,
+                TransitionFrontierPersistenceState::PersistInit { .. }
+            ),
+            TransitionFrontierPersistenceAction::PersistSuccess { best_tip_hash }
+            | TransitionFrontierPersistenceAction::PersistError { best_tip_hash, .. } => {
+                matches!(
+                    persistence,
+                    TransitionFrontierPersistenceState::PersistPending { best_tip_hash: hash, .. }
+                        if hash == best_tip_hash
+                )
+            }
+        }
+    }
+}
+
+impl From<TransitionFrontierPersistenceAction> for crate::Action {
+    fn from(value: TransitionFrontierPersistenceAction) -> Self {
+        crate::transition_frontier::TransitionFrontierAction::Persistence(value).into()
+    }
+}
@@ -0,0 +1,121 @@
+use mina_p2p_messages::v2::StateHash;
+
+use crate::ledger::write::{LedgerWriteAction, LedgerWriteRequest};
+use crate::transition_frontier::TransitionFrontierAction;
+
+use super::{
+    TransitionFrontierPersistenceAction, TransitionFrontierPersistenceActionWithMetaRef,
+    TransitionFrontierPersistenceState,
+};
+
+impl TransitionFrontierPersistenceState {
+    pub fn reducer(
+        mut state_context: crate::Substate<Self>,
+        action: TransitionFrontierPersistenceActionWithMetaRef<'_>,
+        best_tip_hash: Option<&StateHash>,
+    ) {
+        let Ok(state) = state_context.get_substate_mut() else {
+            // TODO: log or propagate
+            return;
+        };
+        let (action, meta) = action.split();
+
+        match action {
+            TransitionFrontierPersistenceAction::RestoreInit => {
+                *state = Self::RestoreInit { time: meta.time() };
+
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                let Some(genesis) = global_state.genesis_block() else {
+                    return;
+                };
+                dispatcher.push(LedgerWriteAction::Init {
+                    request: LedgerWriteRequest::FrontierRestore {
+                        genesis_state_hash: genesis.hash().clone(),
+                    },
+                    on_init: redux::callback!(
+                        on_frontier_restore_init(_request: LedgerWriteRequest) -> crate::Action {
+                            TransitionFrontierPersistenceAction::RestorePending
+                        }
+                    ),
+                });
+            }
+            TransitionFrontierPersistenceAction::RestorePending => {
+                *state = Self::RestorePending { time: meta.time() };
+            }
+            TransitionFrontierPersistenceAction::RestoreSuccess { result } => {
+                let Some(best_tip) = result.chain.last() else {
+                    return;
+                };
+                *state = Self::RestoreSuccess {
+                    time: meta.time(),
+                    best_tip_hash: best_tip.hash().clone(),
+                    chain: result.chain.clone(),
+                    needed_protocol_states: result.needed_protocol_states.clone(),
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(TransitionFrontierAction::SnapshotInject);
+            }
+            TransitionFrontierPersistenceAction::RestoreError { error } => {
+                *state = Self::RestoreError {
+                    time: meta.time(),
+                    error: error.clone(),
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(TransitionFrontierAction::GenesisInject);
+            }
+            TransitionFrontierPersistenceAction::PersistInit => {
+                let Some(best_tip_hash) = best_tip_hash else {
+                    return;
+                };
+                *state = Self::PersistInit {
+                    time: meta.time(),
+                    best_tip_hash: best_tip_hash.clone(),
+                };
+
+                let (dispatcher, global_state) = state_context.into_dispatcher_and_state();
+                let Some(genesis) = global_state.genesis_block() else {
+                    return;
+                };
+                let transition_frontier = &global_state.transition_frontier;
+                dispatcher.push(LedgerWriteAction::Init {
+                    request: LedgerWriteRequest::FrontierPersist {
+                        genesis_state_hash: genesis.hash().clone(),
+                        best_chain: transition_frontier.best_chain.clone(),
+                        needed_protocol_states: transition_frontier.needed_protocol_states.clone(),
+                    },
+                    on_init: redux::callback!(
+                        on_frontier_persist_init(_request: LedgerWriteRequest) -> crate::Action {
+                            TransitionFrontierPersistenceAction::PersistPending
+                        }
+                    ),
+                });
+            }
+            TransitionFrontierPersistenceAction::PersistPending => {
+                if let Self::PersistInit { best_tip_hash, .. } = state {
+                    *state = Self::PersistPending {
+                        time: meta.time(),
+                        best_tip_hash: best_tip_hash.clone(),
+                    };
+                }
+            }
+            TransitionFrontierPersistenceAction::PersistSuccess { best_tip_hash } => {
+                *state = Self::PersistSuccess {
+                    time: meta.time(),
+                    best_tip_hash: best_tip_hash.clone(),
+                };
+            }
+            TransitionFrontierPersistenceAction::PersistError {
+                best_tip_hash,
+                error,
+            } => {
+                *state = Self::PersistError {
+                    time: meta.time(),
+                    best_tip_hash: best_tip_hash.clone(),
+                    error: error.clone(),
+                };
+            }
+        }
+    }
+}
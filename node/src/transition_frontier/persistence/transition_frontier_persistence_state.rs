@@ -0,0 +1,71 @@
+use std::collections::BTreeMap;
+
+use mina_p2p_messages::v2::{MinaStateProtocolStateValueStableV2, StateHash};
+use openmina_core::block::AppliedBlock;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum TransitionFrontierPersistenceState {
+    #[default]
+    Idle,
+    RestoreInit {
+        time: redux::Timestamp,
+    },
+    RestorePending {
+        time: redux::Timestamp,
+    },
+    RestoreSuccess {
+        time: redux::Timestamp,
+        best_tip_hash: StateHash,
+        /// Taken out when the chain is injected into the transition frontier.
+        chain: Vec<AppliedBlock>,
+        needed_protocol_states: BTreeMap<StateHash, MinaStateProtocolStateValueStableV2>,
+    },
+    RestoreError {
+        time: redux::Timestamp,
+        error: String,
+    },
+    PersistInit {
+        time: redux::Timestamp,
+        best_tip_hash: StateHash,
+    },
+    PersistPending {
+        time: redux::Timestamp,
+        best_tip_hash: StateHash,
+    },
+    PersistSuccess {
+        time: redux::Timestamp,
+        best_tip_hash: StateHash,
+    },
+    PersistError {
+        time: redux::Timestamp,
+        best_tip_hash: StateHash,
+        error: String,
+    },
+}
+
+impl TransitionFrontierPersistenceState {
+    pub fn is_restore_pending(&self) -> bool {
+        matches!(self, Self::RestoreInit { .. } | Self::RestorePending { .. })
+    }
+
+    pub fn is_persist_pending(&self) -> bool {
+        matches!(self, Self::PersistInit { .. } | Self::PersistPending { .. })
+    }
+
+    /// Best tip of the last snapshot that was written (or attempted to
+    /// be written) to disk or restored from it.
+    pub fn last_best_tip_hash(&self) -> Option<&StateHash> {
+        match self {
+            Self::Idle
+            | Self::RestoreInit { .. }
+            | Self::RestorePending { .. }
+            | Self::RestoreError { .. } => None,
+            Self::RestoreSuccess { best_tip_hash, .. }
+            | Self::PersistInit { best_tip_hash, .. }
+            | Self::PersistPending { best_tip_hash, .. }
+            | Self::PersistSuccess { best_tip_hash, .. }
+            | Self::PersistError { best_tip_hash, .. } => Some(best_tip_hash),
+        }
+    }
+}
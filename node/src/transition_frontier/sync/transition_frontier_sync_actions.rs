@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use mina_p2p_messages::v2::{LedgerHash, StateHash};
 use openmina_core::block::ArcBlockWithHash;
 use openmina_core::consensus::consensus_take;
@@ -5,7 +7,7 @@ use openmina_core::ActionEvent;
 use redux::Callback;
 use serde::{Deserialize, Serialize};
 
-use crate::ledger::write::CommitResult;
+use crate::ledger::write::{BlockApplyResultArchive, CommitResult};
 use crate::p2p::channels::rpc::P2pRpcId;
 use crate::p2p::PeerId;
 use crate::transition_frontier::sync::TransitionFrontierSyncLedgerPending;
@@ -105,6 +107,7 @@ pub enum TransitionFrontierSyncAction {
     BlocksNextApplySuccess {
         hash: StateHash,
         just_emitted_a_proof: bool,
+        archive_data: Option<Arc<BlockApplyResultArchive>>,
     },
     /// Done applying all pending blocks
     BlocksSuccess,
@@ -125,6 +128,7 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
             TransitionFrontierSyncAction::Init { best_tip, .. } => {
                 !state.transition_frontier.sync.is_pending()
                     && !state.transition_frontier.sync.is_synced()
+                    && !state.transition_frontier.persistence.is_restore_pending()
                     && state
                         .transition_frontier
                         .best_tip()
@@ -343,10 +347,7 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierSyncAction {
                 .sync
                 .blocks_apply_pending()
                 .map_or(false, |b| &b.hash == hash),
-            TransitionFrontierSyncAction::BlocksNextApplySuccess {
-                hash,
-                just_emitted_a_proof: _,
-            } => state
+            TransitionFrontierSyncAction::BlocksNextApplySuccess { hash, .. } => state
                 .transition_frontier
                 .sync
                 .blocks_apply_pending()
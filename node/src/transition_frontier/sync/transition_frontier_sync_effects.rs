@@ -6,7 +6,10 @@ use redux::ActionMeta;
 
 use crate::ledger::write::{LedgerWriteAction, LedgerWriteRequest};
 use crate::p2p::channels::rpc::P2pRpcRequest;
-use crate::service::TransitionFrontierSyncLedgerSnarkedService;
+use crate::service::{
+    TransitionFrontierArchiveService, TransitionFrontierSyncLedgerSnarkedService,
+};
+use crate::transition_frontier::archive::ArchiveAppliedBlock;
 use crate::{p2p_ready, Service, Store, TransitionFrontierAction};
 
 use super::ledger::snarked::TransitionFrontierSyncLedgerSnarkedAction;
@@ -270,14 +273,11 @@ impl TransitionFrontierSyncAction {
                         block,
                         pred_block,
                         skip_verification,
+                        archive: store.state().transition_frontier.config.archive,
                     },
                     on_init: redux::callback!(
                         on_block_next_apply_init(request: LedgerWriteRequest) -> crate::Action {
-                            let LedgerWriteRequest::BlockApply {
-                                block,
-                                pred_block: _,
-                                skip_verification: _,
-                            } = request
+                            let LedgerWriteRequest::BlockApply { block, .. } = request
                             else {
                                 unreachable!()
                             };
@@ -308,10 +308,26 @@ impl TransitionFrontierSyncAction {
             TransitionFrontierSyncAction::BlocksNextApplySuccess {
                 hash,
                 just_emitted_a_proof: _,
+                archive_data,
             } => {
                 if let Some(stats) = store.service.stats() {
                     stats.block_producer().block_apply_end(meta.time(), hash);
                 }
+                if let Some(data) = archive_data {
+                    let block = store
+                        .state()
+                        .transition_frontier
+                        .sync
+                        .block_state(hash)
+                        .and_then(|s| s.applied_block())
+                        .cloned();
+                    if let Some(block) = block {
+                        store.service.send_to_archive(ArchiveAppliedBlock {
+                            block,
+                            data: data.clone(),
+                        });
+                    }
+                }
 
                 if !store.dispatch(TransitionFrontierSyncAction::BlocksNextApplyInit) {
                     store.dispatch(TransitionFrontierSyncAction::BlocksSuccess);
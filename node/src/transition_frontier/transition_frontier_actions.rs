@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use super::genesis::TransitionFrontierGenesisAction;
 use super::genesis_effectful::TransitionFrontierGenesisEffectfulAction;
+use super::persistence::{TransitionFrontierPersistenceAction, TransitionFrontierPersistenceState};
 use super::sync::{SyncError, TransitionFrontierSyncAction, TransitionFrontierSyncState};
 
 pub type TransitionFrontierActionWithMeta = redux::ActionWithMeta<TransitionFrontierAction>;
@@ -28,6 +29,11 @@ pub enum TransitionFrontierAction {
     GenesisInject,
     #[action_event(level = info)]
     GenesisProvenInject,
+    Persistence(TransitionFrontierPersistenceAction),
+    /// Inject best chain restored from the snapshot on disk into the
+    /// transition frontier.
+    #[action_event(level = info)]
+    SnapshotInject,
 
     Sync(TransitionFrontierSyncAction),
     /// Transition frontier synced.
@@ -48,6 +54,7 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
             TransitionFrontierAction::GenesisEffect(a) => a.is_enabled(state, time),
             TransitionFrontierAction::GenesisInject => {
                 state.transition_frontier.root().is_none()
+                    && !state.transition_frontier.persistence.is_restore_pending()
                     && state
                         .transition_frontier
                         .genesis
@@ -58,9 +65,19 @@ impl redux::EnablingCondition<crate::State> for TransitionFrontierAction {
                 let Some(genesis) = state.transition_frontier.genesis.proven_block() else {
                     return false;
                 };
-                state.transition_frontier.root().map_or(true, |b| {
-                    b.is_genesis() && !Arc::ptr_eq(&genesis.block, &b.block)
-                })
+                !state.transition_frontier.persistence.is_restore_pending()
+                    && state.transition_frontier.root().map_or(true, |b| {
+                        b.is_genesis() && !Arc::ptr_eq(&genesis.block, &b.block)
+                    })
+            }
+            TransitionFrontierAction::Persistence(a) => a.is_enabled(state, time),
+            TransitionFrontierAction::SnapshotInject => {
+                state.transition_frontier.root().is_none()
+                    && matches!(
+                        &state.transition_frontier.persistence,
+                        TransitionFrontierPersistenceState::RestoreSuccess { chain, .. }
+                            if !chain.is_empty()
+                    )
             }
             TransitionFrontierAction::Sync(a) => a.is_enabled(state, time),
             TransitionFrontierAction::Synced { .. } => matches!(
@@ -7,10 +7,31 @@ use super::genesis::TransitionFrontierGenesisConfig;
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TransitionFrontierConfig {
     pub genesis: Arc<TransitionFrontierGenesisConfig>,
+    /// Persist the transition frontier to disk and resume from it on
+    /// startup. Requires frontier store to be opened in the ledger service.
+    #[serde(default)]
+    pub persistence: bool,
+    /// Send applied blocks to the archive sink.
+    #[serde(default)]
+    pub archive: bool,
 }
 
 impl TransitionFrontierConfig {
     pub fn new(genesis: Arc<TransitionFrontierGenesisConfig>) -> Self {
-        TransitionFrontierConfig { genesis }
+        TransitionFrontierConfig {
+            genesis,
+            persistence: false,
+            archive: false,
+        }
+    }
+
+    pub fn with_persistence(mut self, persistence: bool) -> Self {
+        self.persistence = persistence;
+        self
+    }
+
+    pub fn with_archive(mut self, archive: bool) -> Self {
+        self.archive = archive;
+        self
     }
 }
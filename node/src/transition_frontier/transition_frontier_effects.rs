@@ -1,14 +1,18 @@
+use openmina_core::block::AppliedBlock;
 use redux::Timestamp;
 
 use crate::block_producer::BlockProducerAction;
-use crate::consensus::ConsensusAction;
+use crate::consensus::{ConsensusAction, ConsensusBlockStatus, ConsensusState};
 use crate::ledger::LEDGER_DEPTH;
 use crate::p2p::channels::best_tip::P2pChannelsBestTipAction;
+use crate::rpc::{RpcChainReorganization, RpcSubscriptionEvent};
+use crate::rpc_effectful::RpcService;
 use crate::snark_pool::{SnarkPoolAction, SnarkWork};
 use crate::stats::sync::SyncingLedger;
 use crate::{Store, TransactionPoolAction};
 
 use super::genesis::TransitionFrontierGenesisAction;
+use super::persistence::TransitionFrontierPersistenceAction;
 use super::sync::ledger::snarked::{
     TransitionFrontierSyncLedgerSnarkedAction, ACCOUNT_SUBTREE_HEIGHT,
 };
@@ -35,6 +39,9 @@ pub fn transition_frontier_effects<S: crate::Service>(
             // whenever any of these is going to happen, genesisinject must happen first
             match &a {
                 TransitionFrontierGenesisAction::Produce => {
+                    // Resume from the snapshot on disk if persistence is
+                    // enabled, genesis is injected if that fails.
+                    store.dispatch(TransitionFrontierPersistenceAction::RestoreInit);
                     store.dispatch(TransitionFrontierAction::GenesisInject);
                 }
                 TransitionFrontierGenesisAction::ProveSuccess { .. } => {
@@ -54,6 +61,10 @@ pub fn transition_frontier_effects<S: crate::Service>(
                 synced_effects(&meta, store);
             }
         }
+        TransitionFrontierAction::Persistence(_) => {}
+        TransitionFrontierAction::SnapshotInject => {
+            synced_effects(&meta, store);
+        }
         TransitionFrontierAction::Sync(a) => {
             match a {
                 TransitionFrontierSyncAction::Init {
@@ -198,10 +209,7 @@ pub fn transition_frontier_effects<S: crate::Service>(
                     }
                 }
                 TransitionFrontierSyncAction::BlocksNextApplyError { .. } => {}
-                TransitionFrontierSyncAction::BlocksNextApplySuccess {
-                    ref hash,
-                    just_emitted_a_proof: _,
-                } => {
+                TransitionFrontierSyncAction::BlocksNextApplySuccess { ref hash, .. } => {
                     if let Some(stats) = store.service.stats() {
                         if let Some(state) =
                             store.state.get().transition_frontier.sync.block_state(hash)
@@ -253,10 +261,18 @@ pub fn transition_frontier_effects<S: crate::Service>(
                             sender: own_peer_id,
                         })
                         .collect();
+                    let subscription_events = subscription_events(
+                        &transition_frontier.best_chain,
+                        chain,
+                        &store.state().consensus,
+                    );
 
                     store.dispatch(TransitionFrontierAction::Synced {
                         needed_protocol_states: result.needed_protocol_states,
                     });
+                    for event in subscription_events {
+                        store.service.publish_subscription_event(event);
+                    }
                     store.dispatch(SnarkPoolAction::JobsUpdate {
                         jobs: result.available_jobs,
                         orphaned_snarks,
@@ -320,6 +336,64 @@ fn synced_effects<S: crate::Service>(
             diff,
         });
     }
+    store.dispatch(TransitionFrontierPersistenceAction::PersistInit);
+}
+
+/// Events for the RPC subscribers describing the switch from `old_chain`
+/// to `new_chain`.
+fn subscription_events(
+    old_chain: &[AppliedBlock],
+    new_chain: &[AppliedBlock],
+    consensus: &ConsensusState,
+) -> Vec<RpcSubscriptionEvent> {
+    let (Some(old_tip), Some(new_tip)) = (old_chain.last(), new_chain.last()) else {
+        // Initial sync, only announce the best tip.
+        return new_chain
+            .last()
+            .cloned()
+            .map(RpcSubscriptionEvent::NewBlock)
+            .into_iter()
+            .collect();
+    };
+    if old_tip.hash() == new_tip.hash() {
+        return vec![];
+    }
+
+    let common_ancestor = new_chain
+        .iter()
+        .rposition(|b| old_chain.iter().rev().any(|old| old.hash() == b.hash()));
+    let new_blocks = common_ancestor
+        .and_then(|i| new_chain.get(i.saturating_add(1)..))
+        .unwrap_or(std::slice::from_ref(new_tip));
+    let common_ancestor = common_ancestor.and_then(|i| new_chain.get(i));
+
+    let mut events = Vec::with_capacity(new_blocks.len().saturating_add(1));
+    if common_ancestor.map_or(true, |b| b.hash() != old_tip.hash()) {
+        let decision = consensus
+            .blocks
+            .get(new_tip.hash())
+            .and_then(|b| match &b.status {
+                ConsensusBlockStatus::ShortRangeForkResolve { decision, .. } => {
+                    Some(decision.clone())
+                }
+                _ => None,
+            });
+        events.push(RpcSubscriptionEvent::ChainReorganization(
+            RpcChainReorganization {
+                old_best_tip: old_tip.hash().clone(),
+                new_best_tip: new_tip.hash().clone(),
+                common_ancestor: common_ancestor.map(|b| b.hash().clone()),
+                decision,
+            },
+        ));
+    }
+    events.extend(
+        new_blocks
+            .iter()
+            .cloned()
+            .map(RpcSubscriptionEvent::NewBlock),
+    );
+    events
 }
 
 // Handling of the actions related to the synchronization of a target ledger
@@ -494,3 +568,116 @@ fn handle_transition_frontier_sync_ledger_action<S: crate::Service>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use mina_p2p_messages::{
+        binprot::BinProtRead,
+        gossip::GossipNetMessageV2,
+        v2::{MinaBlockBlockStableV2, StateHash},
+    };
+    use openmina_core::block::BlockWithHash;
+
+    use super::*;
+
+    fn test_block() -> MinaBlockBlockStableV2 {
+        let mut bytes: &[u8] =
+            include_bytes!("../../../mina-p2p-messages/tests/files/v2/gossip/new_state.bin");
+        let GossipNetMessageV2::NewState(block) =
+            GossipNetMessageV2::binprot_read(&mut bytes).unwrap()
+        else {
+            panic!("expected a block");
+        };
+        block
+    }
+
+    /// Chain of `len` blocks on top of `pred`, blocks of different
+    /// `fork`s at the same height have different hashes.
+    fn test_chain(pred: Option<&AppliedBlock>, len: u32, fork: u32) -> Vec<AppliedBlock> {
+        let base = test_block();
+        let mut pred = pred.cloned();
+        (0..len)
+            .map(|_| {
+                let mut block = base.clone();
+                let protocol_state = &mut block.header.protocol_state;
+                let height = pred.as_ref().map_or(1, |b| b.height() + 1);
+                if let Some(pred) = &pred {
+                    protocol_state.previous_state_hash = pred.hash().clone();
+                }
+                protocol_state.body.consensus_state.blockchain_length = height.into();
+                protocol_state.body.consensus_state.min_window_density = fork.into();
+                let block = AppliedBlock {
+                    block: BlockWithHash::try_new(Arc::new(block)).unwrap(),
+                    just_emitted_a_proof: false,
+                };
+                pred = Some(block.clone());
+                block
+            })
+            .collect()
+    }
+
+    fn new_blocks(events: &[RpcSubscriptionEvent]) -> Vec<StateHash> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                RpcSubscriptionEvent::NewBlock(block) => Some(block.hash().clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn hashes(chain: &[AppliedBlock]) -> Vec<StateHash> {
+        chain.iter().map(|b| b.hash().clone()).collect()
+    }
+
+    #[test]
+    fn test_initial_sync_announces_best_tip() {
+        let chain = test_chain(None, 3, 0);
+        let events = subscription_events(&[], &chain, &ConsensusState::new());
+        assert_eq!(events.len(), 1);
+        assert_eq!(new_blocks(&events), hashes(&chain[2..]));
+    }
+
+    #[test]
+    fn test_extension_announces_new_blocks() {
+        let chain = test_chain(None, 5, 0);
+        let consensus = ConsensusState::new();
+
+        // root moves forward as the chain grows
+        let events = subscription_events(&chain[..3], &chain[1..], &consensus);
+        assert_eq!(events.len(), 2);
+        assert_eq!(new_blocks(&events), hashes(&chain[3..]));
+
+        assert!(subscription_events(&chain, &chain, &consensus).is_empty());
+    }
+
+    #[test]
+    fn test_reorg_is_announced_before_new_blocks() {
+        let old_chain = test_chain(None, 4, 0);
+        let fork = test_chain(Some(&old_chain[1]), 3, 1);
+        let new_chain = [&old_chain[..2], &fork[..]].concat();
+
+        let events = subscription_events(&old_chain, &new_chain, &ConsensusState::new());
+        assert_eq!(events.len(), 4);
+        let RpcSubscriptionEvent::ChainReorganization(reorg) = &events[0] else {
+            panic!("expected reorganization, got: {:?}", events[0]);
+        };
+        assert_eq!(&reorg.old_best_tip, old_chain[3].hash());
+        assert_eq!(&reorg.new_best_tip, fork[2].hash());
+        assert_eq!(reorg.common_ancestor.as_ref(), Some(old_chain[1].hash()));
+        assert!(reorg.decision.is_none());
+        assert_eq!(new_blocks(&events), hashes(&fork));
+
+        // no common block, only the new best tip is announced
+        let other_chain = test_chain(None, 3, 2);
+        let events = subscription_events(&old_chain, &other_chain, &ConsensusState::new());
+        assert_eq!(events.len(), 2);
+        let RpcSubscriptionEvent::ChainReorganization(reorg) = &events[0] else {
+            panic!("expected reorganization, got: {:?}", events[0]);
+        };
+        assert!(reorg.common_ancestor.is_none());
+        assert_eq!(new_blocks(&events), hashes(&other_chain[2..]));
+    }
+}
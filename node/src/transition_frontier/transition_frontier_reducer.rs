@@ -1,3 +1,4 @@
+use super::persistence::TransitionFrontierPersistenceState;
 use super::sync::{SyncError, TransitionFrontierSyncState};
 use super::{
     TransitionFrontierAction, TransitionFrontierActionWithMetaRef, TransitionFrontierState,
@@ -54,6 +55,27 @@ impl TransitionFrontierState {
                     state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
                 }
             }
+            TransitionFrontierAction::Persistence(a) => {
+                let best_tip_hash = state.best_tip().map(|b| b.hash().clone());
+                TransitionFrontierPersistenceState::reducer(
+                    openmina_core::Substate::from_compatible_substate(state_context),
+                    meta.with_action(a),
+                    best_tip_hash.as_ref(),
+                );
+            }
+            TransitionFrontierAction::SnapshotInject => {
+                let TransitionFrontierPersistenceState::RestoreSuccess {
+                    chain,
+                    needed_protocol_states,
+                    ..
+                } = &mut state.persistence
+                else {
+                    return;
+                };
+                state.best_chain = std::mem::take(chain);
+                state.needed_protocol_states = std::mem::take(needed_protocol_states);
+                state.sync = TransitionFrontierSyncState::Synced { time: meta.time() };
+            }
             TransitionFrontierAction::Sync(a) => {
                 let best_chain = state.best_chain.clone();
                 super::sync::TransitionFrontierSyncState::reducer(
@@ -10,6 +10,7 @@ use openmina_core::bug_condition;
 use serde::{Deserialize, Serialize};
 
 use super::genesis::TransitionFrontierGenesisState;
+use super::persistence::TransitionFrontierPersistenceState;
 use super::sync::TransitionFrontierSyncState;
 use super::TransitionFrontierConfig;
 
@@ -31,6 +32,8 @@ pub struct TransitionFrontierState {
     pub blacklist: BTreeMap<StateHash, u32>,
     /// The diff of `Self::best_chain` with the previous one
     pub chain_diff: Option<BestTipDiff>,
+    /// Snapshot of the transition frontier on disk.
+    pub persistence: TransitionFrontierPersistenceState,
 }
 
 impl TransitionFrontierState {
@@ -43,6 +46,7 @@ impl TransitionFrontierState {
             sync: TransitionFrontierSyncState::Idle,
             blacklist: Default::default(),
             chain_diff: None,
+            persistence: Default::default(),
         }
     }
 
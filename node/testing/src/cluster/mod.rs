@@ -26,7 +26,10 @@ use node::core::invariants::InvariantsState;
 use node::core::log::system_time;
 use node::core::requests::RpcId;
 use node::core::{thread, warn};
-use node::p2p::{P2pConnectionEvent, P2pEvent, P2pLimits, P2pMeshsubConfig, PeerId};
+use node::p2p::{
+    P2pConnectionEvent, P2pEvent, P2pLimits, P2pMeshsubConfig, P2pNatConfig, P2pReputationConfig,
+    PeerId,
+};
 use node::snark::{BlockVerifier, TransactionVerifier, VerifierSRS};
 use node::{
     event_source::Event,
@@ -204,9 +207,43 @@ impl Cluster {
     }
 
     pub fn add_rust_node(&mut self, testing_config: RustNodeTestingConfig) -> ClusterNodeId {
+        let node_id = ClusterNodeId::new_unchecked(self.nodes.len());
+        let node = self.build_rust_node(node_id, testing_config);
+        self.nodes.push(node);
+        node_id
+    }
+
+    /// Replaces the node with a freshly started one, built from the same
+    /// config, as if the node was restarted at `time`. Whole state of the
+    /// node is lost.
+    ///
+    /// Restarted node listens on a new libp2p port, since the old one
+    /// might not be released yet.
+    pub fn restart_rust_node(
+        &mut self,
+        node_id: ClusterNodeId,
+        time: redux::Timestamp,
+    ) -> anyhow::Result<()> {
+        let node = self
+            .node(node_id)
+            .ok_or_else(|| anyhow::anyhow!("node {node_id:?} not found"))?;
+        let testing_config = RustNodeTestingConfig {
+            initial_time: time,
+            libp2p_port: None,
+            ..node.config().clone()
+        };
+        let node = self.build_rust_node(node_id, testing_config);
+        self.nodes[node_id.index()] = node;
+        Ok(())
+    }
+
+    fn build_rust_node(
+        &mut self,
+        node_id: ClusterNodeId,
+        testing_config: RustNodeTestingConfig,
+    ) -> Node {
         let rng_seed = [0; 32];
         let node_config = testing_config.clone();
-        let node_id = ClusterNodeId::new_unchecked(self.nodes.len());
         let work_dir = TempDir::new().unwrap();
         let (shutdown_tx, shutdown_rx) = mpsc::channel(1);
         let p2p_sec_key = match testing_config.peer_id {
@@ -275,6 +312,7 @@ impl Cluster {
             },
             p2p: P2pConfig {
                 libp2p_port: Some(libp2p_port),
+                quic_port: None,
                 listen_port: Some(http_port),
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
@@ -290,12 +328,16 @@ impl Cluster {
                         .unwrap_or_default(),
                     ..Default::default()
                 },
+                reputation: P2pReputationConfig::default(),
+                nat: P2pNatConfig::default(),
             },
             transition_frontier: TransitionFrontierConfig::new(testing_config.genesis),
             block_producer: block_producer_config,
             tx_pool: ledger::transaction_pool::Config {
                 trust_system: (),
                 pool_max_size: 3000,
+                max_per_sender: ledger::transaction_pool::DEFAULT_MAX_PER_SENDER,
+                replace_fee: ledger::transaction_pool::DEFAULT_REPLACE_FEE,
                 slot_tx_end: None,
             },
         };
@@ -317,7 +359,7 @@ impl Cluster {
 
         if let Some(keypair) = block_producer_sec_key {
             let provers = BlockProver::make(None, None);
-            service_builder.block_producer_init(keypair, Some(provers));
+            service_builder.block_producer_init(vec![keypair], Some(provers));
         }
 
         let real_service = service_builder
@@ -402,10 +444,7 @@ impl Cluster {
                 .initial_state(rng_seed, p2p_sec_key, store.state.get());
         }
 
-        let node = Node::new(work_dir, node_config, store);
-
-        self.nodes.push(node);
-        node_id
+        Node::new(work_dir, node_config, store)
     }
 
     pub fn add_ocaml_node(&mut self, testing_config: OcamlNodeTestingConfig) -> ClusterOcamlNodeId {
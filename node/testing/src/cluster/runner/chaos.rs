@@ -0,0 +1,722 @@
+//! Seeded fault injection for the cluster runner.
+//!
+//! [`Chaos`] is installed with [`ClusterRunner::set_chaos`] and is then
+//! applied during [`ClusterRunner::run`]: scheduled faults (partitions,
+//! crashes and clock skew) are applied once virtual time reaches them and
+//! pending p2p events are delayed, dropped or reordered. Everything is
+//! derived from the seed, so the same seed reproduces the same schedule.
+//!
+//! [`ClusterRunner`]: super::ClusterRunner
+//! [`ClusterRunner::set_chaos`]: super::ClusterRunner::set_chaos
+//! [`ClusterRunner::run`]: super::ClusterRunner::run
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::RangeInclusive,
+    time::Duration,
+};
+
+use node::{
+    event_source::Event,
+    p2p::{ConnectionAddr, MioEvent, P2pChannelEvent, P2pConnectionEvent, P2pEvent, PeerId},
+    State,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{cluster::ClusterNodeId, scenario::ScenarioStep};
+
+use super::RunDecision;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChaosConfig {
+    pub seed: u64,
+    /// Faults are only scheduled within this window of virtual time,
+    /// counted from the moment chaos is first applied. Everything is
+    /// healed once it elapses.
+    pub duration: Duration,
+    #[serde(default)]
+    pub partitions: Option<ChaosFaultFrequency>,
+    #[serde(default)]
+    pub crashes: Option<ChaosFaultFrequency>,
+    #[serde(default)]
+    pub clock_skew: Option<ChaosClockSkew>,
+    #[serde(default)]
+    pub messages: ChaosMessages,
+}
+
+/// How often a fault starts and how long it lasts, in seconds of virtual
+/// time. Faults of the same kind never overlap.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChaosFaultFrequency {
+    pub every: RangeInclusive<u64>,
+    pub lasts: RangeInclusive<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChaosClockSkew {
+    /// How often a random node's clock is moved forward, in seconds.
+    pub every: RangeInclusive<u64>,
+    /// Maximum skew added at once, in milliseconds.
+    pub max_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChaosMessages {
+    /// Probability of a received message being dropped.
+    ///
+    /// Libp2p connections carry a byte stream, in which a lost chunk
+    /// can't be skipped, so dropping data closes such connection instead.
+    pub drop: f64,
+    /// Probability of a received message being delayed.
+    pub delay: f64,
+    /// Range of the delay in milliseconds.
+    pub delay_ms: RangeInclusive<u64>,
+    /// Process pending events in random order instead of the order they
+    /// arrived in. Order of events within the same connection is kept.
+    pub reorder: bool,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ChaosConfigError {
+    #[error("{0}: period must be a non-empty range of at least 1 second")]
+    InvalidPeriod(&'static str),
+    #[error("{0}: probability must be within [0, 1]")]
+    InvalidProbability(&'static str),
+    #[error("{0}: range must not be empty")]
+    EmptyRange(&'static str),
+}
+
+impl ChaosConfig {
+    /// Checks that the schedule can be generated from the config, which
+    /// otherwise would loop forever or panic.
+    pub fn validate(&self) -> Result<(), ChaosConfigError> {
+        fn period(name: &'static str, range: &RangeInclusive<u64>) -> Result<(), ChaosConfigError> {
+            match !range.is_empty() && *range.start() > 0 {
+                true => Ok(()),
+                false => Err(ChaosConfigError::InvalidPeriod(name)),
+            }
+        }
+        fn probability(name: &'static str, p: f64) -> Result<(), ChaosConfigError> {
+            match (0.0..=1.0).contains(&p) {
+                true => Ok(()),
+                false => Err(ChaosConfigError::InvalidProbability(name)),
+            }
+        }
+
+        if let Some(partitions) = &self.partitions {
+            period("partitions.every", &partitions.every)?;
+            period("partitions.lasts", &partitions.lasts)?;
+        }
+        if let Some(crashes) = &self.crashes {
+            period("crashes.every", &crashes.every)?;
+            period("crashes.lasts", &crashes.lasts)?;
+        }
+        if let Some(clock_skew) = &self.clock_skew {
+            period("clock_skew.every", &clock_skew.every)?;
+        }
+        probability("messages.drop", self.messages.drop)?;
+        probability("messages.delay", self.messages.delay)?;
+        if self.messages.delay_ms.is_empty() {
+            return Err(ChaosConfigError::EmptyRange("messages.delay_ms"));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ChaosMessages {
+    fn default() -> Self {
+        Self {
+            drop: 0.0,
+            delay: 0.0,
+            delay_ms: 10..=1000,
+            reorder: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ChaosScheduledFault {
+    /// Virtual time since chaos was first applied.
+    pub at: Duration,
+    pub fault: ChaosFault,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum ChaosFault {
+    /// Split nodes into groups, which can't reach each other.
+    Partition(Vec<Vec<ClusterNodeId>>),
+    HealPartition,
+    /// Stop the node. It won't process any events and all of its
+    /// connections get closed.
+    Crash(ClusterNodeId),
+    /// Start the crashed node again, with a fresh state.
+    Restart(ClusterNodeId),
+    /// Move node's clock forward.
+    ClockSkew {
+        node_id: ClusterNodeId,
+        by_nanos: u64,
+    },
+    /// Move clocks of all nodes forward, so that they match the one
+    /// which is the furthest ahead.
+    HealClockSkew,
+}
+
+pub struct Chaos {
+    config: ChaosConfig,
+    schedule: Vec<ChaosScheduledFault>,
+    rng: StdRng,
+    started_at: Option<redux::Timestamp>,
+    next_fault: usize,
+    healed: bool,
+    partition: BTreeMap<ClusterNodeId, usize>,
+    crashed: BTreeSet<ClusterNodeId>,
+    skews: BTreeMap<ClusterNodeId, u64>,
+    /// Links with a delayed event, and time at which it will be released.
+    delayed: BTreeMap<(ClusterNodeId, ChaosLink), redux::Timestamp>,
+}
+
+/// Connection through which an event was received. Used to keep order
+/// of events within the same connection and to find the remote node.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum ChaosLink {
+    Connection(ConnectionAddr),
+    Peer(PeerId),
+}
+
+impl Chaos {
+    /// Generates the schedule of faults for the `nodes`. Only nodes in
+    /// `crashable` are crashed.
+    pub fn new(
+        config: ChaosConfig,
+        nodes: impl IntoIterator<Item = ClusterNodeId>,
+        crashable: impl IntoIterator<Item = ClusterNodeId>,
+    ) -> Result<Self, ChaosConfigError> {
+        config.validate()?;
+        let nodes = nodes.into_iter().collect::<Vec<_>>();
+        let crashable = crashable.into_iter().collect::<Vec<_>>();
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut schedule = Vec::new();
+
+        if let Some(frequency) = config.partitions.as_ref().filter(|_| nodes.len() > 1) {
+            for (start, end) in fault_windows(&mut rng, frequency, config.duration) {
+                let mut nodes = nodes.clone();
+                nodes.shuffle(&mut rng);
+                let split = rng.gen_range(1..nodes.len());
+                let (a, b) = nodes.split_at(split);
+                let groups = vec![a.to_vec(), b.to_vec()];
+                schedule.push((start, ChaosFault::Partition(groups)));
+                schedule.push((end, ChaosFault::HealPartition));
+            }
+        }
+
+        if let Some(frequency) = config.crashes.as_ref().filter(|_| !crashable.is_empty()) {
+            for (start, end) in fault_windows(&mut rng, frequency, config.duration) {
+                let node_id = *crashable.choose(&mut rng).unwrap();
+                schedule.push((start, ChaosFault::Crash(node_id)));
+                schedule.push((end, ChaosFault::Restart(node_id)));
+            }
+        }
+
+        if let Some(skew) = config.clock_skew.as_ref().filter(|_| !nodes.is_empty()) {
+            let mut t = Duration::ZERO;
+            loop {
+                t += Duration::from_secs(rng.gen_range(skew.every.clone()));
+                if t >= config.duration {
+                    break;
+                }
+                let node_id = *nodes.choose(&mut rng).unwrap();
+                let by_nanos = rng.gen_range(1..=skew.max_ms.max(1)) * 1_000_000;
+                schedule.push((t, ChaosFault::ClockSkew { node_id, by_nanos }));
+            }
+            schedule.push((config.duration, ChaosFault::HealClockSkew));
+        }
+
+        schedule.sort_by_key(|(at, _)| *at);
+        let schedule = schedule
+            .into_iter()
+            .map(|(at, fault)| ChaosScheduledFault { at, fault })
+            .collect();
+
+        Ok(Self {
+            config,
+            schedule,
+            rng,
+            started_at: None,
+            next_fault: 0,
+            healed: false,
+            partition: Default::default(),
+            crashed: Default::default(),
+            skews: Default::default(),
+            delayed: Default::default(),
+        })
+    }
+
+    pub fn config(&self) -> &ChaosConfig {
+        &self.config
+    }
+
+    pub fn schedule(&self) -> &[ChaosScheduledFault] {
+        &self.schedule
+    }
+
+    /// Whether the chaos duration has elapsed and every scheduled fault
+    /// was healed.
+    pub fn is_healed(&self) -> bool {
+        self.healed
+    }
+
+    pub fn is_crashed(&self, node_id: ClusterNodeId) -> bool {
+        self.crashed.contains(&node_id)
+    }
+
+    /// Whether `from` can currently receive anything from `to`.
+    pub fn can_reach(&self, from: ClusterNodeId, to: ClusterNodeId) -> bool {
+        !self.is_crashed(from)
+            && !self.is_crashed(to)
+            && self.partition.get(&from) == self.partition.get(&to)
+    }
+
+    /// Takes faults which are due at `now`.
+    fn take_due_faults(&mut self, now: redux::Timestamp) -> Vec<ChaosFault> {
+        let started_at = *self.started_at.get_or_insert(now);
+        let elapsed = now.checked_sub(started_at).unwrap_or_default();
+        let due = self.schedule[self.next_fault..]
+            .iter()
+            .take_while(|scheduled| scheduled.at <= elapsed)
+            .map(|scheduled| scheduled.fault.clone())
+            .collect::<Vec<_>>();
+        self.next_fault += due.len();
+        self.healed = elapsed >= self.config.duration && self.next_fault == self.schedule.len();
+        due
+    }
+}
+
+fn fault_windows(
+    rng: &mut StdRng,
+    frequency: &ChaosFaultFrequency,
+    duration: Duration,
+) -> Vec<(Duration, Duration)> {
+    let mut windows = Vec::new();
+    let mut t = Duration::ZERO;
+    loop {
+        t += Duration::from_secs(rng.gen_range(frequency.every.clone()));
+        if t >= duration {
+            return windows;
+        }
+        let end = (t + Duration::from_secs(rng.gen_range(frequency.lasts.clone()))).min(duration);
+        windows.push((t, end));
+        t = end;
+    }
+}
+
+impl ChaosLink {
+    fn of(event: &Event) -> Option<Self> {
+        let Event::P2p(event) = event else {
+            return None;
+        };
+        match event {
+            P2pEvent::MioEvent(event) => match event {
+                MioEvent::IncomingConnectionDidAccept(addr, _) => (*addr).map(Self::Connection),
+                MioEvent::IncomingDataIsReady(addr)
+                | MioEvent::IncomingDataDidReceive(addr, _)
+                | MioEvent::OutgoingConnectionDidConnect(addr, _)
+                | MioEvent::OutgoingDataDidSend(addr, _)
+                | MioEvent::ConnectionDidClose(addr, _)
+                | MioEvent::ConnectionDidCloseOnDemand(addr)
+                | MioEvent::QuicConnectionDidConnect(addr, _)
+                | MioEvent::QuicStreamDidReceive(addr, ..)
+                | MioEvent::QuicStreamDidReset(addr, _) => Some(Self::Connection(*addr)),
+                _ => None,
+            },
+            P2pEvent::Connection(event) => match event {
+                P2pConnectionEvent::OfferSdpReady(peer_id, _)
+                | P2pConnectionEvent::AnswerSdpReady(peer_id, _)
+                | P2pConnectionEvent::AnswerReceived(peer_id, _)
+                | P2pConnectionEvent::Finalized(peer_id, _)
+                | P2pConnectionEvent::Closed(peer_id) => Some(Self::Peer(*peer_id)),
+            },
+            P2pEvent::Channel(event) => match event {
+                P2pChannelEvent::Opened(peer_id, ..)
+                | P2pChannelEvent::Sent(peer_id, ..)
+                | P2pChannelEvent::Received(peer_id, _)
+                | P2pChannelEvent::Closed(peer_id, _) => Some(Self::Peer(*peer_id)),
+            },
+        }
+    }
+
+    /// Whether the event carries data received from the remote peer.
+    fn is_message(event: &Event) -> bool {
+        matches!(
+            event,
+            Event::P2p(
+                P2pEvent::MioEvent(MioEvent::IncomingDataDidReceive(_, Ok(_)))
+                    | P2pEvent::Channel(P2pChannelEvent::Received(_, Ok(_)))
+            )
+        )
+    }
+}
+
+/// Maps peers to nodes in the cluster.
+struct ChaosPeers {
+    by_peer_id: BTreeMap<PeerId, ClusterNodeId>,
+    by_port: BTreeMap<u16, ClusterNodeId>,
+}
+
+impl ChaosPeers {
+    fn remote(&self, state: &State, link: &ChaosLink) -> Option<ClusterNodeId> {
+        match link {
+            ChaosLink::Peer(peer_id) => self.by_peer_id.get(peer_id).copied(),
+            ChaosLink::Connection(addr) => {
+                let connection = state.p2p.ready()?.network.scheduler.connections.get(addr);
+                match connection.and_then(|c| c.peer_id()) {
+                    Some(peer_id) => self.by_peer_id.get(peer_id).copied(),
+                    // all nodes listen on localhost, so the port is enough
+                    // to find the node we dialed.
+                    None if !addr.incoming => self.by_port.get(&addr.sock_addr.port()).copied(),
+                    None => None,
+                }
+            }
+        }
+    }
+}
+
+fn closed_event(link: &ChaosLink, reason: &str) -> Event {
+    let event = match link {
+        ChaosLink::Connection(addr) => {
+            P2pEvent::MioEvent(MioEvent::ConnectionDidClose(*addr, Err(reason.to_owned())))
+        }
+        ChaosLink::Peer(peer_id) => P2pEvent::Connection(P2pConnectionEvent::Closed(*peer_id)),
+    };
+    Event::P2p(event)
+}
+
+impl<'a> super::ClusterRunner<'a> {
+    /// Enable chaos for the following `run` calls.
+    pub fn set_chaos(&mut self, chaos: Chaos) {
+        self.chaos = Some(chaos);
+    }
+
+    pub fn chaos(&self) -> Option<&Chaos> {
+        self.chaos.as_ref()
+    }
+
+    pub fn take_chaos(&mut self) -> Option<Chaos> {
+        self.chaos.take()
+    }
+
+    pub(super) fn is_node_crashed(&self, node_id: ClusterNodeId) -> bool {
+        self.chaos.as_ref().map_or(false, |c| c.is_crashed(node_id))
+    }
+
+    /// Current virtual time, as seen by nodes which aren't crashed.
+    fn chaos_now(&self) -> Option<redux::Timestamp> {
+        self.nodes_iter()
+            .filter(|(node_id, _)| !self.is_node_crashed(*node_id))
+            .map(|(_, node)| node.state().time())
+            .min()
+    }
+
+    fn chaos_peers(&self) -> ChaosPeers {
+        ChaosPeers {
+            by_peer_id: self
+                .nodes_iter()
+                .map(|(node_id, node)| (node.peer_id(), node_id))
+                .collect(),
+            by_port: self
+                .nodes_iter()
+                .filter_map(|(node_id, node)| {
+                    Some((node.state().p2p.ready()?.config.libp2p_port?, node_id))
+                })
+                .collect(),
+        }
+    }
+
+    /// Applies faults which are due and closes connections, which must
+    /// not exist under current faults.
+    pub(super) async fn chaos_apply(&mut self) {
+        let Some(now) = self.chaos.as_ref().and(self.chaos_now()) else {
+            return;
+        };
+        let faults = match self.chaos.as_mut() {
+            Some(chaos) => chaos.take_due_faults(now),
+            None => return,
+        };
+        for fault in faults {
+            eprintln!("[chaos] {fault:?}");
+            self.chaos_apply_fault(fault, now).await;
+        }
+        self.chaos_cut_links().await;
+    }
+
+    async fn chaos_apply_fault(&mut self, fault: ChaosFault, now: redux::Timestamp) {
+        let Some(chaos) = self.chaos.as_mut() else {
+            return;
+        };
+        match fault {
+            ChaosFault::Partition(groups) => {
+                chaos.partition = groups
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(i, group)| group.into_iter().map(move |node_id| (node_id, i)))
+                    .collect();
+            }
+            ChaosFault::HealPartition => chaos.partition.clear(),
+            ChaosFault::Crash(node_id) => {
+                chaos.crashed.insert(node_id);
+                chaos.delayed.retain(|(id, _), _| *id != node_id);
+            }
+            ChaosFault::Restart(node_id) => {
+                chaos.crashed.remove(&node_id);
+                chaos.skews.remove(&node_id);
+                self.cluster.restart_rust_node(node_id, now).unwrap();
+            }
+            ChaosFault::ClockSkew { node_id, by_nanos } => {
+                if chaos.is_crashed(node_id) {
+                    return;
+                }
+                *chaos.skews.entry(node_id).or_default() += by_nanos;
+                self.exec_step(ScenarioStep::AdvanceNodeTime { node_id, by_nanos })
+                    .await
+                    .unwrap();
+            }
+            ChaosFault::HealClockSkew => {
+                let skews = std::mem::take(&mut chaos.skews);
+                let max_skew = skews.values().copied().max().unwrap_or_default();
+                let nodes = self
+                    .nodes_iter()
+                    .map(|(node_id, _)| node_id)
+                    .filter(|node_id| !self.is_node_crashed(*node_id))
+                    .collect::<Vec<_>>();
+                for node_id in nodes {
+                    let by_nanos = max_skew - skews.get(&node_id).copied().unwrap_or_default();
+                    if by_nanos > 0 {
+                        self.exec_step(ScenarioStep::AdvanceNodeTime { node_id, by_nanos })
+                            .await
+                            .unwrap();
+                    }
+                }
+            }
+        }
+    }
+
+    /// Closes connections between nodes, which can't reach each other.
+    async fn chaos_cut_links(&mut self) {
+        let Some(chaos) = self.chaos.as_ref() else {
+            return;
+        };
+        let peers = self.chaos_peers();
+        let mut to_close = Vec::new();
+        for (node_id, node) in self.nodes_iter() {
+            if chaos.is_crashed(node_id) {
+                continue;
+            }
+            let state = node.state();
+            let Some(p2p) = state.p2p.ready() else {
+                continue;
+            };
+            let connections = p2p
+                .network
+                .scheduler
+                .connections
+                .iter()
+                .filter(|(_, connection)| connection.closed.is_none())
+                .map(|(addr, _)| ChaosLink::Connection(*addr));
+            let webrtc_peers = p2p
+                .peers
+                .iter()
+                .filter(|(_, peer)| !peer.is_libp2p() && peer.status.as_ready().is_some())
+                .map(|(peer_id, _)| ChaosLink::Peer(*peer_id));
+            for link in connections.chain(webrtc_peers) {
+                if let Some(remote) = peers.remote(state, &link) {
+                    if !chaos.can_reach(node_id, remote) {
+                        to_close.push((node_id, link));
+                    }
+                }
+            }
+        }
+
+        for (node_id, link) in to_close {
+            let event = closed_event(&link, "chaos: link is cut");
+            self.exec_step(ScenarioStep::ManualEvent {
+                node_id,
+                event: Box::new(event),
+            })
+            .await
+            .unwrap();
+        }
+    }
+
+    /// Picks the next event to be executed, delaying, dropping and
+    /// reordering events as configured.
+    pub(super) async fn chaos_pick_event<EH>(
+        &mut self,
+        handle_event: &mut EH,
+    ) -> Option<(ClusterNodeId, String, RunDecision)>
+    where
+        EH: FnMut(ClusterNodeId, &State, &Event) -> RunDecision,
+    {
+        let now = self.chaos_now()?;
+        let peers = self.chaos_peers();
+        let chaos = self.chaos.as_mut()?;
+        let reorder = chaos.config.messages.reorder && !chaos.healed;
+        let mut links = BTreeSet::new();
+        let mut unreachable = Vec::new();
+        let mut candidates = Vec::new();
+
+        'nodes: for (node_id, state, events) in self.cluster.pending_events(true) {
+            if chaos.is_crashed(node_id) {
+                continue;
+            }
+            for (event_id, event) in events {
+                let link = ChaosLink::of(event);
+                if let Some(link) = &link {
+                    // only the first event of the connection can be
+                    // executed, so that the order within it is kept.
+                    if !links.insert((node_id, link.clone())) {
+                        continue;
+                    }
+                    let key = (node_id, link.clone());
+                    if chaos.delayed.get(&key).map_or(false, |until| *until > now) {
+                        continue;
+                    }
+                    let remote = peers.remote(state, link);
+                    if ChaosLink::is_message(event)
+                        && remote.map_or(false, |remote| !chaos.can_reach(node_id, remote))
+                    {
+                        unreachable.push((node_id, event_id));
+                        continue;
+                    }
+                }
+                let decision = handle_event(node_id, state, event);
+                if decision.stop() || decision.exec() {
+                    let is_message = ChaosLink::is_message(event);
+                    candidates.push((node_id, event_id, link, is_message, decision));
+                    if !reorder {
+                        break 'nodes;
+                    }
+                }
+            }
+        }
+
+        let mut dropped = None;
+        let picked = (!candidates.is_empty()).then(|| {
+            let i = match reorder {
+                true => chaos.rng.gen_range(0..candidates.len()),
+                false => 0,
+            };
+            candidates.swap_remove(i)
+        });
+        let picked = picked.and_then(|(node_id, event_id, link, is_message, decision)| {
+            let Some(link) = link else {
+                return Some((node_id, event_id, decision));
+            };
+            let key = (node_id, link);
+            // event which was already delayed is executed as is.
+            if chaos.delayed.remove(&key).is_some() || !is_message || chaos.healed {
+                return Some((node_id, event_id, decision));
+            }
+            let messages = &chaos.config.messages;
+            if chaos.rng.gen_bool(messages.drop) {
+                dropped = Some((node_id, event_id, key.1));
+                None
+            } else if chaos.rng.gen_bool(messages.delay) {
+                let by_millis = chaos.rng.gen_range(messages.delay_ms.clone());
+                let until = now + Duration::from_millis(by_millis);
+                chaos.delayed.insert(key, until);
+                None
+            } else {
+                Some((node_id, event_id, decision))
+            }
+        });
+
+        for (node_id, event_id) in unreachable {
+            self.node_mut(node_id).unwrap().take_pending_event(event_id);
+        }
+        if let Some((node_id, event_id, link)) = dropped {
+            self.node_mut(node_id).unwrap().take_pending_event(event_id);
+            if let ChaosLink::Connection(_) = link {
+                let event = closed_event(&link, "chaos: data dropped");
+                self.exec_step(ScenarioStep::ManualEvent {
+                    node_id,
+                    event: Box::new(event),
+                })
+                .await
+                .unwrap();
+            }
+        }
+
+        let (node_id, event_id, decision) = picked?;
+        let event = self.node(node_id)?.get_pending_event(event_id)?.to_string();
+        Some((node_id, event, decision))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(seed: u64) -> ChaosConfig {
+        ChaosConfig {
+            seed,
+            duration: Duration::from_secs(30 * 60),
+            partitions: Some(ChaosFaultFrequency {
+                every: 60..=300,
+                lasts: 30..=180,
+            }),
+            crashes: Some(ChaosFaultFrequency {
+                every: 120..=400,
+                lasts: 30..=120,
+            }),
+            clock_skew: Some(ChaosClockSkew {
+                every: 60..=240,
+                max_ms: 2000,
+            }),
+            messages: Default::default(),
+        }
+    }
+
+    fn schedule(config: ChaosConfig) -> String {
+        let nodes = (0..4).map(ClusterNodeId::new_unchecked);
+        let crashable = (2..4).map(ClusterNodeId::new_unchecked);
+        let chaos = Chaos::new(config, nodes, crashable).unwrap();
+        format!("{:?}", chaos.schedule())
+    }
+
+    #[test]
+    fn test_same_seed_same_schedule() {
+        assert_eq!(schedule(config(1)), schedule(config(1)));
+        assert_ne!(schedule(config(1)), schedule(config(2)));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut cfg = config(1);
+        cfg.partitions = Some(ChaosFaultFrequency {
+            every: 0..=0,
+            lasts: 0..=0,
+        });
+        assert!(matches!(
+            cfg.validate(),
+            Err(ChaosConfigError::InvalidPeriod("partitions.every"))
+        ));
+
+        let mut cfg = config(1);
+        cfg.clock_skew = Some(ChaosClockSkew {
+            every: 10..=5,
+            max_ms: 10,
+        });
+        assert!(cfg.validate().is_err());
+
+        let mut cfg = config(1);
+        cfg.messages.drop = 1.5;
+        assert!(matches!(
+            cfg.validate(),
+            Err(ChaosConfigError::InvalidProbability("messages.drop"))
+        ));
+
+        let mut cfg = config(1);
+        cfg.messages.delay = f64::NAN;
+        assert!(cfg.validate().is_err());
+    }
+}
@@ -1,6 +1,9 @@
 mod run;
 pub use run::*;
 
+mod chaos;
+pub use chaos::*;
+
 use std::{path::PathBuf, time::Duration};
 
 use ledger::BaseLedger;
@@ -26,6 +29,7 @@ pub struct ClusterRunner<'a> {
     add_step: Box<dyn 'a + Send + FnMut(&ScenarioStep)>,
     rng: StdRng,
     latest_advance_time: Option<redux::Timestamp>,
+    chaos: Option<Chaos>,
 }
 
 impl<'a> ClusterRunner<'a> {
@@ -38,6 +42,7 @@ impl<'a> ClusterRunner<'a> {
             add_step: Box::new(add_step),
             rng: StdRng::seed_from_u64(0),
             latest_advance_time: None,
+            chaos: None,
         }
     }
 
@@ -83,21 +83,26 @@ impl<'a> super::ClusterRunner<'a> {
         ) as DynEffects;
         tokio::time::timeout(timeout, async move {
             while !dyn_effects_data.inner().exit {
-                let event_to_take_action_on = self
-                    .pending_events(true)
-                    .flat_map(|(node_id, state, events)| {
-                        events.map(move |event| (node_id, state, event))
-                    })
-                    .map(|(node_id, state, (_, event))| {
-                        let decision = handle_event(node_id, state, event);
-                        (node_id, state, event, decision)
-                    })
-                    .find(|(_, _, _, decision)| decision.stop() || decision.exec());
+                self.chaos_apply().await;
 
-                if let Some((node_id, _, event, decision)) = event_to_take_action_on {
+                let event_to_take_action_on = if self.chaos.is_some() {
+                    self.chaos_pick_event(&mut handle_event).await
+                } else {
+                    self.pending_events(true)
+                        .flat_map(|(node_id, state, events)| {
+                            events.map(move |event| (node_id, state, event))
+                        })
+                        .map(|(node_id, state, (_, event))| {
+                            let decision = handle_event(node_id, state, event);
+                            (node_id, event, decision)
+                        })
+                        .find(|(_, _, decision)| decision.stop() || decision.exec())
+                        .map(|(node_id, event, decision)| (node_id, event.to_string(), decision))
+                };
+
+                if let Some((node_id, event, decision)) = event_to_take_action_on {
                     dyn_effects_data.inner().node_id = Some(node_id);
                     if decision.exec() {
-                        let event = event.to_string();
                         dyn_effects = self
                             .exec_step_with_dyn_effects(
                                 dyn_effects,
@@ -138,7 +143,11 @@ impl<'a> super::ClusterRunner<'a> {
                         .unwrap();
                 }
 
-                let all_nodes = self.nodes_iter().map(|(id, _)| id).collect::<Vec<_>>();
+                let all_nodes = self
+                    .nodes_iter()
+                    .map(|(id, _)| id)
+                    .filter(|id| !self.is_node_crashed(*id))
+                    .collect::<Vec<_>>();
                 for node_id in all_nodes {
                     dyn_effects_data.inner().node_id = Some(node_id);
                     dyn_effects = self
@@ -153,7 +162,7 @@ impl<'a> super::ClusterRunner<'a> {
                     }
                 }
 
-                if advance_time.is_some() {
+                if advance_time.is_some() || self.chaos.is_some() {
                     self.wait_for_pending_events_with_timeout(Duration::from_millis(100))
                         .await;
                 } else {
@@ -2,7 +2,9 @@ mod config;
 pub use config::*;
 use mina_p2p_messages::v2::StateHash;
 use node::p2p::{
-    connection::outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
+    connection::outgoing::{
+        P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+    },
     PeerId,
 };
 use openmina_core::{thread, ChainId};
@@ -165,6 +167,7 @@ impl OcamlNode {
             peer_id: self.peer_id(),
             host: [127, 0, 0, 1].into(),
             port: self.libp2p_port,
+            transport: P2pLibp2pTransport::Tcp,
         })
     }
 
@@ -6,7 +6,7 @@ pub use event::*;
 
 use node::event_source::EventSourceAction;
 use node::p2p::connection::outgoing::{
-    P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts,
+    P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
 };
 use node::p2p::webrtc::SignalingMethod;
 use node::p2p::PeerId;
@@ -72,6 +72,7 @@ impl Node {
                 peer_id,
                 host: node::p2p::webrtc::Host::Ipv4([127, 0, 0, 1].into()),
                 port: self.store.state().p2p.config().libp2p_port.unwrap(),
+                transport: P2pLibp2pTransport::Tcp,
             };
             P2pConnectionOutgoingInitOpts::LibP2P(opts)
         }
@@ -32,7 +32,9 @@ use self::multi_node::connection_discovery::RustNodeAsSeed as P2pConnectionDisco
 use self::multi_node::connection_discovery::{
     OCamlToRust, OCamlToRustViaSeed, RustToOCaml, RustToOCamlViaSeed,
 };
-use self::multi_node::pubsub_advanced::MultiNodePubsubPropagateBlock;
+use self::multi_node::pubsub_advanced::{
+    MultiNodePubsubMeshMaintenance, MultiNodePubsubPropagateBlock,
+};
 use self::multi_node::sync_4_block_producers::MultiNodeSync4BlockProducers;
 use self::multi_node::vrf_correct_ledgers::MultiNodeVrfGetCorrectLedgers;
 use self::multi_node::vrf_correct_slots::MultiNodeVrfGetCorrectSlots;
@@ -55,6 +57,7 @@ use self::p2p::pubsub::P2pReceiveBlock;
 use self::p2p::signaling::P2pSignaling;
 use self::record_replay::block_production::RecordReplayBlockProduction;
 use self::record_replay::bootstrap::RecordReplayBootstrap;
+use self::simulation::chaos::SimulationChaos;
 use self::simulation::small::SimulationSmall;
 use self::simulation::small_forever_real_time::SimulationSmallForeverRealTime;
 use self::solo_node::sync_to_genesis::SoloNodeSyncToGenesis;
@@ -83,10 +86,12 @@ pub enum Scenarios {
     MultiNodeBasicConnectivityPeerDiscovery(MultiNodeBasicConnectivityPeerDiscovery),
     SimulationSmall(SimulationSmall),
     SimulationSmallForeverRealTime(SimulationSmallForeverRealTime),
+    SimulationChaos(SimulationChaos),
     P2pReceiveBlock(P2pReceiveBlock),
     P2pSignaling(P2pSignaling),
     P2pConnectionDiscoveryRustNodeAsSeed(P2pConnectionDiscoveryRustNodeAsSeed),
     MultiNodePubsubPropagateBlock(MultiNodePubsubPropagateBlock),
+    MultiNodePubsubMeshMaintenance(MultiNodePubsubMeshMaintenance),
     RecordReplayBootstrap(RecordReplayBootstrap),
     RecordReplayBlockProduction(RecordReplayBlockProduction),
 
@@ -130,6 +135,7 @@ impl Scenarios {
             Self::MultiNodeBasicConnectivityPeerDiscovery(_) => cfg!(feature = "p2p-webrtc"),
             Self::SimulationSmall(_) => true,
             Self::SimulationSmallForeverRealTime(_) => true,
+            Self::SimulationChaos(_) => true,
             Self::MultiNodePubsubPropagateBlock(_) => true, // in progress
             Self::P2pSignaling(_) => cfg!(feature = "p2p-webrtc"),
             _ => false,
@@ -189,12 +195,14 @@ impl Scenarios {
             }
             Self::SimulationSmall(_) => SimulationSmall::DOCS,
             Self::SimulationSmallForeverRealTime(_) => SimulationSmallForeverRealTime::DOCS,
+            Self::SimulationChaos(_) => SimulationChaos::DOCS,
             Self::P2pReceiveBlock(_) => P2pReceiveBlock::DOCS,
             Self::P2pSignaling(_) => P2pSignaling::DOCS,
             Self::P2pConnectionDiscoveryRustNodeAsSeed(_) => {
                 P2pConnectionDiscoveryRustNodeAsSeed::DOCS
             }
             Self::MultiNodePubsubPropagateBlock(_) => MultiNodePubsubPropagateBlock::DOCS,
+            Self::MultiNodePubsubMeshMaintenance(_) => MultiNodePubsubMeshMaintenance::DOCS,
             Self::RecordReplayBootstrap(_) => RecordReplayBootstrap::DOCS,
             Self::RecordReplayBlockProduction(_) => RecordReplayBlockProduction::DOCS,
 
@@ -260,10 +268,12 @@ impl Scenarios {
             Self::MultiNodeBasicConnectivityPeerDiscovery(v) => v.run(runner).await,
             Self::SimulationSmall(v) => v.run(runner).await,
             Self::SimulationSmallForeverRealTime(v) => v.run(runner).await,
+            Self::SimulationChaos(v) => v.run(runner).await,
             Self::P2pReceiveBlock(v) => v.run(runner).await,
             Self::P2pSignaling(v) => v.run(runner).await,
             Self::P2pConnectionDiscoveryRustNodeAsSeed(v) => v.run(runner).await,
             Self::MultiNodePubsubPropagateBlock(v) => v.run(runner).await,
+            Self::MultiNodePubsubMeshMaintenance(v) => v.run(runner).await,
             Self::RecordReplayBootstrap(v) => v.run(runner).await,
             Self::RecordReplayBlockProduction(v) => v.run(runner).await,
 
@@ -12,7 +12,9 @@ use node::{
 };
 
 use crate::{
-    node::Recorder,
+    cluster::ClusterNodeId,
+    node::{NonDeterministicEvent, Recorder, RustNodeTestingConfig},
+    scenario::ScenarioStep,
     scenarios::{ClusterRunner, RunCfgAdvanceTime},
     service::NodeTestingService,
     simulator::{Simulator, SimulatorConfig, SimulatorRunUntil},
@@ -101,6 +103,7 @@ impl MultiNodePubsubPropagateBlock {
             run_until: SimulatorRunUntil::BlockchainLength(4),
             run_until_timeout: Duration::from_secs(10 * 60),
             recorder: Recorder::StateWithInputActions,
+            chaos: None,
         };
         let mut simulator = Simulator::new(initial_time, config);
         simulator
@@ -110,3 +113,179 @@ impl MultiNodePubsubPropagateBlock {
         println!("{}}}\n", graph.lock().unwrap());
     }
 }
+
+/// Check that heartbeats keep pubsub meshes healthy, and that they recover after churn.
+///
+/// 1. Launch a seed node and `TOTAL_PEERS` nodes connecting to it.
+/// 2. Wait until the mesh of every node has at least `outbound_degree_low`
+///    peers, or all of the peers it can graft if there are fewer of them.
+/// 3. Disconnect mesh peers of one node until its mesh is below `outbound_degree_low`.
+/// 4. Wait until its mesh is built up again.
+///
+/// Fail the test if a mesh exceeds `outbound_degree_high`, or has a peer that is
+/// still backing off after a prune, on any step.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct MultiNodePubsubMeshMaintenance;
+
+impl MultiNodePubsubMeshMaintenance {
+    const TOTAL_PEERS: usize = 12;
+    const MAX_PEERS_PER_NODE: usize = 12;
+    const STEPS: usize = 1000;
+    const STEP_DELAY: Duration = Duration::from_millis(200);
+
+    pub async fn run(self, mut runner: ClusterRunner<'_>) {
+        let seed_node = runner.add_rust_node(
+            RustNodeTestingConfig::devnet_default().max_peers(Self::TOTAL_PEERS + 1),
+        );
+        eprintln!("launch Openmina seed node, id: {seed_node}");
+
+        let mut nodes = vec![seed_node];
+        for _ in 0..Self::TOTAL_PEERS {
+            let node = runner.add_rust_node(
+                RustNodeTestingConfig::devnet_default()
+                    .max_peers(Self::MAX_PEERS_PER_NODE)
+                    .initial_peers(vec![seed_node.into()]),
+            );
+            eprintln!("launch Openmina node, id: {node}, connects to {seed_node}");
+            nodes.push(node);
+        }
+
+        let churn_node = nodes[1];
+        let mut churned = false;
+
+        for _ in 0..Self::STEPS {
+            tokio::time::sleep(Self::STEP_DELAY).await;
+            Self::advance(&mut runner, &nodes).await;
+
+            let meshes_complete = nodes
+                .iter()
+                .all(|node_id| Self::check_mesh(&runner, *node_id));
+            if !meshes_complete {
+                continue;
+            }
+            if churned {
+                eprintln!("success");
+                return;
+            }
+
+            let mesh = Self::mesh_peers(&runner, churn_node);
+            let degree_low = runner
+                .node(churn_node)
+                .expect("node must exist")
+                .state()
+                .p2p
+                .unwrap()
+                .config
+                .meshsub
+                .outbound_degree_low;
+            let disconnect = mesh.len().saturating_sub(degree_low / 2);
+            eprintln!(
+                "meshes are complete, disconnect {disconnect} of {} mesh peers of node {churn_node}",
+                mesh.len()
+            );
+            for peer_id in mesh.into_iter().take(disconnect) {
+                runner
+                    .exec_step(ScenarioStep::NonDeterministicEvent {
+                        node_id: churn_node,
+                        event: Box::new(NonDeterministicEvent::P2pConnectionClosed(peer_id)),
+                    })
+                    .await
+                    .unwrap();
+            }
+            churned = true;
+        }
+
+        panic!("pubsub meshes are not complete, churned: {churned}");
+    }
+
+    async fn advance(runner: &mut ClusterRunner<'_>, nodes: &[ClusterNodeId]) {
+        let mut steps = vec![];
+        for &node_id in nodes {
+            let node_steps = runner
+                .node_pending_events(node_id, true)
+                .unwrap()
+                .1
+                .map(|(_, event)| ScenarioStep::Event {
+                    node_id,
+                    event: event.to_string(),
+                })
+                .collect::<Vec<_>>();
+            steps.extend(node_steps);
+        }
+        for step in steps {
+            runner.exec_step(step).await.unwrap();
+        }
+
+        for &node_id in nodes {
+            runner
+                .exec_step(ScenarioStep::AdvanceNodeTime {
+                    node_id,
+                    by_nanos: Self::STEP_DELAY.as_nanos() as _,
+                })
+                .await
+                .unwrap();
+            runner
+                .exec_step(ScenarioStep::CheckTimeouts { node_id })
+                .await
+                .unwrap();
+        }
+    }
+
+    fn mesh_peers(runner: &ClusterRunner<'_>, node_id: ClusterNodeId) -> Vec<PeerId> {
+        let node = runner.node(node_id).expect("node must exist");
+        let Some(p2p) = node.state().p2p.ready() else {
+            return vec![];
+        };
+        let pubsub = &p2p.network.scheduler.broadcast_state;
+        pubsub
+            .topics
+            .values()
+            .flat_map(|peers| peers.iter())
+            .filter(|(_, state)| state.on_mesh())
+            .map(|(peer_id, _)| *peer_id)
+            .collect()
+    }
+
+    /// Asserts the mesh invariants, and returns whether the node's meshes are complete.
+    fn check_mesh(runner: &ClusterRunner<'_>, node_id: ClusterNodeId) -> bool {
+        let node = runner.node(node_id).expect("node must exist");
+        let Some(p2p) = node.state().p2p.ready() else {
+            return false;
+        };
+        let now = node.state().time();
+        let meshsub = &p2p.config.meshsub;
+        let pubsub = &p2p.network.scheduler.broadcast_state;
+
+        let mut complete = !pubsub.topics.is_empty();
+        for (topic_id, peers) in &pubsub.topics {
+            let mesh = peers
+                .iter()
+                .filter(|(_, state)| state.on_mesh())
+                .map(|(peer_id, _)| peer_id)
+                .collect::<Vec<_>>();
+            assert!(
+                mesh.len() <= meshsub.outbound_degree_high,
+                "node {node_id} mesh for {topic_id} has {} peers",
+                mesh.len()
+            );
+            for peer_id in &mesh {
+                assert!(
+                    !pubsub.is_backoff(topic_id, peer_id, now),
+                    "node {node_id} has {peer_id} in mesh for {topic_id} during backoff"
+                );
+            }
+
+            let graftable = peers
+                .keys()
+                .filter(|peer_id| {
+                    pubsub
+                        .clients
+                        .get(peer_id)
+                        .map_or(false, |client| client.outgoing_stream_id.is_some())
+                })
+                .count();
+            complete &= mesh.len() >= meshsub.outbound_degree_low.min(graftable);
+        }
+        complete
+    }
+}
@@ -62,11 +62,7 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
 
         let producer_node = runner.add_rust_node(RustNodeTestingConfig {
             block_producer: Some(RustNodeBlockProducerTestingConfig {
-                config: BlockProducerConfig {
-                    pub_key: sec_key.public_key().into(),
-                    custom_coinbase_receiver: None,
-                    proposed_protocol_version: None,
-                },
+                config: BlockProducerConfig::new(sec_key.public_key().into()),
                 sec_key: sec_key.clone(),
             }),
             ..rust_config.clone()
@@ -139,7 +135,8 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
             let (_, balance) = pending_evaluation
                 .epoch_data
                 .delegator_table
-                .get(&AccountIndex(1))
+                .values()
+                .find_map(|delegators| delegators.get(&AccountIndex(1)))
                 .expect("Account not found");
             eprintln!("Initial balance: {balance}");
             *balance
@@ -235,7 +232,8 @@ impl MultiNodeVrfEpochBoundsCorrectLedger {
             let (_, balance) = pending_evaluation
                 .epoch_data
                 .delegator_table
-                .get(&AccountIndex(1))
+                .values()
+                .find_map(|delegators| delegators.get(&AccountIndex(1)))
                 .expect("Account not found");
             eprintln!("New balance: {balance}");
             *balance
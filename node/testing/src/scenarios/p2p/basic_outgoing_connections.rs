@@ -4,7 +4,9 @@ use std::{
 };
 
 use node::p2p::{
-    connection::outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
+    connection::outgoing::{
+        P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+    },
     identity::SecretKey,
     P2pPeerStatus, P2pTimeouts, PeerId,
 };
@@ -25,6 +27,7 @@ fn custom_listener(peer_id: PeerId, port: u16) -> ListenerNode {
         peer_id,
         host: node::p2p::webrtc::Host::Ipv4([127, 0, 0, 1].into()),
         port,
+        transport: P2pLibp2pTransport::Tcp,
     })
     .into()
 }
@@ -181,6 +184,7 @@ impl DontConnectToSelfInitialPeer {
                 peer_id,
                 host: node::p2p::webrtc::Host::Ipv4([127, 0, 0, 1].into()),
                 port,
+                transport: P2pLibp2pTransport::Tcp,
             });
         let (node_ut, _) = driver.add_rust_node(
             RustNodeTestingConfig::devnet_default()
@@ -378,6 +382,7 @@ impl ConnectToUnavailableInitialPeers {
                         peer_id,
                         host: [127, 0, 0, 1].into(),
                         port,
+                        transport: P2pLibp2pTransport::Tcp,
                     }
                     .into(),
                 );
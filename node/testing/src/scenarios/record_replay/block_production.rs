@@ -37,6 +37,7 @@ impl RecordReplayBlockProduction {
             run_until: SimulatorRunUntil::BlockchainLength(10),
             run_until_timeout: Duration::from_secs(10 * 60),
             recorder: Recorder::StateWithInputActions,
+            chaos: None,
         };
         let mut simulator = Simulator::new(initial_time, cfg);
         simulator.setup_and_run(&mut runner).await;
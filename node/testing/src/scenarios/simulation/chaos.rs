@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use mina_p2p_messages::v2::{BlockTimeTimeStableV1, PROTOCOL_CONSTANTS};
+use node::transition_frontier::genesis::{GenesisConfig, NonStakers};
+
+use crate::{
+    scenarios::{
+        ChaosClockSkew, ChaosConfig, ChaosFaultFrequency, ChaosMessages, ClusterRunner, RunCfg,
+        RunCfgAdvanceTime,
+    },
+    simulator::{Simulator, SimulatorConfig, SimulatorRunUntil},
+};
+
+/// Block producers under chaos.
+///
+/// Run **4** block producers and **2** seed nodes for **30 minutes** of
+/// virtual time under a seeded schedule of faults:
+///
+/// - network partitions,
+/// - crashes and restarts of block producers,
+/// - clock skew,
+/// - delayed, dropped and reordered messages.
+///
+/// Node invariants are checked on every action. After the chaos is healed,
+/// all nodes must converge to the same best tip.
+///
+/// Seed can be overridden with `CHAOS_SEED` env var.
+#[derive(documented::Documented, Default, Clone, Copy)]
+pub struct SimulationChaos;
+
+impl SimulationChaos {
+    const DEFAULT_SEED: u64 = 0;
+    const CONVERGENCE_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+    pub async fn run(self, mut runner: ClusterRunner<'_>) {
+        let seed = std::env::var("CHAOS_SEED")
+            .ok()
+            .map(|seed| seed.parse().expect("CHAOS_SEED must be a number"))
+            .unwrap_or(Self::DEFAULT_SEED);
+        eprintln!("[chaos] seed: {seed}");
+
+        let initial_time = redux::Timestamp::global_now();
+        let mut constants = PROTOCOL_CONSTANTS.clone();
+        constants.genesis_state_timestamp =
+            BlockTimeTimeStableV1((u64::from(initial_time) / 1_000_000).into());
+        let genesis_cfg = GenesisConfig::Counts {
+            whales: 2,
+            fish: 2,
+            non_stakers: NonStakers::None,
+            constants,
+        };
+        let chaos = ChaosConfig {
+            seed,
+            duration: Duration::from_secs(30 * 60),
+            partitions: Some(ChaosFaultFrequency {
+                every: 60..=300,
+                lasts: 30..=180,
+            }),
+            crashes: Some(ChaosFaultFrequency {
+                every: 120..=400,
+                lasts: 30..=120,
+            }),
+            clock_skew: Some(ChaosClockSkew {
+                every: 60..=240,
+                max_ms: 2000,
+            }),
+            messages: ChaosMessages {
+                drop: 0.001,
+                delay: 0.05,
+                delay_ms: 10..=2000,
+                reorder: true,
+            },
+        };
+        let cfg = SimulatorConfig {
+            genesis: genesis_cfg.into(),
+            seed_nodes: 2,
+            normal_nodes: 0,
+            snark_workers: 0,
+            block_producers: 4,
+            advance_time: RunCfgAdvanceTime::Rand(10..=200),
+            run_until: SimulatorRunUntil::BlockchainLength(5),
+            run_until_timeout: Duration::from_secs(60 * 60),
+            recorder: Default::default(),
+            chaos: Some(chaos),
+        };
+        let mut simulator = Simulator::new(initial_time, cfg);
+        simulator.setup_and_run(&mut runner).await;
+
+        assert!(runner.chaos().map_or(false, |chaos| chaos.is_healed()));
+        eprintln!("[chaos] healed, waiting for nodes to converge");
+        Self::wait_for_convergence(&mut runner).await;
+    }
+
+    /// Runs the cluster until all nodes are synced to the same best tip.
+    async fn wait_for_convergence(runner: &mut ClusterRunner<'_>) {
+        let start_t = redux::Instant::now();
+        while start_t.elapsed() < Self::CONVERGENCE_TIMEOUT {
+            let cfg = RunCfg::default()
+                .advance_time(RunCfgAdvanceTime::Rand(10..=200))
+                .timeout(Duration::ZERO);
+            let _ = runner.run(cfg).await;
+
+            let mut best_tips = runner.nodes_iter().map(|(_, node)| {
+                let state = node.state();
+                state
+                    .transition_frontier
+                    .sync
+                    .is_synced()
+                    .then(|| state.transition_frontier.best_tip())
+                    .flatten()
+                    .map(|best_tip| best_tip.hash().clone())
+            });
+            let first = best_tips.next().flatten();
+            if first.is_some() && best_tips.all(|best_tip| best_tip == first) {
+                eprintln!("[chaos] all nodes converged to {}", first.unwrap());
+                return;
+            }
+        }
+
+        for (node_id, node) in runner.nodes_iter() {
+            let best_tip = node.state().transition_frontier.best_tip();
+            eprintln!(
+                "[node_status] node_{node_id} {:?}",
+                best_tip.map(|tip| (tip.height(), tip.hash().to_string()))
+            );
+        }
+        panic!("nodes didn't converge after chaos was healed");
+    }
+}
@@ -1,2 +1,3 @@
+pub mod chaos;
 pub mod small;
 pub mod small_forever_real_time;
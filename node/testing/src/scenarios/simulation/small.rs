@@ -41,6 +41,7 @@ impl SimulationSmall {
             run_until: SimulatorRunUntil::Epoch(3),
             run_until_timeout: Duration::from_secs(30 * 60),
             recorder: Default::default(),
+            chaos: None,
         };
         let mut simulator = Simulator::new(initial_time, cfg);
         simulator.setup_and_run(&mut runner).await;
@@ -41,6 +41,7 @@ impl SimulationSmallForeverRealTime {
             run_until: SimulatorRunUntil::Forever,
             run_until_timeout: Duration::MAX,
             recorder: Default::default(),
+            chaos: None,
         };
         let mut simulator = Simulator::new(initial_time, cfg);
         simulator.setup_and_run(&mut runner).await;
@@ -28,7 +28,8 @@ use node::p2p::service_impl::webrtc_with_libp2p::P2pServiceWebrtcWithLibp2p;
 use node::p2p::P2pCryptoService;
 use node::recorder::Recorder;
 use node::service::{
-    BlockProducerService, BlockProducerVrfEvaluatorService, TransitionFrontierGenesisService,
+    BlockProducerService, BlockProducerVrfEvaluatorService, TransitionFrontierArchiveService,
+    TransitionFrontierGenesisService,
 };
 use node::snark::block_verify::{
     SnarkBlockVerifyId, SnarkBlockVerifyService, VerifiableBlockWithHash,
@@ -39,6 +40,7 @@ use node::snark::work_verify::{SnarkWorkVerifyId, SnarkWorkVerifyService};
 use node::snark::{BlockVerifier, SnarkEvent, TransactionVerifier, VerifierSRS};
 use node::snark_pool::SnarkPoolService;
 use node::stats::Stats;
+use node::transition_frontier::archive::ArchiveAppliedBlock;
 use node::transition_frontier::genesis::GenesisConfig;
 use node::{
     event_source::Event,
@@ -345,6 +347,12 @@ impl TransitionFrontierGenesisService for NodeTestingService {
     }
 }
 
+impl TransitionFrontierArchiveService for NodeTestingService {
+    fn send_to_archive(&mut self, block: ArchiveAppliedBlock) {
+        TransitionFrontierArchiveService::send_to_archive(&mut self.real, block);
+    }
+}
+
 impl P2pServiceWebrtc for NodeTestingService {
     type Event = Event;
 
@@ -514,7 +522,13 @@ impl BlockProducerService for NodeTestingService {
             let dummy_proof = (*ledger::dummy::dummy_blockchain_proof()).clone();
             BlockProducerEvent::BlockProve(block_hash, Ok(dummy_proof.into())).into()
         }
-        let keypair = self.real.block_producer().unwrap().keypair();
+        let block_creator = input.next_state.body.consensus_state.block_creator.clone();
+        let keypair = self
+            .real
+            .block_producer()
+            .unwrap()
+            .keypair(&block_creator.into())
+            .expect("no keypair for block creator");
 
         match self.proof_kind() {
             ProofKind::Dummy => {
@@ -20,6 +20,7 @@ impl RpcService for super::NodeTestingService {
         respond_block_producer_stats_get,
         node::rpc::RpcBlockProducerStatsGetResponse
     );
+    to_real!(respond_metrics_get, node::rpc::RpcMetricsGetResponse);
 
     to_real!(
         respond_action_stats_get,
@@ -107,4 +108,9 @@ impl RpcService for super::NodeTestingService {
         respond_transaction_status,
         node::rpc::RpcTransactionStatusGetResponse,
     );
+    to_real!(respond_block_get, node::rpc::RpcBlockGetResponse,);
+
+    fn publish_subscription_event(&mut self, event: node::rpc::RpcSubscriptionEvent) {
+        self.real.publish_subscription_event(event)
+    }
 }
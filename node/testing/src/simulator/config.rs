@@ -3,7 +3,10 @@ use std::{sync::Arc, time::Duration};
 use node::transition_frontier::genesis::GenesisConfig;
 use serde::{Deserialize, Serialize};
 
-use crate::{node::Recorder, scenarios::RunCfgAdvanceTime};
+use crate::{
+    node::Recorder,
+    scenarios::{ChaosConfig, RunCfgAdvanceTime},
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SimulatorConfig {
@@ -20,6 +23,10 @@ pub struct SimulatorConfig {
     pub run_until_timeout: Duration,
     #[serde(default)]
     pub recorder: Recorder,
+    /// Faults injected while running the simulation. Seed nodes are never
+    /// crashed. Simulation doesn't end until the chaos is healed.
+    #[serde(default)]
+    pub chaos: Option<ChaosConfig>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -15,7 +15,7 @@ use crate::{
     cluster::ClusterNodeId,
     node::{Node, RustNodeBlockProducerTestingConfig, RustNodeTestingConfig},
     scenario::ListenerNode,
-    scenarios::{ClusterRunner, RunCfg},
+    scenarios::{Chaos, ClusterRunner, RunCfg},
     service::NodeTestingService,
 };
 
@@ -205,11 +205,7 @@ impl Simulator {
             );
             let config = RustNodeTestingConfig {
                 block_producer: Some(RustNodeBlockProducerTestingConfig {
-                    config: BlockProducerConfig {
-                        pub_key: sec_key.public_key().into(),
-                        custom_coinbase_receiver: None,
-                        proposed_protocol_version: None,
-                    },
+                    config: BlockProducerConfig::new(sec_key.public_key().into()),
                     sec_key,
                 }),
                 ..node_config.clone()
@@ -266,6 +262,17 @@ impl Simulator {
         let mut last_printed_slot = 0;
         let virtual_initial_time = self.initial_time();
 
+        match self.config.chaos.clone() {
+            Some(config) if runner.chaos().is_none() => {
+                let nodes = runner.nodes_iter().map(|(id, _)| id).collect::<Vec<_>>();
+                let crashable = nodes[self.config.seed_nodes.min(nodes.len())..].to_vec();
+                let chaos = Chaos::new(config, nodes, crashable).expect("invalid chaos config");
+                eprintln!("[chaos] schedule: {:?}", chaos.schedule());
+                runner.set_chaos(chaos);
+            }
+            _ => {}
+        }
+
         while start_t.elapsed() < self.config.run_until_timeout {
             tokio::task::yield_now().await;
             let cfg = RunCfg::default()
@@ -318,7 +325,7 @@ impl Simulator {
                             best_tip.height() >= start_height + *height
                         }
                     };
-                    if stop {
+                    if stop && runner.chaos().map_or(true, Chaos::is_healed) {
                         return;
                     }
                 }
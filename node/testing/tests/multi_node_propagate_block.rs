@@ -6,3 +6,10 @@ scenario_test!(
     openmina_node_testing::scenarios::multi_node::pubsub_advanced::MultiNodePubsubPropagateBlock,
     openmina_node_testing::scenarios::multi_node::pubsub_advanced::MultiNodePubsubPropagateBlock
 );
+
+#[cfg(feature = "p2p-libp2p")]
+scenario_test!(
+    pubsub_mesh_maintenance,
+    openmina_node_testing::scenarios::multi_node::pubsub_advanced::MultiNodePubsubMeshMaintenance,
+    openmina_node_testing::scenarios::multi_node::pubsub_advanced::MultiNodePubsubMeshMaintenance
+);
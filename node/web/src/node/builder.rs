@@ -7,7 +7,8 @@ use node::{
     core::{consensus::ConsensusConstants, constants::constraint_constants},
     p2p::{
         channels::ChannelId, connection::outgoing::P2pConnectionOutgoingInitOpts,
-        identity::SecretKey as P2pSecretKey, P2pLimits, P2pMeshsubConfig, P2pTimeouts,
+        identity::SecretKey as P2pSecretKey, P2pLimits, P2pMeshsubConfig, P2pNatConfig,
+        P2pReputationConfig, P2pTimeouts,
     },
     snark::{get_srs, BlockVerifier, TransactionVerifier, VerifierSRS},
     transition_frontier::genesis::GenesisConfig,
@@ -122,13 +123,9 @@ impl NodeBuilder {
         key: AccountSecretKey,
         provers: Option<BlockProver>,
     ) -> &mut Self {
-        let config = BlockProducerConfig {
-            pub_key: key.public_key().into(),
-            custom_coinbase_receiver: None,
-            proposed_protocol_version: None,
-        };
+        let config = BlockProducerConfig::new(key.public_key().into());
         self.block_producer = Some(config);
-        self.service.block_producer_init(key, provers);
+        self.service.block_producer_init(vec![key], provers);
         self
     }
 
@@ -142,7 +139,9 @@ impl NodeBuilder {
                 "can't set custom_coinbase_receiver when block producer is not initialized."
             )
         })?;
-        bp.custom_coinbase_receiver = Some(addr);
+        if let Some(producer) = bp.producers.first_mut() {
+            producer.custom_coinbase_receiver = Some(addr);
+        }
         Ok(self)
     }
 
@@ -227,6 +226,7 @@ impl NodeBuilder {
             },
             p2p: P2pConfig {
                 libp2p_port: None,
+                quic_port: None,
                 listen_port: None,
                 identity_pub_key: p2p_sec_key.public_key(),
                 initial_peers,
@@ -239,6 +239,8 @@ impl NodeBuilder {
                 },
                 timeouts: P2pTimeouts::default(),
                 limits: P2pLimits::default().with_max_peers(Some(100)),
+                reputation: P2pReputationConfig::default(),
+                nat: P2pNatConfig::default(),
             },
             ledger: LedgerConfig {},
             snark: SnarkConfig {
@@ -252,6 +254,8 @@ impl NodeBuilder {
             tx_pool: ledger::transaction_pool::Config {
                 trust_system: (),
                 pool_max_size: node::daemon_json::Daemon::DEFAULT.tx_pool_max_size(),
+                max_per_sender: ledger::transaction_pool::DEFAULT_MAX_PER_SENDER,
+                replace_fee: ledger::transaction_pool::DEFAULT_REPLACE_FEE,
                 slot_tx_end: node::daemon_json::Daemon::DEFAULT.slot_tx_end(),
             },
         };
@@ -26,6 +26,7 @@ use super::{
 };
 use crate::{
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    reputation::{P2pPenaltyReason, P2pReputationAction},
     P2pState,
 };
 use openmina_core::{block::BlockWithHash, error, Substate};
@@ -267,6 +268,10 @@ impl P2pChannelsState {
 
         if !was_expected {
             // dbg!(&action.message);
+            dispatcher.push(P2pReputationAction::Penalize {
+                peer_id,
+                reason: P2pPenaltyReason::ProtocolViolation,
+            });
             let reason = P2pDisconnectionReason::P2pChannelMsgUnexpected(chain_id);
             dispatcher.push(P2pDisconnectionAction::Init { peer_id, reason });
         }
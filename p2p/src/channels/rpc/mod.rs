@@ -10,13 +10,15 @@ use std::{sync::Arc, time::Duration};
 
 use binprot_derive::{BinProtRead, BinProtWrite};
 use mina_p2p_messages::{
+    core,
     list::List,
     rpc_kernel::QueryID,
     v2::{
-        LedgerHash, MerkleAddressBinableArgStableV1, MinaBasePendingCoinbaseStableV2,
-        MinaBaseStateBodyHashStableV1, MinaLedgerSyncLedgerAnswerStableV2,
-        MinaLedgerSyncLedgerQueryStableV1, MinaStateProtocolStateValueStableV2, StateHash,
-        TransactionSnarkScanStateStableV2,
+        ConsensusProofOfStakeDataConsensusStateValueStableV2, LedgerHash,
+        MerkleAddressBinableArgStableV1, MinaBasePendingCoinbaseStableV2,
+        MinaBaseSparseLedgerBaseStableV2, MinaBaseStateBodyHashStableV1,
+        MinaLedgerSyncLedgerAnswerStableV2, MinaLedgerSyncLedgerQueryStableV1,
+        MinaStateProtocolStateValueStableV2, StateHash, TransactionSnarkScanStateStableV2,
     },
 };
 use openmina_core::{
@@ -54,6 +56,10 @@ pub enum P2pRpcKind {
     Snark,
     Transaction,
     InitialPeers,
+    Ancestry,
+    TransitionChainProof,
+    EpochLedger,
+    BanNotify,
 }
 
 impl P2pRpcKind {
@@ -68,6 +74,10 @@ impl P2pRpcKind {
             Self::Snark => config.snark,
             Self::Transaction => config.transaction,
             Self::InitialPeers => config.initial_peers,
+            Self::Ancestry => config.ancestry,
+            Self::TransitionChainProof => config.transition_chain_proof,
+            Self::EpochLedger => config.epoch_ledger,
+            Self::BanNotify => config.ban_notify,
         }
     }
 
@@ -80,6 +90,10 @@ impl P2pRpcKind {
             Self::Snark => false,
             Self::Transaction => false,
             Self::InitialPeers => true,
+            Self::Ancestry => true,
+            Self::TransitionChainProof => true,
+            Self::EpochLedger => true,
+            Self::BanNotify => true,
         }
     }
 }
@@ -93,6 +107,19 @@ pub enum P2pRpcRequest {
     Snark(SnarkJobId),
     Transaction(TransactionHash),
     InitialPeers,
+    /// Best tip with proof, if it is better than the block with the given
+    /// hash and consensus state.
+    Ancestry(
+        StateHash,
+        Box<ConsensusProofOfStakeDataConsensusStateValueStableV2>,
+    ),
+    /// Merkle list proof of the block with the given hash, starting at the root.
+    /// Only blocks of the best chain are served.
+    TransitionChainProof(StateHash),
+    EpochLedger(LedgerHash),
+    /// Lets the peer know until when it is banned. The peer is
+    /// disconnected once it answers or the request times out.
+    BanNotify(core::Time),
 }
 
 impl P2pRpcRequest {
@@ -107,6 +134,10 @@ impl P2pRpcRequest {
             Self::Snark(_) => P2pRpcKind::Snark,
             Self::Transaction(_) => P2pRpcKind::Transaction,
             Self::InitialPeers => P2pRpcKind::InitialPeers,
+            Self::Ancestry(..) => P2pRpcKind::Ancestry,
+            Self::TransitionChainProof(_) => P2pRpcKind::TransitionChainProof,
+            Self::EpochLedger(_) => P2pRpcKind::EpochLedger,
+            Self::BanNotify(_) => P2pRpcKind::BanNotify,
         }
     }
 }
@@ -153,7 +184,9 @@ impl std::fmt::Display for P2pRpcRequest {
                 write!(f, "ledger: {ledger_hash}")
             }
             Self::StagedLedgerAuxAndPendingCoinbasesAtBlock(block_hash)
-            | Self::Block(block_hash) => {
+            | Self::Block(block_hash)
+            | Self::Ancestry(block_hash, _)
+            | Self::TransitionChainProof(block_hash) => {
                 write!(f, ", {block_hash}")
             }
             Self::Snark(job_id) => {
@@ -163,6 +196,12 @@ impl std::fmt::Display for P2pRpcRequest {
                 write!(f, ", {hash}")
             }
             Self::InitialPeers => Ok(()),
+            Self::EpochLedger(ledger_hash) => {
+                write!(f, ", {ledger_hash}")
+            }
+            Self::BanNotify(until) => {
+                write!(f, ", until: {}", until.as_secs_f64())
+            }
         }
     }
 }
@@ -182,6 +221,16 @@ pub struct StagedLedgerAuxAndPendingCoinbases {
     pub needed_blocks: List<MinaStateProtocolStateValueStableV2>,
 }
 
+/// Merkle list proof of the block, see [`P2pRpcRequest::TransitionChainProof`].
+#[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
+pub struct TransitionChainProof {
+    /// Hash of the first block in the chain.
+    pub first_hash: StateHash,
+    /// Body hashes of the blocks following the first one, up to the
+    /// requested block (inclusive).
+    pub body_hashes: List<MinaBaseStateBodyHashStableV1>,
+}
+
 #[derive(BinProtWrite, BinProtRead, Serialize, Deserialize, Debug, Clone)]
 pub enum P2pRpcResponse {
     BestTipWithProof(BestTipWithProof),
@@ -191,6 +240,9 @@ pub enum P2pRpcResponse {
     Snark(Snark),
     Transaction(Transaction),
     InitialPeers(List<P2pConnectionOutgoingInitOpts>),
+    Ancestry(BestTipWithProof),
+    TransitionChainProof(TransitionChainProof),
+    EpochLedger(Arc<MinaBaseSparseLedgerBaseStableV2>),
 }
 
 impl P2pRpcResponse {
@@ -205,6 +257,9 @@ impl P2pRpcResponse {
             Self::Snark(_) => P2pRpcKind::Snark,
             Self::Transaction(_) => P2pRpcKind::Transaction,
             Self::InitialPeers(_) => P2pRpcKind::InitialPeers,
+            Self::Ancestry(_) => P2pRpcKind::Ancestry,
+            Self::TransitionChainProof(_) => P2pRpcKind::TransitionChainProof,
+            Self::EpochLedger(_) => P2pRpcKind::EpochLedger,
         }
     }
 }
@@ -308,6 +363,51 @@ mod libp2p {
                     .collect();
                 let r = RpcResult(Ok(NeedsLength(r)));
 
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::Ancestry(r) => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let BestTipWithProof {
+                    best_tip,
+                    proof: (middle, block),
+                } = r;
+                let middle = middle.into_iter().map(|hash| hash.0).collect();
+
+                let r = RpcResult(Ok(NeedsLength(Some(rpc::ProofCarryingDataWithHashV1 {
+                    data: best_tip.as_ref().clone(),
+                    proof: (middle, block.as_ref().clone()),
+                }))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::TransitionChainProof(proof) => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let TransitionChainProof {
+                    first_hash,
+                    body_hashes,
+                } = proof;
+                let body_hashes = body_hashes.into_iter().map(|hash| hash.0).collect();
+
+                let r = RpcResult(Ok(NeedsLength(Some((first_hash.0.clone(), body_hashes)))));
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
+                Some((ResponseHeader { id: id as _ }, v.into()))
+            }
+            P2pRpcResponse::EpochLedger(ledger) => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r = RpcResult(Ok(NeedsLength(RpcResult(Ok(ledger.as_ref().clone())))));
+
                 let mut v = vec![];
                 <Payload as BinProtWrite>::binprot_write(&r, &mut v).unwrap_or_default();
                 Some((ResponseHeader { id: id as _ }, v.into()))
@@ -315,6 +415,44 @@ mod libp2p {
         }
     }
 
+    /// Encodes negative answer for RPCs, which have it as a part of the
+    /// protocol, so that the peer doesn't have to wait for timeout.
+    pub fn internal_empty_response_into_libp2p(
+        kind: P2pRpcKind,
+        id: P2pRpcId,
+    ) -> Option<(ResponseHeader, Data)> {
+        use binprot::BinProtWrite;
+
+        let mut v = vec![];
+        match kind {
+            P2pRpcKind::Ancestry => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r: Payload = RpcResult(Ok(NeedsLength(None)));
+                r.binprot_write(&mut v).unwrap_or_default();
+            }
+            P2pRpcKind::TransitionChainProof => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r: Payload = RpcResult(Ok(NeedsLength(None)));
+                r.binprot_write(&mut v).unwrap_or_default();
+            }
+            P2pRpcKind::EpochLedger => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = ResponsePayload<<Method as RpcMethod>::Response>;
+
+                let r: Payload = RpcResult(Ok(NeedsLength(RpcResult(Err(
+                    "epoch ledger not found".into(),
+                )))));
+                r.binprot_write(&mut v).unwrap_or_default();
+            }
+            _ => return None,
+        }
+        Some((ResponseHeader { id: id as _ }, v.into()))
+    }
+
     pub fn internal_request_into_libp2p(
         request: P2pRpcRequest,
         id: P2pRpcId,
@@ -415,6 +553,155 @@ mod libp2p {
                     v.into(),
                 ))
             }
+            P2pRpcRequest::Ancestry(hash, consensus_state) => {
+                type Method = rpc::GetAncestryV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let query = rpc::WithHashV1 {
+                    data: *consensus_state,
+                    hash: hash.0.clone(),
+                };
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(query), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::TransitionChainProof(hash) => {
+                type Method = rpc::GetTransitionChainProofV1ForV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(hash.0.clone()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::EpochLedger(hash) => {
+                type Method = rpc::GetEpochLedgerV2;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(hash.0.clone()), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+            P2pRpcRequest::BanNotify(until) => {
+                type Method = rpc::BanNotifyV1;
+                type Payload = QueryPayload<<Method as RpcMethod>::Query>;
+
+                let mut v = vec![];
+                <Payload as BinProtWrite>::binprot_write(&NeedsLength(until), &mut v)
+                    .unwrap_or_default();
+                Some((
+                    QueryHeader {
+                        tag: Method::NAME.into(),
+                        version: Method::VERSION,
+                        id: id as _,
+                    },
+                    v.into(),
+                ))
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use mina_p2p_messages::{bigint::BigInt, rpc_kernel::PayloadBinprotReader};
+
+        use super::*;
+
+        fn response_bytes(response: Option<(ResponseHeader, Data)>, id: P2pRpcId) -> Vec<u8> {
+            let (header, data) = response.expect("response must be encoded");
+            assert_eq!(header.id, id);
+            data.0.to_vec()
+        }
+
+        #[test]
+        fn test_empty_responses() {
+            let bytes = response_bytes(
+                P2pRpcResponse::internal_empty_response_into_libp2p(P2pRpcKind::Ancestry, 1),
+                1,
+            );
+            let response = rpc::GetAncestryV2::response_payload(&mut bytes.as_slice()).unwrap();
+            assert!(response.is_none());
+
+            let bytes = response_bytes(
+                P2pRpcResponse::internal_empty_response_into_libp2p(
+                    P2pRpcKind::TransitionChainProof,
+                    2,
+                ),
+                2,
+            );
+            let response =
+                rpc::GetTransitionChainProofV1ForV2::response_payload(&mut bytes.as_slice())
+                    .unwrap();
+            assert!(response.is_none());
+
+            let bytes = response_bytes(
+                P2pRpcResponse::internal_empty_response_into_libp2p(P2pRpcKind::EpochLedger, 3),
+                3,
+            );
+            let response = rpc::GetEpochLedgerV2::response_payload(&mut bytes.as_slice()).unwrap();
+            assert!(response.0.is_err());
+
+            // RPCs without a negative answer in the protocol
+            assert!(P2pRpcResponse::internal_empty_response_into_libp2p(
+                P2pRpcKind::BestTipWithProof,
+                4
+            )
+            .is_none());
+        }
+
+        #[test]
+        fn test_transition_chain_proof_response() {
+            let proof = TransitionChainProof {
+                first_hash: StateHash::zero(),
+                body_hashes: [BigInt::zero(), BigInt::one()]
+                    .into_iter()
+                    .map(MinaBaseStateBodyHashStableV1)
+                    .collect(),
+            };
+            let bytes = response_bytes(
+                P2pRpcResponse::internal_response_into_libp2p(
+                    P2pRpcResponse::TransitionChainProof(proof.clone()),
+                    5,
+                ),
+                5,
+            );
+            let (first_hash, body_hashes) =
+                rpc::GetTransitionChainProofV1ForV2::response_payload(&mut bytes.as_slice())
+                    .unwrap()
+                    .unwrap();
+            assert_eq!(first_hash, proof.first_hash.0.clone());
+            assert_eq!(
+                body_hashes.into_iter().collect::<Vec<_>>(),
+                proof
+                    .body_hashes
+                    .iter()
+                    .map(|hash| hash.0.clone())
+                    .collect::<Vec<_>>()
+            );
         }
     }
 }
@@ -1,10 +1,12 @@
 use super::{
     P2pChannelsRpcAction, P2pChannelsRpcState, P2pRpcLocalState, P2pRpcRemotePendingRequestState,
-    P2pRpcRemoteState, P2pRpcResponse, RpcChannelMsg, MAX_P2P_RPC_REMOTE_CONCURRENT_REQUESTS,
+    P2pRpcRemoteState, P2pRpcRequest, P2pRpcResponse, RpcChannelMsg,
+    MAX_P2P_RPC_REMOTE_CONCURRENT_REQUESTS,
 };
 use crate::{
     channels::{ChannelId, ChannelMsg, MsgId, P2pChannelsEffectfulAction},
-    P2pNetworkRpcAction, P2pPeerAction, P2pState,
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    P2pNetworkRpcAction, P2pPeerAction, P2pState, PeerId,
 };
 use openmina_core::{block::BlockWithHash, bug_condition, error, Substate};
 use redux::ActionWithMeta;
@@ -125,9 +127,24 @@ impl P2pChannelsRpcState {
                 Ok(())
             }
             P2pChannelsRpcAction::Timeout { id, .. } => {
+                let is_ban_notify = matches!(
+                    rpc_state,
+                    Self::Ready {
+                        local: P2pRpcLocalState::Requested {
+                            request: P2pRpcRequest::BanNotify(_),
+                            ..
+                        },
+                        ..
+                    }
+                );
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let p2p_state: &P2pState = state.substate()?;
 
+                if is_ban_notify {
+                    disconnect_banned_peer(dispatcher, p2p_state, peer_id);
+                    return Ok(());
+                }
+
                 if let Some(callback) = &p2p_state.callbacks.on_p2p_channels_rpc_timeout {
                     dispatcher.push_callback(callback.clone(), (peer_id, id));
                 }
@@ -153,6 +170,7 @@ impl P2pChannelsRpcState {
                     );
                     return Ok(());
                 };
+                let is_ban_notify = matches!(request, P2pRpcRequest::BanNotify(_));
                 *local = P2pRpcLocalState::Responded {
                     time: meta.time(),
                     id: *id,
@@ -162,6 +180,11 @@ impl P2pChannelsRpcState {
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let p2p_state: &P2pState = state.substate()?;
 
+                if is_ban_notify {
+                    disconnect_banned_peer(dispatcher, p2p_state, peer_id);
+                    return Ok(());
+                }
+
                 if let Some(P2pRpcResponse::BestTipWithProof(resp)) = response.as_deref() {
                     let Ok(best_tip) = BlockWithHash::try_new(resp.best_tip.clone()) else {
                         error!(meta.time(); "P2pChannelsRpcAction::ResponseReceived: Invalid bigint in block");
@@ -223,8 +246,12 @@ impl P2pChannelsRpcState {
                     return Ok(());
                 };
 
-                if let Some(pos) = remote.pending_requests.iter().position(|r| r.id == id) {
-                    remote.pending_requests.remove(pos);
+                let request = remote
+                    .pending_requests
+                    .iter()
+                    .position(|r| r.id == id)
+                    .and_then(|pos| remote.pending_requests.remove(pos));
+                if request.is_some() {
                     remote.last_responded = meta.time();
                 }
 
@@ -232,16 +259,23 @@ impl P2pChannelsRpcState {
 
                 #[cfg(feature = "p2p-libp2p")]
                 if is_libp2p {
-                    if let Some(response) = response {
-                        if let Some((response, data)) =
+                    let response = match response {
+                        Some(response) => {
                             super::libp2p::internal_response_into_libp2p(*response, id)
-                        {
-                            dispatcher.push(P2pNetworkRpcAction::OutgoingResponse {
-                                peer_id,
-                                response,
-                                data,
-                            });
                         }
+                        None => request.and_then(|req| {
+                            super::libp2p::internal_empty_response_into_libp2p(
+                                req.request.kind(),
+                                id,
+                            )
+                        }),
+                    };
+                    if let Some((response, data)) = response {
+                        dispatcher.push(P2pNetworkRpcAction::OutgoingResponse {
+                            peer_id,
+                            response,
+                            data,
+                        });
                     }
 
                     return Ok(());
@@ -257,3 +291,21 @@ impl P2pChannelsRpcState {
         }
     }
 }
+
+/// Disconnects the peer once it got notified about its ban, so that the
+/// notification isn't dropped together with the connection.
+fn disconnect_banned_peer<Action, State>(
+    dispatcher: &mut redux::Dispatcher<Action, State>,
+    p2p_state: &P2pState,
+    peer_id: PeerId,
+) where
+    State: crate::P2pStateTrait,
+    Action: crate::P2pActionTrait<State>,
+{
+    if let Some(ban) = p2p_state.reputation.banned_peers.get(&peer_id) {
+        dispatcher.push(P2pDisconnectionAction::Init {
+            peer_id,
+            reason: P2pDisconnectionReason::Banned(ban.reason),
+        });
+    }
+}
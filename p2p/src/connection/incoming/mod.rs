@@ -6,6 +6,8 @@ pub use p2p_connection_incoming_actions::*;
 
 mod p2p_connection_incoming_reducer;
 
+use std::net::IpAddr;
+
 use serde::{Deserialize, Serialize};
 
 use crate::connection::RejectionReason;
@@ -51,6 +53,10 @@ impl P2pState {
             return Err(RejectionReason::ConnectingToSelf);
         }
 
+        if self.reputation.is_peer_banned(&peer_id) {
+            return Err(RejectionReason::Banned);
+        }
+
         if self.is_peer_connected_or_connecting(&peer_id) {
             // Both nodes trying to connect to each other at the same time.
             // Choose connection arbitrarily based on peer id.
@@ -72,10 +78,113 @@ impl P2pState {
             return Err(RejectionReason::ConnectingToSelf);
         }
 
+        if self.reputation.is_peer_banned(&peer_id) {
+            return Err(RejectionReason::Banned);
+        }
+
         if self.already_has_max_ready_peers() {
             return Err(RejectionReason::PeerCapacityFull);
         }
 
         Ok(())
     }
+
+    /// Checks the remote address of incoming libp2p connection, before
+    /// the peer id is known.
+    pub fn libp2p_incoming_ip_accept(&self, ip: &IpAddr) -> Result<(), RejectionReason> {
+        if self.reputation.is_ip_banned(ip) {
+            return Err(RejectionReason::Banned);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use openmina_core::DEVNET_CHAIN_ID;
+
+    use super::*;
+    use crate::{
+        identity::SecretKey,
+        reputation::{P2pBan, P2pPenaltyReason},
+        P2pCallbacks, P2pConfig, P2pLimits, P2pMeshsubConfig, P2pNatConfig, P2pReputationConfig,
+        P2pTimeouts,
+    };
+
+    fn test_p2p_state() -> P2pState {
+        let config = P2pConfig {
+            libp2p_port: None,
+            quic_port: None,
+            listen_port: None,
+            identity_pub_key: SecretKey::rand().public_key(),
+            initial_peers: vec![],
+            external_addrs: vec![],
+            enabled_channels: Default::default(),
+            timeouts: P2pTimeouts::default(),
+            limits: P2pLimits::default(),
+            peer_discovery: false,
+            meshsub: P2pMeshsubConfig::default(),
+            reputation: P2pReputationConfig::default(),
+            nat: P2pNatConfig::default(),
+        };
+        P2pState::new(config, P2pCallbacks::default(), &DEVNET_CHAIN_ID)
+    }
+
+    fn test_ban() -> P2pBan {
+        P2pBan {
+            since: redux::Timestamp::ZERO,
+            until: redux::Timestamp::ZERO + std::time::Duration::from_secs(60),
+            reason: P2pPenaltyReason::InvalidBlock,
+        }
+    }
+
+    #[test]
+    fn banned_peer_id_is_refused() {
+        let mut state = test_p2p_state();
+        let peer_secret_key = SecretKey::rand();
+        let peer_id = peer_secret_key.public_key().peer_id();
+        let offer = webrtc::Offer {
+            sdp: String::new(),
+            chain_id: state.chain_id.clone(),
+            identity_pub_key: peer_secret_key.public_key(),
+            target_peer_id: state.my_id(),
+            host: webrtc::Host::Ipv4(Ipv4Addr::new(1, 2, 3, 4)),
+            listen_port: None,
+        };
+        assert_eq!(state.libp2p_incoming_accept(peer_id), Ok(()));
+        assert_eq!(state.incoming_accept(peer_id, &offer), Ok(()));
+
+        state.reputation.banned_peers.insert(peer_id, test_ban());
+        assert_eq!(
+            state.libp2p_incoming_accept(peer_id),
+            Err(RejectionReason::Banned)
+        );
+        assert_eq!(
+            state.incoming_accept(peer_id, &offer),
+            Err(RejectionReason::Banned)
+        );
+
+        let other_peer_id = SecretKey::rand().public_key().peer_id();
+        assert_eq!(state.libp2p_incoming_accept(other_peer_id), Ok(()));
+    }
+
+    #[test]
+    fn banned_ip_is_refused() {
+        let mut state = test_p2p_state();
+        let ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4));
+        assert_eq!(state.libp2p_incoming_ip_accept(&ip), Ok(()));
+
+        let ban = test_ban();
+        state.reputation.banned_ips.insert(ip, ban.until);
+        assert_eq!(
+            state.libp2p_incoming_ip_accept(&ip),
+            Err(RejectionReason::Banned)
+        );
+
+        let other_ip = IpAddr::V4(Ipv4Addr::new(1, 2, 3, 5));
+        assert_eq!(state.libp2p_incoming_ip_accept(&other_ip), Ok(()));
+    }
 }
@@ -9,7 +9,9 @@ use crate::{
     connection::{
         incoming::P2pConnectionIncomingError,
         incoming_effectful::P2pConnectionIncomingEffectfulAction,
-        outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
+        outgoing::{
+            P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+        },
         P2pConnectionResponse, P2pConnectionState,
     },
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
@@ -414,6 +416,16 @@ impl P2pConnectionIncomingState {
             P2pConnectionIncomingAction::FinalizePendingLibp2p { addr, .. } => {
                 #[cfg(feature = "p2p-libp2p")]
                 {
+                    // QUIC connections are dialed from the listening socket,
+                    // so the remote address is also the one to dial back
+                    let transport = p2p_state
+                        .network
+                        .scheduler
+                        .connection_state(&ConnectionAddr {
+                            sock_addr: addr,
+                            incoming: true,
+                        })
+                        .map_or(P2pLibp2pTransport::Tcp, |cn| cn.transport());
                     let state = p2p_state
                         .peers
                         .entry(peer_id)
@@ -424,6 +436,7 @@ impl P2pConnectionIncomingState {
                                     peer_id,
                                     host: Host::from(addr.ip()),
                                     port: addr.port(),
+                                    transport,
                                 },
                             )),
                             status: P2pPeerStatus::Disconnected { time: meta.time() },
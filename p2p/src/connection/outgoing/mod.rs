@@ -11,7 +11,6 @@ use std::net::IpAddr;
 use std::net::SocketAddr;
 use std::{fmt, str::FromStr};
 
-use binprot_derive::{BinProtRead, BinProtWrite};
 use multiaddr::{Multiaddr, Protocol};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -28,9 +27,7 @@ use crate::{
 use crate::webrtc::{HttpSignalingInfo, SignalingMethod};
 
 // TODO(binier): maybe move to `crate::webrtc` module
-#[derive(
-    BinProtWrite, BinProtRead, derive_more::From, Debug, Ord, PartialOrd, Eq, PartialEq, Clone,
-)]
+#[derive(derive_more::From, Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub enum P2pConnectionOutgoingInitOpts {
     WebRTC {
         peer_id: PeerId,
@@ -39,11 +36,132 @@ pub enum P2pConnectionOutgoingInitOpts {
     LibP2P(P2pConnectionOutgoingInitLibp2pOpts),
 }
 
-#[derive(BinProtWrite, BinProtRead, Eq, PartialEq, Ord, PartialOrd, Debug, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone)]
 pub struct P2pConnectionOutgoingInitLibp2pOpts {
     pub peer_id: PeerId,
     pub host: Host,
     pub port: u16,
+    pub transport: P2pLibp2pTransport,
+}
+
+/// Transport used to reach a libp2p peer, selected by the peer's multiaddr
+/// (`/tcp/<port>` or `/udp/<port>/quic-v1`).
+#[derive(Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd, Debug, Clone, Copy)]
+pub enum P2pLibp2pTransport {
+    /// TCP with pnet, noise and yamux.
+    Tcp,
+    /// QUIC v1 with libp2p TLS, streams are native QUIC streams.
+    Quic,
+}
+
+impl P2pLibp2pTransport {
+    pub fn is_quic(&self) -> bool {
+        matches!(self, Self::Quic)
+    }
+
+    /// Multiaddr protocols following the host part of the address.
+    pub fn protocols(&self, port: u16) -> impl Iterator<Item = Protocol<'static>> {
+        let (transport, quic) = match self {
+            Self::Tcp => (Protocol::Tcp(port), None),
+            Self::Quic => (Protocol::Udp(port), Some(Protocol::QuicV1)),
+        };
+        std::iter::once(transport).chain(quic)
+    }
+}
+
+/// Binprot encoding of [`P2pConnectionOutgoingInitOpts`], sent to the rust
+/// peers in the `InitialPeers` rpc response.
+///
+/// The transport isn't a field of the libp2p options on the wire, QUIC
+/// options are a separate variant following the original ones. That way
+/// TCP and WebRTC options are encoded the same as by the peers without QUIC
+/// support, though those peers can't decode the QUIC options.
+mod binprot_impl {
+    use binprot::{BinProtRead, BinProtWrite};
+    use binprot_derive::{BinProtRead, BinProtWrite};
+
+    use crate::{
+        webrtc::{Host, SignalingMethod},
+        PeerId,
+    };
+
+    use super::{
+        P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+    };
+
+    #[derive(BinProtWrite, BinProtRead)]
+    enum Opts {
+        WebRTC {
+            peer_id: PeerId,
+            signaling: SignalingMethod,
+        },
+        LibP2P(Libp2pOpts),
+        LibP2PQuic(Libp2pOpts),
+    }
+
+    #[derive(BinProtWrite, BinProtRead)]
+    struct Libp2pOpts {
+        peer_id: PeerId,
+        host: Host,
+        port: u16,
+    }
+
+    impl From<&P2pConnectionOutgoingInitOpts> for Opts {
+        fn from(value: &P2pConnectionOutgoingInitOpts) -> Self {
+            match value {
+                P2pConnectionOutgoingInitOpts::WebRTC { peer_id, signaling } => Opts::WebRTC {
+                    peer_id: *peer_id,
+                    signaling: signaling.clone(),
+                },
+                P2pConnectionOutgoingInitOpts::LibP2P(opts) => {
+                    let libp2p_opts = Libp2pOpts {
+                        peer_id: opts.peer_id,
+                        host: opts.host.clone(),
+                        port: opts.port,
+                    };
+                    match opts.transport {
+                        P2pLibp2pTransport::Tcp => Opts::LibP2P(libp2p_opts),
+                        P2pLibp2pTransport::Quic => Opts::LibP2PQuic(libp2p_opts),
+                    }
+                }
+            }
+        }
+    }
+
+    impl From<Opts> for P2pConnectionOutgoingInitOpts {
+        fn from(value: Opts) -> Self {
+            let libp2p = |opts: Libp2pOpts, transport| {
+                P2pConnectionOutgoingInitOpts::LibP2P(P2pConnectionOutgoingInitLibp2pOpts {
+                    peer_id: opts.peer_id,
+                    host: opts.host,
+                    port: opts.port,
+                    transport,
+                })
+            };
+            match value {
+                Opts::WebRTC { peer_id, signaling } => {
+                    P2pConnectionOutgoingInitOpts::WebRTC { peer_id, signaling }
+                }
+                Opts::LibP2P(opts) => libp2p(opts, P2pLibp2pTransport::Tcp),
+                Opts::LibP2PQuic(opts) => libp2p(opts, P2pLibp2pTransport::Quic),
+            }
+        }
+    }
+
+    impl BinProtWrite for P2pConnectionOutgoingInitOpts {
+        fn binprot_write<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+            Opts::from(self).binprot_write(w)
+        }
+    }
+
+    impl BinProtRead for P2pConnectionOutgoingInitOpts {
+        fn binprot_read<R: std::io::Read + ?Sized>(r: &mut R) -> Result<Self, binprot::Error>
+        where
+            Self: Sized,
+        {
+            Opts::binprot_read(r).map(Into::into)
+        }
+    }
 }
 
 impl P2pConnectionOutgoingInitLibp2pOpts {
@@ -89,18 +207,19 @@ pub(crate) mod libp2p_opts {
 
     use crate::{webrtc::Host, PeerId};
 
+    use super::P2pLibp2pTransport;
+
     impl super::P2pConnectionOutgoingInitLibp2pOpts {
         fn to_peer_id_multiaddr(&self) -> (PeerId, Multiaddr) {
             (
                 self.peer_id,
-                Multiaddr::from_iter([(&self.host).into(), multiaddr::Protocol::Tcp(self.port)]),
+                std::iter::once((&self.host).into())
+                    .chain(self.transport.protocols(self.port))
+                    .collect(),
             )
         }
         fn into_peer_id_multiaddr(self) -> (PeerId, Multiaddr) {
-            (
-                self.peer_id,
-                Multiaddr::from_iter([(&self.host).into(), multiaddr::Protocol::Tcp(self.port)]),
-            )
+            self.to_peer_id_multiaddr()
         }
 
         pub fn matches_socket_addr(&self, addr: SocketAddr) -> bool {
@@ -138,6 +257,7 @@ pub(crate) mod libp2p_opts {
                 peer_id,
                 host,
                 port,
+                transport: P2pLibp2pTransport::Tcp,
             }
         }
     }
@@ -240,6 +360,7 @@ impl P2pConnectionOutgoingInitOpts {
                 peer_id: peer_id.try_into().ok()?,
                 host: host.parse().ok()?,
                 port: msg.libp2p_port.as_u64() as u16,
+                transport: P2pLibp2pTransport::Tcp,
             };
             Self::LibP2P(opts)
         };
@@ -252,6 +373,8 @@ impl P2pConnectionOutgoingInitOpts {
     #[cfg(feature = "p2p-libp2p")]
     pub fn try_into_mina_rpc(&self) -> Option<v2::NetworkPeerPeerStableV1> {
         match self {
+            // the OCaml node would take it for a TCP address
+            P2pConnectionOutgoingInitOpts::LibP2P(opts) if opts.transport.is_quic() => None,
             P2pConnectionOutgoingInitOpts::LibP2P(opts) => Some(v2::NetworkPeerPeerStableV1 {
                 host: opts.host.to_string().as_bytes().into(),
                 libp2p_port: (opts.port as u64).into(),
@@ -405,14 +528,16 @@ impl TryFrom<P2pConnectionOutgoingInitLibp2pOpts> for multiaddr::Multiaddr {
     fn try_from(value: P2pConnectionOutgoingInitLibp2pOpts) -> Result<Self, Self::Error> {
         use multiaddr::Protocol;
 
-        Ok(Self::empty()
-            .with(match &value.host {
-                // maybe should be just `Dns`?
-                Host::Domain(v) => Protocol::Dns4(v.into()),
-                Host::Ipv4(v) => Protocol::Ip4(*v),
-                Host::Ipv6(v) => Protocol::Ip6(*v),
-            })
-            .with(Protocol::Tcp(value.port))
+        let maddr = Self::empty().with(match &value.host {
+            // maybe should be just `Dns`?
+            Host::Domain(v) => Protocol::Dns4(v.into()),
+            Host::Ipv4(v) => Protocol::Ip4(*v),
+            Host::Ipv6(v) => Protocol::Ip6(*v),
+        });
+        Ok(value
+            .transport
+            .protocols(value.port)
+            .fold(maddr, Self::with)
             .with(Protocol::P2p(libp2p_identity::PeerId::try_from(
                 value.peer_id,
             )?)))
@@ -441,7 +566,7 @@ impl TryFrom<&multiaddr::Multiaddr> for P2pConnectionOutgoingInitLibp2pOpts {
     fn try_from(maddr: &multiaddr::Multiaddr) -> Result<Self, Self::Error> {
         use multiaddr::Protocol;
 
-        let mut iter = maddr.iter();
+        let mut iter = maddr.iter().peekable();
         Ok(P2pConnectionOutgoingInitLibp2pOpts {
             host: match iter.next() {
                 Some(Protocol::Ip4(v)) => Host::Ipv4(v),
@@ -463,8 +588,8 @@ impl TryFrom<&multiaddr::Multiaddr> for P2pConnectionOutgoingInitLibp2pOpts {
                     ));
                 }
             },
-            port: match iter.next() {
-                Some(Protocol::Tcp(port)) => port,
+            port: match iter.peek() {
+                Some(Protocol::Tcp(port) | Protocol::Udp(port)) => *port,
                 Some(_) => {
                     return Err(P2pConnectionOutgoingInitOptsParseError::Other(
                         "unexpected part in multiaddr! expected port".to_string(),
@@ -476,6 +601,19 @@ impl TryFrom<&multiaddr::Multiaddr> for P2pConnectionOutgoingInitLibp2pOpts {
                     ));
                 }
             },
+            transport: match (iter.next(), iter.peek()) {
+                (Some(Protocol::Tcp(_)), _) => P2pLibp2pTransport::Tcp,
+                (Some(Protocol::Udp(_)), Some(Protocol::QuicV1)) => {
+                    iter.next();
+                    P2pLibp2pTransport::Quic
+                }
+                _ => {
+                    return Err(P2pConnectionOutgoingInitOptsParseError::Other(
+                        "unexpected part in multiaddr! expected `/tcp` or `/udp/<port>/quic-v1`"
+                            .to_string(),
+                    ));
+                }
+            },
             peer_id: match iter.next() {
                 Some(Protocol::P2p(hash)) => libp2p_identity::PeerId::from_multihash(hash.into())
                     .map_err(|_| {
@@ -503,3 +641,79 @@ impl TryFrom<&multiaddr::Multiaddr> for P2pConnectionOutgoingInitLibp2pOpts {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use binprot::{BinProtRead, BinProtWrite};
+    use binprot_derive::{BinProtRead, BinProtWrite};
+
+    use super::*;
+
+    /// The options as encoded before QUIC support.
+    #[derive(BinProtWrite, BinProtRead)]
+    enum LegacyOpts {
+        #[allow(dead_code)]
+        WebRTC {
+            peer_id: PeerId,
+            signaling: webrtc::SignalingMethod,
+        },
+        LibP2P(LegacyLibp2pOpts),
+    }
+
+    #[derive(BinProtWrite, BinProtRead)]
+    struct LegacyLibp2pOpts {
+        peer_id: PeerId,
+        host: Host,
+        port: u16,
+    }
+
+    fn peer_id() -> PeerId {
+        PeerId::from_bytes([7; 32])
+    }
+
+    fn host() -> Host {
+        Host::Ipv4([203, 0, 113, 7].into())
+    }
+
+    fn opts(transport: P2pLibp2pTransport) -> P2pConnectionOutgoingInitOpts {
+        P2pConnectionOutgoingInitOpts::LibP2P(P2pConnectionOutgoingInitLibp2pOpts {
+            peer_id: peer_id(),
+            host: host(),
+            port: 8302,
+            transport,
+        })
+    }
+
+    fn encode<T: BinProtWrite>(value: &T) -> Vec<u8> {
+        let mut bytes = vec![];
+        value.binprot_write(&mut bytes).expect("encoding failed");
+        bytes
+    }
+
+    #[test]
+    fn tcp_opts_binprot_is_unchanged() {
+        let legacy = LegacyOpts::LibP2P(LegacyLibp2pOpts {
+            peer_id: peer_id(),
+            host: host(),
+            port: 8302,
+        });
+        let bytes = encode(&opts(P2pLibp2pTransport::Tcp));
+        assert_eq!(bytes, encode(&legacy));
+
+        let decoded = P2pConnectionOutgoingInitOpts::binprot_read(&mut bytes.as_slice())
+            .expect("decoding failed");
+        assert_eq!(decoded, opts(P2pLibp2pTransport::Tcp));
+
+        let decoded =
+            LegacyOpts::binprot_read(&mut bytes.as_slice()).expect("legacy decoding failed");
+        assert!(matches!(decoded, LegacyOpts::LibP2P(opts) if opts.port == 8302));
+    }
+
+    #[test]
+    fn quic_opts_binprot_roundtrip() {
+        let bytes = encode(&opts(P2pLibp2pTransport::Quic));
+        let decoded = P2pConnectionOutgoingInitOpts::binprot_read(&mut bytes.as_slice())
+            .expect("decoding failed");
+        assert_eq!(decoded, opts(P2pLibp2pTransport::Quic));
+    }
+}
@@ -11,8 +11,8 @@ use crate::{
     },
     disconnection::P2pDisconnectionAction,
     webrtc::Host,
-    P2pNetworkKadRequestAction, P2pNetworkSchedulerAction, P2pPeerAction, P2pPeerState,
-    P2pPeerStatus, P2pState,
+    P2pNetworkKadRequestAction, P2pNetworkQuicAction, P2pNetworkSchedulerAction, P2pPeerAction,
+    P2pPeerState, P2pPeerStatus, P2pState,
 };
 
 use super::{
@@ -74,6 +74,12 @@ impl P2pConnectionOutgoingState {
                 #[cfg(feature = "p2p-libp2p")]
                 if let P2pConnectionOutgoingInitOpts::LibP2P(libp2p_opts) = &opts {
                     match SocketAddr::try_from(libp2p_opts) {
+                        Ok(addr) if libp2p_opts.transport.is_quic() => {
+                            dispatcher.push(P2pNetworkQuicAction::OutgoingConnect {
+                                addr,
+                                peer_id: libp2p_opts.peer_id,
+                            });
+                        }
                         Ok(addr) => {
                             dispatcher.push(P2pNetworkSchedulerAction::OutgoingConnect { addr });
                         }
@@ -89,6 +95,12 @@ impl P2pConnectionOutgoingState {
                     dispatcher.push(P2pConnectionOutgoingAction::FinalizePending {
                         peer_id: libp2p_opts.peer_id,
                     });
+                    if libp2p_opts.transport.is_quic() && !cfg!(feature = "p2p-quic") {
+                        dispatcher.push(P2pConnectionOutgoingAction::FinalizeError {
+                            peer_id: libp2p_opts.peer_id,
+                            error: "QUIC transport is not supported".to_owned(),
+                        });
+                    }
                     return Ok(());
                 }
 
@@ -114,6 +126,12 @@ impl P2pConnectionOutgoingState {
                 #[cfg(feature = "p2p-libp2p")]
                 if let P2pConnectionOutgoingInitOpts::LibP2P(libp2p_opts) = &opts {
                     match SocketAddr::try_from(libp2p_opts) {
+                        Ok(addr) if libp2p_opts.transport.is_quic() => {
+                            dispatcher.push(P2pNetworkQuicAction::OutgoingConnect {
+                                addr,
+                                peer_id: libp2p_opts.peer_id,
+                            });
+                        }
                         Ok(addr) => {
                             dispatcher.push(P2pNetworkSchedulerAction::OutgoingConnect { addr });
                         }
@@ -129,6 +147,12 @@ impl P2pConnectionOutgoingState {
                     dispatcher.push(P2pConnectionOutgoingAction::FinalizePending {
                         peer_id: *opts.peer_id(),
                     });
+                    if libp2p_opts.transport.is_quic() && !cfg!(feature = "p2p-quic") {
+                        dispatcher.push(P2pConnectionOutgoingAction::FinalizeError {
+                            peer_id: libp2p_opts.peer_id,
+                            error: "QUIC transport is not supported".to_owned(),
+                        });
+                    }
                     return Ok(());
                 }
 
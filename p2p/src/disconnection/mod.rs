@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     channels::{rpc::P2pRpcKind, streaming_rpc::P2pStreamingRpcKind, ChannelId},
     connection::RejectionReason,
+    reputation::P2pPenaltyReason,
 };
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, thiserror::Error)]
@@ -35,6 +36,8 @@ pub enum P2pDisconnectionReason {
     TransitionFrontierSyncLedgerSnarkedNumAccountsRejected,
     #[error("failed to verify snark pool diff")]
     SnarkPoolVerifyError,
+    #[error("peer is banned: {0}")]
+    Banned(P2pPenaltyReason),
     #[error("duplicate connection")]
     DuplicateConnection,
     #[error("timeout")]
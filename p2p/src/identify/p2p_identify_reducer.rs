@@ -5,8 +5,8 @@ use crate::{
     connection::outgoing::P2pConnectionOutgoingInitOpts,
     disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
     token::{BroadcastAlgorithm, DiscoveryAlgorithm, IdentifyAlgorithm, RpcAlgorithm, StreamKind},
-    P2pNetworkConnectionMuxState, P2pNetworkKadRequestAction, P2pNetworkKadState,
-    P2pNetworkKademliaAction, P2pNetworkYamuxAction, P2pState, YamuxStreamKind,
+    P2pNetworkKadRequestAction, P2pNetworkKadState, P2pNetworkKademliaAction, P2pNetworkNatAction,
+    P2pNetworkYamuxAction, P2pState, YamuxStreamKind,
 };
 
 use super::P2pIdentifyAction;
@@ -39,9 +39,8 @@ impl P2pState {
                             .map(|mux| (mux, conn.incoming))
                             .ok_or_else(|| format!("multiplexing is not ready for {addr}"))
                     })
-                    .and_then(|(P2pNetworkConnectionMuxState::Yamux(yamux), incoming)| {
-                        yamux
-                            .next_stream_id(crate::YamuxStreamKind::Identify, incoming)
+                    .and_then(|(mux, incoming)| {
+                        mux.next_stream_id(crate::YamuxStreamKind::Identify, incoming)
                             .ok_or_else(|| format!("cannot get next stream for {addr}"))
                     })?;
 
@@ -73,6 +72,10 @@ impl P2pState {
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
 
+                if let Some(addr) = info.observed_addr {
+                    dispatcher.push(P2pNetworkNatAction::ObservedAddr { peer_id, addr });
+                }
+
                 dispatcher.push(P2pNetworkKademliaAction::UpdateRoutingTable {
                     peer_id,
                     addrs: info.listen_addrs,
@@ -4,6 +4,7 @@ pub mod connection;
 pub mod disconnection;
 pub mod disconnection_effectful;
 pub mod identity;
+pub mod reputation;
 use bootstrap::P2pNetworkKadBootstrapState;
 use channels::{
     best_tip::P2pChannelsBestTipAction,
@@ -32,6 +33,7 @@ use network::identify::{
     P2pNetworkIdentifyStreamAction,
 };
 use openmina_core::SubstateAccess;
+use reputation::P2pReputationAction;
 
 pub mod webrtc;
 
@@ -135,6 +137,7 @@ pub trait P2pActionTrait<State>:
     + From<P2pNetworkRpcAction>
     + From<P2pChannelsRpcAction>
     + From<P2pDisconnectionAction>
+    + From<P2pReputationAction>
     + From<P2pNetworkSchedulerEffectfulAction>
     + From<P2pChannelsBestTipAction>
     + From<P2pChannelsSnarkJobCommitmentAction>
@@ -89,6 +89,13 @@ impl P2pNetworkIdentifyStreamState {
                         .iter()
                         .cloned()
                         .collect::<Vec<_>>();
+                    let quic_addresses = p2p_state
+                        .network
+                        .scheduler
+                        .quic_listeners
+                        .iter()
+                        .cloned()
+                        .collect::<Vec<_>>();
 
                     dispatcher.push(
                         P2pNetworkIdentifyStreamEffectfulAction::GetListenAddresses {
@@ -96,6 +103,7 @@ impl P2pNetworkIdentifyStreamState {
                             peer_id,
                             stream_id,
                             addresses,
+                            quic_addresses,
                         },
                     );
                 }
@@ -374,14 +382,18 @@ impl P2pNetworkIdentifyStreamState {
         Action: crate::P2pActionTrait<State>,
         State: crate::P2pStateTrait,
     {
-        let config = &state.config;
-        let ips = &config.external_addrs;
-        let port = config.libp2p_port.unwrap_or(8302);
+        for external_addr in state.external_addrs() {
+            if !listen_addrs.contains(&external_addr) {
+                listen_addrs.push(external_addr);
+            }
+        }
 
-        listen_addrs.extend(
-            ips.iter()
-                .map(|ip| Multiaddr::from(*ip).with(multiaddr::Protocol::Tcp(port))),
-        );
+        // lets the peer learn its external address, see `P2pNetworkNatAction::ObservedAddr`
+        let observed_addr = state.network.scheduler.connection_state(&addr).map(|conn| {
+            std::iter::once(addr.sock_addr.ip().into())
+                .chain(conn.transport().protocols(addr.sock_addr.port()))
+                .collect()
+        });
 
         let public_key = Some(state.config.identity_pub_key.clone());
 
@@ -401,8 +413,7 @@ impl P2pNetworkIdentifyStreamState {
             agent_version: Some("openmina".to_owned()),
             public_key,
             listen_addrs,
-            // TODO: other peers seem to report inaccurate information, should we implement this?
-            observed_addr: None,
+            observed_addr,
             protocols,
         };
 
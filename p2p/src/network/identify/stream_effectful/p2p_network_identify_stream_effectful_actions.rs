@@ -13,6 +13,7 @@ pub enum P2pNetworkIdentifyStreamEffectfulAction {
         peer_id: PeerId,
         stream_id: StreamId,
         addresses: Vec<SocketAddr>,
+        quic_addresses: Vec<SocketAddr>,
     },
 }
 
@@ -4,9 +4,12 @@ use redux::ActionMeta;
 use std::net::SocketAddr;
 
 use super::P2pNetworkIdentifyStreamEffectfulAction;
-use crate::{network::identify::P2pNetworkIdentifyStreamAction, P2pNetworkService};
+use crate::{
+    connection::outgoing::P2pLibp2pTransport, network::identify::P2pNetworkIdentifyStreamAction,
+    P2pNetworkService,
+};
 
-fn get_addrs<I, S>(addr: &SocketAddr, net_svc: &mut S) -> I
+fn get_addrs<I, S>(addr: &SocketAddr, transport: P2pLibp2pTransport, net_svc: &mut S) -> I
 where
     S: P2pNetworkService,
     I: FromIterator<Multiaddr>,
@@ -27,7 +30,11 @@ where
     };
     ip_addrs
         .into_iter()
-        .map(|addr| Multiaddr::from(addr).with(multiaddr::Protocol::Tcp(port)))
+        .map(|addr| {
+            std::iter::once(multiaddr::Protocol::from(addr))
+                .chain(transport.protocols(port))
+                .collect()
+        })
         .collect()
 }
 
@@ -43,10 +50,22 @@ impl P2pNetworkIdentifyStreamEffectfulAction {
                 peer_id,
                 stream_id,
                 addresses,
+                quic_addresses,
             } => {
                 let mut listen_addresses = Vec::new();
                 for addr in addresses {
-                    listen_addresses.extend(get_addrs::<Vec<_>, _>(&addr, store.service()))
+                    listen_addresses.extend(get_addrs::<Vec<_>, _>(
+                        &addr,
+                        P2pLibp2pTransport::Tcp,
+                        store.service(),
+                    ))
+                }
+                for addr in quic_addresses {
+                    listen_addresses.extend(get_addrs::<Vec<_>, _>(
+                        &addr,
+                        P2pLibp2pTransport::Quic,
+                        store.service(),
+                    ))
                 }
 
                 store.dispatch(P2pNetworkIdentifyStreamAction::SendIdentify {
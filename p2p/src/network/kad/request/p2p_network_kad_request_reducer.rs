@@ -3,9 +3,9 @@ use redux::{ActionWithMeta, Dispatcher};
 
 use crate::{
     connection::outgoing::P2pConnectionOutgoingAction, ConnectionAddr,
-    P2pNetworkConnectionMuxState, P2pNetworkKadBootstrapAction, P2pNetworkKadEffectfulAction,
-    P2pNetworkKadState, P2pNetworkKademliaRpcRequest, P2pNetworkKademliaStreamAction,
-    P2pNetworkYamuxAction, P2pPeerState, P2pState, PeerId,
+    P2pNetworkKadBootstrapAction, P2pNetworkKadEffectfulAction, P2pNetworkKadState,
+    P2pNetworkKademliaRpcRequest, P2pNetworkKademliaStreamAction, P2pNetworkYamuxAction,
+    P2pPeerState, P2pState, PeerId,
 };
 
 use super::{P2pNetworkKadRequestAction, P2pNetworkKadRequestState, P2pNetworkKadRequestStatus};
@@ -82,14 +82,9 @@ impl P2pNetworkKadRequestState {
 
                         return Ok(());
                     };
-                    if let Some(stream_id) = conn_state.mux.as_ref().and_then(
-                        |P2pNetworkConnectionMuxState::Yamux(yamux)| {
-                            yamux.next_stream_id(
-                                crate::YamuxStreamKind::Kademlia,
-                                conn_state.incoming,
-                            )
-                        },
-                    ) {
+                    if let Some(stream_id) = conn_state.mux.as_ref().and_then(|mux| {
+                        mux.next_stream_id(crate::YamuxStreamKind::Kademlia, conn_state.incoming)
+                    }) {
                         // multiplexing is ready, open a stream
                         // TODO: add callbacks
                         dispatcher.push(P2pNetworkYamuxAction::OpenStream {
@@ -150,9 +145,8 @@ impl P2pNetworkKadRequestState {
                             .map(|mux| (mux, conn.incoming))
                             .ok_or_else(|| format!("multiplexing is not ready for {addr}"))
                     })
-                    .and_then(|(P2pNetworkConnectionMuxState::Yamux(yamux), incoming)| {
-                        yamux
-                            .next_stream_id(crate::YamuxStreamKind::Kademlia, incoming)
+                    .and_then(|(mux, incoming)| {
+                        mux.next_stream_id(crate::YamuxStreamKind::Kademlia, incoming)
                             .ok_or_else(|| format!("cannot get next stream for {addr}"))
                     })?;
 
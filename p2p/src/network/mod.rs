@@ -41,6 +41,18 @@ pub mod yamux;
 use self::stream::{P2pNetworkKadIncomingStreamError, P2pNetworkKadOutgoingStreamError};
 pub use self::yamux::*;
 
+pub mod quic;
+pub use self::quic::*;
+
+pub mod quic_effectful;
+pub use self::quic_effectful::*;
+
+pub mod nat;
+pub use self::nat::*;
+
+pub mod nat_effectful;
+pub use self::nat_effectful::*;
+
 pub mod identify;
 
 pub mod kad;
@@ -0,0 +1,8 @@
+mod p2p_network_nat_actions;
+pub use self::p2p_network_nat_actions::*;
+
+mod p2p_network_nat_state;
+pub use self::p2p_network_nat_state::*;
+
+#[cfg(feature = "p2p-libp2p")]
+mod p2p_network_nat_reducer;
@@ -0,0 +1,46 @@
+use multiaddr::Multiaddr;
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{P2pState, PeerId};
+
+use super::P2pNetworkNatMapping;
+
+/// Discovery of our external addresses, from the addresses observed by
+/// peers and from the port mapping on the NAT gateway.
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(display(peer_id), display(addr), debug(mapping), display(error)))]
+pub enum P2pNetworkNatAction {
+    /// The peer reported the address it sees our connection coming from.
+    #[action_event(level = debug)]
+    ObservedAddr { peer_id: PeerId, addr: Multiaddr },
+    /// Request the gateway to map the libp2p port, or refresh the mapping.
+    MappingRequest,
+    #[action_event(level = info)]
+    MappingSuccess { mapping: P2pNetworkNatMapping },
+    #[action_event(level = warn)]
+    MappingError { error: String },
+}
+
+impl From<P2pNetworkNatAction> for crate::P2pAction {
+    fn from(a: P2pNetworkNatAction) -> Self {
+        Self::Network(a.into())
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkNatAction {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        let nat = &state.network.scheduler.nat;
+        match self {
+            P2pNetworkNatAction::ObservedAddr { peer_id, .. } => *peer_id != state.my_id(),
+            P2pNetworkNatAction::MappingRequest => {
+                state.config.nat.port_mapping
+                    && state.config.libp2p_port.is_some()
+                    && !state.network.scheduler.listeners.is_empty()
+                    && nat.mapping.should_request(time)
+            }
+            P2pNetworkNatAction::MappingSuccess { .. }
+            | P2pNetworkNatAction::MappingError { .. } => nat.mapping.is_pending(),
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use openmina_core::Substate;
+use redux::Dispatcher;
+
+use crate::{P2pNetworkKademliaAction, P2pState};
+
+use super::{super::*, *};
+
+impl P2pNetworkNatState {
+    pub fn reducer<State, Action>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: redux::ActionWithMeta<P2pNetworkNatAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+        let config = p2p_state.config.nat.clone();
+        let libp2p_port = p2p_state.config.libp2p_port;
+        let nat_state = &mut p2p_state.network.scheduler.nat;
+        let time = meta.time();
+
+        match action {
+            P2pNetworkNatAction::ObservedAddr { peer_id, addr } => {
+                let Some(ip) = nat_observed_ip(&addr) else {
+                    return Ok(());
+                };
+                nat_state.add_observation(peer_id, P2pNetworkNatObservation { ip, time });
+
+                let confirmed = nat_state.confirmed_ips(time, config.observed_addr_confirmations);
+                let is_new = !confirmed.is_subset(&nat_state.confirmed);
+                nat_state.confirmed = confirmed;
+
+                if is_new {
+                    let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                    let p2p_state: &P2pState = state.substate()?;
+                    Self::publish_external_addrs(dispatcher, p2p_state);
+                }
+                Ok(())
+            }
+            P2pNetworkNatAction::MappingRequest => {
+                let Some(internal_port) = libp2p_port else {
+                    return Ok(());
+                };
+                // Keep advertising the current mapping until the gateway
+                // answers the refresh.
+                nat_state.mapping = match nat_state.mapping().cloned() {
+                    Some(mapping) => P2pNetworkNatMappingState::Renewing { time, mapping },
+                    None => P2pNetworkNatMappingState::Pending { time },
+                };
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkNatEffectfulAction::MapPort {
+                    gateway: config.gateway,
+                    internal_port,
+                    lifetime: config.mapping_lifetime,
+                });
+                Ok(())
+            }
+            P2pNetworkNatAction::MappingSuccess { mapping } => {
+                let is_new = nat_state.mapping() != Some(&mapping);
+                nat_state.mapping = P2pNetworkNatMappingState::Ready { time, mapping };
+
+                if is_new {
+                    let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                    let p2p_state: &P2pState = state.substate()?;
+                    Self::publish_external_addrs(dispatcher, p2p_state);
+                }
+                Ok(())
+            }
+            P2pNetworkNatAction::MappingError { error } => {
+                nat_state.mapping = P2pNetworkNatMappingState::Error { time, error };
+                Ok(())
+            }
+        }
+    }
+
+    /// Adds the external addresses to our own Kademlia entry. Identify
+    /// picks them up from the state when answering the next request.
+    fn publish_external_addrs<State, Action>(
+        dispatcher: &mut Dispatcher<Action, State>,
+        state: &P2pState,
+    ) where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let addrs = state.external_addrs();
+        if !addrs.is_empty() {
+            dispatcher.push(P2pNetworkKademliaAction::UpdateRoutingTable {
+                peer_id: state.my_id(),
+                addrs,
+            });
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::IpAddr,
+    time::Duration,
+};
+
+use multiaddr::{Multiaddr, Protocol};
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::PeerId;
+
+/// Observations older than this are not counted as confirmations.
+pub const NAT_OBSERVED_ADDR_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// At most this many observations are kept, the oldest ones are evicted
+/// first.
+pub const NAT_OBSERVED_ADDR_MAX: usize = 256;
+
+/// Failed port mapping request is retried after this interval.
+pub const NAT_MAPPING_RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Our addresses as seen from the outside.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pNetworkNatState {
+    /// Our address as reported by each peer in identify `observed_addr`.
+    pub observed: BTreeMap<PeerId, P2pNetworkNatObservation>,
+    /// Observed addresses reported by enough distinct peers.
+    pub confirmed: BTreeSet<IpAddr>,
+    pub mapping: P2pNetworkNatMappingState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct P2pNetworkNatObservation {
+    pub ip: IpAddr,
+    pub time: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub enum P2pNetworkNatMappingState {
+    #[default]
+    Idle,
+    Pending {
+        time: Timestamp,
+    },
+    Ready {
+        time: Timestamp,
+        mapping: P2pNetworkNatMapping,
+    },
+    /// Refresh of the `mapping` is requested, it stays in use until the
+    /// gateway answers.
+    Renewing {
+        time: Timestamp,
+        mapping: P2pNetworkNatMapping,
+    },
+    Error {
+        time: Timestamp,
+        error: String,
+    },
+}
+
+/// Port mapping granted by the gateway.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct P2pNetworkNatMapping {
+    pub protocol: P2pNetworkNatProtocol,
+    pub external_ip: IpAddr,
+    pub external_port: u16,
+    pub internal_port: u16,
+    pub lifetime: Duration,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, derive_more::Display)]
+pub enum P2pNetworkNatProtocol {
+    #[display(fmt = "NAT-PMP")]
+    NatPmp,
+    #[display(fmt = "UPnP")]
+    Upnp,
+}
+
+impl P2pNetworkNatState {
+    /// Records the address observed by the peer, dropping the expired
+    /// observations and the oldest ones above [`NAT_OBSERVED_ADDR_MAX`].
+    pub fn add_observation(&mut self, peer_id: PeerId, observation: P2pNetworkNatObservation) {
+        let now = observation.time;
+        self.observed.insert(peer_id, observation);
+        self.observed
+            .retain(|_, observation| !observation.is_expired(now));
+        while self.observed.len() > NAT_OBSERVED_ADDR_MAX {
+            let Some(oldest) = self
+                .observed
+                .iter()
+                .min_by_key(|(_, observation)| observation.time)
+                .map(|(peer_id, _)| *peer_id)
+            else {
+                break;
+            };
+            self.observed.remove(&oldest);
+        }
+    }
+
+    /// Addresses reported by at least `confirmations` distinct peers
+    /// during the last [`NAT_OBSERVED_ADDR_TTL`].
+    pub fn confirmed_ips(&self, now: Timestamp, confirmations: usize) -> BTreeSet<IpAddr> {
+        let mut counts = BTreeMap::<IpAddr, usize>::new();
+        self.observed
+            .values()
+            .filter(|observation| !observation.is_expired(now))
+            .for_each(|observation| *counts.entry(observation.ip).or_default() += 1);
+        counts
+            .into_iter()
+            .filter(|(_, count)| *count >= confirmations.max(1))
+            .map(|(ip, _)| ip)
+            .collect()
+    }
+
+    pub fn mapping(&self) -> Option<&P2pNetworkNatMapping> {
+        match &self.mapping {
+            P2pNetworkNatMappingState::Ready { mapping, .. }
+            | P2pNetworkNatMappingState::Renewing { mapping, .. } => Some(mapping),
+            _ => None,
+        }
+    }
+}
+
+impl P2pNetworkNatObservation {
+    pub fn is_expired(&self, now: Timestamp) -> bool {
+        now.checked_sub(self.time)
+            .map_or(false, |dur| dur >= NAT_OBSERVED_ADDR_TTL)
+    }
+}
+
+impl P2pNetworkNatMappingState {
+    /// Whether the port mapping should be requested from the gateway, either
+    /// for the first time, to refresh it before it expires, or to retry
+    /// after an error.
+    ///
+    /// Refresh interval is at least [`NAT_MAPPING_RETRY_INTERVAL`], so that
+    /// a gateway granting very short (or zero) lifetimes doesn't make us
+    /// request the mapping on every tick.
+    pub fn should_request(&self, now: Timestamp) -> bool {
+        let passed = |time: &Timestamp, dur: Duration| {
+            now.checked_sub(*time).map_or(false, |passed| passed >= dur)
+        };
+        match self {
+            Self::Idle => true,
+            Self::Pending { .. } | Self::Renewing { .. } => false,
+            Self::Ready { time, mapping } => {
+                passed(time, (mapping.lifetime / 2).max(NAT_MAPPING_RETRY_INTERVAL))
+            }
+            Self::Error { time, .. } => passed(time, NAT_MAPPING_RETRY_INTERVAL),
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Pending { .. } | Self::Renewing { .. })
+    }
+}
+
+/// IP address of our node as observed by the remote peer. Only addresses
+/// that may be reachable from the outside are taken into account.
+pub fn nat_observed_ip(addr: &Multiaddr) -> Option<IpAddr> {
+    let ip = match addr.iter().next()? {
+        Protocol::Ip4(ip) => IpAddr::V4(ip),
+        Protocol::Ip6(ip) => IpAddr::V6(ip),
+        _ => return None,
+    };
+    (!ip.is_unspecified() && !ip.is_loopback() && !ip.is_multicast()).then_some(ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(n: u8) -> PeerId {
+        PeerId::from_bytes([n; 32])
+    }
+
+    #[test]
+    fn observed_ip_is_confirmed_by_distinct_peers() {
+        let ip: IpAddr = [203, 0, 113, 7].into();
+        let other: IpAddr = [198, 51, 100, 1].into();
+        let time = Timestamp::ZERO;
+        let mut state = P2pNetworkNatState::default();
+
+        for n in 0..2 {
+            state
+                .observed
+                .insert(peer(n), P2pNetworkNatObservation { ip, time });
+        }
+        state
+            .observed
+            .insert(peer(2), P2pNetworkNatObservation { ip: other, time });
+        assert!(state.confirmed_ips(time, 3).is_empty());
+
+        // the same peer reporting again doesn't count twice
+        state
+            .observed
+            .insert(peer(1), P2pNetworkNatObservation { ip, time });
+        assert!(state.confirmed_ips(time, 3).is_empty());
+
+        state
+            .observed
+            .insert(peer(2), P2pNetworkNatObservation { ip, time });
+        assert_eq!(state.confirmed_ips(time, 3), BTreeSet::from([ip]));
+
+        let later = time + NAT_OBSERVED_ADDR_TTL;
+        assert!(state.confirmed_ips(later, 3).is_empty());
+    }
+
+    #[test]
+    fn mapping_refresh_interval_is_clamped() {
+        let time = Timestamp::ZERO;
+        let ready = |lifetime| P2pNetworkNatMappingState::Ready {
+            time,
+            mapping: P2pNetworkNatMapping {
+                protocol: P2pNetworkNatProtocol::NatPmp,
+                external_ip: [203, 0, 113, 7].into(),
+                external_port: 8302,
+                internal_port: 8302,
+                lifetime,
+            },
+        };
+
+        let state = ready(Duration::from_secs(2 * 60 * 60));
+        assert!(!state.should_request(time + Duration::from_secs(60 * 60 - 1)));
+        assert!(state.should_request(time + Duration::from_secs(60 * 60)));
+
+        for lifetime in [Duration::ZERO, Duration::from_secs(10)] {
+            let state = ready(lifetime);
+            assert!(!state.should_request(time));
+            assert!(
+                !state.should_request(time + NAT_MAPPING_RETRY_INTERVAL - Duration::from_secs(1))
+            );
+            assert!(state.should_request(time + NAT_MAPPING_RETRY_INTERVAL));
+        }
+    }
+
+    #[test]
+    fn observations_are_capped() {
+        let ip: IpAddr = [203, 0, 113, 7].into();
+        let mut state = P2pNetworkNatState::default();
+
+        let total = NAT_OBSERVED_ADDR_MAX + 10;
+        for n in 0..total {
+            let time = Timestamp::ZERO + Duration::from_secs(n as u64);
+            let mut peer_id = [0; 32];
+            peer_id[..8].copy_from_slice(&(n as u64).to_be_bytes());
+            state.add_observation(
+                PeerId::from_bytes(peer_id),
+                P2pNetworkNatObservation { ip, time },
+            );
+        }
+        assert_eq!(state.observed.len(), NAT_OBSERVED_ADDR_MAX);
+        let oldest = state.observed.values().map(|o| o.time).min();
+        assert_eq!(oldest, Some(Timestamp::ZERO + Duration::from_secs(10)));
+
+        // expired observations are dropped with the next one
+        let later = Timestamp::ZERO + NAT_OBSERVED_ADDR_TTL + Duration::from_secs(total as u64);
+        state.add_observation(peer(1), P2pNetworkNatObservation { ip, time: later });
+        assert_eq!(state.observed.len(), 1);
+    }
+
+    #[test]
+    fn mapping_is_kept_while_renewing() {
+        let time = Timestamp::ZERO;
+        let mapping = P2pNetworkNatMapping {
+            protocol: P2pNetworkNatProtocol::Upnp,
+            external_ip: [203, 0, 113, 7].into(),
+            external_port: 8302,
+            internal_port: 8302,
+            lifetime: Duration::from_secs(60 * 60),
+        };
+        let state = P2pNetworkNatState {
+            mapping: P2pNetworkNatMappingState::Renewing {
+                time,
+                mapping: mapping.clone(),
+            },
+            ..Default::default()
+        };
+        assert_eq!(state.mapping(), Some(&mapping));
+        assert!(state.mapping.is_pending());
+        assert!(!state
+            .mapping
+            .should_request(time + NAT_MAPPING_RETRY_INTERVAL));
+    }
+
+    #[test]
+    fn local_observed_addrs_are_ignored() {
+        let addr: Multiaddr = "/ip4/127.0.0.1/tcp/8302".parse().expect("valid multiaddr");
+        assert_eq!(nat_observed_ip(&addr), None);
+
+        let addr: Multiaddr = "/ip4/203.0.113.7/udp/8302/quic-v1"
+            .parse()
+            .expect("valid multiaddr");
+        assert_eq!(nat_observed_ip(&addr), Some([203, 0, 113, 7].into()));
+    }
+}
@@ -0,0 +1,29 @@
+use std::{net::SocketAddr, time::Duration};
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::P2pState;
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(debug(gateway), internal_port, debug(lifetime)))]
+pub enum P2pNetworkNatEffectfulAction {
+    /// Request the gateway to forward the TCP port to us.
+    MapPort {
+        gateway: Option<SocketAddr>,
+        internal_port: u16,
+        lifetime: Duration,
+    },
+}
+
+impl From<P2pNetworkNatEffectfulAction> for crate::P2pEffectfulAction {
+    fn from(a: P2pNetworkNatEffectfulAction) -> crate::P2pEffectfulAction {
+        crate::P2pEffectfulAction::Network(crate::P2pNetworkEffectfulAction::Nat(a))
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkNatEffectfulAction {
+    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
+        true
+    }
+}
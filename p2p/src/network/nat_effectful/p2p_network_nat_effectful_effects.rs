@@ -0,0 +1,25 @@
+use redux::ActionMeta;
+
+use crate::{MioCmd, P2pMioService};
+
+use super::P2pNetworkNatEffectfulAction;
+
+impl P2pNetworkNatEffectfulAction {
+    pub fn effects<Store, S>(self, _meta: &ActionMeta, store: &mut Store)
+    where
+        Store: crate::P2pStore<S>,
+        Store::Service: P2pMioService,
+    {
+        match self {
+            P2pNetworkNatEffectfulAction::MapPort {
+                gateway,
+                internal_port,
+                lifetime,
+            } => {
+                store
+                    .service()
+                    .send_mio_cmd(MioCmd::NatMapPort(gateway, internal_port, lifetime));
+            }
+        }
+    }
+}
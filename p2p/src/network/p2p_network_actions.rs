@@ -2,8 +2,9 @@ use openmina_core::ActionEvent;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    identify::*, kad::*, noise::*, pnet::*, pnet_effectful::*, pubsub::*, rpc::*, scheduler::*,
-    select::*, yamux::*, P2pNetworkSchedulerEffectfulAction,
+    identify::*, kad::*, nat::*, nat_effectful::*, noise::*, pnet::*, pnet_effectful::*, pubsub::*,
+    quic::*, quic_effectful::*, rpc::*, scheduler::*, select::*, yamux::*,
+    P2pNetworkSchedulerEffectfulAction,
 };
 
 use crate::P2pState;
@@ -15,6 +16,8 @@ pub enum P2pNetworkAction {
     Select(P2pNetworkSelectAction),
     Noise(P2pNetworkNoiseAction),
     Yamux(P2pNetworkYamuxAction),
+    Quic(P2pNetworkQuicAction),
+    Nat(P2pNetworkNatAction),
     Identify(P2pNetworkIdentifyAction),
     Kad(P2pNetworkKadAction),
     Pubsub(P2pNetworkPubsubAction),
@@ -29,6 +32,8 @@ impl redux::EnablingCondition<P2pState> for P2pNetworkAction {
             Self::Select(v) => v.is_enabled(state, time),
             Self::Noise(v) => v.is_enabled(state, time),
             Self::Yamux(v) => v.is_enabled(state, time),
+            Self::Quic(v) => v.is_enabled(state, time),
+            Self::Nat(v) => v.is_enabled(state, time),
             Self::Identify(v) => v.is_enabled(state, time),
             Self::Kad(v) => v.is_enabled(state, time),
             Self::Pubsub(v) => v.is_enabled(state, time),
@@ -41,6 +46,8 @@ impl redux::EnablingCondition<P2pState> for P2pNetworkAction {
 pub enum P2pNetworkEffectfulAction {
     Scheduler(P2pNetworkSchedulerEffectfulAction),
     Pnet(P2pNetworkPnetEffectfulAction),
+    Quic(P2pNetworkQuicEffectfulAction),
+    Nat(P2pNetworkNatEffectfulAction),
     Pubsub(P2pNetworkPubsubEffectfulAction),
     Identify(P2pNetworkIdentifyEffectfulAction),
     Kad(P2pNetworkKadEffectfulAction),
@@ -51,6 +58,8 @@ impl redux::EnablingCondition<P2pState> for P2pNetworkEffectfulAction {
         match self {
             Self::Scheduler(v) => v.is_enabled(state, time),
             Self::Pnet(v) => v.is_enabled(state, time),
+            Self::Quic(v) => v.is_enabled(state, time),
+            Self::Nat(v) => v.is_enabled(state, time),
             Self::Pubsub(v) => v.is_enabled(state, time),
             Self::Identify(v) => v.is_enabled(state, time),
             Self::Kad(v) => v.is_enabled(state, time),
@@ -9,6 +9,8 @@ impl P2pNetworkEffectfulAction {
         match self {
             P2pNetworkEffectfulAction::Scheduler(a) => a.effects(meta, store),
             P2pNetworkEffectfulAction::Pnet(v) => v.effects(meta, store),
+            P2pNetworkEffectfulAction::Quic(v) => v.effects(meta, store),
+            P2pNetworkEffectfulAction::Nat(v) => v.effects(meta, store),
             P2pNetworkEffectfulAction::Pubsub(v) => v.effects(meta, store),
             P2pNetworkEffectfulAction::Identify(v) => v.effects(meta, store),
             P2pNetworkEffectfulAction::Kad(v) => v.effects(meta, store),
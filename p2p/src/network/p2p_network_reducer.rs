@@ -1,4 +1,4 @@
-use crate::P2pLimits;
+use crate::{P2pLimits, P2pMeshsubConfig};
 use identify::P2pNetworkIdentifyState;
 use openmina_core::Substate;
 
@@ -9,6 +9,7 @@ impl P2pNetworkState {
         state_context: Substate<Action, State, Self>,
         action: redux::ActionWithMeta<P2pNetworkAction>,
         limits: &P2pLimits,
+        meshsub: &P2pMeshsubConfig,
     ) -> Result<(), String>
     where
         State: crate::P2pStateTrait,
@@ -36,6 +37,14 @@ impl P2pNetworkState {
                 Substate::from_compatible_substate(state_context),
                 meta.with_action(a),
             ),
+            P2pNetworkAction::Quic(a) => P2pNetworkQuicState::reducer(
+                Substate::from_compatible_substate(state_context),
+                meta.with_action(a),
+            ),
+            P2pNetworkAction::Nat(a) => P2pNetworkNatState::reducer(
+                Substate::from_compatible_substate(state_context),
+                meta.with_action(a),
+            ),
             P2pNetworkAction::Identify(a) => P2pNetworkIdentifyState::reducer(
                 Substate::from_compatible_substate(state_context),
                 meta.with_action(a),
@@ -49,6 +58,7 @@ impl P2pNetworkState {
             P2pNetworkAction::Pubsub(a) => P2pNetworkPubsubState::reducer(
                 Substate::from_compatible_substate(state_context),
                 meta.with_action(a),
+                meshsub,
             ),
             P2pNetworkAction::Rpc(a) => P2pNetworkRpcState::reducer(
                 Substate::from_compatible_substate(state_context),
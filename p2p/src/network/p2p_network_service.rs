@@ -1,6 +1,9 @@
-use std::net::{IpAddr, SocketAddr};
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
-use crate::ConnectionAddr;
+use crate::{ConnectionAddr, PeerId, StreamId};
 
 /// The state machine sends commands to the service.
 pub enum MioCmd {
@@ -20,6 +23,20 @@ pub enum MioCmd {
     Send(ConnectionAddr, Box<[u8]>),
     /// Disconnect the remote peer.
     Disconnect(ConnectionAddr),
+
+    /// Bind a QUIC endpoint to the UDP socket, using the ALPN.
+    QuicListenOn(SocketAddr, Box<[u8]>),
+    /// Create a new outgoing QUIC connection to the peer, using the ALPN.
+    QuicConnect(SocketAddr, PeerId, Box<[u8]>),
+    /// Send the data in the QUIC stream, finish the stream if the flag is set.
+    /// The stream is opened if it doesn't exist yet.
+    QuicSend(ConnectionAddr, StreamId, Box<[u8]>, bool),
+    /// Reset the QUIC stream.
+    QuicReset(ConnectionAddr, StreamId),
+
+    /// Request the NAT gateway to forward the TCP port to us for the
+    /// duration. The default gateway is used if the address is not set.
+    NatMapPort(Option<SocketAddr>, u16, Duration),
 }
 
 pub trait P2pMioService: redux::Service {
@@ -38,12 +38,14 @@ impl P2pNetworkState {
             scheduler: P2pNetworkSchedulerState {
                 interfaces: Default::default(),
                 listeners: Default::default(),
+                quic_listeners: Default::default(),
                 local_pk: identity,
                 pnet_key,
                 connections: Default::default(),
                 broadcast_state: Default::default(),
                 identify_state: Default::default(),
                 discovery_state,
+                nat: Default::default(),
                 rpc_incoming_streams: Default::default(),
                 rpc_outgoing_streams: Default::default(),
             },
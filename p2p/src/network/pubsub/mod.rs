@@ -8,6 +8,7 @@ pub use self::p2p_network_pubsub_actions::P2pNetworkPubsubAction;
 mod p2p_network_pubsub_state;
 pub use self::p2p_network_pubsub_state::{
     P2pNetworkPubsubClientState, P2pNetworkPubsubClientTopicState, P2pNetworkPubsubState,
+    P2pNetworkPubsubTopicStats,
 };
 
 #[cfg(feature = "p2p-libp2p")]
@@ -16,5 +17,9 @@ mod p2p_network_pubsub_reducer;
 #[cfg(feature = "p2p-libp2p")]
 const TOPIC: &str = "coda/consensus-messages/0.0.1";
 
+/// Key of [`P2pNetworkPubsubState::topic_stats`] for messages of topics we
+/// aren't subscribed to.
+pub const TOPIC_STATS_OTHER: &str = "other";
+
 pub mod pubsub_effectful;
 pub use pubsub_effectful::P2pNetworkPubsubEffectfulAction;
@@ -69,6 +69,15 @@ pub enum P2pNetworkPubsubAction {
     /// Remove a peer from the mesh network for a specific topic.
     Prune { peer_id: PeerId, topic_id: String },
 
+    /// Periodic mesh maintenance.
+    ///
+    /// Grafts peers to meshes below `outbound_degree_low` and prunes
+    /// meshes above `outbound_degree_high`, grafts opportunistically,
+    /// emits gossip about recent messages to peers outside of the mesh
+    /// and connects to peers received with peer exchange.
+    #[action_event(level = trace)]
+    Heartbeat,
+
     /// Initiate the broadcasting of a message to all subscribed peers.
     ///
     /// **Fields:**
@@ -126,7 +135,7 @@ impl From<P2pNetworkPubsubAction> for crate::P2pAction {
 }
 
 impl redux::EnablingCondition<P2pState> for P2pNetworkPubsubAction {
-    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
         match self {
             P2pNetworkPubsubAction::OutgoingMessage { peer_id } => state
                 .network
@@ -135,6 +144,20 @@ impl redux::EnablingCondition<P2pState> for P2pNetworkPubsubAction {
                 .clients
                 .get(peer_id)
                 .map_or(false, |s| !s.message_is_empty()),
+            P2pNetworkPubsubAction::Heartbeat => state
+                .network
+                .scheduler
+                .broadcast_state
+                .last_heartbeat
+                .map_or(true, |last| {
+                    time.checked_sub(last)
+                        .map_or(false, |dur| dur >= state.config.meshsub.heartbeat_interval)
+                }),
+            // Drop gossip still in flight from peers we banned.
+            P2pNetworkPubsubAction::IncomingData { peer_id, .. }
+            | P2pNetworkPubsubAction::IncomingMessage { peer_id, .. } => {
+                !state.reputation.is_peer_banned(peer_id)
+            }
             _ => true,
         }
     }
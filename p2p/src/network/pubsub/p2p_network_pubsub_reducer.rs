@@ -1,27 +1,40 @@
-use std::collections::btree_map::Entry;
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    time::Duration,
+};
 
 use binprot::BinProtRead;
 use mina_p2p_messages::{gossip, v2};
+use multiaddr::{Multiaddr, Protocol};
 use openmina_core::{block::BlockWithHash, bug_condition, fuzz_maybe, fuzzed_maybe, Substate};
 use redux::{Dispatcher, Timestamp};
 
 use crate::{
     channels::{snark::P2pChannelsSnarkAction, transaction::P2pChannelsTransactionAction},
+    connection::outgoing::{
+        P2pConnectionOutgoingAction, P2pConnectionOutgoingInitLibp2pOpts,
+        P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+    },
     peer::P2pPeerAction,
-    Data, P2pConfig, P2pNetworkYamuxAction, PeerId,
+    reputation::{P2pPenaltyReason, P2pReputationAction},
+    webrtc::Host,
+    Data, P2pConfig, P2pMeshsubConfig, P2pNetworkKadKey, P2pNetworkYamuxAction, P2pState, PeerId,
 };
 
 use super::{
-    p2p_network_pubsub_state::P2pNetworkPubsubClientMeshAddingState,
+    p2p_network_pubsub_state::{
+        P2pNetworkPubsubClientMeshAddingState, MAX_PRUNE_BACKOFF, MAX_PX_PEERS,
+    },
     pb::{self, Message},
     P2pNetworkPubsubAction, P2pNetworkPubsubClientState, P2pNetworkPubsubEffectfulAction,
-    P2pNetworkPubsubState, TOPIC,
+    P2pNetworkPubsubState, TOPIC, TOPIC_STATS_OTHER,
 };
 
 impl P2pNetworkPubsubState {
     pub fn reducer<Action, State>(
         mut state_context: Substate<Action, State, Self>,
         action: redux::ActionWithMeta<P2pNetworkPubsubAction>,
+        meshsub: &P2pMeshsubConfig,
     ) -> Result<(), String>
     where
         State: crate::P2pStateTrait,
@@ -29,6 +42,7 @@ impl P2pNetworkPubsubState {
     {
         let pubsub_state = state_context.get_substate_mut()?;
         let (action, meta) = action.split();
+        let time = meta.time();
 
         match action {
             P2pNetworkPubsubAction::NewStream {
@@ -108,16 +122,16 @@ impl P2pNetworkPubsubState {
                 }
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
-                let config: &P2pConfig = state.substate()?;
                 let state: &P2pNetworkPubsubState = state.substate()?;
 
-                let Some(map) = state.topics.get(TOPIC) else {
+                if !state.topics.contains_key(TOPIC) {
                     // must have this topic already
                     return Ok(());
                 };
                 dispatcher.push(P2pNetworkPubsubAction::OutgoingMessage { peer_id });
-                let mesh_size = map.values().filter(|s| s.on_mesh()).count();
-                if mesh_size < config.meshsub.outbound_degree_desired {
+                if state.mesh_size(TOPIC) < meshsub.outbound_degree_desired
+                    && state.can_graft(TOPIC, &peer_id, time)
+                {
                     dispatcher.push(P2pNetworkPubsubAction::Graft {
                         peer_id,
                         topic_id: TOPIC.to_owned(),
@@ -133,10 +147,12 @@ impl P2pNetworkPubsubState {
                 addr,
                 ..
             } => {
-                pubsub_state.reduce_incoming_data(&peer_id, data, meta.time())?;
+                pubsub_state.reduce_incoming_data(&peer_id, data, time, meshsub)?;
 
                 let dispatcher = state_context.into_dispatcher();
 
+                // Replies to control messages, e.g. prunes refusing their grafts.
+                dispatcher.push(P2pNetworkPubsubAction::OutgoingMessage { peer_id });
                 dispatcher.push(P2pNetworkPubsubAction::ValidateIncomingMessages {
                     peer_id,
                     seen_limit,
@@ -181,24 +197,15 @@ impl P2pNetworkPubsubState {
 
                 dispatcher.push(P2pNetworkPubsubAction::IncomingMessageCleanup { peer_id });
 
+                if reduce_incoming_result.is_err() {
+                    dispatcher.push(P2pReputationAction::Penalize {
+                        peer_id,
+                        reason: P2pPenaltyReason::MalformedMessage,
+                    });
+                }
                 reduce_incoming_result?;
 
                 let state: &Self = global_state.substate()?;
-                let config: &P2pConfig = global_state.substate()?;
-
-                for (topic_id, map) in &state.topics {
-                    let mesh_size = map.values().filter(|s| s.on_mesh()).count();
-                    let could_accept = mesh_size < config.meshsub.outbound_degree_high;
-
-                    if !could_accept {
-                        if let Some(topic_state) = map.get(&peer_id) {
-                            if topic_state.on_mesh() {
-                                let topic_id = topic_id.clone();
-                                dispatcher.push(P2pNetworkPubsubAction::Prune { peer_id, topic_id })
-                            }
-                        }
-                    }
-                }
 
                 if let Err(error) = Self::broadcast(dispatcher, global_state) {
                     bug_condition!(
@@ -271,40 +278,41 @@ impl P2pNetworkPubsubState {
                 Ok(())
             }
             P2pNetworkPubsubAction::Prune { peer_id, topic_id } => {
-                let Some(state) = pubsub_state
-                    .topics
-                    .get_mut(&topic_id)
-                    .and_then(|m| m.get_mut(&peer_id))
-                else {
+                if !pubsub_state.prune_mesh_peer(&peer_id, &topic_id, time, meshsub) {
                     bug_condition!("State not found for action: `P2pNetworkPubsubAction::Prune`");
                     return Ok(());
-                };
-                state.mesh = P2pNetworkPubsubClientMeshAddingState::WeRefused;
-
-                if let Some(state) = pubsub_state.clients.get_mut(&peer_id) {
-                    let control = state
-                        .message
-                        .control
-                        .get_or_insert_with(|| pb::ControlMessage {
-                            ihave: vec![],
-                            iwant: vec![],
-                            graft: vec![],
-                            prune: vec![],
-                        });
-                    control.prune.push(pb::ControlPrune {
-                        topic_id: Some(topic_id),
-                        peers: vec![pb::PeerInfo {
-                            peer_id: None,
-                            signed_peer_record: None,
-                        }],
-                        backoff: None,
-                    });
                 }
 
                 let dispatcher = state_context.into_dispatcher();
                 dispatcher.push(P2pNetworkPubsubAction::OutgoingMessage { peer_id });
                 Ok(())
             }
+            P2pNetworkPubsubAction::Heartbeat => {
+                pubsub_state.last_heartbeat = Some(time);
+                pubsub_state.heartbeat_ticks = pubsub_state.heartbeat_ticks.wrapping_add(1);
+                pubsub_state.expire_backoff(time);
+                pubsub_state.emit_gossip(meshsub);
+                pubsub_state.mcache.shift_window();
+                let px_peers = std::mem::take(&mut pubsub_state.px_peers);
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+
+                for action in Self::maintain_mesh(p2p_state, meshsub, time) {
+                    dispatcher.push(action);
+                }
+                if meshsub.peer_exchange {
+                    for opts in Self::px_dial_opts(p2p_state, meshsub, time, px_peers) {
+                        dispatcher.push(P2pConnectionOutgoingAction::Init {
+                            opts,
+                            rpc_id: None,
+                            on_success: None,
+                        });
+                    }
+                }
+
+                Self::broadcast(dispatcher, state)
+            }
             P2pNetworkPubsubAction::OutgoingMessage { peer_id } => {
                 let msg = if let Some(v) = pubsub_state.clients.get_mut(&peer_id) {
                     &v.message
@@ -408,10 +416,11 @@ impl P2pNetworkPubsubState {
             P2pNetworkPubsubAction::BroadcastSigned { signature } => {
                 if let Some(mut message) = pubsub_state.to_sign.pop_front() {
                     message.signature = Some(signature.0.to_vec());
-                    pubsub_state
-                        .clients
-                        .iter_mut()
-                        .for_each(|(_, state)| state.publish(&message));
+                    let topic = message.topic.clone();
+                    if !pubsub_state.publish_own_message(message, meshsub) {
+                        bug_condition!("publishing to unknown topic {topic}");
+                        return Ok(());
+                    }
                 }
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
@@ -468,6 +477,17 @@ impl P2pNetworkPubsubState {
         Ok(())
     }
 
+    /// Stats of the topic, or of [`TOPIC_STATS_OTHER`] for topics
+    /// we aren't subscribed to, so peers can't grow the map.
+    fn topic_stats_mut(&mut self, topic: &str) -> &mut P2pNetworkPubsubTopicStats {
+        let key = if topic == TOPIC {
+            topic
+        } else {
+            TOPIC_STATS_OTHER
+        };
+        self.topic_stats.entry(key.to_owned()).or_default()
+    }
+
     #[inline(never)]
     fn reduce_incoming_message(
         &mut self,
@@ -475,6 +495,9 @@ impl P2pNetworkPubsubState {
         message: Message,
         seen_limit: usize,
     ) -> Result<(), String> {
+        let stats = self.topic_stats_mut(&message.topic);
+        stats.received = stats.received.saturating_add(1);
+
         let topic = self.topics.entry(message.topic.clone()).or_default();
 
         if let Some(signature) = &message.signature {
@@ -486,6 +509,7 @@ impl P2pNetworkPubsubState {
                     self.seen.pop_front();
                 }
             } else {
+                stats.duplicates = stats.duplicates.saturating_add(1);
                 return Ok(());
             }
         }
@@ -516,10 +540,11 @@ impl P2pNetworkPubsubState {
             }
         }
 
-        let message_id = self.mcache.put(message.clone());
+        self.mcache.put(message.clone());
 
         // TODO: this should only happen after the contents have been validated.
         // The only validation that has happened so far is that the message can be parsed.
+        // Peers outside of the mesh learn about the message from the heartbeat gossip.
         self.clients
             .iter_mut()
             .filter(|(c, _)| {
@@ -527,17 +552,8 @@ impl P2pNetworkPubsubState {
                 *c != &peer_id
             })
             .for_each(|(c, state)| {
-                let Some(topic_state) = topic.get(c) else {
-                    return;
-                };
-                if topic_state.on_mesh() {
+                if topic.get(c).map_or(false, |s| s.on_mesh()) {
                     state.publish(&message)
-                } else {
-                    let ctr = state.message.control.get_or_insert_with(Default::default);
-                    ctr.ihave.push(pb::ControlIHave {
-                        topic_id: Some(message.topic.clone()),
-                        message_ids: message_id.clone().into_iter().collect(),
-                    })
                 }
             });
 
@@ -561,6 +577,7 @@ impl P2pNetworkPubsubState {
         peer_id: &PeerId,
         data: Data,
         timestamp: Timestamp,
+        meshsub: &P2pMeshsubConfig,
     ) -> Result<(), String> {
         let Some(client_state) = self.clients.get_mut(peer_id) else {
             // TODO: investigate, cannot reproduce this
@@ -580,7 +597,7 @@ impl P2pNetworkPubsubState {
                 let control = decoded.control.unwrap_or_default();
 
                 self.update_subscriptions(peer_id, subscriptions);
-                self.apply_control_commands(peer_id, &control);
+                self.apply_control_commands(peer_id, &control, timestamp, meshsub);
                 self.respond_to_iwant_requests(peer_id, &control.iwant);
                 self.process_ihave_messages(peer_id, control.ihave, timestamp);
             }
@@ -616,12 +633,30 @@ impl P2pNetworkPubsubState {
     }
 
     /// Applies control commands (`graft` and `prune`) to manage the peer's mesh states within topics.
-    fn apply_control_commands(&mut self, peer_id: &PeerId, control: &pb::ControlMessage) {
-        // Apply graft commands to add the peer to specific topic meshes.
+    fn apply_control_commands(
+        &mut self,
+        peer_id: &PeerId,
+        control: &pb::ControlMessage,
+        time: Timestamp,
+        meshsub: &P2pMeshsubConfig,
+    ) {
+        // Apply graft commands to add the peer to specific topic meshes,
+        // unless it is backing off or the mesh is full.
         for graft in &control.graft {
-            if let Some(mesh_state) = self
+            let topic_id = graft.topic_id();
+            let Some(mesh_state) = self.topics.get(topic_id).and_then(|m| m.get(peer_id)) else {
+                continue;
+            };
+            if mesh_state.on_mesh() {
+                continue;
+            }
+            if self.is_backoff(topic_id, peer_id, time)
+                || self.mesh_size(topic_id) >= meshsub.outbound_degree_high
+            {
+                self.prune_mesh_peer(peer_id, topic_id, time, meshsub);
+            } else if let Some(mesh_state) = self
                 .topics
-                .get_mut(graft.topic_id())
+                .get_mut(topic_id)
                 .and_then(|m| m.get_mut(peer_id))
             {
                 mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::Added;
@@ -630,14 +665,272 @@ impl P2pNetworkPubsubState {
 
         // Apply prune commands to remove the peer from specific topic meshes.
         for prune in &control.prune {
-            if let Some(mesh_state) = self
+            let topic_id = prune.topic_id();
+            let Some(mesh_state) = self
                 .topics
-                .get_mut(prune.topic_id())
+                .get_mut(topic_id)
                 .and_then(|m| m.get_mut(peer_id))
+            else {
+                continue;
+            };
+            mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::TheyRefused;
+
+            let backoff = prune
+                .backoff
+                .map_or(meshsub.prune_backoff, Duration::from_secs)
+                .min(MAX_PRUNE_BACKOFF);
+            self.set_backoff(topic_id, *peer_id, time + backoff);
+
+            // Score of the sender is checked before dialing the peers.
+            if meshsub.peer_exchange {
+                let px_peers = prune
+                    .peers
+                    .iter()
+                    .filter_map(|info| info.peer_id.as_ref())
+                    .filter_map(|bytes| libp2p_identity::PeerId::from_bytes(bytes).ok())
+                    .filter_map(|peer_id| PeerId::try_from(peer_id).ok());
+                for px_peer in px_peers {
+                    if self.px_peers.len() >= MAX_PX_PEERS {
+                        break;
+                    }
+                    self.px_peers.entry(px_peer).or_insert(*peer_id);
+                }
+            }
+        }
+    }
+
+    /// Removes the peer from the topic mesh, and queues a prune with the
+    /// backoff and peer exchange for it.
+    ///
+    /// Returns `false` if the peer is not subscribed to the topic.
+    fn prune_mesh_peer(
+        &mut self,
+        peer_id: &PeerId,
+        topic_id: &str,
+        time: Timestamp,
+        meshsub: &P2pMeshsubConfig,
+    ) -> bool {
+        let Some(mesh_state) = self
+            .topics
+            .get_mut(topic_id)
+            .and_then(|m| m.get_mut(peer_id))
+        else {
+            return false;
+        };
+        mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::WeRefused;
+        self.set_backoff(topic_id, *peer_id, time + meshsub.prune_backoff);
+
+        let peers = if meshsub.peer_exchange {
+            self.topics
+                .get(topic_id)
+                .into_iter()
+                .flat_map(|m| m.keys())
+                .filter(|id| *id != peer_id)
+                .filter_map(|id| libp2p_identity::PeerId::try_from(*id).ok())
+                .take(meshsub.prune_peers)
+                .map(|id| pb::PeerInfo {
+                    peer_id: Some(id.to_bytes()),
+                    signed_peer_record: None,
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        if let Some(state) = self.clients.get_mut(peer_id) {
+            let control = state.message.control.get_or_insert_with(Default::default);
+            control.prune.push(pb::ControlPrune {
+                topic_id: Some(topic_id.to_owned()),
+                peers,
+                backoff: Some(meshsub.prune_backoff.as_secs()),
+            });
+        }
+        true
+    }
+
+    /// Queues our own signed message for the mesh peers, or for all peers
+    /// subscribed to the topic with flood publishing.
+    ///
+    /// Returns `false` if we are not subscribed to the topic.
+    fn publish_own_message(&mut self, message: pb::Message, meshsub: &P2pMeshsubConfig) -> bool {
+        let Some(topic) = self.topics.get(&message.topic) else {
+            return false;
+        };
+        let stats = self.topic_stats_mut(&message.topic);
+        stats.published = stats.published.saturating_add(1);
+
+        // Our own message must not be processed again when a peer
+        // sends it back, and must be available for iwant requests.
+        if let Some(signature) = &message.signature {
+            self.seen.push_back(signature.clone());
+            if self.seen.len() > meshsub.mcache_len {
+                self.seen.pop_front();
+            }
+        }
+
+        self.clients
+            .iter_mut()
+            .filter(|(peer_id, _)| {
+                topic.get(peer_id).map_or(false, |topic_state| {
+                    meshsub.flood_publish || topic_state.on_mesh()
+                })
+            })
+            .for_each(|(_, state)| state.publish(&message));
+        self.mcache.put(message);
+        true
+    }
+
+    /// Queues ihave with the messages from the recent heartbeats for a
+    /// rotating selection of peers outside of the mesh.
+    fn emit_gossip(&mut self, meshsub: &P2pMeshsubConfig) {
+        let offset = self.heartbeat_ticks as usize;
+
+        for (topic_id, message_ids) in self.mcache.gossip_ids() {
+            let Some(peers) = self.topics.get(&topic_id) else {
+                continue;
+            };
+            let candidates = peers
+                .iter()
+                .filter(|(_, s)| !s.on_mesh())
+                .map(|(peer_id, _)| *peer_id)
+                .filter(|peer_id| {
+                    self.clients
+                        .get(peer_id)
+                        .map_or(false, |c| c.outgoing_stream_id.is_some())
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                continue;
+            }
+
+            let n = candidates.len();
+            let count = meshsub
+                .gossip_lazy
+                .max((meshsub.gossip_factor * n as f64).ceil() as usize)
+                .min(n);
+            for peer_id in candidates.iter().cycle().skip(offset % n).take(count) {
+                if let Some(client) = self.clients.get_mut(peer_id) {
+                    let ctr = client.message.control.get_or_insert_with(Default::default);
+                    ctr.ihave.push(pb::ControlIHave {
+                        topic_id: Some(topic_id.clone()),
+                        message_ids: message_ids.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Grafts peers to meshes below `outbound_degree_low`, prunes the lowest
+    /// scoring peers from meshes above `outbound_degree_high`, and grafts
+    /// better scoring peers if the mesh median score is too low.
+    fn maintain_mesh(
+        p2p_state: &P2pState,
+        meshsub: &P2pMeshsubConfig,
+        time: Timestamp,
+    ) -> Vec<P2pNetworkPubsubAction> {
+        let state = &p2p_state.network.scheduler.broadcast_state;
+        let mut actions = vec![];
+        let score = |peer_id: &PeerId| {
+            p2p_state
+                .reputation
+                .peer_score(peer_id, time, &p2p_state.config.reputation)
+        };
+
+        for (topic_id, peers) in &state.topics {
+            let mut mesh = peers
+                .iter()
+                .filter(|(_, s)| s.on_mesh())
+                .map(|(peer_id, _)| (*peer_id, score(peer_id)))
+                .collect::<Vec<_>>();
+            // lowest score first
+            mesh.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            let mut candidates = peers
+                .iter()
+                .filter(|(peer_id, s)| {
+                    !s.on_mesh()
+                        && state.can_graft(topic_id, peer_id, time)
+                        && !p2p_state.reputation.is_peer_banned(peer_id)
+                })
+                .map(|(peer_id, _)| (*peer_id, score(peer_id)))
+                .filter(|(_, score)| *score >= 0.0)
+                .collect::<Vec<_>>();
+            // highest score first
+            candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            let to_graft = if mesh.len() < meshsub.outbound_degree_low {
+                meshsub.outbound_degree_desired.saturating_sub(mesh.len())
+            } else if mesh.len() > meshsub.outbound_degree_high {
+                let excess = mesh.len().saturating_sub(meshsub.outbound_degree_desired);
+                for (peer_id, _) in mesh.into_iter().take(excess) {
+                    actions.push(P2pNetworkPubsubAction::Prune {
+                        peer_id,
+                        topic_id: topic_id.clone(),
+                    });
+                }
+                continue;
+            } else if meshsub.opportunistic_graft_ticks > 0
+                && state.heartbeat_ticks % meshsub.opportunistic_graft_ticks == 0
+                && !mesh.is_empty()
             {
-                mesh_state.mesh = P2pNetworkPubsubClientMeshAddingState::TheyRefused;
+                let median = mesh[mesh.len() / 2].1;
+                if median >= meshsub.opportunistic_graft_threshold {
+                    continue;
+                }
+                candidates.retain(|(_, score)| *score > median);
+                meshsub.opportunistic_graft_peers
+            } else {
+                continue;
+            };
+
+            for (peer_id, _) in candidates.into_iter().take(to_graft) {
+                actions.push(P2pNetworkPubsubAction::Graft {
+                    peer_id,
+                    topic_id: topic_id.clone(),
+                });
             }
         }
+        actions
+    }
+
+    /// Dial options of peers received with peer exchange, if we know how
+    /// to dial them and the peer which sent them scores high enough.
+    fn px_dial_opts(
+        p2p_state: &P2pState,
+        meshsub: &P2pMeshsubConfig,
+        time: Timestamp,
+        px_peers: BTreeMap<PeerId, PeerId>,
+    ) -> Vec<P2pConnectionOutgoingInitOpts> {
+        let routing_table = p2p_state
+            .network
+            .scheduler
+            .discovery_state()
+            .map(|discovery_state| &discovery_state.routing_table);
+        let accept_px = |sender: &PeerId| {
+            p2p_state
+                .reputation
+                .peer_score(sender, time, &p2p_state.config.reputation)
+                >= meshsub.accept_px_threshold
+        };
+
+        px_peers
+            .into_iter()
+            .filter(|(_, sender)| accept_px(sender))
+            .filter_map(|(peer_id, _)| {
+                p2p_state
+                    .peers
+                    .get(&peer_id)
+                    .and_then(|peer| peer.dial_opts.clone())
+                    .or_else(|| {
+                        let key = P2pNetworkKadKey::try_from(&peer_id).ok()?;
+                        let entry = routing_table?.look_up(&key)?;
+                        entry
+                            .addresses()
+                            .iter()
+                            .find_map(|maddr| libp2p_dial_opts(peer_id, maddr))
+                    })
+            })
+            .collect()
     }
 
     fn respond_to_iwant_requests(&mut self, peer_id: &PeerId, iwant_requests: &[pb::ControlIWant]) {
@@ -702,3 +995,537 @@ impl P2pNetworkPubsubState {
         Ok(())
     }
 }
+
+fn libp2p_dial_opts(peer_id: PeerId, maddr: &Multiaddr) -> Option<P2pConnectionOutgoingInitOpts> {
+    let mut iter = maddr.iter();
+    let host = match iter.next()? {
+        Protocol::Ip4(ip) => Host::Ipv4(ip),
+        Protocol::Ip6(ip) => Host::Ipv6(ip),
+        _ => return None,
+    };
+    let Protocol::Tcp(port) = iter.next()? else {
+        return None;
+    };
+    Some(P2pConnectionOutgoingInitOpts::LibP2P(
+        P2pConnectionOutgoingInitLibp2pOpts {
+            peer_id,
+            host,
+            port,
+            transport: P2pLibp2pTransport::Tcp,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, net::Ipv4Addr};
+
+    use openmina_core::DEVNET_CHAIN_ID;
+
+    use super::super::p2p_network_pubsub_state::{
+        compute_message_id, P2pNetworkPubsubClientTopicState,
+    };
+    use super::*;
+    use crate::{
+        identity::SecretKey, reputation::P2pPeerReputation, token::BroadcastAlgorithm,
+        ConnectionAddr, P2pCallbacks, P2pLimits, P2pNatConfig, P2pPeerState, P2pPeerStatus,
+        P2pReputationConfig, P2pTimeouts,
+    };
+
+    fn test_p2p_state() -> P2pState {
+        let config = P2pConfig {
+            libp2p_port: None,
+            quic_port: None,
+            listen_port: None,
+            identity_pub_key: SecretKey::rand().public_key(),
+            initial_peers: vec![],
+            external_addrs: vec![],
+            enabled_channels: Default::default(),
+            timeouts: P2pTimeouts::default(),
+            limits: P2pLimits::default(),
+            peer_discovery: false,
+            meshsub: P2pMeshsubConfig::default(),
+            reputation: P2pReputationConfig::default(),
+            nat: P2pNatConfig::default(),
+        };
+        P2pState::new(config, P2pCallbacks::default(), &DEVNET_CHAIN_ID)
+    }
+
+    fn time(secs: u64) -> Timestamp {
+        Timestamp::ZERO + Duration::from_secs(secs)
+    }
+
+    fn test_peer() -> PeerId {
+        SecretKey::rand().public_key().peer_id()
+    }
+
+    /// Adds a peer subscribed to [`TOPIC`], with an outgoing stream if
+    /// `can_send` is set.
+    fn add_peer(state: &mut P2pNetworkPubsubState, on_mesh: bool, can_send: bool) -> PeerId {
+        let peer_id = test_peer();
+        state.clients.insert(
+            peer_id,
+            P2pNetworkPubsubClientState {
+                protocol: BroadcastAlgorithm::Meshsub1_1_0,
+                addr: ConnectionAddr {
+                    sock_addr: (Ipv4Addr::LOCALHOST, 8302).into(),
+                    incoming: false,
+                },
+                outgoing_stream_id: can_send.then_some(1),
+                message: Default::default(),
+                cache: Default::default(),
+                buffer: vec![],
+                incoming_messages: vec![],
+            },
+        );
+        let mesh = if on_mesh {
+            P2pNetworkPubsubClientMeshAddingState::Added
+        } else {
+            P2pNetworkPubsubClientMeshAddingState::Initial
+        };
+        state
+            .topics
+            .entry(TOPIC.to_owned())
+            .or_default()
+            .insert(peer_id, P2pNetworkPubsubClientTopicState { mesh });
+        peer_id
+    }
+
+    fn mesh_state(
+        state: &P2pNetworkPubsubState,
+        peer_id: &PeerId,
+    ) -> P2pNetworkPubsubClientMeshAddingState {
+        state.topics[TOPIC][peer_id].mesh
+    }
+
+    fn control(state: &P2pNetworkPubsubState, peer_id: &PeerId) -> pb::ControlMessage {
+        state.clients[peer_id]
+            .message
+            .control
+            .clone()
+            .unwrap_or_default()
+    }
+
+    fn libp2p_peer_id(peer_id: PeerId) -> Vec<u8> {
+        libp2p_identity::PeerId::try_from(peer_id)
+            .unwrap()
+            .to_bytes()
+    }
+
+    fn test_message(seqno: u64) -> pb::Message {
+        pb::Message {
+            from: Some(libp2p_peer_id(test_peer())),
+            data: Some(vec![]),
+            seqno: Some(seqno.to_be_bytes().to_vec()),
+            topic: TOPIC.to_owned(),
+            signature: Some(vec![seqno as u8; 64]),
+            key: None,
+        }
+    }
+
+    fn graft() -> pb::ControlMessage {
+        pb::ControlMessage {
+            graft: vec![pb::ControlGraft {
+                topic_id: Some(TOPIC.to_owned()),
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn prune(peers: &[PeerId], backoff: Option<u64>) -> pb::ControlMessage {
+        pb::ControlMessage {
+            prune: vec![pb::ControlPrune {
+                topic_id: Some(TOPIC.to_owned()),
+                peers: peers
+                    .iter()
+                    .map(|peer_id| pb::PeerInfo {
+                        peer_id: Some(libp2p_peer_id(*peer_id)),
+                        signed_peer_record: None,
+                    })
+                    .collect(),
+                backoff,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_graft_is_refused_during_backoff() {
+        let meshsub = P2pMeshsubConfig::default();
+        let mut state = P2pNetworkPubsubState::default();
+        let peer_id = add_peer(&mut state, false, true);
+
+        state.set_backoff(TOPIC, peer_id, time(10));
+        assert!(!state.can_graft(TOPIC, &peer_id, time(5)));
+        state.apply_control_commands(&peer_id, &graft(), time(5), &meshsub);
+        assert_eq!(
+            mesh_state(&state, &peer_id),
+            P2pNetworkPubsubClientMeshAddingState::WeRefused
+        );
+        // refusal is answered with a prune, which restarts the backoff
+        let prunes = control(&state, &peer_id).prune;
+        assert_eq!(prunes.len(), 1);
+        assert_eq!(prunes[0].backoff, Some(meshsub.prune_backoff.as_secs()));
+        let backoff_secs = 5 + meshsub.prune_backoff.as_secs();
+        assert!(state.is_backoff(TOPIC, &peer_id, time(backoff_secs - 1)));
+        let backoff_end = time(backoff_secs);
+        assert!(!state.is_backoff(TOPIC, &peer_id, backoff_end));
+
+        state.expire_backoff(backoff_end);
+        assert!(state.backoff.is_empty());
+        state.apply_control_commands(&peer_id, &graft(), backoff_end, &meshsub);
+        assert_eq!(
+            mesh_state(&state, &peer_id),
+            P2pNetworkPubsubClientMeshAddingState::Added
+        );
+    }
+
+    #[test]
+    fn test_prune_sets_backoff_and_collects_px() {
+        let meshsub = P2pMeshsubConfig::default();
+        let mut state = P2pNetworkPubsubState::default();
+        let peer_id = add_peer(&mut state, true, true);
+        let px_peers = [test_peer(), test_peer()];
+
+        state.apply_control_commands(&peer_id, &prune(&px_peers, Some(10)), time(0), &meshsub);
+        assert_eq!(
+            mesh_state(&state, &peer_id),
+            P2pNetworkPubsubClientMeshAddingState::TheyRefused
+        );
+        assert!(state.is_backoff(TOPIC, &peer_id, time(9)));
+        assert!(!state.is_backoff(TOPIC, &peer_id, time(10)));
+        assert_eq!(
+            state.px_peers,
+            px_peers.iter().map(|px_peer| (*px_peer, peer_id)).collect()
+        );
+
+        // backoff requested by the peer is capped
+        state.apply_control_commands(&peer_id, &prune(&[], Some(u64::MAX)), time(0), &meshsub);
+        let max_backoff_secs = MAX_PRUNE_BACKOFF.as_secs();
+        assert!(state.is_backoff(TOPIC, &peer_id, time(max_backoff_secs - 1)));
+        assert!(!state.is_backoff(TOPIC, &peer_id, time(max_backoff_secs)));
+
+        // peer exchange disabled
+        let meshsub = P2pMeshsubConfig {
+            peer_exchange: false,
+            ..Default::default()
+        };
+        let mut state = P2pNetworkPubsubState::default();
+        let peer_id = add_peer(&mut state, true, true);
+        state.apply_control_commands(&peer_id, &prune(&px_peers, None), time(0), &meshsub);
+        assert!(state.px_peers.is_empty());
+        let backoff_secs = meshsub.prune_backoff.as_secs();
+        assert!(state.is_backoff(TOPIC, &peer_id, time(backoff_secs - 1)));
+    }
+
+    #[test]
+    fn test_prune_mesh_peer_sends_px() {
+        let meshsub = P2pMeshsubConfig::default();
+        let mut state = P2pNetworkPubsubState::default();
+        let peer_id = add_peer(&mut state, true, true);
+        let others = [
+            add_peer(&mut state, true, true),
+            add_peer(&mut state, false, true),
+        ];
+
+        assert!(!state.prune_mesh_peer(&peer_id, "unknown", time(0), &meshsub));
+        assert!(state.prune_mesh_peer(&peer_id, TOPIC, time(0), &meshsub));
+        assert_eq!(
+            mesh_state(&state, &peer_id),
+            P2pNetworkPubsubClientMeshAddingState::WeRefused
+        );
+        assert!(!state.can_graft(TOPIC, &peer_id, time(1)));
+        let prunes = control(&state, &peer_id).prune;
+        assert_eq!(prunes.len(), 1);
+        let sent_peers = prunes[0]
+            .peers
+            .iter()
+            .map(|info| info.peer_id.clone().unwrap())
+            .collect::<BTreeSet<_>>();
+        assert_eq!(
+            sent_peers,
+            others
+                .iter()
+                .map(|peer_id| libp2p_peer_id(*peer_id))
+                .collect()
+        );
+
+        let meshsub = P2pMeshsubConfig {
+            peer_exchange: false,
+            ..Default::default()
+        };
+        let peer_id = others[0];
+        assert!(state.prune_mesh_peer(&peer_id, TOPIC, time(0), &meshsub));
+        assert!(control(&state, &peer_id).prune[0].peers.is_empty());
+    }
+
+    #[test]
+    fn test_px_is_ignored_from_low_score_peers() {
+        let meshsub = P2pMeshsubConfig::default();
+        let mut p2p_state = test_p2p_state();
+        let now = time(100);
+        let (good_sender, bad_sender) = (test_peer(), test_peer());
+        p2p_state.reputation.peers.insert(
+            bad_sender,
+            P2pPeerReputation {
+                score: meshsub.accept_px_threshold * 2.0,
+                updated_at: now,
+                last_penalty: None,
+            },
+        );
+
+        let mut dial_opts = |peer_id: PeerId| {
+            let opts = P2pConnectionOutgoingInitOpts::LibP2P(P2pConnectionOutgoingInitLibp2pOpts {
+                peer_id,
+                host: Host::Ipv4(Ipv4Addr::new(1, 2, 3, 4)),
+                port: 8302,
+                transport: P2pLibp2pTransport::Tcp,
+            });
+            p2p_state.peers.insert(
+                peer_id,
+                P2pPeerState {
+                    is_libp2p: true,
+                    dial_opts: Some(opts.clone()),
+                    status: P2pPeerStatus::Disconnected { time: now },
+                    identify: None,
+                },
+            );
+            opts
+        };
+        let (good_px, bad_px, unknown_px) = (test_peer(), test_peer(), test_peer());
+        let good_opts = dial_opts(good_px);
+        dial_opts(bad_px);
+
+        let px_peers = [
+            (good_px, good_sender),
+            (bad_px, bad_sender),
+            (unknown_px, good_sender),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            P2pNetworkPubsubState::px_dial_opts(&p2p_state, &meshsub, now, px_peers),
+            vec![good_opts]
+        );
+    }
+
+    #[test]
+    fn test_emit_gossip_rotates_peers() {
+        let meshsub = P2pMeshsubConfig::default();
+        let mut state = P2pNetworkPubsubState::default();
+        let mesh_peer = add_peer(&mut state, true, true);
+        let silent_peer = add_peer(&mut state, false, false);
+        let candidates = (0..8)
+            .map(|_| add_peer(&mut state, false, true))
+            .collect::<BTreeSet<_>>();
+        let message_id = state.mcache.put(test_message(1)).unwrap();
+
+        let gossiped = |state: &mut P2pNetworkPubsubState| {
+            state.emit_gossip(&meshsub);
+            let mut peers = BTreeSet::new();
+            for (peer_id, client) in &mut state.clients {
+                if let Some(control) = client.message.control.take() {
+                    assert_eq!(control.ihave.len(), 1);
+                    assert_eq!(control.ihave[0].message_ids, vec![message_id.clone()]);
+                    peers.insert(*peer_id);
+                }
+            }
+            peers
+        };
+
+        let first = gossiped(&mut state);
+        assert_eq!(first.len(), meshsub.gossip_lazy);
+        assert!(first.is_subset(&candidates));
+        assert!(!first.contains(&mesh_peer) && !first.contains(&silent_peer));
+
+        state.heartbeat_ticks += 1;
+        let second = gossiped(&mut state);
+        assert_eq!(second.len(), meshsub.gossip_lazy);
+        assert_ne!(first, second);
+
+        // message is only advertised for the recent heartbeats
+        for _ in 0..3 {
+            state.mcache.shift_window();
+        }
+        assert!(gossiped(&mut state).is_empty());
+    }
+
+    fn set_score(p2p_state: &mut P2pState, peer_id: PeerId, score: f64, now: Timestamp) {
+        p2p_state.reputation.peers.insert(
+            peer_id,
+            P2pPeerReputation {
+                score,
+                updated_at: now,
+                last_penalty: None,
+            },
+        );
+    }
+
+    fn grafted(actions: &[P2pNetworkPubsubAction]) -> BTreeSet<PeerId> {
+        actions
+            .iter()
+            .map(|action| match action {
+                P2pNetworkPubsubAction::Graft { peer_id, topic_id } if topic_id == TOPIC => {
+                    *peer_id
+                }
+                action => panic!("unexpected action: {action:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_maintain_mesh_degree() {
+        let meshsub = P2pMeshsubConfig::default();
+        let now = time(100);
+        let mut p2p_state = test_p2p_state();
+        let state = &mut p2p_state.network.scheduler.broadcast_state;
+        state.heartbeat_ticks = 1;
+        for _ in 0..meshsub.outbound_degree_low - 1 {
+            add_peer(state, true, true);
+        }
+        let backoff_peer = add_peer(state, false, true);
+        state.set_backoff(TOPIC, backoff_peer, now + Duration::from_secs(1));
+        add_peer(state, false, false);
+        for _ in 0..10 {
+            add_peer(state, false, true);
+        }
+
+        // mesh is grafted up to the desired degree, skipping peers we
+        // can't graft
+        let actions = P2pNetworkPubsubState::maintain_mesh(&p2p_state, &meshsub, now);
+        let grafted = grafted(&actions);
+        assert_eq!(
+            grafted.len(),
+            meshsub.outbound_degree_desired - (meshsub.outbound_degree_low - 1)
+        );
+        assert!(!grafted.contains(&backoff_peer));
+
+        // lowest scoring peers are pruned from an oversized mesh
+        let state = &mut p2p_state.network.scheduler.broadcast_state;
+        let mut mesh = (0..=meshsub.outbound_degree_high)
+            .map(|_| add_peer(state, true, true))
+            .collect::<Vec<_>>();
+        let excess = state.mesh_size(TOPIC) - meshsub.outbound_degree_desired;
+        let worst = mesh.split_off(mesh.len() - excess);
+        for peer_id in &worst {
+            set_score(&mut p2p_state, *peer_id, -5.0, now);
+        }
+        let actions = P2pNetworkPubsubState::maintain_mesh(&p2p_state, &meshsub, now);
+        let pruned = actions
+            .iter()
+            .map(|action| match action {
+                P2pNetworkPubsubAction::Prune { peer_id, .. } => *peer_id,
+                action => panic!("unexpected action: {action:?}"),
+            })
+            .collect::<BTreeSet<_>>();
+        assert_eq!(pruned, worst.into_iter().collect());
+    }
+
+    #[test]
+    fn test_opportunistic_grafting() {
+        let meshsub = P2pMeshsubConfig::default();
+        let now = time(100);
+        let mut p2p_state = test_p2p_state();
+        let state = &mut p2p_state.network.scheduler.broadcast_state;
+        state.heartbeat_ticks = meshsub.opportunistic_graft_ticks;
+        let mesh = (0..meshsub.outbound_degree_desired)
+            .map(|_| add_peer(state, true, true))
+            .collect::<Vec<_>>();
+        let good = (0..3)
+            .map(|_| add_peer(state, false, true))
+            .collect::<BTreeSet<_>>();
+        let worse = add_peer(state, false, true);
+        for peer_id in &mesh {
+            set_score(&mut p2p_state, *peer_id, -20.0, now);
+        }
+        set_score(&mut p2p_state, worse, -30.0, now);
+
+        let actions = P2pNetworkPubsubState::maintain_mesh(&p2p_state, &meshsub, now);
+        let grafted = grafted(&actions);
+        assert_eq!(grafted.len(), meshsub.opportunistic_graft_peers);
+        assert!(grafted.is_subset(&good));
+
+        // only every `opportunistic_graft_ticks` heartbeats
+        p2p_state.network.scheduler.broadcast_state.heartbeat_ticks += 1;
+        assert!(P2pNetworkPubsubState::maintain_mesh(&p2p_state, &meshsub, now).is_empty());
+
+        // and only if the median score is below the threshold
+        p2p_state.network.scheduler.broadcast_state.heartbeat_ticks =
+            meshsub.opportunistic_graft_ticks * 2;
+        for peer_id in &mesh {
+            set_score(&mut p2p_state, *peer_id, 0.0, now);
+        }
+        assert!(P2pNetworkPubsubState::maintain_mesh(&p2p_state, &meshsub, now).is_empty());
+    }
+
+    #[test]
+    fn test_flood_publish() {
+        let mut state = P2pNetworkPubsubState::default();
+        let mesh_peer = add_peer(&mut state, true, true);
+        let other_peer = add_peer(&mut state, false, true);
+        let unsubscribed_peer = add_peer(&mut state, false, true);
+        state
+            .topics
+            .get_mut(TOPIC)
+            .unwrap()
+            .remove(&unsubscribed_peer);
+
+        let published = |state: &mut P2pNetworkPubsubState| {
+            state
+                .clients
+                .iter_mut()
+                .filter(|(_, client)| !client.message.publish.is_empty())
+                .map(|(peer_id, client)| {
+                    client.message.publish.clear();
+                    *peer_id
+                })
+                .collect::<BTreeSet<_>>()
+        };
+
+        let message = test_message(1);
+        let meshsub = P2pMeshsubConfig::default();
+        assert!(meshsub.flood_publish);
+        assert!(state.publish_own_message(message.clone(), &meshsub));
+        assert_eq!(published(&mut state), [mesh_peer, other_peer].into());
+        assert_eq!(state.seen.back(), message.signature.as_ref());
+        assert!(state
+            .mcache
+            .map
+            .contains_key(&compute_message_id(&message).unwrap()));
+        assert_eq!(state.topic_stats[TOPIC].published, 1);
+
+        let meshsub = P2pMeshsubConfig {
+            flood_publish: false,
+            ..Default::default()
+        };
+        assert!(state.publish_own_message(test_message(2), &meshsub));
+        assert_eq!(published(&mut state), [mesh_peer].into());
+
+        let mut message = test_message(3);
+        message.topic = "unknown".to_owned();
+        assert!(!state.publish_own_message(message, &meshsub));
+        assert!(published(&mut state).is_empty());
+    }
+
+    #[test]
+    fn test_unsubscribed_topic_stats() {
+        let mut state = P2pNetworkPubsubState::default();
+        let peer_id = add_peer(&mut state, true, true);
+
+        state
+            .reduce_incoming_message(peer_id, test_message(1), 16)
+            .unwrap();
+        for (seqno, topic) in [(2, "unknown-1"), (3, "unknown-2")] {
+            let mut message = test_message(seqno);
+            message.topic = topic.to_owned();
+            state.reduce_incoming_message(peer_id, message, 16).unwrap();
+        }
+
+        assert_eq!(
+            state.topic_stats.keys().collect::<Vec<_>>(),
+            [TOPIC_STATS_OTHER, TOPIC]
+        );
+        assert_eq!(state.topic_stats[TOPIC].received, 1);
+        assert_eq!(state.topic_stats[TOPIC_STATS_OTHER].received, 2);
+    }
+}
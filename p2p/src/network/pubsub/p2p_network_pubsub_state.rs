@@ -14,6 +14,13 @@ use serde::{Deserialize, Serialize};
 
 pub const IWANT_TIMEOUT_DURATION: Duration = Duration::from_secs(5);
 
+/// Maximum number of peer exchange (PX) candidates kept until the next
+/// heartbeat.
+pub const MAX_PX_PEERS: usize = 16;
+
+/// Upper bound of the backoff requested by a peer which pruned us.
+pub const MAX_PRUNE_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
 /// State of the P2P Network PubSub system.
 ///
 /// This struct maintains information about connected peers, message sequencing,
@@ -58,6 +65,34 @@ pub struct P2pNetworkPubsubState {
 
     /// `iwant` requests, tracking the number of times peers have expressed interest in specific messages.
     pub iwant: VecDeque<P2pNetworkPubsubIwantRequestCount>,
+
+    /// Number of messages received and published, per subscribed topic,
+    /// with the rest under [`super::TOPIC_STATS_OTHER`].
+    pub topic_stats: BTreeMap<String, P2pNetworkPubsubTopicStats>,
+
+    /// Peers which can't be grafted to the topic mesh until the given time,
+    /// since either side pruned the other.
+    pub backoff: BTreeMap<String, BTreeMap<PeerId, Timestamp>>,
+
+    /// Peers received with peer exchange (PX), along with the peer which
+    /// sent them, to connect to on the next heartbeat.
+    pub px_peers: BTreeMap<PeerId, PeerId>,
+
+    /// Time of the last heartbeat.
+    pub last_heartbeat: Option<Timestamp>,
+
+    /// Number of heartbeats so far.
+    pub heartbeat_ticks: u64,
+}
+
+#[derive(Default, Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct P2pNetworkPubsubTopicStats {
+    /// Messages received from peers, including duplicates.
+    pub received: u64,
+    /// Received messages which were already seen.
+    pub duplicates: u64,
+    /// Messages published by this node.
+    pub published: u64,
 }
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
@@ -69,6 +104,56 @@ pub struct P2pNetworkPubsubIwantRequestCount {
 impl P2pNetworkPubsubState {
     pub fn prune_peer_state(&mut self, peer_id: &PeerId) {
         self.clients.remove(peer_id);
+        // Otherwise disconnected peers keep counting towards the mesh degree.
+        for peers in self.topics.values_mut() {
+            peers.remove(peer_id);
+        }
+    }
+
+    pub fn mesh_size(&self, topic_id: &str) -> usize {
+        self.topics
+            .get(topic_id)
+            .map_or(0, |peers| peers.values().filter(|s| s.on_mesh()).count())
+    }
+
+    pub fn is_backoff(&self, topic_id: &str, peer_id: &PeerId, now: Timestamp) -> bool {
+        self.backoff
+            .get(topic_id)
+            .and_then(|peers| peers.get(peer_id))
+            .map_or(false, |until| *until > now)
+    }
+
+    /// Whether we can add the peer to the topic mesh.
+    pub fn can_graft(&self, topic_id: &str, peer_id: &PeerId, now: Timestamp) -> bool {
+        self.clients
+            .get(peer_id)
+            .map_or(false, |client| client.outgoing_stream_id.is_some())
+            && !self.is_backoff(topic_id, peer_id, now)
+    }
+
+    pub fn set_backoff(&mut self, topic_id: &str, peer_id: PeerId, until: Timestamp) {
+        let Some(peers) = self.topics.get(topic_id) else {
+            return;
+        };
+        if !peers.contains_key(&peer_id) {
+            return;
+        }
+        let backoff = self
+            .backoff
+            .entry(topic_id.to_owned())
+            .or_default()
+            .entry(peer_id)
+            .or_insert(until);
+        if *backoff < until {
+            *backoff = until;
+        }
+    }
+
+    pub fn expire_backoff(&mut self, now: Timestamp) {
+        self.backoff.retain(|_, peers| {
+            peers.retain(|_, until| *until > now);
+            !peers.is_empty()
+        });
     }
 
     pub fn filter_iwant_message_ids(&mut self, message_id: &Vec<u8>, timestamp: Timestamp) -> bool {
@@ -209,10 +294,14 @@ pub struct P2pNetworkPubsubRecentlyPublishCache {
 pub struct P2pNetworkPubsubMessageCache {
     pub map: BTreeMap<Vec<u8>, pb::Message>,
     pub queue: VecDeque<Vec<u8>>,
+    /// Ids of messages cached during the recent heartbeats, newest last.
+    pub windows: VecDeque<Vec<Vec<u8>>>,
 }
 
 impl P2pNetworkPubsubMessageCache {
     const CAPACITY: usize = 100;
+    /// Number of heartbeat windows advertised in gossip.
+    const GOSSIP_WINDOWS: usize = 3;
 
     pub fn put(&mut self, message: pb::Message) -> Option<Vec<u8>> {
         let id = compute_message_id(&message)?;
@@ -223,8 +312,33 @@ impl P2pNetworkPubsubMessageCache {
                 self.map.remove(&id);
             }
         }
+        match self.windows.back_mut() {
+            Some(window) => window.push(id.clone()),
+            None => self.windows.push_back(vec![id.clone()]),
+        }
         Some(id)
     }
+
+    /// Ids of messages from the recent heartbeat windows, per topic.
+    pub fn gossip_ids(&self) -> BTreeMap<String, Vec<Vec<u8>>> {
+        let mut ids = BTreeMap::<_, Vec<_>>::new();
+        for id in self.windows.iter().flatten() {
+            if let Some(message) = self.map.get(id) {
+                ids.entry(message.topic.clone())
+                    .or_default()
+                    .push(id.clone());
+            }
+        }
+        ids
+    }
+
+    /// Starts the next heartbeat window.
+    pub fn shift_window(&mut self) {
+        self.windows.push_back(vec![]);
+        while self.windows.len() > Self::GOSSIP_WINDOWS {
+            self.windows.pop_front();
+        }
+    }
 }
 
 // TODO: what if wasm32?
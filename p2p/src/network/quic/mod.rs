@@ -0,0 +1,8 @@
+mod p2p_network_quic_actions;
+pub use self::p2p_network_quic_actions::*;
+
+mod p2p_network_quic_state;
+pub use self::p2p_network_quic_state::*;
+
+#[cfg(feature = "p2p-libp2p")]
+mod p2p_network_quic_reducer;
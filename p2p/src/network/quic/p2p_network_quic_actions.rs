@@ -0,0 +1,135 @@
+use std::net::SocketAddr;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{token, ConnectionAddr, Data, P2pState, PeerId, StreamId};
+
+/// Lifecycle of QUIC connections and their streams.
+///
+/// Stream data goes to and comes from [`crate::P2pNetworkSelectAction`]
+/// the same way it does for yamux streams.
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(
+    display(listener),
+    display(addr),
+    display(peer_id),
+    stream_id,
+    debug(data),
+    fin,
+    debug(result),
+    display(error)
+))]
+pub enum P2pNetworkQuicAction {
+    ListenerReady {
+        listener: SocketAddr,
+    },
+    ListenerError {
+        listener: SocketAddr,
+        error: String,
+    },
+    /// Initialize outgoing QUIC connection to the peer.
+    OutgoingConnect {
+        addr: SocketAddr,
+        peer_id: PeerId,
+    },
+    /// QUIC handshake is finished, either for incoming or for outgoing
+    /// connection. Contains the peer id verified by the TLS certificate.
+    DidConnect {
+        addr: ConnectionAddr,
+        result: Result<PeerId, String>,
+    },
+    /// The connection is accepted and streams can be opened.
+    Init {
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+    },
+    #[action_event(level = trace)]
+    IncomingData {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Data,
+        fin: bool,
+    },
+    #[action_event(level = trace)]
+    OutgoingData {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Data,
+        fin: bool,
+    },
+    OpenStream {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        stream_kind: token::StreamKind,
+    },
+    /// Reset the stream on our side.
+    ResetStream {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
+    /// The remote peer reset the stream.
+    StreamDidReset {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
+}
+
+impl From<P2pNetworkQuicAction> for crate::P2pAction {
+    fn from(a: P2pNetworkQuicAction) -> Self {
+        Self::Network(a.into())
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkQuicAction {
+    fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
+        let scheduler = &state.network.scheduler;
+        match self {
+            P2pNetworkQuicAction::ListenerReady { .. }
+            | P2pNetworkQuicAction::ListenerError { .. } => true,
+            P2pNetworkQuicAction::OutgoingConnect { addr, .. } => {
+                cfg!(feature = "p2p-quic")
+                    && scheduler
+                        .connection_state(&ConnectionAddr {
+                            sock_addr: *addr,
+                            incoming: false,
+                        })
+                        .map_or(true, |cn| cn.closed.is_some())
+            }
+            P2pNetworkQuicAction::DidConnect { addr, .. } => {
+                match scheduler.connection_state(addr) {
+                    None => addr.incoming,
+                    Some(cn) => !addr.incoming && cn.closed.is_none() && cn.quic_state().is_some(),
+                }
+            }
+            P2pNetworkQuicAction::Init { addr, .. } => scheduler
+                .connection_state(addr)
+                .filter(|cn| cn.closed.is_none())
+                .and_then(|cn| cn.quic_state())
+                .map_or(false, |quic| !quic.init),
+            P2pNetworkQuicAction::IncomingData { addr, .. }
+            | P2pNetworkQuicAction::OpenStream { addr, .. }
+            | P2pNetworkQuicAction::StreamDidReset { addr, .. } => scheduler
+                .connection_state(addr)
+                .filter(|cn| cn.closed.is_none())
+                .and_then(|cn| cn.quic_state())
+                .map_or(false, |quic| quic.init),
+            P2pNetworkQuicAction::ResetStream { addr, stream_id } => scheduler
+                .connection_state(addr)
+                .filter(|cn| cn.closed.is_none())
+                .and_then(|cn| cn.quic_state())
+                .map_or(false, |quic| quic.streams.contains_key(stream_id)),
+            P2pNetworkQuicAction::OutgoingData {
+                addr, stream_id, ..
+            } => scheduler
+                .connection_state(addr)
+                .filter(|cn| cn.closed.is_none())
+                .and_then(|cn| cn.quic_state())
+                .map_or(false, |quic| {
+                    quic.streams
+                        .get(stream_id)
+                        .map_or(false, |stream| !stream.fin_sent)
+                }),
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use openmina_core::{bug_condition, Substate};
+
+use crate::{
+    connection::{
+        incoming::{P2pConnectionIncomingAction, P2pConnectionIncomingState},
+        outgoing::P2pConnectionOutgoingAction,
+        RejectionReason,
+    },
+    disconnection::P2pDisconnectionReason,
+    identify::P2pIdentifyAction,
+    Limit, P2pState,
+};
+
+use super::{super::*, *};
+
+impl P2pNetworkQuicState {
+    /// Substate is accessed
+    pub fn reducer<State, Action>(
+        mut state_context: Substate<Action, State, P2pNetworkSchedulerState>,
+        action: redux::ActionWithMeta<P2pNetworkQuicAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let scheduler_state = state_context.get_substate_mut()?;
+
+        match action {
+            P2pNetworkQuicAction::ListenerReady { listener } => {
+                scheduler_state.quic_listeners.insert(listener);
+                Ok(())
+            }
+            P2pNetworkQuicAction::ListenerError { listener, .. } => {
+                scheduler_state.quic_listeners.remove(&listener);
+                Ok(())
+            }
+            P2pNetworkQuicAction::OutgoingConnect { addr, peer_id } => {
+                let pnet_key = scheduler_state.pnet_key;
+                scheduler_state.connections.insert(
+                    ConnectionAddr {
+                        sock_addr: addr,
+                        incoming: false,
+                    },
+                    P2pNetworkConnectionState::new_quic(false, pnet_key, meta.time()),
+                );
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkQuicEffectfulAction::Connect {
+                    addr,
+                    peer_id,
+                    alpn: quic_alpn(&pnet_key),
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::DidConnect { addr, result } => {
+                let peer_id = match result {
+                    Ok(peer_id) => peer_id,
+                    Err(error) => {
+                        if !addr.incoming {
+                            let dispatcher = state_context.into_dispatcher();
+                            dispatcher.push(P2pNetworkSchedulerAction::Error {
+                                addr,
+                                error: P2pNetworkConnectionError::MioError(error),
+                            });
+                        }
+                        return Ok(());
+                    }
+                };
+
+                if addr.incoming {
+                    let pnet_key = scheduler_state.pnet_key;
+                    scheduler_state.connections.insert(
+                        addr,
+                        P2pNetworkConnectionState::new_quic(true, pnet_key, meta.time()),
+                    );
+                }
+                let Some(connection_state) = scheduler_state.connection_state_mut(&addr) else {
+                    bug_condition!(
+                        "Missing connection state for `P2pNetworkQuicAction::DidConnect`"
+                    );
+                    return Ok(());
+                };
+                connection_state.auth = Some(P2pNetworkAuthState::Tls(peer_id));
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+
+                if addr.incoming {
+                    let reject = if let Err(reason) =
+                        p2p_state.libp2p_incoming_ip_accept(&addr.sock_addr.ip())
+                    {
+                        Some(reason)
+                    } else if p2p_state.network.scheduler.connections.len()
+                        > p2p_state.config.limits.max_connections()
+                    {
+                        Some(RejectionReason::PeerCapacityFull)
+                    } else {
+                        None
+                    };
+                    if let Some(reason) = reject {
+                        dispatcher.push(P2pNetworkSchedulerAction::Disconnect {
+                            addr,
+                            reason: P2pDisconnectionReason::Libp2pIncomingRejected(reason),
+                        });
+                        return Ok(());
+                    }
+
+                    dispatcher.push(P2pConnectionIncomingAction::FinalizePendingLibp2p {
+                        peer_id,
+                        addr: addr.sock_addr,
+                    });
+                }
+                dispatcher.push(P2pNetworkQuicAction::Init { addr, peer_id });
+                Ok(())
+            }
+            P2pNetworkQuicAction::Init { addr, peer_id } => {
+                let Some(quic_state) = scheduler_state
+                    .connection_state_mut(&addr)
+                    .and_then(|cn| cn.quic_state_mut())
+                else {
+                    bug_condition!("Missing QUIC state for `P2pNetworkQuicAction::Init`");
+                    return Ok(());
+                };
+                quic_state.init = true;
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+
+                if addr.incoming {
+                    // the connection might be rejected as a duplicate
+                    let this_connection_is_kept = p2p_state
+                        .peers
+                        .get(&peer_id)
+                        .and_then(|peer_state| peer_state.status.as_connecting())
+                        .and_then(|connecting| connecting.as_incoming())
+                        .map_or(false, |incoming| matches!(incoming, P2pConnectionIncomingState::FinalizePendingLibp2p { addr: a, .. } if a == &addr.sock_addr));
+                    if !this_connection_is_kept {
+                        return Ok(());
+                    }
+                    dispatcher.push(P2pConnectionIncomingAction::Libp2pReceived { peer_id });
+                } else {
+                    dispatcher.push(P2pConnectionOutgoingAction::FinalizeSuccess {
+                        peer_id,
+                        remote_auth: None,
+                    });
+                }
+
+                dispatcher.push(P2pIdentifyAction::NewRequest { peer_id, addr });
+                Ok(())
+            }
+            P2pNetworkQuicAction::IncomingData {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => {
+                let Some(connection_state) = scheduler_state.connection_state_mut(&addr) else {
+                    bug_condition!(
+                        "Missing connection state for `P2pNetworkQuicAction::IncomingData`"
+                    );
+                    return Ok(());
+                };
+                let Some(peer_id) = connection_state.peer_id().copied() else {
+                    bug_condition!("Missing peer_id for `P2pNetworkQuicAction::IncomingData`");
+                    return Ok(());
+                };
+                let Some(quic_state) = connection_state.quic_state_mut() else {
+                    bug_condition!("Missing QUIC state for `P2pNetworkQuicAction::IncomingData`");
+                    return Ok(());
+                };
+
+                let new_stream = !quic_state.streams.contains_key(&stream_id);
+                let incoming_streams = quic_state.incoming_streams();
+                let stream = quic_state
+                    .streams
+                    .entry(stream_id)
+                    .or_insert_with(P2pNetworkQuicStreamState::incoming);
+                stream.fin_received |= fin;
+                if stream.is_closed() {
+                    quic_state.streams.remove(&stream_id);
+                }
+                if new_stream {
+                    connection_state
+                        .streams
+                        .insert(stream_id, P2pNetworkStreamState::new_incoming(meta.time()));
+                }
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                if new_stream {
+                    let limits: &crate::P2pLimits = state.substate()?;
+                    if let Limit::Some(limit) = limits.max_streams() {
+                        if incoming_streams >= limit {
+                            dispatcher.push(P2pNetworkQuicAction::ResetStream { addr, stream_id });
+                            dispatcher.push(P2pNetworkSchedulerAction::PruneStream {
+                                peer_id,
+                                stream_id,
+                            });
+                            return Ok(());
+                        }
+                    }
+                    dispatcher.push(P2pNetworkSelectAction::Init {
+                        addr,
+                        kind: SelectKind::Stream(peer_id, stream_id),
+                        incoming: true,
+                    });
+                }
+                dispatcher.push(P2pNetworkSelectAction::IncomingData {
+                    addr,
+                    peer_id,
+                    stream_id,
+                    data,
+                    fin,
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::OutgoingData {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => {
+                let Some(quic_state) = scheduler_state
+                    .connection_state_mut(&addr)
+                    .and_then(|cn| cn.quic_state_mut())
+                else {
+                    bug_condition!("Missing QUIC state for `P2pNetworkQuicAction::OutgoingData`");
+                    return Ok(());
+                };
+                let Some(stream) = quic_state.streams.get_mut(&stream_id) else {
+                    bug_condition!("Missing QUIC stream for `P2pNetworkQuicAction::OutgoingData`");
+                    return Ok(());
+                };
+                stream.fin_sent |= fin;
+                if stream.is_closed() {
+                    quic_state.streams.remove(&stream_id);
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkQuicEffectfulAction::OutgoingData {
+                    addr,
+                    stream_id,
+                    data,
+                    fin,
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::OpenStream {
+                addr,
+                stream_id,
+                stream_kind,
+            } => {
+                let Some(connection_state) = scheduler_state.connection_state_mut(&addr) else {
+                    bug_condition!(
+                        "Missing connection state for `P2pNetworkQuicAction::OpenStream`"
+                    );
+                    return Ok(());
+                };
+                let Some(peer_id) = connection_state.peer_id().copied() else {
+                    bug_condition!("Missing peer_id for `P2pNetworkQuicAction::OpenStream`");
+                    return Ok(());
+                };
+                if let Some(quic_state) = connection_state.quic_state_mut() {
+                    quic_state
+                        .streams
+                        .insert(stream_id, P2pNetworkQuicStreamState::default());
+                }
+                connection_state.streams.insert(
+                    stream_id,
+                    P2pNetworkStreamState::new(stream_kind, meta.time()),
+                );
+
+                // the QUIC stream itself is opened by the service with the first data
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkSelectAction::Init {
+                    addr,
+                    kind: SelectKind::Stream(peer_id, stream_id),
+                    incoming: false,
+                });
+                Ok(())
+            }
+            P2pNetworkQuicAction::ResetStream { addr, stream_id } => {
+                if let Some(quic_state) = scheduler_state
+                    .connection_state_mut(&addr)
+                    .and_then(|cn| cn.quic_state_mut())
+                {
+                    quic_state.streams.remove(&stream_id);
+                }
+
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkQuicEffectfulAction::ResetStream { addr, stream_id });
+                Ok(())
+            }
+            P2pNetworkQuicAction::StreamDidReset { addr, stream_id } => {
+                if let Some(quic_state) = scheduler_state
+                    .connection_state_mut(&addr)
+                    .and_then(|cn| cn.quic_state_mut())
+                {
+                    quic_state.streams.remove(&stream_id);
+                }
+
+                // same as yamux, the upper layers can't recover from the reset stream
+                let dispatcher = state_context.into_dispatcher();
+                dispatcher.push(P2pNetworkSchedulerAction::Error {
+                    addr,
+                    error: P2pNetworkConnectionError::StreamReset(stream_id),
+                });
+                Ok(())
+            }
+        }
+    }
+}
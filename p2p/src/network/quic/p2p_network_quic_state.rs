@@ -0,0 +1,66 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::super::*;
+
+/// Streams opened by the remote peer get ids starting from this one, so
+/// they never clash with the ids we choose for our own streams.
+pub const QUIC_REMOTE_STREAM_ID_BASE: StreamId = 0x8000_0000;
+
+/// State of a QUIC connection.
+///
+/// The QUIC transport authenticates the peer with libp2p TLS and provides
+/// flow controlled streams itself, so there is no pnet, noise or yamux on
+/// top of it. Streams are identified by the same logical ids that yamux
+/// uses, the service maps them to the native QUIC stream ids.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pNetworkQuicState {
+    /// The handshake is done and the connection is accepted by the node.
+    pub init: bool,
+    pub streams: BTreeMap<StreamId, P2pNetworkQuicStreamState>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct P2pNetworkQuicStreamState {
+    pub incoming: bool,
+    pub fin_sent: bool,
+    pub fin_received: bool,
+}
+
+impl P2pNetworkQuicState {
+    /// Returns the stream id for the outgoing stream of the kind.
+    pub fn next_stream_id(&self, kind: YamuxStreamKind, incoming: bool) -> Option<StreamId> {
+        self.init.then(|| kind.stream_id(incoming))
+    }
+
+    pub fn incoming_streams(&self) -> usize {
+        self.streams.values().filter(|s| s.incoming).count()
+    }
+}
+
+impl P2pNetworkQuicStreamState {
+    pub fn incoming() -> Self {
+        P2pNetworkQuicStreamState {
+            incoming: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.fin_sent && self.fin_received
+    }
+}
+
+/// ALPN protocol used for QUIC connections.
+///
+/// QUIC packets can't be wrapped into pnet, so the pnet key is hashed into
+/// the ALPN instead. Nodes of different networks fail the TLS handshake.
+pub fn quic_alpn(pnet_key: &[u8; 32]) -> Vec<u8> {
+    let hash = Sha256::new()
+        .chain_update(b"/openmina/quic/")
+        .chain_update(pnet_key)
+        .finalize();
+    format!("/openmina/quic/{}", hex::encode(&hash[..8])).into_bytes()
+}
@@ -0,0 +1,5 @@
+mod p2p_network_quic_effectful_actions;
+pub use self::p2p_network_quic_effectful_actions::*;
+
+#[cfg(feature = "p2p-libp2p")]
+mod p2p_network_quic_effectful_effects;
@@ -0,0 +1,50 @@
+use std::net::SocketAddr;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use crate::{ConnectionAddr, Data, P2pState, PeerId, StreamId};
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(fields(
+    display(listener),
+    display(addr),
+    display(peer_id),
+    stream_id,
+    debug(data),
+    fin
+))]
+pub enum P2pNetworkQuicEffectfulAction {
+    Listen {
+        listener: SocketAddr,
+        alpn: Vec<u8>,
+    },
+    Connect {
+        addr: SocketAddr,
+        peer_id: PeerId,
+        alpn: Vec<u8>,
+    },
+    #[action_event(level = trace)]
+    OutgoingData {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Data,
+        fin: bool,
+    },
+    ResetStream {
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+    },
+}
+
+impl From<P2pNetworkQuicEffectfulAction> for crate::P2pEffectfulAction {
+    fn from(a: P2pNetworkQuicEffectfulAction) -> crate::P2pEffectfulAction {
+        crate::P2pEffectfulAction::Network(crate::P2pNetworkEffectfulAction::Quic(a))
+    }
+}
+
+impl redux::EnablingCondition<P2pState> for P2pNetworkQuicEffectfulAction {
+    fn is_enabled(&self, _state: &P2pState, _time: redux::Timestamp) -> bool {
+        true
+    }
+}
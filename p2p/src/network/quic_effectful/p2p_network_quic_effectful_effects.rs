@@ -0,0 +1,34 @@
+use redux::ActionMeta;
+
+use crate::{MioCmd, P2pMioService};
+
+use super::P2pNetworkQuicEffectfulAction;
+
+impl P2pNetworkQuicEffectfulAction {
+    pub fn effects<Store, S>(self, _meta: &ActionMeta, store: &mut Store)
+    where
+        Store: crate::P2pStore<S>,
+        Store::Service: P2pMioService,
+    {
+        let cmd = match self {
+            P2pNetworkQuicEffectfulAction::Listen { listener, alpn } => {
+                MioCmd::QuicListenOn(listener, alpn.into())
+            }
+            P2pNetworkQuicEffectfulAction::Connect {
+                addr,
+                peer_id,
+                alpn,
+            } => MioCmd::QuicConnect(addr, peer_id, alpn.into()),
+            P2pNetworkQuicEffectfulAction::OutgoingData {
+                addr,
+                stream_id,
+                data,
+                fin,
+            } => MioCmd::QuicSend(addr, stream_id, data.0, fin),
+            P2pNetworkQuicEffectfulAction::ResetStream { addr, stream_id } => {
+                MioCmd::QuicReset(addr, stream_id)
+            }
+        };
+        store.service().send_mio_cmd(cmd);
+    }
+}
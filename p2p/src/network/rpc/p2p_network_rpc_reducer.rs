@@ -353,6 +353,14 @@ impl P2pNetworkRpcState {
                     limits.rpc_get_some_initial_peers(),
                     GetSomeInitialPeersV1ForV2::NAME,
                 ),
+                GetAncestryV2::NAME => (limits.rpc_get_ancestry(), GetAncestryV2::NAME),
+                GetTransitionChainProofV1ForV2::NAME => (
+                    limits.rpc_get_transition_chain_proof(),
+                    GetTransitionChainProofV1ForV2::NAME,
+                ),
+                GetEpochLedgerV2::NAME => (limits.rpc_get_epoch_ledger(), GetEpochLedgerV2::NAME),
+                // unit response, fits into the query limit
+                BanNotifyV1::NAME => (limits.rpc_query(), BanNotifyV1::NAME),
                 _ => (Limit::Some(0), b"<unimplemented>"),
             }
         } else {
@@ -434,6 +442,40 @@ where
                 request: Box::new(P2pRpcRequest::InitialPeers),
             });
         }
+        (rpc::GetAncestryV2::NAME, rpc::GetAncestryV2::VERSION) => {
+            let query = rpc::GetAncestryV2::query_payload(&mut bytes)?;
+            let hash = v2::StateHash::from(v2::DataHashLibStateHashStableV1(query.hash));
+            let request = Box::new(P2pRpcRequest::Ancestry(hash, Box::new(query.data)));
+
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request,
+            });
+        }
+        (
+            rpc::GetTransitionChainProofV1ForV2::NAME,
+            rpc::GetTransitionChainProofV1ForV2::VERSION,
+        ) => {
+            let hash = rpc::GetTransitionChainProofV1ForV2::query_payload(&mut bytes)?;
+            let hash = v2::StateHash::from(v2::DataHashLibStateHashStableV1(hash));
+
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::TransitionChainProof(hash)),
+            });
+        }
+        (rpc::GetEpochLedgerV2::NAME, rpc::GetEpochLedgerV2::VERSION) => {
+            let hash = rpc::GetEpochLedgerV2::query_payload(&mut bytes)?;
+            let hash = v2::LedgerHash::from(v2::MinaBaseLedgerHash0StableV1(hash));
+
+            dispatcher.push(P2pChannelsRpcAction::RequestReceived {
+                peer_id,
+                id,
+                request: Box::new(P2pRpcRequest::EpochLedger(hash)),
+            });
+        }
         (name, version) => return Err(RpcQueryError::Unimplemented(name, version)),
     }
     Ok(())
@@ -549,6 +591,69 @@ where
                 });
             }
         }
+        (rpc::GetAncestryV2::NAME, rpc::GetAncestryV2::VERSION) => {
+            let response = rpc::GetAncestryV2::response_payload(&mut bytes)?
+                .map(|resp| BestTipWithProof {
+                    best_tip: resp.data.into(),
+                    proof: (
+                        resp.proof
+                            .0
+                            .into_iter()
+                            .map(v2::MinaBaseStateBodyHashStableV1)
+                            .collect(),
+                        resp.proof.1.into(),
+                    ),
+                })
+                .map(P2pRpcResponse::Ancestry)
+                .map(Box::new);
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (
+            rpc::GetTransitionChainProofV1ForV2::NAME,
+            rpc::GetTransitionChainProofV1ForV2::VERSION,
+        ) => {
+            let response = rpc::GetTransitionChainProofV1ForV2::response_payload(&mut bytes)?
+                .map(|(first_hash, body_hashes)| TransitionChainProof {
+                    first_hash: v2::DataHashLibStateHashStableV1(first_hash).into(),
+                    body_hashes: body_hashes
+                        .into_iter()
+                        .map(v2::MinaBaseStateBodyHashStableV1)
+                        .collect(),
+                })
+                .map(P2pRpcResponse::TransitionChainProof)
+                .map(Box::new);
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::GetEpochLedgerV2::NAME, rpc::GetEpochLedgerV2::VERSION) => {
+            let response = Result::from(rpc::GetEpochLedgerV2::response_payload(&mut bytes)?)
+                .ok()
+                .map(|ledger| Box::new(P2pRpcResponse::EpochLedger(Arc::new(ledger))));
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response,
+            });
+        }
+        (rpc::BanNotifyV1::NAME, rpc::BanNotifyV1::VERSION) => {
+            rpc::BanNotifyV1::response_payload(&mut bytes)?;
+
+            dispatcher.push(P2pChannelsRpcAction::ResponseReceived {
+                peer_id,
+                id,
+                response: None,
+            });
+        }
         _ => {}
     }
     Ok(())
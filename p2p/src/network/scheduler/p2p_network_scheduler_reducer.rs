@@ -14,7 +14,7 @@ use crate::{
         incoming::P2pConnectionIncomingAction, outgoing::P2pConnectionOutgoingAction,
         P2pConnectionState,
     },
-    disconnection::P2pDisconnectionAction,
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
     identify::P2pIdentifyAction,
     P2pConfig, P2pPeerStatus, P2pState, PeerId,
 };
@@ -36,6 +36,7 @@ impl P2pNetworkSchedulerState {
         match action {
             P2pNetworkSchedulerAction::InterfaceDetected { ip, .. } => {
                 scheduler_state.interfaces.insert(ip);
+                let pnet_key = scheduler_state.pnet_key;
 
                 let (dispatcher, state) = state_context.into_dispatcher_and_state();
                 let p2p_config: &P2pConfig = state.substate()?;
@@ -44,6 +45,12 @@ impl P2pNetworkSchedulerState {
                     dispatcher
                         .push(P2pNetworkSchedulerEffectfulAction::InterfaceDetected { ip, port });
                 }
+                if let Some(port) = p2p_config.quic_port {
+                    dispatcher.push(P2pNetworkQuicEffectfulAction::Listen {
+                        listener: std::net::SocketAddr::new(ip, port),
+                        alpn: quic_alpn(&pnet_key),
+                    });
+                }
 
                 Ok(())
             }
@@ -97,8 +104,16 @@ impl P2pNetworkSchedulerState {
                     );
                 };
 
-                let dispatcher = state_context.into_dispatcher();
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
                 if let Some(addr) = addr {
+                    if let Err(reason) = p2p_state.libp2p_incoming_ip_accept(&addr.sock_addr.ip()) {
+                        dispatcher.push(P2pNetworkSchedulerAction::Disconnect {
+                            addr,
+                            reason: P2pDisconnectionReason::Libp2pIncomingRejected(reason),
+                        });
+                        return Ok(());
+                    }
                     dispatcher.push(P2pNetworkSchedulerEffectfulAction::IncomingDidAccept {
                         addr,
                         result,
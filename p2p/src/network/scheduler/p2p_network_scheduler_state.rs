@@ -6,7 +6,10 @@ use std::{
 use redux::Timestamp;
 use serde::{Deserialize, Serialize};
 
-use crate::{disconnection::P2pDisconnectionReason, identity::PublicKey, PeerId};
+use crate::{
+    connection::outgoing::P2pLibp2pTransport, disconnection::P2pDisconnectionReason,
+    identity::PublicKey, PeerId,
+};
 
 use super::super::*;
 
@@ -28,6 +31,8 @@ impl std::fmt::Display for ConnectionAddr {
 pub struct P2pNetworkSchedulerState {
     pub interfaces: BTreeSet<IpAddr>,
     pub listeners: BTreeSet<SocketAddr>,
+    /// UDP sockets accepting QUIC connections.
+    pub quic_listeners: BTreeSet<SocketAddr>,
     pub local_pk: PublicKey,
     #[serde_as(as = "serde_with::hex::Hex")]
     pub pnet_key: [u8; 32],
@@ -35,6 +40,7 @@ pub struct P2pNetworkSchedulerState {
     pub broadcast_state: P2pNetworkPubsubState,
     pub identify_state: identify::P2pNetworkIdentifyState,
     pub discovery_state: Option<P2pNetworkKadState>,
+    pub nat: P2pNetworkNatState,
     pub rpc_incoming_streams: StreamState<P2pNetworkRpcState>,
     pub rpc_outgoing_streams: StreamState<P2pNetworkRpcState>,
 }
@@ -94,6 +100,22 @@ pub struct P2pNetworkConnectionState {
 impl P2pNetworkConnectionState {
     pub const INITIAL_LIMIT: usize = 1024;
 
+    /// Creates the state for a QUIC connection, which needs neither
+    /// security nor multiplexer negotiation.
+    pub fn new_quic(incoming: bool, pnet_key: [u8; 32], time: Timestamp) -> Self {
+        P2pNetworkConnectionState {
+            incoming,
+            pnet: P2pNetworkPnetState::new(pnet_key, time),
+            select_auth: P2pNetworkSelectState::default(),
+            auth: None,
+            select_mux: P2pNetworkSelectState::default(),
+            mux: Some(P2pNetworkConnectionMuxState::Quic(Default::default())),
+            streams: BTreeMap::default(),
+            closed: None,
+            limit: 0,
+        }
+    }
+
     pub fn peer_id(&self) -> Option<&PeerId> {
         self.auth.as_ref().and_then(P2pNetworkAuthState::peer_id)
     }
@@ -114,28 +136,53 @@ impl P2pNetworkConnectionState {
         }
     }
 
+    pub fn transport(&self) -> P2pLibp2pTransport {
+        match &self.mux {
+            Some(P2pNetworkConnectionMuxState::Quic(_)) => P2pLibp2pTransport::Quic,
+            _ => P2pLibp2pTransport::Tcp,
+        }
+    }
+
     pub fn noise_state(&self) -> Option<&P2pNetworkNoiseState> {
-        self.auth
-            .as_ref()
-            .map(|P2pNetworkAuthState::Noise(state)| state)
+        match self.auth.as_ref()? {
+            P2pNetworkAuthState::Noise(state) => Some(state),
+            P2pNetworkAuthState::Tls(_) => None,
+        }
     }
 
     pub fn noise_state_mut(&mut self) -> Option<&mut P2pNetworkNoiseState> {
-        self.auth
-            .as_mut()
-            .map(|P2pNetworkAuthState::Noise(state)| state)
+        match self.auth.as_mut()? {
+            P2pNetworkAuthState::Noise(state) => Some(state),
+            P2pNetworkAuthState::Tls(_) => None,
+        }
     }
 
     pub fn yamux_state_mut(&mut self) -> Option<&mut P2pNetworkYamuxState> {
-        self.mux
-            .as_mut()
-            .map(|P2pNetworkConnectionMuxState::Yamux(state)| state)
+        match self.mux.as_mut()? {
+            P2pNetworkConnectionMuxState::Yamux(state) => Some(state),
+            P2pNetworkConnectionMuxState::Quic(_) => None,
+        }
     }
 
     pub fn yamux_state(&self) -> Option<&P2pNetworkYamuxState> {
-        self.mux
-            .as_ref()
-            .map(|P2pNetworkConnectionMuxState::Yamux(state)| state)
+        match self.mux.as_ref()? {
+            P2pNetworkConnectionMuxState::Yamux(state) => Some(state),
+            P2pNetworkConnectionMuxState::Quic(_) => None,
+        }
+    }
+
+    pub fn quic_state_mut(&mut self) -> Option<&mut P2pNetworkQuicState> {
+        match self.mux.as_mut()? {
+            P2pNetworkConnectionMuxState::Quic(state) => Some(state),
+            P2pNetworkConnectionMuxState::Yamux(_) => None,
+        }
+    }
+
+    pub fn quic_state(&self) -> Option<&P2pNetworkQuicState> {
+        match self.mux.as_ref()? {
+            P2pNetworkConnectionMuxState::Quic(state) => Some(state),
+            P2pNetworkConnectionMuxState::Yamux(_) => None,
+        }
     }
 
     pub fn select_state_mut(&mut self, kind: &SelectKind) -> Option<&mut P2pNetworkSelectState> {
@@ -205,12 +252,16 @@ pub enum P2pNetworkConnectionError {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum P2pNetworkAuthState {
     Noise(P2pNetworkNoiseState),
+    /// The peer is authenticated by the libp2p TLS certificate of a QUIC
+    /// connection.
+    Tls(PeerId),
 }
 
 impl P2pNetworkAuthState {
     fn peer_id(&self) -> Option<&PeerId> {
         match self {
             P2pNetworkAuthState::Noise(v) => v.peer_id(),
+            P2pNetworkAuthState::Tls(peer_id) => Some(peer_id),
         }
     }
 }
@@ -218,18 +269,31 @@ impl P2pNetworkAuthState {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum P2pNetworkConnectionMuxState {
     Yamux(P2pNetworkYamuxState),
+    Quic(P2pNetworkQuicState),
 }
 
 impl P2pNetworkConnectionMuxState {
     pub fn consume(&mut self, len: usize) {
         match self {
             Self::Yamux(state) => state.consume(len),
+            // flow control is done by the QUIC transport
+            Self::Quic(_) => {}
         }
     }
 
     fn limit(&self) -> usize {
         match self {
             Self::Yamux(state) => state.limit(),
+            Self::Quic(_) => 0,
+        }
+    }
+
+    /// Returns the stream id for the outgoing stream of the kind,
+    /// if the multiplexer is ready.
+    pub fn next_stream_id(&self, kind: YamuxStreamKind, incoming: bool) -> Option<StreamId> {
+        match self {
+            Self::Yamux(state) => state.next_stream_id(kind, incoming),
+            Self::Quic(state) => state.next_stream_id(kind, incoming),
         }
     }
 }
@@ -59,12 +59,20 @@ impl From<P2pNetworkYamuxAction> for crate::P2pAction {
 
 impl redux::EnablingCondition<P2pState> for P2pNetworkYamuxAction {
     fn is_enabled(&self, state: &P2pState, _time: redux::Timestamp) -> bool {
-        let Some(yamux_state) = state
-            .network
-            .scheduler
-            .connection_state(self.addr())
-            .and_then(|state| state.yamux_state())
-        else {
+        let Some(connection_state) = state.network.scheduler.connection_state(self.addr()) else {
+            return false;
+        };
+        if let Some(quic_state) = connection_state.quic_state() {
+            // forwarded to the QUIC streams
+            return match self {
+                P2pNetworkYamuxAction::OutgoingData { stream_id, .. } => {
+                    quic_state.streams.contains_key(stream_id)
+                }
+                P2pNetworkYamuxAction::OpenStream { .. } => quic_state.init,
+                _ => false,
+            };
+        }
+        let Some(yamux_state) = connection_state.yamux_state() else {
             return false;
         };
 
@@ -36,10 +36,15 @@ impl P2pNetworkYamuxState {
             .ok_or_else(|| format!("Connection not found for action: {action:?}"))
             .inspect_err(|e| bug_condition!("{}", e))?;
 
-        let P2pNetworkConnectionMuxState::Yamux(yamux_state) = connection_state
-            .mux
-            .as_mut()
-            .ok_or_else(|| format!("Invalid yamux state for action: {action:?}"))?;
+        let yamux_state = match connection_state.mux.as_mut() {
+            Some(P2pNetworkConnectionMuxState::Yamux(yamux_state)) => yamux_state,
+            Some(P2pNetworkConnectionMuxState::Quic(_)) => {
+                let dispatcher = state_context.into_dispatcher();
+                Self::forward_to_quic(dispatcher, action);
+                return Ok(());
+            }
+            None => return Err(format!("Invalid yamux state for action: {action:?}")),
+        };
 
         if yamux_state.terminated.is_some() {
             return Ok(());
@@ -259,11 +264,7 @@ impl P2pNetworkYamuxState {
                     .and_then(|yamux_state| yamux_state.streams.get(&frame.stream_id))
                     .ok_or_else(|| format!("Stream with id {} not found for `P2pNetworkYamuxAction::IncomingFrame`", frame.stream_id))?;
 
-                let peer_id = match connection_state
-                    .auth
-                    .as_ref()
-                    .and_then(|P2pNetworkAuthState::Noise(noise)| noise.peer_id())
-                {
+                let peer_id = match connection_state.peer_id() {
                     Some(peer_id) => *peer_id,
                     None => return Ok(()),
                 };
@@ -448,11 +449,7 @@ impl P2pNetworkYamuxState {
                     P2pNetworkStreamState::new(stream_kind, meta.time()),
                 );
 
-                let peer_id = match connection_state
-                    .auth
-                    .as_ref()
-                    .and_then(|P2pNetworkAuthState::Noise(noise)| noise.peer_id())
-                {
+                let peer_id = match connection_state.peer_id() {
                     Some(peer_id) => *peer_id,
                     None => return Ok(()),
                 };
@@ -469,6 +466,52 @@ impl P2pNetworkYamuxState {
     }
 }
 
+impl P2pNetworkYamuxState {
+    /// Upper layers use yamux actions to open streams and to send data,
+    /// on QUIC connections those are served by the native QUIC streams.
+    fn forward_to_quic<Action, State>(
+        dispatcher: &mut redux::Dispatcher<Action, State>,
+        action: P2pNetworkYamuxAction,
+    ) where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        match action {
+            P2pNetworkYamuxAction::OutgoingData {
+                addr,
+                stream_id,
+                data,
+                flags,
+            } => {
+                if flags.contains(YamuxFlags::RST) {
+                    dispatcher.push(P2pNetworkQuicAction::ResetStream { addr, stream_id });
+                } else {
+                    dispatcher.push(P2pNetworkQuicAction::OutgoingData {
+                        addr,
+                        stream_id,
+                        data,
+                        fin: flags.contains(YamuxFlags::FIN),
+                    });
+                }
+            }
+            P2pNetworkYamuxAction::OpenStream {
+                addr,
+                stream_id,
+                stream_kind,
+            } => {
+                dispatcher.push(P2pNetworkQuicAction::OpenStream {
+                    addr,
+                    stream_id,
+                    stream_kind,
+                });
+            }
+            action => {
+                bug_condition!("unexpected yamux action on QUIC connection: {action:?}");
+            }
+        }
+    }
+}
+
 impl YamuxStreamState {
     pub fn update_window(&mut self, ours: bool, difference: i32) {
         let window = if ours {
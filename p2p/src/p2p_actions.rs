@@ -13,6 +13,7 @@ use super::disconnection::P2pDisconnectionAction;
 use super::identify::P2pIdentifyAction;
 use super::network::P2pNetworkAction;
 use super::peer::P2pPeerAction;
+use super::reputation::P2pReputationAction;
 use super::P2pState;
 
 #[derive(Serialize, Deserialize, Debug, Clone, derive_more::From, ActionEvent)]
@@ -20,6 +21,7 @@ pub enum P2pAction {
     Initialization(P2pInitializeAction),
     Connection(P2pConnectionAction),
     Disconnection(P2pDisconnectionAction),
+    Reputation(P2pReputationAction),
     Identify(P2pIdentifyAction),
     Channels(P2pChannelsAction),
     Peer(P2pPeerAction),
@@ -56,6 +58,7 @@ impl redux::EnablingCondition<crate::P2pState> for P2pAction {
             P2pAction::Initialization(a) => a.is_enabled(state, time),
             P2pAction::Connection(a) => a.is_enabled(state, time),
             P2pAction::Disconnection(a) => a.is_enabled(state, time),
+            P2pAction::Reputation(a) => a.is_enabled(state, time),
             P2pAction::Channels(a) => a.is_enabled(state, time),
             P2pAction::Peer(a) => a.is_enabled(state, time),
             P2pAction::Identify(a) => a.is_enabled(state, time),
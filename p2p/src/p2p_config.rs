@@ -1,4 +1,8 @@
-use std::{collections::BTreeSet, net::IpAddr, time::Duration};
+use std::{
+    collections::BTreeSet,
+    net::{IpAddr, SocketAddr},
+    time::Duration,
+};
 
 use serde::{Deserialize, Serialize};
 
@@ -16,6 +20,9 @@ pub const DEVNET_SEEDS: &[&str] = &[
 pub struct P2pConfig {
     /// TCP port where libp2p is listening incoming connections.
     pub libp2p_port: Option<u16>,
+    /// UDP port where libp2p is listening incoming QUIC connections.
+    /// Only used when built with the `p2p-quic` feature.
+    pub quic_port: Option<u16>,
     /// The HTTP port where signaling server is listening SDP offers and SDP answers.
     pub listen_port: Option<u16>,
     /// The public key used for authentication all p2p communication.
@@ -35,9 +42,13 @@ pub struct P2pConfig {
     pub peer_discovery: bool,
 
     pub meshsub: P2pMeshsubConfig,
+
+    pub reputation: P2pReputationConfig,
+
+    pub nat: P2pNatConfig,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct P2pMeshsubConfig {
     /// Unix time. Used as an initial nonce for pubsub.
     pub initial_time: Duration,
@@ -46,6 +57,75 @@ pub struct P2pMeshsubConfig {
     pub outbound_degree_low: usize,
     pub outbound_degree_high: usize,
     pub mcache_len: usize,
+
+    /// Interval at which the mesh is maintained and gossip is emitted.
+    pub heartbeat_interval: Duration,
+    /// For how long a pruned peer can't be grafted again.
+    pub prune_backoff: Duration,
+    /// Send peer exchange (PX) with prunes and connect to peers received
+    /// with prunes.
+    pub peer_exchange: bool,
+    /// Maximum number of peers sent with a prune.
+    pub prune_peers: usize,
+    /// Peers received with a prune are ignored unless the score of the
+    /// peer which sent them is at or above this threshold.
+    pub accept_px_threshold: f64,
+    /// Minimum number of peers outside of the mesh to send gossip to.
+    pub gossip_lazy: usize,
+    /// Fraction of peers outside of the mesh to send gossip to, if it
+    /// is more than `gossip_lazy`.
+    pub gossip_factor: f64,
+    /// Opportunistic grafting is attempted every this many heartbeats.
+    /// Zero disables it.
+    pub opportunistic_graft_ticks: u64,
+    /// Number of peers grafted by a single opportunistic grafting.
+    pub opportunistic_graft_peers: usize,
+    /// Opportunistic grafting happens when the median score of mesh
+    /// peers is below this threshold.
+    pub opportunistic_graft_threshold: f64,
+    /// Publish own messages to all subscribed peers, not only to the mesh.
+    pub flood_publish: bool,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum P2pMeshsubConfigError {
+    #[error(
+        "outbound degrees must satisfy low <= desired <= high, got {low} <= {desired} <= {high}"
+    )]
+    InvalidOutboundDegree {
+        low: usize,
+        desired: usize,
+        high: usize,
+    },
+}
+
+impl P2pMeshsubConfig {
+    /// Sets the mesh degrees, checking that they are consistent.
+    pub fn with_outbound_degree(
+        mut self,
+        low: usize,
+        desired: usize,
+        high: usize,
+    ) -> Result<Self, P2pMeshsubConfigError> {
+        self.outbound_degree_low = low;
+        self.outbound_degree_desired = desired;
+        self.outbound_degree_high = high;
+        self.validate()?;
+        Ok(self)
+    }
+
+    pub fn validate(&self) -> Result<(), P2pMeshsubConfigError> {
+        let (low, desired, high) = (
+            self.outbound_degree_low,
+            self.outbound_degree_desired,
+            self.outbound_degree_high,
+        );
+        if low <= desired && desired <= high {
+            Ok(())
+        } else {
+            Err(P2pMeshsubConfigError::InvalidOutboundDegree { low, desired, high })
+        }
+    }
 }
 
 impl Default for P2pMeshsubConfig {
@@ -56,6 +136,63 @@ impl Default for P2pMeshsubConfig {
             outbound_degree_low: 4,
             outbound_degree_high: 12,
             mcache_len: 256,
+            heartbeat_interval: Duration::from_secs(1),
+            prune_backoff: Duration::from_secs(60),
+            peer_exchange: true,
+            prune_peers: 16,
+            accept_px_threshold: -10.0,
+            gossip_lazy: 6,
+            gossip_factor: 0.25,
+            opportunistic_graft_ticks: 60,
+            opportunistic_graft_peers: 2,
+            opportunistic_graft_threshold: -1.0,
+            flood_publish: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pReputationConfig {
+    /// Peer is banned once its score drops to or below this value.
+    pub ban_threshold: f64,
+    /// For how long banned peer and its addresses are refused.
+    pub ban_duration: Duration,
+    /// Time it takes for a penalty to decay by half.
+    pub score_half_life: Duration,
+}
+
+impl Default for P2pReputationConfig {
+    fn default() -> Self {
+        Self {
+            ban_threshold: -100.0,
+            ban_duration: Duration::from_secs(24 * 60 * 60),
+            score_half_life: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pNatConfig {
+    /// Address observed by this many distinct peers is considered to be
+    /// our external address and is published in identify and Kademlia.
+    pub observed_addr_confirmations: usize,
+    /// Request the gateway to forward `libp2p_port` to us, using NAT-PMP
+    /// and falling back to UPnP.
+    pub port_mapping: bool,
+    /// NAT-PMP gateway. If not set, the default gateway is used.
+    pub gateway: Option<SocketAddr>,
+    /// Lifetime of the port mapping requested from the gateway. The mapping
+    /// is refreshed once half of the lifetime granted by the gateway passes.
+    pub mapping_lifetime: Duration,
+}
+
+impl Default for P2pNatConfig {
+    fn default() -> Self {
+        Self {
+            observed_addr_confirmations: 3,
+            port_mapping: false,
+            gateway: None,
+            mapping_lifetime: Duration::from_secs(60 * 60),
         }
     }
 }
@@ -74,6 +211,10 @@ pub struct P2pTimeouts {
     pub snark: Option<Duration>,
     pub transaction: Option<Duration>,
     pub initial_peers: Option<Duration>,
+    pub ancestry: Option<Duration>,
+    pub transition_chain_proof: Option<Duration>,
+    pub epoch_ledger: Option<Duration>,
+    pub ban_notify: Option<Duration>,
     pub kademlia_bootstrap: Option<Duration>,
     pub kademlia_initial_bootstrap: Option<Duration>,
     pub select: Option<Duration>,
@@ -124,6 +265,13 @@ impl Default for P2pTimeouts {
             snark: from_env_or("SNARK_TIMEOUT", Some(Duration::from_secs(8))),
             transaction: from_env_or("TRANSACTION_TIMEOUT", Some(Duration::from_secs(8))),
             initial_peers: from_env_or("INITIAL_PEERS_TIMEOUT", Some(Duration::from_secs(5))),
+            ancestry: from_env_or("ANCESTRY_TIMEOUT", Some(Duration::from_secs(15))),
+            transition_chain_proof: from_env_or(
+                "TRANSITION_CHAIN_PROOF_TIMEOUT",
+                Some(Duration::from_secs(8)),
+            ),
+            epoch_ledger: from_env_or("EPOCH_LEDGER_TIMEOUT", Some(Duration::from_secs(180))),
+            ban_notify: from_env_or("BAN_NOTIFY_TIMEOUT", Some(Duration::from_secs(5))),
             kademlia_bootstrap: from_env_or(
                 "KADEMLIA_BOOTSTRAP_TIMEOUT",
                 Some(Duration::from_secs(60)),
@@ -146,6 +294,9 @@ impl P2pTimeouts {
             staged_ledger_aux_and_pending_coinbases_at_block: None,
             block: None,
             snark: None,
+            ancestry: None,
+            transition_chain_proof: None,
+            epoch_ledger: None,
             ..Default::default()
         }
     }
@@ -265,6 +416,9 @@ pub struct P2pLimits {
     rpc_get_staged_ledger: Limit<usize>,
     rpc_get_transition_chain: Limit<usize>,
     rpc_get_some_initial_peers: Limit<usize>,
+    rpc_get_ancestry: Limit<usize>,
+    rpc_get_transition_chain_proof: Limit<usize>,
+    rpc_get_epoch_ledger: Limit<usize>,
 }
 
 macro_rules! limit {
@@ -383,6 +537,18 @@ impl P2pLimits {
         #[doc = "RPC some_initial_peers"]
         rpc_get_some_initial_peers
     );
+    limit!(
+        #[doc = "RPC get_ancestry"]
+        rpc_get_ancestry
+    );
+    limit!(
+        #[doc = "RPC get_transition_chain_proof"]
+        rpc_get_transition_chain_proof
+    );
+    limit!(
+        #[doc = "RPC get_epoch_ledger"]
+        rpc_get_epoch_ledger
+    );
 }
 
 impl Default for P2pLimits {
@@ -405,6 +571,9 @@ impl Default for P2pLimits {
         let rpc_get_staged_ledger = Limit::Some(400_000_000); // 59286608 as observed, may go higher
         let rpc_get_transition_chain = Limit::Some(3_500_000); // 2979112 as observed
         let rpc_get_some_initial_peers = Limit::Some(32_000); // TODO: calculate
+        let rpc_get_ancestry = rpc_get_best_tip; // same payload as get_best_tip
+        let rpc_get_transition_chain_proof = Limit::Some(100_000); // up to k body hashes
+        let rpc_get_epoch_ledger = rpc_get_staged_ledger; // sparse ledger of the whole epoch ledger
 
         Self {
             max_peers,
@@ -425,6 +594,9 @@ impl Default for P2pLimits {
             rpc_get_staged_ledger,
             rpc_get_transition_chain,
             rpc_get_some_initial_peers,
+            rpc_get_ancestry,
+            rpc_get_transition_chain_proof,
+            rpc_get_epoch_ledger,
         }
     }
 }
@@ -432,7 +604,7 @@ impl Default for P2pLimits {
 #[cfg(test)]
 mod tests {
 
-    use super::Limit;
+    use super::{Limit, P2pMeshsubConfig, P2pMeshsubConfigError};
 
     #[test]
     fn test_limits() {
@@ -445,4 +617,21 @@ mod tests {
         assert!(0 < unlimited);
         assert!(usize::MAX < unlimited);
     }
+
+    #[test]
+    fn test_meshsub_outbound_degree() {
+        let config = P2pMeshsubConfig::default();
+        assert_eq!(config.validate(), Ok(()));
+        assert!(config.with_outbound_degree(2, 4, 8).is_ok());
+        assert!(config.with_outbound_degree(4, 4, 4).is_ok());
+        assert_eq!(
+            config.with_outbound_degree(6, 4, 8).unwrap_err(),
+            P2pMeshsubConfigError::InvalidOutboundDegree {
+                low: 6,
+                desired: 4,
+                high: 8
+            }
+        );
+        assert!(config.with_outbound_degree(2, 10, 8).is_err());
+    }
 }
@@ -8,12 +8,12 @@ use crate::channels::signaling::discovery::SignalingDiscoveryChannelMsg;
 use crate::channels::signaling::exchange::SignalingExchangeChannelMsg;
 use crate::channels::streaming_rpc::StreamingRpcChannelMsg;
 use crate::webrtc::ConnectionAuthEncrypted;
-use crate::ConnectionAddr;
 use crate::{
     channels::{transaction::TransactionPropagationChannelMsg, ChannelId, ChannelMsg, MsgId},
     connection::P2pConnectionResponse,
     PeerId,
 };
+use crate::{ConnectionAddr, P2pNetworkNatMapping, StreamId};
 
 #[derive(Serialize, Deserialize, From, Debug, Clone)]
 pub enum P2pEvent {
@@ -54,6 +54,22 @@ pub enum MioEvent {
 
     /// The remote peer is disconnected by our node.
     ConnectionDidCloseOnDemand(ConnectionAddr),
+
+    /// Started listening for QUIC connections on a local UDP port.
+    QuicListenerReady { listener: SocketAddr },
+    /// Error listening for QUIC connections on a local UDP port.
+    QuicListenerError { listener: SocketAddr, error: String },
+    /// QUIC handshake with the remote peer is finished, contains the peer id
+    /// from the peer's certificate.
+    QuicConnectionDidConnect(ConnectionAddr, Result<PeerId, String>),
+    /// We received the data in the QUIC stream, the flag is set if the remote
+    /// peer finished the stream.
+    QuicStreamDidReceive(ConnectionAddr, StreamId, crate::Data, bool),
+    /// The remote peer reset the QUIC stream.
+    QuicStreamDidReset(ConnectionAddr, StreamId),
+
+    /// The NAT gateway replied to the port mapping request.
+    NatPortMapped(Result<P2pNetworkNatMapping, String>),
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -299,6 +315,31 @@ impl fmt::Display for MioEvent {
             Self::ConnectionDidCloseOnDemand(addr) => {
                 write!(f, "ConnectionDidCloseOnDemand, {addr}")
             }
+            Self::QuicListenerReady { listener } => write!(f, "QuicListenerReady, {listener}"),
+            Self::QuicListenerError { listener, error } => {
+                write!(f, "QuicListenerError, {listener}, {error}")
+            }
+            Self::QuicConnectionDidConnect(addr, res) => {
+                write!(f, "QuicConnectionDidConnect, {addr}, {}", res_kind(res))
+            }
+            Self::QuicStreamDidReceive(addr, stream_id, data, fin) => {
+                write!(
+                    f,
+                    "QuicStreamDidReceive, {addr}, {stream_id}, {}, {fin}",
+                    data.len()
+                )
+            }
+            Self::QuicStreamDidReset(addr, stream_id) => {
+                write!(f, "QuicStreamDidReset, {addr}, {stream_id}")
+            }
+            Self::NatPortMapped(res) => match res {
+                Ok(mapping) => write!(
+                    f,
+                    "NatPortMapped, {}, {}:{}",
+                    mapping.protocol, mapping.external_ip, mapping.external_port
+                ),
+                Err(error) => write!(f, "NatPortMapped, Err, {error}"),
+            },
         }
     }
 }
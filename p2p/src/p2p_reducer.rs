@@ -8,6 +8,7 @@ use crate::{
         P2pConnectionState,
     },
     disconnection::{P2pDisconnectedState, P2pDisconnectionAction},
+    reputation::{P2pReputationAction, P2pReputationState},
     P2pAction, P2pNetworkKadKey, P2pNetworkKademliaAction, P2pNetworkPnetAction,
     P2pNetworkRpcAction, P2pNetworkSelectAction, P2pNetworkState, P2pPeerState, P2pState, PeerId,
 };
@@ -40,6 +41,9 @@ impl P2pState {
             P2pAction::Disconnection(action) => {
                 P2pDisconnectedState::reducer(state_context, meta.with_action(action))
             }
+            P2pAction::Reputation(action) => {
+                P2pReputationState::reducer(state_context, meta.with_action(action))
+            }
             P2pAction::Peer(action) => P2pPeerState::reducer(
                 Substate::from_compatible_substate(state_context),
                 meta.with_action(action),
@@ -56,10 +60,12 @@ impl P2pState {
                 #[cfg(feature = "p2p-libp2p")]
                 {
                     let limits = state.config.limits;
+                    let meshsub = state.config.meshsub;
                     P2pNetworkState::reducer(
                         Substate::from_compatible_substate(state_context),
                         meta.with_action(_action),
                         &limits,
+                        &meshsub,
                     )?;
                 }
                 Ok(())
@@ -82,6 +88,7 @@ impl P2pState {
         state.p2p_connection_timeouts_dispatch(dispatcher, time)?;
         dispatcher.push(P2pConnectionOutgoingAction::RandomInit);
         dispatcher.push(P2pDisconnectionAction::RandomTry);
+        dispatcher.push(P2pReputationAction::Prune);
 
         state.p2p_try_reconnect_disconnected_peers(dispatcher, time)?;
         state.p2p_discovery(dispatcher, time)?;
@@ -91,6 +98,8 @@ impl P2pState {
             state.p2p_pnet_timeouts(dispatcher, time)?;
             state.p2p_select_timeouts(dispatcher, time)?;
             state.p2p_rpc_heartbeats(dispatcher, time)?;
+            dispatcher.push(crate::P2pNetworkPubsubAction::Heartbeat);
+            dispatcher.push(crate::P2pNetworkNatAction::MappingRequest);
         }
 
         state.rpc_timeouts(dispatcher, time)?;
@@ -25,6 +25,7 @@ use crate::{
         incoming::P2pConnectionIncomingState,
         outgoing::{
             P2pConnectionOutgoingError, P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingState,
+            P2pLibp2pTransport,
         },
         P2pConnectionResponse, P2pConnectionState,
     },
@@ -33,6 +34,7 @@ use crate::{
         identify::{P2pNetworkIdentify, P2pNetworkIdentifyState},
         P2pNetworkState,
     },
+    reputation::P2pReputationState,
     Limit, P2pConfig, P2pLimits, P2pNetworkKadState, P2pNetworkPubsubState,
     P2pNetworkSchedulerState, P2pTimeouts, PeerId,
 };
@@ -44,6 +46,7 @@ pub struct P2pState {
     pub config: P2pConfig,
     pub network: P2pNetworkState,
     pub peers: BTreeMap<PeerId, P2pPeerState>,
+    pub reputation: P2pReputationState,
 
     pub last_random_disconnection_try: redux::Timestamp,
 
@@ -124,6 +127,7 @@ impl P2pState {
             config,
             network,
             peers,
+            reputation: P2pReputationState::default(),
 
             last_random_disconnection_try: redux::Timestamp::ZERO,
 
@@ -135,6 +139,55 @@ impl P2pState {
         self.config.identity_pub_key.peer_id()
     }
 
+    /// Addresses at which the node is reachable from the outside: the ones
+    /// from the config, the observed addresses confirmed by peers and the
+    /// port mapped on the NAT gateway.
+    ///
+    /// Only the TCP port is mapped on the gateway, so QUIC is advertised
+    /// just for the addresses from the config.
+    pub fn external_addrs(&self) -> Vec<multiaddr::Multiaddr> {
+        let config = &self.config;
+        let nat = &self.network.scheduler.nat;
+        let port = config.libp2p_port.unwrap_or(8302);
+
+        let mut ips = config.external_addrs.clone();
+        for ip in &nat.confirmed {
+            if !ips.contains(ip) {
+                ips.push(*ip);
+            }
+        }
+
+        let mut addrs = ips
+            .iter()
+            .map(|ip| (*ip, P2pLibp2pTransport::Tcp, port))
+            .chain(config.quic_port.into_iter().flat_map(|quic_port| {
+                config
+                    .external_addrs
+                    .iter()
+                    .map(move |ip| (*ip, P2pLibp2pTransport::Quic, quic_port))
+            }))
+            .collect::<Vec<_>>();
+        if let Some(mapping) = nat.mapping() {
+            let mapped = (
+                mapping.external_ip,
+                P2pLibp2pTransport::Tcp,
+                mapping.external_port,
+            );
+            if !addrs.contains(&mapped) {
+                addrs.push(mapped);
+            }
+        }
+
+        addrs
+            .into_iter()
+            .map(|(ip, transport, port)| {
+                std::iter::once(ip.into())
+                    .chain(transport.protocols(port))
+                    .collect()
+            })
+            .collect()
+    }
+
     pub fn peer_connection_rpc_id(&self, peer_id: &PeerId) -> Option<RpcId> {
         self.peers.get(peer_id)?.connection_rpc_id()
     }
@@ -157,8 +210,12 @@ impl P2pState {
             .any(|(_, p)| p.status.as_ready().is_some())
     }
 
+    /// Disconnected peers which we can dial, i.e. which aren't banned.
     pub fn disconnected_peers(&self) -> impl '_ + Iterator<Item = P2pConnectionOutgoingInitOpts> {
-        self.peers.iter().filter_map(|(_, state)| {
+        self.peers.iter().filter_map(|(peer_id, state)| {
+            if self.reputation.is_peer_banned(peer_id) {
+                return None;
+            }
             if let P2pPeerState {
                 status: P2pPeerStatus::Disconnected { .. },
                 dial_opts: Some(opts),
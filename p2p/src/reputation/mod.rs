@@ -0,0 +1,7 @@
+mod p2p_reputation_state;
+pub use p2p_reputation_state::*;
+
+mod p2p_reputation_actions;
+pub use p2p_reputation_actions::*;
+
+mod p2p_reputation_reducer;
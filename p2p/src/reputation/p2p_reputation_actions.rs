@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use openmina_core::ActionEvent;
+use serde::{Deserialize, Serialize};
+
+use super::P2pPenaltyReason;
+use crate::{P2pState, PeerId};
+
+pub type P2pReputationActionWithMetaRef<'a> = redux::ActionWithMeta<&'a P2pReputationAction>;
+
+const PRUNE_FREQUENCY: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize, Debug, Clone, ActionEvent)]
+#[action_event(level = debug)]
+pub enum P2pReputationAction {
+    /// Lower peer's score, banning it once the score drops below the
+    /// configured threshold.
+    #[action_event(fields(display(peer_id), display(reason)), level = info)]
+    Penalize {
+        peer_id: PeerId,
+        reason: P2pPenaltyReason,
+    },
+    /// Ban peer and its addresses, then disconnect from it.
+    #[action_event(fields(display(peer_id), display(reason)), level = warn)]
+    Ban {
+        peer_id: PeerId,
+        reason: P2pPenaltyReason,
+    },
+    /// Remove expired bans and scores which decayed back to neutral.
+    Prune,
+}
+
+impl redux::EnablingCondition<P2pState> for P2pReputationAction {
+    fn is_enabled(&self, state: &P2pState, time: redux::Timestamp) -> bool {
+        match self {
+            P2pReputationAction::Penalize { peer_id, .. }
+            | P2pReputationAction::Ban { peer_id, .. } => {
+                *peer_id != state.my_id() && !state.reputation.is_peer_banned(peer_id)
+            }
+            P2pReputationAction::Prune => time
+                .checked_sub(state.reputation.last_prune)
+                .map_or(false, |dur| dur >= PRUNE_FREQUENCY),
+        }
+    }
+}
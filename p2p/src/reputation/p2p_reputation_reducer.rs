@@ -0,0 +1,143 @@
+use openmina_core::Substate;
+use redux::ActionWithMeta;
+
+use crate::{
+    disconnection::{P2pDisconnectionAction, P2pDisconnectionReason},
+    P2pState,
+};
+
+use super::{P2pBan, P2pPeerReputation, P2pReputationAction, P2pReputationState};
+
+/// Scores above this are considered neutral and forgotten on prune.
+const NEUTRAL_SCORE: f64 = -1.0;
+
+impl P2pReputationState {
+    pub fn reducer<Action, State>(
+        mut state_context: Substate<Action, State, P2pState>,
+        action: ActionWithMeta<P2pReputationAction>,
+    ) -> Result<(), String>
+    where
+        State: crate::P2pStateTrait,
+        Action: crate::P2pActionTrait<State>,
+    {
+        let (action, meta) = action.split();
+        let p2p_state = state_context.get_substate_mut()?;
+        let config = &p2p_state.config.reputation;
+        let now = meta.time();
+
+        match action {
+            P2pReputationAction::Penalize { peer_id, reason } => {
+                let peer = p2p_state
+                    .reputation
+                    .peers
+                    .entry(peer_id)
+                    .or_insert(P2pPeerReputation {
+                        score: 0.0,
+                        updated_at: now,
+                        last_penalty: None,
+                    });
+                peer.score = peer.score_at(now, config.score_half_life) - reason.penalty();
+                peer.updated_at = now;
+                peer.last_penalty = Some(reason);
+
+                if reason.is_fatal() || peer.score <= config.ban_threshold {
+                    let dispatcher = state_context.into_dispatcher();
+                    dispatcher.push(P2pReputationAction::Ban { peer_id, reason });
+                }
+                Ok(())
+            }
+            P2pReputationAction::Ban { peer_id, reason } => {
+                let until = now + config.ban_duration;
+                p2p_state.reputation.banned_peers.insert(
+                    peer_id,
+                    P2pBan {
+                        since: now,
+                        until,
+                        reason,
+                    },
+                );
+
+                #[cfg(feature = "p2p-libp2p")]
+                {
+                    // Local addresses are shared by all peers in test setups,
+                    // banning them would lock out everyone.
+                    let ips = p2p_state
+                        .network
+                        .scheduler
+                        .connections
+                        .iter()
+                        .filter(|(_, conn_state)| conn_state.peer_id() == Some(&peer_id))
+                        .map(|(addr, _)| addr.sock_addr.ip())
+                        .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+                        .collect::<Vec<_>>();
+                    for ip in ips {
+                        p2p_state.reputation.banned_ips.insert(ip, until);
+                    }
+                }
+
+                let (dispatcher, state) = state_context.into_dispatcher_and_state();
+                let p2p_state: &P2pState = state.substate()?;
+
+                // Peer gets disconnected once it answers the notification
+                // or the request times out.
+                #[cfg(feature = "p2p-libp2p")]
+                if let Some(action) = ban_notify_request(p2p_state, peer_id, until, now) {
+                    dispatcher.push(action);
+                    return Ok(());
+                }
+
+                dispatcher.push(P2pDisconnectionAction::Init {
+                    peer_id,
+                    reason: P2pDisconnectionReason::Banned(reason),
+                });
+                Ok(())
+            }
+            P2pReputationAction::Prune => {
+                let half_life = config.score_half_life;
+                let reputation = &mut p2p_state.reputation;
+                reputation.last_prune = now;
+                reputation.banned_peers.retain(|_, ban| ban.until > now);
+                reputation.banned_ips.retain(|_, until| *until > now);
+                reputation
+                    .peers
+                    .retain(|_, peer| peer.score_at(now, half_life) < NEUTRAL_SCORE);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// `ban_notify` request, letting OCaml peers know until when they are
+/// banned. `None` if it can't be sent right now, e.g. another request to
+/// the peer is in progress.
+#[cfg(feature = "p2p-libp2p")]
+fn ban_notify_request(
+    p2p_state: &P2pState,
+    peer_id: crate::PeerId,
+    until: redux::Timestamp,
+    now: redux::Timestamp,
+) -> Option<crate::channels::rpc::P2pChannelsRpcAction> {
+    use crate::channels::rpc::{P2pChannelsRpcAction, P2pRpcRequest};
+    use mina_p2p_messages::core;
+    use redux::EnablingCondition;
+
+    let peer = p2p_state.peers.get(&peer_id)?;
+    let has_outgoing_stream = p2p_state
+        .network
+        .scheduler
+        .rpc_outgoing_streams
+        .get(&peer_id)
+        .map_or(false, |streams| !streams.is_empty());
+    if !peer.is_libp2p() || !has_outgoing_stream {
+        return None;
+    }
+
+    let until = core::Time::from_secs_f64(u64::from(until) as f64 / 1_000_000_000.0);
+    let action = P2pChannelsRpcAction::RequestSend {
+        peer_id,
+        id: peer.status.as_ready()?.channels.next_local_rpc_id(),
+        request: Box::new(P2pRpcRequest::BanNotify(until)),
+        on_init: None,
+    };
+    action.is_enabled(p2p_state, now).then_some(action)
+}
@@ -0,0 +1,132 @@
+use std::{collections::BTreeMap, net::IpAddr, time::Duration};
+
+use redux::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::{P2pReputationConfig, PeerId};
+
+/// Per-peer scores and active bans.
+///
+/// Kept separately from [`crate::P2pState::peers`], since peer state is
+/// removed once the peer is disconnected, but its score and ban must
+/// outlive the connection.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pReputationState {
+    pub peers: BTreeMap<PeerId, P2pPeerReputation>,
+    pub banned_peers: BTreeMap<PeerId, P2pBan>,
+    pub banned_ips: BTreeMap<IpAddr, Timestamp>,
+    pub last_prune: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pPeerReputation {
+    /// Score at `updated_at`. Zero is neutral, penalties make it negative.
+    pub score: f64,
+    pub updated_at: Timestamp,
+    pub last_penalty: Option<P2pPenaltyReason>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct P2pBan {
+    pub since: Timestamp,
+    pub until: Timestamp,
+    pub reason: P2pPenaltyReason,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum P2pPenaltyReason {
+    #[error("invalid block")]
+    InvalidBlock,
+    #[error("invalid snark work")]
+    InvalidSnark,
+    #[error("malformed message")]
+    MalformedMessage,
+    #[error("protocol violation")]
+    ProtocolViolation,
+    #[error("timeout")]
+    Timeout,
+}
+
+impl P2pPenaltyReason {
+    /// Peers sending invalid proofs are banned straight away, as there is
+    /// no way an honest peer would relay them.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, Self::InvalidBlock | Self::InvalidSnark)
+    }
+
+    pub fn penalty(&self) -> f64 {
+        match self {
+            Self::InvalidBlock | Self::InvalidSnark => 100.0,
+            Self::MalformedMessage => 25.0,
+            Self::ProtocolViolation => 10.0,
+            Self::Timeout => 2.0,
+        }
+    }
+}
+
+impl P2pPeerReputation {
+    /// Score at `now`. Penalties decay exponentially towards zero.
+    pub fn score_at(&self, now: Timestamp, half_life: Duration) -> f64 {
+        let elapsed = now.checked_sub(self.updated_at).unwrap_or_default();
+        if half_life.is_zero() {
+            return 0.0;
+        }
+        self.score * 0.5_f64.powf(elapsed.as_secs_f64() / half_life.as_secs_f64())
+    }
+}
+
+impl Default for P2pReputationState {
+    fn default() -> Self {
+        Self {
+            peers: Default::default(),
+            banned_peers: Default::default(),
+            banned_ips: Default::default(),
+            last_prune: Timestamp::ZERO,
+        }
+    }
+}
+
+impl P2pReputationState {
+    pub fn peer_score(
+        &self,
+        peer_id: &PeerId,
+        now: Timestamp,
+        config: &P2pReputationConfig,
+    ) -> f64 {
+        self.peers
+            .get(peer_id)
+            .map_or(0.0, |peer| peer.score_at(now, config.score_half_life))
+    }
+
+    /// Expired bans are removed by [`super::P2pReputationAction::Prune`],
+    /// so presence in the map is enough.
+    pub fn is_peer_banned(&self, peer_id: &PeerId) -> bool {
+        self.banned_peers.contains_key(peer_id)
+    }
+
+    pub fn is_ip_banned(&self, ip: &IpAddr) -> bool {
+        self.banned_ips.contains_key(ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn score_decays_by_half_each_half_life() {
+        let half_life = Duration::from_secs(60);
+        let peer = P2pPeerReputation {
+            score: -80.0,
+            updated_at: Timestamp::ZERO,
+            last_penalty: Some(P2pPenaltyReason::MalformedMessage),
+        };
+
+        assert_eq!(peer.score_at(Timestamp::ZERO, half_life), -80.0);
+        assert_eq!(peer.score_at(Timestamp::ZERO + half_life, half_life), -40.0);
+        assert_eq!(
+            peer.score_at(Timestamp::ZERO + half_life * 2, half_life),
+            -20.0
+        );
+    }
+}
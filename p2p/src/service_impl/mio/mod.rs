@@ -1,12 +1,17 @@
 mod token;
 use self::token::{Token, TokenRegistry};
 
+#[cfg(feature = "p2p-quic")]
+mod quic;
+
+mod nat;
+
 use std::{
     collections::{BTreeMap, VecDeque},
     io::{self, Read, Write},
     net::{IpAddr, Ipv4Addr, Shutdown, SocketAddr},
     process,
-    sync::mpsc,
+    sync::{mpsc, Arc},
 };
 
 use libp2p_identity::Keypair;
@@ -139,12 +144,14 @@ impl MioRunningService {
 
         let mut inner = MioServiceInner {
             poll,
-            event_sender,
+            event_sender: Arc::new(event_sender),
             cmd_receiver: rx,
             tokens,
             listeners: BTreeMap::default(),
             connections: BTreeMap::default(),
             recv_buf: vec![0; 0x8000],
+            #[cfg(feature = "p2p-quic")]
+            quic: quic::QuicService::new(keypair.clone()),
         };
 
         std::thread::Builder::new()
@@ -173,12 +180,14 @@ impl MioRunningService {
 
 struct MioServiceInner<F> {
     poll: mio::Poll,
-    event_sender: F,
+    event_sender: Arc<F>,
     cmd_receiver: mpsc::Receiver<MioCmd>,
     tokens: TokenRegistry,
     listeners: BTreeMap<SocketAddr, Listener>,
     connections: BTreeMap<ConnectionAddr, Connection>,
     recv_buf: Vec<u8>,
+    #[cfg(feature = "p2p-quic")]
+    quic: quic::QuicService,
 }
 
 struct Listener {
@@ -199,7 +208,12 @@ where
     F: 'static + Send + Sync + Fn(MioEvent),
 {
     fn run(&mut self, events: &mut mio::Events) {
-        if let Err(err) = self.poll.poll(events, None) {
+        #[cfg(feature = "p2p-quic")]
+        let timeout = self.quic.timeout();
+        #[cfg(not(feature = "p2p-quic"))]
+        let timeout = None;
+
+        if let Err(err) = self.poll.poll(events, timeout) {
             MioError::Poll(err).report();
         }
 
@@ -211,6 +225,8 @@ where
                         self.handle(cmd);
                     }
                 }
+                #[cfg(feature = "p2p-quic")]
+                Some(Token::Quic(local)) => self.quic.recv(local),
                 Some(Token::Listener(addr)) => {
                     let Some(mut listener) = self.listeners.remove(&addr) else {
                         continue 'events;
@@ -331,6 +347,9 @@ where
             }
         }
         events.clear();
+
+        #[cfg(feature = "p2p-quic")]
+        self.quic.drive(&*self.event_sender);
     }
 
     fn handle(&mut self, cmd: MioCmd) {
@@ -579,8 +598,62 @@ where
                         .deregister(&mut cn.stream)
                         .unwrap_or_default();
                 }
+                #[cfg(feature = "p2p-quic")]
+                self.quic.disconnect(addr);
                 self.send(MioEvent::ConnectionDidCloseOnDemand(addr));
             }
+            #[cfg(feature = "p2p-quic")]
+            QuicListenOn(listener, alpn) => self.quic.listen(
+                self.poll.registry(),
+                &mut self.tokens,
+                listener,
+                alpn,
+                &*self.event_sender,
+            ),
+            #[cfg(feature = "p2p-quic")]
+            QuicConnect(addr, peer_id, alpn) => self.quic.connect(
+                self.poll.registry(),
+                &mut self.tokens,
+                addr,
+                peer_id,
+                alpn,
+                &*self.event_sender,
+            ),
+            #[cfg(feature = "p2p-quic")]
+            QuicSend(addr, stream_id, data, fin) => {
+                self.quic
+                    .send(addr, stream_id, data, fin, &*self.event_sender)
+            }
+            #[cfg(feature = "p2p-quic")]
+            QuicReset(addr, stream_id) => self.quic.reset(addr, stream_id),
+            #[cfg(not(feature = "p2p-quic"))]
+            QuicListenOn(listener, _) => self.send(MioEvent::QuicListenerError {
+                listener,
+                error: "QUIC transport is not supported".to_owned(),
+            }),
+            #[cfg(not(feature = "p2p-quic"))]
+            QuicConnect(sock_addr, ..) => self.send(MioEvent::QuicConnectionDidConnect(
+                ConnectionAddr {
+                    sock_addr,
+                    incoming: false,
+                },
+                Err("QUIC transport is not supported".to_owned()),
+            )),
+            #[cfg(not(feature = "p2p-quic"))]
+            QuicSend(..) | QuicReset(..) => {}
+            NatMapPort(gateway, internal_port, lifetime) => {
+                // talking to the gateway takes a while, don't block the event loop
+                let event_sender = self.event_sender.clone();
+                if let Err(err) = std::thread::Builder::new()
+                    .name("nat-port-mapping".into())
+                    .spawn(move || {
+                        let result = nat::map_port(gateway, internal_port, lifetime);
+                        event_sender(MioEvent::NatPortMapped(result));
+                    })
+                {
+                    self.send(MioEvent::NatPortMapped(Err(err.to_string())));
+                }
+            }
         }
     }
 
@@ -0,0 +1,199 @@
+//! Port mapping on the NAT gateway.
+//!
+//! NAT-PMP (RFC 6886) is tried first, it is a couple of UDP datagrams
+//! exchanged with the gateway. If the gateway doesn't answer, UPnP IGD
+//! is used, unless the gateway is set explicitly.
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use crate::{P2pNetworkNatMapping, P2pNetworkNatProtocol};
+
+const NAT_PMP_PORT: u16 = 5351;
+
+/// NAT-PMP retransmission starts at 250 ms and doubles each attempt,
+/// RFC suggests up to 9 attempts, we give up much sooner.
+const NAT_PMP_ATTEMPTS: u32 = 4;
+const NAT_PMP_INITIAL_TIMEOUT: Duration = Duration::from_millis(250);
+
+const UPNP_SEARCH_TIMEOUT: Duration = Duration::from_secs(5);
+const UPNP_DESCRIPTION: &str = "openmina";
+
+const OP_EXTERNAL_ADDRESS: u8 = 0;
+const OP_MAP_TCP: u8 = 2;
+const OP_RESPONSE: u8 = 0x80;
+
+#[derive(Debug, thiserror::Error)]
+enum NatPmpError {
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+    #[error("no response from the gateway")]
+    Timeout,
+    #[error("malformed response")]
+    Malformed,
+    #[error("gateway refused the request, result code: {0}")]
+    ResultCode(u16),
+}
+
+/// Requests the gateway to forward the TCP `internal_port` to us.
+/// Blocks until the gateway responds or all attempts time out.
+pub fn map_port(
+    gateway: Option<SocketAddr>,
+    internal_port: u16,
+    lifetime: Duration,
+) -> Result<P2pNetworkNatMapping, String> {
+    let nat_pmp_gateway = gateway
+        .or_else(|| default_gateway().map(|ip| SocketAddr::new(IpAddr::V4(ip), NAT_PMP_PORT)));
+    let nat_pmp_error = match nat_pmp_gateway {
+        Some(gateway) => match nat_pmp_map_port(gateway, internal_port, lifetime) {
+            Ok(mapping) => return Ok(mapping),
+            Err(err) => format!("NAT-PMP gateway {gateway}: {err}"),
+        },
+        None => "NAT-PMP: default gateway is unknown".to_owned(),
+    };
+    if gateway.is_some() {
+        return Err(nat_pmp_error);
+    }
+    upnp_map_port(internal_port, lifetime)
+        .map_err(|upnp_error| format!("{nat_pmp_error}; UPnP: {upnp_error}"))
+}
+
+fn nat_pmp_map_port(
+    gateway: SocketAddr,
+    internal_port: u16,
+    lifetime: Duration,
+) -> Result<P2pNetworkNatMapping, NatPmpError> {
+    let bind_ip: IpAddr = match gateway {
+        SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+        SocketAddr::V6(_) => std::net::Ipv6Addr::UNSPECIFIED.into(),
+    };
+    let socket = UdpSocket::bind((bind_ip, 0))?;
+    socket.connect(gateway)?;
+
+    let response = nat_pmp_request(&socket, &[0, OP_EXTERNAL_ADDRESS], 12)?;
+    let external_ip = Ipv4Addr::new(response[8], response[9], response[10], response[11]);
+
+    let lifetime_secs = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+    let mut request = [0; 12];
+    request[1] = OP_MAP_TCP;
+    request[4..6].copy_from_slice(&internal_port.to_be_bytes());
+    request[6..8].copy_from_slice(&internal_port.to_be_bytes());
+    request[8..12].copy_from_slice(&lifetime_secs.to_be_bytes());
+    let response = nat_pmp_request(&socket, &request, 16)?;
+
+    let response_internal_port = u16::from_be_bytes([response[8], response[9]]);
+    if response_internal_port != internal_port {
+        return Err(NatPmpError::Malformed);
+    }
+    let external_port = u16::from_be_bytes([response[10], response[11]]);
+    let lifetime = u32::from_be_bytes([response[12], response[13], response[14], response[15]]);
+
+    Ok(P2pNetworkNatMapping {
+        protocol: P2pNetworkNatProtocol::NatPmp,
+        external_ip: external_ip.into(),
+        external_port,
+        internal_port,
+        lifetime: Duration::from_secs(lifetime.into()),
+    })
+}
+
+/// Sends the request until the response with the matching opcode and of
+/// at least `len` bytes is received, checks the result code.
+fn nat_pmp_request(
+    socket: &UdpSocket,
+    request: &[u8],
+    len: usize,
+) -> Result<[u8; 16], NatPmpError> {
+    let mut timeout = NAT_PMP_INITIAL_TIMEOUT;
+    for _ in 0..NAT_PMP_ATTEMPTS {
+        socket.send(request)?;
+        socket.set_read_timeout(Some(timeout))?;
+        let mut buf = [0; 16];
+        loop {
+            match socket.recv(&mut buf) {
+                Ok(read) if read >= 4 && buf[1] == (OP_RESPONSE | request[1]) => {
+                    if buf[0] != 0 || read < len {
+                        return Err(NatPmpError::Malformed);
+                    }
+                    return match u16::from_be_bytes([buf[2], buf[3]]) {
+                        0 => Ok(buf),
+                        code => Err(NatPmpError::ResultCode(code)),
+                    };
+                }
+                // unrelated datagram
+                Ok(_) => continue,
+                Err(err)
+                    if matches!(
+                        err.kind(),
+                        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    break
+                }
+                // ICMP port unreachable, no NAT-PMP on the gateway
+                Err(err) if err.kind() == io::ErrorKind::ConnectionRefused => {
+                    return Err(NatPmpError::Timeout)
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        timeout *= 2;
+    }
+    Err(NatPmpError::Timeout)
+}
+
+fn upnp_map_port(internal_port: u16, lifetime: Duration) -> Result<P2pNetworkNatMapping, String> {
+    let gateway = igd_next::search_gateway(igd_next::SearchOptions {
+        timeout: Some(UPNP_SEARCH_TIMEOUT),
+        ..Default::default()
+    })
+    .map_err(|err| err.to_string())?;
+
+    // the local address the gateway sees us at
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(|err| err.to_string())?;
+    socket
+        .connect(gateway.addr)
+        .map_err(|err| err.to_string())?;
+    let local_ip = socket.local_addr().map_err(|err| err.to_string())?.ip();
+
+    let lifetime_secs = u32::try_from(lifetime.as_secs()).unwrap_or(u32::MAX);
+    gateway
+        .add_port(
+            igd_next::PortMappingProtocol::TCP,
+            internal_port,
+            SocketAddr::new(local_ip, internal_port),
+            lifetime_secs,
+            UPNP_DESCRIPTION,
+        )
+        .map_err(|err| err.to_string())?;
+    let external_ip = gateway.get_external_ip().map_err(|err| err.to_string())?;
+
+    Ok(P2pNetworkNatMapping {
+        protocol: P2pNetworkNatProtocol::Upnp,
+        external_ip,
+        external_port: internal_port,
+        internal_port,
+        lifetime,
+    })
+}
+
+/// IPv4 gateway of the default route.
+#[cfg(target_os = "linux")]
+fn default_gateway() -> Option<Ipv4Addr> {
+    // Iface  Destination  Gateway  Flags ...
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace().skip(1);
+        let destination = fields.next()?;
+        let gateway = u32::from_str_radix(fields.next()?, 16).ok()?;
+        (destination == "00000000" && gateway != 0).then(|| Ipv4Addr::from(gateway.to_le_bytes()))
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn default_gateway() -> Option<Ipv4Addr> {
+    None
+}
@@ -0,0 +1,645 @@
+//! QUIC transport driven by `quinn-proto`.
+//!
+//! `quinn-proto` doesn't do any IO, so the service owns the UDP sockets,
+//! feeds the received datagrams into the endpoints, sends the produced
+//! datagrams and reports connections and streams as [`MioEvent`]s.
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use bytes::{Bytes, BytesMut};
+use libp2p_identity::Keypair;
+use mio::net::UdpSocket;
+use quinn_proto::{
+    ClientConfig, ConnectionError, ConnectionHandle, DatagramEvent, Dir, Endpoint, EndpointConfig,
+    Event, ReadError, SendStream, ServerConfig, StreamEvent, TransportConfig, VarInt, WriteError,
+};
+
+use super::token::{Token, TokenRegistry};
+use crate::{ConnectionAddr, MioEvent, PeerId, StreamId, QUIC_REMOTE_STREAM_ID_BASE};
+
+const IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(10);
+// maximal ammount of queued data to send per stream is 16 MiB
+const MAX_QUEUED_BYTES: usize = 0x1000000;
+
+/// Endpoint local address and the connection handle in the endpoint.
+type ConnectionKey = (SocketAddr, usize);
+
+pub struct QuicService {
+    keypair: Keypair,
+    endpoints: BTreeMap<SocketAddr, QuicEndpoint>,
+    connections: BTreeMap<ConnectionKey, QuicConnection>,
+    /// The latest connection for the address, closed connections
+    /// stay in `connections` until they are drained.
+    addrs: BTreeMap<ConnectionAddr, ConnectionKey>,
+    recv_buf: Vec<u8>,
+}
+
+struct QuicEndpoint {
+    socket: UdpSocket,
+    endpoint: Endpoint,
+    /// The endpoint accepts incoming connections, otherwise it only dials.
+    listener: bool,
+}
+
+struct QuicConnection {
+    addr: ConnectionAddr,
+    handle: ConnectionHandle,
+    inner: quinn_proto::Connection,
+    connected: bool,
+    /// Closed by our node or already reported as lost.
+    closed: bool,
+    streams: BTreeMap<StreamId, QuicStream>,
+    stream_ids: BTreeMap<quinn_proto::StreamId, StreamId>,
+    send_queues: BTreeMap<StreamId, SendQueue>,
+}
+
+struct QuicStream {
+    id: quinn_proto::StreamId,
+    fin_sent: bool,
+    fin_received: bool,
+}
+
+#[derive(Default)]
+struct SendQueue {
+    chunks: VecDeque<Bytes>,
+    queued_bytes: usize,
+    fin: bool,
+}
+
+fn transport_config() -> Arc<TransportConfig> {
+    let mut config = TransportConfig::default();
+    config
+        .max_idle_timeout(IDLE_TIMEOUT.try_into().ok())
+        .keep_alive_interval(Some(KEEP_ALIVE_INTERVAL))
+        .max_concurrent_uni_streams(VarInt::from_u32(0));
+    Arc::new(config)
+}
+
+impl QuicService {
+    pub fn new(keypair: Keypair) -> Self {
+        QuicService {
+            keypair,
+            endpoints: BTreeMap::default(),
+            connections: BTreeMap::default(),
+            addrs: BTreeMap::default(),
+            recv_buf: vec![0; 0x10000],
+        }
+    }
+
+    /// Time until the earliest connection timer fires.
+    pub fn timeout(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        self.connections
+            .values_mut()
+            .filter_map(|cn| cn.inner.poll_timeout())
+            .min()
+            .map(|timeout| timeout.saturating_duration_since(now))
+    }
+
+    pub fn listen<F>(
+        &mut self,
+        registry: &mio::Registry,
+        tokens: &mut TokenRegistry,
+        listener: SocketAddr,
+        alpn: Box<[u8]>,
+        send: &F,
+    ) where
+        F: Fn(MioEvent),
+    {
+        let result = self
+            .server_config(alpn)
+            .and_then(|config| self.bind(registry, tokens, listener, Some(config)));
+        match result {
+            Ok(_) => send(MioEvent::QuicListenerReady { listener }),
+            Err(error) => send(MioEvent::QuicListenerError { listener, error }),
+        }
+    }
+
+    pub fn connect<F>(
+        &mut self,
+        registry: &mio::Registry,
+        tokens: &mut TokenRegistry,
+        sock_addr: SocketAddr,
+        peer_id: PeerId,
+        alpn: Box<[u8]>,
+        send: &F,
+    ) where
+        F: Fn(MioEvent),
+    {
+        let addr = ConnectionAddr {
+            sock_addr,
+            incoming: false,
+        };
+        if let Err(error) = self.try_connect(registry, tokens, addr, peer_id, alpn) {
+            send(MioEvent::QuicConnectionDidConnect(addr, Err(error)));
+        }
+    }
+
+    pub fn send<F>(
+        &mut self,
+        addr: ConnectionAddr,
+        stream_id: StreamId,
+        data: Box<[u8]>,
+        fin: bool,
+        send: &F,
+    ) where
+        F: Fn(MioEvent),
+    {
+        if let Some(cn) = self.connection_mut(&addr) {
+            cn.send(stream_id, data, fin, send);
+        }
+    }
+
+    pub fn reset(&mut self, addr: ConnectionAddr, stream_id: StreamId) {
+        if let Some(cn) = self.connection_mut(&addr) {
+            cn.reset(stream_id);
+        }
+    }
+
+    pub fn disconnect(&mut self, addr: ConnectionAddr) {
+        if let Some(cn) = self.connection_mut(&addr) {
+            cn.close();
+        }
+    }
+
+    /// Reads all datagrams available on the socket.
+    pub fn recv(&mut self, local: SocketAddr) {
+        let Some(endpoint) = self.endpoints.get_mut(&local) else {
+            return;
+        };
+        loop {
+            let (len, remote) = match endpoint.socket.recv_from(&mut self.recv_buf) {
+                Ok(v) => v,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            let data = BytesMut::from(&self.recv_buf[..len]);
+            match endpoint
+                .endpoint
+                .handle(Instant::now(), remote, None, None, data)
+            {
+                None => {}
+                Some((handle, DatagramEvent::NewConnection(inner))) => {
+                    let addr = ConnectionAddr {
+                        sock_addr: remote,
+                        incoming: true,
+                    };
+                    let key = (local, handle.0);
+                    self.addrs.insert(addr, key);
+                    self.connections
+                        .insert(key, QuicConnection::new(addr, handle, inner));
+                }
+                Some((handle, DatagramEvent::ConnectionEvent(event))) => {
+                    if let Some(cn) = self.connections.get_mut(&(local, handle.0)) {
+                        cn.inner.handle_event(event);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Processes timers and events of all connections and sends
+    /// the produced datagrams.
+    pub fn drive<F>(&mut self, send: &F)
+    where
+        F: Fn(MioEvent),
+    {
+        let now = Instant::now();
+        let mut drained = Vec::new();
+        for (key, cn) in &mut self.connections {
+            let Some(endpoint) = self.endpoints.get_mut(&key.0) else {
+                continue;
+            };
+            if cn
+                .inner
+                .poll_timeout()
+                .map_or(false, |timeout| timeout <= now)
+            {
+                cn.inner.handle_timeout(now);
+            }
+            while let Some(event) = cn.inner.poll_endpoint_events() {
+                if event.is_drained() {
+                    drained.push(*key);
+                }
+                if let Some(event) = endpoint.endpoint.handle_event(cn.handle, event) {
+                    cn.inner.handle_event(event);
+                }
+            }
+            while let Some(event) = cn.inner.poll() {
+                cn.handle_event(event, send);
+            }
+            while let Some(transmit) = cn.inner.poll_transmit(now, 1) {
+                endpoint.send(transmit);
+            }
+        }
+        for endpoint in self.endpoints.values_mut() {
+            while let Some(transmit) = endpoint.endpoint.poll_transmit() {
+                endpoint.send(transmit);
+            }
+        }
+        for key in drained {
+            if let Some(cn) = self.connections.remove(&key) {
+                if self.addrs.get(&cn.addr) == Some(&key) {
+                    self.addrs.remove(&cn.addr);
+                }
+            }
+        }
+    }
+
+    fn connection_mut(&mut self, addr: &ConnectionAddr) -> Option<&mut QuicConnection> {
+        self.addrs
+            .get(addr)
+            .and_then(|key| self.connections.get_mut(key))
+    }
+
+    fn server_config(&self, alpn: Box<[u8]>) -> Result<ServerConfig, String> {
+        let mut crypto =
+            libp2p_tls::make_server_config(&self.keypair).map_err(|err| err.to_string())?;
+        crypto.alpn_protocols = vec![alpn.into_vec()];
+        let mut config = ServerConfig::with_crypto(Arc::new(crypto));
+        config.transport_config(transport_config());
+        Ok(config)
+    }
+
+    fn client_config(&self, peer_id: PeerId, alpn: Box<[u8]>) -> Result<ClientConfig, String> {
+        let peer_id = libp2p_identity::PeerId::try_from(peer_id).map_err(|err| err.to_string())?;
+        let mut crypto = libp2p_tls::make_client_config(&self.keypair, Some(peer_id))
+            .map_err(|err| err.to_string())?;
+        crypto.alpn_protocols = vec![alpn.into_vec()];
+        let mut config = ClientConfig::new(Arc::new(crypto));
+        config.transport_config(transport_config());
+        Ok(config)
+    }
+
+    fn bind(
+        &mut self,
+        registry: &mio::Registry,
+        tokens: &mut TokenRegistry,
+        addr: SocketAddr,
+        server_config: Option<ServerConfig>,
+    ) -> Result<SocketAddr, String> {
+        let mut socket = UdpSocket::bind(addr).map_err(|err| err.to_string())?;
+        let local = socket.local_addr().map_err(|err| err.to_string())?;
+        registry
+            .register(
+                &mut socket,
+                tokens.register(Token::Quic(local)),
+                mio::Interest::READABLE,
+            )
+            .map_err(|err| err.to_string())?;
+        let listener = server_config.is_some();
+        let endpoint = Endpoint::new(
+            Arc::new(EndpointConfig::default()),
+            server_config.map(Arc::new),
+            true,
+        );
+        self.endpoints.insert(
+            local,
+            QuicEndpoint {
+                socket,
+                endpoint,
+                listener,
+            },
+        );
+        Ok(local)
+    }
+
+    fn try_connect(
+        &mut self,
+        registry: &mio::Registry,
+        tokens: &mut TokenRegistry,
+        addr: ConnectionAddr,
+        peer_id: PeerId,
+        alpn: Box<[u8]>,
+    ) -> Result<(), String> {
+        let config = self.client_config(peer_id, alpn)?;
+
+        // dial from the listening socket if there is one, so the remote peer
+        // sees our listening port
+        let is_ipv4 = addr.sock_addr.is_ipv4();
+        let local = self
+            .endpoints
+            .iter()
+            .filter(|(local, _)| local.is_ipv4() == is_ipv4)
+            .max_by_key(|(_, endpoint)| endpoint.listener)
+            .map(|(local, _)| *local);
+        let local = match local {
+            Some(local) => local,
+            None => {
+                let ip = if is_ipv4 {
+                    IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+                } else {
+                    IpAddr::V6(Ipv6Addr::UNSPECIFIED)
+                };
+                self.bind(registry, tokens, SocketAddr::new(ip, 0), None)?
+            }
+        };
+        let endpoint = self
+            .endpoints
+            .get_mut(&local)
+            .ok_or_else(|| "no such endpoint".to_owned())?;
+
+        let (handle, inner) = endpoint
+            .endpoint
+            .connect(config, addr.sock_addr, "l")
+            .map_err(|err| err.to_string())?;
+        let key = (local, handle.0);
+        self.addrs.insert(addr, key);
+        self.connections
+            .insert(key, QuicConnection::new(addr, handle, inner));
+        Ok(())
+    }
+}
+
+impl QuicEndpoint {
+    fn send(&self, transmit: quinn_proto::Transmit) {
+        // QUIC recovers lost packets, so the datagram is dropped if the socket is busy
+        let _ = self
+            .socket
+            .send_to(&transmit.contents, transmit.destination);
+    }
+}
+
+impl QuicConnection {
+    fn new(addr: ConnectionAddr, handle: ConnectionHandle, inner: quinn_proto::Connection) -> Self {
+        QuicConnection {
+            addr,
+            handle,
+            inner,
+            connected: false,
+            closed: false,
+            streams: BTreeMap::default(),
+            stream_ids: BTreeMap::default(),
+            send_queues: BTreeMap::default(),
+        }
+    }
+
+    fn close(&mut self) {
+        if !self.closed {
+            self.closed = true;
+            self.inner
+                .close(Instant::now(), VarInt::from_u32(0), Bytes::new());
+        }
+    }
+
+    /// Peer id from the certificate the remote peer presented.
+    fn peer_id(&self) -> Result<PeerId, String> {
+        let certificates = self
+            .inner
+            .crypto_session()
+            .peer_identity()
+            .and_then(|identity| identity.downcast::<Vec<rustls::Certificate>>().ok())
+            .ok_or_else(|| "missing peer certificate".to_owned())?;
+        let certificate = certificates
+            .first()
+            .ok_or_else(|| "missing peer certificate".to_owned())?;
+        let peer_id = libp2p_tls::certificate::parse(certificate)
+            .map_err(|err| err.to_string())?
+            .peer_id();
+        PeerId::try_from(peer_id).map_err(|err| err.to_string())
+    }
+
+    fn handle_event<F>(&mut self, event: Event, send: &F)
+    where
+        F: Fn(MioEvent),
+    {
+        let addr = self.addr;
+        match event {
+            Event::Connected => {
+                self.connected = true;
+                let result = self.peer_id();
+                if result.is_err() {
+                    self.close();
+                }
+                send(MioEvent::QuicConnectionDidConnect(addr, result));
+            }
+            Event::ConnectionLost { reason } => {
+                if self.closed {
+                    return;
+                }
+                self.closed = true;
+                if self.connected {
+                    let result = match reason {
+                        ConnectionError::ApplicationClosed(_)
+                        | ConnectionError::ConnectionClosed(_) => Ok(()),
+                        reason => Err(reason.to_string()),
+                    };
+                    send(MioEvent::ConnectionDidClose(addr, result));
+                } else if !addr.incoming {
+                    send(MioEvent::QuicConnectionDidConnect(
+                        addr,
+                        Err(reason.to_string()),
+                    ));
+                }
+            }
+            Event::Stream(StreamEvent::Opened { dir: Dir::Bi }) => {
+                while let Some(id) = self.inner.streams().accept(Dir::Bi) {
+                    let stream_id = QUIC_REMOTE_STREAM_ID_BASE | id.index() as StreamId;
+                    self.streams.insert(stream_id, QuicStream::new(id));
+                    self.stream_ids.insert(id, stream_id);
+                    self.read(id, send);
+                }
+            }
+            Event::Stream(StreamEvent::Readable { id }) => self.read(id, send),
+            Event::Stream(StreamEvent::Writable { id }) => {
+                if let Some(stream_id) = self.stream_ids.get(&id).copied() {
+                    self.flush(stream_id, send);
+                }
+            }
+            Event::Stream(StreamEvent::Stopped { id, .. }) => {
+                if let Some(stream_id) = self.stream_ids.get(&id).copied() {
+                    self.remove_stream(stream_id);
+                    send(MioEvent::QuicStreamDidReset(addr, stream_id));
+                }
+            }
+            Event::Stream(StreamEvent::Available { dir: Dir::Bi }) => {
+                // our streams that wait for the stream limit
+                let pending = self
+                    .send_queues
+                    .keys()
+                    .filter(|stream_id| !self.streams.contains_key(stream_id))
+                    .copied()
+                    .collect::<Vec<_>>();
+                for stream_id in pending {
+                    self.flush(stream_id, send);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn read<F>(&mut self, id: quinn_proto::StreamId, send: &F)
+    where
+        F: Fn(MioEvent),
+    {
+        let Some(stream_id) = self.stream_ids.get(&id).copied() else {
+            return;
+        };
+
+        let mut data = Vec::new();
+        let mut fin = false;
+        let mut reset = false;
+        {
+            let mut stream = self.inner.recv_stream(id);
+            let Ok(mut chunks) = stream.read(true) else {
+                return;
+            };
+            loop {
+                match chunks.next(usize::MAX) {
+                    Ok(Some(chunk)) => data.extend_from_slice(&chunk.bytes),
+                    Ok(None) => {
+                        fin = true;
+                        break;
+                    }
+                    Err(ReadError::Reset(_)) => {
+                        reset = true;
+                        break;
+                    }
+                    Err(_) => break,
+                }
+            }
+            let _ = chunks.finalize();
+        }
+
+        if !data.is_empty() || fin {
+            send(MioEvent::QuicStreamDidReceive(
+                self.addr,
+                stream_id,
+                data.into(),
+                fin,
+            ));
+        }
+        if reset {
+            self.remove_stream(stream_id);
+            send(MioEvent::QuicStreamDidReset(self.addr, stream_id));
+        } else if fin {
+            if let Some(stream) = self.streams.get_mut(&stream_id) {
+                stream.fin_received = true;
+                if stream.fin_sent {
+                    self.remove_stream(stream_id);
+                }
+            }
+        }
+    }
+
+    fn send<F>(&mut self, stream_id: StreamId, data: Box<[u8]>, fin: bool, send: &F)
+    where
+        F: Fn(MioEvent),
+    {
+        // the logical id is reused by a new stream of the same kind
+        if self.streams.get(&stream_id).map_or(false, |s| s.fin_sent) {
+            self.remove_stream(stream_id);
+        }
+
+        let queue = self.send_queues.entry(stream_id).or_default();
+        queue.queued_bytes += data.len();
+        queue.chunks.push_back(Bytes::from(data.into_vec()));
+        queue.fin |= fin;
+        if queue.queued_bytes > MAX_QUEUED_BYTES {
+            // the peer doesn't read the stream
+            self.reset(stream_id);
+            send(MioEvent::QuicStreamDidReset(self.addr, stream_id));
+            return;
+        }
+
+        self.flush(stream_id, send);
+    }
+
+    fn flush<F>(&mut self, stream_id: StreamId, send: &F)
+    where
+        F: Fn(MioEvent),
+    {
+        let id = match self.streams.get(&stream_id) {
+            Some(stream) => stream.id,
+            None if stream_id < QUIC_REMOTE_STREAM_ID_BASE => {
+                // our stream is opened with the first data,
+                // or later on `StreamEvent::Available`
+                let Some(id) = self.inner.streams().open(Dir::Bi) else {
+                    return;
+                };
+                self.streams.insert(stream_id, QuicStream::new(id));
+                self.stream_ids.insert(id, stream_id);
+                id
+            }
+            None => {
+                self.send_queues.remove(&stream_id);
+                return;
+            }
+        };
+
+        let Some(queue) = self.send_queues.get_mut(&stream_id) else {
+            return;
+        };
+        match write_queue(&mut self.inner.send_stream(id), queue) {
+            Ok(false) => {}
+            Ok(true) => {
+                self.send_queues.remove(&stream_id);
+                if let Some(stream) = self.streams.get_mut(&stream_id) {
+                    stream.fin_sent = true;
+                    if stream.fin_received {
+                        self.remove_stream(stream_id);
+                    }
+                }
+            }
+            Err(_) => {
+                self.remove_stream(stream_id);
+                send(MioEvent::QuicStreamDidReset(self.addr, stream_id));
+            }
+        }
+    }
+
+    fn reset(&mut self, stream_id: StreamId) {
+        if let Some(stream) = self.streams.get(&stream_id) {
+            let id = stream.id;
+            let _ = self.inner.send_stream(id).reset(VarInt::from_u32(0));
+            let _ = self.inner.recv_stream(id).stop(VarInt::from_u32(0));
+        }
+        self.remove_stream(stream_id);
+    }
+
+    fn remove_stream(&mut self, stream_id: StreamId) {
+        if let Some(stream) = self.streams.remove(&stream_id) {
+            self.stream_ids.remove(&stream.id);
+        }
+        self.send_queues.remove(&stream_id);
+    }
+}
+
+impl QuicStream {
+    fn new(id: quinn_proto::StreamId) -> Self {
+        QuicStream {
+            id,
+            fin_sent: false,
+            fin_received: false,
+        }
+    }
+}
+
+/// Writes the queued data into the stream, returns `true` if all the data
+/// is written and the stream is finished.
+fn write_queue(stream: &mut SendStream<'_>, queue: &mut SendQueue) -> Result<bool, WriteError> {
+    while let Some(chunk) = queue.chunks.front_mut() {
+        let len = match stream.write(chunk) {
+            Ok(len) => len,
+            Err(WriteError::Blocked) => return Ok(false),
+            Err(err) => return Err(err),
+        };
+        queue.queued_bytes -= len;
+        let _ = chunk.split_to(len);
+        if chunk.is_empty() {
+            queue.chunks.pop_front();
+        }
+    }
+    if queue.fin {
+        // the stream might be already stopped by the peer, it is reported by an event
+        let _ = stream.finish();
+    }
+    Ok(queue.fin)
+}
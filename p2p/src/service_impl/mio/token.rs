@@ -7,6 +7,8 @@ pub enum Token {
     Waker,
     Listener(SocketAddr),
     Connection(ConnectionAddr),
+    #[cfg(feature = "p2p-quic")]
+    Quic(SocketAddr),
 }
 
 #[derive(Default)]
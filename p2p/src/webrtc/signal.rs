@@ -51,6 +51,8 @@ pub enum RejectionReason {
     AlreadyConnected,
     #[error("self connection detected")]
     ConnectingToSelf,
+    #[error("peer is banned")]
+    Banned,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -12,10 +12,10 @@ use openmina_core::{ChainId, Substate, DEVNET_CHAIN_ID};
 use p2p::{
     connection::outgoing::{
         P2pConnectionOutgoingAction, P2pConnectionOutgoingInitLibp2pOpts,
-        P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingInitOptsParseError,
+        P2pConnectionOutgoingInitOpts, P2pConnectionOutgoingInitOptsParseError, P2pLibp2pTransport,
     },
     identity::SecretKey,
-    P2pCallbacks, P2pConfig, P2pMeshsubConfig, P2pState, PeerId,
+    P2pCallbacks, P2pConfig, P2pMeshsubConfig, P2pReputationConfig, P2pState, PeerId,
 };
 use redux::SystemTime;
 use tokio::sync::mpsc;
@@ -299,6 +299,7 @@ impl Cluster {
                         peer_id,
                         host,
                         port,
+                        transport: P2pLibp2pTransport::Tcp,
                     },
                 ))
             }
@@ -312,6 +313,7 @@ impl Cluster {
                         peer_id,
                         host,
                         port,
+                        transport: P2pLibp2pTransport::Tcp,
                     },
                 ))
             }
@@ -344,6 +346,7 @@ impl Cluster {
             Self::secret_key(config.peer_id, self.rust_nodes.len(), RUST_NODE_SIG_BYTE);
         let libp2p_port = self.next_port()?;
         let listen_port = self.next_port()?;
+        let quic_port = config.quic.then(|| self.next_port()).transpose()?;
         let initial_peers = config
             .initial_peers
             .into_iter()
@@ -351,6 +354,7 @@ impl Cluster {
             .collect::<Result<_>>()?;
         let config = P2pConfig {
             libp2p_port: Some(libp2p_port),
+            quic_port,
             listen_port: Some(listen_port),
             identity_pub_key: secret_key.public_key(),
             initial_peers,
@@ -360,6 +364,8 @@ impl Cluster {
             timeouts: config.timeouts,
             limits: config.limits,
             meshsub: P2pMeshsubConfig::default(),
+            reputation: P2pReputationConfig::default(),
+            nat: config.nat,
         };
 
         Ok((config, secret_key))
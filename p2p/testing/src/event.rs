@@ -223,6 +223,8 @@ pub fn is_error(event: &ClusterEvent) -> bool {
                     | MioEvent::OutgoingConnectionDidConnect(_, Err(_))
                     | MioEvent::OutgoingDataDidSend(_, Err(_))
                     | MioEvent::ConnectionDidClose(_, Err(_))
+                    | MioEvent::QuicListenerError { .. }
+                    | MioEvent::QuicConnectionDidConnect(_, Err(_))
             ),
         },
         _ => false,
@@ -165,6 +165,8 @@ pub fn default_errors(event: &ClusterEvent) -> bool {
                         | p2p::MioEvent::OutgoingConnectionDidConnect(_, Err(_))
                         | p2p::MioEvent::OutgoingDataDidSend(_, Err(_))
                         | p2p::MioEvent::ConnectionDidClose(_, Err(_))
+                        | p2p::MioEvent::QuicListenerError { .. }
+                        | p2p::MioEvent::QuicConnectionDidConnect(_, Err(_))
                 ),
             },
             _ => false,
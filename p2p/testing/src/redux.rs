@@ -257,6 +257,38 @@ pub(super) fn event_effect(store: &mut crate::redux::Store, event: P2pEvent) ->
             MioEvent::ConnectionDidCloseOnDemand(addr) => {
                 SubStore::dispatch(store, P2pNetworkSchedulerAction::Prune { addr })
             }
+            MioEvent::QuicListenerReady { listener } => {
+                SubStore::dispatch(store, p2p::P2pNetworkQuicAction::ListenerReady { listener })
+            }
+            MioEvent::QuicListenerError { listener, error } => SubStore::dispatch(
+                store,
+                p2p::P2pNetworkQuicAction::ListenerError { listener, error },
+            ),
+            MioEvent::QuicConnectionDidConnect(addr, result) => SubStore::dispatch(
+                store,
+                p2p::P2pNetworkQuicAction::DidConnect { addr, result },
+            ),
+            MioEvent::QuicStreamDidReceive(addr, stream_id, data, fin) => SubStore::dispatch(
+                store,
+                p2p::P2pNetworkQuicAction::IncomingData {
+                    addr,
+                    stream_id,
+                    data,
+                    fin,
+                },
+            ),
+            MioEvent::QuicStreamDidReset(addr, stream_id) => SubStore::dispatch(
+                store,
+                p2p::P2pNetworkQuicAction::StreamDidReset { addr, stream_id },
+            ),
+            MioEvent::NatPortMapped(result) => match result {
+                Ok(mapping) => {
+                    SubStore::dispatch(store, p2p::P2pNetworkNatAction::MappingSuccess { mapping })
+                }
+                Err(error) => {
+                    SubStore::dispatch(store, p2p::P2pNetworkNatAction::MappingError { error })
+                }
+            },
         },
         _ => false,
     }
@@ -285,6 +317,8 @@ impl_from_p2p!(P2pNetworkKadRequestAction);
 impl_from_p2p!(P2pNetworkKadBootstrapAction);
 impl_from_p2p!(P2pPeerAction);
 impl_from_p2p!(P2pNetworkYamuxAction);
+impl_from_p2p!(p2p::P2pNetworkQuicAction);
+impl_from_p2p!(p2p::P2pNetworkNatAction);
 impl_from_p2p!(P2pConnectionOutgoingAction);
 impl_from_p2p!(P2pNetworkSchedulerAction);
 impl_from_p2p!(P2pNetworkIdentifyStreamAction);
@@ -301,6 +335,7 @@ impl_from_p2p!(P2pChannelsSnarkAction);
 impl_from_p2p!(p2p::P2pNetworkRpcAction);
 impl_from_p2p!(P2pChannelsRpcAction);
 impl_from_p2p!(P2pDisconnectionAction);
+impl_from_p2p!(p2p::reputation::P2pReputationAction);
 impl_from_p2p!(P2pChannelsBestTipAction);
 impl_from_p2p!(P2pChannelsSnarkJobCommitmentAction);
 impl_from_p2p!(P2pChannelsStreamingRpcAction);
@@ -309,6 +344,8 @@ impl_from_p2p!(effectful P2pNetworkKadEffectfulAction);
 impl_from_p2p!(effectful P2pConnectionIncomingEffectfulAction);
 impl_from_p2p!(effectful p2p::P2pNetworkSchedulerEffectfulAction);
 impl_from_p2p!(effectful p2p::P2pNetworkPnetEffectfulAction);
+impl_from_p2p!(effectful p2p::P2pNetworkQuicEffectfulAction);
+impl_from_p2p!(effectful p2p::P2pNetworkNatEffectfulAction);
 impl_from_p2p!(effectful p2p::P2pNetworkPubsubEffectfulAction);
 impl_from_p2p!(effectful P2pNetworkIdentifyStreamEffectfulAction);
 impl_from_p2p!(effectful P2pConnectionOutgoingEffectfulAction);
@@ -5,7 +5,7 @@ use std::{
 };
 
 use futures::Stream;
-use p2p::{P2pAction, P2pEvent, P2pLimits, P2pState, P2pTimeouts, PeerId};
+use p2p::{P2pAction, P2pEvent, P2pLimits, P2pNatConfig, P2pState, P2pTimeouts, PeerId};
 use redux::{Effects, EnablingCondition, Reducer, SubStore};
 use tokio::sync::mpsc;
 
@@ -27,6 +27,8 @@ pub struct RustNodeConfig {
     pub timeouts: P2pTimeouts,
     pub limits: P2pLimits,
     pub discovery: bool,
+    pub quic: bool,
+    pub nat: P2pNatConfig,
     pub override_fn: Option<Effects<State, ClusterService, Action>>,
     pub override_reducer: Option<Reducer<State, Action>>,
 }
@@ -60,6 +62,16 @@ impl RustNodeConfig {
         self
     }
 
+    pub fn with_quic(mut self, quic: bool) -> Self {
+        self.quic = quic;
+        self
+    }
+
+    pub fn with_nat(mut self, nat: P2pNatConfig) -> Self {
+        self.nat = nat;
+        self
+    }
+
     pub fn with_override(mut self, override_fn: Effects<State, ClusterService, Action>) -> Self {
         self.override_fn = Some(override_fn);
         self
@@ -3,7 +3,9 @@ use std::net::IpAddr;
 use libp2p::multiaddr::multiaddr;
 use libp2p::Multiaddr;
 use p2p::{
-    connection::outgoing::{P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts},
+    connection::outgoing::{
+        P2pConnectionOutgoingInitLibp2pOpts, P2pConnectionOutgoingInitOpts, P2pLibp2pTransport,
+    },
     PeerId,
 };
 
@@ -17,6 +19,7 @@ pub trait TestNode {
             peer_id: self.peer_id(),
             host: host.into(),
             port: self.libp2p_port(),
+            transport: P2pLibp2pTransport::Tcp,
         })
     }
 
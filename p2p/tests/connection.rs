@@ -74,6 +74,57 @@ async fn rust_to_rust() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that a Rust node can connect to another Rust node using QUIC.
+#[cfg(feature = "p2p-quic")]
+#[tokio::test]
+async fn rust_to_rust_quic() -> anyhow::Result<()> {
+    let mut cluster = ClusterBuilder::default()
+        .ports_with_len(10)
+        .total_duration(Duration::from_secs(10))
+        .start()
+        .await?;
+
+    let rust_node = cluster.add_rust_node(RustNodeConfig::default().with_quic(true))?;
+    let rust_node1 = cluster.add_rust_node(RustNodeConfig::default().with_quic(true))?;
+    let peer_id = cluster.peer_id(rust_node1);
+    let quic_port = cluster
+        .rust_node(rust_node1)
+        .state()
+        .config
+        .quic_port
+        .expect("QUIC port should be present");
+
+    let listening =
+        wait_for_all_nodes_to_listen(&mut cluster, [rust_node1], Duration::from_secs(2)).await;
+    assert!(listening);
+
+    let maddr = format!(
+        "/ip4/127.0.0.1/udp/{quic_port}/quic-v1/p2p/{}",
+        peer_id.to_libp2p_string()
+    )
+    .parse()?;
+    cluster.connect(rust_node, p2p_testing::cluster::Listener::Multiaddr(maddr))?;
+
+    let connected =
+        try_wait_for_nodes_to_connect(&mut cluster, [(rust_node, peer_id)], Duration::from_secs(5))
+            .await?;
+    assert!(connected);
+
+    assert_peer_is_ready(&cluster, rust_node, peer_id);
+    let state = cluster.rust_node(rust_node).state();
+    assert!(
+        state
+            .network
+            .scheduler
+            .connections
+            .values()
+            .any(|cn| cn.peer_id() == Some(&peer_id) && cn.quic_state().is_some()),
+        "connection should use QUIC"
+    );
+
+    Ok(())
+}
+
 /// Tests that a Rust node can connect to a libp2p client.
 #[tokio::test]
 async fn rust_to_libp2p() -> anyhow::Result<()> {
@@ -183,7 +183,7 @@ fn bad_node_effects(
                         addr,
                         peer_id,
                         stream_id,
-                        addresses: _,
+                        ..
                     },
                 )),
             )) => {
@@ -0,0 +1,128 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    thread,
+    time::Duration,
+};
+
+use multiaddr::multiaddr;
+use p2p::{P2pNatConfig, P2pNetworkNatProtocol};
+use p2p_testing::{
+    cluster::{ClusterBuilder, ClusterEvent},
+    event::RustNodeEvent,
+    futures::TryStreamExt,
+    predicates::{async_fn, listener_is_ready},
+    rust_node::RustNodeConfig,
+    stream::ClusterStreamExt,
+    test_node::TestNode,
+    utils::run_cluster,
+};
+
+/// Stand-in NAT-PMP gateway, maps any requested TCP port to the
+/// `external_port` of the `external_ip`.
+fn nat_pmp_gateway(external_ip: Ipv4Addr, external_port: u16) -> SocketAddr {
+    let socket = UdpSocket::bind("127.0.0.1:0").expect("bind gateway socket");
+    let addr = socket.local_addr().expect("gateway address");
+    thread::spawn(move || {
+        let mut buf = [0; 12];
+        while let Ok((len, peer)) = socket.recv_from(&mut buf) {
+            // version, opcode, result code, seconds since start of epoch
+            let mut response = vec![0, 0x80 | buf[1], 0, 0, 0, 0, 0, 1];
+            match (buf[1], len) {
+                // external address
+                (0, 2) => response.extend(external_ip.octets()),
+                // TCP port mapping
+                (2, 12) => {
+                    response.extend(&buf[4..6]);
+                    response.extend(external_port.to_be_bytes());
+                    response.extend(&buf[8..12]);
+                }
+                // unsupported opcode
+                _ => response[3] = 5,
+            }
+            socket.send_to(&response, peer).expect("gateway response");
+        }
+    });
+    addr
+}
+
+#[tokio::test]
+async fn port_mapping_is_published() -> anyhow::Result<()> {
+    let gateway = nat_pmp_gateway(Ipv4Addr::new(203, 0, 113, 7), 40302);
+
+    let mut cluster = ClusterBuilder::new()
+        .ports_with_len(10)
+        .idle_duration(Duration::from_millis(100))
+        .start()
+        .await?;
+
+    let node1 = cluster.add_rust_node(RustNodeConfig::default().with_discovery(true).with_nat(
+        P2pNatConfig {
+            port_mapping: true,
+            gateway: Some(gateway),
+            ..Default::default()
+        },
+    ))?;
+    let node2 = cluster.add_rust_node(RustNodeConfig::default().with_discovery(true))?;
+    let peer_id1 = cluster.rust_node(node1).peer_id();
+    let libp2p_port1 = cluster.rust_node(node1).libp2p_port();
+
+    let listener_is_ready = cluster
+        .try_stream()
+        .take_during(Duration::from_secs(2))
+        .try_any(listener_is_ready(node1))
+        .await?;
+    assert!(listener_is_ready, "node1 should be ready");
+
+    // the mapping is requested on the next timeouts check after the listener is ready
+    let mut mapping = None;
+    for _ in 0..50 {
+        let nat = &cluster.rust_node(node1).state().network.scheduler.nat;
+        mapping = nat.mapping().cloned();
+        if mapping.is_some() {
+            break;
+        }
+        run_cluster(&mut cluster, Duration::from_millis(100)).await;
+    }
+    let mapping = mapping.expect("port should be mapped");
+    assert_eq!(mapping.protocol, P2pNetworkNatProtocol::NatPmp);
+    assert_eq!(mapping.external_ip, Ipv4Addr::new(203, 0, 113, 7));
+    assert_eq!(mapping.external_port, 40302);
+    assert_eq!(mapping.internal_port, libp2p_port1);
+
+    let external_addr = multiaddr!(Ip4([203, 0, 113, 7]), Tcp(40302u16));
+
+    let own_entry = cluster
+        .rust_node(node1)
+        .state()
+        .network
+        .scheduler
+        .discovery_state()
+        .expect("State must be initialized")
+        .routing_table
+        .look_up(&peer_id1.try_into().expect("PeerId conversion failed"))
+        .expect("own entry should be in the routing table");
+    assert!(
+        own_entry.addresses().contains(&external_addr),
+        "mapped address should be in own Kademlia entry: {:?}",
+        own_entry.addresses()
+    );
+
+    cluster.connect(node2, node1)?;
+
+    let identified = cluster
+        .try_stream()
+        .take_during(Duration::from_secs(10))
+        .try_any(async_fn(|event| {
+            matches!(
+                event,
+                ClusterEvent::Rust {
+                    id,
+                    event: RustNodeEvent::Identify { peer_id, info },
+                } if id == node2 && peer_id == peer_id1 && info.listen_addrs.contains(&external_addr)
+            )
+        }))
+        .await?;
+    assert!(identified, "mapped address should be sent with identify");
+
+    Ok(())
+}
@@ -264,19 +264,25 @@ pub mod params {
 
     use super::*;
 
+    /// Builds param for a string which isn't known at compile time
+    /// (e.g. signature prefix of a custom network).
+    ///
+    /// Panics if `string` is longer than 20 bytes.
+    pub fn param(string: &'static str) -> Box<LazyParam> {
+        let mut sponge = Sponge::<Fp>::default();
+        sponge.absorb(&[param_to_field(string)]);
+        let last_squeezed = sponge.squeeze();
+        Box::new(LazyParam {
+            sponge_state: sponge.sponge_state,
+            state: sponge.state,
+            last_squeezed,
+            string,
+        })
+    }
+
     macro_rules! impl_params {
         ($({$name:tt, $string:tt}),*) => ($(
-            pub static $name: Lazy<Box<LazyParam>> = Lazy::new(|| {
-                let mut sponge = Sponge::<Fp>::default();
-                sponge.absorb(&[param_to_field($string)]);
-                let last_squeezed = sponge.squeeze();
-                Box::new(LazyParam {
-                    sponge_state: sponge.sponge_state,
-                    state: sponge.state,
-                    last_squeezed,
-                    string: $string,
-                })
-            });
+            pub static $name: Lazy<Box<LazyParam>> = Lazy::new(|| param($string));
         )*)
     }
 
@@ -539,19 +545,25 @@ pub mod legacy {
 
         use super::*;
 
+        /// Builds legacy param for a string which isn't known at compile
+        /// time (e.g. signature prefix of a custom network).
+        ///
+        /// Panics if `string` is longer than 20 bytes.
+        pub fn param(string: &'static str) -> Box<LazyParam> {
+            let mut sponge = Sponge::new_legacy();
+            sponge.absorb(&[param_to_field(string)]);
+            let last_squeezed = sponge.squeeze();
+            Box::new(LazyParam {
+                sponge_state: sponge.sponge_state,
+                state: sponge.state,
+                last_squeezed,
+                string,
+            })
+        }
+
         macro_rules! impl_params {
             ($({$name:tt, $string:tt}),*) => ($(
-                pub static $name: Lazy<Box<LazyParam>> = Lazy::new(|| {
-                    let mut sponge = Sponge::new_legacy();
-                    sponge.absorb(&[param_to_field($string)]);
-                    let last_squeezed = sponge.squeeze();
-                    Box::new(LazyParam {
-                        sponge_state: sponge.sponge_state,
-                        state: sponge.state,
-                        last_squeezed,
-                        string: $string,
-                    })
-                });
+                pub static $name: Lazy<Box<LazyParam>> = Lazy::new(|| param($string));
             )*)
         }
 
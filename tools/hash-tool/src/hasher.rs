@@ -0,0 +1,93 @@
+use mina_curves::pasta::Fp;
+use mina_p2p_messages::bigint::BigInt;
+use poseidon::{hash::param_to_field, Sponge, SpongeConstants};
+use serde_json::Value;
+
+/// Maximum length of a domain string accepted by [`param_to_field`].
+const MAX_DOMAIN_LEN: usize = 20;
+
+/// Parses a field element given as a decimal or as `0x` prefixed big-endian hex.
+pub fn parse_field(s: &str) -> anyhow::Result<Fp> {
+    let value = match s.strip_prefix("0x") {
+        Some(hex) => format!("0x{hex:0>64}"),
+        None => s.to_owned(),
+    };
+    let bigint = serde_json::from_value::<BigInt>(Value::String(value))
+        .map_err(|_| anyhow::anyhow!("invalid field element: {s}"))?;
+    bigint
+        .to_field()
+        .map_err(|_| anyhow::anyhow!("field element out of range: {s}"))
+}
+
+/// Poseidon hash of the fields, prefixed with the domain if set, the same
+/// way `poseidon::hash::hash_with_kimchi` does with its params.
+pub fn hash(fields: &[Fp], domain: Option<&str>, legacy: bool) -> anyhow::Result<Fp> {
+    let domain = match domain {
+        Some(domain) if domain.len() > MAX_DOMAIN_LEN => {
+            anyhow::bail!("domain must be {MAX_DOMAIN_LEN} bytes maximum")
+        }
+        Some(domain) => Some(param_to_field(domain)),
+        None => None,
+    };
+
+    if legacy {
+        Ok(absorb_and_squeeze(Sponge::new_legacy(), fields, domain))
+    } else {
+        Ok(absorb_and_squeeze(Sponge::<Fp>::default(), fields, domain))
+    }
+}
+
+fn absorb_and_squeeze<C: SpongeConstants>(
+    mut sponge: Sponge<Fp, C>,
+    fields: &[Fp],
+    domain: Option<Fp>,
+) -> Fp {
+    if let Some(domain) = domain {
+        sponge.absorb(&[domain]);
+        sponge.squeeze();
+    }
+    sponge.absorb(fields);
+    sponge.squeeze()
+}
+
+pub fn to_hex(field: Fp) -> String {
+    match serde_json::to_value(BigInt::from(field)) {
+        Ok(Value::String(hex)) => hex,
+        _ => unreachable!("field elements are serialized as hex strings"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use poseidon::hash::{hash_with_kimchi, legacy, params::MINA_ACCOUNT, Inputs};
+
+    use super::*;
+
+    #[test]
+    fn test_hash_matches_params() {
+        let fields = vec![Fp::from(1u64), Fp::from(2u64)];
+        assert_eq!(
+            hash(&fields, Some("MinaAccount"), false).unwrap(),
+            hash_with_kimchi(&MINA_ACCOUNT, &fields)
+        );
+        assert_eq!(
+            hash(&fields, Some("CodaReceiptUC"), true).unwrap(),
+            legacy::hash_with_kimchi(&legacy::params::CODA_RECEIPT_UC, &fields)
+        );
+
+        let mut inputs = Inputs::new();
+        inputs.append_field(Fp::from(3u64));
+        let fields = inputs.to_fields();
+        assert_eq!(
+            hash(&fields, Some("MinaAccount"), false).unwrap(),
+            hash_with_kimchi(&MINA_ACCOUNT, &fields)
+        );
+    }
+
+    #[test]
+    fn test_parse_field() {
+        assert_eq!(parse_field("42").unwrap(), Fp::from(42u64));
+        assert_eq!(parse_field("0x2a").unwrap(), Fp::from(42u64));
+        assert_eq!(to_hex(Fp::from(42u64)), format!("0x{:0>64}", "2a"));
+    }
+}
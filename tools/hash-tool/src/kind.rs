@@ -0,0 +1,264 @@
+use std::{fmt, ops::Deref, str::FromStr};
+
+use mina_p2p_messages::{
+    b58,
+    b58version::*,
+    binprot::BinProtWrite,
+    v2::{
+        CoinbaseStackData, CoinbaseStackHash, EpochSeed, LedgerHash, NonZeroCurvePoint,
+        PendingCoinbaseHash, PrivateKey, ReceiptChainHash, Signature, StateBodyHash, StateHash,
+        TokenIdKeyHash, TransactionHash,
+    },
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// Kind of a base58check encoded value, identified by its version byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    LedgerHash,
+    StateHash,
+    StateBodyHash,
+    PendingCoinbaseHash,
+    ReceiptChainHash,
+    EpochSeed,
+    CoinbaseStackData,
+    CoinbaseStackHash,
+    TokenId,
+    PublicKey,
+    PrivateKey,
+    Signature,
+    TransactionHash,
+    StagedLedgerHashAuxHash,
+    PendingCoinbaseAux,
+    Memo,
+    VrfOutput,
+    PendingCoinbaseHashBuilder,
+    VerificationKey,
+}
+
+impl Kind {
+    pub const ALL: &'static [Kind] = &[
+        Self::LedgerHash,
+        Self::StateHash,
+        Self::StateBodyHash,
+        Self::PendingCoinbaseHash,
+        Self::ReceiptChainHash,
+        Self::EpochSeed,
+        Self::CoinbaseStackData,
+        Self::CoinbaseStackHash,
+        Self::TokenId,
+        Self::PublicKey,
+        Self::PrivateKey,
+        Self::Signature,
+        Self::TransactionHash,
+        Self::StagedLedgerHashAuxHash,
+        Self::PendingCoinbaseAux,
+        Self::Memo,
+        Self::VrfOutput,
+        Self::PendingCoinbaseHashBuilder,
+        Self::VerificationKey,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LedgerHash => "ledger-hash",
+            Self::StateHash => "state-hash",
+            Self::StateBodyHash => "state-body-hash",
+            Self::PendingCoinbaseHash => "pending-coinbase-hash",
+            Self::ReceiptChainHash => "receipt-chain-hash",
+            Self::EpochSeed => "epoch-seed",
+            Self::CoinbaseStackData => "coinbase-stack-data",
+            Self::CoinbaseStackHash => "coinbase-stack-hash",
+            Self::TokenId => "token-id",
+            Self::PublicKey => "public-key",
+            Self::PrivateKey => "private-key",
+            Self::Signature => "signature",
+            Self::TransactionHash => "transaction-hash",
+            Self::StagedLedgerHashAuxHash => "staged-ledger-hash-aux-hash",
+            Self::PendingCoinbaseAux => "pending-coinbase-aux",
+            Self::Memo => "memo",
+            Self::VrfOutput => "vrf-output",
+            Self::PendingCoinbaseHashBuilder => "pending-coinbase-hash-builder",
+            Self::VerificationKey => "verification-key",
+        }
+    }
+
+    pub fn version(self) -> u8 {
+        match self {
+            Self::LedgerHash => LEDGER_HASH,
+            Self::StateHash => STATE_HASH,
+            Self::StateBodyHash => STATE_BODY_HASH,
+            // pending coinbase hashes share the version byte with receipt chain hashes
+            Self::PendingCoinbaseHash | Self::ReceiptChainHash => RECEIPT_CHAIN_HASH,
+            Self::EpochSeed => EPOCH_SEED,
+            Self::CoinbaseStackData => COINBASE_STACK_DATA,
+            Self::CoinbaseStackHash => COINBASE_STACK_HASH,
+            Self::TokenId => TOKEN_ID_KEY,
+            Self::PublicKey => NON_ZERO_CURVE_POINT_COMPRESSED,
+            Self::PrivateKey => PRIVATE_KEY,
+            Self::Signature => SIGNATURE,
+            Self::TransactionHash => TRANSACTION_HASH,
+            Self::StagedLedgerHashAuxHash => STAGED_LEDGER_HASH_AUX_HASH,
+            Self::PendingCoinbaseAux => STAGED_LEDGER_HASH_PENDING_COINBASE_AUX,
+            Self::Memo => USER_COMMAND_MEMO,
+            Self::VrfOutput => VRF_TRUNCATED_OUTPUT,
+            Self::PendingCoinbaseHashBuilder => PENDING_COINBASE_HASH_BUILDER,
+            Self::VerificationKey => VERIFICATION_KEY,
+        }
+    }
+
+    /// Kinds which could have produced the base58check string.
+    pub fn detect(b58: &str) -> anyhow::Result<Vec<Kind>> {
+        let bytes = bs58::decode(b58).with_check(None).into_vec()?;
+        let version = *bytes
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("empty input"))?;
+        Ok(Self::ALL
+            .iter()
+            .copied()
+            .filter(|kind| kind.version() == version)
+            .collect())
+    }
+
+    /// Decodes the base58check string into JSON of the underlying value.
+    ///
+    /// Kinds without a typed representation are decoded into the hex of
+    /// their payload.
+    pub fn decode(self, b58: &str) -> anyhow::Result<Value> {
+        match self {
+            Self::LedgerHash => decode_as::<LedgerHash>(b58),
+            Self::StateHash => decode_as::<StateHash>(b58),
+            Self::StateBodyHash => decode_as::<StateBodyHash>(b58),
+            Self::PendingCoinbaseHash => decode_as::<PendingCoinbaseHash>(b58),
+            Self::ReceiptChainHash => decode_as::<ReceiptChainHash>(b58),
+            Self::EpochSeed => decode_as::<EpochSeed>(b58),
+            Self::CoinbaseStackData => decode_as::<CoinbaseStackData>(b58),
+            Self::CoinbaseStackHash => decode_as::<CoinbaseStackHash>(b58),
+            Self::TokenId => decode_as::<TokenIdKeyHash>(b58),
+            Self::PublicKey => decode_as::<NonZeroCurvePoint>(b58),
+            Self::PrivateKey => decode_as::<PrivateKey>(b58),
+            Self::Signature => decode_as::<Signature>(b58),
+            Self::TransactionHash => {
+                let hash = b58.parse::<TransactionHash>()?;
+                let mut bytes = Vec::new();
+                hash.binprot_write(&mut bytes)?;
+                Ok(Value::String(hex::encode(bytes)))
+            }
+            Self::StagedLedgerHashAuxHash
+            | Self::PendingCoinbaseAux
+            | Self::Memo
+            | Self::VrfOutput
+            | Self::PendingCoinbaseHashBuilder
+            | Self::VerificationKey => {
+                let bytes = b58::decode(b58, self.version())?;
+                Ok(Value::String(hex::encode(&bytes[1..])))
+            }
+        }
+    }
+
+    /// Encodes JSON of the underlying value, as produced by [`Kind::decode`],
+    /// into the base58check string.
+    pub fn encode(self, value: Value) -> anyhow::Result<String> {
+        match self {
+            Self::LedgerHash => encode_as::<LedgerHash>(value),
+            Self::StateHash => encode_as::<StateHash>(value),
+            Self::StateBodyHash => encode_as::<StateBodyHash>(value),
+            Self::PendingCoinbaseHash => encode_as::<PendingCoinbaseHash>(value),
+            Self::ReceiptChainHash => encode_as::<ReceiptChainHash>(value),
+            Self::EpochSeed => encode_as::<EpochSeed>(value),
+            Self::CoinbaseStackData => encode_as::<CoinbaseStackData>(value),
+            Self::CoinbaseStackHash => encode_as::<CoinbaseStackHash>(value),
+            Self::TokenId => encode_as::<TokenIdKeyHash>(value),
+            Self::PublicKey => encode_as::<NonZeroCurvePoint>(value),
+            Self::PrivateKey => encode_as::<PrivateKey>(value),
+            Self::Signature => encode_as::<Signature>(value),
+            Self::TransactionHash => {
+                let bytes: [u8; 32] = hex_payload(&value)?
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("transaction hash must be 32 bytes"))?;
+                Ok(TransactionHash::from(&bytes).to_string())
+            }
+            Self::StagedLedgerHashAuxHash
+            | Self::PendingCoinbaseAux
+            | Self::Memo
+            | Self::VrfOutput
+            | Self::PendingCoinbaseHashBuilder
+            | Self::VerificationKey => Ok(b58::encode(&hex_payload(&value)?, self.version())),
+        }
+    }
+}
+
+impl FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names = Self::ALL.iter().map(|kind| kind.name()).collect::<Vec<_>>();
+                anyhow::anyhow!("unknown kind `{s}`, expected one of: {}", names.join(", "))
+            })
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+fn decode_as<B>(b58: &str) -> anyhow::Result<Value>
+where
+    B: FromStr + Deref,
+    B::Err: std::error::Error + Send + Sync + 'static,
+    B::Target: Serialize,
+{
+    let value = b58.parse::<B>()?;
+    Ok(serde_json::to_value(&*value)?)
+}
+
+fn encode_as<B>(value: Value) -> anyhow::Result<String>
+where
+    B: Deref + From<<B as Deref>::Target> + fmt::Display,
+    B::Target: DeserializeOwned + Sized,
+{
+    let value = serde_json::from_value::<B::Target>(value)?;
+    Ok(B::from(value).to_string())
+}
+
+fn hex_payload(value: &Value) -> anyhow::Result<Vec<u8>> {
+    let s = value
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("expected hex string"))?;
+    Ok(hex::decode(s.strip_prefix("0x").unwrap_or(s))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_roundtrip() {
+        let b58 = "jwrPvAMUNo3EKT2puUk5Fxz6B7apRAoKNTGpAA49t3TRSfzvdrL";
+        assert_eq!(Kind::detect(b58).unwrap(), vec![Kind::LedgerHash]);
+
+        let value = Kind::LedgerHash.decode(b58).unwrap();
+        assert_eq!(
+            value,
+            Value::String(
+                "0x014825d6d524b1158655d8bfbbf08c1f99b43fc2c74343bc178e27672d5b6f63".to_owned()
+            )
+        );
+        assert_eq!(Kind::LedgerHash.encode(value).unwrap(), b58);
+    }
+
+    #[test]
+    fn test_kind_names() {
+        for kind in Kind::ALL {
+            assert_eq!(kind.name().parse::<Kind>().unwrap(), *kind);
+        }
+    }
+}
@@ -1,42 +1,171 @@
+use std::{fs, path::PathBuf};
+
+use mina_p2p_messages::bigint::BigInt;
+use serde_json::Value;
 use structopt::StructOpt;
 
+mod hasher;
+mod kind;
+mod payload;
+use kind::Kind;
+use payload::PayloadType;
+
 #[derive(StructOpt)]
-enum Arg {
+enum Command {
+    /// Converts a ledger hash between base58check and hex.
     Ledger {
         #[structopt(long)]
         hash: String,
     },
+    /// Converts a state hash between base58check and hex.
     State {
         #[structopt(long)]
         hash: String,
     },
+    /// Converts a pending coinbase hash between base58check and hex.
     PendingCoinbase {
         #[structopt(long)]
         hash: String,
     },
+    /// Lists the supported base58check kinds and their version bytes.
+    Kinds,
+    /// Decodes a base58check string into JSON.
+    Decode {
+        value: String,
+        /// Kind of the value, detected from the version byte if not set.
+        #[structopt(long)]
+        kind: Option<Kind>,
+    },
+    /// Encodes a value into a base58check string.
+    Encode {
+        #[structopt(long)]
+        kind: Kind,
+        /// JSON as printed by `decode`, a field element or a hex payload.
+        value: String,
+    },
+    /// Decodes a binprot payload into JSON.
+    Binprot {
+        /// One of: block, header, transaction, user-command,
+        /// signed-command, zkapp-command.
+        #[structopt(long = "type")]
+        ty: PayloadType,
+        /// Payload as hex or base64.
+        payload: Option<String>,
+        /// File with the raw payload.
+        #[structopt(long, conflicts_with = "payload")]
+        file: Option<PathBuf>,
+        /// Only print the state hash of a block or the transaction hash.
+        #[structopt(long)]
+        hash: bool,
+    },
+    /// Computes Poseidon hash of field elements.
+    Poseidon {
+        /// Field elements as decimals or `0x` prefixed hex.
+        fields: Vec<String>,
+        /// Domain string, e.g. `MinaAccount`.
+        #[structopt(long)]
+        domain: Option<String>,
+        /// Use legacy params instead of kimchi ones.
+        #[structopt(long)]
+        legacy: bool,
+    },
 }
 
-fn main() {
-    let arg = Arg::from_args();
-    let (version, hash) = match arg {
-        Arg::Ledger { hash } => (5, hash),
-        Arg::State { hash } => (16, hash),
-        Arg::PendingCoinbase { hash } => (12, hash),
-    };
-    let x = if let Ok(mut bytes) = hex::decode(format!("{hash}01")) {
-        bytes.reverse();
-        bs58::encode(bytes)
-            .with_check_version(version)
-            .into_string()
+fn main() -> anyhow::Result<()> {
+    match Command::from_args() {
+        Command::Ledger { hash } => convert(Kind::LedgerHash, hash),
+        Command::State { hash } => convert(Kind::StateHash, hash),
+        Command::PendingCoinbase { hash } => convert(Kind::PendingCoinbaseHash, hash),
+        Command::Kinds => {
+            for kind in Kind::ALL {
+                println!("{kind} 0x{:02x}", kind.version());
+            }
+            Ok(())
+        }
+        Command::Decode { value, kind } => decode(value, kind),
+        Command::Encode { kind, value } => {
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            println!("{}", kind.encode(value)?);
+            Ok(())
+        }
+        Command::Binprot {
+            ty,
+            payload,
+            file,
+            hash,
+        } => binprot(ty, payload, file, hash),
+        Command::Poseidon {
+            fields,
+            domain,
+            legacy,
+        } => poseidon(fields, domain, legacy),
+    }
+}
+
+/// Converts a field-like hash from hex to base58check, or the other way.
+fn convert(kind: Kind, hash: String) -> anyhow::Result<()> {
+    if let Ok(value) = kind.decode(&hash) {
+        let hex = value.as_str().unwrap_or_default();
+        println!("{}", hex.strip_prefix("0x").unwrap_or(hex));
     } else {
-        let mut bytes = bs58::decode(hash)
-            .with_check(Some(version))
-            .into_vec()
-            .unwrap();
-        bytes.reverse();
-        hex::encode(&bytes[..(bytes.len() - 2)])
+        let hex = hash.strip_prefix("0x").unwrap_or(&hash);
+        println!("{}", kind.encode(Value::String(format!("0x{hex:0>64}")))?);
+    }
+    Ok(())
+}
+
+fn decode(value: String, kind: Option<Kind>) -> anyhow::Result<()> {
+    let kinds = match kind {
+        Some(kind) => vec![kind],
+        None => Kind::detect(&value)?,
     };
-    println!("{x}");
+    if kinds.is_empty() {
+        anyhow::bail!("unknown version byte");
+    }
+    for kind in kinds {
+        match kind.decode(&value) {
+            Ok(decoded) => {
+                println!("{kind}: {}", serde_json::to_string_pretty(&decoded)?);
+            }
+            Err(err) => eprintln!("{kind}: {err}"),
+        }
+    }
+    Ok(())
+}
+
+fn binprot(
+    ty: PayloadType,
+    payload: Option<String>,
+    file: Option<PathBuf>,
+    hash: bool,
+) -> anyhow::Result<()> {
+    let bytes = match (payload, file) {
+        (Some(payload), _) => payload::parse_bytes(&payload)?,
+        (None, Some(file)) => fs::read(file)?,
+        (None, None) => anyhow::bail!("must provide either a payload or `--file`"),
+    };
+    let decoded = ty.decode(&bytes)?;
+    if hash {
+        let hash = decoded
+            .hash
+            .ok_or_else(|| anyhow::anyhow!("failed to compute the hash"))?;
+        println!("{hash}");
+    } else {
+        println!("{}", serde_json::to_string_pretty(&decoded.value)?);
+        if let Some(hash) = decoded.hash {
+            eprintln!("hash: {hash}");
+        }
+    }
+    Ok(())
+}
+
+fn poseidon(fields: Vec<String>, domain: Option<String>, legacy: bool) -> anyhow::Result<()> {
+    let fields = fields
+        .iter()
+        .map(|field| hasher::parse_field(field))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let hash = hasher::hash(&fields, domain.as_deref(), legacy)?;
+    println!("{}", BigInt::from(hash).to_decimal());
+    println!("{}", hasher::to_hex(hash));
+    Ok(())
 }
-// jwrPvAMUNo3EKT2puUk5Fxz6B7apRAoKNTGpAA49t3TRSfzvdrL
-// 636f5b2d67278e17bc4343c7c23fb4991f8cf0bbbfd8558615b124d5d62548
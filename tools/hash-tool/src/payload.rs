@@ -0,0 +1,132 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose, Engine as _};
+use mina_p2p_messages::{
+    binprot::BinProtRead,
+    v2::{
+        MinaBaseSignedCommandStableV2, MinaBaseUserCommandStableV2,
+        MinaBaseZkappCommandTStableV1WireStableV1, MinaBlockBlockStableV2, MinaBlockHeaderStableV2,
+        MinaTransactionTransactionStableV2,
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Type of a binprot encoded payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadType {
+    Block,
+    Header,
+    Transaction,
+    UserCommand,
+    SignedCommand,
+    ZkappCommand,
+}
+
+impl FromStr for PayloadType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "block" => Ok(Self::Block),
+            "header" => Ok(Self::Header),
+            "transaction" => Ok(Self::Transaction),
+            "user-command" => Ok(Self::UserCommand),
+            "signed-command" => Ok(Self::SignedCommand),
+            "zkapp-command" => Ok(Self::ZkappCommand),
+            _ => anyhow::bail!(
+                "unknown type `{s}`, expected one of: block, header, transaction, \
+                 user-command, signed-command, zkapp-command"
+            ),
+        }
+    }
+}
+
+pub struct DecodedPayload {
+    pub value: Value,
+    /// State hash of a block or a header, transaction hash of a command.
+    pub hash: Option<String>,
+}
+
+impl PayloadType {
+    pub fn decode(self, bytes: &[u8]) -> anyhow::Result<DecodedPayload> {
+        match self {
+            Self::Block => decode_as(bytes, |block: &MinaBlockBlockStableV2| {
+                block
+                    .try_hash()
+                    .map(|hash| hash.to_string())
+                    .map_err(|_| anyhow::anyhow!("invalid field element"))
+            }),
+            Self::Header => decode_as(bytes, |header: &MinaBlockHeaderStableV2| {
+                header
+                    .try_hash()
+                    .map(|hash| hash.to_string())
+                    .map_err(|_| anyhow::anyhow!("invalid field element"))
+            }),
+            Self::Transaction => decode_as(bytes, |tx: &MinaTransactionTransactionStableV2| {
+                Ok(tx.hash()?.to_string())
+            }),
+            Self::UserCommand => decode_as(bytes, |cmd: &MinaBaseUserCommandStableV2| {
+                Ok(cmd.hash()?.to_string())
+            }),
+            Self::SignedCommand => decode_as(bytes, |cmd: &MinaBaseSignedCommandStableV2| {
+                Ok(cmd.hash()?.to_string())
+            }),
+            Self::ZkappCommand => {
+                decode_as(bytes, |cmd: &MinaBaseZkappCommandTStableV1WireStableV1| {
+                    Ok(cmd.hash()?.to_string())
+                })
+            }
+        }
+    }
+}
+
+fn decode_as<T>(
+    bytes: &[u8],
+    hash: impl FnOnce(&T) -> anyhow::Result<String>,
+) -> anyhow::Result<DecodedPayload>
+where
+    T: BinProtRead + Serialize,
+{
+    let mut r = bytes;
+    let value = T::binprot_read(&mut r)?;
+    if !r.is_empty() {
+        eprintln!(
+            "warning: {} trailing byte(s) after the decoded value",
+            r.len()
+        );
+    }
+    let hash = hash(&value)
+        .map_err(|err| eprintln!("warning: failed to compute the hash: {err}"))
+        .ok();
+    Ok(DecodedPayload {
+        value: serde_json::to_value(&value)?,
+        hash,
+    })
+}
+
+/// Parses the payload given as hex (with an optional `0x` prefix) or base64.
+pub fn parse_bytes(s: &str) -> anyhow::Result<Vec<u8>> {
+    let s = s.trim();
+    if let Ok(bytes) = hex::decode(s.strip_prefix("0x").unwrap_or(s)) {
+        return Ok(bytes);
+    }
+    general_purpose::STANDARD
+        .decode(s)
+        .or_else(|_| general_purpose::URL_SAFE.decode(s))
+        .map_err(|_| anyhow::anyhow!("payload is neither hex nor base64"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(parse_bytes("0x0102ff").unwrap(), vec![1, 2, 255]);
+        assert_eq!(parse_bytes("0102ff").unwrap(), vec![1, 2, 255]);
+        assert_eq!(parse_bytes("AQL/").unwrap(), vec![1, 2, 255]);
+        assert_eq!(parse_bytes("AQL_").unwrap(), vec![1, 2, 255]);
+        assert!(parse_bytes("not a payload!").is_err());
+    }
+}
@@ -1,25 +1,98 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     fs::{self, File},
+    io::Write,
     path::PathBuf,
 };
 
+use ledger::{Account, AccountId, TokenId};
+use mina_curves::pasta::Fp;
+use node::{
+    daemon_json::{self, to_mina_string},
+    transition_frontier::genesis::PrebuiltGenesisConfig,
+};
 use reqwest::Url;
-
+use serde_json::Value;
 use structopt::StructOpt;
 
+mod source;
+use source::{LedgerSource, LoadedLedger};
+
 #[derive(StructOpt)]
 struct Args {
+    /// Same as `convert --input`, used when no subcommand is given.
     #[structopt(short, long)]
     input: Option<PathBuf>,
+    /// Same as `convert --url`, used when no subcommand is given.
     #[structopt(long)]
     url: Option<Url>,
+    /// Same as `convert --output`, used when no subcommand is given.
     #[structopt(short, long)]
-    output: PathBuf,
+    output: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(StructOpt)]
+enum Command {
+    /// Converts a daemon.json into a prebuilt genesis config.
+    Convert {
+        #[structopt(short, long)]
+        input: Option<PathBuf>,
+        #[structopt(long)]
+        url: Option<Url>,
+        #[structopt(short, long)]
+        output: PathBuf,
+    },
+    /// Prints the merkle root and the number of accounts of a ledger.
+    Hash { ledger: LedgerSource },
+    /// Prints an account of a ledger in the Mina JSON ledger format.
+    Account {
+        ledger: LedgerSource,
+        /// Public key of the account.
+        #[structopt(long)]
+        pk: String,
+        /// Token id of the account, the default token if not set.
+        #[structopt(long)]
+        token: Option<String>,
+    },
+    /// Prints stake delegated to each block producer, largest first.
+    Delegations {
+        ledger: LedgerSource,
+        /// Only print this many producers.
+        #[structopt(long)]
+        top: Option<usize>,
+    },
+    /// Compares two ledgers account by account.
+    Diff { a: LedgerSource, b: LedgerSource },
+    /// Exports a ledger to the Mina JSON ledger format.
+    Export {
+        ledger: LedgerSource,
+        /// Printed to stdout if not set.
+        #[structopt(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 fn main() -> anyhow::Result<()> {
-    let Args { input, url, output } = Args::from_args();
+    let args = Args::from_args();
+    let Some(command) = args.command else {
+        let output = args
+            .output
+            .ok_or_else(|| anyhow::anyhow!("must provide a subcommand or `--output`"))?;
+        return convert(args.input, args.url, output);
+    };
+    match command {
+        Command::Convert { input, url, output } => convert(input, url, output),
+        Command::Hash { ledger } => hash(ledger),
+        Command::Account { ledger, pk, token } => account(ledger, pk, token),
+        Command::Delegations { ledger, top } => delegations(ledger, top),
+        Command::Diff { a, b } => diff(a, b),
+        Command::Export { ledger, output } => export(ledger, output),
+    }
+}
 
+fn convert(input: Option<PathBuf>, url: Option<Url>, output: PathBuf) -> anyhow::Result<()> {
     let data = if let Some(input) = input {
         fs::read(input)?
     } else if let Some(url) = url {
@@ -28,12 +101,366 @@ fn main() -> anyhow::Result<()> {
         anyhow::bail!("must provide either `--input` or `--url`");
     };
 
-    let daemon_json = serde_json::from_slice::<node::daemon_json::DaemonJson>(&data)?;
+    convert_daemon_json(&data)?.store(File::create(output)?)?;
+
+    Ok(())
+}
+
+/// Builds the prebuilt genesis config, checking the epoch ledger hashes.
+fn convert_daemon_json(data: &[u8]) -> anyhow::Result<PrebuiltGenesisConfig> {
+    let daemon_json = serde_json::from_slice::<daemon_json::DaemonJson>(data)?;
+    Ok(PrebuiltGenesisConfig::try_from(daemon_json)?)
+}
+
+fn hash(source: LedgerSource) -> anyhow::Result<()> {
+    let mut ledger = source::load(&source)?;
+    println!("hash: {}", ledger.hash());
+    println!("accounts: {}", ledger.accounts.len());
+    if let Some(matches) = ledger.hash_matches() {
+        let status = if matches { "matches" } else { "MISMATCH" };
+        println!(
+            "expected hash: {} ({status})",
+            ledger.expected_hash.as_deref().unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+fn account(source: LedgerSource, pk: String, token: Option<String>) -> anyhow::Result<()> {
+    let public_key = ledger::compressed_pubkey_from_address_maybe_with_error(&pk)
+        .map_err(|_| anyhow::anyhow!("invalid public key: {pk}"))?;
+    let token_id = match token {
+        None => TokenId::default(),
+        Some(token) => TokenId(
+            token
+                .parse::<Fp>()
+                .map_err(|_| anyhow::anyhow!("invalid token id: {token}"))?,
+        ),
+    };
+    let account_id = AccountId::new(public_key, token_id);
+
+    let ledger = source::load(&source)?;
+    let (index, account) = ledger
+        .accounts
+        .iter()
+        .enumerate()
+        .find(|(_, account)| account.id() == account_id)
+        .ok_or_else(|| anyhow::anyhow!("account not found in the ledger"))?;
+
+    println!("index: {index}");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&daemon_json::Account::from(account))?
+    );
+    Ok(())
+}
+
+fn delegations(source: LedgerSource, top: Option<usize>) -> anyhow::Result<()> {
+    let ledger = source::load(&source)?;
+    let StakeSummary {
+        total_stake,
+        stakes,
+    } = stake_summary(&ledger.accounts);
+
+    println!("producers: {}", stakes.len());
+    println!("total stake: {}", to_mina_string(total_stake));
+    for (producer, (stake, delegators)) in stakes.iter().take(top.unwrap_or(usize::MAX)) {
+        let share = *stake as f64 / total_stake.max(1) as f64 * 100.0;
+        println!(
+            "{producer} {} ({share:.4}%) delegators: {delegators}",
+            to_mina_string(*stake)
+        );
+    }
+    Ok(())
+}
+
+struct StakeSummary {
+    /// Stake of the default token delegated to any producer.
+    total_stake: u64,
+    /// Stake and the number of delegators of each producer, largest first.
+    stakes: Vec<(String, (u64, usize))>,
+}
+
+fn stake_summary(accounts: &[Account]) -> StakeSummary {
+    let mut total_stake = 0u64;
+    let mut stakes = BTreeMap::<String, (u64, usize)>::new();
+    for account in accounts {
+        let Some(delegate) = account.delegate.as_ref() else {
+            continue;
+        };
+        if !account.token_id.is_default() {
+            continue;
+        }
+        let balance = account.balance.as_u64();
+        total_stake = total_stake.saturating_add(balance);
+        let (stake, delegators) = stakes.entry(delegate.into_address()).or_default();
+        *stake = stake.saturating_add(balance);
+        *delegators += 1;
+    }
 
-    let prebuilt_config =
-        node::transition_frontier::genesis::PrebuiltGenesisConfig::try_from(daemon_json)?;
+    let mut stakes = stakes.into_iter().collect::<Vec<_>>();
+    stakes.sort_by(|(_, (a, _)), (_, (b, _))| b.cmp(a));
 
-    prebuilt_config.store(File::create(output)?)?;
+    StakeSummary {
+        total_stake,
+        stakes,
+    }
+}
 
+fn diff(a: LedgerSource, b: LedgerSource) -> anyhow::Result<()> {
+    let mut a = source::load(&a)?;
+    let mut b = source::load(&b)?;
+    println!("a: {} ({} accounts)", a.hash(), a.accounts.len());
+    println!("b: {} ({} accounts)", b.hash(), b.accounts.len());
+
+    let index = |ledger: &LoadedLedger| {
+        ledger
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(i, account)| (account.id(), i))
+            .collect::<HashMap<_, _>>()
+    };
+    let (a_index, b_index) = (index(&a), index(&b));
+
+    let (mut removed, mut added, mut changed, mut moved) = (0, 0, 0, 0);
+    for (a_i, account) in a.accounts.iter().enumerate() {
+        let Some(&b_i) = b_index.get(&account.id()) else {
+            println!("- {}", account_name(account));
+            removed += 1;
+            continue;
+        };
+        let changes = account_changes(account, &b.accounts[b_i]);
+        if !changes.is_empty() {
+            println!("~ {}", account_name(account));
+            for (field, before, after) in changes {
+                println!("    {field}: {before} -> {after}");
+            }
+            changed += 1;
+        }
+        if a_i != b_i {
+            println!(
+                "  {} moved from index {a_i} to {b_i}",
+                account_name(account)
+            );
+            moved += 1;
+        }
+    }
+    for account in &b.accounts {
+        if !a_index.contains_key(&account.id()) {
+            println!("+ {}", account_name(account));
+            added += 1;
+        }
+    }
+
+    println!("removed: {removed}, added: {added}, changed: {changed}, moved: {moved}");
     Ok(())
 }
+
+fn export(source: LedgerSource, output: Option<PathBuf>) -> anyhow::Result<()> {
+    let mut ledger = source::load(&source)?;
+
+    let with_vk = ledger
+        .accounts
+        .iter()
+        .filter(|account| {
+            account
+                .zkapp
+                .as_ref()
+                .is_some_and(|zkapp| zkapp.verification_key.is_some())
+        })
+        .count();
+    if with_vk > 0 {
+        eprintln!("warning: verification keys of {with_vk} zkApp account(s) aren't exported");
+    }
+
+    let json_ledger = daemon_json::Ledger {
+        accounts: Some(ledger.accounts.iter().map(Into::into).collect()),
+        num_accounts: Some(ledger.accounts.len()),
+        balances: None,
+        hash: Some(ledger.hash().to_string()),
+        s3_data_hash: None,
+        name: None,
+        add_genesis_winner: Some(false),
+    };
+
+    match output {
+        Some(output) => serde_json::to_writer_pretty(File::create(output)?, &json_ledger)?,
+        None => {
+            let mut stdout = std::io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, &json_ledger)?;
+            writeln!(stdout)?;
+        }
+    }
+    Ok(())
+}
+
+fn account_name(account: &Account) -> String {
+    let pk = account.public_key.into_address();
+    if account.token_id.is_default() {
+        pk
+    } else {
+        format!(
+            "{pk} (token {})",
+            ledger::FpExt::to_decimal(&account.token_id.0)
+        )
+    }
+}
+
+/// Fields of the account, in the Mina JSON ledger format, which differ.
+fn account_changes(a: &Account, b: &Account) -> Vec<(String, Value, Value)> {
+    let to_value = |account: &Account| {
+        serde_json::to_value(daemon_json::Account::from(account)).unwrap_or_default()
+    };
+    let (Value::Object(a), Value::Object(b)) = (to_value(a), to_value(b)) else {
+        return vec![];
+    };
+    a.into_iter()
+        .filter_map(|(field, before)| {
+            let after = b.get(&field).cloned().unwrap_or_default();
+            (before != after).then_some((field, before, after))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use ledger::scan_state::currency::Balance;
+    use node::account::AccountSecretKey;
+    use serde_json::json;
+
+    use super::*;
+
+    const SEED: &str = "2va9BGv9JrLTtrzZttiEMDYw1Zj6a6EHzXjmP9evHDTG3oEquURA";
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "openmina-ledger-tool-test-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn test_accounts() -> Value {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../../tests/files/vrf_genesis_epoch/daemon.json"
+        );
+        let mut daemon_json: Value = serde_json::from_slice(&fs::read(path).unwrap()).unwrap();
+        daemon_json["ledger"]["accounts"].take()
+    }
+
+    fn load(path: &std::path::Path, kind: &str) -> LoadedLedger {
+        source::load(&format!("{}{kind}", path.display()).parse().unwrap()).unwrap()
+    }
+
+    fn write_json(path: &std::path::Path, value: &Value) {
+        fs::write(path, serde_json::to_vec(value).unwrap()).unwrap();
+    }
+
+    /// Hash of a JSON ledger with `accounts` and without the genesis winner.
+    fn accounts_hash(dir: &std::path::Path, accounts: &Value) -> String {
+        let path = dir.join("accounts.json");
+        write_json(&path, accounts);
+        load(&path, "").hash().to_string()
+    }
+
+    fn without_last(accounts: &Value) -> Value {
+        let mut accounts = accounts.clone();
+        accounts.as_array_mut().unwrap().pop();
+        accounts
+    }
+
+    #[test]
+    fn test_convert() {
+        let dir = test_dir("convert");
+        let accounts = test_accounts();
+        let epoch_hash = accounts_hash(&dir, &accounts);
+
+        let daemon_json = |staking_hash: &str| {
+            let epoch = json!({ "accounts": accounts, "hash": staking_hash, "seed": SEED });
+            json!({
+                "ledger": { "accounts": accounts, "add_genesis_winner": false },
+                "epoch_data": { "staking": epoch, "next": epoch },
+            })
+        };
+        let daemon_json_path = dir.join("daemon.json");
+        write_json(&daemon_json_path, &daemon_json(&epoch_hash));
+
+        let output = dir.join("ledger.bin");
+        convert(Some(daemon_json_path.clone()), None, output.clone()).unwrap();
+
+        let mut genesis = load(&output, "");
+        let mut expected = load(&daemon_json_path, "");
+        assert_eq!(genesis.hash(), expected.hash());
+        assert_eq!(genesis.accounts, expected.accounts);
+        for kind in [":staking", ":next"] {
+            let mut epoch = load(&output, kind);
+            assert_eq!(epoch.hash().to_string(), epoch_hash);
+            assert_eq!(epoch.hash_matches(), Some(true));
+        }
+
+        // Epoch ledger hashes are verified on conversion
+        let other_hash = accounts_hash(&dir, &without_last(&accounts));
+        let invalid = serde_json::to_vec(&daemon_json(&other_hash)).unwrap();
+        assert!(convert_daemon_json(&invalid).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hash_matches() {
+        let dir = test_dir("hash");
+        let accounts = test_accounts();
+        let path = dir.join("ledger.json");
+
+        write_json(&path, &json!({ "accounts": accounts }));
+        let mut ledger = load(&path, "");
+        assert_eq!(ledger.hash_matches(), None);
+        let hash = ledger.hash().to_string();
+
+        write_json(&path, &json!({ "accounts": accounts, "hash": hash }));
+        assert_eq!(load(&path, "").hash_matches(), Some(true));
+
+        let other_hash = accounts_hash(&dir, &without_last(&accounts));
+        write_json(&path, &json!({ "accounts": accounts, "hash": other_hash }));
+        assert_eq!(load(&path, "").hash_matches(), Some(false));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stake_summary() {
+        let producer1 = AccountSecretKey::rand().public_key_compressed();
+        let producer2 = AccountSecretKey::rand().public_key_compressed();
+        let account = |balance: u64, delegate: Option<&mina_signer::CompressedPubKey>| {
+            let public_key = AccountSecretKey::rand().public_key_compressed();
+            let mut account = Account::create_with(
+                AccountId::new(public_key, TokenId::default()),
+                Balance::from_u64(balance),
+            );
+            account.delegate = delegate.cloned();
+            account
+        };
+        let mut token_account = account(1_000, Some(&producer2));
+        token_account.token_id = TokenId(Fp::from(2u64));
+
+        let summary = stake_summary(&[
+            account(10, Some(&producer1)),
+            account(3, Some(&producer2)),
+            account(5, Some(&producer1)),
+            account(100, None),
+            token_account,
+        ]);
+        assert_eq!(summary.total_stake, 18);
+        assert_eq!(
+            summary.stakes,
+            vec![
+                (producer1.into_address(), (15, 2)),
+                (producer2.into_address(), (3, 1)),
+            ]
+        );
+    }
+}
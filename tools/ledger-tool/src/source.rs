@@ -0,0 +1,216 @@
+use std::{
+    fs::{self, File},
+    io::BufReader,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use anyhow::Context;
+use ledger::{Account, BaseLedger, Mask};
+use mina_p2p_messages::v2::{LedgerHash, MinaBaseAccountBinableArgStableV2};
+use node::{
+    daemon_json::{self, DaemonJson},
+    ledger::LEDGER_DEPTH,
+    transition_frontier::genesis::PrebuiltGenesisConfig,
+};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKind {
+    Genesis,
+    StakingEpoch,
+    NextEpoch,
+}
+
+impl FromStr for LedgerKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "genesis" => Ok(Self::Genesis),
+            "staking" => Ok(Self::StakingEpoch),
+            "next" => Ok(Self::NextEpoch),
+            _ => anyhow::bail!("unknown ledger `{s}`, expected: genesis, staking or next"),
+        }
+    }
+}
+
+/// Ledger to load, in the form `<path>[:genesis|staking|next]`.
+///
+/// The path is either a daemon.json, a JSON ledger, a prebuilt genesis
+/// config or a directory with a ledger database of a node. The suffix
+/// selects an epoch ledger of a daemon.json or a prebuilt config.
+#[derive(Debug, Clone)]
+pub struct LedgerSource {
+    pub path: PathBuf,
+    pub kind: LedgerKind,
+}
+
+impl FromStr for LedgerSource {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, kind) = match s.rsplit_once(':') {
+            Some((path, kind)) if !path.is_empty() => match kind.parse() {
+                Ok(kind) => (path, kind),
+                // not a ledger selector, but a part of the path
+                Err(_) => (s, LedgerKind::Genesis),
+            },
+            _ => (s, LedgerKind::Genesis),
+        };
+        Ok(Self {
+            path: path.into(),
+            kind,
+        })
+    }
+}
+
+pub struct LoadedLedger {
+    pub accounts: Vec<Account>,
+    pub mask: Mask,
+    /// Hash of the ledger according to the source, if it has one.
+    pub expected_hash: Option<String>,
+}
+
+impl LoadedLedger {
+    pub fn hash(&mut self) -> LedgerHash {
+        LedgerHash::from_fp(self.mask.merkle_root())
+    }
+
+    /// Whether the hash matches the one from the source, if it has one.
+    pub fn hash_matches(&mut self) -> Option<bool> {
+        let hash = self.hash().to_string();
+        self.expected_hash
+            .as_ref()
+            .map(|expected| *expected == hash)
+    }
+}
+
+pub fn load(source: &LedgerSource) -> anyhow::Result<LoadedLedger> {
+    let path = &source.path;
+    if path.is_dir() {
+        if source.kind != LedgerKind::Genesis {
+            anyhow::bail!("ledger database in {path:?} contains a single ledger");
+        }
+        return load_database(path);
+    }
+
+    let data = fs::read(path).with_context(|| format!("failed to read {path:?}"))?;
+    match serde_json::from_slice::<Value>(&data) {
+        Ok(json) => load_json(json, source.kind),
+        Err(_) => {
+            let config =
+                PrebuiltGenesisConfig::read(BufReader::new(File::open(path)?)).map_err(|err| {
+                    anyhow::anyhow!("{path:?} is neither json nor a prebuilt genesis config: {err}")
+                })?;
+            load_prebuilt(&config, source.kind)
+        }
+    }
+}
+
+fn load_database(path: &Path) -> anyhow::Result<LoadedLedger> {
+    if fs::read_dir(path)?.next().is_none() {
+        anyhow::bail!("no ledger database found in {path:?}");
+    }
+    let db = ledger::Database::open_ondisk(LEDGER_DEPTH as u8, path.to_path_buf())
+        .with_context(|| format!("failed to open ledger database in {path:?}"))?;
+    let mask = Mask::new_root(db);
+    Ok(LoadedLedger {
+        accounts: mask.to_list(),
+        mask,
+        expected_hash: None,
+    })
+}
+
+fn load_json(json: Value, kind: LedgerKind) -> anyhow::Result<LoadedLedger> {
+    let is_daemon_json = json
+        .as_object()
+        .is_some_and(|obj| obj.contains_key("ledger") || obj.contains_key("epoch_data"));
+
+    let (accounts, expected_hash) = if is_daemon_json {
+        let daemon_json = serde_json::from_value::<DaemonJson>(json)?;
+        match kind {
+            LedgerKind::Genesis => {
+                let ledger = daemon_json.ledger.context("daemon.json has no ledger")?;
+                (ledger.accounts_with_genesis_winner(), ledger.hash)
+            }
+            LedgerKind::StakingEpoch | LedgerKind::NextEpoch => {
+                let epochs = daemon_json
+                    .epoch_data
+                    .context("daemon.json has no epoch data")?;
+                let epoch = match kind {
+                    LedgerKind::StakingEpoch => Some(epochs.staking),
+                    _ => epochs.next,
+                };
+                let epoch = epoch.context("daemon.json has no next epoch data")?;
+                (epoch.accounts.unwrap_or_default(), epoch.hash)
+            }
+        }
+    } else {
+        if kind != LedgerKind::Genesis {
+            anyhow::bail!("JSON ledger contains a single ledger");
+        }
+        if json.is_array() {
+            (
+                serde_json::from_value::<Vec<daemon_json::Account>>(json)?,
+                None,
+            )
+        } else {
+            let ledger = serde_json::from_value::<daemon_json::Ledger>(json)?;
+            (ledger.accounts_with_genesis_winner(), ledger.hash)
+        }
+    };
+
+    let accounts = accounts
+        .iter()
+        .map(daemon_json::Account::to_account)
+        .collect::<Result<Vec<_>, _>>()?;
+    build(accounts, expected_hash)
+}
+
+fn load_prebuilt(config: &PrebuiltGenesisConfig, kind: LedgerKind) -> anyhow::Result<LoadedLedger> {
+    let (hash, accounts) = match kind {
+        LedgerKind::Genesis => config.genesis_ledger(),
+        LedgerKind::StakingEpoch => config.staking_epoch_ledger(),
+        LedgerKind::NextEpoch => config.next_epoch_ledger(),
+    };
+    let accounts = accounts
+        .iter()
+        .map(|account: &MinaBaseAccountBinableArgStableV2| {
+            Account::try_from(account).map_err(|_| anyhow::anyhow!("invalid account"))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    build(accounts, Some(hash.to_string()))
+}
+
+fn build(accounts: Vec<Account>, expected_hash: Option<String>) -> anyhow::Result<LoadedLedger> {
+    let mut mask = Mask::new_root(ledger::Database::create(LEDGER_DEPTH as u8));
+    for account in &accounts {
+        mask.get_or_create_account(account.id(), account.clone())
+            .map_err(|err| anyhow::anyhow!("failed to add account: {err:?}"))?;
+    }
+    Ok(LoadedLedger {
+        accounts,
+        mask,
+        expected_hash,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ledger_source() {
+        let source = "devnet.bin:staking".parse::<LedgerSource>().unwrap();
+        assert_eq!(source.path, PathBuf::from("devnet.bin"));
+        assert_eq!(source.kind, LedgerKind::StakingEpoch);
+
+        let source = "devnet.bin".parse::<LedgerSource>().unwrap();
+        assert_eq!(source.kind, LedgerKind::Genesis);
+
+        let source = "C:ledger.json".parse::<LedgerSource>().unwrap();
+        assert_eq!(source.path, PathBuf::from("C:ledger.json"));
+        assert_eq!(source.kind, LedgerKind::Genesis);
+    }
+}